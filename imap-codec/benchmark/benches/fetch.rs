@@ -0,0 +1,77 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use imap_codec::{decode::Decoder, ResponseCodec};
+
+fn criterion_benchmark(c: &mut Criterion) {
+    // # Setup
+    let codec = ResponseCodec::new();
+    let instances = [
+        ("flags", create_flags_sync(10_000)),
+        ("headers", create_headers_sync(1_000)),
+    ];
+
+    let mut group = c.benchmark_group("bench_fetch_parse");
+
+    for (instance, input) in instances {
+        group.throughput(Throughput::Bytes(input.len() as u64));
+
+        group.bench_function(instance, |b| b.iter(|| parse(&codec, black_box(&input))));
+    }
+
+    group.finish();
+}
+
+/// Create `count` FETCH responses as seen when (re-)synchronizing the flags of a large mailbox.
+fn create_flags_sync(count: usize) -> Vec<u8> {
+    let mut out = Vec::new();
+
+    for i in 1..=count {
+        let flags = match i % 4 {
+            0 => "",
+            1 => "\\Seen",
+            2 => "\\Seen \\Answered",
+            _ => "\\Seen \\Flagged $Forwarded",
+        };
+
+        out.extend_from_slice(format!("* {i} FETCH (UID {i} FLAGS ({flags}))\r\n").as_bytes());
+    }
+
+    out
+}
+
+/// Create `count` FETCH responses as seen when downloading the headers of a large mailbox.
+fn create_headers_sync(count: usize) -> Vec<u8> {
+    let mut out = Vec::new();
+
+    for i in 1..=count {
+        let header = format!(
+            "From: Terry Gray <gray@cac.washington.edu>\r\nSubject: Message {i}\r\nDate: Wed, 17 Jul 1996 02:23:25 -0700\r\n\r\n"
+        );
+
+        out.extend_from_slice(
+            format!(
+                "* {i} FETCH (UID {i} RFC822.SIZE 4286 INTERNALDATE \"17-Jul-1996 02:44:25 -0700\" FLAGS (\\Seen) BODYSTRUCTURE (\"TEXT\" \"PLAIN\" (\"CHARSET\" \"US-ASCII\") NIL NIL \"7BIT\" 3028 92) BODY[HEADER.FIELDS (FROM SUBJECT DATE)] {{{}}}\r\n{header})\r\n",
+                header.len()
+            )
+            .as_bytes(),
+        );
+    }
+
+    out
+}
+
+#[inline]
+fn parse(codec: &ResponseCodec, mut input: &[u8]) -> usize {
+    let mut count = 0;
+
+    while !input.is_empty() {
+        let (remaining, _) = codec.decode(input).unwrap();
+        input = remaining;
+        count += 1;
+    }
+
+    count
+}
+
+criterion_group!(benches, criterion_benchmark);
+
+criterion_main!(benches);
@@ -0,0 +1,78 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use imap_codec::{fragmentizer::Fragmentizer, ResponseCodec};
+
+fn criterion_benchmark(c: &mut Criterion) {
+    // # Setup
+    let codec = ResponseCodec::new();
+    let instances = [
+        ("lines", create_fetch(1000, 0)),
+        ("literals", create_fetch(100, 64 * 1024)),
+    ];
+
+    let mut group = c.benchmark_group("bench_fragmentizer_fetch");
+
+    for (instance, input) in instances {
+        group.throughput(Throughput::Bytes(input.len() as u64));
+
+        group.bench_function(format!("{instance}_fragment"), |b| {
+            b.iter(|| fragment(black_box(&input)))
+        });
+
+        group.bench_function(format!("{instance}_decode"), |b| {
+            b.iter(|| decode(&codec, black_box(&input)))
+        });
+    }
+
+    group.finish();
+}
+
+/// Create `count` FETCH responses with a long `ENVELOPE` line and a `BODY[]` literal of `size` bytes.
+fn create_fetch(count: usize, size: usize) -> Vec<u8> {
+    let mut out = Vec::new();
+
+    for i in 1..=count {
+        out.extend_from_slice(
+            format!(
+                "* {i} FETCH (UID {i} FLAGS (\\Seen) ENVELOPE (\"Wed, 17 Jul 1996 02:23:25 -0700 (PDT)\" \"IMAP4rev1 WG mtg summary and minutes\" ((\"Terry Gray\" NIL \"gray\" \"cac.washington.edu\")) ((\"Terry Gray\" NIL \"gray\" \"cac.washington.edu\")) ((\"Terry Gray\" NIL \"gray\" \"cac.washington.edu\")) ((NIL NIL \"imap\" \"cac.washington.edu\")) ((NIL NIL \"minutes\" \"CNRI.Reston.VA.US\")(\"John Klensin\" NIL \"KLENSIN\" \"MIT.EDU\")) NIL NIL \"<B27397-0100000@cac.washington.edu>\") BODY[] {{{size}}}\r\n"
+            )
+            .as_bytes(),
+        );
+        out.extend(std::iter::repeat(b'x').take(size));
+        out.extend_from_slice(b")\r\n");
+    }
+
+    out
+}
+
+#[inline]
+fn fragment(input: &[u8]) -> usize {
+    let mut fragmentizer = Fragmentizer::without_max_message_size();
+    fragmentizer.enqueue_bytes(input);
+
+    let mut count = 0;
+    while fragmentizer.progress().is_some() {
+        count += 1;
+    }
+
+    count
+}
+
+#[inline]
+fn decode(codec: &ResponseCodec, input: &[u8]) -> usize {
+    let mut fragmentizer = Fragmentizer::without_max_message_size();
+    fragmentizer.enqueue_bytes(input);
+
+    let mut count = 0;
+    while fragmentizer.progress().is_some() {
+        if fragmentizer.is_message_complete() {
+            assert!(fragmentizer.decode_message(codec).is_ok());
+            count += 1;
+        }
+    }
+
+    count
+}
+
+criterion_group!(benches, criterion_benchmark);
+
+criterion_main!(benches);
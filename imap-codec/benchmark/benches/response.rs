@@ -1,9 +1,7 @@
-use std::num::NonZeroU32;
-
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use imap_codec::{decode::Decoder, encode::Encoder, imap_types::response::Response, ResponseCodec};
 use imap_proto::Response as ImapProtoResponse;
-use imap_types::{core::Vec1, fetch::MessageDataItem, response::Data};
+use imap_types::{core::Vec1, fetch::MessageDataItem, response::Data, sequence::SeqNo};
 
 fn criterion_benchmark(c: &mut Criterion) {
     // # Setup
@@ -35,7 +33,7 @@ fn create_simple() -> Response<'static> {
 
 fn create_complex() -> Response<'static> {
     Response::Data(Data::Fetch {
-        seq: NonZeroU32::try_from(u32::MAX).unwrap(),
+        seq: SeqNo::new(u32::MAX).unwrap(),
         items: Vec1::try_from(vec![MessageDataItem::Rfc822Size(0)]).unwrap(),
     })
 }
@@ -0,0 +1,79 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use imap_codec::{decode::Decoder, ResponseCodec};
+
+fn criterion_benchmark(c: &mut Criterion) {
+    // # Setup
+    let codec = ResponseCodec::new();
+    let instances = [
+        ("text", create_text(1_000)),
+        ("quoted", create_quoted(1_000)),
+        ("literal", create_literal(100)),
+    ];
+
+    let mut group = c.benchmark_group("bench_strings_parse");
+
+    for (instance, input) in instances {
+        group.throughput(Throughput::Bytes(input.len() as u64));
+
+        group.bench_function(instance, |b| b.iter(|| parse(&codec, black_box(&input))));
+    }
+
+    group.finish();
+}
+
+/// Create `count` status responses with a long `text`.
+fn create_text(count: usize) -> Vec<u8> {
+    let text = "The quick brown fox jumps over the lazy dog. ".repeat(20);
+    let mut out = Vec::new();
+
+    for i in 1..=count {
+        out.extend_from_slice(format!("* OK [UIDNEXT {i}] {text}\r\n").as_bytes());
+    }
+
+    out
+}
+
+/// Create `count` FETCH responses with long (escaped) quoted strings.
+fn create_quoted(count: usize) -> Vec<u8> {
+    let subject = r#"Re: \"The quick brown fox\" jumps over the lazy dog. "#.repeat(20);
+    let mut out = Vec::new();
+
+    for i in 1..=count {
+        out.extend_from_slice(
+            format!("* {i} FETCH (ENVELOPE (\"Wed, 17 Jul 1996 02:23:25 -0700\" \"{subject}\" NIL NIL NIL NIL NIL NIL NIL \"<{i}@example.org>\"))\r\n").as_bytes(),
+        );
+    }
+
+    out
+}
+
+/// Create `count` FETCH responses with large literals as seen when downloading messages.
+fn create_literal(count: usize) -> Vec<u8> {
+    let body = "The quick brown fox jumps over the lazy dog.\r\n".repeat(1_000);
+    let mut out = Vec::new();
+
+    for i in 1..=count {
+        out.extend_from_slice(
+            format!("* {i} FETCH (BODY[] {{{}}}\r\n{body})\r\n", body.len()).as_bytes(),
+        );
+    }
+
+    out
+}
+
+#[inline]
+fn parse(codec: &ResponseCodec, mut input: &[u8]) -> usize {
+    let mut count = 0;
+
+    while !input.is_empty() {
+        let (remaining, _) = codec.decode(input).unwrap();
+        input = remaining;
+        count += 1;
+    }
+
+    count
+}
+
+criterion_group!(benches, criterion_benchmark);
+
+criterion_main!(benches);
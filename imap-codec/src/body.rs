@@ -1,3 +1,5 @@
+use alloc::{boxed::Box, vec, vec::Vec};
+
 use abnf_core::streaming::sp;
 use imap_types::{
     body::{
@@ -10,20 +12,20 @@ use nom::{
     branch::alt,
     bytes::streaming::{tag, tag_no_case},
     combinator::{map, opt},
-    multi::{many0, many1, separated_list0, separated_list1},
+    multi::{many0, many1, separated_list1},
     sequence::{delimited, preceded, tuple},
 };
 
 use crate::{
-    core::{nil, nstring, number, string},
-    decode::{IMAPErrorKind, IMAPParseError, IMAPResult},
+    core::{nil, nstring, number, separated_list0, string},
+    decode::{quirks, recursion_limits, IMAPErrorKind, IMAPParseError, IMAPResult},
     envelope::envelope,
 };
 
 /// `body = "(" (body-type-1part / body-type-mpart) ")"`
 ///
 /// Note: This parser is recursively defined. Thus, in order to not overflow the stack,
-/// it is needed to limit how may recursions are allowed. (8 should suffice).
+/// it is needed to limit how may recursions are allowed. See [`RecursionLimits`](crate::decode::RecursionLimits).
 pub(crate) fn body(
     remaining_recursions: usize,
 ) -> impl Fn(&[u8]) -> IMAPResult<&[u8], BodyStructure> {
@@ -275,21 +277,18 @@ pub(crate) fn body_fld_enc(input: &[u8]) -> IMAPResult<&[u8], IString> {
 /// The following erroneous messages were observed:
 ///
 /// * A negative number, specifically `-1`, in Dovecot.
-#[allow(clippy::needless_return)]
 pub(crate) fn body_fld_octets(input: &[u8]) -> IMAPResult<&[u8], u32> {
-    #[cfg(not(feature = "quirk_rectify_numbers"))]
-    return number(input);
-
-    #[cfg(feature = "quirk_rectify_numbers")]
-    {
-        return alt((
-            number,
-            map(tuple((tag("-"), number)), |(_, _)| {
-                log::warn!("Rectified negative number to 0");
-                0
-            }),
-        ))(input);
+    if !quirks().rectify_numbers {
+        return number(input);
     }
+
+    alt((
+        number,
+        map(tuple((tag("-"), number)), |(_, _)| {
+            log::warn!("Rectified negative number to 0");
+            0
+        }),
+    ))(input)
 }
 
 #[inline]
@@ -321,7 +320,10 @@ pub(crate) fn body_ext_1part(input: &[u8]) -> IMAPResult<&[u8], SinglePartExtens
                             opt(map(
                                 tuple((
                                     preceded(sp, body_fld_loc),
-                                    many0(preceded(sp, body_extension(8))),
+                                    many0(preceded(
+                                        sp,
+                                        body_extension(recursion_limits().extension),
+                                    )),
                                 )),
                                 |(location, extensions)| Location {
                                     location,
@@ -346,10 +348,19 @@ pub(crate) fn body_fld_md5(input: &[u8]) -> IMAPResult<&[u8], NString> {
 }
 
 /// `body-fld-dsp = "(" string SP body-fld-param ")" / nil`
+///
+/// Note: Also accepts `()` (as NIL) with [`Quirks::empty_lists`](crate::decode::Quirks::empty_lists).
 #[allow(clippy::type_complexity)]
 pub(crate) fn body_fld_dsp(
     input: &[u8],
 ) -> IMAPResult<&[u8], Option<(IString, Vec<(IString, IString)>)>> {
+    if quirks().empty_lists {
+        if let Ok((remaining, _)) = tag::<_, _, IMAPParseError<&[u8]>>(b"()")(input) {
+            log::warn!("Rectified empty disposition to NIL");
+            return Ok((remaining, None));
+        }
+    }
+
     alt((
         delimited(
             tag(b"("),
@@ -364,7 +375,16 @@ pub(crate) fn body_fld_dsp(
 }
 
 /// `body-fld-lang = nstring / "(" string *(SP string) ")"`
+///
+/// Note: Also accepts `()` (as NIL) with [`Quirks::empty_lists`](crate::decode::Quirks::empty_lists).
 pub(crate) fn body_fld_lang(input: &[u8]) -> IMAPResult<&[u8], Vec<IString>> {
+    if quirks().empty_lists {
+        if let Ok((remaining, _)) = tag::<_, _, IMAPParseError<&[u8]>>(b"()")(input) {
+            log::warn!("Rectified empty language list to NIL");
+            return Ok((remaining, vec![]));
+        }
+    }
+
     alt((
         map(nstring, |nstring| match nstring.0 {
             Some(item) => vec![item],
@@ -484,7 +504,10 @@ pub(crate) fn body_ext_mpart(input: &[u8]) -> IMAPResult<&[u8], MultiPartExtensi
                             opt(map(
                                 tuple((
                                     preceded(sp, body_fld_loc),
-                                    many0(preceded(sp, body_extension(8))),
+                                    many0(preceded(
+                                        sp,
+                                        body_extension(recursion_limits().extension),
+                                    )),
                                 )),
                                 |(location, extensions)| Location {
                                     location,
@@ -572,12 +595,11 @@ pub(crate) fn media_text(input: &[u8]) -> IMAPResult<&[u8], IString> {
 
 #[cfg(test)]
 mod tests {
-    use std::num::NonZeroU32;
-
     use imap_types::{
         core::{Literal, Quoted},
         fetch::MessageDataItem,
         response::{Data, Response},
+        sequence::SeqNo,
     };
 
     use super::*;
@@ -626,6 +648,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_body_fld_empty_lists() {
+        let dsp = body_fld_dsp(b"() ");
+        let lang = body_fld_lang(b"() ");
+
+        #[cfg(not(feature = "quirk_empty_lists"))]
+        {
+            assert!(dsp.is_err());
+            assert!(lang.is_err());
+        }
+        #[cfg(feature = "quirk_empty_lists")]
+        {
+            assert_eq!(dsp.unwrap(), (b" ".as_ref(), None));
+            assert_eq!(lang.unwrap(), (b" ".as_ref(), vec![]));
+        }
+    }
+
     #[test]
     fn test_body_rec() {
         let _ = body(8)(str::repeat("(", 1_000_000).as_bytes());
@@ -666,7 +705,7 @@ mod tests {
             b"* 3372220415 FETCH (BODYSTRUCTURE ((((((({0}\r\n {0}\r\n NIL NIL NIL {0}\r\n 0 \"FOO\" NIL NIL \"LOCATION\" 1337) \"mixed\") \"mixed\") \"mixed\") \"mixed\") \"mixed\") \"mixed\"))\r\n".as_ref(),
             b"".as_ref(),
             Response::Data(Data::Fetch {
-                seq: NonZeroU32::try_from(3372220415).unwrap(),
+                seq: SeqNo::new(3372220415).unwrap(),
                 items: Vec1::from(MessageDataItem::BodyStructure(
                     BodyStructure::Multi {
                         bodies: Vec1::from(BodyStructure::Multi {
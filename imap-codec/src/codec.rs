@@ -1,5 +1,13 @@
 pub mod decode;
 pub mod encode;
+pub mod frozen;
+#[cfg(feature = "futures")]
+#[cfg_attr(docsrs, doc(cfg(feature = "futures")))]
+pub mod futures;
+pub mod stream;
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub mod tokio;
 
 /// Codec for greetings.
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -8,27 +16,83 @@ pub mod encode;
 // This allows to add configuration options later. For example, the
 // codec could transparently replace all literals with non-sync literals.
 #[non_exhaustive]
-pub struct GreetingCodec;
+pub struct GreetingCodec {
+    pub(crate) max_line_length: Option<usize>,
+    #[cfg(feature = "std")]
+    pub(crate) quirks: decode::Quirks,
+}
 
 /// Codec for commands.
 #[derive(Clone, Debug, Default, PartialEq)]
 #[non_exhaustive]
-pub struct CommandCodec;
+pub struct CommandCodec {
+    pub(crate) max_line_length: Option<usize>,
+    #[cfg(feature = "std")]
+    pub(crate) max_literal_size: Option<u32>,
+    #[cfg(feature = "std")]
+    pub(crate) recursion_limits: decode::RecursionLimits,
+    #[cfg(feature = "std")]
+    pub(crate) quirks: decode::Quirks,
+    pub(crate) encode_options: encode::EncodeOptions,
+    #[cfg(feature = "std")]
+    pub(crate) tag_generator: Option<encode::SharedTagGenerator>,
+}
 
 /// Codec for authenticate data lines.
 #[derive(Clone, Debug, Default, PartialEq)]
 #[non_exhaustive]
-pub struct AuthenticateDataCodec;
+pub struct AuthenticateDataCodec {
+    pub(crate) max_line_length: Option<usize>,
+    #[cfg(feature = "std")]
+    pub(crate) quirks: decode::Quirks,
+}
 
 /// Codec for responses.
 #[derive(Clone, Debug, Default, PartialEq)]
 #[non_exhaustive]
-pub struct ResponseCodec;
+pub struct ResponseCodec {
+    pub(crate) max_line_length: Option<usize>,
+    #[cfg(feature = "std")]
+    pub(crate) max_literal_size: Option<u32>,
+    #[cfg(feature = "std")]
+    pub(crate) recursion_limits: decode::RecursionLimits,
+    #[cfg(feature = "std")]
+    pub(crate) quirks: decode::Quirks,
+    pub(crate) encode_options: encode::EncodeOptions,
+}
 
 /// Codec for idle dones.
 #[derive(Clone, Debug, Default, PartialEq)]
 #[non_exhaustive]
-pub struct IdleDoneCodec;
+pub struct IdleDoneCodec {
+    pub(crate) max_line_length: Option<usize>,
+    #[cfg(feature = "std")]
+    pub(crate) quirks: decode::Quirks,
+}
+
+/// Codec for literal data, i.e., the bytes that follow a literal announcement.
+///
+/// After a [`CommandDecodeError::LiteralFound`](decode::CommandDecodeError::LiteralFound), a server
+/// sends a command continuation request and waits for the literal. Use this codec on the bytes
+/// following the announcement (`{<length>}\r\n`) to check whether the literal was received
+/// completely. The rest of the command is then decoded using the [`CommandCodec`] again.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct LiteralCodec {
+    pub(crate) length: u32,
+}
+
+impl LiteralCodec {
+    /// Create codec for a literal of `length` bytes.
+    pub fn new(length: u32) -> Self {
+        Self { length }
+    }
+
+    /// Length of the literal.
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+}
 
 macro_rules! impl_codec_new {
     ($codec:ty) => {
@@ -37,6 +101,68 @@ macro_rules! impl_codec_new {
             pub fn new() -> Self {
                 Self::default()
             }
+
+            /// Limit the length of lines (including the line ending).
+            ///
+            /// When a line exceeds the limit, decoding fails with a `LineTooLong` error, even when
+            /// the line is not complete yet. This prevents unbounded buffering when a peer never
+            /// sends a line ending. Literal data is not counted.
+            pub fn max_line_length(mut self, max_line_length: usize) -> Self {
+                self.max_line_length = Some(max_line_length);
+                self
+            }
+
+            /// Set the parsing mode, i.e., disable ([`ParseMode::Strict`](decode::ParseMode::Strict))
+            /// or enable ([`ParseMode::Lenient`](decode::ParseMode::Lenient)) all quirks.
+            ///
+            /// By default, the quirks of the activated `quirk_` features are enabled.
+            ///
+            /// Note: The configuration is passed to the parsers via a thread local and thus
+            /// requires `std`. Without `std`, the quirks of the activated `quirk_` features are used.
+            #[cfg(feature = "std")]
+            #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+            pub fn parse_mode(mut self, parse_mode: decode::ParseMode) -> Self {
+                self.quirks = parse_mode.into();
+                self
+            }
+
+            /// Set the quirks individually.
+            ///
+            /// Note: Requires `std`, see [`Self::parse_mode`].
+            #[cfg(feature = "std")]
+            #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+            pub fn quirks(mut self, quirks: decode::Quirks) -> Self {
+                self.quirks = quirks;
+                self
+            }
+        }
+    };
+}
+
+macro_rules! impl_codec_encode_options {
+    ($codec:ty) => {
+        impl $codec {
+            /// Encode literals as quoted strings when possible.
+            ///
+            /// A literal is encoded as a quoted string when its content is allowed in a quoted
+            /// string and it isn't longer than `max_length` bytes. This avoids round trips caused
+            /// by synchronizing literals, e.g., when sending passwords or mailbox names. Literals
+            /// that are required by the protocol, e.g., the message in an `APPEND`, are not
+            /// affected.
+            pub fn prefer_quoted(mut self, max_length: usize) -> Self {
+                self.encode_options.prefer_quoted = Some(max_length);
+                self
+            }
+
+            /// Allow UTF-8 in quoted strings.
+            ///
+            /// Enable this after `UTF8=ACCEPT` was enabled in the session. Then, strings (and
+            /// mailbox names) that contain UTF-8 are encoded as quoted strings instead of literals.
+            /// The maximum length set via `prefer_quoted` is respected.
+            pub fn utf8(mut self, enabled: bool) -> Self {
+                self.encode_options.utf8 = enabled;
+                self
+            }
         }
     };
 }
@@ -47,6 +173,111 @@ impl_codec_new!(AuthenticateDataCodec);
 impl_codec_new!(ResponseCodec);
 impl_codec_new!(IdleDoneCodec);
 
+impl_codec_encode_options!(CommandCodec);
+impl_codec_encode_options!(ResponseCodec);
+
+impl CommandCodec {
+    /// Install a tag generator used by [`CommandCodec::encode_body`].
+    ///
+    /// The generator is shared between clones of this codec. Any [`GenerateTag`] can be used,
+    /// e.g., a closure or a `TagGenerator` (requires the `tag_generator` feature).
+    ///
+    /// [`GenerateTag`]: imap_types::core::GenerateTag
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imap_codec::{imap_types::core::Tag, CommandCodec};
+    ///
+    /// let mut counter = 0;
+    /// let codec = CommandCodec::new().tag_generator(move || {
+    ///     counter += 1;
+    ///     Tag::try_from(format!("A{counter}")).unwrap()
+    /// });
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn tag_generator<G>(mut self, generator: G) -> Self
+    where
+        G: imap_types::core::GenerateTag + Send + 'static,
+    {
+        self.tag_generator = Some(encode::SharedTagGenerator::new(generator));
+        self
+    }
+
+    /// Encode all literals using `mode`, overriding the mode of the individual literals.
+    ///
+    /// Use [`LiteralMode::NonSync`](imap_types::core::LiteralMode::NonSync) only when the server
+    /// advertised `LITERAL+` (or `LITERAL-` and the literals are small enough).
+    pub fn literal_mode(mut self, mode: imap_types::core::LiteralMode) -> Self {
+        self.encode_options.literal_mode = Some(mode);
+        self
+    }
+
+    /// Limit the size of literals.
+    ///
+    /// When a larger literal is announced, decoding fails with
+    /// [`CommandDecodeError::LiteralTooLarge`](decode::CommandDecodeError::LiteralTooLarge)
+    /// instead of [`CommandDecodeError::LiteralFound`](decode::CommandDecodeError::LiteralFound).
+    /// The server should then reject the command.
+    ///
+    /// Note: The limit is checked right after the announcement, i.e., also for literals that were
+    /// received completely. The limit is passed to the parsers via a thread local and thus requires
+    /// `std`. Without `std`, literals are not limited.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn max_literal_size(mut self, max_literal_size: u32) -> Self {
+        self.max_literal_size = Some(max_literal_size);
+        self
+    }
+
+    /// Set the maximum nesting depths of recursively defined messages.
+    ///
+    /// When a message is nested deeper, decoding fails with
+    /// [`CommandDecodeError::RecursionLimitExceeded`](decode::CommandDecodeError::RecursionLimitExceeded).
+    ///
+    /// Note: Higher limits require more stack space. The limits are passed to the parsers via a
+    /// thread local and thus require `std`. Without `std`, the default limits are used.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn recursion_limits(mut self, recursion_limits: decode::RecursionLimits) -> Self {
+        self.recursion_limits = recursion_limits;
+        self
+    }
+}
+
+impl ResponseCodec {
+    /// Limit the size of literals.
+    ///
+    /// When a larger literal is announced, decoding fails with
+    /// [`ResponseDecodeError::LiteralTooLarge`](decode::ResponseDecodeError::LiteralTooLarge)
+    /// instead of [`ResponseDecodeError::LiteralFound`](decode::ResponseDecodeError::LiteralFound).
+    ///
+    /// Note: The limit is checked right after the announcement, i.e., also for literals that were
+    /// received completely. The limit is passed to the parsers via a thread local and thus requires
+    /// `std`. Without `std`, literals are not limited.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn max_literal_size(mut self, max_literal_size: u32) -> Self {
+        self.max_literal_size = Some(max_literal_size);
+        self
+    }
+
+    /// Set the maximum nesting depths of recursively defined messages.
+    ///
+    /// When a message is nested deeper, decoding fails with
+    /// [`ResponseDecodeError::RecursionLimitExceeded`](decode::ResponseDecodeError::RecursionLimitExceeded).
+    ///
+    /// Note: Higher limits require more stack space. The limits are passed to the parsers via a
+    /// thread local and thus require `std`. Without `std`, the default limits are used.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn recursion_limits(mut self, recursion_limits: decode::RecursionLimits) -> Self {
+        self.recursion_limits = recursion_limits;
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::num::NonZeroU32;
@@ -54,16 +285,21 @@ mod tests {
     use imap_types::{
         auth::AuthenticateData,
         command::{Command, CommandBody},
-        core::{IString, Literal, LiteralMode, NString, Tag, Vec1},
+        core::{Atom, IString, Literal, LiteralMode, NString, Tag, Vec1},
         extensions::idle::IdleDone,
         fetch::MessageDataItem,
+        flag::{Flag, FlagFetch},
         mailbox::Mailbox,
         response::{Data, Greeting, GreetingKind, Response},
+        sequence::SeqNo,
     };
 
     use super::*;
     use crate::{
-        decode::{CommandDecodeError, Decoder, GreetingDecodeError, ResponseDecodeError},
+        decode::{
+            CommandDecodeError, Decoder, GreetingDecodeError, ParseMode, Quirks, RecursionLimits,
+            ResponseDecodeError,
+        },
         testing::{
             kat_inverse_authenticate_data, kat_inverse_command, kat_inverse_done,
             kat_inverse_greeting, kat_inverse_response,
@@ -111,7 +347,6 @@ mod tests {
                     "a",
                     CommandBody::Select {
                         mailbox: Mailbox::Inbox,
-                        #[cfg(feature = "ext_condstore_qresync")]
                         parameters: Vec::default(),
                     },
                 )
@@ -124,7 +359,6 @@ mod tests {
                     "a",
                     CommandBody::Select {
                         mailbox: Mailbox::Inbox,
-                        #[cfg(feature = "ext_condstore_qresync")]
                         parameters: Vec::default(),
                     },
                 )
@@ -140,7 +374,7 @@ mod tests {
                 b"* SEARCH 1\r\n".as_ref(),
                 b"".as_ref(),
                 Response::Data(Data::Search(
-                    vec![NonZeroU32::new(1).unwrap()],
+                    vec![NonZeroU32::new(1).unwrap()].into(),
                     #[cfg(feature = "ext_condstore_qresync")]
                     None,
                 )),
@@ -149,7 +383,7 @@ mod tests {
                 b"* SEARCH 1\r\n???",
                 b"???",
                 Response::Data(Data::Search(
-                    vec![NonZeroU32::new(1).unwrap()],
+                    vec![NonZeroU32::new(1).unwrap()].into(),
                     #[cfg(feature = "ext_condstore_qresync")]
                     None,
                 )),
@@ -158,7 +392,7 @@ mod tests {
                 b"* 1 FETCH (RFC822 {5}\r\nhello)\r\n",
                 b"",
                 Response::Data(Data::Fetch {
-                    seq: NonZeroU32::new(1).unwrap(),
+                    seq: SeqNo::new(1).unwrap(),
                     items: Vec1::from(MessageDataItem::Rfc822(NString(Some(IString::Literal(
                         Literal::try_from(b"hello".as_ref()).unwrap(),
                     ))))),
@@ -318,4 +552,171 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_max_line_length() {
+        let codec = CommandCodec::new().max_line_length(10);
+        assert!(codec.decode(b"a noop\r\n").is_ok());
+        assert_eq!(
+            codec.decode(b"a noop\r\nb select inbox\r\n").unwrap().0,
+            b"b select inbox\r\n"
+        );
+        // Incomplete lines are checked, too.
+        assert_eq!(codec.decode(b"a noop"), Err(CommandDecodeError::Incomplete));
+        assert_eq!(
+            codec.decode(b"a select inbox"),
+            Err(CommandDecodeError::LineTooLong)
+        );
+
+        // Literal data is not counted.
+        let codec = CommandCodec::new().max_line_length(16);
+        assert!(codec
+            .decode(b"a lsub {20}\r\nxxxxxxxxxxxxxxxxxxxx x\r\n")
+            .is_ok());
+
+        let codec = ResponseCodec::new().max_line_length(10);
+        assert_eq!(
+            codec.decode(b"* 1 FETCH (RFC822 {5}\r\n"),
+            Err(ResponseDecodeError::LineTooLong)
+        );
+
+        let codec = GreetingCodec::new().max_line_length(10);
+        assert_eq!(
+            codec.decode(b"* OK ".repeat(100).as_slice()),
+            Err(GreetingDecodeError::LineTooLong)
+        );
+    }
+
+    #[test]
+    fn test_recursion_limits() {
+        // Deeply nested messages must not overflow the stack.
+        let search = format!(
+            "A SEARCH {}1{}\r\n",
+            "(".repeat(100_000),
+            ")".repeat(100_000)
+        );
+        assert_eq!(
+            CommandCodec::new().decode(search.as_bytes()),
+            Err(CommandDecodeError::RecursionLimitExceeded)
+        );
+
+        for response in [
+            format!("* 1 FETCH (BODYSTRUCTURE {}\r\n", "(".repeat(100_000)),
+            format!("* THREAD {}\r\n", "(".repeat(100_000)),
+        ] {
+            assert_eq!(
+                ResponseCodec::new().decode(response.as_bytes()),
+                Err(ResponseDecodeError::RecursionLimitExceeded)
+            );
+        }
+
+        // Limits are configurable.
+        let search = b"A SEARCH ((((1))))\r\n";
+        assert!(CommandCodec::new().decode(search).is_ok());
+
+        let limits = RecursionLimits {
+            search_key: 4,
+            ..RecursionLimits::default()
+        };
+        assert_eq!(
+            CommandCodec::new().recursion_limits(limits).decode(search),
+            Err(CommandDecodeError::RecursionLimitExceeded)
+        );
+    }
+
+    #[test]
+    fn test_max_literal_size() {
+        let codec = CommandCodec::new().max_literal_size(5);
+        assert!(matches!(
+            codec.decode(b"a login {5}\r\n"),
+            Err(CommandDecodeError::LiteralFound { length: 5, .. })
+        ));
+        assert_eq!(
+            codec.decode(b"a login {6}\r\n"),
+            Err(CommandDecodeError::LiteralTooLarge {
+                tag: Tag::try_from("a").unwrap(),
+                length: 6,
+            })
+        );
+        // Literals that were received completely are checked, too.
+        assert!(codec.decode(b"a login {5}\r\nalice x\r\n").is_ok());
+        assert_eq!(
+            codec.decode(b"a login {6}\r\nalice! x\r\n"),
+            Err(CommandDecodeError::LiteralTooLarge {
+                tag: Tag::try_from("a").unwrap(),
+                length: 6,
+            })
+        );
+
+        let codec = ResponseCodec::new().max_literal_size(5);
+        assert_eq!(
+            codec.decode(b"* 1 FETCH (RFC822 {5}\r\n"),
+            Err(ResponseDecodeError::LiteralFound { length: 5 })
+        );
+        assert_eq!(
+            codec.decode(b"* 1 FETCH (RFC822 {4294967295}\r\n"),
+            Err(ResponseDecodeError::LiteralTooLarge { length: 4294967295 })
+        );
+        assert_eq!(
+            codec.decode(b"* 1 FETCH (RFC822 {6}\r\nfoobar)\r\n"),
+            Err(ResponseDecodeError::LiteralTooLarge { length: 6 })
+        );
+    }
+
+    #[test]
+    fn test_parse_mode() {
+        let strict = CommandCodec::new().parse_mode(ParseMode::Strict);
+        let lenient = CommandCodec::new().parse_mode(ParseMode::Lenient);
+        assert_eq!(strict.decode(b"a noop\n"), Err(CommandDecodeError::Failed));
+        assert_eq!(
+            lenient.decode(b"a noop\n"),
+            Ok((b"".as_ref(), Command::new("a", CommandBody::Noop).unwrap()))
+        );
+
+        let response = b"* 1 FETCH (FLAGS (Seen))\r\n";
+        let flags = |flag| {
+            Response::Data(Data::fetch(1, vec![MessageDataItem::Flags(vec![flag])]).unwrap())
+        };
+        assert_eq!(
+            ResponseCodec::new()
+                .parse_mode(ParseMode::Strict)
+                .decode(response),
+            Ok((
+                b"".as_ref(),
+                flags(FlagFetch::Flag(Flag::Keyword(
+                    Atom::try_from("Seen").unwrap()
+                )))
+            ))
+        );
+        assert_eq!(
+            ResponseCodec::new()
+                .parse_mode(ParseMode::Lenient)
+                .decode(response),
+            Ok((b"".as_ref(), flags(FlagFetch::Flag(Flag::Seen))))
+        );
+
+        // 8-bit text requires the `quirk_8bit_text` feature and can be disabled at runtime.
+        let input = "* OK Grüße\r\n".as_bytes();
+        assert!(ResponseCodec::new()
+            .parse_mode(ParseMode::Strict)
+            .decode(input)
+            .is_err());
+        assert_eq!(
+            ResponseCodec::new()
+                .parse_mode(ParseMode::Lenient)
+                .decode(input)
+                .is_ok(),
+            cfg!(feature = "quirk_8bit_text")
+        );
+
+        // Quirks can be enabled individually.
+        let mut quirks = Quirks::none();
+        quirks.crlf_relaxed = true;
+        let codec = ResponseCodec::new().quirks(quirks);
+        assert!(codec.decode(b"* OK done\n").is_ok());
+        assert!(matches!(
+            codec.decode(response),
+            Ok((_, response)) if response == flags(FlagFetch::Flag(Flag::Keyword(Atom::try_from("Seen").unwrap())))
+        ));
+    }
 }
@@ -8,25 +8,49 @@
 //! This makes real-world decoding of IMAP more elaborate.
 //!
 //! Have a look at the [parse_command](https://github.com/duesee/imap-codec/blob/main/imap-codec/examples/parse_command.rs) example to see how a real-world application could decode IMAP.
+//!
+//! ## Zero-copy
+//!
+//! Decoded messages borrow from the input wherever the IMAP grammar allows it.
+//! Atoms, tags, texts, literals, and quoted strings without escapes are never copied.
+//! Keywords, e.g., system flags, capabilities, or authentication mechanisms, are matched without allocating.
+//!
+//! Allocations are still required for:
+//!
+//! * lists, e.g., flags, capabilities, or fetch items,
+//! * quoted strings containing escapes (`\\` or `\"`),
+//! * base64-encoded data, and
+//! * nested structures, e.g., search keys or body structures.
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use core::cell::Cell;
+use core::{
+    fmt::{Display, Formatter},
+    num::{ParseIntError, TryFromIntError},
+    str::Utf8Error,
+};
 
-use std::num::{ParseIntError, TryFromIntError};
-
+#[cfg(feature = "ext_condstore_qresync")]
+use imap_types::extensions::condstore_qresync::error::ModSeqError;
 use imap_types::{
     auth::AuthenticateData,
     command::Command,
     core::{LiteralMode, Tag},
-    extensions::idle::IdleDone,
+    extensions::{idle::IdleDone, quota::error::QuotaValueError},
     response::{Greeting, Response},
     IntoStatic,
 };
+use memchr::memchr;
 use nom::error::{ErrorKind, FromExternalError, ParseError};
 
 use crate::{
     auth::authenticate_data,
     command::command,
     extensions::idle::idle_done,
+    fragmentizer::parse_tag,
     response::{greeting, response},
-    AuthenticateDataCodec, CommandCodec, GreetingCodec, IdleDoneCodec, ResponseCodec,
+    AuthenticateDataCodec, CommandCodec, GreetingCodec, IdleDoneCodec, LiteralCodec, ResponseCodec,
 };
 
 /// An extended version of [`nom::IResult`].
@@ -35,7 +59,6 @@ pub(crate) type IMAPResult<'a, I, O> = Result<(I, O), nom::Err<IMAPParseError<'a
 /// An extended version of [`nom::error::Error`].
 #[derive(Debug)]
 pub(crate) struct IMAPParseError<'a, I> {
-    #[allow(unused)]
     pub input: I,
     pub kind: IMAPErrorKind<'a>,
 }
@@ -48,6 +71,10 @@ pub(crate) enum IMAPErrorKind<'a> {
         length: u32,
         mode: LiteralMode,
     },
+    LiteralTooLarge {
+        tag: Option<Tag<'a>>,
+        length: u32,
+    },
     BadNumber,
     BadBase64,
     BadDateTime,
@@ -90,6 +117,34 @@ impl<I> FromExternalError<I, TryFromIntError> for IMAPParseError<'_, I> {
     }
 }
 
+impl<I> FromExternalError<I, Utf8Error> for IMAPParseError<'_, I> {
+    fn from_external_error(input: I, kind: ErrorKind, _: Utf8Error) -> Self {
+        Self {
+            input,
+            kind: IMAPErrorKind::Nom(kind),
+        }
+    }
+}
+
+#[cfg(feature = "ext_condstore_qresync")]
+impl<I> FromExternalError<I, ModSeqError> for IMAPParseError<'_, I> {
+    fn from_external_error(input: I, _: ErrorKind, _: ModSeqError) -> Self {
+        Self {
+            input,
+            kind: IMAPErrorKind::BadNumber,
+        }
+    }
+}
+
+impl<I> FromExternalError<I, QuotaValueError> for IMAPParseError<'_, I> {
+    fn from_external_error(input: I, _: ErrorKind, _: QuotaValueError) -> Self {
+        Self {
+            input,
+            kind: IMAPErrorKind::BadNumber,
+        }
+    }
+}
+
 impl<I> FromExternalError<I, base64::DecodeError> for IMAPParseError<'_, I> {
     fn from_external_error(input: I, _: ErrorKind, _: base64::DecodeError) -> Self {
         Self {
@@ -99,6 +154,228 @@ impl<I> FromExternalError<I, base64::DecodeError> for IMAPParseError<'_, I> {
     }
 }
 
+/// Maximum nesting depths of recursively defined messages.
+///
+/// Some messages, e.g., `BODYSTRUCTURE`s, search keys, or threads, can be nested arbitrarily. The
+/// parsers stop at these depths to not overflow the stack. The defaults are sane for real-world
+/// messages and stack sizes.
+///
+/// See [`CommandCodec::recursion_limits`] and [`ResponseCodec::recursion_limits`].
+///
+/// Note: Configuring the limits of a codec requires the `std` feature. Without it, the defaults
+/// are always used.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub struct RecursionLimits {
+    /// Nesting of `body` (in `BODY` and `BODYSTRUCTURE`).
+    pub body: usize,
+    /// Nesting of `search-key`, e.g., via `NOT`, `OR`, or parentheses.
+    pub search_key: usize,
+    /// Nesting of `thread-list`.
+    pub thread: usize,
+    /// Nesting of generic extension data, i.e., `body-extension` and `tagged-ext-comp`.
+    pub extension: usize,
+}
+
+impl RecursionLimits {
+    const DEFAULT: Self = Self {
+        body: 8,
+        search_key: 9,
+        thread: 8,
+        extension: 8,
+    };
+}
+
+impl Default for RecursionLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Parsing mode of a codec.
+///
+/// See, e.g., [`CommandCodec::parse_mode`].
+///
+/// Note: Setting the parsing mode of a codec requires the `std` feature.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ParseMode {
+    /// Only accept messages that conform to the IMAP grammar, i.e., disable all quirks.
+    Strict,
+    /// Accept common deviations from the IMAP grammar, i.e., enable all quirks.
+    Lenient,
+}
+
+/// Deviations from the IMAP grammar that are accepted during decoding.
+///
+/// By default, a quirk is enabled when the corresponding `quirk_` feature is activated.
+///
+/// See, e.g., [`CommandCodec::quirks`].
+///
+/// Note: Configuring the quirks of a codec requires the `std` feature. Without it, the quirks are
+/// always decided by the activated `quirk_` features.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub struct Quirks {
+    /// Accept 8-bit (UTF-8) characters in `quoted` and `text`.
+    ///
+    /// Note: Requires the `quirk_8bit_text` feature. Without it, this quirk is never applied.
+    pub eight_bit_text: bool,
+    /// Interpret keywords named like system flags, e.g., `Seen`, as system flags, e.g., `\Seen`.
+    pub bare_system_flags: bool,
+    /// Make `\r` in `\r\n` optional.
+    pub crlf_relaxed: bool,
+    /// Accept empty lists, e.g., `()`, in address lists, dispositions, and languages.
+    pub empty_lists: bool,
+    /// Rectify a missing `text` element in `resp-text` to `...`.
+    pub missing_text: bool,
+    /// Rectify negative numbers in `body-fld-octets` to `0`.
+    pub rectify_numbers: bool,
+    /// Accept spaces between addresses.
+    pub spaces_between_addresses: bool,
+    /// Accept a trailing space after `STATUS` responses.
+    pub trailing_space: bool,
+}
+
+impl Quirks {
+    const DEFAULT: Self = Self {
+        eight_bit_text: cfg!(feature = "quirk_8bit_text"),
+        bare_system_flags: cfg!(feature = "quirk_bare_system_flags"),
+        crlf_relaxed: cfg!(feature = "quirk_crlf_relaxed"),
+        empty_lists: cfg!(feature = "quirk_empty_lists"),
+        missing_text: cfg!(feature = "quirk_missing_text"),
+        rectify_numbers: cfg!(feature = "quirk_rectify_numbers"),
+        spaces_between_addresses: cfg!(feature = "quirk_spaces_between_addresses"),
+        trailing_space: cfg!(feature = "quirk_trailing_space"),
+    };
+
+    /// Disable all quirks.
+    pub const fn none() -> Self {
+        Self {
+            eight_bit_text: false,
+            bare_system_flags: false,
+            crlf_relaxed: false,
+            empty_lists: false,
+            missing_text: false,
+            rectify_numbers: false,
+            spaces_between_addresses: false,
+            trailing_space: false,
+        }
+    }
+
+    /// Enable all quirks.
+    ///
+    /// Note: 8-bit text is only accepted with the `quirk_8bit_text` feature.
+    pub const fn all() -> Self {
+        Self {
+            eight_bit_text: cfg!(feature = "quirk_8bit_text"),
+            bare_system_flags: true,
+            crlf_relaxed: true,
+            empty_lists: true,
+            missing_text: true,
+            rectify_numbers: true,
+            spaces_between_addresses: true,
+            trailing_space: true,
+        }
+    }
+}
+
+impl Default for Quirks {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl From<ParseMode> for Quirks {
+    fn from(mode: ParseMode) -> Self {
+        match mode {
+            ParseMode::Strict => Self::none(),
+            ParseMode::Lenient => Self::all(),
+        }
+    }
+}
+
+/// Configuration of the current decoding.
+#[derive(Clone, Copy)]
+struct Context {
+    recursion_limits: RecursionLimits,
+    quirks: Quirks,
+    max_literal_size: Option<u32>,
+}
+
+impl Context {
+    const DEFAULT: Self = Self {
+        recursion_limits: RecursionLimits::DEFAULT,
+        quirks: Quirks::DEFAULT,
+        max_literal_size: None,
+    };
+}
+
+#[cfg(feature = "std")]
+thread_local! {
+    static CONTEXT: Cell<Context> = const { Cell::new(Context::DEFAULT) };
+}
+
+/// Returns the recursion limits of the current decoding.
+///
+/// Note: The configuration is passed via a thread local to not thread it through every parser.
+/// Without `std`, there are no thread locals. Thus, codecs can't be configured and the defaults
+/// are used.
+pub(crate) fn recursion_limits() -> RecursionLimits {
+    context().recursion_limits
+}
+
+/// Returns the quirks of the current decoding.
+pub(crate) fn quirks() -> Quirks {
+    context().quirks
+}
+
+/// Returns the maximum literal size of the current decoding.
+pub(crate) fn max_literal_size() -> Option<u32> {
+    context().max_literal_size
+}
+
+#[cfg(feature = "std")]
+fn context() -> Context {
+    CONTEXT.with(|cell| cell.get())
+}
+
+#[cfg(not(feature = "std"))]
+fn context() -> Context {
+    Context::DEFAULT
+}
+
+/// Sets the configuration of the current decoding (until dropped).
+#[cfg(feature = "std")]
+struct ContextGuard {
+    previous: Context,
+}
+
+#[cfg(feature = "std")]
+impl ContextGuard {
+    fn set(
+        recursion_limits: RecursionLimits,
+        quirks: Quirks,
+        max_literal_size: Option<u32>,
+    ) -> Self {
+        let context = Context {
+            recursion_limits,
+            quirks,
+            max_literal_size,
+        };
+
+        Self {
+            previous: CONTEXT.with(|cell| cell.replace(context)),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        CONTEXT.with(|cell| cell.set(self.previous));
+    }
+}
+
 /// Decoder.
 ///
 /// Implemented for types that know how to decode a specific IMAP message. See [implementors](trait.Decoder.html#implementors).
@@ -120,6 +397,158 @@ pub trait Decoder {
         let (remaining, value) = self.decode(input).map_err(IntoStatic::into_static)?;
         Ok((remaining, value.into_static()))
     }
+
+    /// Decode a message and return the consumed input together with it.
+    ///
+    /// Returns the remaining input, the message, and the bytes the message was decoded from. This
+    /// is useful, e.g., to log or forward the exact original bytes of a message.
+    #[allow(clippy::type_complexity)]
+    fn decode_with_span<'a>(
+        &self,
+        input: &'a [u8],
+    ) -> Result<(&'a [u8], Self::Message<'a>, &'a [u8]), Self::Error<'a>> {
+        let (remaining, message) = self.decode(input)?;
+        let consumed = &input[..input.len() - remaining.len()];
+
+        Ok((remaining, message, consumed))
+    }
+
+    /// Decode messages until more data is needed or an error occurs.
+    ///
+    /// Returns the decoded messages, the remaining input, and the error that stopped decoding (if
+    /// any). The remaining input should be kept and prepended to the next received data.
+    ///
+    /// Note: Errors that only signal that more data is needed (see [`DecodeError::is_incomplete`])
+    /// are not returned. Others, e.g., [`CommandDecodeError::LiteralFound`], are.
+    #[allow(clippy::type_complexity)]
+    fn decode_many<'a>(
+        &self,
+        mut input: &'a [u8],
+    ) -> (Vec<Self::Message<'a>>, &'a [u8], Option<Self::Error<'a>>)
+    where
+        Self::Error<'a>: DecodeError,
+    {
+        let mut messages = Vec::new();
+
+        loop {
+            match self.decode(input) {
+                Ok((remaining, message)) => {
+                    messages.push(message);
+                    input = remaining;
+                }
+                Err(error) if error.is_incomplete() => return (messages, input, None),
+                Err(error) => return (messages, input, Some(error)),
+            }
+        }
+    }
+
+    /// Decode a message and remember its original representation.
+    ///
+    /// See [`Preserved`].
+    #[allow(clippy::type_complexity)]
+    fn decode_preserved<'a>(
+        &self,
+        input: &'a [u8],
+    ) -> Result<(&'a [u8], Preserved<'a, Self::Message<'a>>), Self::Error<'a>> {
+        let (remaining, message, raw) = self.decode_with_span(input)?;
+
+        Ok((
+            remaining,
+            Preserved {
+                raw,
+                message,
+                modified: false,
+            },
+        ))
+    }
+}
+
+/// A decoded message that remembers its original representation.
+///
+/// Decoding normalizes some details, e.g., the case of keywords or optional whitespace. Thus,
+/// encoding a decoded message doesn't necessarily reproduce the input. This is a problem, e.g., for
+/// proxies that should forward messages unchanged. [`Preserved::encode`] reproduces the input
+/// byte-for-byte as long as the message wasn't accessed via [`Preserved::message_mut`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Preserved<'a, M> {
+    pub(crate) raw: &'a [u8],
+    pub(crate) message: M,
+    /// Set when the message was (potentially) modified.
+    pub(crate) modified: bool,
+}
+
+impl<'a, M> Preserved<'a, M> {
+    /// Original bytes of the message.
+    pub fn raw(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Decoded message.
+    pub fn message(&self) -> &M {
+        &self.message
+    }
+
+    /// Decoded message for modification.
+    ///
+    /// Note: The message is considered modified afterwards, even if it wasn't changed.
+    pub fn message_mut(&mut self) -> &mut M {
+        self.modified = true;
+        &mut self.message
+    }
+
+    /// Get the decoded message (and forget the original representation).
+    pub fn into_message(self) -> M {
+        self.message
+    }
+
+    /// Check if the message was modified, i.e., if encoding normalizes the message.
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+}
+
+/// Error that is returned by a [`Decoder`].
+pub trait DecodeError {
+    /// More data is needed and nothing needs to be done.
+    fn is_incomplete(&self) -> bool;
+}
+
+impl DecodeError for GreetingDecodeError {
+    fn is_incomplete(&self) -> bool {
+        matches!(self, Self::Incomplete)
+    }
+}
+
+impl DecodeError for CommandDecodeError<'_> {
+    fn is_incomplete(&self) -> bool {
+        // The server must agree to the receival of (some) literals.
+        matches!(self, Self::Incomplete)
+    }
+}
+
+impl DecodeError for AuthenticateDataDecodeError {
+    fn is_incomplete(&self) -> bool {
+        matches!(self, Self::Incomplete)
+    }
+}
+
+impl DecodeError for ResponseDecodeError {
+    fn is_incomplete(&self) -> bool {
+        // The client must accept the literal anyway.
+        matches!(self, Self::Incomplete | Self::LiteralFound { .. })
+    }
+}
+
+impl DecodeError for IdleDoneDecodeError {
+    fn is_incomplete(&self) -> bool {
+        matches!(self, Self::Incomplete)
+    }
+}
+
+impl DecodeError for LiteralDecodeError {
+    fn is_incomplete(&self) -> bool {
+        matches!(self, Self::Incomplete { .. })
+    }
 }
 
 /// Error during greeting decoding.
@@ -128,6 +557,11 @@ pub enum GreetingDecodeError {
     /// More data is needed.
     Incomplete,
 
+    /// A line exceeds the configured maximum length.
+    ///
+    /// See [`GreetingCodec::max_line_length`].
+    LineTooLong,
+
     /// Decoding failed.
     Failed,
 }
@@ -195,6 +629,28 @@ pub enum CommandDecodeError<'a> {
         mode: LiteralMode,
     },
 
+    /// The decoder stopped at a literal that exceeds the configured maximum size.
+    ///
+    /// See [`CommandCodec::max_literal_size`]. The server should reject the command, e.g., with a
+    /// tagged `NO` response, and must not send a command continuation request.
+    LiteralTooLarge {
+        /// The corresponding command (tag) to which this literal is bound.
+        tag: Tag<'a>,
+
+        /// Literal length.
+        length: u32,
+    },
+
+    /// A line exceeds the configured maximum length.
+    ///
+    /// See [`CommandCodec::max_line_length`].
+    LineTooLong,
+
+    /// The command is nested deeper than allowed.
+    ///
+    /// See [`CommandCodec::recursion_limits`].
+    RecursionLimitExceeded,
+
     /// Decoding failed.
     Failed,
 }
@@ -212,6 +668,16 @@ impl IntoStatic for CommandDecodeError<'_> {
                     mode,
                 }
             }
+            CommandDecodeError::LiteralTooLarge { tag, length } => {
+                CommandDecodeError::LiteralTooLarge {
+                    tag: tag.into_static(),
+                    length,
+                }
+            }
+            CommandDecodeError::LineTooLong => CommandDecodeError::LineTooLong,
+            CommandDecodeError::RecursionLimitExceeded => {
+                CommandDecodeError::RecursionLimitExceeded
+            }
             CommandDecodeError::Failed => CommandDecodeError::Failed,
         }
     }
@@ -223,6 +689,11 @@ pub enum AuthenticateDataDecodeError {
     /// More data is needed.
     Incomplete,
 
+    /// A line exceeds the configured maximum length.
+    ///
+    /// See [`AuthenticateDataCodec::max_line_length`].
+    LineTooLong,
+
     /// Decoding failed.
     Failed,
 }
@@ -254,6 +725,25 @@ pub enum ResponseDecodeError {
         length: u32,
     },
 
+    /// The decoder stopped at a literal that exceeds the configured maximum size.
+    ///
+    /// See [`ResponseCodec::max_literal_size`]. As the client can't reject the literal, it should,
+    /// e.g., close the connection.
+    LiteralTooLarge {
+        /// Literal length.
+        length: u32,
+    },
+
+    /// A line exceeds the configured maximum length.
+    ///
+    /// See [`ResponseCodec::max_line_length`].
+    LineTooLong,
+
+    /// The response is nested deeper than allowed.
+    ///
+    /// See [`ResponseCodec::recursion_limits`].
+    RecursionLimitExceeded,
+
     /// Decoding failed.
     Failed,
 }
@@ -272,6 +762,11 @@ pub enum IdleDoneDecodeError {
     /// More data is needed.
     Incomplete,
 
+    /// A line exceeds the configured maximum length.
+    ///
+    /// See [`IdleDoneCodec::max_line_length`].
+    LineTooLong,
+
     /// Decoding failed.
     Failed,
 }
@@ -284,6 +779,459 @@ impl IntoStatic for IdleDoneDecodeError {
     }
 }
 
+/// Error during literal decoding.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LiteralDecodeError {
+    /// More data is needed.
+    Incomplete {
+        /// Number of missing bytes.
+        missing: u32,
+    },
+}
+
+impl IntoStatic for LiteralDecodeError {
+    type Static = Self;
+
+    fn into_static(self) -> Self::Static {
+        self
+    }
+}
+
+/// Details about a decoding failure.
+///
+/// This is intended for debugging, e.g., interoperability problems with real servers. The
+/// descriptions are not considered stable.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Diagnostic {
+    /// Offset of the first byte that couldn't be decoded (relative to the input).
+    pub offset: usize,
+    /// The (innermost) rule that failed, e.g., `number` or `Tag`.
+    pub rule: String,
+    /// A short description of what was expected.
+    pub expected: &'static str,
+}
+
+impl Diagnostic {
+    fn new(input: &[u8], error: &IMAPParseError<&[u8]>) -> Self {
+        let (rule, expected) = match &error.kind {
+            IMAPErrorKind::Literal { .. } => ("literal".into(), "literal data"),
+            IMAPErrorKind::BadNumber => ("number".into(), "a number in range"),
+            IMAPErrorKind::BadBase64 => ("base64".into(), "valid base64"),
+            IMAPErrorKind::BadDateTime => ("date-time".into(), "a valid date and time"),
+            IMAPErrorKind::LiteralContainsNull => ("literal".into(), "literal data without NUL"),
+            IMAPErrorKind::LiteralTooLarge { .. } => ("literal".into(), "a smaller literal"),
+            IMAPErrorKind::RecursionLimitExceeded => {
+                ("recursion limit".into(), "less deeply nested data")
+            }
+            IMAPErrorKind::Nom(kind) => {
+                let expected = match kind {
+                    ErrorKind::Tag => "a keyword or delimiter",
+                    ErrorKind::Char | ErrorKind::OneOf => "a specific character",
+                    ErrorKind::NoneOf => "a different character",
+                    ErrorKind::Alt => "one of multiple alternatives",
+                    ErrorKind::Digit => "a digit",
+                    ErrorKind::CrLf => "CRLF",
+                    ErrorKind::TakeWhile1 | ErrorKind::TakeWhileMN => "an allowed character",
+                    ErrorKind::MapRes | ErrorKind::Verify => "a valid value",
+                    ErrorKind::Not => "something else",
+                    ErrorKind::Eof => "more data",
+                    _ => "valid syntax",
+                };
+
+                (kind.description().into(), expected)
+            }
+        };
+
+        Self {
+            offset: input.len().saturating_sub(error.input.len()),
+            rule,
+            expected,
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        write!(
+            f,
+            "{} failed at byte {} (expected {})",
+            self.rule, self.offset, self.expected
+        )
+    }
+}
+
+/// Decoding error with an optional [`Diagnostic`].
+///
+/// See, e.g., [`CommandCodec::decode_detailed`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DetailedDecodeError<E> {
+    pub error: E,
+    /// Details about the failure (not available for, e.g., incomplete messages).
+    pub diagnostic: Option<Diagnostic>,
+}
+
+impl<E> DetailedDecodeError<E> {
+    fn failed(error: E, input: &[u8], parse_error: &IMAPParseError<&[u8]>) -> Self {
+        Self {
+            error,
+            diagnostic: Some(Diagnostic::new(input, parse_error)),
+        }
+    }
+}
+
+impl<E> From<E> for DetailedDecodeError<E> {
+    fn from(error: E) -> Self {
+        Self {
+            error,
+            diagnostic: None,
+        }
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+
+impl GreetingCodec {
+    /// Like [`Decoder::decode`] but with a [`Diagnostic`] on failure.
+    pub fn decode_detailed<'a>(
+        &self,
+        input: &'a [u8],
+    ) -> Result<(&'a [u8], Greeting<'a>), DetailedDecodeError<GreetingDecodeError>> {
+        #[cfg(feature = "std")]
+        let _context = ContextGuard::set(RecursionLimits::DEFAULT, self.quirks, None);
+
+        let result = match greeting(input) {
+            Ok((rem, grt)) => Ok((rem, grt)),
+            Err(nom::Err::Incomplete(_)) => Err(GreetingDecodeError::Incomplete.into()),
+            Err(nom::Err::Failure(error)) | Err(nom::Err::Error(error)) => Err(
+                DetailedDecodeError::failed(GreetingDecodeError::Failed, input, &error),
+            ),
+        };
+
+        enforce_max_line_length(
+            input,
+            self.max_line_length,
+            result,
+            GreetingDecodeError::LineTooLong,
+        )
+    }
+}
+
+impl CommandCodec {
+    /// Like [`Decoder::decode`] but with a [`Diagnostic`] on failure.
+    pub fn decode_detailed<'a>(
+        &self,
+        input: &'a [u8],
+    ) -> Result<(&'a [u8], Command<'a>), DetailedDecodeError<CommandDecodeError<'a>>> {
+        #[cfg(feature = "std")]
+        let _context = ContextGuard::set(self.recursion_limits, self.quirks, self.max_literal_size);
+
+        let result = match command(input) {
+            Ok((rem, cmd)) => Ok((rem, cmd)),
+            Err(nom::Err::Incomplete(_)) => Err(CommandDecodeError::Incomplete.into()),
+            Err(nom::Err::Failure(error)) => match error {
+                IMAPParseError {
+                    input: _,
+                    kind: IMAPErrorKind::Literal { tag, length, mode },
+                } => {
+                    // Unwrap: We *must* receive a `tag` during command parsing.
+                    let tag =
+                        tag.expect("Expected `Some(tag)` in `IMAPErrorKind::Literal`, got `None`");
+
+                    Err(CommandDecodeError::LiteralFound { tag, length, mode }.into())
+                }
+                IMAPParseError {
+                    input: _,
+                    kind: IMAPErrorKind::LiteralTooLarge { tag, length },
+                } => {
+                    // Unwrap: We *must* receive a `tag` during command parsing.
+                    let tag = tag.expect(
+                        "Expected `Some(tag)` in `IMAPErrorKind::LiteralTooLarge`, got `None`",
+                    );
+
+                    Err(CommandDecodeError::LiteralTooLarge { tag, length }.into())
+                }
+                error @ IMAPParseError {
+                    kind: IMAPErrorKind::RecursionLimitExceeded,
+                    ..
+                } => Err(DetailedDecodeError::failed(
+                    CommandDecodeError::RecursionLimitExceeded,
+                    input,
+                    &error,
+                )),
+                error => Err(DetailedDecodeError::failed(
+                    CommandDecodeError::Failed,
+                    input,
+                    &error,
+                )),
+            },
+            Err(nom::Err::Error(error)) => Err(DetailedDecodeError::failed(
+                CommandDecodeError::Failed,
+                input,
+                &error,
+            )),
+        };
+
+        enforce_max_line_length(
+            input,
+            self.max_line_length,
+            result,
+            CommandDecodeError::LineTooLong,
+        )
+    }
+
+    /// Extract the tag of a (possibly malformed) command on best-effort basis.
+    ///
+    /// A server must respond with a tagged `BAD` even when a command can't be decoded, e.g., after
+    /// a [`CommandDecodeError::Failed`]. This function returns the leading tag of `input`, i.e.,
+    /// the bytes before the first space, if it is a valid tag.
+    ///
+    /// See also [`Fragmentizer::decode_tag`](crate::fragmentizer::Fragmentizer::decode_tag).
+    pub fn extract_tag<'a>(&self, input: &'a [u8]) -> Option<Tag<'a>> {
+        parse_tag(input)
+    }
+}
+
+impl ResponseCodec {
+    /// Like [`Decoder::decode`] but with a [`Diagnostic`] on failure.
+    pub fn decode_detailed<'a>(
+        &self,
+        input: &'a [u8],
+    ) -> Result<(&'a [u8], Response<'a>), DetailedDecodeError<ResponseDecodeError>> {
+        #[cfg(feature = "std")]
+        let _context = ContextGuard::set(self.recursion_limits, self.quirks, self.max_literal_size);
+
+        let result = match response(input) {
+            Ok((rem, rsp)) => Ok((rem, rsp)),
+            Err(nom::Err::Incomplete(_)) => Err(ResponseDecodeError::Incomplete.into()),
+            Err(nom::Err::Error(error) | nom::Err::Failure(error)) => match error {
+                IMAPParseError {
+                    kind: IMAPErrorKind::Literal { length, .. },
+                    ..
+                } => Err(ResponseDecodeError::LiteralFound { length }.into()),
+                IMAPParseError {
+                    kind: IMAPErrorKind::LiteralTooLarge { length, .. },
+                    ..
+                } => Err(ResponseDecodeError::LiteralTooLarge { length }.into()),
+                error @ IMAPParseError {
+                    kind: IMAPErrorKind::RecursionLimitExceeded,
+                    ..
+                } => Err(DetailedDecodeError::failed(
+                    ResponseDecodeError::RecursionLimitExceeded,
+                    input,
+                    &error,
+                )),
+                error => Err(DetailedDecodeError::failed(
+                    ResponseDecodeError::Failed,
+                    input,
+                    &error,
+                )),
+            },
+        };
+
+        enforce_max_line_length(
+            input,
+            self.max_line_length,
+            result,
+            ResponseDecodeError::LineTooLong,
+        )
+    }
+}
+
+/// Replaces `result` with `line_too_long` when a line exceeds `max_line_length`.
+///
+/// For decoded messages, only the consumed bytes are checked. Failures are kept as is.
+fn enforce_max_line_length<'a, M, E>(
+    input: &'a [u8],
+    max_line_length: Option<usize>,
+    result: Result<(&'a [u8], M), DetailedDecodeError<E>>,
+    line_too_long: E,
+) -> Result<(&'a [u8], M), DetailedDecodeError<E>> {
+    let max_line_length = match max_line_length {
+        Some(max_line_length) => max_line_length,
+        None => return result,
+    };
+
+    let checked = match &result {
+        Ok((remaining, _)) => &input[..input.len() - remaining.len()],
+        // Only failures come with a diagnostic.
+        Err(DetailedDecodeError {
+            diagnostic: Some(_),
+            ..
+        }) => return result,
+        Err(_) => input,
+    };
+
+    if longest_line(checked) > max_line_length {
+        Err(line_too_long.into())
+    } else {
+        result
+    }
+}
+
+/// Returns the length of the longest (possibly incomplete) line in `input`.
+///
+/// Literal data is skipped.
+fn longest_line(input: &[u8]) -> usize {
+    let mut longest = 0;
+    let mut position = 0;
+
+    while position < input.len() {
+        match input[position..].iter().position(|b| *b == b'\n') {
+            Some(index) => {
+                let line_end = position + index + 1;
+                longest = longest.max(line_end - position);
+
+                let literal = literal_announcement(&input[position..line_end]);
+                position = line_end.saturating_add(literal.unwrap_or(0));
+            }
+            None => return longest.max(input.len() - position),
+        }
+    }
+
+    longest
+}
+
+/// Response decoded by [`ResponseCodec::decode_lossy`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LossyResponse<'a> {
+    Response(Response<'a>),
+    /// A message that couldn't be decoded (including the line ending and literals).
+    Unknown(Cow<'a, [u8]>),
+}
+
+impl IntoStatic for LossyResponse<'_> {
+    type Static = LossyResponse<'static>;
+
+    fn into_static(self) -> Self::Static {
+        match self {
+            LossyResponse::Response(response) => LossyResponse::Response(response.into_static()),
+            LossyResponse::Unknown(data) => LossyResponse::Unknown(Cow::Owned(data.into_owned())),
+        }
+    }
+}
+
+impl ResponseCodec {
+    /// Like [`Decoder::decode`] but returns undecodable messages instead of failing.
+    ///
+    /// On failure, the message is skipped up to the end of its line. Literals announced at the end
+    /// of a line are skipped, too, so that literal data is never interpreted as a response. This
+    /// allows long-running clients to log the message and continue.
+    ///
+    /// [`ResponseDecodeError::Failed`] and [`ResponseDecodeError::RecursionLimitExceeded`] are
+    /// never returned.
+    pub fn decode_lossy<'a>(
+        &self,
+        input: &'a [u8],
+    ) -> Result<(&'a [u8], LossyResponse<'a>), ResponseDecodeError> {
+        match self.decode(input) {
+            Ok((rem, rsp)) => Ok((rem, LossyResponse::Response(rsp))),
+            Err(ResponseDecodeError::Failed | ResponseDecodeError::RecursionLimitExceeded) => {
+                match skip_message(input) {
+                    Some(end) => Ok((
+                        &input[end..],
+                        LossyResponse::Unknown(Cow::Borrowed(&input[..end])),
+                    )),
+                    None => Err(ResponseDecodeError::Incomplete),
+                }
+            }
+            Err(error) => Err(error),
+        }
+    }
+}
+
+/// Returns the length of the next message, i.e., of its lines and announced literals.
+///
+/// Returns `None` when the message is incomplete.
+fn skip_message(input: &[u8]) -> Option<usize> {
+    let mut position = 0;
+
+    loop {
+        let line_end = position + memchr(b'\n', &input[position..])? + 1;
+        let line = &input[position..line_end];
+        position = line_end;
+
+        match literal_announcement(line) {
+            Some(length) => {
+                position = position.checked_add(length)?;
+
+                if position > input.len() {
+                    return None;
+                }
+            }
+            None => return Some(position),
+        }
+    }
+}
+
+/// Returns the length of the literal announced at the end of `line` (if any).
+fn literal_announcement(line: &[u8]) -> Option<usize> {
+    let line = line.strip_suffix(b"\n")?;
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    let line = line.strip_suffix(b"}")?;
+    let line = line.strip_suffix(b"+").unwrap_or(line);
+
+    let start = line.iter().rposition(|b| !b.is_ascii_digit())?;
+
+    if line[start] != b'{' || start + 1 == line.len() {
+        return None;
+    }
+
+    core::str::from_utf8(&line[start + 1..]).ok()?.parse().ok()
+}
+
+impl AuthenticateDataCodec {
+    /// Like [`Decoder::decode`] but with a [`Diagnostic`] on failure.
+    pub fn decode_detailed<'a>(
+        &self,
+        input: &'a [u8],
+    ) -> Result<(&'a [u8], AuthenticateData<'a>), DetailedDecodeError<AuthenticateDataDecodeError>>
+    {
+        #[cfg(feature = "std")]
+        let _context = ContextGuard::set(RecursionLimits::DEFAULT, self.quirks, None);
+
+        let result = match authenticate_data(input) {
+            Ok((rem, rsp)) => Ok((rem, rsp)),
+            Err(nom::Err::Incomplete(_)) => Err(AuthenticateDataDecodeError::Incomplete.into()),
+            Err(nom::Err::Failure(error)) | Err(nom::Err::Error(error)) => Err(
+                DetailedDecodeError::failed(AuthenticateDataDecodeError::Failed, input, &error),
+            ),
+        };
+
+        enforce_max_line_length(
+            input,
+            self.max_line_length,
+            result,
+            AuthenticateDataDecodeError::LineTooLong,
+        )
+    }
+}
+
+impl IdleDoneCodec {
+    /// Like [`Decoder::decode`] but with a [`Diagnostic`] on failure.
+    pub fn decode_detailed<'a>(
+        &self,
+        input: &'a [u8],
+    ) -> Result<(&'a [u8], IdleDone), DetailedDecodeError<IdleDoneDecodeError>> {
+        #[cfg(feature = "std")]
+        let _context = ContextGuard::set(RecursionLimits::DEFAULT, self.quirks, None);
+
+        let result = match idle_done(input) {
+            Ok((rem, rsp)) => Ok((rem, rsp)),
+            Err(nom::Err::Incomplete(_)) => Err(IdleDoneDecodeError::Incomplete.into()),
+            Err(nom::Err::Failure(error)) | Err(nom::Err::Error(error)) => Err(
+                DetailedDecodeError::failed(IdleDoneDecodeError::Failed, input, &error),
+            ),
+        };
+
+        enforce_max_line_length(
+            input,
+            self.max_line_length,
+            result,
+            IdleDoneDecodeError::LineTooLong,
+        )
+    }
+}
+
 // -------------------------------------------------------------------------------------------------
 
 impl Decoder for GreetingCodec {
@@ -294,11 +1242,7 @@ impl Decoder for GreetingCodec {
         &self,
         input: &'a [u8],
     ) -> Result<(&'a [u8], Self::Message<'a>), Self::Error<'static>> {
-        match greeting(input) {
-            Ok((rem, grt)) => Ok((rem, grt)),
-            Err(nom::Err::Incomplete(_)) => Err(GreetingDecodeError::Incomplete),
-            Err(nom::Err::Failure(_)) | Err(nom::Err::Error(_)) => Err(GreetingDecodeError::Failed),
-        }
+        self.decode_detailed(input).map_err(|error| error.error)
     }
 }
 
@@ -310,23 +1254,7 @@ impl Decoder for CommandCodec {
         &self,
         input: &'a [u8],
     ) -> Result<(&'a [u8], Self::Message<'a>), Self::Error<'a>> {
-        match command(input) {
-            Ok((rem, cmd)) => Ok((rem, cmd)),
-            Err(nom::Err::Incomplete(_)) => Err(CommandDecodeError::Incomplete),
-            Err(nom::Err::Failure(error)) => match error {
-                IMAPParseError {
-                    input: _,
-                    kind: IMAPErrorKind::Literal { tag, length, mode },
-                } => Err(CommandDecodeError::LiteralFound {
-                    // Unwrap: We *must* receive a `tag` during command parsing.
-                    tag: tag.expect("Expected `Some(tag)` in `IMAPErrorKind::Literal`, got `None`"),
-                    length,
-                    mode,
-                }),
-                _ => Err(CommandDecodeError::Failed),
-            },
-            Err(nom::Err::Error(_)) => Err(CommandDecodeError::Failed),
-        }
+        self.decode_detailed(input).map_err(|error| error.error)
     }
 }
 
@@ -338,17 +1266,7 @@ impl Decoder for ResponseCodec {
         &self,
         input: &'a [u8],
     ) -> Result<(&'a [u8], Self::Message<'a>), Self::Error<'static>> {
-        match response(input) {
-            Ok((rem, rsp)) => Ok((rem, rsp)),
-            Err(nom::Err::Incomplete(_)) => Err(ResponseDecodeError::Incomplete),
-            Err(nom::Err::Error(error) | nom::Err::Failure(error)) => match error {
-                IMAPParseError {
-                    kind: IMAPErrorKind::Literal { length, .. },
-                    ..
-                } => Err(ResponseDecodeError::LiteralFound { length }),
-                _ => Err(ResponseDecodeError::Failed),
-            },
-        }
+        self.decode_detailed(input).map_err(|error| error.error)
     }
 }
 
@@ -360,13 +1278,7 @@ impl Decoder for AuthenticateDataCodec {
         &self,
         input: &'a [u8],
     ) -> Result<(&'a [u8], Self::Message<'a>), Self::Error<'static>> {
-        match authenticate_data(input) {
-            Ok((rem, rsp)) => Ok((rem, rsp)),
-            Err(nom::Err::Incomplete(_)) => Err(AuthenticateDataDecodeError::Incomplete),
-            Err(nom::Err::Failure(_)) | Err(nom::Err::Error(_)) => {
-                Err(AuthenticateDataDecodeError::Failed)
-            }
-        }
+        self.decode_detailed(input).map_err(|error| error.error)
     }
 }
 
@@ -378,11 +1290,30 @@ impl Decoder for IdleDoneCodec {
         &self,
         input: &'a [u8],
     ) -> Result<(&'a [u8], Self::Message<'a>), Self::Error<'static>> {
-        match idle_done(input) {
-            Ok((rem, rsp)) => Ok((rem, rsp)),
-            Err(nom::Err::Incomplete(_)) => Err(IdleDoneDecodeError::Incomplete),
-            Err(nom::Err::Failure(_)) | Err(nom::Err::Error(_)) => Err(IdleDoneDecodeError::Failed),
+        self.decode_detailed(input).map_err(|error| error.error)
+    }
+}
+
+impl Decoder for LiteralCodec {
+    type Message<'a> = &'a [u8];
+    type Error<'a> = LiteralDecodeError;
+
+    fn decode<'a>(
+        &self,
+        input: &'a [u8],
+    ) -> Result<(&'a [u8], Self::Message<'a>), Self::Error<'static>> {
+        // Note: A `u32` always fits into a `usize` on supported platforms.
+        let length = self.length as usize;
+
+        if input.len() < length {
+            return Err(LiteralDecodeError::Incomplete {
+                missing: (length - input.len()) as u32,
+            });
         }
+
+        let (literal, remaining) = input.split_at(length);
+
+        Ok((remaining, literal))
     }
 }
 
@@ -397,9 +1328,11 @@ mod tests {
         fetch::MessageDataItem,
         mailbox::Mailbox,
         response::{Data, Greeting, GreetingKind, Response},
+        sequence::SeqNo,
     };
 
     use super::*;
+    use crate::LiteralCodec;
 
     #[test]
     fn test_decode_greeting() {
@@ -474,7 +1407,6 @@ mod tests {
                         "a",
                         CommandBody::Select {
                             mailbox: Mailbox::Inbox,
-                            #[cfg(feature = "ext_condstore_qresync")]
                             parameters: Vec::default(),
                         },
                     )
@@ -489,7 +1421,6 @@ mod tests {
                         "a",
                         CommandBody::Select {
                             mailbox: Mailbox::Inbox,
-                            #[cfg(feature = "ext_condstore_qresync")]
                             parameters: Vec::default(),
                         },
                     )
@@ -680,7 +1611,7 @@ mod tests {
                 Ok((
                     b"".as_ref(),
                     Response::Data(Data::Search(
-                        vec![NonZeroU32::new(1).unwrap()],
+                        vec![NonZeroU32::new(1).unwrap()].into(),
                         #[cfg(feature = "ext_condstore_qresync")]
                         None,
                     )),
@@ -691,7 +1622,7 @@ mod tests {
                 Ok((
                     b"???".as_ref(),
                     Response::Data(Data::Search(
-                        vec![NonZeroU32::new(1).unwrap()],
+                        vec![NonZeroU32::new(1).unwrap()].into(),
                         #[cfg(feature = "ext_condstore_qresync")]
                         None,
                     )),
@@ -702,7 +1633,7 @@ mod tests {
                 Ok((
                     b"".as_ref(),
                     Response::Data(Data::Fetch {
-                        seq: NonZeroU32::new(1).unwrap(),
+                        seq: SeqNo::new(1).unwrap(),
                         items: Vec1::from(MessageDataItem::Rfc822(NString(Some(
                             IString::Literal(Literal::try_from(b"hello".as_ref()).unwrap()),
                         )))),
@@ -732,4 +1663,197 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_decode_detailed() {
+        let tests = [
+            (b"A1 NOOP X\r\n".as_ref(), 7),
+            (b"A1 FOO\r\n".as_ref(), 3),
+            (b"A1 SELECT\r\n".as_ref(), 3),
+        ];
+
+        for (test, offset) in tests {
+            let error = CommandCodec::default().decode_detailed(test).unwrap_err();
+            assert_eq!(error.error, CommandDecodeError::Failed);
+
+            let diagnostic = error.diagnostic.unwrap();
+            dbg!((std::str::from_utf8(test).unwrap(), &diagnostic));
+            assert_eq!(diagnostic.offset, offset);
+            assert!(!diagnostic.to_string().is_empty());
+        }
+
+        // No diagnostic when more data is needed.
+        assert_eq!(
+            ResponseCodec::default().decode_detailed(b"* 1 FETCH (RFC822 {5}\r\n"),
+            Err(DetailedDecodeError {
+                error: ResponseDecodeError::LiteralFound { length: 5 },
+                diagnostic: None,
+            })
+        );
+        assert_eq!(
+            GreetingCodec::default().decode_detailed(b"* OK"),
+            Err(DetailedDecodeError {
+                error: GreetingDecodeError::Incomplete,
+                diagnostic: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_lossy() {
+        let tests = [
+            (
+                b"* 1 EXISTS\r\n* FOO\r\n".as_ref(),
+                Ok((
+                    b"* FOO\r\n".as_ref(),
+                    LossyResponse::Response(Response::Data(Data::Exists(1))),
+                )),
+            ),
+            (
+                b"* FOO bar\r\n* 1 EXISTS\r\n".as_ref(),
+                Ok((
+                    b"* 1 EXISTS\r\n".as_ref(),
+                    LossyResponse::Unknown(Cow::Borrowed(b"* FOO bar\r\n".as_ref())),
+                )),
+            ),
+            (
+                b"* 1 FETCH (X-FOO {5}\r\n* 2\r\n)\r\n* 3 EXISTS\r\n".as_ref(),
+                Ok((
+                    b"* 3 EXISTS\r\n".as_ref(),
+                    LossyResponse::Unknown(Cow::Borrowed(
+                        b"* 1 FETCH (X-FOO {5}\r\n* 2\r\n)\r\n".as_ref(),
+                    )),
+                )),
+            ),
+            (
+                b"* 1 FETCH (RFC822 {5}\r\n".as_ref(),
+                Err(ResponseDecodeError::LiteralFound { length: 5 }),
+            ),
+            (b"* FOO".as_ref(), Err(ResponseDecodeError::Incomplete)),
+            (
+                b"* FOO {5}\r\nhel".as_ref(),
+                Err(ResponseDecodeError::Incomplete),
+            ),
+        ];
+
+        for (test, expected) in tests {
+            let got = ResponseCodec::default().decode_lossy(test);
+            dbg!((std::str::from_utf8(test).unwrap(), &expected, &got));
+            assert_eq!(expected, got);
+        }
+    }
+
+    #[test]
+    fn test_decode_literal() {
+        let codec = LiteralCodec::new(5);
+
+        assert_eq!(
+            codec.decode(b""),
+            Err(LiteralDecodeError::Incomplete { missing: 5 })
+        );
+        assert_eq!(
+            codec.decode(b"hel"),
+            Err(LiteralDecodeError::Incomplete { missing: 2 })
+        );
+        assert_eq!(
+            codec.decode(b"hello"),
+            Ok((b"".as_ref(), b"hello".as_ref()))
+        );
+        assert_eq!(
+            codec.decode(b"hello)\r\n"),
+            Ok((b")\r\n".as_ref(), b"hello".as_ref()))
+        );
+        assert_eq!(
+            LiteralCodec::new(0).decode(b"\r\n"),
+            Ok((b"\r\n".as_ref(), b"".as_ref()))
+        );
+
+        // Literal data is consumed and the command parser takes over again.
+        let input = b"A1 LOGIN {5}\r\nalice {5}\r\nhello\r\n";
+        let announcement = b"A1 LOGIN {5}\r\n".len();
+        assert!(matches!(
+            CommandCodec::default().decode(input),
+            Ok((rem, _)) if rem.is_empty()
+        ));
+        let (remaining, literal) = codec.decode(&input[announcement..]).unwrap();
+        assert_eq!(literal, b"alice");
+        assert_eq!(remaining, b" {5}\r\nhello\r\n");
+    }
+
+    #[test]
+    fn test_extract_tag() {
+        let codec = CommandCodec::default();
+
+        for input in [
+            b"A1 FOO\r\n".as_ref(),
+            b"A1 LOGIN alice\r\n",
+            b"A1 NOOP NOOP\r\n",
+        ] {
+            assert_eq!(codec.decode(input), Err(CommandDecodeError::Failed));
+            assert_eq!(codec.extract_tag(input), Some(Tag::try_from("A1").unwrap()));
+        }
+
+        assert_eq!(codec.extract_tag(b"* FOO\r\n"), None);
+        assert_eq!(codec.extract_tag(b"A1\r\n"), None);
+    }
+
+    #[test]
+    fn test_decode_with_span() {
+        let input = b"* 1 EXISTS\r\n* 2 EXISTS\r\n";
+
+        let (remaining, response, consumed) =
+            ResponseCodec::default().decode_with_span(input).unwrap();
+        assert_eq!(response, Response::Data(Data::Exists(1)));
+        assert_eq!(consumed, b"* 1 EXISTS\r\n");
+        assert_eq!(remaining, b"* 2 EXISTS\r\n");
+
+        let (remaining, _, consumed) = ResponseCodec::default()
+            .decode_with_span(remaining)
+            .unwrap();
+        assert_eq!(consumed, b"* 2 EXISTS\r\n");
+        assert!(remaining.is_empty());
+
+        assert_eq!(
+            GreetingCodec::default().decode_with_span(b"* OK ..."),
+            Err(GreetingDecodeError::Incomplete)
+        );
+    }
+
+    #[test]
+    fn test_decode_many() {
+        let (responses, remaining, error) = ResponseCodec::default()
+            .decode_many(b"* 1 EXISTS\r\n* 2 EXISTS\r\n* 1 FETCH (RFC822 {5}\r\nhel");
+        assert_eq!(
+            responses,
+            vec![
+                Response::Data(Data::Exists(1)),
+                Response::Data(Data::Exists(2))
+            ]
+        );
+        assert_eq!(remaining, b"* 1 FETCH (RFC822 {5}\r\nhel");
+        assert_eq!(error, None);
+
+        let (responses, remaining, error) =
+            ResponseCodec::default().decode_many(b"* 1 EXISTS\r\n* FOO\r\n* 2 EXISTS\r\n");
+        assert_eq!(responses, vec![Response::Data(Data::Exists(1))]);
+        assert_eq!(remaining, b"* FOO\r\n* 2 EXISTS\r\n");
+        assert_eq!(error, Some(ResponseDecodeError::Failed));
+
+        let (commands, remaining, error) =
+            CommandCodec::default().decode_many(b"a NOOP\r\nb LOGIN {5}\r\n");
+        assert_eq!(
+            commands,
+            vec![Command::new("a", CommandBody::Noop).unwrap()]
+        );
+        assert_eq!(remaining, b"b LOGIN {5}\r\n");
+        assert!(matches!(
+            error,
+            Some(CommandDecodeError::LiteralFound { length: 5, .. })
+        ));
+
+        let (greetings, remaining, error) = GreetingCodec::default().decode_many(b"");
+        assert!(greetings.is_empty());
+        assert_eq!(remaining, b"");
+        assert_eq!(error, None);
+    }
 }
@@ -11,6 +11,7 @@
 //!     imap_types::{
 //!         command::{Command, CommandBody},
 //!         core::LiteralMode,
+//!         utils::escape_byte_string,
 //!     },
 //!     CommandCodec,
 //! };
@@ -21,7 +22,7 @@
 //!     match fragment {
 //!         Fragment::Line { data } => {
 //!             // A line that is ready to be send.
-//!             println!("C: {}", String::from_utf8(data).unwrap());
+//!             println!("C: {}", escape_byte_string(data));
 //!         }
 //!         Fragment::Literal { data, mode } => match mode {
 //!             LiteralMode::Sync => {
@@ -45,21 +46,29 @@
 //! C: Pa²²W0rD
 //! ```
 
-#[cfg(feature = "ext_condstore_qresync")]
-use std::num::NonZeroU64;
-use std::{borrow::Borrow, collections::VecDeque, io::Write, num::NonZeroU32};
+use alloc::{borrow::Borrow, collections::VecDeque, string::String, vec::Vec};
+use core::{
+    convert::Infallible,
+    fmt::{self, Debug},
+    num::NonZeroU32,
+};
+#[cfg(feature = "std")]
+use std::{
+    io::{IoSlice, Read, Seek, SeekFrom, Write},
+    sync::{Arc, Mutex, PoisonError},
+};
 
 use base64::{engine::general_purpose::STANDARD as base64, Engine};
 use chrono::{DateTime as ChronoDateTime, FixedOffset};
-#[cfg(feature = "ext_condstore_qresync")]
-use imap_types::command::{FetchModifier, SelectParameter, StoreModifier};
+#[cfg(feature = "std")]
+use imap_types::core::GenerateTag;
 use imap_types::{
     auth::{AuthMechanism, AuthenticateData},
     body::{
         BasicFields, Body, BodyExtension, BodyStructure, Disposition, Language, Location,
         MultiPartExtensionData, SinglePartExtensionData, SpecificFields,
     },
-    command::{Command, CommandBody},
+    command::{Command, CommandBody, SelectParameter, SelectParameterOther},
     core::{
         AString, Atom, AtomExt, Charset, IString, Literal, LiteralMode, NString, NString8, Quoted,
         QuotedChar, Tag, Text,
@@ -77,13 +86,22 @@ use imap_types::{
         Response, Status, StatusBody, StatusKind, Tagged,
     },
     search::SearchKey,
-    sequence::{SeqOrUid, Sequence, SequenceSet},
-    status::{StatusDataItem, StatusDataItemName},
-    utils::escape_quoted,
+    sequence::{SeqNo, SeqOrUid, Sequence, SequenceSet, Uid},
+    status::{StatusDataItem, StatusDataItemName, StatusDataItemNameOther},
+    utils::{escape_byte_string, escape_quoted, indicators::is_text_char},
+};
+#[cfg(feature = "ext_condstore_qresync")]
+use imap_types::{
+    command::{FetchModifier, StoreModifier},
+    extensions::condstore_qresync::ModSeq,
 };
 use utils::{join_serializable, List1AttributeValueOrNil, List1OrNil};
 
-use crate::{AuthenticateDataCodec, CommandCodec, GreetingCodec, IdleDoneCodec, ResponseCodec};
+use crate::{
+    decode::Preserved,
+    fragmentizer::{FragmentInfo, Fragmentizer},
+    AuthenticateDataCodec, CommandCodec, GreetingCodec, IdleDoneCodec, ResponseCodec,
+};
 
 /// Encoder.
 ///
@@ -95,6 +113,93 @@ pub trait Encoder {
     ///
     /// This will return an [`Encoded`] message.
     fn encode(&self, message: &Self::Message<'_>) -> Encoded;
+
+    /// Encode this message directly into `writer`.
+    ///
+    /// Unlike [`Encoder::encode`], no intermediate [`Fragment`]s are created. Thus, the message is
+    /// written at once, i.e., including all literals. This is useful, e.g., for servers that write
+    /// responses into a pooled output buffer or socket. Don't use this method when the message
+    /// flow needs to be interrupted, e.g., for a command with a synchronizing literal.
+    fn encode_to<W: Writer>(
+        &self,
+        message: &Self::Message<'_>,
+        writer: &mut W,
+    ) -> Result<(), W::Error> {
+        writer.write_all(&self.encode(message).dump())
+    }
+
+    /// Compute the number of bytes this message is encoded to.
+    ///
+    /// The message is not serialized into a buffer. This is useful, e.g., to pre-size buffers or
+    /// to enforce size limits before sending a message.
+    fn encoded_len(&self, message: &Self::Message<'_>) -> usize {
+        let mut counter = Counter(0);
+        // Unwrap: `Counter` never fails.
+        self.encode_to(message, &mut counter).unwrap();
+        counter.0
+    }
+}
+
+/// Destination of encoded bytes, see [`Encoder::encode_to`].
+///
+/// This is implemented for `Vec<u8>`. With the `std` feature, every [`std::io::Write`] can be
+/// used via [`IoWriter`].
+pub trait Writer {
+    type Error;
+
+    /// Write the whole `buf`.
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error>;
+}
+
+impl Writer for Vec<u8> {
+    type Error = Infallible;
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.extend_from_slice(buf);
+        Ok(())
+    }
+}
+
+/// Adapter from a [`std::io::Write`] to a [`Writer`].
+///
+/// ```rust
+/// use imap_codec::{
+///     encode::{Encoder, IoWriter},
+///     imap_types::command::{Command, CommandBody},
+///     CommandCodec,
+/// };
+///
+/// let cmd = Command::new("A", CommandBody::Noop).unwrap();
+///
+/// let mut writer = IoWriter(std::io::Cursor::new(Vec::new()));
+/// CommandCodec::default().encode_to(&cmd, &mut writer).unwrap();
+///
+/// assert_eq!(writer.0.into_inner(), b"A NOOP\r\n");
+/// ```
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IoWriter<W>(pub W);
+
+#[cfg(feature = "std")]
+impl<W: Write> Writer for IoWriter<W> {
+    type Error = std::io::Error;
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.0.write_all(buf)
+    }
+}
+
+/// Writer that only counts bytes.
+pub(crate) struct Counter(pub(crate) usize);
+
+impl Writer for Counter {
+    type Error = Infallible;
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.0 += buf.len();
+        Ok(())
+    }
 }
 
 /// An encoded message.
@@ -123,28 +228,257 @@ pub trait Encoder {
 /// }
 /// ```
 #[derive(Clone, Debug)]
-pub struct Encoded {
-    items: VecDeque<Fragment>,
+pub struct Encoded<D = Vec<u8>> {
+    items: VecDeque<Fragment<D>>,
+}
+
+impl<D> Encoded<D> {
+    /// Iterate over the (remaining) [`Fragment`]s without consuming them.
+    pub fn fragments(&self) -> alloc::collections::vec_deque::Iter<'_, Fragment<D>> {
+        self.items.iter()
+    }
 }
 
 impl Encoded {
     /// Dump the (remaining) encoded data without being guided by [`Fragment`]s.
     pub fn dump(self) -> Vec<u8> {
         let mut out = Vec::new();
+        self.dump_into(&mut out);
+        out
+    }
 
+    /// Expose the (remaining) encoded data as [`IoSlice`]s without being guided by [`Fragment`]s.
+    ///
+    /// The slices can be passed to [`Write::write_vectored`] to avoid copying lines and literals
+    /// into a contiguous buffer.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn as_io_slices(&self) -> Vec<IoSlice<'_>> {
+        self.items
+            .iter()
+            .map(|fragment| match fragment {
+                Fragment::Line { data } | Fragment::Literal { data, .. } => IoSlice::new(data),
+            })
+            .collect()
+    }
+
+    /// Dump the (remaining) encoded data as a printable string.
+    ///
+    /// Control characters, quotes, backslashes, and non-ASCII bytes are escaped (see
+    /// [`escape_byte_string`]). Unlike `String::from_utf8(dump()).unwrap()`, this never panics.
+    /// The result is not a valid IMAP message.
+    ///
+    /// **Warning:** Nothing is redacted, i.e., the result contains `LOGIN` passwords,
+    /// `AUTHENTICATE` data, and literals in cleartext. Don't log it. Use the (redacted) `Display`
+    /// implementations of the messages instead.
+    ///
+    /// ```rust
+    /// use imap_codec::{
+    ///     encode::Encoder,
+    ///     imap_types::{
+    ///         command::{Command, CommandBody},
+    ///         core::Literal,
+    ///     },
+    ///     CommandCodec,
+    /// };
+    ///
+    /// let message = Literal::try_from(b"Gr\xc3\xbc\xc3\x9fe".as_ref()).unwrap();
+    /// let command = Command::new(
+    ///     "A1",
+    ///     CommandBody::append("INBOX", vec![], None, message).unwrap(),
+    /// )
+    /// .unwrap();
+    /// let encoded = CommandCodec::default().encode(&command);
+    ///
+    /// assert_eq!(
+    ///     encoded.dump_lossy(),
+    ///     r"A1 APPEND INBOX {7}\r\nGr\xc3\xbc\xc3\x9fe\r\n"
+    /// );
+    /// ```
+    pub fn dump_lossy(self) -> String {
+        escape_byte_string(self.dump())
+    }
+
+    /// Append the (remaining) encoded data to `out` without being guided by [`Fragment`]s.
+    ///
+    /// Other than [`Encoded::dump`], this allows to reuse an output buffer, e.g., per connection.
+    pub fn dump_into(self, out: &mut Vec<u8>) {
         for fragment in self.items {
             match fragment {
-                Fragment::Line { mut data } => out.append(&mut data),
-                Fragment::Literal { mut data, .. } => out.append(&mut data),
+                Fragment::Line { data } => out.extend_from_slice(&data),
+                Fragment::Literal { data, .. } => out.extend_from_slice(&data),
             }
         }
+    }
 
-        out
+    /// Replace the payload of the first empty literal with `length` bytes read from `reader`.
+    ///
+    /// This allows to send large literals, e.g., in an `APPEND`, without loading them into memory.
+    /// Encode the message with an empty literal as a placeholder and pass the actual data here.
+    /// The literal announcement is changed to `length` accordingly. `reader` can be anything that
+    /// yields the data, e.g., a [`Read`](std::io::Read), an `AsyncRead`, or an iterator of chunks.
+    ///
+    /// Returns `None` if the message doesn't contain an empty literal.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imap_codec::{
+    ///     encode::{Encoder, Fragment, LiteralPayload},
+    ///     imap_types::{
+    ///         command::{Command, CommandBody},
+    ///         core::Literal,
+    ///     },
+    ///     CommandCodec,
+    /// };
+    ///
+    /// // Use, e.g., a `File` here.
+    /// let reader: &[u8] = b"Subject: Hello\r\n\r\n...";
+    ///
+    /// let placeholder = Literal::try_from("").unwrap();
+    /// let command = Command::new(
+    ///     "A1",
+    ///     CommandBody::append("INBOX", vec![], None, placeholder).unwrap(),
+    /// )
+    /// .unwrap();
+    ///
+    /// let encoded = CommandCodec::default()
+    ///     .encode(&command)
+    ///     .with_literal_reader(reader.len() as u32, reader)
+    ///     .unwrap();
+    ///
+    /// let mut out = Vec::new();
+    ///
+    /// for fragment in encoded {
+    ///     match fragment {
+    ///         Fragment::Line { data } => out.extend_from_slice(&data),
+    ///         Fragment::Literal { data, .. } => {
+    ///             // Wait for a continuation request (if required) ...
+    ///             data.write_to(&mut out).unwrap();
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(
+    ///     out,
+    ///     b"A1 APPEND INBOX {21}\r\nSubject: Hello\r\n\r\n...\r\n"
+    /// );
+    /// ```
+    pub fn with_literal_reader<R>(
+        self,
+        length: u32,
+        reader: R,
+    ) -> Option<Encoded<LiteralPayload<R>>> {
+        let mut reader = Some(reader);
+        let mut items = VecDeque::with_capacity(self.items.len());
+
+        for fragment in self.items {
+            match fragment {
+                Fragment::Line { data } => items.push_back(Fragment::Line { data }),
+                Fragment::Literal { data, mode } if data.is_empty() && reader.is_some() => {
+                    match items.back_mut() {
+                        Some(Fragment::Line { data }) => announce_length(data, length)?,
+                        _ => return None,
+                    }
+
+                    items.push_back(Fragment::Literal {
+                        data: LiteralPayload::Reader {
+                            reader: reader.take()?,
+                            length,
+                        },
+                        mode,
+                    });
+                }
+                Fragment::Literal { data, mode } => items.push_back(Fragment::Literal {
+                    data: LiteralPayload::Bytes(data),
+                    mode,
+                }),
+            }
+        }
+
+        match reader {
+            Some(_) => None,
+            None => Some(Encoded { items }),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl Encoded {
+    /// Replace the payload of the first empty literal with the remaining data of `reader`.
+    ///
+    /// Other than [`Encoded::with_literal_reader`], the length is determined by seeking to the end
+    /// of `reader` (and back), so a message on disk can be referenced via a [`File`](std::fs::File).
+    ///
+    /// Returns `Ok(None)` if the message doesn't contain an empty literal. Fails with
+    /// [`ErrorKind::InvalidInput`](std::io::ErrorKind::InvalidInput) when the remaining data doesn't
+    /// fit into a literal, i.e., exceeds `u32::MAX` bytes.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use std::fs::File;
+    ///
+    /// use imap_codec::{
+    ///     encode::Encoder,
+    ///     imap_types::{
+    ///         command::{Command, CommandBody},
+    ///         core::Literal,
+    ///     },
+    ///     CommandCodec,
+    /// };
+    ///
+    /// let placeholder = Literal::try_from("").unwrap();
+    /// let command = Command::new(
+    ///     "A1",
+    ///     CommandBody::append("INBOX", vec![], None, placeholder).unwrap(),
+    /// )
+    /// .unwrap();
+    ///
+    /// let encoded = CommandCodec::default()
+    ///     .encode(&command)
+    ///     .with_literal_file(File::open("message.eml").unwrap())
+    ///     .unwrap()
+    ///     .unwrap();
+    /// ```
+    pub fn with_literal_file<R>(
+        self,
+        mut reader: R,
+    ) -> std::io::Result<Option<Encoded<LiteralPayload<R>>>>
+    where
+        R: Read + Seek,
+    {
+        let start = reader.stream_position()?;
+        let end = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(start))?;
+
+        let length = u32::try_from(end.saturating_sub(start)).map_err(|_| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "literal too large")
+        })?;
+
+        Ok(self.with_literal_reader(length, reader))
     }
 }
 
-impl Iterator for Encoded {
-    type Item = Fragment;
+/// Replace the length of an empty literal announced at the end of `line` with `length`.
+fn announce_length(line: &mut Vec<u8>, length: u32) -> Option<()> {
+    let position = line.iter().rposition(|byte| *byte == b'{')?;
+
+    if !line[position..].starts_with(b"{0}") && !line[position..].starts_with(b"{0+}") {
+        return None;
+    }
+
+    let tail = line.split_off(position + 2);
+    line.truncate(position + 1);
+    line.extend_from_slice(utils::format_number(u64::from(length), &mut [0; 20]));
+    line.extend_from_slice(&tail);
+
+    Some(())
+}
+
+impl<D> Iterator for Encoded<D> {
+    type Item = Fragment<D>;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.items.pop_front()
@@ -153,36 +487,276 @@ impl Iterator for Encoded {
 
 /// The intended action of a client or server.
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub enum Fragment {
+pub enum Fragment<D = Vec<u8>> {
     /// A line that is ready to be send.
     Line { data: Vec<u8> },
 
     /// A literal that may require an action before it should be send.
-    Literal { data: Vec<u8>, mode: LiteralMode },
+    Literal { data: D, mode: LiteralMode },
+}
+
+impl Fragment {
+    /// Number of bytes of this fragment.
+    pub fn len(&self) -> usize {
+        match self {
+            Fragment::Line { data } | Fragment::Literal { data, .. } => data.len(),
+        }
+    }
+
+    /// Check if this fragment is empty, e.g., an empty literal.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Payload of a literal, see [`Encoded::with_literal_reader`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LiteralPayload<R> {
+    /// Data that is held in memory.
+    Bytes(Vec<u8>),
+    /// Data that is read from `reader`.
+    Reader {
+        reader: R,
+        /// Number of bytes that must be read.
+        length: u32,
+    },
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl<R: Read> LiteralPayload<R> {
+    /// Write the payload into `writer`.
+    ///
+    /// Fails with [`ErrorKind::UnexpectedEof`](std::io::ErrorKind::UnexpectedEof) when the reader
+    /// yields less than `length` bytes.
+    pub fn write_to<W: Write>(self, writer: &mut W) -> std::io::Result<()> {
+        match self {
+            LiteralPayload::Bytes(data) => writer.write_all(&data),
+            LiteralPayload::Reader { reader, length } => {
+                let copied = std::io::copy(&mut reader.take(u64::from(length)), writer)?;
+
+                if copied != u64::from(length) {
+                    return Err(std::io::ErrorKind::UnexpectedEof.into());
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl CommandCodec {
+    /// Encode a command with a tag from the installed tag generator.
+    ///
+    /// Returns the generated tag together with the encoded command, or `None` if no tag generator
+    /// was installed via [`CommandCodec::tag_generator`].
+    pub fn encode_body(&self, body: &CommandBody<'_>) -> Option<(Tag<'static>, Encoded)> {
+        let tag = self.tag_generator.as_ref()?.generate();
+
+        let mut encode_context = EncodeContext::new().options(self.encode_options);
+        tag.encode_ctx(&mut encode_context).unwrap();
+        encode_context.write_all(b" ").unwrap();
+        body.encode_ctx(&mut encode_context).unwrap();
+        encode_context.write_all(b"\r\n").unwrap();
+
+        Some((
+            tag,
+            Encoded {
+                items: encode_context.into_items(),
+            },
+        ))
+    }
+}
+
+impl<'a, M> Preserved<'a, M> {
+    /// Encode the message.
+    ///
+    /// When the message wasn't modified, the original bytes are returned (split into
+    /// [`Fragment`]s). Otherwise, the message is encoded using `codec`.
+    pub fn encode<C>(&self, codec: &C) -> Encoded
+    where
+        C: Encoder<Message<'a> = M>,
+    {
+        if self.is_modified() {
+            return codec.encode(&self.message);
+        }
+
+        let mut fragmentizer = Fragmentizer::without_max_message_size();
+        fragmentizer.enqueue_bytes(self.raw);
+
+        let mut items = VecDeque::new();
+        let mut mode = LiteralMode::Sync;
+
+        while let Some(fragment_info) = fragmentizer.progress() {
+            let data = fragmentizer.fragment_bytes(fragment_info).to_vec();
+
+            match fragment_info {
+                FragmentInfo::Line { announcement, .. } => {
+                    if let Some(announcement) = announcement {
+                        mode = announcement.mode;
+                    }
+
+                    items.push_back(Fragment::Line { data });
+                }
+                FragmentInfo::Literal { .. } => items.push_back(Fragment::Literal { data, mode }),
+            }
+
+            if fragmentizer.is_message_complete() {
+                break;
+            }
+        }
+
+        Encoded { items }
+    }
+}
+
+/// Tag generator that can be installed on a [`CommandCodec`].
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub(crate) struct SharedTagGenerator(Arc<Mutex<dyn GenerateTag + Send>>);
+
+#[cfg(feature = "std")]
+impl SharedTagGenerator {
+    pub(crate) fn new<G>(generator: G) -> Self
+    where
+        G: GenerateTag + Send + 'static,
+    {
+        Self(Arc::new(Mutex::new(generator)))
+    }
+
+    fn generate(&self) -> Tag<'static> {
+        // A panic in the generator doesn't leave it in an inconsistent state (from our side).
+        let mut generator = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        generator.generate()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Debug for SharedTagGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SharedTagGenerator")
+    }
+}
+
+#[cfg(feature = "std")]
+impl PartialEq for SharedTagGenerator {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
 }
 
 //--------------------------------------------------------------------------------------------------
 
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub(crate) struct EncodeContext {
+/// Options that change how messages are encoded, see the codecs' builder methods.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) struct EncodeOptions {
+    /// Encode literals up to this length as quoted strings when possible.
+    pub(crate) prefer_quoted: Option<usize>,
+    /// Encode all literals using this mode.
+    pub(crate) literal_mode: Option<LiteralMode>,
+    /// Allow UTF-8 in quoted strings (`UTF8=ACCEPT`).
+    pub(crate) utf8: bool,
+}
+
+/// Error of an [`EncodeContext`] whose writer failed.
+///
+/// The error of the writer itself is kept by the [`Sink`].
+#[derive(Debug)]
+pub(crate) struct WriteError;
+
+/// Adapter from a [`Writer`] to an [`ErasedWriter`] that keeps the (typed) error of the writer.
+pub(crate) struct Sink<'w, W: Writer> {
+    writer: &'w mut W,
+    error: Option<W::Error>,
+}
+
+impl<'w, W: Writer> Sink<'w, W> {
+    pub fn new(writer: &'w mut W) -> Self {
+        Self {
+            writer,
+            error: None,
+        }
+    }
+
+    /// Take the error of the writer after encoding failed with a [`WriteError`].
+    pub fn into_error(self) -> W::Error {
+        // Unwrap: A `WriteError` is only returned when the writer failed.
+        self.error.unwrap()
+    }
+}
+
+/// Object-safe version of [`Writer`] used by the [`EncodeContext`].
+pub(crate) trait ErasedWriter {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), WriteError>;
+}
+
+impl<W: Writer> ErasedWriter for Sink<'_, W> {
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), WriteError> {
+        self.writer.write_all(buf).map_err(|error| {
+            self.error = Some(error);
+            WriteError
+        })
+    }
+}
+
+pub(crate) struct EncodeContext<'a> {
     accumulator: Vec<u8>,
     items: VecDeque<Fragment>,
+    /// When set, bytes are written directly into the writer (and no fragments are created).
+    writer: Option<&'a mut dyn ErasedWriter>,
+    options: EncodeOptions,
 }
 
-impl EncodeContext {
+impl EncodeContext<'static> {
     pub fn new() -> Self {
-        Self::default()
+        Self {
+            accumulator: Vec::new(),
+            items: VecDeque::new(),
+            writer: None,
+            options: EncodeOptions::default(),
+        }
+    }
+}
+
+impl<'a> EncodeContext<'a> {
+    pub fn with_writer(writer: &'a mut dyn ErasedWriter) -> Self {
+        Self {
+            accumulator: Vec::new(),
+            items: VecDeque::new(),
+            writer: Some(writer),
+            options: EncodeOptions::default(),
+        }
+    }
+
+    pub fn options(mut self, options: EncodeOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Mode to use for a literal with the given `mode`.
+    pub fn literal_mode(&self, mode: LiteralMode) -> LiteralMode {
+        self.options.literal_mode.unwrap_or(mode)
     }
 
     pub fn push_line(&mut self) {
+        if self.writer.is_some() {
+            return;
+        }
+
         self.items.push_back(Fragment::Line {
-            data: std::mem::take(&mut self.accumulator),
+            data: core::mem::take(&mut self.accumulator),
         })
     }
 
     pub fn push_literal(&mut self, mode: LiteralMode) {
+        if self.writer.is_some() {
+            return;
+        }
+
         self.items.push_back(Fragment::Literal {
-            data: std::mem::take(&mut self.accumulator),
+            data: core::mem::take(&mut self.accumulator),
             mode,
         })
     }
@@ -191,6 +765,7 @@ impl EncodeContext {
         let Self {
             accumulator,
             mut items,
+            ..
         } = self;
 
         if !accumulator.is_empty() {
@@ -216,64 +791,144 @@ impl EncodeContext {
     }
 }
 
-impl Write for EncodeContext {
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        self.accumulator.extend_from_slice(buf);
-        Ok(buf.len())
+impl EncodeContext<'_> {
+    pub fn write_all(&mut self, buf: &[u8]) -> Result<(), WriteError> {
+        match &mut self.writer {
+            Some(writer) => writer.write_all(buf),
+            None => {
+                self.accumulator.extend_from_slice(buf);
+                Ok(())
+            }
+        }
     }
 
-    fn flush(&mut self) -> std::io::Result<()> {
-        Ok(())
+    /// Write `number` in decimal without going through [`fmt`].
+    pub fn write_number(&mut self, number: u64) -> Result<(), WriteError> {
+        self.write_all(utils::format_number(number, &mut [0; 20]))
+    }
+
+    /// Support for `write!(ctx, ...)`.
+    pub fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> Result<(), WriteError> {
+        struct Adapter<'c, 'a> {
+            ctx: &'c mut EncodeContext<'a>,
+            failed: bool,
+        }
+
+        impl fmt::Write for Adapter<'_, '_> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.ctx.write_all(s.as_bytes()).map_err(|_| {
+                    self.failed = true;
+                    fmt::Error
+                })
+            }
+        }
+
+        let mut adapter = Adapter {
+            ctx: self,
+            failed: false,
+        };
+
+        match fmt::write(&mut adapter, args) {
+            Ok(()) => Ok(()),
+            Err(_) if adapter.failed => Err(WriteError),
+            Err(_) => panic!("a formatting trait implementation returned an error"),
+        }
     }
 }
 
 macro_rules! impl_encoder_for_codec {
     ($codec:ty, $message:ty) => {
+        impl_encoder_for_codec!($codec, $message, |_| EncodeOptions::default());
+    };
+    ($codec:ty, $message:ty, $options:expr) => {
         impl Encoder for $codec {
             type Message<'a> = $message;
 
             fn encode(&self, message: &Self::Message<'_>) -> Encoded {
-                let mut encode_context = EncodeContext::new();
+                let options: fn(&Self) -> EncodeOptions = $options;
+                let mut encode_context = EncodeContext::new().options(options(self));
                 EncodeIntoContext::encode_ctx(message.borrow(), &mut encode_context).unwrap();
 
                 Encoded {
                     items: encode_context.into_items(),
                 }
             }
+
+            fn encode_to<W: Writer>(
+                &self,
+                message: &Self::Message<'_>,
+                writer: &mut W,
+            ) -> Result<(), W::Error> {
+                let options: fn(&Self) -> EncodeOptions = $options;
+                let mut sink = Sink::new(writer);
+                let mut encode_context =
+                    EncodeContext::with_writer(&mut sink).options(options(self));
+
+                match EncodeIntoContext::encode_ctx(message.borrow(), &mut encode_context) {
+                    Ok(()) => Ok(()),
+                    Err(WriteError) => Err(sink.into_error()),
+                }
+            }
         }
     };
 }
 
 impl_encoder_for_codec!(GreetingCodec, Greeting<'a>);
-impl_encoder_for_codec!(CommandCodec, Command<'a>);
+impl_encoder_for_codec!(CommandCodec, Command<'a>, |codec| codec.encode_options);
 impl_encoder_for_codec!(AuthenticateDataCodec, AuthenticateData<'a>);
-impl_encoder_for_codec!(ResponseCodec, Response<'a>);
+impl_encoder_for_codec!(ResponseCodec, Response<'a>, |codec| codec.encode_options);
 impl_encoder_for_codec!(IdleDoneCodec, IdleDone);
 
+/// Lossy rendering of messages, e.g., for debugging.
+///
+/// The message is encoded using the default codec and rendered with [`Encoded::dump_lossy`].
+///
+/// **Warning:** Nothing is redacted (see [`Encoded::dump_lossy`]). Don't log the result.
+pub trait ToWireLossy {
+    /// Encode the message and render it as a printable string.
+    fn to_wire_lossy(&self) -> String;
+}
+
+macro_rules! impl_to_wire_lossy {
+    ($codec:ty, $message:ty) => {
+        impl ToWireLossy for $message {
+            fn to_wire_lossy(&self) -> String {
+                <$codec>::default().encode(self).dump_lossy()
+            }
+        }
+    };
+}
+
+impl_to_wire_lossy!(GreetingCodec, Greeting<'_>);
+impl_to_wire_lossy!(CommandCodec, Command<'_>);
+impl_to_wire_lossy!(AuthenticateDataCodec, AuthenticateData<'_>);
+impl_to_wire_lossy!(ResponseCodec, Response<'_>);
+impl_to_wire_lossy!(IdleDoneCodec, IdleDone);
+
 // -------------------------------------------------------------------------------------------------
 
 pub(crate) trait EncodeIntoContext {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()>;
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError>;
 }
 
 // ----- Primitive ---------------------------------------------------------------------------------
 
 impl EncodeIntoContext for u32 {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
-        ctx.write_all(self.to_string().as_bytes())
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        ctx.write_number(u64::from(*self))
     }
 }
 
 impl EncodeIntoContext for u64 {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
-        ctx.write_all(self.to_string().as_bytes())
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        ctx.write_number(*self)
     }
 }
 
 // ----- Command -----------------------------------------------------------------------------------
 
 impl EncodeIntoContext for Command<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         self.tag.encode_ctx(ctx)?;
         ctx.write_all(b" ")?;
         self.body.encode_ctx(ctx)?;
@@ -282,13 +937,13 @@ impl EncodeIntoContext for Command<'_> {
 }
 
 impl EncodeIntoContext for Tag<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         ctx.write_all(self.inner().as_bytes())
     }
 }
 
 impl EncodeIntoContext for CommandBody<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             CommandBody::Capability => ctx.write_all(b"CAPABILITY"),
             CommandBody::Noop => ctx.write_all(b"NOOP"),
@@ -327,14 +982,12 @@ impl EncodeIntoContext for CommandBody<'_> {
             }
             CommandBody::Select {
                 mailbox,
-                #[cfg(feature = "ext_condstore_qresync")]
                 parameters,
             } => {
                 ctx.write_all(b"SELECT")?;
                 ctx.write_all(b" ")?;
                 mailbox.encode_ctx(ctx)?;
 
-                #[cfg(feature = "ext_condstore_qresync")]
                 if !parameters.is_empty() {
                     ctx.write_all(b" (")?;
                     join_serializable(parameters, b" ", ctx)?;
@@ -346,14 +999,12 @@ impl EncodeIntoContext for CommandBody<'_> {
             CommandBody::Unselect => ctx.write_all(b"UNSELECT"),
             CommandBody::Examine {
                 mailbox,
-                #[cfg(feature = "ext_condstore_qresync")]
                 parameters,
             } => {
                 ctx.write_all(b"EXAMINE")?;
                 ctx.write_all(b" ")?;
                 mailbox.encode_ctx(ctx)?;
 
-                #[cfg(feature = "ext_condstore_qresync")]
                 if !parameters.is_empty() {
                     ctx.write_all(b" (")?;
                     join_serializable(parameters, b" ", ctx)?;
@@ -474,16 +1125,23 @@ impl EncodeIntoContext for CommandBody<'_> {
                 join_serializable(criteria.as_ref(), b" ", ctx)
             }
             CommandBody::Sort {
+                return_options,
                 sort_criteria,
                 charset,
                 search_criteria,
                 uid,
             } => {
                 if *uid {
-                    ctx.write_all(b"UID SORT (")?;
+                    ctx.write_all(b"UID SORT ")?;
                 } else {
-                    ctx.write_all(b"SORT (")?;
+                    ctx.write_all(b"SORT ")?;
+                }
+                if !return_options.is_empty() {
+                    ctx.write_all(b"RETURN (")?;
+                    join_serializable(return_options, b" ", ctx)?;
+                    ctx.write_all(b") ")?;
                 }
+                ctx.write_all(b"(")?;
                 join_serializable(sort_criteria.as_ref(), b" ", ctx)?;
                 ctx.write_all(b") ")?;
                 charset.encode_ctx(ctx)?;
@@ -700,34 +1358,57 @@ impl EncodeIntoContext for CommandBody<'_> {
                     ctx.write_all(b")")
                 }
             }
+            #[cfg(feature = "ext_xapplepushservice")]
+            CommandBody::XApplePushService { parameters } => {
+                ctx.write_all(b"XAPPLEPUSHSERVICE ")?;
+                join_serializable(parameters.as_ref(), b" ", ctx)
+            }
+            #[cfg(feature = "ext_xlist")]
+            CommandBody::XList {
+                reference,
+                mailbox_wildcard,
+            } => {
+                ctx.write_all(b"XLIST")?;
+                ctx.write_all(b" ")?;
+                reference.encode_ctx(ctx)?;
+                ctx.write_all(b" ")?;
+                mailbox_wildcard.encode_ctx(ctx)
+            }
         }
     }
 }
 
 #[cfg(feature = "ext_condstore_qresync")]
 impl EncodeIntoContext for FetchModifier {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
-            FetchModifier::ChangedSince(since) => write!(ctx, "CHANGEDSINCE {since}"),
-            FetchModifier::Vanished => write!(ctx, "VANISHED"),
+            FetchModifier::ChangedSince(since) => {
+                ctx.write_all(b"CHANGEDSINCE ")?;
+                since.encode_ctx(ctx)
+            }
+            FetchModifier::Vanished => ctx.write_all(b"VANISHED"),
         }
     }
 }
 
 #[cfg(feature = "ext_condstore_qresync")]
 impl EncodeIntoContext for StoreModifier {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
-            StoreModifier::UnchangedSince(since) => write!(ctx, "UNCHANGEDSINCE {since}"),
+            StoreModifier::UnchangedSince(since) => {
+                ctx.write_all(b"UNCHANGEDSINCE ")?;
+                encode_mod_sequence_valzer(*since, ctx)
+            }
         }
     }
 }
 
-#[cfg(feature = "ext_condstore_qresync")]
-impl EncodeIntoContext for SelectParameter {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+impl EncodeIntoContext for SelectParameter<'_> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
+            #[cfg(feature = "ext_condstore_qresync")]
             SelectParameter::CondStore => write!(ctx, "CONDSTORE"),
+            #[cfg(feature = "ext_condstore_qresync")]
             SelectParameter::QResync {
                 uid_validity,
                 mod_sequence_value,
@@ -754,18 +1435,25 @@ impl EncodeIntoContext for SelectParameter {
 
                 write!(ctx, ")")
             }
+            SelectParameter::Other(item) => item.encode_ctx(ctx),
         }
     }
 }
 
+impl EncodeIntoContext for SelectParameterOther<'_> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        self.inner().encode_ctx(ctx)
+    }
+}
+
 impl EncodeIntoContext for AuthMechanism<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         write!(ctx, "{}", self)
     }
 }
 
 impl EncodeIntoContext for AuthenticateData<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             Self::Continue(data) => {
                 let encoded = base64.encode(data.declassify());
@@ -778,7 +1466,7 @@ impl EncodeIntoContext for AuthenticateData<'_> {
 }
 
 impl EncodeIntoContext for AString<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             AString::Atom(atom) => atom.encode_ctx(ctx),
             AString::String(imap_str) => imap_str.encode_ctx(ctx),
@@ -787,49 +1475,81 @@ impl EncodeIntoContext for AString<'_> {
 }
 
 impl EncodeIntoContext for Atom<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         ctx.write_all(self.inner().as_bytes())
     }
 }
 
 impl EncodeIntoContext for AtomExt<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         ctx.write_all(self.inner().as_bytes())
     }
 }
 
 impl EncodeIntoContext for IString<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
-            Self::Literal(val) => val.encode_ctx(ctx),
+            Self::Literal(val) => {
+                let data = val.as_ref();
+
+                if matches!(ctx.options.prefer_quoted, Some(max_length) if data.len() <= max_length)
+                {
+                    if let Ok(quoted) = Quoted::try_from(data) {
+                        return quoted.encode_ctx(ctx);
+                    }
+                }
+
+                // With `UTF8=ACCEPT`, a literal that is only required because of UTF-8 is quoted.
+                if ctx.options.utf8
+                    && !matches!(ctx.options.prefer_quoted, Some(max_length) if data.len() > max_length)
+                {
+                    if let Ok(text) = core::str::from_utf8(data) {
+                        if !text.is_ascii()
+                            && text.bytes().all(|b| !b.is_ascii() || is_text_char(b))
+                        {
+                            ctx.write_all(b"\"")?;
+                            ctx.write_all(escape_quoted(text).as_bytes())?;
+                            return ctx.write_all(b"\"");
+                        }
+                    }
+                }
+
+                val.encode_ctx(ctx)
+            }
             Self::Quoted(val) => val.encode_ctx(ctx),
         }
     }
 }
 
 impl EncodeIntoContext for Literal<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
-        match self.mode() {
-            LiteralMode::Sync => write!(ctx, "{{{}}}\r\n", self.as_ref().len())?,
-            LiteralMode::NonSync => write!(ctx, "{{{}+}}\r\n", self.as_ref().len())?,
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        let mode = ctx.literal_mode(self.mode());
+
+        ctx.write_all(b"{")?;
+        ctx.write_number(self.as_ref().len() as u64)?;
+        match mode {
+            LiteralMode::Sync => ctx.write_all(b"}\r\n")?,
+            LiteralMode::NonSync => ctx.write_all(b"+}\r\n")?,
         }
 
         ctx.push_line();
         ctx.write_all(self.as_ref())?;
-        ctx.push_literal(self.mode());
+        ctx.push_literal(mode);
 
         Ok(())
     }
 }
 
 impl EncodeIntoContext for Quoted<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
-        write!(ctx, "\"{}\"", escape_quoted(self.inner()))
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        ctx.write_all(b"\"")?;
+        ctx.write_all(escape_quoted(self.inner()).as_bytes())?;
+        ctx.write_all(b"\"")
     }
 }
 
 impl EncodeIntoContext for Mailbox<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             Mailbox::Inbox => ctx.write_all(b"INBOX"),
             Mailbox::Other(other) => other.encode_ctx(ctx),
@@ -838,13 +1558,13 @@ impl EncodeIntoContext for Mailbox<'_> {
 }
 
 impl EncodeIntoContext for MailboxOther<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         self.inner().encode_ctx(ctx)
     }
 }
 
 impl EncodeIntoContext for ListMailbox<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             ListMailbox::Token(lcs) => lcs.encode_ctx(ctx),
             ListMailbox::String(istr) => istr.encode_ctx(ctx),
@@ -853,13 +1573,13 @@ impl EncodeIntoContext for ListMailbox<'_> {
 }
 
 impl EncodeIntoContext for ListCharString<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         ctx.write_all(self.as_ref())
     }
 }
 
-impl EncodeIntoContext for StatusDataItemName {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+impl EncodeIntoContext for StatusDataItemName<'_> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             Self::Messages => ctx.write_all(b"MESSAGES"),
             Self::Recent => ctx.write_all(b"RECENT"),
@@ -870,18 +1590,33 @@ impl EncodeIntoContext for StatusDataItemName {
             Self::DeletedStorage => ctx.write_all(b"DELETED-STORAGE"),
             #[cfg(feature = "ext_condstore_qresync")]
             Self::HighestModSeq => ctx.write_all(b"HIGHESTMODSEQ"),
+            Self::Other(name) => name.encode_ctx(ctx),
         }
     }
 }
 
+impl EncodeIntoContext for StatusDataItemNameOther<'_> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        self.inner().encode_ctx(ctx)
+    }
+}
+
 impl EncodeIntoContext for Flag<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
-        write!(ctx, "{}", self)
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        match self {
+            Flag::Answered => ctx.write_all(b"\\Answered"),
+            Flag::Deleted => ctx.write_all(b"\\Deleted"),
+            Flag::Draft => ctx.write_all(b"\\Draft"),
+            Flag::Flagged => ctx.write_all(b"\\Flagged"),
+            Flag::Seen => ctx.write_all(b"\\Seen"),
+            Flag::Extension(_) => write!(ctx, "{}", self),
+            Flag::Keyword(atom) => atom.encode_ctx(ctx),
+        }
     }
 }
 
 impl EncodeIntoContext for FlagFetch<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             Self::Flag(flag) => flag.encode_ctx(ctx),
             Self::Recent => ctx.write_all(b"\\Recent"),
@@ -890,7 +1625,7 @@ impl EncodeIntoContext for FlagFetch<'_> {
 }
 
 impl EncodeIntoContext for FlagPerm<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             Self::Flag(flag) => flag.encode_ctx(ctx),
             Self::Asterisk => ctx.write_all(b"\\*"),
@@ -899,13 +1634,13 @@ impl EncodeIntoContext for FlagPerm<'_> {
 }
 
 impl EncodeIntoContext for DateTime {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         self.as_ref().encode_ctx(ctx)
     }
 }
 
 impl EncodeIntoContext for Charset<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             Charset::Atom(atom) => atom.encode_ctx(ctx),
             Charset::Quoted(quoted) => quoted.encode_ctx(ctx),
@@ -914,7 +1649,7 @@ impl EncodeIntoContext for Charset<'_> {
 }
 
 impl EncodeIntoContext for SearchKey<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             SearchKey::All => ctx.write_all(b"ALL"),
             SearchKey::Answered => ctx.write_all(b"ANSWERED"),
@@ -983,7 +1718,10 @@ impl EncodeIntoContext for SearchKey<'_> {
                 ctx.write_all(b" ")?;
                 astring.encode_ctx(ctx)
             }
-            SearchKey::Larger(number) => write!(ctx, "LARGER {number}"),
+            SearchKey::Larger(number) => {
+                ctx.write_all(b"LARGER ")?;
+                number.encode_ctx(ctx)
+            }
             SearchKey::Not(search_key) => {
                 ctx.write_all(b"NOT ")?;
                 search_key.encode_ctx(ctx)
@@ -1006,7 +1744,10 @@ impl EncodeIntoContext for SearchKey<'_> {
                 ctx.write_all(b"SENTSINCE ")?;
                 date.encode_ctx(ctx)
             }
-            SearchKey::Smaller(number) => write!(ctx, "SMALLER {number}"),
+            SearchKey::Smaller(number) => {
+                ctx.write_all(b"SMALLER ")?;
+                number.encode_ctx(ctx)
+            }
             SearchKey::Uid(sequence_set) => {
                 ctx.write_all(b"UID ")?;
                 sequence_set.encode_ctx(ctx)
@@ -1020,7 +1761,7 @@ impl EncodeIntoContext for SearchKey<'_> {
                     write!(ctx, " {entry_type_req}")?;
                 }
                 ctx.write_all(b" ")?;
-                modseq.encode_ctx(ctx)
+                encode_mod_sequence_valzer(*modseq, ctx)
             }
             SearchKey::SequenceSet(sequence_set) => sequence_set.encode_ctx(ctx),
             SearchKey::And(search_keys) => {
@@ -1033,13 +1774,13 @@ impl EncodeIntoContext for SearchKey<'_> {
 }
 
 impl EncodeIntoContext for SequenceSet {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         join_serializable(self.0.as_ref(), b",", ctx)
     }
 }
 
 impl EncodeIntoContext for Sequence {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             Sequence::Single(seq_no) => seq_no.encode_ctx(ctx),
             Sequence::Range(from, to) => {
@@ -1052,22 +1793,22 @@ impl EncodeIntoContext for Sequence {
 }
 
 impl EncodeIntoContext for SeqOrUid {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
-            SeqOrUid::Value(number) => write!(ctx, "{number}"),
+            SeqOrUid::Value(number) => number.encode_ctx(ctx),
             SeqOrUid::Asterisk => ctx.write_all(b"*"),
         }
     }
 }
 
 impl EncodeIntoContext for NaiveDate {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         write!(ctx, "\"{}\"", self.as_ref().format("%d-%b-%Y"))
     }
 }
 
 impl EncodeIntoContext for MacroOrMessageDataItemNames<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             Self::Macro(m) => m.encode_ctx(ctx),
             Self::MessageDataItemNames(item_names) => {
@@ -1084,13 +1825,13 @@ impl EncodeIntoContext for MacroOrMessageDataItemNames<'_> {
 }
 
 impl EncodeIntoContext for Macro {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         write!(ctx, "{}", self)
     }
 }
 
 impl EncodeIntoContext for MessageDataItemName<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             Self::Body => ctx.write_all(b"BODY"),
             Self::BodyExt {
@@ -1108,7 +1849,11 @@ impl EncodeIntoContext for MessageDataItemName<'_> {
                 }
                 ctx.write_all(b"]")?;
                 if let Some((a, b)) = partial {
-                    write!(ctx, "<{a}.{b}>")?;
+                    ctx.write_all(b"<")?;
+                    a.encode_ctx(ctx)?;
+                    ctx.write_all(b".")?;
+                    b.encode_ctx(ctx)?;
+                    ctx.write_all(b">")?;
                 }
 
                 Ok(())
@@ -1155,12 +1900,18 @@ impl EncodeIntoContext for MessageDataItemName<'_> {
             }
             #[cfg(feature = "ext_condstore_qresync")]
             MessageDataItemName::ModSeq => ctx.write_all(b"MODSEQ"),
+            #[cfg(feature = "ext_gmail")]
+            MessageDataItemName::GmailMessageId => ctx.write_all(b"X-GM-MSGID"),
+            #[cfg(feature = "ext_gmail")]
+            MessageDataItemName::GmailThreadId => ctx.write_all(b"X-GM-THRID"),
+            #[cfg(feature = "ext_gmail")]
+            MessageDataItemName::GmailLabels => ctx.write_all(b"X-GM-LABELS"),
         }
     }
 }
 
 impl EncodeIntoContext for Section<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             Section::Part(part) => part.encode_ctx(ctx),
             Section::Header(maybe_part) => match maybe_part {
@@ -1208,26 +1959,47 @@ impl EncodeIntoContext for Section<'_> {
 }
 
 impl EncodeIntoContext for Part {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         join_serializable(self.0.as_ref(), b".", ctx)
     }
 }
 
 impl EncodeIntoContext for NonZeroU32 {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
-        write!(ctx, "{self}")
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        ctx.write_number(u64::from(self.get()))
+    }
+}
+
+impl EncodeIntoContext for SeqNo {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        ctx.write_number(u64::from(self.get()))
+    }
+}
+
+impl EncodeIntoContext for Uid {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        ctx.write_number(u64::from(self.get()))
     }
 }
 
 #[cfg(feature = "ext_condstore_qresync")]
-impl EncodeIntoContext for NonZeroU64 {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
-        write!(ctx, "{self}")
+impl EncodeIntoContext for ModSeq {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        ctx.write_number(self.get())
     }
 }
 
+/// Encode a `mod-sequence-valzer`, i.e., `None` as `0`.
+#[cfg(feature = "ext_condstore_qresync")]
+fn encode_mod_sequence_valzer(
+    value: Option<ModSeq>,
+    ctx: &mut EncodeContext,
+) -> Result<(), WriteError> {
+    ctx.write_number(value.map_or(0, ModSeq::get))
+}
+
 impl EncodeIntoContext for Capability<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         write!(ctx, "{}", self)
     }
 }
@@ -1235,7 +2007,7 @@ impl EncodeIntoContext for Capability<'_> {
 // ----- Responses ---------------------------------------------------------------------------------
 
 impl EncodeIntoContext for Response<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             Response::Status(status) => status.encode_ctx(ctx),
             Response::Data(data) => data.encode_ctx(ctx),
@@ -1247,7 +2019,7 @@ impl EncodeIntoContext for Response<'_> {
 }
 
 impl EncodeIntoContext for Greeting<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         ctx.write_all(b"* ")?;
         self.kind.encode_ctx(ctx)?;
         ctx.write_all(b" ")?;
@@ -1264,7 +2036,7 @@ impl EncodeIntoContext for Greeting<'_> {
 }
 
 impl EncodeIntoContext for GreetingKind {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             GreetingKind::Ok => ctx.write_all(b"OK"),
             GreetingKind::PreAuth => ctx.write_all(b"PREAUTH"),
@@ -1274,14 +2046,14 @@ impl EncodeIntoContext for GreetingKind {
 }
 
 impl EncodeIntoContext for Status<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         fn format_status(
             tag: Option<&Tag>,
             status: &str,
             code: &Option<Code>,
             comment: &Text,
             ctx: &mut EncodeContext,
-        ) -> std::io::Result<()> {
+        ) -> Result<(), WriteError> {
             match tag {
                 Some(tag) => tag.encode_ctx(ctx)?,
                 None => ctx.write_all(b"*")?,
@@ -1318,7 +2090,7 @@ impl EncodeIntoContext for Status<'_> {
 }
 
 impl EncodeIntoContext for Code<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             Code::Alert => ctx.write_all(b"ALERT"),
             Code::BadCharset { allowed } => {
@@ -1410,19 +2182,19 @@ impl EncodeIntoContext for Code<'_> {
 }
 
 impl EncodeIntoContext for CodeOther<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         ctx.write_all(self.inner())
     }
 }
 
 impl EncodeIntoContext for Text<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         ctx.write_all(self.inner().as_bytes())
     }
 }
 
 impl EncodeIntoContext for Data<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             Data::Capability(caps) => {
                 ctx.write_all(b"* CAPABILITY ")?;
@@ -1476,21 +2248,21 @@ impl EncodeIntoContext for Data<'_> {
             // TODO: Exclude pattern via cfg?
             #[cfg(not(feature = "ext_condstore_qresync"))]
             Data::Search(seqs) => {
-                if seqs.is_empty() {
-                    ctx.write_all(b"* SEARCH")?;
-                } else {
-                    ctx.write_all(b"* SEARCH ")?;
-                    join_serializable(seqs, b" ", ctx)?;
+                // Numbers are written from their runs, i.e., without materializing them.
+                ctx.write_all(b"* SEARCH")?;
+                for seq in seqs {
+                    ctx.write_all(b" ")?;
+                    seq.encode_ctx(ctx)?;
                 }
             }
             // TODO: Exclude pattern via cfg?
             #[cfg(feature = "ext_condstore_qresync")]
             Data::Search(seqs, modseq) => {
-                if seqs.is_empty() {
-                    ctx.write_all(b"* SEARCH")?;
-                } else {
-                    ctx.write_all(b"* SEARCH ")?;
-                    join_serializable(seqs, b" ", ctx)?;
+                // Numbers are written from their runs, i.e., without materializing them.
+                ctx.write_all(b"* SEARCH")?;
+                for seq in seqs {
+                    ctx.write_all(b" ")?;
+                    seq.encode_ctx(ctx)?;
                 }
 
                 if let Some(modseq) = modseq {
@@ -1525,6 +2297,21 @@ impl EncodeIntoContext for Data<'_> {
                     ctx.write_all(b")")?;
                 }
             }
+            Data::ESearch { tag, uid, data } => {
+                ctx.write_all(b"* ESEARCH")?;
+                if let Some(tag) = tag {
+                    ctx.write_all(b" (TAG \"")?;
+                    tag.encode_ctx(ctx)?;
+                    ctx.write_all(b"\")")?;
+                }
+                if *uid {
+                    ctx.write_all(b" UID")?;
+                }
+                for item in data {
+                    ctx.write_all(b" ")?;
+                    item.encode_ctx(ctx)?;
+                }
+            }
             Data::Thread(threads) => {
                 if threads.is_empty() {
                     ctx.write_all(b"* THREAD")?;
@@ -1540,11 +2327,25 @@ impl EncodeIntoContext for Data<'_> {
                 join_serializable(flags, b" ", ctx)?;
                 ctx.write_all(b")")?;
             }
-            Data::Exists(count) => write!(ctx, "* {count} EXISTS")?,
-            Data::Recent(count) => write!(ctx, "* {count} RECENT")?,
-            Data::Expunge(msg) => write!(ctx, "* {msg} EXPUNGE")?,
+            Data::Exists(count) => {
+                ctx.write_all(b"* ")?;
+                count.encode_ctx(ctx)?;
+                ctx.write_all(b" EXISTS")?;
+            }
+            Data::Recent(count) => {
+                ctx.write_all(b"* ")?;
+                count.encode_ctx(ctx)?;
+                ctx.write_all(b" RECENT")?;
+            }
+            Data::Expunge(msg) => {
+                ctx.write_all(b"* ")?;
+                msg.encode_ctx(ctx)?;
+                ctx.write_all(b" EXPUNGE")?;
+            }
             Data::Fetch { seq, items } => {
-                write!(ctx, "* {seq} FETCH (")?;
+                ctx.write_all(b"* ")?;
+                seq.encode_ctx(ctx)?;
+                ctx.write_all(b" FETCH (")?;
                 join_serializable(items.as_ref(), b" ", ctx)?;
                 ctx.write_all(b")")?;
             }
@@ -1627,6 +2428,31 @@ impl EncodeIntoContext for Data<'_> {
                 ctx.write_all(b" ")?;
                 known_uids.encode_ctx(ctx)?;
             }
+            #[cfg(feature = "ext_xapplepushservice")]
+            Data::XApplePushService { parameters } => {
+                ctx.write_all(b"* XAPPLEPUSHSERVICE ")?;
+                join_serializable(parameters.as_ref(), b" ", ctx)?;
+            }
+            #[cfg(feature = "ext_xlist")]
+            Data::XList {
+                items,
+                delimiter,
+                mailbox,
+            } => {
+                ctx.write_all(b"* XLIST (")?;
+                join_serializable(items, b" ", ctx)?;
+                ctx.write_all(b") ")?;
+
+                if let Some(delimiter) = delimiter {
+                    ctx.write_all(b"\"")?;
+                    delimiter.encode_ctx(ctx)?;
+                    ctx.write_all(b"\"")?;
+                } else {
+                    ctx.write_all(b"NIL")?;
+                }
+                ctx.write_all(b" ")?;
+                mailbox.encode_ctx(ctx)?;
+            }
         }
 
         ctx.write_all(b"\r\n")
@@ -1634,13 +2460,13 @@ impl EncodeIntoContext for Data<'_> {
 }
 
 impl EncodeIntoContext for FlagNameAttribute<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         write!(ctx, "{}", self)
     }
 }
 
 impl EncodeIntoContext for QuotedChar {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self.inner() {
             '\\' => ctx.write_all(b"\\\\"),
             '"' => ctx.write_all(b"\\\""),
@@ -1649,8 +2475,8 @@ impl EncodeIntoContext for QuotedChar {
     }
 }
 
-impl EncodeIntoContext for StatusDataItem {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+impl EncodeIntoContext for StatusDataItem<'_> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             Self::Messages(count) => {
                 ctx.write_all(b"MESSAGES ")?;
@@ -1683,14 +2509,22 @@ impl EncodeIntoContext for StatusDataItem {
             #[cfg(feature = "ext_condstore_qresync")]
             Self::HighestModSeq(value) => {
                 ctx.write_all(b"HIGHESTMODSEQ ")?;
-                value.encode_ctx(ctx)
+                encode_mod_sequence_valzer(*value, ctx)
+            }
+            Self::Other { name, value } => {
+                name.encode_ctx(ctx)?;
+                ctx.write_all(b" ")?;
+                match value {
+                    Some(value) => value.encode_ctx(ctx),
+                    None => ctx.write_all(b"NIL"),
+                }
             }
         }
     }
 }
 
 impl EncodeIntoContext for MessageDataItem<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             Self::BodyExt {
                 section,
@@ -1703,7 +2537,9 @@ impl EncodeIntoContext for MessageDataItem<'_> {
                 }
                 ctx.write_all(b"]")?;
                 if let Some(origin) = origin {
-                    write!(ctx, "<{origin}>")?;
+                    ctx.write_all(b"<")?;
+                    origin.encode_ctx(ctx)?;
+                    ctx.write_all(b">")?;
                 }
                 ctx.write_all(b" ")?;
                 data.encode_ctx(ctx)
@@ -1738,12 +2574,18 @@ impl EncodeIntoContext for MessageDataItem<'_> {
                 ctx.write_all(b"RFC822.HEADER ")?;
                 nstring.encode_ctx(ctx)
             }
-            Self::Rfc822Size(size) => write!(ctx, "RFC822.SIZE {size}"),
+            Self::Rfc822Size(size) => {
+                ctx.write_all(b"RFC822.SIZE ")?;
+                size.encode_ctx(ctx)
+            }
             Self::Rfc822Text(nstring) => {
                 ctx.write_all(b"RFC822.TEXT ")?;
                 nstring.encode_ctx(ctx)
             }
-            Self::Uid(uid) => write!(ctx, "UID {uid}"),
+            Self::Uid(uid) => {
+                ctx.write_all(b"UID ")?;
+                uid.encode_ctx(ctx)
+            }
             Self::Binary { section, value } => {
                 ctx.write_all(b"BINARY[")?;
                 join_serializable(section, b".", ctx)?;
@@ -1757,13 +2599,32 @@ impl EncodeIntoContext for MessageDataItem<'_> {
                 size.encode_ctx(ctx)
             }
             #[cfg(feature = "ext_condstore_qresync")]
-            Self::ModSeq(value) => write!(ctx, "MODSEQ {value}"),
+            Self::ModSeq(value) => {
+                ctx.write_all(b"MODSEQ ")?;
+                value.encode_ctx(ctx)
+            }
+            #[cfg(feature = "ext_gmail")]
+            Self::GmailMessageId(id) => {
+                ctx.write_all(b"X-GM-MSGID ")?;
+                id.encode_ctx(ctx)
+            }
+            #[cfg(feature = "ext_gmail")]
+            Self::GmailThreadId(id) => {
+                ctx.write_all(b"X-GM-THRID ")?;
+                id.encode_ctx(ctx)
+            }
+            #[cfg(feature = "ext_gmail")]
+            Self::GmailLabels(labels) => {
+                ctx.write_all(b"X-GM-LABELS (")?;
+                join_serializable(labels, b" ", ctx)?;
+                ctx.write_all(b")")
+            }
         }
     }
 }
 
 impl EncodeIntoContext for NString<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match &self.0 {
             Some(imap_str) => imap_str.encode_ctx(ctx),
             None => ctx.write_all(b"NIL"),
@@ -1772,7 +2633,7 @@ impl EncodeIntoContext for NString<'_> {
 }
 
 impl EncodeIntoContext for NString8<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             NString8::NString(nstring) => nstring.encode_ctx(ctx),
             NString8::Literal8(literal8) => literal8.encode_ctx(ctx),
@@ -1781,7 +2642,7 @@ impl EncodeIntoContext for NString8<'_> {
 }
 
 impl EncodeIntoContext for BodyStructure<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         ctx.write_all(b"(")?;
         match self {
             BodyStructure::Single {
@@ -1816,7 +2677,7 @@ impl EncodeIntoContext for BodyStructure<'_> {
 }
 
 impl EncodeIntoContext for Body<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self.specific {
             SpecificFields::Basic {
                 r#type: ref type_,
@@ -1840,7 +2701,7 @@ impl EncodeIntoContext for Body<'_> {
                 ctx.write_all(b" ")?;
                 body_structure.encode_ctx(ctx)?;
                 ctx.write_all(b" ")?;
-                write!(ctx, "{number_of_lines}")
+                number_of_lines.encode_ctx(ctx)
             }
             SpecificFields::Text {
                 ref subtype,
@@ -1851,14 +2712,14 @@ impl EncodeIntoContext for Body<'_> {
                 ctx.write_all(b" ")?;
                 self.basic.encode_ctx(ctx)?;
                 ctx.write_all(b" ")?;
-                write!(ctx, "{number_of_lines}")
+                number_of_lines.encode_ctx(ctx)
             }
         }
     }
 }
 
 impl EncodeIntoContext for BasicFields<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         List1AttributeValueOrNil(&self.parameter_list).encode_ctx(ctx)?;
         ctx.write_all(b" ")?;
         self.id.encode_ctx(ctx)?;
@@ -1867,12 +2728,12 @@ impl EncodeIntoContext for BasicFields<'_> {
         ctx.write_all(b" ")?;
         self.content_transfer_encoding.encode_ctx(ctx)?;
         ctx.write_all(b" ")?;
-        write!(ctx, "{}", self.size)
+        self.size.encode_ctx(ctx)
     }
 }
 
 impl EncodeIntoContext for Envelope<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         ctx.write_all(b"(")?;
         self.date.encode_ctx(ctx)?;
         ctx.write_all(b" ")?;
@@ -1898,7 +2759,7 @@ impl EncodeIntoContext for Envelope<'_> {
 }
 
 impl EncodeIntoContext for Address<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         ctx.write_all(b"(")?;
         self.name.encode_ctx(ctx)?;
         ctx.write_all(b" ")?;
@@ -1914,7 +2775,7 @@ impl EncodeIntoContext for Address<'_> {
 }
 
 impl EncodeIntoContext for SinglePartExtensionData<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         self.md5.encode_ctx(ctx)?;
 
         if let Some(disposition) = &self.tail {
@@ -1927,7 +2788,7 @@ impl EncodeIntoContext for SinglePartExtensionData<'_> {
 }
 
 impl EncodeIntoContext for MultiPartExtensionData<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         List1AttributeValueOrNil(&self.parameter_list).encode_ctx(ctx)?;
 
         if let Some(disposition) = &self.tail {
@@ -1940,7 +2801,7 @@ impl EncodeIntoContext for MultiPartExtensionData<'_> {
 }
 
 impl EncodeIntoContext for Disposition<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match &self.disposition {
             Some((s, param)) => {
                 ctx.write_all(b"(")?;
@@ -1962,7 +2823,7 @@ impl EncodeIntoContext for Disposition<'_> {
 }
 
 impl EncodeIntoContext for Language<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         List1OrNil(&self.language, b" ").encode_ctx(ctx)?;
 
         if let Some(location) = &self.tail {
@@ -1975,7 +2836,7 @@ impl EncodeIntoContext for Language<'_> {
 }
 
 impl EncodeIntoContext for Location<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         self.location.encode_ctx(ctx)?;
 
         for body_extension in &self.extensions {
@@ -1988,7 +2849,7 @@ impl EncodeIntoContext for Location<'_> {
 }
 
 impl EncodeIntoContext for BodyExtension<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             BodyExtension::NString(nstring) => nstring.encode_ctx(ctx),
             BodyExtension::Number(number) => number.encode_ctx(ctx),
@@ -2002,13 +2863,13 @@ impl EncodeIntoContext for BodyExtension<'_> {
 }
 
 impl EncodeIntoContext for ChronoDateTime<FixedOffset> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         write!(ctx, "\"{}\"", self.format("%d-%b-%Y %H:%M:%S %z"))
     }
 }
 
 impl EncodeIntoContext for CommandContinuationRequest<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             Self::Basic(continue_basic) => match continue_basic.code() {
                 Some(code) => {
@@ -2034,19 +2895,36 @@ impl EncodeIntoContext for CommandContinuationRequest<'_> {
 }
 
 pub(crate) mod utils {
-    use std::io::Write;
+    use alloc::vec::Vec;
 
-    use super::{EncodeContext, EncodeIntoContext};
+    use super::{EncodeContext, EncodeIntoContext, WriteError};
 
     pub struct List1OrNil<'a, T>(pub &'a Vec<T>, pub &'a [u8]);
 
     pub struct List1AttributeValueOrNil<'a, T>(pub &'a Vec<(T, T)>);
 
+    /// Format `number` in decimal into the end of `buffer`.
+    pub(crate) fn format_number(mut number: u64, buffer: &mut [u8; 20]) -> &[u8] {
+        let mut start = buffer.len();
+
+        loop {
+            start -= 1;
+            buffer[start] = b'0' + (number % 10) as u8;
+            number /= 10;
+
+            if number == 0 {
+                break;
+            }
+        }
+
+        &buffer[start..]
+    }
+
     pub(crate) fn join_serializable<I: EncodeIntoContext>(
         elements: &[I],
         sep: &[u8],
         ctx: &mut EncodeContext,
-    ) -> std::io::Result<()> {
+    ) -> Result<(), WriteError> {
         if let Some((last, head)) = elements.split_last() {
             for item in head {
                 item.encode_ctx(ctx)?;
@@ -2063,7 +2941,7 @@ pub(crate) mod utils {
     where
         T: EncodeIntoContext,
     {
-        fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
             if let Some((last, head)) = self.0.split_last() {
                 ctx.write_all(b"(")?;
 
@@ -2085,7 +2963,7 @@ pub(crate) mod utils {
     where
         T: EncodeIntoContext,
     {
-        fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+        fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
             if let Some((last, head)) = self.0.split_last() {
                 ctx.write_all(b"(")?;
 
@@ -2111,8 +2989,6 @@ pub(crate) mod utils {
 
 #[cfg(test)]
 mod tests {
-    use std::num::NonZeroU32;
-
     use imap_types::{
         auth::AuthMechanism,
         command::{Command, CommandBody},
@@ -2123,6 +2999,7 @@ mod tests {
     };
 
     use super::*;
+    use crate::decode::Decoder;
 
     #[test]
     fn test_api_encoder_usage() {
@@ -2165,6 +3042,459 @@ mod tests {
         assert_eq!(got_encoded, out);
     }
 
+    #[test]
+    fn test_encode_to() {
+        let rsp = Response::Data(
+            Data::fetch(
+                1,
+                vec![MessageDataItem::Rfc822(NString(Some(IString::Literal(
+                    Literal::try_from("hello").unwrap(),
+                ))))],
+            )
+            .unwrap(),
+        );
+
+        let mut out = b"* OK ...\r\n".to_vec();
+        ResponseCodec::default().encode_to(&rsp, &mut out).unwrap();
+        assert_eq!(out, b"* OK ...\r\n* 1 FETCH (RFC822 {5}\r\nhello)\r\n");
+
+        let cmd = Command::new("A", CommandBody::login("alice", "pass").unwrap()).unwrap();
+        let mut out = Vec::new();
+        CommandCodec::default().encode_to(&cmd, &mut out).unwrap();
+        assert_eq!(out, CommandCodec::default().encode(&cmd).dump());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_encode_to_io_writer() {
+        /// Sink that fails after a limited number of bytes.
+        struct Limited(usize);
+
+        impl Write for Limited {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                let count = buf.len().min(self.0);
+                if count == 0 {
+                    return Err(std::io::ErrorKind::WriteZero.into());
+                }
+
+                self.0 -= count;
+                Ok(count)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let cmd = Command::new("A", CommandBody::login("alice", "pass").unwrap()).unwrap();
+
+        let mut writer = IoWriter(Vec::new());
+        CommandCodec::default()
+            .encode_to(&cmd, &mut writer)
+            .unwrap();
+        assert_eq!(writer.0, b"A LOGIN alice pass\r\n");
+
+        let mut writer = IoWriter(Limited(7));
+        assert_eq!(
+            CommandCodec::default()
+                .encode_to(&cmd, &mut writer)
+                .unwrap_err()
+                .kind(),
+            std::io::ErrorKind::WriteZero
+        );
+    }
+
+    #[test]
+    fn test_format_number() {
+        for number in [0, 1, 9, 10, 42, 1000, u64::from(u32::MAX), u64::MAX] {
+            assert_eq!(
+                utils::format_number(number, &mut [0; 20]),
+                number.to_string().as_bytes()
+            );
+        }
+    }
+
+    #[test]
+    fn test_encode_to_custom_writer() {
+        /// Writer that accepts a limited number of bytes.
+        struct Limited(Vec<u8>, usize);
+
+        impl Writer for Limited {
+            type Error = usize;
+
+            fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+                if self.0.len() + buf.len() > self.1 {
+                    return Err(self.0.len());
+                }
+
+                self.0.extend_from_slice(buf);
+                Ok(())
+            }
+        }
+
+        let cmd = Command::new("A", CommandBody::login("alice", "pass").unwrap()).unwrap();
+
+        let mut writer = Limited(Vec::new(), 64);
+        CommandCodec::default()
+            .encode_to(&cmd, &mut writer)
+            .unwrap();
+        assert_eq!(writer.0, b"A LOGIN alice pass\r\n");
+
+        let mut writer = Limited(Vec::new(), 7);
+        assert_eq!(CommandCodec::default().encode_to(&cmd, &mut writer), Err(7));
+        assert_eq!(writer.0, b"A LOGIN");
+    }
+
+    #[test]
+    fn test_encoded_dump_into() {
+        let cmd = Command::new("A", CommandBody::login("alice", "Pa²²W0rD").unwrap()).unwrap();
+        let encoded = CommandCodec::default().encode(&cmd);
+
+        let mut out = Vec::new();
+        encoded.clone().dump_into(&mut out);
+        assert_eq!(out, encoded.clone().dump());
+
+        // Reuse buffer.
+        out.clear();
+        encoded.clone().dump_into(&mut out);
+        encoded.clone().dump_into(&mut out);
+        assert_eq!(
+            out,
+            [encoded.clone().dump(), encoded.clone().dump()].concat()
+        );
+
+        let fragments: Vec<_> = encoded.fragments().cloned().collect();
+        assert_eq!(fragments, encoded.collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_encoded_as_io_slices() {
+        let cmd = Command::new("A", CommandBody::login("alice", "Pa²²W0rD").unwrap()).unwrap();
+        let encoded = CommandCodec::default().encode(&cmd);
+
+        let slices = encoded.as_io_slices();
+        assert_eq!(slices.len(), 3);
+        assert_eq!(
+            slices
+                .iter()
+                .flat_map(|slice| slice.iter().copied())
+                .collect::<Vec<_>>(),
+            encoded.clone().dump()
+        );
+
+        let mut out = Vec::new();
+        let written = out.write_vectored(&slices).unwrap();
+        assert_eq!(written, out.len());
+        assert_eq!(out, encoded.dump());
+    }
+
+    #[test]
+    fn test_encode_prefer_quoted() {
+        let cmd = Command::new(
+            "A",
+            CommandBody::login(
+                Literal::try_from("alice").unwrap(),
+                Literal::try_from("Pa²²W0rD").unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let codec = CommandCodec::default().prefer_quoted(64);
+        assert_eq!(
+            codec.encode(&cmd).collect::<Vec<_>>(),
+            vec![
+                Fragment::Line {
+                    data: b"A LOGIN \"alice\" {10}\r\n".to_vec()
+                },
+                Fragment::Literal {
+                    data: "Pa²²W0rD".as_bytes().to_vec(),
+                    mode: LiteralMode::Sync
+                },
+                Fragment::Line {
+                    data: b"\r\n".to_vec()
+                },
+            ]
+        );
+
+        let mut out = Vec::new();
+        codec.encode_to(&cmd, &mut out).unwrap();
+        assert_eq!(out, codec.encode(&cmd).dump());
+
+        let codec = CommandCodec::default().prefer_quoted(3);
+        assert_eq!(
+            codec.encode(&cmd).next(),
+            Some(Fragment::Line {
+                data: b"A LOGIN {5}\r\n".to_vec()
+            })
+        );
+    }
+
+    #[test]
+    fn test_encode_literal_mode() {
+        let cmd = Command::new(
+            "A",
+            CommandBody::login(
+                Literal::unvalidated_non_sync(b"alice".as_ref()),
+                Literal::try_from("Pa²²W0rD").unwrap(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let tests = [
+            (
+                CommandCodec::default(),
+                b"A LOGIN {5+}\r\nalice {10}\r\nPa\xc2\xb2\xc2\xb2W0rD\r\n".as_ref(),
+            ),
+            (
+                CommandCodec::default().literal_mode(LiteralMode::NonSync),
+                b"A LOGIN {5+}\r\nalice {10+}\r\nPa\xc2\xb2\xc2\xb2W0rD\r\n".as_ref(),
+            ),
+            (
+                CommandCodec::default().literal_mode(LiteralMode::Sync),
+                b"A LOGIN {5}\r\nalice {10}\r\nPa\xc2\xb2\xc2\xb2W0rD\r\n".as_ref(),
+            ),
+        ];
+
+        for (codec, expected) in tests {
+            assert_eq!(codec.encode(&cmd).dump(), expected);
+        }
+
+        let modes: Vec<_> = CommandCodec::default()
+            .literal_mode(LiteralMode::NonSync)
+            .encode(&cmd)
+            .filter_map(|fragment| match fragment {
+                Fragment::Literal { mode, .. } => Some(mode),
+                Fragment::Line { .. } => None,
+            })
+            .collect();
+        assert_eq!(modes, [LiteralMode::NonSync, LiteralMode::NonSync]);
+    }
+
+    #[test]
+    fn test_encode_utf8() {
+        let cmd = Command::new("A", CommandBody::select("Entwürfe").unwrap()).unwrap();
+
+        assert_eq!(
+            CommandCodec::default().encode(&cmd).dump(),
+            b"A SELECT {9}\r\nEntw\xc3\xbcrfe\r\n"
+        );
+        assert_eq!(
+            CommandCodec::default().utf8(true).encode(&cmd).dump(),
+            b"A SELECT \"Entw\xc3\xbcrfe\"\r\n"
+        );
+        assert_eq!(
+            CommandCodec::default()
+                .utf8(true)
+                .prefer_quoted(4)
+                .encode(&cmd)
+                .dump(),
+            b"A SELECT {9}\r\nEntw\xc3\xbcrfe\r\n"
+        );
+
+        // Explicit literals without UTF-8 are kept.
+        let cmd = Command::new(
+            "A",
+            CommandBody::select(Mailbox::from(AString::from(
+                Literal::try_from("Drafts").unwrap(),
+            )))
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            CommandCodec::default().utf8(true).encode(&cmd).dump(),
+            b"A SELECT {6}\r\nDrafts\r\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_body() {
+        assert!(CommandCodec::default()
+            .encode_body(&CommandBody::Noop)
+            .is_none());
+
+        let mut counter = 0;
+        let codec = CommandCodec::default().tag_generator(move || {
+            counter += 1;
+            Tag::try_from(format!("A{counter}")).unwrap()
+        });
+
+        let (tag, encoded) = codec.encode_body(&CommandBody::Noop).unwrap();
+        assert_eq!(tag, Tag::try_from("A1").unwrap());
+        assert_eq!(encoded.dump(), b"A1 NOOP\r\n");
+
+        // Clones share the generator.
+        let (tag, encoded) = codec.clone().encode_body(&CommandBody::Capability).unwrap();
+        assert_eq!(tag, Tag::try_from("A2").unwrap());
+        assert_eq!(encoded.dump(), b"A2 CAPABILITY\r\n");
+    }
+
+    #[test]
+    fn test_encode_preserved() {
+        let codec = CommandCodec::default();
+        let input = b"a1 login alice {4+}\r\npass\r\n";
+
+        let (remaining, mut preserved) = codec.decode_preserved(input.as_ref()).unwrap();
+        assert!(remaining.is_empty());
+        assert_eq!(preserved.raw(), input);
+        assert!(!preserved.is_modified());
+        assert_eq!(
+            codec.encode(preserved.message()).dump(),
+            b"a1 LOGIN alice {4+}\r\npass\r\n"
+        );
+        assert_eq!(
+            preserved.encode(&codec).collect::<Vec<_>>(),
+            vec![
+                Fragment::Line {
+                    data: b"a1 login alice {4+}\r\n".to_vec()
+                },
+                Fragment::Literal {
+                    data: b"pass".to_vec(),
+                    mode: LiteralMode::NonSync
+                },
+                Fragment::Line {
+                    data: b"\r\n".to_vec()
+                },
+            ]
+        );
+
+        preserved.message_mut().body = CommandBody::Noop;
+        assert!(preserved.is_modified());
+        assert_eq!(preserved.encode(&codec).dump(), b"a1 NOOP\r\n");
+    }
+
+    #[test]
+    fn test_encoded_len() {
+        let cmd = Command::new("A", CommandBody::login("alice", "Pa²²W0rD").unwrap()).unwrap();
+        let codec = CommandCodec::default();
+
+        assert_eq!(codec.encoded_len(&cmd), codec.encode(&cmd).dump().len());
+        assert_eq!(
+            codec
+                .encode(&cmd)
+                .map(|fragment| fragment.len())
+                .collect::<Vec<_>>(),
+            vec![b"A LOGIN alice {10}\r\n".len(), 10, 2]
+        );
+
+        let rsp = Response::Data(Data::Exists(42));
+        assert_eq!(
+            ResponseCodec::default().encoded_len(&rsp),
+            b"* 42 EXISTS\r\n".len()
+        );
+    }
+
+    #[test]
+    fn test_to_wire_lossy() {
+        let rsp = Response::Data(
+            Data::fetch(
+                1,
+                vec![MessageDataItem::BodyExt {
+                    section: None,
+                    origin: None,
+                    data: NString::from(Literal::try_from(&b"\xff\r\n"[..]).unwrap()),
+                }],
+            )
+            .unwrap(),
+        );
+
+        assert_eq!(
+            rsp.to_wire_lossy(),
+            r"* 1 FETCH (BODY[] {3}\r\n\xff\r\n)\r\n"
+        );
+
+        let cmd = Command::new("A", CommandBody::Noop).unwrap();
+        assert_eq!(cmd.to_wire_lossy(), r"A NOOP\r\n");
+    }
+
+    #[test]
+    fn test_encoded_with_literal_reader() {
+        let cmd = Command::new(
+            "A",
+            CommandBody::append("INBOX", vec![], None, Literal::try_from("").unwrap()).unwrap(),
+        )
+        .unwrap();
+
+        let encoded = CommandCodec::default()
+            .encode(&cmd)
+            .with_literal_reader(5, b"hello".as_ref())
+            .unwrap();
+        let fragments: Vec<_> = encoded.collect();
+
+        assert_eq!(
+            fragments,
+            vec![
+                Fragment::Line {
+                    data: b"A APPEND INBOX {5}\r\n".to_vec()
+                },
+                Fragment::Literal {
+                    data: LiteralPayload::Reader {
+                        reader: b"hello".as_ref(),
+                        length: 5
+                    },
+                    mode: LiteralMode::Sync,
+                },
+                Fragment::Line {
+                    data: b"\r\n".to_vec()
+                },
+            ]
+        );
+
+        // The reader yields too few bytes.
+        let mut out = Vec::new();
+        let error = LiteralPayload::Reader {
+            reader: b"hell".as_ref(),
+            length: 5,
+        }
+        .write_to(&mut out)
+        .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+
+        // There is no placeholder.
+        let cmd = Command::new("A", CommandBody::login("alice", "password").unwrap()).unwrap();
+        assert!(CommandCodec::default()
+            .encode(&cmd)
+            .with_literal_reader(5, b"hello".as_ref())
+            .is_none());
+    }
+
+    #[test]
+    fn test_encoded_with_literal_file() {
+        let cmd = Command::new(
+            "A",
+            CommandBody::append("INBOX", vec![], None, Literal::try_from("").unwrap()).unwrap(),
+        )
+        .unwrap();
+
+        // Only the remaining data is used.
+        let mut reader = std::io::Cursor::new(b"xxhello".to_vec());
+        reader.seek(SeekFrom::Start(2)).unwrap();
+
+        let encoded = CommandCodec::default()
+            .encode(&cmd)
+            .with_literal_file(reader)
+            .unwrap()
+            .unwrap();
+
+        let mut out = Vec::new();
+        for fragment in encoded {
+            match fragment {
+                Fragment::Line { data } => out.extend_from_slice(&data),
+                Fragment::Literal { data, .. } => data.write_to(&mut out).unwrap(),
+            }
+        }
+        assert_eq!(out, b"A APPEND INBOX {5}\r\nhello\r\n");
+
+        // There is no placeholder.
+        let cmd = Command::new("A", CommandBody::login("alice", "password").unwrap()).unwrap();
+        assert!(CommandCodec::default()
+            .encode(&cmd)
+            .with_literal_file(std::io::Cursor::new(b"hello"))
+            .unwrap()
+            .is_none());
+    }
+
     #[test]
     fn test_encode_command() {
         kat_encoder::<CommandCodec, Command<'_>, &[Fragment]>(&[
@@ -2242,7 +3572,7 @@ mod tests {
         kat_encoder::<ResponseCodec, Response<'_>, &[Fragment]>(&[
             (
                 Response::Data(Data::Fetch {
-                    seq: NonZeroU32::new(12345).unwrap(),
+                    seq: SeqNo::new(12345).unwrap(),
                     items: Vec1::from(MessageDataItem::BodyExt {
                         section: None,
                         origin: None,
@@ -2265,7 +3595,7 @@ mod tests {
             ),
             (
                 Response::Data(Data::Fetch {
-                    seq: NonZeroU32::new(12345).unwrap(),
+                    seq: SeqNo::new(12345).unwrap(),
                     items: Vec1::from(MessageDataItem::BodyExt {
                         section: None,
                         origin: None,
@@ -0,0 +1,179 @@
+//! # Messages that own their input.
+//!
+//! Decoded messages borrow from the input. Sending a message to another task or thread thus
+//! requires [`IntoStatic::into_static`](imap_types::IntoStatic::into_static), which copies every
+//! borrowed value, i.e., is O(message size).
+//!
+//! A frozen message keeps the (shared) input alongside the message borrowing from it. Moving a
+//! frozen message is O(1) and accessing it doesn't require any copying.
+//!
+//! ## Example
+//!
+//! ```rust
+//! use imap_codec::{frozen::FrozenResponse, ResponseCodec};
+//!
+//! let codec = ResponseCodec::default();
+//! let frozen = FrozenResponse::decode(&codec, b"* 42 EXISTS\r\n".to_vec()).unwrap();
+//!
+//! std::thread::spawn(move || {
+//!     println!("{:?}", frozen.message());
+//! })
+//! .join()
+//! .unwrap();
+//! ```
+
+use alloc::sync::Arc;
+
+use imap_types::{
+    command::Command,
+    response::{Greeting, Response},
+    IntoStatic,
+};
+use self_cell::self_cell;
+
+use crate::{
+    decode::{CommandDecodeError, Decoder, GreetingDecodeError, ResponseDecodeError},
+    CommandCodec, GreetingCodec, ResponseCodec,
+};
+
+macro_rules! impl_frozen {
+    ($frozen:ident, $module:ident, $message:ident, $codec:ident, $error:ty) => {
+        // `self_cell!` generates methods we don't use.
+        #[allow(dead_code)]
+        mod $module {
+            use super::*;
+
+            self_cell!(
+                pub(super) struct Cell {
+                    owner: Arc<[u8]>,
+
+                    #[covariant]
+                    dependent: $message,
+                }
+
+                impl {Debug, PartialEq, Eq}
+            );
+        }
+
+        #[doc = concat!("A [`", stringify!($message), "`] that owns its input.")]
+        ///
+        /// See the [module documentation](self).
+        #[derive(Debug, PartialEq, Eq)]
+        pub struct $frozen {
+            cell: $module::Cell,
+            len: usize,
+        }
+
+        impl $frozen {
+            #[doc = concat!("Decode a [`", stringify!($message), "`] and keep `input` alongside it.")]
+            ///
+            /// Only the first message is decoded. Trailing bytes are ignored.
+            pub fn decode(codec: &$codec, input: impl Into<Arc<[u8]>>) -> Result<Self, $error> {
+                let mut len = 0;
+
+                let cell = $module::Cell::try_new(input.into(), |input| {
+                    codec
+                        .decode(input)
+                        .map(|(remaining, message)| {
+                            len = input.len() - remaining.len();
+                            message
+                        })
+                        .map_err(IntoStatic::into_static)
+                })?;
+
+                Ok(Self { cell, len })
+            }
+
+            /// Borrow the message.
+            pub fn message(&self) -> &$message<'_> {
+                self.cell.borrow_dependent()
+            }
+
+            /// Return the bytes the message was decoded from.
+            pub fn as_bytes(&self) -> &[u8] {
+                &self.cell.borrow_owner()[..self.len]
+            }
+
+            /// Return the (shared) input.
+            pub fn into_input(self) -> Arc<[u8]> {
+                self.cell.into_owner()
+            }
+        }
+    };
+}
+
+impl_frozen!(
+    FrozenGreeting,
+    greeting,
+    Greeting,
+    GreetingCodec,
+    GreetingDecodeError
+);
+impl_frozen!(
+    FrozenCommand,
+    command,
+    Command,
+    CommandCodec,
+    CommandDecodeError<'static>
+);
+impl_frozen!(
+    FrozenResponse,
+    response,
+    Response,
+    ResponseCodec,
+    ResponseDecodeError
+);
+
+#[cfg(test)]
+mod tests {
+    use imap_types::{
+        command::CommandBody,
+        core::{LiteralMode, Tag},
+        response::{Data, GreetingKind},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_frozen_greeting() {
+        let frozen =
+            FrozenGreeting::decode(&GreetingCodec::default(), b"* OK hello\r\n".to_vec()).unwrap();
+
+        assert_eq!(
+            frozen.message(),
+            &Greeting::new(GreetingKind::Ok, None, "hello").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_frozen_command() {
+        let input: Arc<[u8]> = Arc::from(&b"A1 SELECT inbox\r\nA2 NOOP\r\n"[..]);
+        let frozen = FrozenCommand::decode(&CommandCodec::default(), input.clone()).unwrap();
+
+        assert_eq!(
+            frozen.message(),
+            &Command::new("A1", CommandBody::select("inbox").unwrap()).unwrap()
+        );
+        assert_eq!(frozen.as_bytes(), b"A1 SELECT inbox\r\n");
+        assert!(Arc::ptr_eq(&frozen.into_input(), &input));
+
+        assert_eq!(
+            FrozenCommand::decode(&CommandCodec::default(), b"A1 SELECT {5}\r\n".to_vec()),
+            Err(CommandDecodeError::LiteralFound {
+                tag: Tag::try_from("A1").unwrap(),
+                length: 5,
+                mode: LiteralMode::Sync,
+            })
+        );
+    }
+
+    #[test]
+    fn test_frozen_response_is_send() {
+        let frozen =
+            FrozenResponse::decode(&ResponseCodec::default(), b"* 42 EXISTS\r\n".to_vec()).unwrap();
+
+        let frozen = std::thread::spawn(move || frozen).join().unwrap();
+
+        assert_eq!(frozen.message(), &Response::Data(Data::Exists(42)));
+    }
+}
@@ -0,0 +1,174 @@
+//! Async reading and writing of messages using futures' [`AsyncRead`] and [`AsyncWrite`].
+//!
+//! This mirrors the [`tokio`](https://docs.rs/tokio) integration for other runtimes, e.g.,
+//! async-std or smol, and only depends on `futures-io`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use imap_codec::{
+//!     futures::{AsyncReadDecoder, AsyncWriteEncoder, WriteEvent},
+//!     imap_types::command::{Command, CommandBody},
+//!     CommandCodec, GreetingCodec, ResponseCodec,
+//! };
+//!
+//! # futures::executor::block_on(async {
+//! // Use, e.g., the halves of a `TcpStream` here.
+//! let reader: &[u8] = b"* OK ...\r\n+ ...\r\nA1 OK ...\r\n";
+//! let writer = Vec::new();
+//!
+//! let mut decoder = AsyncReadDecoder::new(reader, GreetingCodec::new());
+//! let mut encoder = AsyncWriteEncoder::new(writer, CommandCodec::new());
+//!
+//! let greeting = decoder.read().await.unwrap();
+//! let mut decoder = decoder.with_codec(ResponseCodec::new());
+//!
+//! let command = Command::new("A1", CommandBody::login("alice", "Pa²²W0rD").unwrap()).unwrap();
+//!
+//! let mut event = encoder.write(&command).await.unwrap();
+//!
+//! while let WriteEvent::LiteralPending { .. } = event {
+//!     // Wait for the command continuation request ...
+//!     let continuation = decoder.read().await.unwrap();
+//!     // ... and send the literal.
+//!     event = encoder.continue_literal().await.unwrap();
+//! }
+//!
+//! let ok = decoder.read().await.unwrap();
+//! # });
+//! ```
+
+use std::{future::poll_fn, io, pin::Pin};
+
+use futures_io::{AsyncRead, AsyncWrite};
+
+use crate::stream::impl_async_adapters;
+pub use crate::stream::WriteEvent;
+
+impl_async_adapters!();
+
+async fn read<R: AsyncRead + Unpin>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    poll_fn(|cx| Pin::new(&mut *reader).poll_read(cx, buf)).await
+}
+
+async fn write_all<W: AsyncWrite + Unpin>(writer: &mut W, mut buf: &[u8]) -> io::Result<()> {
+    while !buf.is_empty() {
+        match poll_fn(|cx| Pin::new(&mut *writer).poll_write(cx, buf)).await {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(count) => buf = &buf[count..],
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error),
+        }
+    }
+
+    Ok(())
+}
+
+async fn flush<W: AsyncWrite + Unpin>(writer: &mut W) -> io::Result<()> {
+    poll_fn(|cx| Pin::new(&mut *writer).poll_flush(cx)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+    use imap_types::{
+        command::{Command, CommandBody},
+        core::{IString, Literal, NString},
+        fetch::MessageDataItem,
+        response::{Data, Greeting, Response},
+    };
+
+    use super::*;
+    use crate::{
+        decode::CommandDecodeError, encode::Encoder, stream::ReadDecodeError, CommandCodec,
+        GreetingCodec, ResponseCodec,
+    };
+
+    #[test]
+    fn test_async_read_decoder() {
+        block_on(async {
+            let reader: &[u8] = b"* OK hello\r\n* 1 FETCH (RFC822 {5}\r\nhello)\r\n";
+
+            let mut decoder = AsyncReadDecoder::new(reader, GreetingCodec::new());
+            assert_eq!(
+                decoder.read().await.unwrap(),
+                Greeting::ok(None, "hello").unwrap()
+            );
+
+            let mut decoder = decoder.with_codec(ResponseCodec::new());
+            assert_eq!(
+                decoder.read().await.unwrap(),
+                Response::Data(
+                    Data::fetch(
+                        1,
+                        vec![MessageDataItem::Rfc822(NString(Some(IString::Literal(
+                            Literal::try_from("hello").unwrap()
+                        ))))]
+                    )
+                    .unwrap()
+                )
+            );
+            assert!(matches!(
+                decoder.read().await,
+                Err(ReadDecodeError::Io(error)) if error.kind() == io::ErrorKind::UnexpectedEof
+            ));
+        });
+    }
+
+    #[test]
+    fn test_async_read_decoder_literal() {
+        block_on(async {
+            let reader: &[u8] = b"A1 LOGIN {5}\r\n";
+
+            let mut decoder = AsyncReadDecoder::new(reader, CommandCodec::new());
+            assert!(matches!(
+                decoder.read().await,
+                Err(ReadDecodeError::Decode(CommandDecodeError::LiteralFound {
+                    length: 5,
+                    ..
+                }))
+            ));
+        });
+    }
+
+    #[test]
+    fn test_async_write_encoder() {
+        block_on(async {
+            let command =
+                Command::new("A1", CommandBody::login("alice", "Pa²²W0rD").unwrap()).unwrap();
+
+            let mut encoder = AsyncWriteEncoder::new(Vec::new(), CommandCodec::new());
+            assert_eq!(
+                encoder.write(&command).await.unwrap(),
+                WriteEvent::LiteralPending { length: 10 }
+            );
+            assert_eq!(encoder.get_ref(), b"A1 LOGIN alice {10}\r\n");
+
+            assert_eq!(encoder.continue_literal().await.unwrap(), WriteEvent::Done);
+            assert_eq!(
+                encoder.get_ref(),
+                "A1 LOGIN alice {10}\r\nPa²²W0rD\r\n".as_bytes()
+            );
+        });
+    }
+
+    #[test]
+    fn test_async_write_encoder_encoded() {
+        block_on(async {
+            let command =
+                Command::new("A1", CommandBody::login("alice", "Pa²²W0rD").unwrap()).unwrap();
+            let encoded = CommandCodec::new().encode(&command);
+
+            let mut encoder = AsyncWriteEncoder::new(Vec::new(), ResponseCodec::new());
+            assert_eq!(
+                encoder.write_encoded(encoded).await.unwrap(),
+                WriteEvent::LiteralPending { length: 10 }
+            );
+            assert_eq!(encoder.continue_literal().await.unwrap(), WriteEvent::Done);
+            assert_eq!(
+                encoder.get_ref(),
+                "A1 LOGIN alice {10}\r\nPa²²W0rD\r\n".as_bytes()
+            );
+        });
+    }
+}
@@ -0,0 +1,794 @@
+//! Push-based decoding of IMAP byte streams.
+//!
+//! A [`StreamingDecoder`] owns a buffer for received bytes and yields complete messages. This
+//! removes the need to implement the buffering and continuation loop around [`Decoder::decode`].
+//! A [`ReadDecoder`] additionally reads the bytes from a (blocking) [`Read`] implementation, e.g.,
+//! a `TcpStream`.
+//!
+//! # Example
+//!
+//! ```rust
+//! use imap_codec::{decode::CommandDecodeError, stream::StreamingDecoder, CommandCodec};
+//!
+//! let mut decoder = StreamingDecoder::new(CommandCodec::new());
+//!
+//! // Bytes are fed as they are received ...
+//! decoder.feed(b"A1 NOOP\r\nA2 LOGIN {5}\r\n");
+//!
+//! // ... and complete messages are yielded.
+//! let noop = decoder.next().unwrap().unwrap();
+//!
+//! // The server needs to agree to the receival of a synchronizing literal.
+//! assert!(matches!(
+//!     decoder.next(),
+//!     Some(Err(CommandDecodeError::LiteralFound { .. }))
+//! ));
+//! // <-- Send command continuation request.
+//!
+//! // More data is required.
+//! assert!(decoder.next().is_none());
+//!
+//! decoder.feed(b"alice {5}\r\n");
+//! decoder.feed(b"hello\r\n");
+//!
+//! let login = decoder.next().unwrap().unwrap();
+//! ```
+//!
+//! # Example (blocking)
+//!
+//! ```rust
+//! use imap_codec::{stream::ReadDecoder, GreetingCodec, ResponseCodec};
+//!
+//! // Use, e.g., a `TcpStream` here.
+//! let stream: &[u8] = b"* OK ...\r\n* 1 FETCH (RFC822 {5}\r\nhello)\r\nA1 OK ...\r\n";
+//!
+//! let mut decoder = ReadDecoder::new(stream, GreetingCodec::new());
+//! let greeting = decoder.read().unwrap();
+//!
+//! // Switch to responses without losing already received bytes.
+//! let mut decoder = decoder.with_codec(ResponseCodec::new());
+//! let fetch = decoder.read().unwrap();
+//! let ok = decoder.read().unwrap();
+//! ```
+
+use alloc::vec::Vec;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+use std::collections::VecDeque;
+#[cfg(feature = "std")]
+use std::io::{self, Read};
+
+#[cfg(any(feature = "tokio", feature = "futures"))]
+use imap_types::core::LiteralMode;
+use imap_types::IntoStatic;
+
+#[cfg(any(feature = "tokio", feature = "futures"))]
+use crate::encode::{Encoded, Fragment};
+use crate::{
+    decode::{DecodeError, Decoder},
+    AuthenticateDataCodec, CommandCodec, GreetingCodec, IdleDoneCodec, ResponseCodec,
+};
+
+/// Decoder that buffers received bytes and yields complete messages.
+///
+/// Bytes are added via [`StreamingDecoder::feed`] and messages are retrieved via
+/// [`Iterator::next`]. `None` means that more bytes are needed. Thus, `next` may yield messages
+/// again after more bytes were fed.
+///
+/// `Incomplete` errors are handled internally. A `LiteralFound` error is only yielded when some
+/// action is required, i.e., when a server receives a command with a literal. Errors are yielded
+/// only once until more bytes are fed.
+///
+/// Note: After a decoding failure, the buffer is left as is because it isn't possible to reliably
+/// find the start of the next message. Typically, the connection should be closed.
+#[derive(Clone, Debug)]
+pub struct StreamingDecoder<C> {
+    codec: C,
+    buffer: Vec<u8>,
+    /// Buffer length when the last error was yielded (used to yield every error only once).
+    error_yielded_at: Option<usize>,
+}
+
+impl<C> StreamingDecoder<C> {
+    /// Create a streaming decoder using `codec`.
+    pub fn new(codec: C) -> Self {
+        Self {
+            codec,
+            buffer: Vec::new(),
+            error_yielded_at: None,
+        }
+    }
+
+    /// Append received bytes to the buffer.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Bytes that were fed but not decoded (yet).
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Discard all buffered bytes.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.error_yielded_at = None;
+    }
+
+    pub fn codec(&self) -> &C {
+        &self.codec
+    }
+
+    /// Use another codec for subsequent messages, e.g., after a greeting was received.
+    ///
+    /// Buffered bytes are kept.
+    pub fn with_codec<D>(self, codec: D) -> StreamingDecoder<D> {
+        StreamingDecoder {
+            codec,
+            buffer: self.buffer,
+            error_yielded_at: self.error_yielded_at,
+        }
+    }
+
+    fn consume(&mut self, count: usize) {
+        self.buffer.drain(..count);
+        self.error_yielded_at = None;
+    }
+
+    fn should_yield_error(&mut self) -> bool {
+        if self.error_yielded_at == Some(self.buffer.len()) {
+            return false;
+        }
+
+        self.error_yielded_at = Some(self.buffer.len());
+        true
+    }
+}
+
+macro_rules! impl_streaming_decoder {
+    ($codec:ty) => {
+        impl Iterator for StreamingDecoder<$codec> {
+            type Item =
+                Result<<$codec as Decoder>::Message<'static>, <$codec as Decoder>::Error<'static>>;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let result = match self.codec.decode(&self.buffer) {
+                    Ok((remaining, message)) => {
+                        Ok((self.buffer.len() - remaining.len(), message.into_static()))
+                    }
+                    Err(error) => Err(error.into_static()),
+                };
+
+                match result {
+                    Ok((consumed, message)) => {
+                        self.consume(consumed);
+                        Some(Ok(message))
+                    }
+                    Err(error) if error.is_incomplete() => None,
+                    Err(error) => self.should_yield_error().then_some(Err(error)),
+                }
+            }
+        }
+    };
+}
+
+impl_streaming_decoder!(GreetingCodec);
+impl_streaming_decoder!(CommandCodec);
+impl_streaming_decoder!(AuthenticateDataCodec);
+impl_streaming_decoder!(ResponseCodec);
+impl_streaming_decoder!(IdleDoneCodec);
+
+/// Error during reading and decoding.
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+pub enum ReadDecodeError<E> {
+    /// Reading failed.
+    ///
+    /// Note: When the stream was closed before a message was complete, this is an
+    /// [`io::ErrorKind::UnexpectedEof`] error.
+    Io(io::Error),
+    /// Decoding failed (or, in case of a command, a literal was found).
+    Decode(E),
+}
+
+#[cfg(feature = "std")]
+impl<E> From<io::Error> for ReadDecodeError<E> {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// Outcome of writing a message with an async encoder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WriteEvent {
+    /// The message was written (and flushed) completely.
+    Done,
+    /// The message was written up to a synchronizing literal.
+    ///
+    /// The client must wait for a command continuation request and call `continue_literal`. When
+    /// the server rejects the command instead, the rest of the message must be dropped using
+    /// `discard`.
+    LiteralPending { length: usize },
+}
+
+/// Fragments of a message that were not written (yet).
+///
+/// This is the I/O-free part of the async encoders. It decides where writing must stop until a
+/// command continuation request was received.
+#[cfg(any(feature = "tokio", feature = "futures"))]
+#[derive(Debug, Default)]
+pub(crate) struct PendingFragments {
+    fragments: VecDeque<Fragment>,
+    await_literals: bool,
+    literal_accepted: bool,
+}
+
+/// What to do next with the writer.
+#[cfg(any(feature = "tokio", feature = "futures"))]
+pub(crate) enum WriteStep {
+    /// Write the data and ask again.
+    Write(Vec<u8>),
+    /// Flush the writer and return the event.
+    Flush(WriteEvent),
+}
+
+#[cfg(any(feature = "tokio", feature = "futures"))]
+impl PendingFragments {
+    /// Replace the pending fragments with the fragments of another message.
+    ///
+    /// When `await_literals` is set, writing stops at every synchronizing literal.
+    pub(crate) fn replace(&mut self, encoded: Encoded, await_literals: bool) {
+        self.fragments.clear();
+        self.fragments.extend(encoded);
+        self.await_literals = await_literals;
+        self.literal_accepted = false;
+    }
+
+    /// Allow the synchronizing literal at which writing stopped to be written.
+    pub(crate) fn accept_literal(&mut self) {
+        self.literal_accepted = true;
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.fragments.is_empty()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.fragments.clear();
+    }
+
+    pub(crate) fn next_step(&mut self) -> WriteStep {
+        match self.fragments.pop_front() {
+            Some(Fragment::Literal {
+                data,
+                mode: LiteralMode::Sync,
+            }) if self.await_literals && !self.literal_accepted => {
+                let length = data.len();
+                self.fragments.push_front(Fragment::Literal {
+                    data,
+                    mode: LiteralMode::Sync,
+                });
+
+                WriteStep::Flush(WriteEvent::LiteralPending { length })
+            }
+            Some(Fragment::Line { data } | Fragment::Literal { data, .. }) => {
+                self.literal_accepted = false;
+                WriteStep::Write(data)
+            }
+            None => WriteStep::Flush(WriteEvent::Done),
+        }
+    }
+}
+
+/// Define `AsyncReadDecoder` and `AsyncWriteEncoder` for an async runtime.
+///
+/// The invoking module provides the runtime's `AsyncRead` and `AsyncWrite` traits as well as
+/// `read`, `write_all`, and `flush` functions. Everything else is shared between the runtimes.
+#[cfg(any(feature = "tokio", feature = "futures"))]
+macro_rules! impl_async_adapters {
+    () => {
+        /// Decoder that reads from an [`AsyncRead`] implementation and returns complete messages.
+        ///
+        /// This is the async counterpart of [`ReadDecoder`](crate::stream::ReadDecoder).
+        #[derive(Debug)]
+        pub struct AsyncReadDecoder<R, C> {
+            reader: R,
+            decoder: $crate::stream::StreamingDecoder<C>,
+        }
+
+        impl<R, C> AsyncReadDecoder<R, C> {
+            /// Create a decoder that reads from `reader` using `codec`.
+            pub fn new(reader: R, codec: C) -> Self {
+                Self {
+                    reader,
+                    decoder: $crate::stream::StreamingDecoder::new(codec),
+                }
+            }
+
+            /// Use another codec for subsequent messages, e.g., after a greeting was received.
+            ///
+            /// Already read (but not yet decoded) bytes are kept.
+            pub fn with_codec<D>(self, codec: D) -> AsyncReadDecoder<R, D> {
+                AsyncReadDecoder {
+                    reader: self.reader,
+                    decoder: self.decoder.with_codec(codec),
+                }
+            }
+
+            pub fn get_ref(&self) -> &R {
+                &self.reader
+            }
+
+            pub fn get_mut(&mut self) -> &mut R {
+                &mut self.reader
+            }
+
+            /// Bytes that were read but not decoded (yet).
+            pub fn buffer(&self) -> &[u8] {
+                self.decoder.buffer()
+            }
+
+            pub fn into_inner(self) -> R {
+                self.reader
+            }
+        }
+
+        impl<R, C, M, E> AsyncReadDecoder<R, C>
+        where
+            R: AsyncRead + Unpin,
+            $crate::stream::StreamingDecoder<C>: Iterator<Item = Result<M, E>>,
+        {
+            /// Read until a message is complete and return it.
+            ///
+            /// When a server receives a command with a synchronizing literal,
+            /// [`ReadDecodeError::Decode`](crate::stream::ReadDecodeError::Decode) with
+            /// `CommandDecodeError::LiteralFound` is returned. The server should then send a
+            /// command continuation request and call `read` again.
+            ///
+            /// This method is cancel safe. Bytes that were already read are kept in the buffer.
+            pub async fn read(&mut self) -> Result<M, $crate::stream::ReadDecodeError<E>> {
+                let mut chunk = [0; 4096];
+
+                loop {
+                    if let Some(result) = self.decoder.next() {
+                        return result.map_err($crate::stream::ReadDecodeError::Decode);
+                    }
+
+                    let count = read(&mut self.reader, &mut chunk).await?;
+
+                    if count == 0 {
+                        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+                    }
+
+                    self.decoder.feed(&chunk[..count]);
+                }
+            }
+        }
+
+        /// Encoder that writes messages to an [`AsyncWrite`] implementation.
+        ///
+        /// Only commands stop at synchronizing literals. Literals in responses are written right
+        /// away.
+        #[derive(Debug)]
+        pub struct AsyncWriteEncoder<W, C> {
+            writer: W,
+            codec: C,
+            pending: $crate::stream::PendingFragments,
+        }
+
+        impl<W, C> AsyncWriteEncoder<W, C> {
+            /// Create an encoder that writes to `writer` using `codec`.
+            pub fn new(writer: W, codec: C) -> Self {
+                Self {
+                    writer,
+                    codec,
+                    pending: Default::default(),
+                }
+            }
+
+            /// Check if a message was only written partially.
+            pub fn is_pending(&self) -> bool {
+                !self.pending.is_empty()
+            }
+
+            /// Drop the rest of a partially written message.
+            pub fn discard(&mut self) {
+                self.pending.clear();
+            }
+
+            pub fn get_ref(&self) -> &W {
+                &self.writer
+            }
+
+            pub fn get_mut(&mut self) -> &mut W {
+                &mut self.writer
+            }
+
+            pub fn into_inner(self) -> W {
+                self.writer
+            }
+        }
+
+        impl<W, C> AsyncWriteEncoder<W, C>
+        where
+            W: AsyncWrite + Unpin,
+        {
+            /// Write the pending literal (after a command continuation request was received) and
+            /// continue with the rest of the message.
+            pub async fn continue_literal(
+                &mut self,
+            ) -> std::io::Result<$crate::stream::WriteEvent> {
+                self.pending.accept_literal();
+                self.write_pending().await
+            }
+
+            /// Write an already encoded message, e.g., one that was encoded by another codec.
+            ///
+            /// Unlike [`AsyncWriteEncoder::write`], this stops at every synchronizing literal
+            /// regardless of the codec.
+            ///
+            /// Note: A partially written message is discarded.
+            pub async fn write_encoded(
+                &mut self,
+                encoded: $crate::encode::Encoded,
+            ) -> std::io::Result<$crate::stream::WriteEvent> {
+                self.pending.replace(encoded, true);
+                self.write_pending().await
+            }
+
+            async fn write_pending(&mut self) -> std::io::Result<$crate::stream::WriteEvent> {
+                loop {
+                    match self.pending.next_step() {
+                        $crate::stream::WriteStep::Write(data) => {
+                            write_all(&mut self.writer, &data).await?;
+                        }
+                        $crate::stream::WriteStep::Flush(event) => {
+                            flush(&mut self.writer).await?;
+                            return Ok(event);
+                        }
+                    }
+                }
+            }
+        }
+
+        $crate::stream::impl_async_adapters!($crate::GreetingCodec, false);
+        $crate::stream::impl_async_adapters!($crate::CommandCodec, true);
+        $crate::stream::impl_async_adapters!($crate::AuthenticateDataCodec, false);
+        $crate::stream::impl_async_adapters!($crate::ResponseCodec, false);
+        $crate::stream::impl_async_adapters!($crate::IdleDoneCodec, false);
+    };
+    ($codec:ty, $await_literals:literal) => {
+        impl<W> AsyncWriteEncoder<W, $codec>
+        where
+            W: AsyncWrite + Unpin,
+        {
+            /// Encode and write a message.
+            ///
+            /// Note: A partially written message is discarded.
+            pub async fn write(
+                &mut self,
+                message: &<$codec as $crate::encode::Encoder>::Message<'_>,
+            ) -> std::io::Result<$crate::stream::WriteEvent> {
+                let encoded = $crate::encode::Encoder::encode(&self.codec, message);
+                self.pending.replace(encoded, $await_literals);
+                self.write_pending().await
+            }
+        }
+    };
+}
+
+#[cfg(any(feature = "tokio", feature = "futures"))]
+pub(crate) use impl_async_adapters;
+
+/// Decoder that reads bytes from a [`Read`] implementation and returns complete messages.
+///
+/// Reading blocks until a message is complete. Literals in responses are received transparently.
+/// When a server receives a command with a synchronizing literal, [`ReadDecodeError::Decode`] with
+/// `CommandDecodeError::LiteralFound` is returned. The server should then send a command
+/// continuation request and call [`ReadDecoder::read`] again.
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+pub struct ReadDecoder<R, C> {
+    reader: R,
+    decoder: StreamingDecoder<C>,
+}
+
+#[cfg(feature = "std")]
+impl<R, C> ReadDecoder<R, C> {
+    /// Create a decoder that reads from `reader` using `codec`.
+    pub fn new(reader: R, codec: C) -> Self {
+        Self {
+            reader,
+            decoder: StreamingDecoder::new(codec),
+        }
+    }
+
+    /// Use another codec for subsequent messages, e.g., after a greeting was received.
+    ///
+    /// Already read (but not yet decoded) bytes are kept.
+    pub fn with_codec<D>(self, codec: D) -> ReadDecoder<R, D> {
+        ReadDecoder {
+            reader: self.reader,
+            decoder: self.decoder.with_codec(codec),
+        }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Returns a mutable reference to the reader, e.g., to send a command continuation request.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// Bytes that were read but not decoded (yet).
+    pub fn buffer(&self) -> &[u8] {
+        self.decoder.buffer()
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R, C, M, E> ReadDecoder<R, C>
+where
+    R: Read,
+    StreamingDecoder<C>: Iterator<Item = Result<M, E>>,
+{
+    /// Read until a message is complete and return it.
+    pub fn read(&mut self) -> Result<M, ReadDecodeError<E>> {
+        let mut chunk = [0; 4096];
+
+        loop {
+            if let Some(result) = self.decoder.next() {
+                return result.map_err(ReadDecodeError::Decode);
+            }
+
+            let count = match self.reader.read(&mut chunk) {
+                Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+                Ok(count) => count,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error.into()),
+            };
+
+            self.decoder.feed(&chunk[..count]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use imap_types::{
+        command::{Command, CommandBody},
+        core::{AString, IString, Literal, LiteralMode, NString, Tag},
+        fetch::MessageDataItem,
+        response::{Data, Greeting, Response},
+        secret::Secret,
+    };
+
+    use super::*;
+    use crate::decode::{CommandDecodeError, GreetingDecodeError};
+
+    #[test]
+    fn test_streaming_decoder_command() {
+        let input = b"A1 NOOP\r\nA2 LOGIN {5}\r\nalice {5+}\r\nhello\r\nA3 NOOP\r\n";
+
+        let mut decoder = StreamingDecoder::new(CommandCodec::new());
+        let mut events = Vec::new();
+
+        // Feed byte-by-byte to exercise the buffering.
+        for byte in input {
+            decoder.feed(&[*byte]);
+
+            for event in decoder.by_ref() {
+                events.push(event);
+            }
+        }
+
+        assert_eq!(
+            events,
+            vec![
+                Ok(Command::new("A1", CommandBody::Noop).unwrap()),
+                Err(CommandDecodeError::LiteralFound {
+                    tag: Tag::try_from("A2").unwrap(),
+                    length: 5,
+                    mode: LiteralMode::Sync,
+                }),
+                Err(CommandDecodeError::LiteralFound {
+                    tag: Tag::try_from("A2").unwrap(),
+                    length: 5,
+                    mode: LiteralMode::NonSync,
+                }),
+                Ok(Command::new(
+                    "A2",
+                    CommandBody::Login {
+                        username: AString::String(IString::Literal(
+                            Literal::try_from("alice").unwrap()
+                        )),
+                        password: Secret::new(AString::String(IString::Literal(
+                            Literal::try_from("hello").unwrap().into_non_sync()
+                        ))),
+                    }
+                )
+                .unwrap()),
+                Ok(Command::new("A3", CommandBody::Noop).unwrap()),
+            ]
+        );
+        assert!(decoder.buffer().is_empty());
+    }
+
+    #[test]
+    fn test_streaming_decoder_response() {
+        let mut decoder = StreamingDecoder::new(ResponseCodec::new());
+
+        decoder.feed(b"* 1 FETCH (RFC822 {5}\r\n");
+        assert_eq!(decoder.next(), None);
+        assert_eq!(decoder.next(), None);
+
+        decoder.feed(b"hello)\r\n* 2 EXI");
+        assert_eq!(
+            decoder.next(),
+            Some(Ok(Response::Data(
+                Data::fetch(
+                    1,
+                    vec![MessageDataItem::Rfc822(NString(Some(IString::Literal(
+                        Literal::try_from("hello").unwrap()
+                    ))))]
+                )
+                .unwrap()
+            )))
+        );
+        assert_eq!(decoder.next(), None);
+        assert_eq!(decoder.buffer(), b"* 2 EXI");
+
+        decoder.feed(b"STS\r\n");
+        assert_eq!(decoder.next(), Some(Ok(Response::Data(Data::Exists(2)))));
+    }
+
+    #[cfg(any(feature = "tokio", feature = "futures"))]
+    #[test]
+    fn test_pending_fragments() {
+        use crate::encode::Encoder;
+
+        let command = Command::new("A1", CommandBody::login("alice", "Pa²²W0rD").unwrap()).unwrap();
+
+        let mut pending = PendingFragments::default();
+        pending.replace(CommandCodec::new().encode(&command), true);
+
+        assert!(matches!(
+            pending.next_step(),
+            WriteStep::Write(data) if data == b"A1 LOGIN alice {10}\r\n"
+        ));
+        // Writing stops until the literal is accepted.
+        for _ in 0..2 {
+            assert!(matches!(
+                pending.next_step(),
+                WriteStep::Flush(WriteEvent::LiteralPending { length: 10 })
+            ));
+        }
+
+        pending.accept_literal();
+        assert!(matches!(
+            pending.next_step(),
+            WriteStep::Write(data) if data == "Pa²²W0rD".as_bytes()
+        ));
+        assert!(matches!(
+            pending.next_step(),
+            WriteStep::Write(data) if data == b"\r\n"
+        ));
+        assert!(matches!(
+            pending.next_step(),
+            WriteStep::Flush(WriteEvent::Done)
+        ));
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_streaming_decoder_failure() {
+        let mut decoder = StreamingDecoder::new(GreetingCodec::new());
+
+        decoder.feed(b"A1 NOOP\r\n");
+        assert_eq!(decoder.next(), Some(Err(GreetingDecodeError::Failed)));
+        assert_eq!(decoder.next(), None);
+
+        decoder.clear();
+        assert_eq!(decoder.next(), None);
+    }
+
+    /// Reader that returns at most one byte per `read`.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match (self.0.split_first(), buf.first_mut()) {
+                (Some((byte, rest)), Some(first)) => {
+                    *first = *byte;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_decoder_response() {
+        let reader = Trickle(b"* OK hello\r\n* 1 FETCH (RFC822 {5}\r\nhello)\r\n* 2 EXI");
+
+        let mut decoder = ReadDecoder::new(reader, GreetingCodec::new());
+        assert_eq!(
+            decoder.read().unwrap(),
+            Greeting::ok(None, "hello").unwrap()
+        );
+
+        let mut decoder = decoder.with_codec(ResponseCodec::new());
+        assert_eq!(
+            decoder.read().unwrap(),
+            Response::Data(
+                Data::fetch(
+                    1,
+                    vec![MessageDataItem::Rfc822(NString(Some(IString::Literal(
+                        Literal::try_from("hello").unwrap()
+                    ))))]
+                )
+                .unwrap()
+            )
+        );
+        assert!(matches!(
+            decoder.read(),
+            Err(ReadDecodeError::Io(error)) if error.kind() == io::ErrorKind::UnexpectedEof
+        ));
+        assert_eq!(decoder.buffer(), b"* 2 EXI");
+    }
+
+    #[test]
+    fn test_read_decoder_command() {
+        let reader = Trickle(b"A1 LOGIN {5}\r\nalice {5+}\r\nhello\r\n");
+
+        let mut decoder = ReadDecoder::new(reader, CommandCodec::new());
+        assert!(matches!(
+            decoder.read(),
+            Err(ReadDecodeError::Decode(CommandDecodeError::LiteralFound {
+                mode: LiteralMode::Sync,
+                ..
+            }))
+        ));
+        // <-- Send command continuation request.
+        assert!(matches!(
+            decoder.read(),
+            Err(ReadDecodeError::Decode(CommandDecodeError::LiteralFound {
+                mode: LiteralMode::NonSync,
+                ..
+            }))
+        ));
+        assert_eq!(
+            decoder.read().unwrap(),
+            Command::new(
+                "A1",
+                CommandBody::Login {
+                    username: AString::String(IString::Literal(
+                        Literal::try_from("alice").unwrap()
+                    )),
+                    password: Secret::new(AString::String(IString::Literal(
+                        Literal::try_from("hello").unwrap().into_non_sync()
+                    ))),
+                }
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_read_decoder_failure() {
+        let mut decoder = ReadDecoder::new(b"A1 NOOP\r\n".as_ref(), GreetingCodec::new());
+
+        assert!(matches!(
+            decoder.read(),
+            Err(ReadDecodeError::Decode(GreetingDecodeError::Failed))
+        ));
+    }
+}
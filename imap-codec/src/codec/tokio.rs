@@ -0,0 +1,179 @@
+//! Async reading and writing of messages using tokio's [`AsyncRead`] and [`AsyncWrite`].
+//!
+//! The adapters work directly on a reader or writer, i.e., without an additional framing layer.
+//! Points in the message flow where a literal requires a command continuation request are exposed
+//! to the caller.
+//!
+//! # Example
+//!
+//! ```rust
+//! use imap_codec::{
+//!     imap_types::command::{Command, CommandBody},
+//!     tokio::{AsyncReadDecoder, AsyncWriteEncoder, WriteEvent},
+//!     CommandCodec, GreetingCodec, ResponseCodec,
+//! };
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! // Use, e.g., the halves of a `TcpStream` here.
+//! let reader: &[u8] = b"* OK ...\r\n+ ...\r\nA1 OK ...\r\n";
+//! let writer = Vec::new();
+//!
+//! let mut decoder = AsyncReadDecoder::new(reader, GreetingCodec::new());
+//! let mut encoder = AsyncWriteEncoder::new(writer, CommandCodec::new());
+//!
+//! let greeting = decoder.read().await.unwrap();
+//! let mut decoder = decoder.with_codec(ResponseCodec::new());
+//!
+//! let command = Command::new("A1", CommandBody::login("alice", "Pa²²W0rD").unwrap()).unwrap();
+//!
+//! let mut event = encoder.write(&command).await.unwrap();
+//!
+//! while let WriteEvent::LiteralPending { .. } = event {
+//!     // Wait for the command continuation request ...
+//!     let continuation = decoder.read().await.unwrap();
+//!     // ... and send the literal.
+//!     event = encoder.continue_literal().await.unwrap();
+//! }
+//!
+//! let ok = decoder.read().await.unwrap();
+//! # }
+//! ```
+
+#[cfg(feature = "tokio_util")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio_util")))]
+pub mod framed;
+
+use std::io;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::stream::impl_async_adapters;
+pub use crate::stream::WriteEvent;
+
+impl_async_adapters!();
+
+async fn read<R: AsyncRead + Unpin>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    reader.read(buf).await
+}
+
+async fn write_all<W: AsyncWrite + Unpin>(writer: &mut W, buf: &[u8]) -> io::Result<()> {
+    writer.write_all(buf).await
+}
+
+async fn flush<W: AsyncWrite + Unpin>(writer: &mut W) -> io::Result<()> {
+    writer.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use imap_types::{
+        command::{Command, CommandBody},
+        core::{IString, Literal, NString},
+        fetch::MessageDataItem,
+        response::{Data, Greeting, Response},
+    };
+
+    use super::*;
+    use crate::{
+        decode::CommandDecodeError, encode::Encoder, stream::ReadDecodeError, CommandCodec,
+        GreetingCodec, ResponseCodec,
+    };
+
+    #[tokio::test]
+    async fn test_async_read_decoder() {
+        let reader: &[u8] = b"* OK hello\r\n* 1 FETCH (RFC822 {5}\r\nhello)\r\n";
+
+        let mut decoder = AsyncReadDecoder::new(reader, GreetingCodec::new());
+        assert_eq!(
+            decoder.read().await.unwrap(),
+            Greeting::ok(None, "hello").unwrap()
+        );
+
+        let mut decoder = decoder.with_codec(ResponseCodec::new());
+        assert_eq!(
+            decoder.read().await.unwrap(),
+            Response::Data(
+                Data::fetch(
+                    1,
+                    vec![MessageDataItem::Rfc822(NString(Some(IString::Literal(
+                        Literal::try_from("hello").unwrap()
+                    ))))]
+                )
+                .unwrap()
+            )
+        );
+        assert!(matches!(
+            decoder.read().await,
+            Err(ReadDecodeError::Io(error)) if error.kind() == io::ErrorKind::UnexpectedEof
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_async_read_decoder_literal() {
+        let reader: &[u8] = b"A1 LOGIN {5}\r\n";
+
+        let mut decoder = AsyncReadDecoder::new(reader, CommandCodec::new());
+        assert!(matches!(
+            decoder.read().await,
+            Err(ReadDecodeError::Decode(CommandDecodeError::LiteralFound {
+                length: 5,
+                ..
+            }))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_async_write_encoder() {
+        let command = Command::new("A1", CommandBody::login("alice", "Pa²²W0rD").unwrap()).unwrap();
+
+        let mut encoder = AsyncWriteEncoder::new(Vec::new(), CommandCodec::new());
+        assert_eq!(
+            encoder.write(&command).await.unwrap(),
+            WriteEvent::LiteralPending { length: 10 }
+        );
+        assert_eq!(encoder.get_ref(), b"A1 LOGIN alice {10}\r\n");
+        assert!(encoder.is_pending());
+
+        assert_eq!(encoder.continue_literal().await.unwrap(), WriteEvent::Done);
+        assert_eq!(
+            encoder.get_ref(),
+            "A1 LOGIN alice {10}\r\nPa²²W0rD\r\n".as_bytes()
+        );
+        assert!(!encoder.is_pending());
+    }
+
+    #[tokio::test]
+    async fn test_async_write_encoder_encoded() {
+        let command = Command::new("A1", CommandBody::login("alice", "Pa²²W0rD").unwrap()).unwrap();
+        let encoded = CommandCodec::new().encode(&command);
+
+        let mut encoder = AsyncWriteEncoder::new(Vec::new(), ResponseCodec::new());
+        assert_eq!(
+            encoder.write_encoded(encoded).await.unwrap(),
+            WriteEvent::LiteralPending { length: 10 }
+        );
+        assert_eq!(encoder.continue_literal().await.unwrap(), WriteEvent::Done);
+        assert_eq!(
+            encoder.get_ref(),
+            "A1 LOGIN alice {10}\r\nPa²²W0rD\r\n".as_bytes()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_async_write_encoder_response() {
+        let response = Response::Data(
+            Data::fetch(
+                1,
+                vec![MessageDataItem::Rfc822(NString(Some(IString::Literal(
+                    Literal::try_from("hello").unwrap(),
+                ))))],
+            )
+            .unwrap(),
+        );
+
+        let mut encoder = AsyncWriteEncoder::new(Vec::new(), ResponseCodec::new());
+        assert_eq!(encoder.write(&response).await.unwrap(), WriteEvent::Done);
+        assert_eq!(encoder.get_ref(), b"* 1 FETCH (RFC822 {5}\r\nhello)\r\n");
+    }
+}
@@ -0,0 +1,892 @@
+//! Codecs for [`tokio_util::codec`], e.g., to be used with `Framed`.
+//!
+//! [`ImapServerCodec`] and [`ImapClientCodec`] split received bytes into messages using the
+//! [`Fragmentizer`] and decode them according to the state of the connection. Malformed messages
+//! are discarded and reported as events (not as errors), so that a connection can be continued.
+//!
+//! # Example
+//!
+//! ```rust
+//! use imap_codec::tokio::framed::{ImapServerCodec, NonSyncLiteralPolicy, ServerEvent};
+//! use tokio_util::{bytes::BytesMut, codec::Decoder};
+//!
+//! let mut codec = ImapServerCodec::builder()
+//!     .max_literal_size(1024)
+//!     .non_sync_literals(NonSyncLiteralPolicy::LiteralPlus)
+//!     .build();
+//!
+//! let mut src = BytesMut::from(&b"A1 LOGIN {5}\r\n"[..]);
+//!
+//! match codec.decode(&mut src).unwrap() {
+//!     Some(ServerEvent::LiteralAnnounced { tag, length }) => {
+//!         // Send a command continuation request.
+//!     }
+//!     _ => unreachable!(),
+//! }
+//! ```
+
+use std::{borrow::Cow, io};
+
+use imap_types::{
+    auth::AuthenticateData,
+    command::Command,
+    core::{LiteralMode, Tag},
+    extensions::idle::IdleDone,
+    response::{CommandContinuationRequest, Greeting, Response},
+    IntoStatic,
+};
+use tokio_util::{
+    bytes::BytesMut,
+    codec::{Decoder as TokioDecoder, Encoder as TokioEncoder},
+};
+
+use crate::{
+    decode::Decoder,
+    encode::{Encoder, Fragment},
+    fragmentizer::{FragmentInfo, Fragmentizer, LineEnding, LiteralAnnouncement},
+    AuthenticateDataCodec, CommandCodec, GreetingCodec, IdleDoneCodec, ResponseCodec,
+};
+
+/// Maximum size of a non-synchronizing literal when only LITERAL- is supported (RFC 7888).
+const LITERAL_MINUS_MAX_SIZE: u32 = 4096;
+
+/// Why a message was discarded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DiscardReason {
+    /// A line exceeded the maximum line length.
+    LineTooLong,
+    /// The message exceeded the maximum message size.
+    MessageTooLong,
+    /// A non-synchronizing literal exceeded the maximum literal size.
+    LiteralTooLong,
+    /// A non-synchronizing literal is not allowed by the [`NonSyncLiteralPolicy`].
+    NonSyncLiteralNotAllowed,
+    /// A line was terminated by LF instead of CRLF.
+    BareLf,
+    /// The message couldn't be decoded.
+    DecodingFailure,
+}
+
+/// Policy for non-synchronizing literals (RFC 7888).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum NonSyncLiteralPolicy {
+    /// Reject all non-synchronizing literals.
+    Reject,
+    /// Accept non-synchronizing literals up to 4096 bytes (LITERAL-).
+    ///
+    /// This is required by IMAP4rev2.
+    #[default]
+    LiteralMinus,
+    /// Accept all non-synchronizing literals (LITERAL+).
+    LiteralPlus,
+}
+
+/// The kind of message a server expects next.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ServerMode {
+    /// Decode commands.
+    #[default]
+    Command,
+    /// Decode authenticate data, i.e., lines sent by the client during `AUTHENTICATE`.
+    AuthenticateData,
+    /// Decode the `DONE` line that ends `IDLE`.
+    IdleDone,
+}
+
+/// Event emitted by [`ImapServerCodec`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ServerEvent {
+    Command(Command<'static>),
+    /// A command announced a synchronizing literal.
+    ///
+    /// Send a command continuation request to receive the literal. Otherwise, reject the command
+    /// and call [`ImapServerCodec::reject_literal`].
+    LiteralAnnounced {
+        tag: Tag<'static>,
+        length: u32,
+    },
+    /// A command announced a synchronizing literal exceeding the maximum literal size.
+    ///
+    /// The command was discarded and the client won't send the literal. Reject the command, e.g.,
+    /// with a tagged `NO [TOOBIG]`.
+    LiteralRejected {
+        tag: Tag<'static>,
+        length: u32,
+    },
+    AuthenticateData(AuthenticateData<'static>),
+    IdleDone,
+    /// A message was discarded.
+    ///
+    /// The tag is provided (when it can be recovered) to respond with a tagged `BAD`.
+    Discarded {
+        tag: Option<Tag<'static>>,
+        reason: DiscardReason,
+    },
+}
+
+/// Event emitted by [`ImapClientCodec`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClientEvent {
+    Greeting(Greeting<'static>),
+    /// A response that is not a command continuation request.
+    Response(Response<'static>),
+    /// A command continuation request, e.g., to send a literal or authenticate data.
+    ContinuationRequest(CommandContinuationRequest<'static>),
+    /// A message was discarded.
+    Discarded {
+        reason: DiscardReason,
+    },
+}
+
+/// Builder for [`ImapServerCodec`].
+#[derive(Clone, Debug)]
+pub struct ImapServerCodecBuilder {
+    max_message_size: u32,
+    max_line_length: usize,
+    max_literal_size: u32,
+    crlf_relaxed: bool,
+    non_sync_literals: NonSyncLiteralPolicy,
+}
+
+impl Default for ImapServerCodecBuilder {
+    fn default() -> Self {
+        Self {
+            max_message_size: 32 * 1024 * 1024,
+            max_line_length: 8 * 1024,
+            max_literal_size: 25 * 1024 * 1024,
+            crlf_relaxed: false,
+            non_sync_literals: NonSyncLiteralPolicy::default(),
+        }
+    }
+}
+
+impl ImapServerCodecBuilder {
+    /// Maximum size of a message (including literals).
+    pub fn max_message_size(mut self, max_message_size: u32) -> Self {
+        self.max_message_size = max_message_size;
+        self
+    }
+
+    /// Maximum length of a line (excluding literals).
+    pub fn max_line_length(mut self, max_line_length: usize) -> Self {
+        self.max_line_length = max_line_length;
+        self
+    }
+
+    /// Maximum size of a single literal.
+    pub fn max_literal_size(mut self, max_literal_size: u32) -> Self {
+        self.max_literal_size = max_literal_size;
+        self
+    }
+
+    /// Accept lines terminated by LF (instead of CRLF).
+    pub fn crlf_relaxed(mut self, crlf_relaxed: bool) -> Self {
+        self.crlf_relaxed = crlf_relaxed;
+        self
+    }
+
+    pub fn non_sync_literals(mut self, policy: NonSyncLiteralPolicy) -> Self {
+        self.non_sync_literals = policy;
+        self
+    }
+
+    pub fn build(self) -> ImapServerCodec {
+        ImapServerCodec {
+            framer: Framer::new(
+                self.max_message_size,
+                self.max_line_length,
+                self.crlf_relaxed,
+            ),
+            max_literal_size: self.max_literal_size,
+            non_sync_literals: self.non_sync_literals,
+            mode: ServerMode::default(),
+        }
+    }
+}
+
+/// Server-side codec that decodes commands (and friends) and encodes greetings and responses.
+///
+/// The server is responsible for switching the [`ServerMode`], e.g., after sending a command
+/// continuation request during `AUTHENTICATE`.
+#[derive(Debug)]
+pub struct ImapServerCodec {
+    framer: Framer,
+    max_literal_size: u32,
+    non_sync_literals: NonSyncLiteralPolicy,
+    mode: ServerMode,
+}
+
+impl Default for ImapServerCodec {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl ImapServerCodec {
+    pub fn builder() -> ImapServerCodecBuilder {
+        ImapServerCodecBuilder::default()
+    }
+
+    pub fn mode(&self) -> ServerMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: ServerMode) {
+        self.mode = mode;
+    }
+
+    /// Discard the command after [`ServerEvent::LiteralAnnounced`] without receiving the literal.
+    pub fn reject_literal(&mut self) {
+        self.framer.skip_message();
+    }
+
+    fn check_literal(&mut self, mode: LiteralMode, length: u32) -> Option<ServerEvent> {
+        if self.mode != ServerMode::Command {
+            self.framer.discard(DiscardReason::DecodingFailure);
+        }
+
+        match mode {
+            LiteralMode::Sync => {
+                let event = match (self.framer.tag(), self.framer.discard_reason) {
+                    (Some(tag), None) if length <= self.max_literal_size => {
+                        return Some(ServerEvent::LiteralAnnounced { tag, length });
+                    }
+                    (Some(tag), None) => ServerEvent::LiteralRejected { tag, length },
+                    (tag, reason) => ServerEvent::Discarded {
+                        tag: self.tag_if_command(tag),
+                        reason: reason.unwrap_or(DiscardReason::DecodingFailure),
+                    },
+                };
+
+                // The client waits for a command continuation request. Thus, the rest of the
+                // message can be skipped safely.
+                self.framer.skip_message();
+
+                Some(event)
+            }
+            LiteralMode::NonSync => {
+                // The client sends the literal anyway. Thus, it must be received (and discarded).
+                if length > self.max_literal_size {
+                    self.framer.discard(DiscardReason::LiteralTooLong);
+                }
+
+                match self.non_sync_literals {
+                    NonSyncLiteralPolicy::Reject => {
+                        self.framer.discard(DiscardReason::NonSyncLiteralNotAllowed);
+                    }
+                    NonSyncLiteralPolicy::LiteralMinus if length > LITERAL_MINUS_MAX_SIZE => {
+                        self.framer.discard(DiscardReason::NonSyncLiteralNotAllowed);
+                    }
+                    _ => {}
+                }
+
+                None
+            }
+        }
+    }
+
+    fn decode_message(&self) -> ServerEvent {
+        let result = self
+            .framer
+            .message_bytes()
+            .and_then(|bytes| match self.mode {
+                ServerMode::Command => decode_exact(&CommandCodec::new(), &bytes)
+                    .map(|command| ServerEvent::Command(command.into_static())),
+                ServerMode::AuthenticateData => decode_exact(&AuthenticateDataCodec::new(), &bytes)
+                    .map(|data| ServerEvent::AuthenticateData(data.into_static())),
+                ServerMode::IdleDone => {
+                    decode_exact(&IdleDoneCodec::new(), &bytes).map(|_| ServerEvent::IdleDone)
+                }
+            });
+
+        result.unwrap_or_else(|reason| ServerEvent::Discarded {
+            tag: self.tag_if_command(self.framer.tag()),
+            reason,
+        })
+    }
+
+    fn tag_if_command(&self, tag: Option<Tag<'static>>) -> Option<Tag<'static>> {
+        match self.mode {
+            ServerMode::Command => tag,
+            ServerMode::AuthenticateData | ServerMode::IdleDone => None,
+        }
+    }
+}
+
+impl TokioDecoder for ImapServerCodec {
+    type Item = ServerEvent;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        while let Some(fragment) = self.framer.progress(src) {
+            if let FragmentInfo::Line {
+                announcement: Some(LiteralAnnouncement { mode, length }),
+                ..
+            } = fragment
+            {
+                if let Some(event) = self.check_literal(mode, length) {
+                    return Ok(Some(event));
+                }
+            }
+
+            if self.framer.is_message_complete() {
+                return Ok(Some(self.decode_message()));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.decode(src)? {
+            Some(event) => Ok(Some(event)),
+            None => self.framer.check_eof().map(|_| None),
+        }
+    }
+}
+
+impl<'a> TokioEncoder<&Greeting<'a>> for ImapServerCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: &Greeting<'a>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.extend_from_slice(&GreetingCodec::new().encode(item).dump());
+        Ok(())
+    }
+}
+
+impl<'a> TokioEncoder<&Response<'a>> for ImapServerCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: &Response<'a>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // Literals in responses don't require a command continuation request.
+        dst.extend_from_slice(&ResponseCodec::new().encode(item).dump());
+        Ok(())
+    }
+}
+
+/// Builder for [`ImapClientCodec`].
+#[derive(Clone, Debug)]
+pub struct ImapClientCodecBuilder {
+    max_message_size: u32,
+    max_line_length: usize,
+    crlf_relaxed: bool,
+    expect_greeting: bool,
+}
+
+impl Default for ImapClientCodecBuilder {
+    fn default() -> Self {
+        Self {
+            max_message_size: 64 * 1024 * 1024,
+            max_line_length: 1024 * 1024,
+            crlf_relaxed: false,
+            expect_greeting: true,
+        }
+    }
+}
+
+impl ImapClientCodecBuilder {
+    /// Maximum size of a message (including literals).
+    pub fn max_message_size(mut self, max_message_size: u32) -> Self {
+        self.max_message_size = max_message_size;
+        self
+    }
+
+    /// Maximum length of a line (excluding literals).
+    pub fn max_line_length(mut self, max_line_length: usize) -> Self {
+        self.max_line_length = max_line_length;
+        self
+    }
+
+    /// Accept lines terminated by LF (instead of CRLF).
+    pub fn crlf_relaxed(mut self, crlf_relaxed: bool) -> Self {
+        self.crlf_relaxed = crlf_relaxed;
+        self
+    }
+
+    /// Decode the first message as greeting (default).
+    ///
+    /// Disable this, e.g., when the greeting was already received before the codec was set up.
+    pub fn expect_greeting(mut self, expect_greeting: bool) -> Self {
+        self.expect_greeting = expect_greeting;
+        self
+    }
+
+    pub fn build(self) -> ImapClientCodec {
+        ImapClientCodec {
+            framer: Framer::new(
+                self.max_message_size,
+                self.max_line_length,
+                self.crlf_relaxed,
+            ),
+            greeting_expected: self.expect_greeting,
+        }
+    }
+}
+
+/// Client-side codec that decodes greetings and responses and encodes commands (and friends).
+///
+/// Note: Encoding a [`Command`] doesn't wait for command continuation requests before literals.
+/// To do so, encode the [`Fragment`]s of the command one by one and wait for a
+/// [`ClientEvent::ContinuationRequest`] before sending a synchronizing literal.
+#[derive(Debug)]
+pub struct ImapClientCodec {
+    framer: Framer,
+    greeting_expected: bool,
+}
+
+impl Default for ImapClientCodec {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl ImapClientCodec {
+    pub fn builder() -> ImapClientCodecBuilder {
+        ImapClientCodecBuilder::default()
+    }
+
+    fn decode_message(&mut self) -> ClientEvent {
+        let result = self.framer.message_bytes().and_then(|bytes| {
+            if self.greeting_expected {
+                decode_exact(&GreetingCodec::new(), &bytes)
+                    .map(|greeting| ClientEvent::Greeting(greeting.into_static()))
+            } else {
+                decode_exact(&ResponseCodec::new(), &bytes).map(|response| {
+                    match response.into_static() {
+                        Response::CommandContinuationRequest(continuation) => {
+                            ClientEvent::ContinuationRequest(continuation)
+                        }
+                        response => ClientEvent::Response(response),
+                    }
+                })
+            }
+        });
+
+        match result {
+            Ok(event) => {
+                if let ClientEvent::Greeting(_) = event {
+                    self.greeting_expected = false;
+                }
+
+                event
+            }
+            Err(reason) => ClientEvent::Discarded { reason },
+        }
+    }
+}
+
+impl TokioDecoder for ImapClientCodec {
+    type Item = ClientEvent;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        while self.framer.progress(src).is_some() {
+            if self.framer.is_message_complete() {
+                return Ok(Some(self.decode_message()));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.decode(src)? {
+            Some(event) => Ok(Some(event)),
+            None => self.framer.check_eof().map(|_| None),
+        }
+    }
+}
+
+impl<'a> TokioEncoder<&Command<'a>> for ImapClientCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: &Command<'a>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.extend_from_slice(&CommandCodec::new().encode(item).dump());
+        Ok(())
+    }
+}
+
+impl TokioEncoder<Fragment> for ImapClientCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: Fragment, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        match item {
+            Fragment::Line { data } | Fragment::Literal { data, .. } => {
+                dst.extend_from_slice(&data)
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> TokioEncoder<&AuthenticateData<'a>> for ImapClientCodec {
+    type Error = io::Error;
+
+    fn encode(
+        &mut self,
+        item: &AuthenticateData<'a>,
+        dst: &mut BytesMut,
+    ) -> Result<(), Self::Error> {
+        dst.extend_from_slice(&AuthenticateDataCodec::new().encode(item).dump());
+        Ok(())
+    }
+}
+
+impl TokioEncoder<&IdleDone> for ImapClientCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: &IdleDone, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        dst.extend_from_slice(&IdleDoneCodec::new().encode(item).dump());
+        Ok(())
+    }
+}
+
+/// Splits received bytes into messages and enforces the limits shared by both sides.
+#[derive(Debug)]
+struct Framer {
+    fragmentizer: Fragmentizer,
+    crlf_relaxed: bool,
+    /// Why the current message will be discarded (if it will be discarded).
+    discard_reason: Option<DiscardReason>,
+    /// Ends of lines in the current message that were terminated by LF only.
+    lf_line_ends: Vec<usize>,
+}
+
+impl Framer {
+    fn new(max_message_size: u32, max_line_length: usize, crlf_relaxed: bool) -> Self {
+        Self {
+            fragmentizer: Fragmentizer::new(max_message_size).with_max_line_length(max_line_length),
+            crlf_relaxed,
+            discard_reason: None,
+            lf_line_ends: Vec::new(),
+        }
+    }
+
+    fn progress(&mut self, src: &mut BytesMut) -> Option<FragmentInfo> {
+        if !src.is_empty() {
+            self.fragmentizer.enqueue_bytes(src);
+            src.clear();
+        }
+
+        if self.fragmentizer.is_message_complete() {
+            // `Fragmentizer::progress` will start the next message.
+            self.discard_reason = None;
+            self.lf_line_ends.clear();
+        }
+
+        let fragment = self.fragmentizer.progress()?;
+
+        if let FragmentInfo::Line { end, ending, .. } = fragment {
+            if ending == LineEnding::Lf {
+                if self.crlf_relaxed {
+                    self.lf_line_ends.push(end);
+                } else {
+                    self.discard(DiscardReason::BareLf);
+                }
+            }
+        }
+
+        Some(fragment)
+    }
+
+    fn is_message_complete(&self) -> bool {
+        self.fragmentizer.is_message_complete()
+    }
+
+    /// Fail when the stream ended in the middle of a message.
+    ///
+    /// Note: All received bytes are moved into the [`Fragmentizer`]. Thus, `tokio_util`'s default
+    /// check for remaining bytes in the read buffer doesn't work.
+    fn check_eof(&self) -> io::Result<()> {
+        if self.is_message_complete() || self.fragmentizer.message_span().is_empty() {
+            return Ok(());
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "stream ended in the middle of a message",
+        ))
+    }
+
+    fn discard(&mut self, reason: DiscardReason) {
+        self.discard_reason.get_or_insert(reason);
+        self.fragmentizer.poison_message();
+    }
+
+    fn skip_message(&mut self) {
+        self.fragmentizer.skip_message();
+        self.discard_reason = None;
+        self.lf_line_ends.clear();
+    }
+
+    fn tag(&self) -> Option<Tag<'static>> {
+        self.fragmentizer.decode_tag().map(IntoStatic::into_static)
+    }
+
+    /// Bytes of the current (complete) message with LF line endings replaced by CRLF.
+    fn message_bytes(&self) -> Result<Cow<[u8]>, DiscardReason> {
+        if self.fragmentizer.is_max_line_length_exceeded() {
+            return Err(DiscardReason::LineTooLong);
+        }
+
+        if self.fragmentizer.is_max_message_size_exceeded() {
+            return Err(DiscardReason::MessageTooLong);
+        }
+
+        if let Some(reason) = self.discard_reason {
+            return Err(reason);
+        }
+
+        let bytes = self.fragmentizer.message_bytes();
+
+        if self.lf_line_ends.is_empty() {
+            return Ok(Cow::Borrowed(bytes));
+        }
+
+        let mut normalized = Vec::with_capacity(bytes.len() + self.lf_line_ends.len());
+        let mut start = 0;
+
+        for end in &self.lf_line_ends {
+            normalized.extend_from_slice(&bytes[start..end - 1]);
+            normalized.extend_from_slice(b"\r\n");
+            start = *end;
+        }
+
+        normalized.extend_from_slice(&bytes[start..]);
+
+        Ok(Cow::Owned(normalized))
+    }
+}
+
+fn decode_exact<'a, C: Decoder>(
+    codec: &C,
+    bytes: &'a [u8],
+) -> Result<C::Message<'a>, DiscardReason> {
+    match codec.decode(bytes) {
+        Ok(([], message)) => Ok(message),
+        _ => Err(DiscardReason::DecodingFailure),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use imap_types::{
+        command::CommandBody,
+        core::{AString, IString, Literal},
+        response::{Data, GreetingKind, Status},
+        secret::Secret,
+    };
+
+    use super::*;
+
+    fn decode_all<C: TokioDecoder>(codec: &mut C, bytes: &[u8]) -> Vec<C::Item>
+    where
+        C::Error: std::fmt::Debug,
+    {
+        let mut src = BytesMut::from(bytes);
+        let mut events = Vec::new();
+
+        while let Some(event) = codec.decode(&mut src).unwrap() {
+            events.push(event);
+        }
+
+        events
+    }
+
+    #[test]
+    fn test_server_codec_literals() {
+        let mut codec = ImapServerCodec::builder().max_literal_size(5).build();
+
+        assert_eq!(
+            decode_all(&mut codec, b"A1 LOGIN {5}\r\n"),
+            vec![ServerEvent::LiteralAnnounced {
+                tag: Tag::try_from("A1").unwrap(),
+                length: 5
+            }]
+        );
+        assert_eq!(
+            decode_all(&mut codec, b"alice {6}\r\nA2 NOOP\r\n"),
+            vec![
+                ServerEvent::LiteralRejected {
+                    tag: Tag::try_from("A1").unwrap(),
+                    length: 6
+                },
+                ServerEvent::Command(Command::new("A2", CommandBody::Noop).unwrap()),
+            ]
+        );
+
+        assert_eq!(
+            decode_all(&mut codec, b"A3 LOGIN {5}\r\n"),
+            vec![ServerEvent::LiteralAnnounced {
+                tag: Tag::try_from("A3").unwrap(),
+                length: 5
+            }]
+        );
+        assert_eq!(
+            decode_all(&mut codec, b"alice bob\r\n"),
+            vec![ServerEvent::Command(
+                Command::new(
+                    "A3",
+                    CommandBody::Login {
+                        username: AString::String(IString::Literal(
+                            Literal::try_from("alice").unwrap()
+                        )),
+                        password: Secret::new(AString::try_from("bob").unwrap()),
+                    }
+                )
+                .unwrap()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_server_codec_non_sync_literals() {
+        let mut codec = ImapServerCodec::builder()
+            .non_sync_literals(NonSyncLiteralPolicy::Reject)
+            .build();
+
+        assert_eq!(
+            decode_all(&mut codec, b"A1 LOGIN {5+}\r\nalice bob\r\nA2 NOOP\r\n"),
+            vec![
+                ServerEvent::Discarded {
+                    tag: Some(Tag::try_from("A1").unwrap()),
+                    reason: DiscardReason::NonSyncLiteralNotAllowed,
+                },
+                ServerEvent::Command(Command::new("A2", CommandBody::Noop).unwrap()),
+            ]
+        );
+
+        let mut codec = ImapServerCodec::default();
+
+        assert!(matches!(
+            decode_all(&mut codec, b"A1 LOGIN {5+}\r\nalice bob\r\n").as_slice(),
+            [ServerEvent::Command(_)]
+        ));
+    }
+
+    #[test]
+    fn test_server_codec_limits() {
+        let mut codec = ImapServerCodec::builder().max_line_length(16).build();
+
+        assert_eq!(
+            decode_all(&mut codec, b"A1 SELECT VERY_LONG_MAILBOX\r\nA2 NOOP\n"),
+            vec![
+                ServerEvent::Discarded {
+                    tag: Some(Tag::try_from("A1").unwrap()),
+                    reason: DiscardReason::LineTooLong,
+                },
+                ServerEvent::Discarded {
+                    tag: Some(Tag::try_from("A2").unwrap()),
+                    reason: DiscardReason::BareLf,
+                },
+            ]
+        );
+
+        let mut codec = ImapServerCodec::builder().crlf_relaxed(true).build();
+
+        assert_eq!(
+            decode_all(&mut codec, b"A1 NOOP\n"),
+            vec![ServerEvent::Command(
+                Command::new("A1", CommandBody::Noop).unwrap()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_server_codec_modes() {
+        let mut codec = ImapServerCodec::default();
+
+        assert!(matches!(
+            decode_all(&mut codec, b"A1 AUTHENTICATE PLAIN\r\n").as_slice(),
+            [ServerEvent::Command(_)]
+        ));
+
+        codec.set_mode(ServerMode::AuthenticateData);
+        assert_eq!(
+            decode_all(&mut codec, b"*\r\n"),
+            vec![ServerEvent::AuthenticateData(AuthenticateData::Cancel)]
+        );
+
+        codec.set_mode(ServerMode::IdleDone);
+        assert_eq!(
+            decode_all(&mut codec, b"done\r\n"),
+            vec![ServerEvent::IdleDone]
+        );
+    }
+
+    #[test]
+    fn test_client_codec() {
+        let mut codec = ImapClientCodec::default();
+
+        assert_eq!(
+            decode_all(&mut codec, b"* OK hello\r\n+ go ahead\r\n* 1 EXISTS\r\n"),
+            vec![
+                ClientEvent::Greeting(Greeting::new(GreetingKind::Ok, None, "hello").unwrap()),
+                ClientEvent::ContinuationRequest(
+                    CommandContinuationRequest::basic(None, "go ahead").unwrap()
+                ),
+                ClientEvent::Response(Response::Data(Data::Exists(1))),
+            ]
+        );
+
+        let mut codec = ImapClientCodec::builder().expect_greeting(false).build();
+
+        assert_eq!(
+            decode_all(&mut codec, b"* OK hello\r\n* 1 FETCH (RFC822 {5}\r\nhel"),
+            vec![ClientEvent::Response(Response::Status(
+                Status::ok(None, None, "hello").unwrap()
+            ))]
+        );
+    }
+
+    #[test]
+    fn test_decode_eof() {
+        let mut codec = ImapServerCodec::default();
+        let mut src = BytesMut::from(&b"A1 NOOP\r\nA2 NO"[..]);
+
+        assert_eq!(
+            codec.decode_eof(&mut src).unwrap(),
+            Some(ServerEvent::Command(
+                Command::new("A1", CommandBody::Noop).unwrap()
+            ))
+        );
+        assert_eq!(
+            codec.decode_eof(&mut src).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+
+        let mut codec = ImapClientCodec::default();
+        let mut src = BytesMut::from(&b"* OK hello\r\n"[..]);
+
+        assert!(matches!(
+            codec.decode_eof(&mut src).unwrap(),
+            Some(ClientEvent::Greeting(_))
+        ));
+        assert_eq!(codec.decode_eof(&mut src).unwrap(), None);
+
+        let mut src = BytesMut::from(&b"* 1 FETCH (RFC822 {5}\r\nhel"[..]);
+        assert_eq!(
+            codec.decode_eof(&mut src).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn test_client_codec_encode() {
+        let command = Command::new("A1", CommandBody::login("alice", "Pa²²W0rD").unwrap()).unwrap();
+
+        let mut codec = ImapClientCodec::default();
+        let mut dst = BytesMut::new();
+
+        for fragment in CommandCodec::new().encode(&command) {
+            codec.encode(fragment, &mut dst).unwrap();
+        }
+        codec.encode(&IdleDone, &mut dst).unwrap();
+
+        assert_eq!(
+            &dst[..],
+            "A1 LOGIN alice {10}\r\nPa²²W0rD\r\nDONE\r\n".as_bytes()
+        );
+    }
+}
@@ -1,15 +1,13 @@
-use std::borrow::Cow;
+use alloc::{borrow::Cow, vec, vec::Vec};
 
-#[cfg(not(feature = "quirk_crlf_relaxed"))]
-use abnf_core::streaming::crlf;
-#[cfg(feature = "quirk_crlf_relaxed")]
-use abnf_core::streaming::crlf_relaxed as crlf;
 use abnf_core::streaming::sp;
 #[cfg(feature = "ext_condstore_qresync")]
-use imap_types::command::{FetchModifier, SelectParameter, StoreModifier};
+use imap_types::command::{FetchModifier, StoreModifier};
+#[cfg(feature = "ext_condstore_qresync")]
+use imap_types::utils::indicators::is_tagged_label_char;
 use imap_types::{
     auth::AuthMechanism,
-    command::{Command, CommandBody},
+    command::{Command, CommandBody, SelectParameter, SelectParameterOther},
     core::AString,
     extensions::binary::LiteralOrLiteral8,
     fetch::{Macro, MacroOrMessageDataItemNames},
@@ -17,13 +15,18 @@ use imap_types::{
     secret::Secret,
 };
 #[cfg(feature = "ext_condstore_qresync")]
+use nom::bytes::streaming::take_while_m_n;
+#[cfg(feature = "ext_condstore_qresync")]
 use nom::character::streaming::char;
 #[cfg(feature = "ext_condstore_qresync")]
+use nom::combinator::{not, peek};
+#[cfg(feature = "ext_condstore_qresync")]
 use nom::sequence::separated_pair;
 use nom::{
     branch::alt,
     bytes::streaming::{tag, tag_no_case},
-    combinator::{map, opt, value},
+    combinator::{map, map_res, opt, value, verify},
+    error::{ErrorKind, ParseError},
     multi::{separated_list0, separated_list1},
     sequence::{delimited, preceded, terminated, tuple},
 };
@@ -38,11 +41,15 @@ use crate::extensions::condstore_qresync::mod_sequence_valzer;
 use crate::extensions::id::id;
 #[cfg(feature = "ext_metadata")]
 use crate::extensions::metadata::{getmetadata, setmetadata};
+#[cfg(feature = "ext_xapplepushservice")]
+use crate::extensions::xapplepushservice::xapplepushservice;
+#[cfg(feature = "ext_xlist")]
+use crate::extensions::xlist::xlist;
 use crate::{
     auth::auth_type,
-    core::{astring, base64, literal, tag_imap},
+    core::{astring, base64, crlf, keyword, literal, tag_imap, unknown_keyword},
     datetime::date_time,
-    decode::{IMAPErrorKind, IMAPResult},
+    decode::{IMAPErrorKind, IMAPParseError, IMAPResult},
     extensions::{
         binary::literal8,
         compress::compress,
@@ -51,6 +58,7 @@ use crate::{
         quota::{getquota, getquotaroot, setquota},
         r#move::r#move,
         sort::sort,
+        tagged_ext::extension_item,
         thread::thread,
         uidplus::uid_expunge,
     },
@@ -70,10 +78,7 @@ use crate::{
 ///                   ) CRLF`
 pub(crate) fn command(input: &[u8]) -> IMAPResult<&[u8], Command> {
     let mut parser_tag = terminated(tag_imap, sp);
-    let mut parser_body = terminated(
-        alt((command_any, command_auth, command_nonauth, command_select)),
-        crlf,
-    );
+    let mut parser_body = terminated(command_body, crlf);
 
     let (remaining, obtained_tag) = parser_tag(input)?;
 
@@ -86,9 +91,11 @@ pub(crate) fn command(input: &[u8]) -> IMAPResult<&[u8], Command> {
             },
         )),
         Err(mut error) => {
-            // If we got an `IMAPErrorKind::Literal`, we fill in the missing `tag`.
+            // If we got an `IMAPErrorKind::Literal{TooLarge}`, we fill in the missing `tag`.
             if let nom::Err::Error(ref mut err) | nom::Err::Failure(ref mut err) = error {
-                if let IMAPErrorKind::Literal { ref mut tag, .. } = err.kind {
+                if let IMAPErrorKind::Literal { ref mut tag, .. }
+                | IMAPErrorKind::LiteralTooLarge { ref mut tag, .. } = err.kind
+                {
                     *tag = Some(obtained_tag);
                 }
             }
@@ -98,79 +105,116 @@ pub(crate) fn command(input: &[u8]) -> IMAPResult<&[u8], Command> {
     }
 }
 
-// # Command Any
-
 /// ```abnf
-/// command-any = "CAPABILITY" /
-///               "LOGOUT" /
-///               "NOOP" /
-///               x-command /
-///               id ; adds id command to command_any (See RFC 2971)
+/// command-any     = "CAPABILITY" /
+///                   "LOGOUT" /
+///                   "NOOP" /
+///                   x-command /
+///                   id ; adds id command to command_any (See RFC 2971)
+///
+/// command-auth    = append / create / delete / examine / list / lsub / rename / select / status /
+///                   subscribe / unsubscribe /
+///                   idle /         ; RFC 2177
+///                   enable /       ; RFC 5161
+///                   compress /     ; RFC 4978
+///                   getquota /     ; RFC 9208
+///                   getquotaroot / ; RFC 9208
+///                   setquota /     ; RFC 9208
+///                   setmetadata /  ; RFC 5464
+///                   getmetadata /  ; RFC 5464
+///                   xapplepushservice / ; XAPPLEPUSHSERVICE (Apple)
+///                   xlist          ; XLIST (legacy)
+///
+/// command-nonauth = login / authenticate / "STARTTLS"
+///
+/// command-select  = "CHECK" / "CLOSE" / "EXPUNGE" / copy / fetch / store / uid / search
 /// ```
 ///
-/// Note: Valid in all states
-pub(crate) fn command_any(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
-    alt((
-        value(CommandBody::Capability, tag_no_case(b"CAPABILITY")),
-        value(CommandBody::Logout, tag_no_case(b"LOGOUT")),
-        value(CommandBody::Noop, tag_no_case(b"NOOP")),
-        // x-command = "X" atom <experimental command arguments>
-        #[cfg(feature = "ext_id")]
-        map(id, |parameters| CommandBody::Id { parameters }),
-    ))(input)
-}
+/// Note: The command name is recognized first and used to select the parser directly instead of
+/// trying (and backtracking from) all alternatives.
+fn command_body(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
+    // Length of the longest command name, i.e., "XAPPLEPUSHSERVICE".
+    let mut buffer = [0; 17];
 
-// # Command Auth
+    let name = match keyword(input, &mut buffer)? {
+        Some(name) => name,
+        None => return unknown_keyword(input),
+    };
 
-/// ```abnf
-/// command-auth = append /
-///                create /
-///                delete /
-///                examine /
-///                list /
-///                lsub /
-///                rename /
-///                select /
-///                status /
-///                subscribe /
-///                unsubscribe /
-///                idle /         ; RFC 2177
-///                enable /       ; RFC 5161
-///                compress /     ; RFC 4978
-///                getquota /     ; RFC 9208
-///                getquotaroot / ; RFC 9208
-///                setquota /     ; RFC 9208
-///                setmetadata /  ; RFC 5464
-///                getmetadata    ; RFC 5464
-/// ```
-///
-/// Note: Valid only in Authenticated or Selected state
-pub(crate) fn command_auth(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
-    alt((
-        append,
-        create,
-        delete,
-        examine,
-        list,
-        lsub,
-        rename,
-        select,
-        status,
-        subscribe,
-        unsubscribe,
-        idle,
-        enable,
-        compress,
-        getquota,
-        getquotaroot,
-        setquota,
+    let remaining = &input[name.len()..];
+
+    let result = match name {
+        // command-any
+        b"CAPABILITY" => Ok((remaining, CommandBody::Capability)),
+        b"LOGOUT" => Ok((remaining, CommandBody::Logout)),
+        b"NOOP" => Ok((remaining, CommandBody::Noop)),
+        #[cfg(feature = "ext_id")]
+        b"ID" => map(id, |parameters| CommandBody::Id { parameters })(input),
+        // command-auth
+        b"APPEND" => append(input),
+        b"CREATE" => create(input),
+        b"DELETE" => delete(input),
+        b"EXAMINE" => examine(input),
+        b"LIST" => list(input),
+        b"LSUB" => lsub(input),
+        b"RENAME" => rename(input),
+        b"SELECT" => select(input),
+        b"STATUS" => status(input),
+        b"SUBSCRIBE" => subscribe(input),
+        b"UNSUBSCRIBE" => unsubscribe(input),
+        b"IDLE" => idle(input),
+        b"ENABLE" => enable(input),
+        b"COMPRESS" => compress(input),
+        b"GETQUOTA" => getquota(input),
+        b"GETQUOTAROOT" => getquotaroot(input),
+        b"SETQUOTA" => setquota(input),
         #[cfg(feature = "ext_metadata")]
-        setmetadata,
+        b"SETMETADATA" => setmetadata(input),
         #[cfg(feature = "ext_metadata")]
-        getmetadata,
-    ))(input)
+        b"GETMETADATA" => getmetadata(input),
+        #[cfg(feature = "ext_xapplepushservice")]
+        b"XAPPLEPUSHSERVICE" => map(xapplepushservice, |parameters| {
+            CommandBody::XApplePushService { parameters }
+        })(input),
+        #[cfg(feature = "ext_xlist")]
+        b"XLIST" => xlist(input),
+        // command-nonauth
+        b"LOGIN" => login(input),
+        b"AUTHENTICATE" => map(authenticate, |(mechanism, initial_response)| {
+            CommandBody::Authenticate {
+                mechanism,
+                initial_response,
+            }
+        })(input),
+        #[cfg(feature = "starttls")]
+        b"STARTTLS" => Ok((remaining, CommandBody::StartTLS)),
+        // command-select
+        b"CHECK" => Ok((remaining, CommandBody::Check)),
+        b"CLOSE" => Ok((remaining, CommandBody::Close)),
+        b"EXPUNGE" => Ok((remaining, CommandBody::Expunge)),
+        b"UNSELECT" => Ok((remaining, CommandBody::Unselect)),
+        b"COPY" => copy(input),
+        b"FETCH" => fetch(input),
+        b"STORE" => store(input),
+        b"UID" => alt((uid_expunge, uid))(input),
+        b"SEARCH" => search(input),
+        b"SORT" => sort(input),
+        b"THREAD" => thread(input),
+        b"MOVE" => r#move(input),
+        _ => return unknown_keyword(input),
+    };
+
+    // Like `alt`, point to the start of the command name.
+    result.map_err(|error| match error {
+        nom::Err::Error(error) => {
+            nom::Err::Error(IMAPParseError::append(input, ErrorKind::Alt, error))
+        }
+        error => error,
+    })
 }
 
+// # Command Auth
+
 /// `append = "APPEND" SP mailbox [SP flag-list] [SP date-time] SP literal`
 pub(crate) fn append(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
     let mut parser = tuple((
@@ -231,7 +275,6 @@ pub(crate) fn examine(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
 
     let (remaining, mailbox) = parser(input)?;
 
-    #[cfg(feature = "ext_condstore_qresync")]
     let (remaining, parameters) =
         map(opt(select_params), |params| params.unwrap_or_default())(remaining)?;
 
@@ -239,7 +282,6 @@ pub(crate) fn examine(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
         remaining,
         CommandBody::Examine {
             mailbox,
-            #[cfg(feature = "ext_condstore_qresync")]
             parameters,
         },
     ))
@@ -303,7 +345,6 @@ pub(crate) fn select(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
 
     let (remaining, mailbox) = parser(input)?;
 
-    #[cfg(feature = "ext_condstore_qresync")]
     let (remaining, parameters) =
         map(opt(select_params), |params| params.unwrap_or_default())(remaining)?;
 
@@ -311,7 +352,6 @@ pub(crate) fn select(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
         remaining,
         CommandBody::Select {
             mailbox,
-            #[cfg(feature = "ext_condstore_qresync")]
             parameters,
         },
     ))
@@ -322,7 +362,6 @@ pub(crate) fn select(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
 /// ```abnf
 /// select-params = SP "(" select-param *(SP select-param) ")"
 /// ```
-#[cfg(feature = "ext_condstore_qresync")]
 pub(crate) fn select_params(input: &[u8]) -> IMAPResult<&[u8], Vec<SelectParameter>> {
     delimited(tag(" ("), separated_list1(sp, select_param), tag(")"))(input)
 }
@@ -371,10 +410,19 @@ pub(crate) fn select_params(input: &[u8]) -> IMAPResult<&[u8], Vec<SelectParamet
 ///                 ;; known-sequence-set, in ascending order.
 ///                 ;; * is not allowed.
 /// ```
-#[cfg(feature = "ext_condstore_qresync")]
+///
+/// Note: Parameters of unknown extensions are parsed as [`SelectParameter::Other`].
 pub(crate) fn select_param(input: &[u8]) -> IMAPResult<&[u8], SelectParameter> {
     alt((
-        value(SelectParameter::CondStore, tag_no_case("CONDSTORE")),
+        #[cfg(feature = "ext_condstore_qresync")]
+        value(
+            SelectParameter::CondStore,
+            terminated(
+                tag_no_case("CONDSTORE"),
+                not(peek(take_while_m_n(1, 1, is_tagged_label_char))),
+            ),
+        ),
+        #[cfg(feature = "ext_condstore_qresync")]
         map(
             delimited(
                 tag_no_case("QRESYNC ("),
@@ -395,6 +443,10 @@ pub(crate) fn select_param(input: &[u8]) -> IMAPResult<&[u8], SelectParameter> {
                 }
             },
         ),
+        map(
+            map_res(extension_item, SelectParameterOther::try_from),
+            SelectParameter::Other,
+        ),
     ))(input)
 }
 
@@ -437,27 +489,6 @@ pub(crate) fn unsubscribe(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
 
 // # Command NonAuth
 
-/// `command-nonauth = login / authenticate / "STARTTLS"`
-///
-/// Note: Valid only when in Not Authenticated state
-pub(crate) fn command_nonauth(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
-    let mut parser = alt((
-        login,
-        map(authenticate, |(mechanism, initial_response)| {
-            CommandBody::Authenticate {
-                mechanism,
-                initial_response,
-            }
-        }),
-        #[cfg(feature = "starttls")]
-        value(CommandBody::StartTLS, tag_no_case(b"STARTTLS")),
-    ));
-
-    let (remaining, parsed_command_nonauth) = parser(input)?;
-
-    Ok((remaining, parsed_command_nonauth))
-}
-
 /// `login = "LOGIN" SP userid SP password`
 pub(crate) fn login(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
     let mut parser = tuple((tag_no_case(b"LOGIN"), sp, userid, sp, password));
@@ -522,34 +553,6 @@ pub(crate) fn authenticate(
 
 // # Command Select
 
-/// `command-select = "CHECK" /
-///                   "CLOSE" /
-///                   "EXPUNGE" /
-///                   copy /
-///                   fetch /
-///                   store /
-///                   uid /
-///                   search`
-///
-/// Note: Valid only when in Selected state
-pub(crate) fn command_select(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
-    alt((
-        value(CommandBody::Check, tag_no_case(b"CHECK")),
-        value(CommandBody::Close, tag_no_case(b"CLOSE")),
-        value(CommandBody::Expunge, tag_no_case(b"EXPUNGE")),
-        uid_expunge,
-        copy,
-        fetch,
-        store,
-        uid,
-        search,
-        sort,
-        thread,
-        value(CommandBody::Unselect, tag_no_case(b"UNSELECT")),
-        r#move,
-    ))(input)
-}
-
 /// `copy = "COPY" SP sequence-set SP mailbox`
 pub(crate) fn copy(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
     let mut parser = tuple((tag_no_case(b"COPY"), sp, sequence_set, sp, mailbox));
@@ -761,7 +764,10 @@ pub(crate) fn store_att_flags(
             }),
         )),
         sp,
-        alt((flag_list, separated_list1(sp, flag))),
+        verify(
+            alt((flag_list, separated_list1(sp, flag))),
+            |flags: &Vec<Flag>| flags.iter().all(Flag::is_storable),
+        ),
     ));
 
     let (remaining, ((store_type, _, store_response), _, flag_list)) = parser(input)?;
@@ -800,6 +806,8 @@ mod tests {
     use imap_types::{
         core::Tag,
         fetch::{MessageDataItemName, Section},
+        mailbox::Mailbox,
+        sequence::SequenceSet,
     };
 
     use super::*;
@@ -867,6 +875,69 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_command_body() {
+        let tests = [
+            (b"cApAbIlItY\r\n".as_ref(), CommandBody::Capability),
+            (
+                b"uid expunge 1:*\r\n",
+                CommandBody::ExpungeUid {
+                    sequence_set: SequenceSet::try_from("1:*").unwrap(),
+                },
+            ),
+            (
+                b"UID COPY 1 inbox\r\n",
+                CommandBody::Copy {
+                    sequence_set: SequenceSet::try_from("1").unwrap(),
+                    mailbox: Mailbox::Inbox,
+                    uid: true,
+                },
+            ),
+            (
+                b"select inbox\r\n",
+                CommandBody::Select {
+                    mailbox: Mailbox::Inbox,
+                    parameters: Vec::default(),
+                },
+            ),
+            (b"unselect\r\n", CommandBody::Unselect),
+        ];
+
+        for (test, expected) in tests {
+            let (remaining, got) = command_body(test).unwrap();
+            assert_eq!(remaining, b"\r\n");
+
+            assert_eq!(got, expected);
+        }
+
+        // Incomplete command name.
+        assert!(matches!(command_body(b"NOO"), Err(nom::Err::Incomplete(_))));
+
+        // Unknown command names.
+        for test in [
+            b"NOOP_X\r\n".as_ref(),
+            b"FOO\r\n",
+            b"XAPPLEPUSHSERVICEX\r\n",
+        ] {
+            assert!(matches!(command_body(test), Err(nom::Err::Error(_))));
+        }
+    }
+
+    #[test]
+    fn test_parse_store_att_flags() {
+        let (_, (kind, response, flags)) = store_att_flags(b"+FLAGS.SILENT (\\Seen)\r\n").unwrap();
+        assert_eq!(kind, StoreType::Add);
+        assert_eq!(response, StoreResponse::Silent);
+        assert_eq!(flags, vec![Flag::Seen]);
+
+        // `\Recent` can't be stored.
+        assert!(matches!(
+            store_att_flags(b"FLAGS (\\Seen \\Recent)\r\n"),
+            Err(nom::Err::Error(_))
+        ));
+        assert!(store_att_flags(b"-FLAGS \\recent\r\n").is_err());
+    }
+
     #[test]
     fn test_that_empty_ir_is_encoded_correctly() {
         let command = Command::new(
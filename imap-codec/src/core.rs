@@ -1,9 +1,6 @@
-use std::{borrow::Cow, num::NonZeroU32, str::from_utf8};
+use alloc::{borrow::Cow, vec::Vec};
+use core::{num::NonZeroU32, str::from_utf8};
 
-#[cfg(not(feature = "quirk_crlf_relaxed"))]
-use abnf_core::streaming::crlf;
-#[cfg(feature = "quirk_crlf_relaxed")]
-use abnf_core::streaming::crlf_relaxed as crlf;
 use abnf_core::{is_alpha, is_digit, streaming::dquote};
 use base64::{engine::general_purpose::STANDARD as _base64, Engine};
 use imap_types::{
@@ -12,21 +9,176 @@ use imap_types::{
         QuotedChar, Tag, Text,
     },
     utils::{
-        indicators::{is_astring_char, is_atom_char, is_quoted_specials, is_text_char},
+        indicators::{
+            is_astring_char, is_atom_char, is_quoted_specials, is_tagged_label_char, is_text_char,
+        },
         unescape_quoted,
     },
 };
+use memchr::{memchr, memchr2, memchr3};
 #[cfg(feature = "fuzz")]
 use nom::IResult;
 use nom::{
     branch::alt,
-    bytes::streaming::{escaped, tag, tag_no_case, take, take_while, take_while1, take_while_m_n},
-    character::streaming::{char, digit1, one_of},
+    bytes::streaming::{tag, tag_no_case, take, take_while, take_while1, take_while_m_n},
+    character::streaming::{char, digit1},
     combinator::{map, map_res, opt, recognize},
+    error::{ErrorKind, ParseError},
     sequence::{delimited, terminated, tuple},
+    Needed, Parser,
 };
 
-use crate::decode::{IMAPErrorKind, IMAPParseError, IMAPResult};
+use crate::decode::{max_literal_size, quirks, IMAPErrorKind, IMAPParseError, IMAPResult};
+
+/// `CRLF = CR LF`
+///
+/// Note: Also accepts a bare LF when [`Quirks::crlf_relaxed`](crate::decode::Quirks::crlf_relaxed)
+/// is enabled.
+pub(crate) fn crlf(input: &[u8]) -> IMAPResult<&[u8], &[u8]> {
+    // Match the (at most two) bytes directly instead of going through `tag`.
+    match input {
+        [b'\r', b'\n', ..] => Ok((&input[2..], &input[..2])),
+        [b'\n', ..] if quirks().crlf_relaxed => Ok((&input[1..], &input[..1])),
+        [] | [b'\r'] => Err(nom::Err::Incomplete(Needed::new(2 - input.len()))),
+        _ => Err(nom::Err::Error(IMAPParseError::from_error_kind(
+            input,
+            ErrorKind::CrLf,
+        ))),
+    }
+}
+
+// ----- lists -----
+
+/// Buffer used to collect list elements.
+///
+/// With the `perf` feature, the first elements are collected on the stack. Short lists, e.g., flags
+/// or body parameters, are then moved into a `Vec` with a single (exact) allocation.
+#[cfg(feature = "perf")]
+type ListBuffer<O> = smallvec::SmallVec<[O; 8]>;
+
+#[cfg(not(feature = "perf"))]
+type ListBuffer<O> = Vec<O>;
+
+#[cfg(feature = "perf")]
+fn into_vec<O>(buffer: ListBuffer<O>) -> Vec<O> {
+    buffer.into_vec()
+}
+
+#[cfg(not(feature = "perf"))]
+fn into_vec<O>(buffer: ListBuffer<O>) -> Vec<O> {
+    buffer
+}
+
+/// Same as [`nom::multi::separated_list0`] but uses a [`ListBuffer`].
+pub(crate) fn separated_list0<'a, O, O2, G, F>(
+    sep: G,
+    f: F,
+) -> impl FnMut(&'a [u8]) -> IMAPResult<'a, &'a [u8], Vec<O>>
+where
+    G: Parser<&'a [u8], O2, IMAPParseError<'a, &'a [u8]>>,
+    F: Parser<&'a [u8], O, IMAPParseError<'a, &'a [u8]>>,
+{
+    separated_list(false, sep, f)
+}
+
+/// Same as [`nom::multi::separated_list1`] but uses a [`ListBuffer`].
+pub(crate) fn separated_list1<'a, O, O2, G, F>(
+    sep: G,
+    f: F,
+) -> impl FnMut(&'a [u8]) -> IMAPResult<'a, &'a [u8], Vec<O>>
+where
+    G: Parser<&'a [u8], O2, IMAPParseError<'a, &'a [u8]>>,
+    F: Parser<&'a [u8], O, IMAPParseError<'a, &'a [u8]>>,
+{
+    separated_list(true, sep, f)
+}
+
+fn separated_list<'a, O, O2, G, F>(
+    at_least_one: bool,
+    mut sep: G,
+    mut f: F,
+) -> impl FnMut(&'a [u8]) -> IMAPResult<'a, &'a [u8], Vec<O>>
+where
+    G: Parser<&'a [u8], O2, IMAPParseError<'a, &'a [u8]>>,
+    F: Parser<&'a [u8], O, IMAPParseError<'a, &'a [u8]>>,
+{
+    move |mut input: &'a [u8]| {
+        let mut buffer = ListBuffer::new();
+
+        match f.parse(input) {
+            Err(nom::Err::Error(_)) if !at_least_one => return Ok((input, into_vec(buffer))),
+            Err(error) => return Err(error),
+            Ok((remaining, item)) => {
+                buffer.push(item);
+                input = remaining;
+            }
+        }
+
+        loop {
+            let remaining = match sep.parse(input) {
+                Err(nom::Err::Error(_)) => return Ok((input, into_vec(buffer))),
+                Err(error) => return Err(error),
+                Ok((remaining, _)) => remaining,
+            };
+
+            // Infinite loop check: the separator must always consume.
+            if remaining.len() == input.len() {
+                return Err(nom::Err::Error(IMAPParseError::from_error_kind(
+                    remaining,
+                    ErrorKind::SeparatedList,
+                )));
+            }
+
+            match f.parse(remaining) {
+                Err(nom::Err::Error(_)) => return Ok((input, into_vec(buffer))),
+                Err(error) => return Err(error),
+                Ok((remaining, item)) => {
+                    buffer.push(item);
+                    input = remaining;
+                }
+            }
+        }
+    }
+}
+
+// ----- keywords -----
+
+/// Recognize a keyword, i.e., `*tagged-label-char`, of at most `N` bytes.
+///
+/// The keyword is copied into `buffer` and uppercased, so that callers can dispatch on it with a
+/// single `match` instead of trying many `tag_no_case` alternatives. Returns `None` when the
+/// keyword is longer than `N` bytes (and thus unknown to the caller), and `Incomplete` when the
+/// keyword is not yet terminated.
+pub(crate) fn keyword<'a, 'b, const N: usize>(
+    input: &'a [u8],
+    buffer: &'b mut [u8; N],
+) -> Result<Option<&'b [u8]>, nom::Err<IMAPParseError<'a, &'a [u8]>>> {
+    match input
+        .iter()
+        .take(N + 1)
+        .position(|b| !is_tagged_label_char(*b))
+    {
+        Some(end) => {
+            let keyword = &mut buffer[..end];
+            keyword.copy_from_slice(&input[..end]);
+            keyword.make_ascii_uppercase();
+
+            Ok(Some(keyword))
+        }
+        None if input.len() > N => Ok(None),
+        None => Err(nom::Err::Incomplete(Needed::Unknown)),
+    }
+}
+
+/// Error for an unknown keyword.
+///
+/// Like `alt`, the error points to the start of the keyword.
+pub(crate) fn unknown_keyword<'a, O>(input: &'a [u8]) -> IMAPResult<'a, &'a [u8], O> {
+    Err(nom::Err::Error(IMAPParseError::from_error_kind(
+        input,
+        ErrorKind::Alt,
+    )))
+}
 
 // ----- number -----
 
@@ -56,7 +208,8 @@ pub(crate) fn number64(input: &[u8]) -> IMAPResult<&[u8], u64> {
         //
         // `unwrap` is safe because `1*DIGIT` contains ASCII-only characters.
         map(digit1, |val| from_utf8(val).unwrap()),
-        str::parse::<u64>,
+        // Parse as `i64` to reject numbers that don't fit into 63 bits.
+        |val: &str| val.parse::<i64>().map(|val| val as u64),
     )(input)
 }
 
@@ -78,26 +231,80 @@ pub(crate) fn string(input: &[u8]) -> IMAPResult<&[u8], IString> {
 ///
 /// This function only allocates a new String, when needed, i.e. when
 /// quoted chars need to be replaced.
+///
+/// Note: Also accepts 8-bit characters as long as the quoted string is valid UTF-8 when
+/// [`Quirks::eight_bit_text`](crate::decode::Quirks::eight_bit_text) is enabled.
 pub(crate) fn quoted(input: &[u8]) -> IMAPResult<&[u8], Quoted> {
-    let mut parser = tuple((
+    #[cfg(feature = "quirk_8bit_text")]
+    if quirks().eight_bit_text {
+        return quoted_8bit(input);
+    }
+
+    let (remaining, quoted) = delimited(dquote, |input| quoted_chars(input, false), dquote)(input)?;
+
+    // # Saftey
+    //
+    // `unwrap` is safe because `quoted_chars` only accepts ASCII-only characters.
+    let quoted = from_utf8(quoted).unwrap();
+
+    Ok((remaining, Quoted::unvalidated(unescape_quoted(quoted))))
+}
+
+#[cfg(feature = "quirk_8bit_text")]
+fn quoted_8bit(input: &[u8]) -> IMAPResult<&[u8], Quoted> {
+    let (remaining, quoted) = delimited(
         dquote,
-        map(
-            escaped(
-                take_while1(is_any_text_char_except_quoted_specials),
-                '\\',
-                one_of("\\\""),
-            ),
-            // # Saftey
-            //
-            // `unwrap` is safe because val contains ASCII-only characters.
-            |val| from_utf8(val).unwrap(),
-        ),
+        map_res(|input| quoted_chars(input, true), from_utf8),
         dquote,
-    ));
+    )(input)?;
 
-    let (remaining, (_, quoted, _)) = parser(input)?;
+    if quoted.is_ascii() {
+        Ok((remaining, Quoted::unvalidated(unescape_quoted(quoted))))
+    } else {
+        log::warn!("Rectified 8-bit characters in quoted string");
 
-    Ok((remaining, Quoted::unvalidated(unescape_quoted(quoted))))
+        Ok((remaining, Quoted::unvalidated_8bit(unescape_quoted(quoted))))
+    }
+}
+
+/// `*QUOTED-CHAR`
+///
+/// Returns the (still escaped) content of a quoted string. 8-bit characters are accepted when
+/// `eight_bit` is set.
+fn quoted_chars(input: &[u8], eight_bit: bool) -> IMAPResult<&[u8], &[u8]> {
+    let mut position = 0;
+
+    loop {
+        let rest = &input[position..];
+
+        // Jump to the next quoted-special. All bytes in front of it must be `TEXT-CHAR`s.
+        let special = memchr2(b'"', b'\\', rest);
+
+        if let Some(end) = find_non_text_char(&rest[..special.unwrap_or(rest.len())], eight_bit) {
+            // Let the caller fail on the missing `DQUOTE`.
+            return Ok((&rest[end..], &input[..position + end]));
+        }
+
+        let special = match special {
+            Some(special) => special,
+            None => return Err(nom::Err::Incomplete(Needed::new(1))),
+        };
+
+        if rest[special] == b'"' {
+            return Ok((&rest[special..], &input[..position + special]));
+        }
+
+        match rest.get(special + 1) {
+            Some(b'"' | b'\\') => position += special + 2,
+            Some(_) => {
+                return Err(nom::Err::Error(IMAPParseError::from_error_kind(
+                    &rest[special + 1..],
+                    ErrorKind::OneOf,
+                )))
+            }
+            None => return Err(nom::Err::Incomplete(Needed::new(1))),
+        }
+    }
 }
 
 /// `QUOTED-CHAR = <any TEXT-CHAR except quoted-specials> / "\" quoted-specials`
@@ -157,6 +364,8 @@ pub(crate) fn literal(input: &[u8]) -> IMAPResult<&[u8], Literal> {
         crlf,
     )(input)?;
 
+    enforce_max_literal_size(input, length)?;
+
     // Signal that an continuation request could be required.
     // Note: This doesn't trigger when there is data following the literal prefix.
     if remaining.is_empty() {
@@ -173,16 +382,37 @@ pub(crate) fn literal(input: &[u8]) -> IMAPResult<&[u8], Literal> {
 
     let (remaining, data) = take(length)(remaining)?;
 
-    match Literal::try_from(data) {
-        Ok(mut literal) => {
-            literal.set_mode(mode);
-
-            Ok((remaining, literal))
-        }
-        Err(_) => Err(nom::Err::Failure(IMAPParseError {
+    // Literals can be large. Thus, search for NUL via `memchr` instead of `Literal::validate`.
+    if memchr(b'\0', data).is_some() {
+        return Err(nom::Err::Failure(IMAPParseError {
             input,
             kind: IMAPErrorKind::LiteralContainsNull,
-        })),
+        }));
+    }
+
+    let mut literal = Literal::unvalidated(data);
+    literal.set_mode(mode);
+
+    Ok((remaining, literal))
+}
+
+/// Rejects a literal of `length` bytes if it exceeds the maximum literal size of the codec.
+///
+/// This is checked right after the announcement, i.e., regardless of whether the literal data
+/// was already received.
+pub(crate) fn enforce_max_literal_size(input: &[u8], length: u32) -> IMAPResult<&[u8], ()> {
+    match max_literal_size() {
+        Some(max_literal_size) if length > max_literal_size => {
+            Err(nom::Err::Failure(IMAPParseError {
+                input,
+                kind: IMAPErrorKind::LiteralTooLarge {
+                    // See `IMAPErrorKind::Literal`.
+                    tag: None,
+                    length,
+                },
+            }))
+        }
+        _ => Ok((input, ())),
     }
 }
 
@@ -198,7 +428,7 @@ pub(crate) fn astring(input: &[u8]) -> IMAPResult<&[u8], AString> {
             //   * contain ASCII-only characters, i.e., `from_utf8` will return `Ok`.
             //   * are valid according to `AtomExt::verify(), i.e., `unvalidated` is safe.
             AString::Atom(AtomExt::unvalidated(Cow::Borrowed(
-                std::str::from_utf8(bytes).unwrap(),
+                core::str::from_utf8(bytes).unwrap(),
             )))
         }),
         map(string, AString::String),
@@ -241,13 +471,68 @@ pub(crate) fn nil(input: &[u8]) -> IMAPResult<&[u8], &[u8]> {
 // ----- text -----
 
 /// `text = 1*TEXT-CHAR`
+///
+/// Note: Also accepts 8-bit characters as long as the text is valid UTF-8 when
+/// [`Quirks::eight_bit_text`](crate::decode::Quirks::eight_bit_text) is enabled.
 pub(crate) fn text(input: &[u8]) -> IMAPResult<&[u8], Text> {
-    map(take_while1(is_text_char), |bytes|
+    #[cfg(feature = "quirk_8bit_text")]
+    if quirks().eight_bit_text {
+        return text_8bit(input);
+    }
+
+    map(
+        |input| text_chars(input, false),
+        |bytes|
         // # Safety
-        // 
-        // `is_text_char` makes sure that the sequence of bytes
+        //
+        //
+        // `text_chars` makes sure that the sequence of bytes
         // is always valid ASCII. Thus, it is also valid UTF-8.
-        Text::unvalidated(from_utf8(bytes).unwrap()))(input)
+        Text::unvalidated(from_utf8(bytes).unwrap()),
+    )(input)
+}
+
+#[cfg(feature = "quirk_8bit_text")]
+fn text_8bit(input: &[u8]) -> IMAPResult<&[u8], Text> {
+    let (remaining, text) = map_res(|input| text_chars(input, true), from_utf8)(input)?;
+
+    if text.is_ascii() {
+        Ok((remaining, Text::unvalidated(text)))
+    } else {
+        log::warn!("Rectified 8-bit characters in text");
+
+        Ok((remaining, Text::unvalidated_8bit(text)))
+    }
+}
+
+/// `1*TEXT-CHAR`
+///
+/// 8-bit characters are accepted when `eight_bit` is set.
+fn text_chars(input: &[u8], eight_bit: bool) -> IMAPResult<&[u8], &[u8]> {
+    match find_non_text_char(input, eight_bit) {
+        Some(0) => Err(nom::Err::Error(IMAPParseError::from_error_kind(
+            input,
+            ErrorKind::TakeWhile1,
+        ))),
+        Some(end) => Ok((&input[end..], &input[..end])),
+        None => Err(nom::Err::Incomplete(Needed::new(1))),
+    }
+}
+
+/// Returns the position of the first byte that is not a `TEXT-CHAR`.
+///
+/// 8-bit characters are accepted when `eight_bit` is set.
+fn find_non_text_char(input: &[u8], eight_bit: bool) -> Option<usize> {
+    // Search for NUL, CR, and LF first ...
+    let end = memchr3(b'\0', b'\r', b'\n', input);
+    let chars = &input[..end.unwrap_or(input.len())];
+
+    // ... and only then for 8-bit characters in front of them.
+    if eight_bit || chars.is_ascii() {
+        end
+    } else {
+        chars.iter().position(|byte| !byte.is_ascii())
+    }
 }
 
 // ----- base64 -----
@@ -347,10 +632,11 @@ mod tests {
 
         let (rem, val) = quoted(br#""Hello \"World\""???"#).unwrap();
         assert_eq!(rem, br#"???"#);
-        // Should it be this (Hello \"World\") ...
-        //assert_eq!(val, r#"Hello \"World\""#);
-        // ... or this (Hello "World")?
+        // The decoded value is unescaped ...
         assert_eq!(val, Quoted::try_from("Hello \"World\"").unwrap());
+        assert_eq!(val.unescaped(), r#"Hello "World""#);
+        // ... and escaped again for the wire.
+        assert_eq!(val.escaped(), r#"Hello \"World\""#);
 
         // Test Incomplete
         assert!(matches!(quoted(br#""#), Err(nom::Err::Incomplete(_))));
@@ -364,6 +650,85 @@ mod tests {
         assert!(matches!(quoted(br#"\"#), Err(nom::Err::Error(_))));
     }
 
+    #[test]
+    fn test_quoted_scanning() {
+        // Escapes in front of, between, and after the characters.
+        let (rem, val) = quoted(br#""\\a\"\\b\"\\"???"#).unwrap();
+        assert_eq!(rem, b"???");
+        assert_eq!(val.inner(), r#"\a"\b"\"#);
+
+        // Control characters and line endings end the quoted string without a `DQUOTE`.
+        assert!(matches!(quoted(b"\"a\x00b\""), Err(nom::Err::Error(_))));
+        assert!(matches!(quoted(b"\"a\r\nb\""), Err(nom::Err::Error(_))));
+        assert!(matches!(quoted(b"\"a\\\r\""), Err(nom::Err::Error(_))));
+
+        // ... even before the quoted string is complete.
+        assert!(matches!(quoted(b"\"a\nb"), Err(nom::Err::Error(_))));
+        assert!(matches!(quoted(b"\"a\\\""), Err(nom::Err::Incomplete(_))));
+    }
+
+    #[test]
+    fn test_text() {
+        let (rem, val) = text(b"Hello, World!\r\n").unwrap();
+        assert_eq!(rem, b"\r\n");
+        assert_eq!(val.inner(), "Hello, World!");
+
+        let (rem, val) = text(b"Hello\x00World").unwrap();
+        assert_eq!(rem, b"\x00World");
+        assert_eq!(val.inner(), "Hello");
+
+        assert!(matches!(text(b"\r\n"), Err(nom::Err::Error(_))));
+        assert!(matches!(text(b""), Err(nom::Err::Incomplete(_))));
+        assert!(matches!(text(b"Hello"), Err(nom::Err::Incomplete(_))));
+    }
+
+    #[test]
+    fn test_crlf() {
+        assert_eq!(crlf(b"\r\nxxx").unwrap(), (&b"xxx"[..], &b"\r\n"[..]));
+        assert!(matches!(crlf(b""), Err(nom::Err::Incomplete(_))));
+        assert!(matches!(crlf(b"\r"), Err(nom::Err::Incomplete(_))));
+        assert!(matches!(crlf(b"\rx"), Err(nom::Err::Error(_))));
+        assert!(matches!(crlf(b"x\r\n"), Err(nom::Err::Error(_))));
+
+        let result = crlf(b"\nxxx");
+        if quirks().crlf_relaxed {
+            assert_eq!(result.unwrap(), (&b"xxx"[..], &b"\n"[..]));
+        } else {
+            assert!(matches!(result, Err(nom::Err::Error(_))));
+        }
+    }
+
+    #[test]
+    fn test_quoted_8bit() {
+        let result = quoted("\"Grüße\"???".as_bytes());
+
+        #[cfg(not(feature = "quirk_8bit_text"))]
+        assert!(result.is_err());
+        #[cfg(feature = "quirk_8bit_text")]
+        {
+            let (rem, val) = result.unwrap();
+            assert_eq!(rem, b"???");
+            assert_eq!(val.inner(), "Grüße");
+        }
+
+        // Latin-1 is never accepted.
+        assert!(quoted(b"\"Gr\xfc\xdfe\"???").is_err());
+    }
+
+    #[test]
+    fn test_text_8bit() {
+        let result = text("Grüße\r\n".as_bytes());
+
+        #[cfg(not(feature = "quirk_8bit_text"))]
+        assert_eq!(result.unwrap().1.inner(), "Gr");
+        #[cfg(feature = "quirk_8bit_text")]
+        {
+            let (rem, val) = result.unwrap();
+            assert_eq!(rem, b"\r\n");
+            assert_eq!(val.inner(), "Grüße");
+        }
+    }
+
     #[test]
     fn test_quoted_char() {
         let (rem, val) = quoted_char(b"\\\"xxx").unwrap();
@@ -395,6 +760,7 @@ mod tests {
     fn test_literal() {
         assert!(literal(b"{3}\r\n123").is_ok());
         assert!(literal(b"{3}\r\n1\x003").is_err());
+        assert!(literal(b"{3}\r\n12\x00").is_err());
 
         let (rem, val) = literal(b"{3}\r\n123xxx").unwrap();
         assert_eq!(rem, b"xxx");
@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use abnf_core::streaming::sp;
 use imap_types::{
     core::NString,
@@ -6,14 +8,14 @@ use imap_types::{
 use nom::{
     branch::alt,
     bytes::streaming::tag,
-    combinator::map,
-    multi::many1,
-    sequence::{delimited, tuple},
+    combinator::{cond, map, verify},
+    multi::many0,
+    sequence::{delimited, preceded, tuple},
 };
 
 use crate::{
     core::{nil, nstring},
-    decode::IMAPResult,
+    decode::{quirks, IMAPResult},
 };
 
 /// ```abnf
@@ -113,48 +115,56 @@ pub(crate) fn env_subject(input: &[u8]) -> IMAPResult<&[u8], NString> {
 
 /// `env-from = "(" 1*address ")" / nil`
 pub(crate) fn env_from(input: &[u8]) -> IMAPResult<&[u8], Vec<Address>> {
-    alt((
-        delimited(tag(b"("), many1(address), tag(b")")),
-        map(nil, |_| Vec::new()),
-    ))(input)
+    address_list(input)
 }
 
 /// `env-sender = "(" 1*address ")" / nil`
 pub(crate) fn env_sender(input: &[u8]) -> IMAPResult<&[u8], Vec<Address>> {
-    alt((
-        delimited(tag(b"("), many1(address), tag(b")")),
-        map(nil, |_| Vec::new()),
-    ))(input)
+    address_list(input)
 }
 
 /// `env-reply-to = "(" 1*address ")" / nil`
 pub(crate) fn env_reply_to(input: &[u8]) -> IMAPResult<&[u8], Vec<Address>> {
-    alt((
-        delimited(tag(b"("), many1(address), tag(b")")),
-        map(nil, |_| Vec::new()),
-    ))(input)
+    address_list(input)
 }
 
 /// `env-to = "(" 1*address ")" / nil`
 pub(crate) fn env_to(input: &[u8]) -> IMAPResult<&[u8], Vec<Address>> {
-    alt((
-        delimited(tag(b"("), many1(address), tag(b")")),
-        map(nil, |_| Vec::new()),
-    ))(input)
+    address_list(input)
 }
 
 /// `env-cc = "(" 1*address ")" / nil`
 pub(crate) fn env_cc(input: &[u8]) -> IMAPResult<&[u8], Vec<Address>> {
-    alt((
-        delimited(tag(b"("), many1(address), tag(b")")),
-        map(nil, |_| Vec::new()),
-    ))(input)
+    address_list(input)
 }
 
 /// `env-bcc = "(" 1*address ")" / nil`
 pub(crate) fn env_bcc(input: &[u8]) -> IMAPResult<&[u8], Vec<Address>> {
+    address_list(input)
+}
+
+/// `"(" 1*address ")" / nil`
+///
+/// Note: Also accepts an empty list, i.e., `()`, with
+/// [`Quirks::empty_lists`](crate::decode::Quirks::empty_lists).
+fn address_list(input: &[u8]) -> IMAPResult<&[u8], Vec<Address>> {
+    let empty_lists = quirks().empty_lists;
+
+    let addresses = map(
+        verify(many0(address), move |addresses: &Vec<Address>| {
+            empty_lists || !addresses.is_empty()
+        }),
+        |addresses| {
+            if addresses.is_empty() {
+                log::warn!("Rectified empty address list");
+            }
+
+            addresses
+        },
+    );
+
     alt((
-        delimited(tag(b"("), many1(address), tag(b")")),
+        delimited(tag(b"("), addresses, tag(b")")),
         map(nil, |_| Vec::new()),
     ))(input)
 }
@@ -178,16 +188,15 @@ pub(crate) fn env_message_id(input: &[u8]) -> IMAPResult<&[u8], NString> {
 ///             addr-host
 ///             ")"`
 pub(crate) fn address(input: &[u8]) -> IMAPResult<&[u8], Address> {
-    #[cfg_attr(feature = "quirk_spaces_between_addresses", allow(unused_mut))]
-    let mut parser = delimited(
-        tag(b"("),
-        tuple((addr_name, sp, addr_adl, sp, addr_mailbox, sp, addr_host)),
-        tag(b")"),
+    let mut parser = preceded(
+        cond(quirks().spaces_between_addresses, many0(sp)),
+        delimited(
+            tag(b"("),
+            tuple((addr_name, sp, addr_adl, sp, addr_mailbox, sp, addr_host)),
+            tag(b")"),
+        ),
     );
 
-    #[cfg(feature = "quirk_spaces_between_addresses")]
-    let mut parser = nom::sequence::preceded(nom::multi::many0(sp), parser);
-
     let (remaining, (name, _, adl, _, mailbox, _, host)) = parser(input)?;
 
     Ok((
@@ -263,4 +272,14 @@ mod tests {
         );
         assert_eq!(rem, b"");
     }
+
+    #[test]
+    fn test_parse_address_list_empty() {
+        let result = env_from(b"() ");
+
+        #[cfg(not(feature = "quirk_empty_lists"))]
+        assert!(result.is_err());
+        #[cfg(feature = "quirk_empty_lists")]
+        assert_eq!(result.unwrap(), (b" ".as_ref(), vec![]));
+    }
 }
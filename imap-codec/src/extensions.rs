@@ -3,6 +3,8 @@ pub mod compress;
 #[cfg(feature = "ext_condstore_qresync")]
 pub mod condstore_qresync;
 pub mod enable;
+#[cfg(feature = "ext_gmail")]
+pub mod gmail;
 #[cfg(feature = "ext_id")]
 pub mod id;
 pub mod idle;
@@ -12,6 +14,11 @@ pub mod metadata;
 pub mod r#move;
 pub mod quota;
 pub mod sort;
+pub mod tagged_ext;
 pub mod thread;
 pub mod uidplus;
 pub mod unselect;
+#[cfg(feature = "ext_xapplepushservice")]
+pub mod xapplepushservice;
+#[cfg(feature = "ext_xlist")]
+pub mod xlist;
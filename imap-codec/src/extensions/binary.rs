@@ -1,11 +1,8 @@
-use std::{borrow::Cow, io::Write, num::NonZeroU32};
+use alloc::vec::Vec;
+use core::num::NonZeroU32;
 
-#[cfg(not(feature = "quirk_crlf_relaxed"))]
-use abnf_core::streaming::crlf;
-#[cfg(feature = "quirk_crlf_relaxed")]
-use abnf_core::streaming::crlf_relaxed as crlf;
 use imap_types::{
-    core::LiteralMode,
+    core::{LiteralData, LiteralMode},
     extensions::binary::{Literal8, LiteralOrLiteral8},
 };
 use nom::{
@@ -16,9 +13,9 @@ use nom::{
 };
 
 use crate::{
-    core::{number, nz_number},
+    core::{crlf, enforce_max_literal_size, number, nz_number},
     decode::{IMAPErrorKind, IMAPParseError, IMAPResult},
-    encode::{EncodeContext, EncodeIntoContext},
+    encode::{EncodeContext, EncodeIntoContext, WriteError},
     fetch::section_part,
 };
 
@@ -44,6 +41,8 @@ pub(crate) fn literal8(input: &[u8]) -> IMAPResult<&[u8], Literal8> {
         crlf,
     )(input)?;
 
+    enforce_max_literal_size(input, length)?;
+
     // Signal that an continuation request could be required.
     // Note: This doesn't trigger when there is data following the literal prefix.
     if remaining.is_empty() {
@@ -63,14 +62,14 @@ pub(crate) fn literal8(input: &[u8]) -> IMAPResult<&[u8], Literal8> {
     Ok((
         remaining,
         Literal8 {
-            data: Cow::Borrowed(data),
+            data: LiteralData::Borrowed(data),
             mode,
         },
     ))
 }
 
 impl EncodeIntoContext for LiteralOrLiteral8<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             LiteralOrLiteral8::Literal(lit) => lit.encode_ctx(ctx),
             LiteralOrLiteral8::Literal8(lit8) => lit8.encode_ctx(ctx),
@@ -79,15 +78,19 @@ impl EncodeIntoContext for LiteralOrLiteral8<'_> {
 }
 
 impl EncodeIntoContext for Literal8<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
-        match self.mode {
-            LiteralMode::Sync => write!(ctx, "~{{{}}}\r\n", self.data.len())?,
-            LiteralMode::NonSync => write!(ctx, "~{{{}+}}\r\n", self.data.len())?,
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        let mode = ctx.literal_mode(self.mode);
+
+        ctx.write_all(b"~{")?;
+        ctx.write_number(self.data.len() as u64)?;
+        match mode {
+            LiteralMode::Sync => ctx.write_all(b"}\r\n")?,
+            LiteralMode::NonSync => ctx.write_all(b"+}\r\n")?,
         }
 
         ctx.push_line();
         ctx.write_all(&self.data)?;
-        ctx.push_literal(self.mode);
+        ctx.push_literal(mode);
 
         Ok(())
     }
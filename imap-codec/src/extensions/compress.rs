@@ -6,8 +6,6 @@
 // capability     =/ "COMPRESS=" algorithm
 // resp-text-code =/ "COMPRESSIONACTIVE"
 
-use std::io::Write;
-
 use imap_types::{command::CommandBody, extensions::compress::CompressionAlgorithm};
 use nom::{
     bytes::streaming::tag_no_case,
@@ -17,7 +15,7 @@ use nom::{
 
 use crate::{
     decode::IMAPResult,
-    encode::{EncodeContext, EncodeIntoContext},
+    encode::{EncodeContext, EncodeIntoContext, WriteError},
 };
 
 /// `algorithm = "DEFLATE"`
@@ -33,7 +31,7 @@ pub(crate) fn compress(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
 }
 
 impl EncodeIntoContext for CompressionAlgorithm {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         write!(ctx, "{}", self)
     }
 }
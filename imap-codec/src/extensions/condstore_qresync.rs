@@ -1,8 +1,6 @@
-use std::num::NonZeroU64;
-
 use abnf_core::streaming::sp;
 #[cfg(feature = "ext_condstore_qresync")]
-use imap_types::extensions::condstore_qresync::{AttributeFlag, EntryTypeReq};
+use imap_types::extensions::condstore_qresync::{AttributeFlag, EntryTypeReq, ModSeq};
 use nom::{
     branch::alt,
     bytes::streaming::{tag, tag_no_case},
@@ -19,23 +17,28 @@ use crate::{
 /// ```abnf
 /// mod-sequence-valzer = "0" / mod-sequence-value
 /// ```
-pub(crate) fn mod_sequence_valzer(input: &[u8]) -> IMAPResult<&[u8], u64> {
-    number64(input)
+///
+/// Note: `0` is returned as `None`.
+pub(crate) fn mod_sequence_valzer(input: &[u8]) -> IMAPResult<&[u8], Option<ModSeq>> {
+    map_res(number64, |value| match value {
+        0 => Ok(None),
+        value => ModSeq::try_from(value).map(Some),
+    })(input)
 }
 
-/// Positive unsigned 64-bit integer (mod-sequence) (1 <= n < 18,446,744,073,709,551,615)
+/// Positive unsigned 63-bit integer (mod-sequence) (1 <= n <= 9,223,372,036,854,775,807)
 ///
 /// ```abnf
 /// mod-sequence-value  = 1*DIGIT
 /// ```
-pub(crate) fn mod_sequence_value(input: &[u8]) -> IMAPResult<&[u8], NonZeroU64> {
-    map_res(number64, NonZeroU64::try_from)(input)
+pub(crate) fn mod_sequence_value(input: &[u8]) -> IMAPResult<&[u8], ModSeq> {
+    map_res(number64, ModSeq::try_from)(input)
 }
 
 /// ```abnf
 /// search-sort-mod-seq = "(" "MODSEQ" SP mod-sequence-value ")"
 /// ```
-pub(crate) fn search_sort_mod_seq(input: &[u8]) -> IMAPResult<&[u8], NonZeroU64> {
+pub(crate) fn search_sort_mod_seq(input: &[u8]) -> IMAPResult<&[u8], ModSeq> {
     delimited(
         char('('),
         preceded(tag_no_case("MODSEQ "), mod_sequence_value),
@@ -49,7 +52,7 @@ pub(crate) fn search_sort_mod_seq(input: &[u8]) -> IMAPResult<&[u8], NonZeroU64>
 #[allow(clippy::type_complexity)]
 pub(crate) fn search_modsequence(
     input: &[u8],
-) -> IMAPResult<&[u8], (Option<(AttributeFlag, EntryTypeReq)>, u64)> {
+) -> IMAPResult<&[u8], (Option<(AttributeFlag, EntryTypeReq)>, Option<ModSeq>)> {
     preceded(
         tag_no_case("MODSEQ"),
         tuple((opt(search_modseq_ext), preceded(sp, mod_sequence_valzer))),
@@ -140,6 +143,7 @@ pub(crate) fn entry_type_req(input: &[u8]) -> IMAPResult<&[u8], EntryTypeReq> {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::response::resp_text;
 
     #[test]
@@ -151,4 +155,21 @@ mod tests {
         .is_ok());
         assert!(resp_text(b"[HIGHESTMODSEQ 715194045007] Highest\r\n").is_ok());
     }
+
+    #[test]
+    fn test_mod_sequence_value() {
+        let (rem, val) = mod_sequence_value(b"9223372036854775807 ").unwrap();
+        assert_eq!((rem, val), (&b" "[..], ModSeq::MAX));
+        assert!(mod_sequence_value(b"0 ").is_err());
+        assert!(mod_sequence_value(b"9223372036854775808 ").is_err());
+    }
+
+    #[test]
+    fn test_mod_sequence_valzer() {
+        let (rem, val) = mod_sequence_valzer(b"0 ").unwrap();
+        assert_eq!((rem, val), (&b" "[..], None));
+        let (rem, val) = mod_sequence_valzer(b"42 ").unwrap();
+        assert_eq!((rem, val), (&b" "[..], ModSeq::new(42)));
+        assert!(mod_sequence_valzer(b"9223372036854775808 ").is_err());
+    }
 }
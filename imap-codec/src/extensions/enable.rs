@@ -6,8 +6,6 @@
 // command-any   =/ "ENABLE" 1*(SP capability)
 // response-data =/ "*" SP enable-data CRLF
 
-use std::io::Write;
-
 use abnf_core::streaming::sp;
 use imap_types::{command::CommandBody, extensions::enable::CapabilityEnable, response::Data};
 use nom::{
@@ -20,7 +18,7 @@ use nom::{
 use crate::{
     core::atom,
     decode::IMAPResult,
-    encode::{EncodeContext, EncodeIntoContext},
+    encode::{EncodeContext, EncodeIntoContext, WriteError},
 };
 
 /// `command-any =/ "ENABLE" 1*(SP capability)`
@@ -67,7 +65,7 @@ pub(crate) fn enable_data(input: &[u8]) -> IMAPResult<&[u8], Data> {
 }
 
 impl EncodeIntoContext for CapabilityEnable<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         write!(ctx, "{}", self)
     }
 }
@@ -0,0 +1,190 @@
+//! Gmail IMAP Extensions
+
+use core::str::from_utf8;
+
+use abnf_core::streaming::sp;
+use imap_types::{
+    extensions::gmail::GmailLabel,
+    fetch::{MessageDataItem, MessageDataItemName},
+};
+use nom::{
+    branch::alt,
+    bytes::streaming::{tag, tag_no_case},
+    character::streaming::digit1,
+    combinator::{map, map_res, value},
+    multi::separated_list0,
+    sequence::{delimited, preceded},
+};
+
+use crate::{
+    core::{astring, atom},
+    decode::IMAPResult,
+    encode::{EncodeContext, EncodeIntoContext, WriteError},
+};
+
+/// ```abnf
+/// x-gm-fetch-att = "X-GM-MSGID" / "X-GM-THRID" / "X-GM-LABELS"
+/// ```
+pub(crate) fn x_gm_fetch_att(input: &[u8]) -> IMAPResult<&[u8], MessageDataItemName> {
+    alt((
+        value(
+            MessageDataItemName::GmailMessageId,
+            tag_no_case("X-GM-MSGID"),
+        ),
+        value(
+            MessageDataItemName::GmailThreadId,
+            tag_no_case("X-GM-THRID"),
+        ),
+        value(MessageDataItemName::GmailLabels, tag_no_case("X-GM-LABELS")),
+    ))(input)
+}
+
+/// ```abnf
+/// x-gm-msg-att = "X-GM-MSGID" SP uint64 /
+///                "X-GM-THRID" SP uint64 /
+///                "X-GM-LABELS" SP "(" [x-gm-label *(SP x-gm-label)] ")"
+/// ```
+pub(crate) fn x_gm_msg_att(input: &[u8]) -> IMAPResult<&[u8], MessageDataItem> {
+    alt((
+        map(
+            preceded(tag_no_case("X-GM-MSGID "), uint64),
+            MessageDataItem::GmailMessageId,
+        ),
+        map(
+            preceded(tag_no_case("X-GM-THRID "), uint64),
+            MessageDataItem::GmailThreadId,
+        ),
+        map(
+            preceded(
+                tag_no_case("X-GM-LABELS "),
+                delimited(tag("("), separated_list0(sp, x_gm_label), tag(")")),
+            ),
+            MessageDataItem::GmailLabels,
+        ),
+    ))(input)
+}
+
+/// ```abnf
+/// uint64 = 1*DIGIT
+/// ```
+///
+/// Unsigned 64-bit integer (0 <= n < 18,446,744,073,709,551,616)
+///
+/// Note: Gmail's IDs use the full 64 bits and are thus not a `number64` (which has 63 bits).
+fn uint64(input: &[u8]) -> IMAPResult<&[u8], u64> {
+    map_res(
+        // # Safety
+        //
+        // `unwrap` is safe because `1*DIGIT` contains ASCII-only characters.
+        map(digit1, |val| from_utf8(val).unwrap()),
+        str::parse::<u64>,
+    )(input)
+}
+
+/// ```abnf
+/// x-gm-label = "\" atom / astring
+/// ```
+pub(crate) fn x_gm_label(input: &[u8]) -> IMAPResult<&[u8], GmailLabel> {
+    alt((
+        map(preceded(tag("\\"), atom), GmailLabel::System),
+        map(astring, GmailLabel::Other),
+    ))(input)
+}
+
+impl EncodeIntoContext for GmailLabel<'_> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        match self {
+            GmailLabel::System(atom) => {
+                ctx.write_all(b"\\")?;
+                atom.encode_ctx(ctx)
+            }
+            GmailLabel::Other(astring) => astring.encode_ctx(ctx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use imap_types::{
+        command::{Command, CommandBody},
+        core::{AString, Atom, IString, Vec1},
+        fetch::{MacroOrMessageDataItemNames, MessageDataItem, MessageDataItemName},
+        response::{Data, Response},
+        sequence::SeqNo,
+    };
+
+    use super::*;
+    use crate::testing::{kat_inverse_command, kat_inverse_response};
+
+    #[test]
+    fn test_kat_inverse_command_fetch_gmail() {
+        kat_inverse_command(&[(
+            b"A FETCH 1 (X-GM-MSGID X-GM-THRID X-GM-LABELS)\r\n".as_ref(),
+            b"".as_ref(),
+            Command::new(
+                "A",
+                CommandBody::fetch(
+                    "1",
+                    MacroOrMessageDataItemNames::MessageDataItemNames(vec![
+                        MessageDataItemName::GmailMessageId,
+                        MessageDataItemName::GmailThreadId,
+                        MessageDataItemName::GmailLabels,
+                    ]),
+                    false,
+                )
+                .unwrap(),
+            )
+            .unwrap(),
+        )]);
+    }
+
+    #[test]
+    fn test_kat_inverse_response_fetch_gmail() {
+        kat_inverse_response(&[
+            (
+                b"* 1 FETCH (X-GM-MSGID 1278455344230334865 X-GM-THRID 1266894439832287888)\r\n"
+                    .as_ref(),
+                b"".as_ref(),
+                Response::Data(Data::Fetch {
+                    seq: SeqNo::new(1).unwrap(),
+                    items: Vec1::try_from(vec![
+                        MessageDataItem::GmailMessageId(1278455344230334865),
+                        MessageDataItem::GmailThreadId(1266894439832287888),
+                    ])
+                    .unwrap(),
+                }),
+            ),
+            (
+                b"* 1 FETCH (X-GM-MSGID 18446744073709551615)\r\n".as_ref(),
+                b"".as_ref(),
+                Response::Data(Data::Fetch {
+                    seq: SeqNo::new(1).unwrap(),
+                    items: Vec1::from(MessageDataItem::GmailMessageId(u64::MAX)),
+                }),
+            ),
+            (
+                b"* 2 FETCH (X-GM-LABELS (\\Inbox \\Sent Important \"Muy Importante\"))\r\n",
+                b"",
+                Response::Data(Data::Fetch {
+                    seq: SeqNo::new(2).unwrap(),
+                    items: Vec1::from(MessageDataItem::GmailLabels(vec![
+                        GmailLabel::System(Atom::try_from("Inbox").unwrap()),
+                        GmailLabel::System(Atom::try_from("Sent").unwrap()),
+                        GmailLabel::Other(AString::try_from("Important").unwrap()),
+                        GmailLabel::Other(AString::String(
+                            IString::try_from("Muy Importante").unwrap(),
+                        )),
+                    ])),
+                }),
+            ),
+            (
+                b"* 3 FETCH (X-GM-LABELS ())\r\n",
+                b"",
+                Response::Data(Data::Fetch {
+                    seq: SeqNo::new(3).unwrap(),
+                    items: Vec1::from(MessageDataItem::GmailLabels(vec![])),
+                }),
+            ),
+        ]);
+    }
+}
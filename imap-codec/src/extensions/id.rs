@@ -5,6 +5,8 @@
 // command_any ::= "CAPABILITY" / "LOGOUT" / "NOOP" / x_command / id
 // response_data ::= "*" SPACE (resp_cond_state / resp_cond_bye / mailbox_data / message_data / capability_data / id_response)
 
+use alloc::vec::Vec;
+
 use abnf_core::streaming::sp;
 use imap_types::core::{IString, NString};
 use nom::{
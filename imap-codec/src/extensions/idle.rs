@@ -7,18 +7,13 @@
 //
 // command_auth =/ idle
 
-use std::io::Write;
-
-#[cfg(not(feature = "quirk_crlf_relaxed"))]
-use abnf_core::streaming::crlf;
-#[cfg(feature = "quirk_crlf_relaxed")]
-use abnf_core::streaming::crlf_relaxed as crlf;
 use imap_types::{command::CommandBody, extensions::idle::IdleDone};
 use nom::{bytes::streaming::tag_no_case, combinator::value, sequence::tuple};
 
 use crate::{
+    core::crlf,
     decode::IMAPResult,
-    encode::{EncodeContext, EncodeIntoContext},
+    encode::{EncodeContext, EncodeIntoContext, WriteError},
 };
 
 /// `idle = "IDLE" CRLF "DONE"` (edited)
@@ -55,7 +50,7 @@ pub(crate) fn idle_done(input: &[u8]) -> IMAPResult<&[u8], IdleDone> {
 }
 
 impl EncodeIntoContext for IdleDone {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         ctx.write_all(b"DONE\r\n")
     }
 }
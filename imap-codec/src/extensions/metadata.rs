@@ -1,7 +1,5 @@
 //! The IMAP METADATA Extension
 
-use std::io::Write;
-
 use abnf_core::streaming::sp;
 use imap_types::{
     command::CommandBody,
@@ -23,7 +21,7 @@ use nom::{
 use crate::{
     core::{astring, nstring, number},
     decode::{IMAPErrorKind, IMAPParseError, IMAPResult},
-    encode::{utils::join_serializable, EncodeContext, EncodeIntoContext},
+    encode::{utils::join_serializable, EncodeContext, EncodeIntoContext, WriteError},
     extensions::binary::literal8,
     mailbox::mailbox,
 };
@@ -218,7 +216,7 @@ pub(crate) fn entry_list(input: &[u8]) -> IMAPResult<&[u8], Vec1<Entry>> {
 }
 
 impl EncodeIntoContext for MetadataResponse<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             MetadataResponse::WithValues(list) => {
                 ctx.write_all(b"(")?;
@@ -252,7 +250,7 @@ pub(crate) fn metadata_code(input: &[u8]) -> IMAPResult<&[u8], MetadataCode> {
 }
 
 impl EncodeIntoContext for MetadataCode {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             MetadataCode::LongEntries(number) => {
                 ctx.write_all(b"LONGENTRIES ")?;
@@ -269,13 +267,13 @@ impl EncodeIntoContext for MetadataCode {
 }
 
 impl EncodeIntoContext for Entry<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         self.inner().encode_ctx(ctx)
     }
 }
 
 impl EncodeIntoContext for GetMetadataOption {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             GetMetadataOption::MaxSize(number) => {
                 ctx.write_all(b"MAXSIZE ")?;
@@ -290,7 +288,7 @@ impl EncodeIntoContext for GetMetadataOption {
 }
 
 impl EncodeIntoContext for Depth {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         ctx.write_all(match self {
             Depth::Null => b"0",
             Depth::One => b"1",
@@ -300,7 +298,7 @@ impl EncodeIntoContext for Depth {
 }
 
 impl EncodeIntoContext for EntryValue<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         self.entry.encode_ctx(ctx)?;
         ctx.write_all(b" ")?;
         self.value.encode_ctx(ctx)
@@ -1,7 +1,5 @@
 //! IMAP QUOTA Extension
 
-use std::io::Write;
-
 use abnf_core::streaming::sp;
 use imap_types::{
     command::CommandBody,
@@ -11,7 +9,7 @@ use imap_types::{
 };
 use nom::{
     bytes::streaming::{tag, tag_no_case},
-    combinator::map,
+    combinator::{map, map_res},
     multi::{many0, separated_list0, separated_list1},
     sequence::{delimited, preceded, tuple},
 };
@@ -19,7 +17,7 @@ use nom::{
 use crate::{
     core::{astring, atom, number64},
     decode::IMAPResult,
-    encode::{EncodeContext, EncodeIntoContext},
+    encode::{EncodeContext, EncodeIntoContext, WriteError},
     mailbox::mailbox,
 };
 
@@ -64,18 +62,10 @@ pub(crate) fn getquotaroot(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
 /// resource-limit = number64
 /// ```
 pub(crate) fn quota_resource(input: &[u8]) -> IMAPResult<&[u8], QuotaGet> {
-    let mut parser = tuple((resource_name, sp, number64, sp, number64));
-
-    let (remaining, (resource, _, usage, _, limit)) = parser(input)?;
-
-    Ok((
-        remaining,
-        QuotaGet {
-            resource,
-            usage,
-            limit,
-        },
-    ))
+    map_res(
+        tuple((resource_name, sp, number64, sp, number64)),
+        |(resource, _, usage, _, limit)| QuotaGet::new(resource, usage, limit),
+    )(input)
 }
 
 /// ```abnf
@@ -151,11 +141,10 @@ pub(crate) fn setquota(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
 /// setquota-resource = resource-name SP resource-limit
 /// ```
 pub(crate) fn setquota_resource(input: &[u8]) -> IMAPResult<&[u8], QuotaSet> {
-    let mut parser = tuple((resource_name, sp, number64));
-
-    let (remaining, (resource, _, limit)) = parser(input)?;
-
-    Ok((remaining, QuotaSet { resource, limit }))
+    map_res(
+        tuple((resource_name, sp, number64)),
+        |(resource, _, limit)| QuotaSet::new(resource, limit),
+    )(input)
 }
 
 // This had to be inlined into the `capability` parser because `CapabilityOther("QUOTAFOO")` would
@@ -190,22 +179,26 @@ pub(crate) fn setquota_resource(input: &[u8]) -> IMAPResult<&[u8], QuotaSet> {
 // }
 
 impl EncodeIntoContext for Resource<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
-        ctx.write_all(self.to_string().as_bytes())
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        write!(ctx, "{}", self)
     }
 }
 
 impl EncodeIntoContext for QuotaGet<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
-        self.resource.encode_ctx(ctx)?;
-        write!(ctx, " {} {}", self.usage, self.limit)
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        self.resource().encode_ctx(ctx)?;
+        ctx.write_all(b" ")?;
+        ctx.write_number(self.usage())?;
+        ctx.write_all(b" ")?;
+        ctx.write_number(self.limit())
     }
 }
 
 impl EncodeIntoContext for QuotaSet<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
-        self.resource.encode_ctx(ctx)?;
-        write!(ctx, " {}", self.limit)
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        self.resource().encode_ctx(ctx)?;
+        ctx.write_all(b" ")?;
+        ctx.write_number(self.limit())
     }
 }
 
@@ -214,7 +207,7 @@ mod tests {
     use imap_types::{
         command::{Command, CommandBody},
         core::{IString, Tag},
-        extensions::quota::{QuotaGet, QuotaSet, Resource},
+        extensions::quota::{QuotaGet, QuotaSet, Resource, QUOTA_VALUE_MAX},
         mailbox::Mailbox,
         response::{Capability, Code, Response, Status},
         status::{StatusDataItem, StatusDataItemName},
@@ -247,6 +240,13 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_quota_value_out_of_range() {
+        assert!(setquota_resource(b"STORAGE 9223372036854775808)").is_err());
+        assert!(quota_resource(b"STORAGE 9223372036854775808 1)").is_err());
+        assert!(quota_resource(b"STORAGE 1 18446744073709551615)").is_err());
+    }
+
     #[test]
     fn test_kat_inverse_command_get_quota() {
         kat_inverse_command(&[
@@ -326,39 +326,21 @@ mod tests {
                 b"",
                 Command::new("A", CommandBody::set_quota(
                     "INBOX",
-                    vec![QuotaSet {
-                        resource: Resource::Storage,
-                        limit: 256,
-                    }],
+                    vec![QuotaSet::new(Resource::Storage, 256).unwrap()],
                 )
                     .unwrap()).unwrap(),
             ),
             (
-                b"A SETQUOTA INBOX (STORAGE 0 MESSAGE 512 MAILBOX 512 ANNOTATION-STORAGE 123 Foo 18446744073709551615)\r\n",
+                b"A SETQUOTA INBOX (STORAGE 0 MESSAGE 512 MAILBOX 512 ANNOTATION-STORAGE 123 Foo 9223372036854775807)\r\n",
                 b"",
                 Command::new("A", CommandBody::set_quota(
                     "INBOX",
                     vec![
-                        QuotaSet {
-                            resource: Resource::Storage,
-                            limit: 0,
-                        },
-                        QuotaSet {
-                            resource: Resource::Message,
-                            limit: 512,
-                        },
-                        QuotaSet {
-                            resource: Resource::Mailbox,
-                            limit: 512,
-                        },
-                        QuotaSet {
-                            resource: Resource::AnnotationStorage,
-                            limit: 123,
-                        },
-                        QuotaSet {
-                            resource: Resource::try_from("Foo").unwrap(),
-                            limit: u64::MAX,
-                        },
+                        QuotaSet::new(Resource::Storage, 0).unwrap(),
+                        QuotaSet::new(Resource::Message, 512).unwrap(),
+                        QuotaSet::new(Resource::Mailbox, 512).unwrap(),
+                        QuotaSet::new(Resource::AnnotationStorage, 123).unwrap(),
+                        QuotaSet::new(Resource::try_from("Foo").unwrap(), QUOTA_VALUE_MAX).unwrap(),
                     ],
                 )
                     .unwrap()).unwrap(),
@@ -368,7 +350,7 @@ mod tests {
                 b"",
                 CommandBody::set_quota(
                     AString::String(IString::try_from("#user/alice").unwrap()),
-                    vec![QuotaSet::new(Resource::Storage, 510)],
+                    vec![QuotaSet::new(Resource::Storage, 510).unwrap()],
                 )
                     .unwrap()
                     .tag("S0001")
@@ -379,7 +361,7 @@ mod tests {
                 b"",
                 CommandBody::set_quota(
                     AString::String(IString::try_from("!partition/sda4").unwrap()),
-                    vec![QuotaSet::new(Resource::Storage, 99999999)],
+                    vec![QuotaSet::new(Resource::Storage, 99999999).unwrap()],
                 )
                     .unwrap()
                     .tag("S0002")
@@ -388,7 +370,7 @@ mod tests {
             (
                 b"A001 SETQUOTA \"\" (STORAGE 512)\r\n",
                 b"",
-                CommandBody::set_quota("", vec![QuotaSet::new(Resource::Storage, 512)])
+                CommandBody::set_quota("", vec![QuotaSet::new(Resource::Storage, 512).unwrap()])
                     .unwrap()
                     .tag("A001")
                     .unwrap(),
@@ -424,11 +406,7 @@ mod tests {
                 Response::Data(
                     Data::quota(
                         "INBOX",
-                        vec![QuotaGet {
-                            resource: Resource::Message,
-                            usage: 1024,
-                            limit: 2048,
-                        }],
+                        vec![QuotaGet::new(Resource::Message, 1024, 2048).unwrap()],
                     )
                     .unwrap(),
                 ),
@@ -491,7 +469,7 @@ mod tests {
                 Response::Data(
                     Data::quota(
                         AString::String(IString::try_from("!partition/sda4").unwrap()),
-                        vec![QuotaGet::new(Resource::Storage, 104, 10923847)],
+                        vec![QuotaGet::new(Resource::Storage, 104, 10923847).unwrap()],
                     )
                     .unwrap(),
                 ),
@@ -501,7 +479,7 @@ mod tests {
                 b"",
                 Response::Data(Data::Quota {
                     root: "".try_into().unwrap(),
-                    quotas: vec![QuotaGet::new(Resource::Storage, 10, 512)]
+                    quotas: vec![QuotaGet::new(Resource::Storage, 10, 512).unwrap()]
                         .try_into()
                         .unwrap(),
                 }),
@@ -511,7 +489,7 @@ mod tests {
                 b"",
                 Response::Data(Data::Quota {
                     root: AString::String(IString::try_from("#user/alice").unwrap()),
-                    quotas: vec![QuotaGet::new(Resource::Message, 42, 1000)]
+                    quotas: vec![QuotaGet::new(Resource::Message, 42, 1000).unwrap()]
                         .try_into()
                         .unwrap(),
                 }),
@@ -523,8 +501,8 @@ mod tests {
                     Data::quota(
                         AString::String(IString::try_from("#user/alice").unwrap()),
                         vec![
-                            QuotaGet::new(Resource::Storage, 54, 111),
-                            QuotaGet::new(Resource::Message, 42, 1000),
+                            QuotaGet::new(Resource::Storage, 54, 111).unwrap(),
+                            QuotaGet::new(Resource::Message, 42, 1000).unwrap(),
                         ],
                     )
                     .unwrap(),
@@ -536,7 +514,7 @@ mod tests {
                 Response::Data(
                     Data::quota(
                         AString::String(IString::try_from("#user/alice").unwrap()),
-                        vec![QuotaGet::new(Resource::Storage, 58, 512)],
+                        vec![QuotaGet::new(Resource::Storage, 58, 512).unwrap()],
                     )
                     .unwrap(),
                 ),
@@ -1,42 +1,51 @@
-use std::io::Write;
+use alloc::{vec, vec::Vec};
 
-use abnf_core::streaming::sp;
+use abnf_core::streaming::{dquote, sp};
 use imap_types::{
     command::CommandBody,
     core::Vec1,
-    extensions::sort::{SortCriterion, SortKey},
+    extensions::sort::{PartialRange, SearchReturnData, SortCriterion, SortKey, SortReturnOption},
+    response::Data,
 };
 use nom::{
     branch::alt,
     bytes::streaming::{tag, tag_no_case},
     combinator::{map, opt, value},
-    multi::separated_list1,
-    sequence::{delimited, tuple},
+    multi::{many0, separated_list0, separated_list1},
+    sequence::{delimited, preceded, separated_pair, terminated, tuple},
 };
 
 use crate::{
+    core::{nil, number, nz_number, tag_imap},
     decode::IMAPResult,
-    encode::{EncodeContext, EncodeIntoContext},
+    encode::{EncodeContext, EncodeIntoContext, WriteError},
+    extensions::tagged_ext::{tagged_ext_label, tagged_ext_val},
     search::search_criteria,
+    sequence::sequence_set,
 };
 
 /// ```abnf
-/// sort = ["UID" SP] "SORT" SP sort-criteria SP search-criteria
+/// sort = ["UID" SP] "SORT" [SP sort-return-opts] SP sort-criteria SP search-criteria
 /// ```
+///
+/// Note: `sort-return-opts` was added by RFC 5267.
 pub(crate) fn sort(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
     let mut parser = tuple((
         map(opt(tag_no_case("UID ")), |thing| thing.is_some()),
         tag_no_case("SORT "),
+        opt(terminated(sort_return_opts, sp)),
         sort_criteria,
         sp,
         search_criteria,
     ));
 
-    let (remaining, (uid, _, sort_criteria, _, (charset, search_key))) = parser(input)?;
+    let (remaining, (uid, _, return_options, sort_criteria, _, (charset, search_key))) =
+        parser(input)?;
 
     Ok((
         remaining,
         CommandBody::Sort {
+            return_options: return_options.unwrap_or_default(),
             sort_criteria,
             charset,
             search_criteria: search_key,
@@ -45,6 +54,129 @@ pub(crate) fn sort(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
     ))
 }
 
+/// ```abnf
+/// sort-return-opts = "RETURN" SP "(" [sort-return-opt *(SP sort-return-opt)] ")"
+/// ```
+///
+/// Note: An empty list is treated as `RETURN (ALL)` (RFC 5267, section 3.2).
+pub(crate) fn sort_return_opts(input: &[u8]) -> IMAPResult<&[u8], Vec<SortReturnOption>> {
+    let mut parser = preceded(
+        tag_no_case("RETURN ("),
+        terminated(separated_list0(sp, sort_return_opt), tag(")")),
+    );
+
+    let (remaining, options) = parser(input)?;
+
+    if options.is_empty() {
+        return Ok((remaining, vec![SortReturnOption::All]));
+    }
+
+    Ok((remaining, options))
+}
+
+/// ```abnf
+/// sort-return-opt = "MIN" / "MAX" / "ALL" / "COUNT" / "PARTIAL" SP partial-range
+/// ```
+pub(crate) fn sort_return_opt(input: &[u8]) -> IMAPResult<&[u8], SortReturnOption> {
+    alt((
+        value(SortReturnOption::Min, tag_no_case("MIN")),
+        value(SortReturnOption::Max, tag_no_case("MAX")),
+        value(SortReturnOption::All, tag_no_case("ALL")),
+        value(SortReturnOption::Count, tag_no_case("COUNT")),
+        map(
+            preceded(tag_no_case("PARTIAL "), partial_range),
+            SortReturnOption::Partial,
+        ),
+    ))(input)
+}
+
+/// ```abnf
+/// partial-range = nz-number ":" nz-number
+/// ```
+pub(crate) fn partial_range(input: &[u8]) -> IMAPResult<&[u8], PartialRange> {
+    let mut parser = tuple((nz_number, tag(":"), nz_number));
+
+    let (remaining, (first, _, last)) = parser(input)?;
+
+    Ok((remaining, PartialRange { first, last }))
+}
+
+/// ```abnf
+/// esearch-response = "ESEARCH" [search-correlator] [SP "UID"] *(SP search-return-data)
+///
+/// search-correlator = SP "(" "TAG" SP tag-string ")"
+/// ```
+///
+/// Note: `tag-string` is restricted to a quoted tag.
+pub(crate) fn esearch_response(input: &[u8]) -> IMAPResult<&[u8], Data> {
+    let mut parser = tuple((
+        tag_no_case("ESEARCH"),
+        opt(delimited(
+            tuple((tag_no_case(" (TAG "), dquote)),
+            tag_imap,
+            tuple((dquote, tag(")"))),
+        )),
+        map(opt(tag_no_case(" UID")), |thing| thing.is_some()),
+        many0(preceded(sp, search_return_data)),
+    ));
+
+    let (remaining, (_, tag, uid, data)) = parser(input)?;
+
+    Ok((remaining, Data::ESearch { tag, uid, data }))
+}
+
+/// ```abnf
+/// search-return-data = "MIN" SP nz-number /
+///                      "MAX" SP nz-number /
+///                      "ALL" SP sequence-set /
+///                      "COUNT" SP number /
+///                      "PARTIAL" SP "(" partial-range SP partial-results ")" ; RFC 5267
+///
+/// partial-results = sequence-set / "NIL"
+///
+/// search-return-data =/ search-modifier-name SP search-return-value ; RFC 4466
+///
+/// search-modifier-name = tagged-ext-label
+///
+/// search-return-value = tagged-ext-val
+/// ```
+pub(crate) fn search_return_data(input: &[u8]) -> IMAPResult<&[u8], SearchReturnData> {
+    alt((
+        map(
+            preceded(tag_no_case("MIN "), nz_number),
+            SearchReturnData::Min,
+        ),
+        map(
+            preceded(tag_no_case("MAX "), nz_number),
+            SearchReturnData::Max,
+        ),
+        map(
+            preceded(tag_no_case("ALL "), sequence_set),
+            SearchReturnData::All,
+        ),
+        map(
+            preceded(tag_no_case("COUNT "), number),
+            SearchReturnData::Count,
+        ),
+        map(
+            delimited(
+                tag_no_case("PARTIAL ("),
+                tuple((
+                    partial_range,
+                    sp,
+                    alt((map(sequence_set, Some), value(None, nil))),
+                )),
+                tag(")"),
+            ),
+            |(range, _, results)| SearchReturnData::Partial { range, results },
+        ),
+        map(
+            separated_pair(tagged_ext_label, sp, tagged_ext_val),
+            |(name, value)| SearchReturnData::Other { name, value },
+        ),
+    ))(input)
+}
+
 /// ```abnf
 /// sort-criteria = "(" sort-criterion *(SP sort-criterion) ")"
 /// ```
@@ -88,7 +220,7 @@ pub(crate) fn sort_key(input: &[u8]) -> IMAPResult<&[u8], SortKey> {
 }
 
 impl EncodeIntoContext for SortCriterion {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         if self.reverse {
             ctx.write_all(b"REVERSE ")?;
         }
@@ -96,3 +228,191 @@ impl EncodeIntoContext for SortCriterion {
         ctx.write_all(self.key.as_ref().as_bytes())
     }
 }
+
+impl EncodeIntoContext for SortReturnOption {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        ctx.write_all(self.as_ref().as_bytes())?;
+
+        if let SortReturnOption::Partial(range) = self {
+            ctx.write_all(b" ")?;
+            range.encode_ctx(ctx)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl EncodeIntoContext for PartialRange {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        self.first.encode_ctx(ctx)?;
+        ctx.write_all(b":")?;
+        self.last.encode_ctx(ctx)
+    }
+}
+
+impl EncodeIntoContext for SearchReturnData<'_> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        match self {
+            SearchReturnData::Min(min) => {
+                ctx.write_all(b"MIN ")?;
+                min.encode_ctx(ctx)
+            }
+            SearchReturnData::Max(max) => {
+                ctx.write_all(b"MAX ")?;
+                max.encode_ctx(ctx)
+            }
+            SearchReturnData::All(sequence_set) => {
+                ctx.write_all(b"ALL ")?;
+                sequence_set.encode_ctx(ctx)
+            }
+            SearchReturnData::Count(count) => {
+                ctx.write_all(b"COUNT ")?;
+                count.encode_ctx(ctx)
+            }
+            SearchReturnData::Partial { range, results } => {
+                ctx.write_all(b"PARTIAL (")?;
+                range.encode_ctx(ctx)?;
+                ctx.write_all(b" ")?;
+                match results {
+                    Some(sequence_set) => sequence_set.encode_ctx(ctx)?,
+                    None => ctx.write_all(b"NIL")?,
+                }
+                ctx.write_all(b")")
+            }
+            SearchReturnData::Other { name, value } => {
+                name.encode_ctx(ctx)?;
+                ctx.write_all(b" ")?;
+                value.encode_ctx(ctx)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use imap_types::{
+        command::Command,
+        core::{Charset, Tag},
+        response::Response,
+        search::SearchKey,
+        sequence::SequenceSet,
+    };
+
+    use super::*;
+    use crate::testing::{kat_inverse_command, kat_inverse_response, known_answer_test_parse};
+
+    #[test]
+    fn test_kat_inverse_command_sort_return() {
+        kat_inverse_command(&[
+            (
+                b"A282 SORT (SUBJECT) UTF-8 ALL\r\n".as_ref(),
+                b"".as_ref(),
+                Command::new(
+                    "A282",
+                    CommandBody::Sort {
+                        return_options: vec![],
+                        sort_criteria: Vec1::from(SortCriterion {
+                            reverse: false,
+                            key: SortKey::Subject,
+                        }),
+                        charset: Charset::try_from("UTF-8").unwrap(),
+                        search_criteria: Vec1::from(SearchKey::All),
+                        uid: false,
+                    },
+                )
+                .unwrap(),
+            ),
+            (
+                b"A283 UID SORT RETURN (MIN COUNT PARTIAL 1:500) (REVERSE DATE) UTF-8 ALL\r\n",
+                b"",
+                Command::new(
+                    "A283",
+                    CommandBody::Sort {
+                        return_options: vec![
+                            SortReturnOption::Min,
+                            SortReturnOption::Count,
+                            SortReturnOption::Partial(PartialRange {
+                                first: NonZeroU32::new(1).unwrap(),
+                                last: NonZeroU32::new(500).unwrap(),
+                            }),
+                        ],
+                        sort_criteria: Vec1::from(SortCriterion {
+                            reverse: true,
+                            key: SortKey::Date,
+                        }),
+                        charset: Charset::try_from("UTF-8").unwrap(),
+                        search_criteria: Vec1::from(SearchKey::All),
+                        uid: true,
+                    },
+                )
+                .unwrap(),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_parse_sort_return_opts_empty() {
+        known_answer_test_parse(
+            (
+                b"RETURN () ".as_ref(),
+                b" ".as_ref(),
+                vec![SortReturnOption::All],
+            ),
+            sort_return_opts,
+        );
+    }
+
+    #[test]
+    fn test_kat_inverse_response_esearch() {
+        kat_inverse_response(&[
+            (
+                b"* ESEARCH\r\n".as_ref(),
+                b"".as_ref(),
+                Response::Data(Data::ESearch {
+                    tag: None,
+                    uid: false,
+                    data: vec![],
+                }),
+            ),
+            (
+                b"* ESEARCH (TAG \"A283\") UID MIN 4 COUNT 3\r\n",
+                b"",
+                Response::Data(Data::ESearch {
+                    tag: Some(Tag::try_from("A283").unwrap()),
+                    uid: true,
+                    data: vec![
+                        SearchReturnData::Min(NonZeroU32::new(4).unwrap()),
+                        SearchReturnData::Count(3),
+                    ],
+                }),
+            ),
+            (
+                b"* ESEARCH (TAG \"A284\") ALL 5,3,4:7 PARTIAL (1:2 5,3) PARTIAL (100:200 NIL)\r\n",
+                b"",
+                Response::Data(Data::ESearch {
+                    tag: Some(Tag::try_from("A284").unwrap()),
+                    uid: false,
+                    data: vec![
+                        SearchReturnData::All(SequenceSet::try_from("5,3,4:7").unwrap()),
+                        SearchReturnData::Partial {
+                            range: PartialRange {
+                                first: NonZeroU32::new(1).unwrap(),
+                                last: NonZeroU32::new(2).unwrap(),
+                            },
+                            results: Some(SequenceSet::try_from("5,3").unwrap()),
+                        },
+                        SearchReturnData::Partial {
+                            range: PartialRange {
+                                first: NonZeroU32::new(100).unwrap(),
+                                last: NonZeroU32::new(200).unwrap(),
+                            },
+                            results: None,
+                        },
+                    ],
+                }),
+            ),
+        ]);
+    }
+}
@@ -0,0 +1,422 @@
+//! Generic Extension Parameters (RFC 4466)
+
+use core::str::from_utf8;
+
+use abnf_core::streaming::sp;
+use imap_types::{
+    core::Vec1,
+    extensions::tagged_ext::{
+        ExtensionItem, TaggedExtComp, TaggedExtLabel, TaggedExtSimple, TaggedExtVal,
+    },
+    utils::indicators::{is_tagged_label_char, is_tagged_label_fchar},
+};
+use nom::{
+    branch::alt,
+    bytes::streaming::{tag, take_while, take_while_m_n},
+    character::streaming::one_of,
+    combinator::{map, not, opt, peek, recognize},
+    multi::separated_list1,
+    sequence::{delimited, preceded, terminated, tuple},
+};
+
+use crate::{
+    core::{astring, number64},
+    decode::{recursion_limits, IMAPErrorKind, IMAPParseError, IMAPResult},
+    encode::{utils::join_serializable, EncodeContext, EncodeIntoContext, WriteError},
+    sequence::sequence_set,
+};
+
+/// ```abnf
+/// tagged-ext-label   = tagged-label-fchar *tagged-label-char
+///
+/// tagged-label-fchar = ALPHA / "-" / "_" / "."
+///
+/// tagged-label-char  = tagged-label-fchar / DIGIT / ":"
+/// ```
+pub(crate) fn tagged_ext_label(input: &[u8]) -> IMAPResult<&[u8], TaggedExtLabel> {
+    let mut parser = recognize(tuple((
+        take_while_m_n(1, 1, is_tagged_label_fchar),
+        take_while(is_tagged_label_char),
+    )));
+
+    let (remaining, label) = parser(input)?;
+
+    // # Safety
+    //
+    // `unwrap` is safe, because `is_tagged_label_(f)char` enforces ...
+    // * that the string is always UTF8, and ...
+    // * contains only the allowed characters.
+    Ok((
+        remaining,
+        TaggedExtLabel::unvalidated(from_utf8(label).unwrap()),
+    ))
+}
+
+/// ```abnf
+/// tagged-ext-val = tagged-ext-simple /
+///                  "(" [tagged-ext-comp] ")"
+/// ```
+pub(crate) fn tagged_ext_val(input: &[u8]) -> IMAPResult<&[u8], TaggedExtVal> {
+    alt((
+        map(tagged_ext_simple, TaggedExtVal::Simple),
+        map(
+            delimited(
+                tag("("),
+                opt(tagged_ext_comp(recursion_limits().extension)),
+                tag(")"),
+            ),
+            |comp| TaggedExtVal::Comp(comp.map(Vec1::into_inner).unwrap_or_default()),
+        ),
+    ))(input)
+}
+
+/// ```abnf
+/// tagged-ext-simple = sequence-set / number / number64
+/// ```
+///
+/// Note: A number is parsed as `number64` when it isn't followed by another sequence set item.
+pub(crate) fn tagged_ext_simple(input: &[u8]) -> IMAPResult<&[u8], TaggedExtSimple> {
+    alt((
+        map(
+            terminated(number64, not(peek(one_of(":,")))),
+            TaggedExtSimple::Number,
+        ),
+        map(sequence_set, TaggedExtSimple::SequenceSet),
+    ))(input)
+}
+
+/// ```abnf
+/// tagged-ext-comp = astring /
+///                   tagged-ext-comp *(SP tagged-ext-comp) /
+///                   "(" tagged-ext-comp ")"
+/// ```
+///
+/// This parser is recursively defined. Thus, in order to not overflow the stack,
+/// it is needed to limit how may recursions are allowed.
+pub(crate) fn tagged_ext_comp(
+    remaining_recursions: usize,
+) -> impl Fn(&[u8]) -> IMAPResult<&[u8], Vec1<TaggedExtComp>> {
+    move |input: &[u8]| tagged_ext_comp_limited(input, remaining_recursions)
+}
+
+fn tagged_ext_comp_limited(
+    input: &[u8],
+    remaining_recursion: usize,
+) -> IMAPResult<&[u8], Vec1<TaggedExtComp>> {
+    if remaining_recursion == 0 {
+        return Err(nom::Err::Failure(IMAPParseError {
+            input,
+            kind: IMAPErrorKind::RecursionLimitExceeded,
+        }));
+    }
+
+    let tagged_ext_comp =
+        |input| tagged_ext_comp_limited(input, remaining_recursion.saturating_sub(1));
+
+    map(
+        separated_list1(
+            sp,
+            alt((
+                map(astring, TaggedExtComp::AString),
+                map(
+                    delimited(tag("("), tagged_ext_comp, tag(")")),
+                    TaggedExtComp::List,
+                ),
+            )),
+        ),
+        Vec1::unvalidated,
+    )(input)
+}
+
+/// ```abnf
+/// extension-item = tagged-ext-label [SP tagged-ext-val]
+/// ```
+///
+/// Note: This is the generic form of, e.g., `select-param`.
+pub(crate) fn extension_item(input: &[u8]) -> IMAPResult<&[u8], ExtensionItem> {
+    let mut parser = tuple((tagged_ext_label, opt(preceded(sp, tagged_ext_val))));
+
+    let (remaining, (label, value)) = parser(input)?;
+
+    Ok((remaining, ExtensionItem { label, value }))
+}
+
+impl EncodeIntoContext for TaggedExtLabel<'_> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        ctx.write_all(self.inner().as_bytes())
+    }
+}
+
+impl EncodeIntoContext for TaggedExtVal<'_> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        match self {
+            TaggedExtVal::Simple(simple) => simple.encode_ctx(ctx),
+            TaggedExtVal::Comp(comp) => {
+                ctx.write_all(b"(")?;
+                join_serializable(comp, b" ", ctx)?;
+                ctx.write_all(b")")
+            }
+        }
+    }
+}
+
+impl EncodeIntoContext for TaggedExtSimple {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        match self {
+            TaggedExtSimple::SequenceSet(sequence_set) => sequence_set.encode_ctx(ctx),
+            TaggedExtSimple::Number(number) => number.encode_ctx(ctx),
+        }
+    }
+}
+
+impl EncodeIntoContext for TaggedExtComp<'_> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        match self {
+            TaggedExtComp::AString(astring) => astring.encode_ctx(ctx),
+            TaggedExtComp::List(list) => {
+                ctx.write_all(b"(")?;
+                join_serializable(list.as_ref(), b" ", ctx)?;
+                ctx.write_all(b")")
+            }
+        }
+    }
+}
+
+impl EncodeIntoContext for ExtensionItem<'_> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        self.label.encode_ctx(ctx)?;
+
+        if let Some(value) = &self.value {
+            ctx.write_all(b" ")?;
+            value.encode_ctx(ctx)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use imap_types::{
+        command::{Command, CommandBody, SelectParameter, SelectParameterOther},
+        core::AString,
+        extensions::sort::SearchReturnData,
+        mailbox::Mailbox,
+        response::{Data, Response},
+        status::{StatusDataItem, StatusDataItemName, StatusDataItemNameOther},
+    };
+
+    use super::*;
+    use crate::testing::{kat_inverse_command, kat_inverse_response, known_answer_test_parse};
+
+    #[test]
+    fn test_parse_tagged_ext_val() {
+        let tests = [
+            (
+                b"123 ".as_ref(),
+                b" ".as_ref(),
+                TaggedExtVal::Simple(TaggedExtSimple::Number(123)),
+            ),
+            (
+                b"1:5,7 ",
+                b" ",
+                TaggedExtVal::Simple(TaggedExtSimple::SequenceSet("1:5,7".try_into().unwrap())),
+            ),
+            (b"() ", b" ", TaggedExtVal::Comp(vec![])),
+            (
+                b"(a \"b c\" (d (e))) ",
+                b" ",
+                TaggedExtVal::Comp(vec![
+                    TaggedExtComp::AString(AString::try_from("a").unwrap()),
+                    TaggedExtComp::AString(AString::try_from("b c").unwrap()),
+                    TaggedExtComp::List(
+                        Vec1::try_from(vec![
+                            TaggedExtComp::AString(AString::try_from("d").unwrap()),
+                            TaggedExtComp::List(Vec1::from(TaggedExtComp::AString(
+                                AString::try_from("e").unwrap(),
+                            ))),
+                        ])
+                        .unwrap(),
+                    ),
+                ]),
+            ),
+        ];
+
+        for test in tests {
+            known_answer_test_parse(test, tagged_ext_val);
+        }
+    }
+
+    #[test]
+    fn test_parse_tagged_ext_comp_recursion() {
+        let input = format!("({}x{})", "(".repeat(16), ")".repeat(16));
+
+        assert!(matches!(
+            tagged_ext_val(input.as_bytes()),
+            Err(nom::Err::Failure(_))
+        ));
+    }
+
+    #[test]
+    fn test_kat_inverse_command_select_other() {
+        kat_inverse_command(&[
+            (
+                b"A SELECT INBOX (X-FOO)\r\n".as_ref(),
+                b"".as_ref(),
+                Command::new(
+                    "A",
+                    CommandBody::Select {
+                        mailbox: Mailbox::Inbox,
+                        parameters: vec![SelectParameter::Other(
+                            SelectParameterOther::try_from(ExtensionItem::new(
+                                TaggedExtLabel::try_from("X-FOO").unwrap(),
+                                None,
+                            ))
+                            .unwrap(),
+                        )],
+                    },
+                )
+                .unwrap(),
+            ),
+            (
+                b"A EXAMINE INBOX (X-FOO 1:* X-BAR (a (b)))\r\n".as_ref(),
+                b"".as_ref(),
+                Command::new(
+                    "A",
+                    CommandBody::Examine {
+                        mailbox: Mailbox::Inbox,
+                        parameters: vec![
+                            SelectParameter::Other(
+                                SelectParameterOther::try_from(ExtensionItem::new(
+                                    TaggedExtLabel::try_from("X-FOO").unwrap(),
+                                    Some(TaggedExtVal::Simple(TaggedExtSimple::SequenceSet(
+                                        "1:*".try_into().unwrap(),
+                                    ))),
+                                ))
+                                .unwrap(),
+                            ),
+                            SelectParameter::Other(
+                                SelectParameterOther::try_from(ExtensionItem::new(
+                                    TaggedExtLabel::try_from("X-BAR").unwrap(),
+                                    Some(TaggedExtVal::Comp(vec![
+                                        TaggedExtComp::AString(AString::try_from("a").unwrap()),
+                                        TaggedExtComp::List(Vec1::from(TaggedExtComp::AString(
+                                            AString::try_from("b").unwrap(),
+                                        ))),
+                                    ])),
+                                ))
+                                .unwrap(),
+                            ),
+                        ],
+                    },
+                )
+                .unwrap(),
+            ),
+        ]);
+    }
+
+    #[cfg(feature = "ext_condstore_qresync")]
+    #[test]
+    fn test_select_other_known() {
+        // A known parameter can't be represented as `Other` ...
+        assert!(SelectParameterOther::try_from(ExtensionItem::new(
+            TaggedExtLabel::try_from("condstore").unwrap(),
+            None,
+        ))
+        .is_err());
+
+        // ... but a parameter that only starts with a known parameter is.
+        kat_inverse_command(&[(
+            b"A SELECT INBOX (CONDSTORE CONDSTORE-X)\r\n".as_ref(),
+            b"".as_ref(),
+            Command::new(
+                "A",
+                CommandBody::Select {
+                    mailbox: Mailbox::Inbox,
+                    parameters: vec![
+                        SelectParameter::CondStore,
+                        SelectParameter::Other(
+                            SelectParameterOther::try_from(ExtensionItem::new(
+                                TaggedExtLabel::try_from("CONDSTORE-X").unwrap(),
+                                None,
+                            ))
+                            .unwrap(),
+                        ),
+                    ],
+                },
+            )
+            .unwrap(),
+        )]);
+    }
+
+    #[test]
+    fn test_kat_inverse_command_status_other() {
+        kat_inverse_command(&[(
+            b"A STATUS INBOX (MESSAGES X-FOO)\r\n".as_ref(),
+            b"".as_ref(),
+            Command::new(
+                "A",
+                CommandBody::status(
+                    "INBOX",
+                    vec![
+                        StatusDataItemName::Messages,
+                        StatusDataItemName::try_from("X-FOO").unwrap(),
+                    ],
+                )
+                .unwrap(),
+            )
+            .unwrap(),
+        )]);
+    }
+
+    #[test]
+    fn test_kat_inverse_response_other() {
+        kat_inverse_response(&[
+            (
+                b"* STATUS INBOX (MESSAGES 1 X-FOO 2 APPENDLIMIT NIL)\r\n".as_ref(),
+                b"".as_ref(),
+                Response::Data(Data::Status {
+                    mailbox: Mailbox::Inbox,
+                    items: vec![
+                        StatusDataItem::Messages(1),
+                        StatusDataItem::Other {
+                            name: StatusDataItemNameOther::try_from(
+                                TaggedExtLabel::try_from("X-FOO").unwrap(),
+                            )
+                            .unwrap(),
+                            value: Some(2),
+                        },
+                        StatusDataItem::Other {
+                            name: StatusDataItemNameOther::try_from(
+                                TaggedExtLabel::try_from("APPENDLIMIT").unwrap(),
+                            )
+                            .unwrap(),
+                            value: None,
+                        },
+                    ]
+                    .into(),
+                }),
+            ),
+            (
+                b"* ESEARCH UID MIN 1 X-FOO (a b)\r\n".as_ref(),
+                b"".as_ref(),
+                Response::Data(Data::ESearch {
+                    tag: None,
+                    uid: true,
+                    data: vec![
+                        SearchReturnData::Min(NonZeroU32::new(1).unwrap()),
+                        SearchReturnData::Other {
+                            name: TaggedExtLabel::try_from("X-FOO").unwrap(),
+                            value: TaggedExtVal::Comp(vec![
+                                TaggedExtComp::AString(AString::try_from("a").unwrap()),
+                                TaggedExtComp::AString(AString::try_from("b").unwrap()),
+                            ]),
+                        },
+                    ],
+                }),
+            ),
+        ]);
+    }
+}
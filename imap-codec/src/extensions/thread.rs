@@ -1,5 +1,3 @@
-use std::io::Write;
-
 use abnf_core::streaming::sp;
 use imap_types::{
     command::CommandBody,
@@ -17,19 +15,19 @@ use nom::{
 
 use crate::{
     core::{atom, nz_number},
-    decode::{IMAPErrorKind, IMAPParseError, IMAPResult},
-    encode::{EncodeContext, EncodeIntoContext},
+    decode::{recursion_limits, IMAPErrorKind, IMAPParseError, IMAPResult},
+    encode::{EncodeContext, EncodeIntoContext, WriteError},
     search::search_criteria,
 };
 
 impl EncodeIntoContext for Thread {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
-        ctx.write_all(self.to_string().as_bytes())
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        write!(ctx, "{}", self)
     }
 }
 
 impl EncodeIntoContext for ThreadingAlgorithm<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             ThreadingAlgorithm::OrderedSubject => ctx.write_all(b"ORDEREDSUBJECT"),
             ThreadingAlgorithm::References => ctx.write_all(b"REFERENCES"),
@@ -39,7 +37,7 @@ impl EncodeIntoContext for ThreadingAlgorithm<'_> {
 }
 
 impl EncodeIntoContext for ThreadingAlgorithmOther<'_> {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         ctx.write_all(self.as_ref().as_bytes())
     }
 }
@@ -84,7 +82,7 @@ pub(crate) fn thread_alg(input: &[u8]) -> IMAPResult<&[u8], ThreadingAlgorithm>
 pub(crate) fn thread_data(input: &[u8]) -> IMAPResult<&[u8], Data> {
     let mut parser = preceded(
         tag_no_case("THREAD"),
-        opt(preceded(sp, many1(thread_list(8)))),
+        opt(preceded(sp, many1(thread_list(recursion_limits().thread)))),
     );
 
     let (remaining, thread_list) = parser(input)?;
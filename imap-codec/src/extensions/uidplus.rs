@@ -1,4 +1,4 @@
-use std::{io::Write, num::NonZeroU32};
+use core::num::NonZeroU32;
 
 use abnf_core::streaming::sp;
 use imap_types::{
@@ -18,7 +18,8 @@ use nom::{
 use crate::{
     core::nz_number,
     decode::IMAPResult,
-    encode::{utils::join_serializable, EncodeContext, EncodeIntoContext},
+    encode::{utils::join_serializable, EncodeContext, EncodeIntoContext, WriteError},
+    fetch::uniqueid,
     sequence::sequence_set,
 };
 
@@ -41,7 +42,7 @@ pub(crate) fn uid_expunge(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
 /// ```
 pub(crate) fn resp_code_apnd(input: &[u8]) -> IMAPResult<&[u8], Code> {
     let (rem, (_, uid_validity, _, uid)) =
-        tuple((tag_no_case("APPENDUID "), nz_number, sp, nz_number))(input)?;
+        tuple((tag_no_case("APPENDUID "), nz_number, sp, uniqueid))(input)?;
 
     Ok((rem, Code::AppendUid { uid_validity, uid }))
 }
@@ -96,13 +97,13 @@ pub(crate) fn uid_range(input: &[u8]) -> IMAPResult<&[u8], (NonZeroU32, NonZeroU
 }
 
 impl EncodeIntoContext for UidSet {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         join_serializable(self.0.as_ref(), b",", ctx)
     }
 }
 
 impl EncodeIntoContext for UidElement {
-    fn encode_ctx(&self, ctx: &mut EncodeContext) -> std::io::Result<()> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
         match self {
             UidElement::Single(uid) => uid.encode_ctx(ctx),
             Range(start, end) => {
@@ -0,0 +1,171 @@
+//! Apple's XAPPLEPUSHSERVICE command
+
+use abnf_core::streaming::sp;
+use imap_types::{
+    core::{Atom, Vec1},
+    extensions::xapplepushservice::ApplePushValue,
+};
+use nom::{
+    branch::alt,
+    bytes::streaming::{tag, tag_no_case},
+    combinator::map,
+    multi::{separated_list0, separated_list1},
+    sequence::{delimited, preceded, separated_pair},
+};
+
+use crate::{
+    core::{astring, atom},
+    decode::IMAPResult,
+    encode::{utils::join_serializable, EncodeContext, EncodeIntoContext, WriteError},
+};
+
+/// ```abnf
+/// xapplepushservice = "XAPPLEPUSHSERVICE" 1*(SP aps-param)
+/// ```
+///
+/// Note: The same syntax is used for the untagged response.
+#[allow(clippy::type_complexity)]
+pub(crate) fn xapplepushservice(input: &[u8]) -> IMAPResult<&[u8], Vec1<(Atom, ApplePushValue)>> {
+    map(
+        preceded(
+            tag_no_case("XAPPLEPUSHSERVICE "),
+            separated_list1(sp, aps_param),
+        ),
+        Vec1::unvalidated,
+    )(input)
+}
+
+/// ```abnf
+/// aps-param = atom SP aps-value
+///
+/// aps-value = astring / "(" [astring *(SP astring)] ")"
+/// ```
+pub(crate) fn aps_param(input: &[u8]) -> IMAPResult<&[u8], (Atom, ApplePushValue)> {
+    separated_pair(
+        atom,
+        sp,
+        alt((
+            map(
+                delimited(tag("("), separated_list0(sp, astring), tag(")")),
+                ApplePushValue::List,
+            ),
+            map(astring, ApplePushValue::String),
+        )),
+    )(input)
+}
+
+impl EncodeIntoContext for (Atom<'_>, ApplePushValue<'_>) {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        let (key, value) = self;
+
+        key.encode_ctx(ctx)?;
+        ctx.write_all(b" ")?;
+        value.encode_ctx(ctx)
+    }
+}
+
+impl EncodeIntoContext for ApplePushValue<'_> {
+    fn encode_ctx(&self, ctx: &mut EncodeContext) -> Result<(), WriteError> {
+        match self {
+            ApplePushValue::String(value) => value.encode_ctx(ctx),
+            ApplePushValue::List(values) => {
+                ctx.write_all(b"(")?;
+                join_serializable(values, b" ", ctx)?;
+                ctx.write_all(b")")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use imap_types::{
+        command::{Command, CommandBody},
+        core::{AString, IString},
+        response::{Capability, Data, Response},
+    };
+
+    use super::*;
+    use crate::testing::{kat_inverse_command, kat_inverse_response};
+
+    #[test]
+    fn test_kat_inverse_command_xapplepushservice() {
+        kat_inverse_command(&[(
+            b"A XAPPLEPUSHSERVICE aps-version 2 aps-account-id 0715A26B-CA09-4730-A419-793000CA982E aps-device-token 2918390218931890821908309283098109381029309829018310983092892829 aps-subtopic com.apple.mobilemail mailboxes (INBOX \"Sent Messages\")\r\n".as_ref(),
+            b"".as_ref(),
+            Command::new(
+                "A",
+                CommandBody::xapplepushservice(vec![
+                    (
+                        Atom::try_from("aps-version").unwrap(),
+                        ApplePushValue::from(AString::try_from("2").unwrap()),
+                    ),
+                    (
+                        Atom::try_from("aps-account-id").unwrap(),
+                        ApplePushValue::from(
+                            AString::try_from("0715A26B-CA09-4730-A419-793000CA982E").unwrap(),
+                        ),
+                    ),
+                    (
+                        Atom::try_from("aps-device-token").unwrap(),
+                        ApplePushValue::from(
+                            AString::try_from(
+                                "2918390218931890821908309283098109381029309829018310983092892829",
+                            )
+                            .unwrap(),
+                        ),
+                    ),
+                    (
+                        Atom::try_from("aps-subtopic").unwrap(),
+                        ApplePushValue::from(AString::try_from("com.apple.mobilemail").unwrap()),
+                    ),
+                    (
+                        Atom::try_from("mailboxes").unwrap(),
+                        ApplePushValue::from(vec![
+                            AString::try_from("INBOX").unwrap(),
+                            AString::String(IString::try_from("Sent Messages").unwrap()),
+                        ]),
+                    ),
+                ])
+                .unwrap(),
+            )
+            .unwrap(),
+        )]);
+    }
+
+    #[test]
+    fn test_kat_inverse_response_xapplepushservice() {
+        kat_inverse_response(&[
+            (
+                b"* XAPPLEPUSHSERVICE aps-version \"2\" aps-topic \"com.apple.mail.XServer.1234\"\r\n"
+                    .as_ref(),
+                b"".as_ref(),
+                Response::Data(
+                    Data::xapplepushservice(vec![
+                        (
+                            Atom::try_from("aps-version").unwrap(),
+                            ApplePushValue::from(AString::String(
+                                IString::try_from("2").unwrap(),
+                            )),
+                        ),
+                        (
+                            Atom::try_from("aps-topic").unwrap(),
+                            ApplePushValue::from(AString::String(
+                                IString::try_from("com.apple.mail.XServer.1234").unwrap(),
+                            )),
+                        ),
+                    ])
+                    .unwrap(),
+                ),
+            ),
+            (
+                b"* CAPABILITY IMAP4REV1 XAPPLEPUSHSERVICE\r\n",
+                b"",
+                Response::Data(
+                    Data::capability(vec![Capability::Imap4Rev1, Capability::XApplePushService])
+                        .unwrap(),
+                ),
+            ),
+        ]);
+    }
+}
@@ -0,0 +1,121 @@
+//! The (legacy) XLIST command
+
+use abnf_core::streaming::sp;
+use imap_types::{command::CommandBody, response::Data};
+use nom::{bytes::streaming::tag_no_case, sequence::tuple};
+
+use crate::{
+    decode::IMAPResult,
+    mailbox::{list_mailbox, mailbox, mailbox_list},
+};
+
+/// ```abnf
+/// xlist = "XLIST" SP mailbox SP list-mailbox
+/// ```
+pub(crate) fn xlist(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
+    let mut parser = tuple((tag_no_case(b"XLIST "), mailbox, sp, list_mailbox));
+
+    let (remaining, (_, reference, _, mailbox_wildcard)) = parser(input)?;
+
+    Ok((
+        remaining,
+        CommandBody::XList {
+            reference,
+            mailbox_wildcard,
+        },
+    ))
+}
+
+/// ```abnf
+/// xlist-response = "XLIST" SP mailbox-list
+/// ```
+pub(crate) fn xlist_response(input: &[u8]) -> IMAPResult<&[u8], Data> {
+    let mut parser = tuple((tag_no_case(b"XLIST "), mailbox_list));
+
+    let (remaining, (_, (items, delimiter, mailbox))) = parser(input)?;
+
+    Ok((
+        remaining,
+        Data::XList {
+            items: items.unwrap_or_default(),
+            delimiter,
+            mailbox,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use imap_types::{
+        command::Command,
+        core::{Atom, QuotedChar},
+        flag::FlagNameAttribute,
+        mailbox::Mailbox,
+        response::{Capability, Response},
+    };
+
+    use super::*;
+    use crate::testing::{kat_inverse_command, kat_inverse_response};
+
+    #[test]
+    fn test_kat_inverse_command_xlist() {
+        kat_inverse_command(&[
+            (
+                b"A XLIST \"\" *\r\n".as_ref(),
+                b"".as_ref(),
+                Command::new("A", CommandBody::xlist("", "*").unwrap()).unwrap(),
+            ),
+            (
+                b"A XLIST INBOX %\r\n",
+                b"",
+                Command::new("A", CommandBody::xlist("inbox", "%").unwrap()).unwrap(),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_kat_inverse_response_xlist() {
+        kat_inverse_response(&[
+            (
+                b"* XLIST (\\HasNoChildren \\Inbox) \"/\" INBOX\r\n".as_ref(),
+                b"".as_ref(),
+                Response::Data(Data::XList {
+                    items: vec![
+                        FlagNameAttribute::from(Atom::try_from("HasNoChildren").unwrap()),
+                        FlagNameAttribute::Inbox,
+                    ],
+                    delimiter: Some(QuotedChar::try_from('/').unwrap()),
+                    mailbox: Mailbox::Inbox,
+                }),
+            ),
+            (
+                b"* XLIST (\\HasNoChildren \\AllMail) \"/\" \"[Gmail]/All Mail\"\r\n",
+                b"",
+                Response::Data(Data::XList {
+                    items: vec![
+                        FlagNameAttribute::from(Atom::try_from("HasNoChildren").unwrap()),
+                        FlagNameAttribute::AllMail,
+                    ],
+                    delimiter: Some(QuotedChar::try_from('/').unwrap()),
+                    mailbox: Mailbox::try_from("[Gmail]/All Mail").unwrap(),
+                }),
+            ),
+            (
+                b"* XLIST (\\Spam \\Starred) NIL Junk\r\n",
+                b"",
+                Response::Data(Data::XList {
+                    items: vec![FlagNameAttribute::Spam, FlagNameAttribute::Starred],
+                    delimiter: None,
+                    mailbox: Mailbox::try_from("Junk").unwrap(),
+                }),
+            ),
+            (
+                b"* CAPABILITY IMAP4REV1 XLIST\r\n",
+                b"",
+                Response::Data(
+                    Data::capability(vec![Capability::Imap4Rev1, Capability::XList]).unwrap(),
+                ),
+            ),
+        ]);
+    }
+}
@@ -1,26 +1,32 @@
-use std::num::NonZeroU32;
+use alloc::vec::Vec;
+use core::num::NonZeroU32;
 
 use abnf_core::streaming::sp;
+#[cfg(feature = "ext_condstore_qresync")]
+use imap_types::extensions::condstore_qresync::ModSeq;
 use imap_types::{
     core::{AString, NString8, Vec1},
     fetch::{MessageDataItem, MessageDataItemName, Part, PartSpecifier, Section},
+    flag::FlagFetch,
+    sequence::Uid,
 };
 use nom::{
     branch::alt,
     bytes::streaming::{tag, tag_no_case},
     character::streaming::char,
     combinator::{map, opt, value},
-    multi::separated_list1,
     sequence::{delimited, preceded, tuple},
 };
 
 #[cfg(feature = "ext_condstore_qresync")]
 use crate::extensions::condstore_qresync::mod_sequence_value;
+#[cfg(feature = "ext_gmail")]
+use crate::extensions::gmail::{x_gm_fetch_att, x_gm_msg_att};
 use crate::{
     body::body,
-    core::{astring, nstring, number, nz_number},
+    core::{astring, keyword, nstring, number, nz_number, separated_list1, unknown_keyword},
     datetime::date_time,
-    decode::IMAPResult,
+    decode::{recursion_limits, IMAPResult},
     envelope::envelope,
     extensions::binary::{literal8, partial, section_binary},
     flag::flag_fetch,
@@ -38,84 +44,82 @@ use crate::{
 ///             "BINARY"      section-binary [partial] / ; RFC 3516
 ///             "BINARY.PEEK" section-binary [partial] / ; RFC 3516
 ///             "BINARY.SIZE" section-binary           / ; RFC 3516
-///             "MODSEQ"                                 ; RFC 7162
+///             "MODSEQ"                               / ; RFC 7162
+///             x-gm-fetch-att                           ; Gmail
 /// ```
+///
+/// Note: The item name is recognized first and used to select the parser directly instead of trying
+/// (and backtracking from) all alternatives.
 pub(crate) fn fetch_att(input: &[u8]) -> IMAPResult<&[u8], MessageDataItemName> {
-    alt((
-        value(MessageDataItemName::Envelope, tag_no_case(b"ENVELOPE")),
-        value(MessageDataItemName::Flags, tag_no_case(b"FLAGS")),
-        value(
-            MessageDataItemName::InternalDate,
-            tag_no_case(b"INTERNALDATE"),
-        ),
-        value(
-            MessageDataItemName::BodyStructure,
-            tag_no_case(b"BODYSTRUCTURE"),
-        ),
-        map(
-            tuple((
-                tag_no_case(b"BODY.PEEK"),
-                section,
-                opt(delimited(
-                    tag(b"<"),
-                    tuple((number, tag(b"."), nz_number)),
-                    tag(b">"),
-                )),
-            )),
-            |(_, section, byterange)| MessageDataItemName::BodyExt {
-                section,
-                partial: byterange.map(|(start, _, end)| (start, end)),
-                peek: true,
-            },
-        ),
-        map(
-            tuple((
-                tag_no_case(b"BODY"),
-                section,
-                opt(delimited(
-                    tag(b"<"),
-                    tuple((number, tag(b"."), nz_number)),
-                    tag(b">"),
-                )),
-            )),
-            |(_, section, byterange)| MessageDataItemName::BodyExt {
+    // Length of the longest keyword handled below, i.e., "BODYSTRUCTURE" or "RFC822.HEADER".
+    let mut buffer = [0; 13];
+
+    let keyword = match keyword(input, &mut buffer)? {
+        Some(keyword) => keyword,
+        None => return unknown_keyword(input),
+    };
+
+    let remaining = &input[keyword.len()..];
+
+    match keyword {
+        b"ENVELOPE" => Ok((remaining, MessageDataItemName::Envelope)),
+        b"FLAGS" => Ok((remaining, MessageDataItemName::Flags)),
+        b"INTERNALDATE" => Ok((remaining, MessageDataItemName::InternalDate)),
+        b"BODYSTRUCTURE" => Ok((remaining, MessageDataItemName::BodyStructure)),
+        b"BODY" if remaining[0] == b'[' => body_ext_name(false)(remaining),
+        b"BODY" => Ok((remaining, MessageDataItemName::Body)),
+        b"BODY.PEEK" => body_ext_name(true)(remaining),
+        b"BINARY" => map(
+            tuple((section_binary, opt(partial))),
+            |(section, partial)| MessageDataItemName::Binary {
                 section,
-                partial: byterange.map(|(start, _, end)| (start, end)),
+                partial,
                 peek: false,
             },
-        ),
-        map(
-            tuple((tag_no_case("BINARY.PEEK"), section_binary, opt(partial))),
-            |(_, section, partial)| MessageDataItemName::Binary {
+        )(remaining),
+        b"BINARY.PEEK" => map(
+            tuple((section_binary, opt(partial))),
+            |(section, partial)| MessageDataItemName::Binary {
                 section,
                 partial,
                 peek: true,
             },
-        ),
-        map(
-            tuple((tag_no_case("BINARY"), section_binary, opt(partial))),
-            |(_, section, partial)| MessageDataItemName::Binary {
-                section,
-                partial,
-                peek: false,
-            },
-        ),
-        map(
-            preceded(tag_no_case("BINARY.SIZE"), section_binary),
-            |section| MessageDataItemName::BinarySize { section },
-        ),
-        value(MessageDataItemName::Body, tag_no_case(b"BODY")),
-        value(MessageDataItemName::Uid, tag_no_case(b"UID")),
-        value(
-            MessageDataItemName::Rfc822Header,
-            tag_no_case(b"RFC822.HEADER"),
-        ),
-        value(MessageDataItemName::Rfc822Size, tag_no_case(b"RFC822.SIZE")),
-        value(MessageDataItemName::Rfc822Text, tag_no_case(b"RFC822.TEXT")),
-        value(MessageDataItemName::Rfc822, tag_no_case(b"RFC822")),
+        )(remaining),
+        b"BINARY.SIZE" => map(section_binary, |section| MessageDataItemName::BinarySize {
+            section,
+        })(remaining),
+        b"UID" => Ok((remaining, MessageDataItemName::Uid)),
+        b"RFC822.HEADER" => Ok((remaining, MessageDataItemName::Rfc822Header)),
+        b"RFC822.SIZE" => Ok((remaining, MessageDataItemName::Rfc822Size)),
+        b"RFC822.TEXT" => Ok((remaining, MessageDataItemName::Rfc822Text)),
+        b"RFC822" => Ok((remaining, MessageDataItemName::Rfc822)),
         #[cfg(feature = "ext_condstore_qresync")]
-        value(MessageDataItemName::ModSeq, tag_no_case(b"MODSEQ")),
-    ))(input)
+        b"MODSEQ" => Ok((remaining, MessageDataItemName::ModSeq)),
+        #[cfg(feature = "ext_gmail")]
+        b"X-GM-MSGID" | b"X-GM-THRID" | b"X-GM-LABELS" => x_gm_fetch_att(input),
+        _ => unknown_keyword(input),
+    }
+}
+
+/// `section ["<" number "." nz-number ">"]`
+fn body_ext_name<'a>(
+    peek: bool,
+) -> impl FnMut(&'a [u8]) -> IMAPResult<'a, &'a [u8], MessageDataItemName<'a>> {
+    map(
+        tuple((
+            section,
+            opt(delimited(
+                tag(b"<"),
+                tuple((number, tag(b"."), nz_number)),
+                tag(b">"),
+            )),
+        )),
+        move |(section, byterange)| MessageDataItemName::BodyExt {
+            section,
+            partial: byterange.map(|(start, _, end)| (start, end)),
+            peek,
+        },
+    )
 }
 
 /// ```abnf
@@ -123,17 +127,69 @@ pub(crate) fn fetch_att(input: &[u8]) -> IMAPResult<&[u8], MessageDataItemName>
 ///           (msg-att-dynamic / msg-att-static) *(SP (msg-att-dynamic / msg-att-static))
 ///           ")"
 /// ```
+///
+/// Note: Extended by `x-gm-msg-att` when the `ext_gmail` feature is enabled.
 pub(crate) fn msg_att(input: &[u8]) -> IMAPResult<&[u8], Vec1<MessageDataItem>> {
     delimited(
         tag(b"("),
-        map(
-            separated_list1(sp, alt((msg_att_dynamic, msg_att_static))),
-            Vec1::unvalidated,
-        ),
+        map(separated_list1(sp, msg_att_item), Vec1::unvalidated),
         tag(b")"),
     )(input)
 }
 
+/// Parse a single item of `msg-att`.
+///
+/// FETCH responses dominate real-world traffic. Thus, frequent items are recognized by their
+/// keyword and parsed directly instead of trying (and backtracking from) all alternatives. All
+/// other items are handled by the generic parsers.
+fn msg_att_item(input: &[u8]) -> IMAPResult<&[u8], MessageDataItem> {
+    // Length of the longest keyword handled below, i.e., "BODYSTRUCTURE".
+    let mut buffer = [0; 13];
+
+    if let Some(keyword) = keyword(input, &mut buffer)? {
+        let remaining = &input[keyword.len()..];
+
+        match (keyword, remaining[0]) {
+            (b"UID", b' ') => {
+                return map(preceded(sp, uniqueid), MessageDataItem::Uid)(remaining);
+            }
+            (b"FLAGS", b' ') => {
+                return map(preceded(sp, flag_fetch_list), MessageDataItem::Flags)(remaining);
+            }
+            (b"RFC822.SIZE", b' ') => {
+                return map(preceded(sp, number), MessageDataItem::Rfc822Size)(remaining);
+            }
+            (b"INTERNALDATE", b' ') => {
+                return map(preceded(sp, date_time), MessageDataItem::InternalDate)(remaining);
+            }
+            (b"ENVELOPE", b' ') => {
+                return map(preceded(sp, envelope), MessageDataItem::Envelope)(remaining);
+            }
+            (b"BODYSTRUCTURE", b' ') => {
+                return map(
+                    preceded(sp, body(recursion_limits().body)),
+                    MessageDataItem::BodyStructure,
+                )(remaining);
+            }
+            (b"BODY", b'[') => {
+                return body_ext(remaining);
+            }
+            #[cfg(feature = "ext_condstore_qresync")]
+            (b"MODSEQ", b' ') => {
+                return map(preceded(sp, mod_sequence_list), MessageDataItem::ModSeq)(remaining);
+            }
+            _ => {}
+        }
+    }
+
+    alt((
+        msg_att_dynamic,
+        msg_att_static,
+        #[cfg(feature = "ext_gmail")]
+        x_gm_msg_att,
+    ))(input)
+}
+
 /// ```abnf
 /// msg-att-dynamic = "FLAGS" SP "(" [flag-fetch *(SP flag-fetch)] ")"
 /// ```
@@ -141,18 +197,12 @@ pub(crate) fn msg_att(input: &[u8]) -> IMAPResult<&[u8], Vec1<MessageDataItem>>
 /// Note: MAY change for a message
 pub(crate) fn msg_att_dynamic(input: &[u8]) -> IMAPResult<&[u8], MessageDataItem> {
     let flags = map(
-        preceded(
-            tag_no_case(b"FLAGS "),
-            delimited(char('('), opt(separated_list1(sp, flag_fetch)), char(')')),
-        ),
-        |flags| MessageDataItem::Flags(flags.unwrap_or_default()),
+        preceded(tag_no_case(b"FLAGS "), flag_fetch_list),
+        MessageDataItem::Flags,
     );
     #[cfg(feature = "ext_condstore_qresync")]
     let modseq = map(
-        preceded(
-            tag_no_case("MODSEQ "),
-            delimited(char('('), mod_sequence_value, char(')')),
-        ),
+        preceded(tag_no_case("MODSEQ "), mod_sequence_list),
         MessageDataItem::ModSeq,
     );
 
@@ -167,6 +217,20 @@ pub(crate) fn msg_att_dynamic(input: &[u8]) -> IMAPResult<&[u8], MessageDataItem
     Ok((remaining, item))
 }
 
+/// `"(" [flag-fetch *(SP flag-fetch)] ")"`
+fn flag_fetch_list(input: &[u8]) -> IMAPResult<&[u8], Vec<FlagFetch>> {
+    map(
+        delimited(char('('), opt(separated_list1(sp, flag_fetch)), char(')')),
+        Option::unwrap_or_default,
+    )(input)
+}
+
+/// `"(" mod-sequence-value ")"`
+#[cfg(feature = "ext_condstore_qresync")]
+fn mod_sequence_list(input: &[u8]) -> IMAPResult<&[u8], ModSeq> {
+    delimited(char('('), mod_sequence_value, char(')'))(input)
+}
+
 /// ```abnf
 /// msg-att-static = "ENVELOPE" SP envelope /
 ///                  "INTERNALDATE" SP date-time /
@@ -207,27 +271,17 @@ pub(crate) fn msg_att_static(input: &[u8]) -> IMAPResult<&[u8], MessageDataItem>
             MessageDataItem::Rfc822,
         ),
         map(
-            preceded(tag_no_case(b"BODYSTRUCTURE "), body(8)),
+            preceded(
+                tag_no_case(b"BODYSTRUCTURE "),
+                body(recursion_limits().body),
+            ),
             MessageDataItem::BodyStructure,
         ),
         map(
-            preceded(tag_no_case(b"BODY "), body(8)),
+            preceded(tag_no_case(b"BODY "), body(recursion_limits().body)),
             MessageDataItem::Body,
         ),
-        map(
-            tuple((
-                tag_no_case(b"BODY"),
-                section,
-                opt(delimited(tag(b"<"), number, tag(b">"))),
-                sp,
-                nstring,
-            )),
-            |(_, section, origin, _, data)| MessageDataItem::BodyExt {
-                section,
-                origin,
-                data,
-            },
-        ),
+        preceded(tag_no_case(b"BODY"), body_ext),
         map(
             preceded(tag_no_case(b"UID "), uniqueid),
             MessageDataItem::Uid,
@@ -251,12 +305,29 @@ pub(crate) fn msg_att_static(input: &[u8]) -> IMAPResult<&[u8], MessageDataItem>
     ))(input)
 }
 
+/// `section ["<" number ">"] SP nstring` (after `"BODY"`)
+fn body_ext(input: &[u8]) -> IMAPResult<&[u8], MessageDataItem> {
+    map(
+        tuple((
+            section,
+            opt(delimited(tag(b"<"), number, tag(b">"))),
+            sp,
+            nstring,
+        )),
+        |(section, origin, _, data)| MessageDataItem::BodyExt {
+            section,
+            origin,
+            data,
+        },
+    )(input)
+}
+
 #[inline]
 /// `uniqueid = nz-number`
 ///
 /// Note: Strictly ascending
-pub(crate) fn uniqueid(input: &[u8]) -> IMAPResult<&[u8], NonZeroU32> {
-    nz_number(input)
+pub(crate) fn uniqueid(input: &[u8]) -> IMAPResult<&[u8], Uid> {
+    map(nz_number, Uid::from)(input)
 }
 
 /// `section = "[" [section-spec] "]"`
@@ -483,7 +554,7 @@ mod tests {
                 b"RFC822.TEXT NIL",
             ),
             (
-                MessageDataItem::Uid(NonZeroU32::try_from(u32::MAX).unwrap()),
+                MessageDataItem::Uid(Uid::new(u32::MAX).unwrap()),
                 b"UID 4294967295",
             ),
         ];
@@ -542,4 +613,46 @@ mod tests {
             known_answer_test_encode(test)
         }
     }
+
+    #[test]
+    fn test_msg_att_item_fast_path() {
+        let tests: &[&[u8]] = &[
+            b"UID 42)",
+            b"uid 42)",
+            b"FLAGS ()",
+            b"FLAGS (\\Seen \\Recent custom))",
+            b"RFC822.SIZE 1337)",
+            b"INTERNALDATE \"17-Jul-1996 02:44:25 -0700\")",
+            b"ENVELOPE (NIL NIL NIL NIL NIL NIL NIL NIL NIL NIL))",
+            b"BODYSTRUCTURE (\"TEXT\" \"PLAIN\" NIL NIL NIL \"7BIT\" 0 0))",
+            b"BODY[] NIL)",
+            b"body[HEADER]<42> {3}\r\nfoo)",
+            // Handled by the generic parsers.
+            b"BODY (\"TEXT\" \"PLAIN\" NIL NIL NIL \"7BIT\" 0 0))",
+            b"RFC822 NIL)",
+            b"BINARY[1] NIL)",
+        ];
+
+        for test in tests {
+            let (remaining, got) = msg_att_item(test).unwrap();
+            let (expected_remaining, expected) =
+                alt((msg_att_dynamic, msg_att_static))(test).unwrap();
+
+            assert_eq!(remaining, expected_remaining);
+            assert_eq!(got, expected);
+        }
+
+        for test in [b"UID".as_ref(), b"UID 42", b"FLAGS (\\Seen", b"BODY[]"] {
+            assert!(matches!(msg_att_item(test), Err(nom::Err::Incomplete(_))));
+        }
+
+        for test in [
+            b"UID x)".as_ref(),
+            b"FLAGS x)",
+            b"BODY[x] NIL)",
+            b"UNKNOWN 1)",
+        ] {
+            assert!(matches!(msg_att_item(test), Err(nom::Err::Error(_))));
+        }
+    }
 }
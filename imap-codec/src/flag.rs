@@ -1,3 +1,5 @@
+use alloc::vec::Vec;
+
 use abnf_core::streaming::sp;
 use imap_types::flag::{Flag, FlagFetch, FlagNameAttribute, FlagPerm};
 use nom::{
@@ -5,11 +7,13 @@ use nom::{
     bytes::streaming::tag,
     character::streaming::char,
     combinator::{map, recognize, value},
-    multi::{separated_list0, separated_list1},
     sequence::{delimited, preceded, tuple},
 };
 
-use crate::{core::atom, decode::IMAPResult};
+use crate::{
+    core::{atom, separated_list0, separated_list1},
+    decode::{quirks, IMAPResult},
+};
 
 /// ```abnf
 /// flag = "\Answered" /
@@ -50,11 +54,40 @@ pub(crate) fn flag(input: &[u8]) -> IMAPResult<&[u8], Flag> {
 //     preceded(tag(b"\\"), atom)(input)
 // }
 
+/// [`flag`] as used in responses.
+///
+/// With [`Quirks::bare_system_flags`](crate::decode::Quirks::bare_system_flags), a keyword that is named like a system flag, e.g., `Seen`, is
+/// interpreted as the system flag, e.g., `\Seen`.
+pub(crate) fn flag_response(input: &[u8]) -> IMAPResult<&[u8], Flag> {
+    let (remaining, flag) = flag(input)?;
+
+    let flag = match flag {
+        Flag::Keyword(atom) if quirks().bare_system_flags && is_system_flag_name(atom.as_ref()) => {
+            log::warn!("Rectified bare system flag `{}`", atom.as_ref());
+            Flag::system(atom)
+        }
+        flag => flag,
+    };
+
+    Ok((remaining, flag))
+}
+
+fn is_system_flag_name(name: &str) -> bool {
+    ["Answered", "Deleted", "Draft", "Flagged", "Seen"]
+        .iter()
+        .any(|system| system.eq_ignore_ascii_case(name))
+}
+
 /// `flag-list = "(" [flag *(SP flag)] ")"`
 pub(crate) fn flag_list(input: &[u8]) -> IMAPResult<&[u8], Vec<Flag>> {
     delimited(tag(b"("), separated_list0(sp, flag), tag(b")"))(input)
 }
 
+/// [`flag_list`] as used in responses (see [`flag_response`]).
+pub(crate) fn flag_list_response(input: &[u8]) -> IMAPResult<&[u8], Vec<Flag>> {
+    delimited(tag(b"("), separated_list0(sp, flag_response), tag(b")"))(input)
+}
+
 /// `flag-fetch = flag / "\Recent"`
 pub(crate) fn flag_fetch(input: &[u8]) -> IMAPResult<&[u8], FlagFetch> {
     if let Ok((rem, peek)) = recognize(tuple((char('\\'), atom)))(input) {
@@ -63,14 +96,23 @@ pub(crate) fn flag_fetch(input: &[u8]) -> IMAPResult<&[u8], FlagFetch> {
         }
     }
 
-    map(flag, FlagFetch::Flag)(input)
+    if quirks().bare_system_flags {
+        if let Ok((rem, peek)) = atom(input) {
+            if peek.as_ref().eq_ignore_ascii_case("recent") {
+                log::warn!("Rectified bare system flag `{}`", peek.as_ref());
+                return Ok((rem, FlagFetch::Recent));
+            }
+        }
+    }
+
+    map(flag_response, FlagFetch::Flag)(input)
 }
 
 /// `flag-perm = flag / "\*"`
 pub(crate) fn flag_perm(input: &[u8]) -> IMAPResult<&[u8], FlagPerm> {
     alt((
         value(FlagPerm::Asterisk, tag("\\*")),
-        map(flag, FlagPerm::Flag),
+        map(flag_response, FlagPerm::Flag),
     ))(input)
 }
 
@@ -173,6 +215,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_flag_response_quirk() {
+        let tests = [
+            ("\\seen)", Flag::Seen),
+            ("Deleted)", {
+                #[cfg(not(feature = "quirk_bare_system_flags"))]
+                {
+                    Flag::Keyword(Atom::try_from("Deleted").unwrap())
+                }
+                #[cfg(feature = "quirk_bare_system_flags")]
+                {
+                    Flag::Deleted
+                }
+            }),
+            (
+                "Deletedx)",
+                Flag::Keyword(Atom::try_from("Deletedx").unwrap()),
+            ),
+        ];
+
+        for (test, expected) in tests {
+            let (rem, got) = flag_response(test.as_bytes()).unwrap();
+            assert_eq!(rem.len(), 1);
+            assert_eq!(expected, got);
+        }
+
+        // Commands are not affected.
+        let (_, got) = flag(b"Deleted)").unwrap();
+        assert_eq!(got, Flag::Keyword(Atom::try_from("Deleted").unwrap()));
+    }
+
     #[test]
     fn test_parse_mbx_list_flags() {
         let tests = [
@@ -42,14 +42,19 @@
 //! }
 //! # }
 //! ```
-use std::{collections::VecDeque, ops::Range};
+use alloc::{collections::VecDeque, vec::Vec};
+use core::ops::Range;
 
 use imap_types::{
     core::{LiteralMode, Tag},
     secret::Secret,
 };
+use memchr::memchr3;
 
-use crate::decode::Decoder;
+use crate::{
+    decode::Decoder,
+    encode::{Counter, Writer},
+};
 
 /// Splits IMAP bytes into line and literal fragments.
 ///
@@ -68,6 +73,18 @@ pub struct Fragmentizer {
     max_message_size: Option<u32>,
     /// Whether the size limit is exceeded for the current message.
     max_message_size_exceeded: bool,
+    /// Upper limit for the length of lines (including the line ending).
+    max_line_length: Option<usize>,
+    /// Where the line length limit was exceeded in the current message. Bytes after this position
+    /// are dropped.
+    max_line_length_exceeded_at: Option<usize>,
+    /// Upper limit for the size of literals.
+    max_literal_size: Option<u32>,
+    /// Where the literal size limit was exceeded in the current message (and the announcement of
+    /// the literal). Bytes after this position are dropped.
+    max_literal_size_exceeded_at: Option<(usize, LiteralAnnouncement)>,
+    /// Literals of at least this size are streamed automatically.
+    stream_literals_from: Option<u32>,
     /// The current message was poisoned. The message will still be parsed, but the decoding
     /// will fail.
     message_poisoned: bool,
@@ -77,6 +94,12 @@ pub struct Fragmentizer {
     /// Parser for the next fragment of the current message. Is `None` if no fragment is expected
     /// because the message is complete.
     parser: Option<Parser>,
+    /// Fragments of the current message.
+    message_fragments: Vec<FragmentInfo>,
+    /// Position of the current message in the stream of enqueued bytes.
+    message_offset: u64,
+    /// Number of bytes that were dequeued from the stream of enqueued bytes.
+    stream_offset: u64,
 }
 
 impl Fragmentizer {
@@ -88,9 +111,17 @@ impl Fragmentizer {
             unparsed_buffer: VecDeque::new(),
             max_message_size: Some(max_message_size),
             max_message_size_exceeded: false,
+            max_line_length: None,
+            max_line_length_exceeded_at: None,
+            max_literal_size: None,
+            max_literal_size_exceeded_at: None,
+            stream_literals_from: None,
             message_poisoned: false,
             message_buffer: Vec::new(),
             parser: Some(Parser::Line(LineParser::new(0))),
+            message_fragments: Vec::new(),
+            message_offset: 0,
+            stream_offset: 0,
         }
     }
 
@@ -105,12 +136,53 @@ impl Fragmentizer {
             unparsed_buffer: VecDeque::new(),
             max_message_size: None,
             max_message_size_exceeded: false,
+            max_line_length: None,
+            max_line_length_exceeded_at: None,
+            max_literal_size: None,
+            max_literal_size_exceeded_at: None,
+            stream_literals_from: None,
             message_poisoned: false,
             message_buffer: Vec::new(),
             parser: Some(Parser::Line(LineParser::new(0))),
+            message_fragments: Vec::new(),
+            message_offset: 0,
+            stream_offset: 0,
         }
     }
 
+    /// Limits the length of lines (including the line ending).
+    ///
+    /// The limit is enforced while a line is received, i.e., without waiting for the line ending.
+    /// When the limit is exceeded, [`Fragmentizer::is_max_line_length_exceeded`] returns true,
+    /// bytes after the limit are dropped, and [`Fragmentizer::decode_message`] will fail. As with
+    /// the message size, fragmentation seamlessly continues with the following message.
+    pub fn with_max_line_length(mut self, max_line_length: usize) -> Self {
+        self.max_line_length = Some(max_line_length);
+        self
+    }
+
+    /// Limits the size of literals.
+    ///
+    /// The limit is enforced when a literal is announced. When the limit is exceeded,
+    /// [`Fragmentizer::limit_exceeded`] returns [`LimitExceeded::LiteralSize`], the literal (and
+    /// the rest of the message) is dropped, and [`Fragmentizer::decode_message`] will fail.
+    pub fn with_max_literal_size(mut self, max_literal_size: u32) -> Self {
+        self.max_literal_size = Some(max_literal_size);
+        self
+    }
+
+    /// Streams all literals of at least `min_length` bytes automatically.
+    ///
+    /// This works like calling [`Fragmentizer::stream_literal`] after each announcement of such
+    /// a literal. Thus, the literal bytes are never retained in the message buffer. When
+    /// [`Fragmentizer::is_literal_streamed`] returns true, the literal must be drained (or
+    /// skipped) via the [`LiteralHandle`] returned by [`Fragmentizer::stream_literal`]. Other than
+    /// with manual streaming, the [`FragmentInfo`] of the announcing line is up to date.
+    pub fn with_streamed_literals(mut self, min_length: u32) -> Self {
+        self.stream_literals_from = Some(min_length);
+        self
+    }
+
     /// Continue parsing the current message until the next fragment is detected.
     ///
     /// Returns `None` if more bytes need to be enqueued via [`Fragmentizer::enqueue_bytes`].
@@ -118,6 +190,37 @@ impl Fragmentizer {
     /// then the message was fully parsed. The following call of this function will then start
     /// the next message.
     pub fn progress(&mut self) -> Option<FragmentInfo> {
+        let mut fragment = self.progress_fragment()?;
+
+        if let (
+            Some(min_length),
+            FragmentInfo::Line {
+                end,
+                announcement: Some(announcement),
+                ..
+            },
+        ) = (self.stream_literals_from, &mut fragment)
+        {
+            if announcement.length >= min_length {
+                if let Some(removed) = self.start_streamed_literal() {
+                    *end -= removed;
+
+                    if let Some(last) = self.message_fragments.last_mut() {
+                        *last = fragment;
+                    }
+                }
+            }
+        }
+
+        Some(fragment)
+    }
+
+    fn progress_fragment(&mut self) -> Option<FragmentInfo> {
+        if let Some(Parser::StreamedLiteral { .. }) = self.parser {
+            // The literal must be drained via `LiteralHandle` first
+            return None;
+        }
+
         let parser = match &mut self.parser {
             Some(parser) => {
                 // Continue current message
@@ -126,20 +229,53 @@ impl Fragmentizer {
             None => {
                 // Start next message
                 self.max_message_size_exceeded = false;
+                self.max_line_length_exceeded_at = None;
+                self.max_literal_size_exceeded_at = None;
                 self.message_poisoned = false;
                 self.message_buffer.clear();
+                self.message_fragments.clear();
+                self.message_offset = self.stream_offset;
                 self.parser.insert(Parser::Line(LineParser::new(0)))
             }
         };
 
         // Progress fragment
         let (parsed_byte_count, fragment) = match parser {
-            Parser::Line(parser) => parser.parse(&self.unparsed_buffer),
+            Parser::Line(parser) => {
+                let result = parser.parse(&self.unparsed_buffer);
+
+                if let Some(max_line_length) = self.max_line_length {
+                    if self.max_line_length_exceeded_at.is_none()
+                        && parser.end - parser.start > max_line_length
+                    {
+                        self.max_line_length_exceeded_at = Some(parser.start + max_line_length);
+                    }
+                }
+
+                result
+            }
             Parser::Literal(parser) => parser.parse(&self.unparsed_buffer),
+            Parser::StreamedLiteral { .. } => unreachable!(),
         };
         self.dequeue_parsed_bytes(parsed_byte_count);
 
         if let Some(fragment) = fragment {
+            if let FragmentInfo::Line {
+                end,
+                announcement: Some(announcement),
+                ..
+            } = fragment
+            {
+                if let Some(max_literal_size) = self.max_literal_size {
+                    if self.max_literal_size_exceeded_at.is_none()
+                        && announcement.length > max_literal_size
+                    {
+                        self.max_literal_size_exceeded_at = Some((end, announcement));
+                    }
+                }
+            }
+
+            self.message_fragments.push(fragment);
             self.parser = match fragment {
                 // Finish current message
                 FragmentInfo::Line {
@@ -159,6 +295,76 @@ impl Fragmentizer {
         fragment
     }
 
+    /// Streams the announced literal instead of adding it to the current message.
+    ///
+    /// Call this after [`Fragmentizer::progress`] returned a line with a literal announcement,
+    /// e.g., when the announced length is large. The literal data can then be drained chunk by chunk
+    /// via the returned [`LiteralHandle`]. In the current message, the announcement is replaced with
+    /// an empty literal, e.g., `{52428800}` becomes `{0}`. Thus, the message can be decoded as usual
+    /// and the application is responsible for putting the literal data in its place.
+    ///
+    /// Until the literal was drained completely, [`Fragmentizer::progress`] returns `None`. Call
+    /// this function again (after more bytes were enqueued) to get the handle again.
+    ///
+    /// Returns `None` if no literal is expected or if a limit was exceeded for the current message.
+    ///
+    /// Note: The end of the announcing line changes. Thus, its [`FragmentInfo`] is outdated.
+    pub fn stream_literal(&mut self) -> Option<LiteralHandle<'_>> {
+        if let Some(Parser::Literal(_)) = self.parser {
+            self.start_streamed_literal()?;
+        }
+
+        match self.parser {
+            Some(Parser::StreamedLiteral { length, .. }) => Some(LiteralHandle {
+                fragmentizer: self,
+                length,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns whether a literal is streamed and must be drained via [`LiteralHandle`].
+    pub fn is_literal_streamed(&self) -> bool {
+        matches!(self.parser, Some(Parser::StreamedLiteral { .. }))
+    }
+
+    /// Switches to streaming of the expected literal.
+    ///
+    /// Returns the number of bytes that were removed from the announcing line.
+    fn start_streamed_literal(&mut self) -> Option<usize> {
+        let (start, end, remaining) = match self.parser {
+            Some(Parser::Literal(LiteralParser {
+                start,
+                end,
+                remaining,
+            })) => (start, end, remaining),
+            _ => return None,
+        };
+
+        if start != end
+            || self.max_message_size_exceeded
+            || self.max_line_length_exceeded_at.is_some()
+            || self.max_literal_size_exceeded_at.is_some()
+            || self.message_buffer.len() != start
+        {
+            return None;
+        }
+
+        let digits = announced_length_range(&self.message_buffer)?;
+        let removed = digits.len() - 1;
+        self.message_buffer.drain(digits.start + 1..digits.end);
+        self.message_buffer[digits.start] = b'0';
+
+        // The maximum message size is not affected because the message got shorter.
+        self.parser = Some(Parser::StreamedLiteral {
+            position: start - removed,
+            length: remaining,
+            remaining,
+        });
+
+        Some(removed)
+    }
+
     /// Enqueues more byte that can be parsed by [`Fragmentizer::progress`].
     ///
     /// Note that the message size limit is not enforced on the enqueued bytes. You can control
@@ -179,6 +385,32 @@ impl Fragmentizer {
         &self.message_buffer[start..end]
     }
 
+    /// Returns the fragments of the current message that were parsed so far.
+    pub fn message_fragments(&self) -> &[FragmentInfo] {
+        &self.message_fragments
+    }
+
+    /// Returns the range of the current message in the stream of enqueued bytes.
+    ///
+    /// The stream starts with the first byte ever enqueued via [`Fragmentizer::enqueue_bytes`].
+    /// Other than [`Fragmentizer::message_bytes`], the range also covers bytes that were dropped
+    /// because a limit was exceeded. This is useful, e.g., for zero-copy forwarding or to report
+    /// the position of a malformed message.
+    pub fn message_span(&self) -> Range<u64> {
+        self.message_offset..self.stream_offset
+    }
+
+    /// Returns the range of a fragment of the current message in the stream of enqueued bytes.
+    ///
+    /// See [`Fragmentizer::message_span`].
+    ///
+    /// Note: After [`Fragmentizer::stream_literal`] was used, this is only accurate for fragments
+    /// before the streamed literal.
+    pub fn fragment_span(&self, fragment_info: FragmentInfo) -> Range<u64> {
+        let range = fragment_info.range();
+        self.message_offset + range.start as u64..self.message_offset + range.end as u64
+    }
+
     /// Returns whether the current message was fully parsed.
     ///
     /// If it returns true then it makes sense to call [`Fragmentizer::decode_message`]
@@ -196,6 +428,8 @@ impl Fragmentizer {
     ///   [`Fragmentizer::is_message_complete`].
     /// - The size limit might be exceeded and bytes might be dropped. You can check this
     ///   via [`Fragmentizer::is_max_message_size_exceeded`]
+    /// - The line length limit might be exceeded and bytes might be dropped. You can check this
+    ///   via [`Fragmentizer::is_max_line_length_exceeded`]
     pub fn message_bytes(&self) -> &[u8] {
         &self.message_buffer
     }
@@ -205,6 +439,38 @@ impl Fragmentizer {
         self.max_message_size_exceeded
     }
 
+    /// Returns whether the line length limit is exceeded for the current message.
+    ///
+    /// This may return true before the message is complete, e.g., to close the connection early.
+    pub fn is_max_line_length_exceeded(&self) -> bool {
+        self.max_line_length_exceeded_at.is_some()
+    }
+
+    /// Returns whether the literal size limit is exceeded for the current message.
+    pub fn is_max_literal_size_exceeded(&self) -> bool {
+        self.max_literal_size_exceeded_at.is_some()
+    }
+
+    /// Returns which limit is exceeded for the current message (if any).
+    ///
+    /// When multiple limits are exceeded, the line length is reported first, followed by the
+    /// literal size, and the message size.
+    pub fn limit_exceeded(&self) -> Option<LimitExceeded> {
+        if self.max_line_length_exceeded_at.is_some() {
+            return Some(LimitExceeded::LineLength);
+        }
+
+        if let Some((_, announcement)) = self.max_literal_size_exceeded_at {
+            return Some(LimitExceeded::LiteralSize { announcement });
+        }
+
+        if self.max_message_size_exceeded {
+            return Some(LimitExceeded::MessageSize);
+        }
+
+        None
+    }
+
     /// Returns whether the current message was explicitly poisoned to prevent decoding.
     pub fn is_message_poisoned(&self) -> bool {
         self.message_poisoned
@@ -219,8 +485,12 @@ impl Fragmentizer {
     /// client. Otherwise consider using [`Fragmentizer::poison_message`].
     pub fn skip_message(&mut self) {
         self.max_message_size_exceeded = false;
+        self.max_line_length_exceeded_at = None;
+        self.max_literal_size_exceeded_at = None;
         self.message_poisoned = false;
         self.message_buffer.clear();
+        self.message_fragments.clear();
+        self.message_offset = self.stream_offset;
         self.parser = Some(Parser::Line(LineParser::new(0)));
     }
 
@@ -260,6 +530,19 @@ impl Fragmentizer {
             });
         }
 
+        if self.max_line_length_exceeded_at.is_some() {
+            return Err(DecodeMessageError::LineTooLong {
+                initial: Secret::new(&self.message_buffer),
+            });
+        }
+
+        if let Some((_, announcement)) = self.max_literal_size_exceeded_at {
+            return Err(DecodeMessageError::LiteralTooLarge {
+                initial: Secret::new(&self.message_buffer),
+                announcement,
+            });
+        }
+
         if self.message_poisoned {
             return Err(DecodeMessageError::MessagePoisoned {
                 discarded: Secret::new(&self.message_buffer),
@@ -282,8 +565,24 @@ impl Fragmentizer {
     }
 
     fn dequeue_parsed_bytes(&mut self, parsed_byte_count: usize) {
+        self.stream_offset += parsed_byte_count as u64;
+
         // This will remove the parsed bytes even if we don't add them to the message buffer
         let parsed_bytes = self.unparsed_buffer.drain(..parsed_byte_count);
+        // Drop bytes after an overlong line or at an oversized literal
+        let drop_at = match (
+            self.max_line_length_exceeded_at,
+            self.max_literal_size_exceeded_at,
+        ) {
+            (Some(at), Some((literal_at, _))) => Some(at.min(literal_at)),
+            (Some(at), None) | (None, Some((at, _))) => Some(at),
+            (None, None) => None,
+        };
+        let parsed_byte_count = match drop_at {
+            Some(at) => parsed_byte_count.min(at.saturating_sub(self.message_buffer.len())),
+            None => parsed_byte_count,
+        };
+        let parsed_bytes = parsed_bytes.take(parsed_byte_count);
         // How many bytes can we add to the message buffer?
         let remaining_size = self
             .max_message_size
@@ -308,6 +607,125 @@ impl Fragmentizer {
 enum Parser {
     Line(LineParser),
     Literal(LiteralParser),
+    /// A literal that is drained via [`LiteralHandle`] and not added to the message.
+    StreamedLiteral {
+        /// Where the literal would be in the current message.
+        position: usize,
+        /// Length of the literal.
+        length: u32,
+        /// Remaining bytes that need to be drained.
+        remaining: u32,
+    },
+}
+
+/// Access to a literal that is streamed instead of added to the current message.
+///
+/// See [`Fragmentizer::stream_literal`].
+#[derive(Debug)]
+pub struct LiteralHandle<'a> {
+    fragmentizer: &'a mut Fragmentizer,
+    /// Announced length of the literal.
+    length: u32,
+}
+
+impl LiteralHandle<'_> {
+    /// Returns the announced length of the literal.
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+
+    /// Returns the number of bytes that were not drained yet.
+    pub fn remaining(&self) -> u32 {
+        match self.fragmentizer.parser {
+            Some(Parser::StreamedLiteral { remaining, .. }) => remaining,
+            _ => 0,
+        }
+    }
+
+    /// Returns whether the literal was drained completely.
+    ///
+    /// If it returns true, [`Fragmentizer::progress`] continues with the rest of the message.
+    pub fn is_complete(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Discards the enqueued bytes of the literal.
+    ///
+    /// Returns the number of skipped bytes. If the literal is not complete afterwards, more bytes
+    /// need to be enqueued via [`Fragmentizer::enqueue_bytes`].
+    pub fn skip(&mut self) -> usize {
+        // Unwrap: `Counter` never fails.
+        self.drain_into(&mut Counter(0)).unwrap()
+    }
+
+    /// Writes the enqueued bytes of the literal into `writer`.
+    ///
+    /// Returns the number of drained bytes. If the literal is not complete afterwards, more bytes
+    /// need to be enqueued via [`Fragmentizer::enqueue_bytes`].
+    ///
+    /// Bytes are only drained after they were written successfully. Thus, when `writer` fails,
+    /// calling this function again continues with the first byte that was not written yet.
+    pub fn drain_into<W: Writer>(&mut self, writer: &mut W) -> Result<usize, W::Error> {
+        let mut drained = 0;
+
+        // The enqueued bytes may wrap around the end of the ring buffer. Write (and drain) them
+        // slice by slice.
+        loop {
+            let (front, _) = self.fragmentizer.unparsed_buffer.as_slices();
+            let count = front.len().min(self.remaining() as usize);
+
+            if count == 0 {
+                return Ok(drained);
+            }
+
+            writer.write_all(&front[..count])?;
+            self.consume(count);
+            drained += count;
+        }
+    }
+
+    /// Drains `count` bytes of the literal from the unparsed bytes.
+    fn consume(&mut self, count: usize) {
+        let (position, remaining) = match self.fragmentizer.parser {
+            Some(Parser::StreamedLiteral {
+                position,
+                remaining,
+                ..
+            }) => (position, remaining),
+            _ => return,
+        };
+
+        self.fragmentizer.unparsed_buffer.drain(..count);
+        self.fragmentizer.stream_offset += count as u64;
+
+        let remaining = remaining - count as u32;
+        self.fragmentizer.parser = Some(if remaining == 0 {
+            // Next fragment will be a line
+            Parser::Line(LineParser::new(position))
+        } else {
+            Parser::StreamedLiteral {
+                position,
+                length: self.length,
+                remaining,
+            }
+        });
+    }
+}
+
+/// Returns the range of the announced length in a line, e.g., `123` in `... {123}\r\n`.
+fn announced_length_range(line: &[u8]) -> Option<Range<usize>> {
+    let line = line.strip_suffix(b"\n")?;
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    let line = line.strip_suffix(b"}")?;
+    let line = line.strip_suffix(b"+").unwrap_or(line);
+
+    let start = line.iter().rposition(|b| !b.is_ascii_digit())? + 1;
+
+    if line[start - 1] != b'{' || start == line.len() {
+        return None;
+    }
+
+    Some(start..line.len())
 }
 
 /// Stateful parser for the next line fragment.
@@ -331,135 +749,166 @@ impl LineParser {
     }
 
     fn parse(&mut self, unprocessed_bytes: &VecDeque<u8>) -> (usize, Option<FragmentInfo>) {
+        let (front, back) = unprocessed_bytes.as_slices();
+
+        let (parsed_byte_count, parsed_line) = self.parse_slice(front);
+        if parsed_line.is_some() {
+            return (parsed_byte_count, parsed_line);
+        }
+
+        let (more_parsed_byte_count, parsed_line) = self.parse_slice(back);
+        (parsed_byte_count + more_parsed_byte_count, parsed_line)
+    }
+
+    fn parse_slice(&mut self, bytes: &[u8]) -> (usize, Option<FragmentInfo>) {
         let mut parsed_byte_count = 0;
-        let mut parsed_line = None;
 
-        // Parse next byte
-        for &next_byte in unprocessed_bytes {
+        while parsed_byte_count < bytes.len() {
+            if let LatestByte::Other = self.latest_byte {
+                // Only `\r`, `\n`, and `{` change the state. Skip everything else at once.
+                match memchr3(b'\r', b'\n', b'{', &bytes[parsed_byte_count..]) {
+                    Some(skipped) => {
+                        parsed_byte_count += skipped;
+                        self.end += skipped;
+                    }
+                    None => {
+                        self.end += bytes.len() - parsed_byte_count;
+                        return (bytes.len(), None);
+                    }
+                }
+            }
+
             parsed_byte_count += 1;
             self.end += 1;
 
-            self.latest_byte = match self.latest_byte {
-                LatestByte::Other => match next_byte {
-                    b'\r' => LatestByte::Cr { announcement: None },
-                    b'\n' => {
-                        parsed_line = Some(FragmentInfo::Line {
-                            start: self.start,
-                            end: self.end,
-                            announcement: None,
-                            ending: LineEnding::Lf,
-                        });
-                        LatestByte::Other
-                    }
-                    b'{' => LatestByte::OpeningBracket,
-                    _ => LatestByte::Other,
-                },
-                LatestByte::OpeningBracket => match next_byte {
-                    b'\r' => LatestByte::Cr { announcement: None },
-                    b'\n' => {
-                        parsed_line = Some(FragmentInfo::Line {
-                            start: self.start,
-                            end: self.end,
-                            announcement: None,
-                            ending: LineEnding::Lf,
-                        });
-                        LatestByte::Other
-                    }
-                    b'{' => LatestByte::OpeningBracket,
-                    b'0'..=b'9' => {
-                        let digit = (next_byte - b'0') as u32;
-                        LatestByte::Digit { length: digit }
-                    }
-                    _ => LatestByte::Other,
-                },
-                LatestByte::Plus { length } => match next_byte {
-                    b'\r' => LatestByte::Cr { announcement: None },
-                    b'\n' => {
-                        parsed_line = Some(FragmentInfo::Line {
-                            start: self.start,
-                            end: self.end,
-                            announcement: None,
-                            ending: LineEnding::Lf,
-                        });
-                        LatestByte::Other
-                    }
-                    b'{' => LatestByte::OpeningBracket,
-                    b'}' => LatestByte::ClosingBracket {
-                        announcement: LiteralAnnouncement {
-                            mode: LiteralMode::NonSync,
-                            length,
-                        },
+            // Parse next byte
+            if let Some(parsed_line) = self.parse_byte(bytes[parsed_byte_count - 1]) {
+                // We parsed a complete line
+                return (parsed_byte_count, Some(parsed_line));
+            }
+        }
+
+        (parsed_byte_count, None)
+    }
+
+    fn parse_byte(&mut self, next_byte: u8) -> Option<FragmentInfo> {
+        let mut parsed_line = None;
+
+        self.latest_byte = match self.latest_byte {
+            LatestByte::Other => match next_byte {
+                b'\r' => LatestByte::Cr { announcement: None },
+                b'\n' => {
+                    parsed_line = Some(FragmentInfo::Line {
+                        start: self.start,
+                        end: self.end,
+                        announcement: None,
+                        ending: LineEnding::Lf,
+                    });
+                    LatestByte::Other
+                }
+                b'{' => LatestByte::OpeningBracket,
+                _ => LatestByte::Other,
+            },
+            LatestByte::OpeningBracket => match next_byte {
+                b'\r' => LatestByte::Cr { announcement: None },
+                b'\n' => {
+                    parsed_line = Some(FragmentInfo::Line {
+                        start: self.start,
+                        end: self.end,
+                        announcement: None,
+                        ending: LineEnding::Lf,
+                    });
+                    LatestByte::Other
+                }
+                b'{' => LatestByte::OpeningBracket,
+                b'0'..=b'9' => {
+                    let digit = (next_byte - b'0') as u32;
+                    LatestByte::Digit { length: digit }
+                }
+                _ => LatestByte::Other,
+            },
+            LatestByte::Plus { length } => match next_byte {
+                b'\r' => LatestByte::Cr { announcement: None },
+                b'\n' => {
+                    parsed_line = Some(FragmentInfo::Line {
+                        start: self.start,
+                        end: self.end,
+                        announcement: None,
+                        ending: LineEnding::Lf,
+                    });
+                    LatestByte::Other
+                }
+                b'{' => LatestByte::OpeningBracket,
+                b'}' => LatestByte::ClosingBracket {
+                    announcement: LiteralAnnouncement {
+                        mode: LiteralMode::NonSync,
+                        length,
                     },
-                    _ => LatestByte::Other,
                 },
-                LatestByte::Digit { length } => match next_byte {
-                    b'\r' => LatestByte::Cr { announcement: None },
-                    b'\n' => {
-                        parsed_line = Some(FragmentInfo::Line {
-                            start: self.start,
-                            end: self.end,
-                            announcement: None,
-                            ending: LineEnding::Lf,
-                        });
-                        LatestByte::Other
-                    }
-                    b'{' => LatestByte::OpeningBracket,
-                    b'0'..=b'9' => {
-                        let digit = (next_byte - b'0') as u32;
-                        let new_length = length.checked_mul(10).and_then(|x| x.checked_add(digit));
-                        match new_length {
-                            None => LatestByte::Other,
-                            Some(length) => LatestByte::Digit { length },
-                        }
+                _ => LatestByte::Other,
+            },
+            LatestByte::Digit { length } => match next_byte {
+                b'\r' => LatestByte::Cr { announcement: None },
+                b'\n' => {
+                    parsed_line = Some(FragmentInfo::Line {
+                        start: self.start,
+                        end: self.end,
+                        announcement: None,
+                        ending: LineEnding::Lf,
+                    });
+                    LatestByte::Other
+                }
+                b'{' => LatestByte::OpeningBracket,
+                b'0'..=b'9' => {
+                    let digit = (next_byte - b'0') as u32;
+                    let new_length = length.checked_mul(10).and_then(|x| x.checked_add(digit));
+                    match new_length {
+                        None => LatestByte::Other,
+                        Some(length) => LatestByte::Digit { length },
                     }
-                    b'+' => LatestByte::Plus { length },
-                    b'}' => LatestByte::ClosingBracket {
-                        announcement: LiteralAnnouncement {
-                            mode: LiteralMode::Sync,
-                            length,
-                        },
-                    },
-                    _ => LatestByte::Other,
-                },
-                LatestByte::ClosingBracket { announcement } => match next_byte {
-                    b'\r' => LatestByte::Cr {
-                        announcement: Some(announcement),
+                }
+                b'+' => LatestByte::Plus { length },
+                b'}' => LatestByte::ClosingBracket {
+                    announcement: LiteralAnnouncement {
+                        mode: LiteralMode::Sync,
+                        length,
                     },
-                    b'\n' => {
-                        parsed_line = Some(FragmentInfo::Line {
-                            start: self.start,
-                            end: self.end,
-                            announcement: Some(announcement),
-                            ending: LineEnding::Lf,
-                        });
-                        LatestByte::Other
-                    }
-                    b'{' => LatestByte::OpeningBracket,
-                    _ => LatestByte::Other,
                 },
-                LatestByte::Cr { announcement } => match next_byte {
-                    b'\r' => LatestByte::Cr { announcement: None },
-                    b'\n' => {
-                        parsed_line = Some(FragmentInfo::Line {
-                            start: self.start,
-                            end: self.end,
-                            announcement,
-                            ending: LineEnding::CrLf,
-                        });
-                        LatestByte::Other
-                    }
-                    b'{' => LatestByte::OpeningBracket,
-                    _ => LatestByte::Other,
+                _ => LatestByte::Other,
+            },
+            LatestByte::ClosingBracket { announcement } => match next_byte {
+                b'\r' => LatestByte::Cr {
+                    announcement: Some(announcement),
                 },
-            };
-
-            if parsed_line.is_some() {
-                // We parsed a complete line
-                break;
-            }
-        }
+                b'\n' => {
+                    parsed_line = Some(FragmentInfo::Line {
+                        start: self.start,
+                        end: self.end,
+                        announcement: Some(announcement),
+                        ending: LineEnding::Lf,
+                    });
+                    LatestByte::Other
+                }
+                b'{' => LatestByte::OpeningBracket,
+                _ => LatestByte::Other,
+            },
+            LatestByte::Cr { announcement } => match next_byte {
+                b'\r' => LatestByte::Cr { announcement: None },
+                b'\n' => {
+                    parsed_line = Some(FragmentInfo::Line {
+                        start: self.start,
+                        end: self.end,
+                        announcement,
+                        ending: LineEnding::CrLf,
+                    });
+                    LatestByte::Other
+                }
+                b'{' => LatestByte::OpeningBracket,
+                _ => LatestByte::Other,
+            },
+        };
 
-        (parsed_byte_count, parsed_line)
+        parsed_line
     }
 }
 
@@ -569,6 +1018,24 @@ pub struct LiteralAnnouncement {
     pub length: u32,
 }
 
+/// A limit of the [`Fragmentizer`] that was exceeded by the current message.
+///
+/// Fragmentation continues in all cases, i.e., the [`Fragmentizer`] stays synchronized with the
+/// stream and starts with the next message after the current one was dropped.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LimitExceeded {
+    /// The message is larger than allowed, see [`Fragmentizer::new`].
+    MessageSize,
+    /// A line is longer than allowed, see [`Fragmentizer::with_max_line_length`].
+    LineLength,
+    /// A literal is larger than allowed, see [`Fragmentizer::with_max_literal_size`].
+    ///
+    /// The announced number of bytes will be skipped. A server should reject the command. For a
+    /// synchronizing literal, the client won't send the literal (without a command continuation
+    /// request) and the server must call [`Fragmentizer::skip_message`] after the rejection.
+    LiteralSize { announcement: LiteralAnnouncement },
+}
+
 /// The character sequence used for ending a line.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum LineEnding {
@@ -592,11 +1059,18 @@ pub enum DecodeMessageError<'a, C: Decoder> {
     },
     /// Max message size was exceeded and bytes were dropped.
     MessageTooLong { initial: Secret<&'a [u8]> },
+    /// Max line length was exceeded and bytes were dropped.
+    LineTooLong { initial: Secret<&'a [u8]> },
+    /// Max literal size was exceeded and the literal (and following bytes) were dropped.
+    LiteralTooLarge {
+        initial: Secret<&'a [u8]>,
+        announcement: LiteralAnnouncement,
+    },
     /// The message was explicitly poisoned to prevent decoding.
     MessagePoisoned { discarded: Secret<&'a [u8]> },
 }
 
-fn parse_tag(message_bytes: &[u8]) -> Option<Tag> {
+pub(crate) fn parse_tag(message_bytes: &[u8]) -> Option<Tag> {
     let mut bytes = message_bytes.iter().enumerate();
     let sp = loop {
         let (i, byte) = bytes.next()?;
@@ -620,15 +1094,17 @@ mod tests {
 
     use imap_types::{
         command::{Command, CommandBody},
-        core::{LiteralMode, Tag},
+        core::{Literal, LiteralMode, Tag},
         secret::Secret,
     };
 
     use super::{
-        parse_tag, FragmentInfo, Fragmentizer, LineEnding, LineParser, LiteralAnnouncement,
+        parse_tag, FragmentInfo, Fragmentizer, LimitExceeded, LineEnding, LineParser,
+        LiteralAnnouncement,
     };
     use crate::{
-        decode::ResponseDecodeError, fragmentizer::DecodeMessageError, CommandCodec, ResponseCodec,
+        decode::ResponseDecodeError, encode::Writer, fragmentizer::DecodeMessageError,
+        CommandCodec, ResponseCodec,
     };
 
     #[test]
@@ -1028,6 +1504,37 @@ mod tests {
         assert_eq!(ending, expected_ending);
     }
 
+    #[test]
+    fn fragmentizer_progress_lines_longer_than_max_line_length() {
+        let mut fragmentizer = Fragmentizer::without_max_message_size().with_max_line_length(10);
+        fragmentizer.enqueue_bytes(b"A1 NOOP\r\n");
+
+        fragmentizer.progress().unwrap();
+        assert!(fragmentizer.is_message_complete());
+        assert!(!fragmentizer.is_max_line_length_exceeded());
+        assert!(fragmentizer
+            .decode_message(&CommandCodec::default())
+            .is_ok());
+
+        // The limit is exceeded before the line is complete.
+        fragmentizer.enqueue_bytes(b"A2 LOGIN ABCDE");
+        assert_eq!(fragmentizer.progress(), None);
+        assert!(fragmentizer.is_max_line_length_exceeded());
+
+        fragmentizer.enqueue_bytes(b" EFGIJ\r\nA3 NOOP\r\n");
+        fragmentizer.progress().unwrap();
+        assert!(fragmentizer.is_message_complete());
+        assert!(matches!(
+            fragmentizer.decode_message(&CommandCodec::default()),
+            Err(DecodeMessageError::LineTooLong { .. })
+        ));
+
+        fragmentizer.progress().unwrap();
+        assert!(fragmentizer.is_message_complete());
+        assert!(!fragmentizer.is_max_line_length_exceeded());
+        assert_eq!(fragmentizer.message_bytes(), b"A3 NOOP\r\n");
+    }
+
     #[test]
     fn fragmentizer_progress_multiple_messages_longer_than_max_size() {
         let mut fragmentizer = Fragmentizer::new(17);
@@ -1430,6 +1937,75 @@ mod tests {
         assert!(!fragmentizer.is_message_poisoned());
     }
 
+    #[test]
+    fn fragmentizer_stream_literal() {
+        let mut fragmentizer = Fragmentizer::new(32);
+        fragmentizer.enqueue_bytes(b"A1 APPEND INBOX {10}\r\n01234");
+
+        // No literal is announced yet.
+        assert!(fragmentizer.stream_literal().is_none());
+
+        let fragment_info = fragmentizer.progress().unwrap();
+        assert_eq!(
+            fragment_info,
+            FragmentInfo::Line {
+                start: 0,
+                end: 22,
+                announcement: Some(LiteralAnnouncement {
+                    mode: LiteralMode::Sync,
+                    length: 10,
+                }),
+                ending: LineEnding::CrLf,
+            }
+        );
+
+        let mut data = Vec::new();
+
+        let mut literal = fragmentizer.stream_literal().unwrap();
+        assert_eq!(literal.length(), 10);
+        assert_eq!(literal.drain_into(&mut data).unwrap(), 5);
+        assert_eq!(literal.remaining(), 5);
+        assert!(!literal.is_complete());
+
+        // The literal must be drained first.
+        assert_eq!(fragmentizer.progress(), None);
+
+        fragmentizer.enqueue_bytes(b"56789\r\nA2 NOOP\r\n");
+
+        let mut literal = fragmentizer.stream_literal().unwrap();
+        assert_eq!(literal.drain_into(&mut data).unwrap(), 5);
+        assert!(literal.is_complete());
+        assert_eq!(literal.length(), 10);
+        assert_eq!(data, b"0123456789");
+
+        let fragment_info = fragmentizer.progress().unwrap();
+        assert_eq!(
+            fragment_info,
+            FragmentInfo::Line {
+                start: 21,
+                end: 23,
+                announcement: None,
+                ending: LineEnding::CrLf,
+            }
+        );
+        assert!(fragmentizer.is_message_complete());
+        assert_eq!(fragmentizer.message_bytes(), b"A1 APPEND INBOX {0}\r\n\r\n");
+        assert_eq!(
+            fragmentizer.decode_message(&CommandCodec::new()),
+            Ok(Command::new(
+                "A1",
+                CommandBody::append("INBOX", vec![], None, Literal::try_from("").unwrap()).unwrap()
+            )
+            .unwrap()),
+        );
+
+        fragmentizer.progress();
+        assert_eq!(
+            fragmentizer.decode_message(&CommandCodec::new()),
+            Ok(Command::new("A2", CommandBody::Noop).unwrap()),
+        );
+    }
+
     #[track_caller]
     fn assert_not_line(not_a_line_bytes: &[u8]) {
         let mut line_parser = LineParser::new(0);
@@ -1552,6 +2128,85 @@ mod tests {
         assert_is_line(b"foo {4294967296}\r\n", 18, None, LineEnding::CrLf);
     }
 
+    #[test]
+    fn fragmentizer_stream_literal_wrapped_buffer_failing_writer() {
+        /// Writer that fails once after the first write.
+        struct FailOnce(Vec<u8>, bool);
+
+        impl Writer for FailOnce {
+            type Error = ();
+
+            fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+                if !self.0.is_empty() && !self.1 {
+                    self.1 = true;
+                    return Err(());
+                }
+
+                self.0.extend_from_slice(buf);
+                Ok(())
+            }
+        }
+
+        let mut fragmentizer = Fragmentizer::new(32);
+        fragmentizer.enqueue_bytes(b"A1 APPEND INBOX {10}\r\n");
+        fragmentizer.progress().unwrap();
+
+        // Let the literal wrap around the end of the ring buffer.
+        let mut unparsed_buffer = VecDeque::with_capacity(16);
+        let capacity = unparsed_buffer.capacity();
+        unparsed_buffer.extend(vec![b'x'; capacity - 4]);
+        for _ in 0..capacity - 4 {
+            unparsed_buffer.pop_front();
+        }
+        unparsed_buffer.extend(b"0123456789\r\n");
+        assert!(!unparsed_buffer.as_slices().1.is_empty());
+        fragmentizer.unparsed_buffer = unparsed_buffer;
+
+        let mut writer = FailOnce(Vec::new(), false);
+
+        let mut literal = fragmentizer.stream_literal().unwrap();
+        assert_eq!(literal.drain_into(&mut writer), Err(()));
+        assert_eq!(writer.0, b"0123");
+        assert_eq!(literal.remaining(), 6);
+        assert_eq!(literal.drain_into(&mut writer), Ok(6));
+        assert!(literal.is_complete());
+        assert_eq!(literal.length(), 10);
+        assert_eq!(writer.0, b"0123456789");
+
+        fragmentizer.progress().unwrap();
+        assert!(fragmentizer.is_message_complete());
+    }
+
+    #[test]
+    fn parse_line_wrapped_buffer() {
+        // Let the line (and its announcement) wrap around the end of the ring buffer.
+        let mut unprocessed_bytes = VecDeque::with_capacity(16);
+        let capacity = unprocessed_bytes.capacity();
+        unprocessed_bytes.extend(vec![b'x'; capacity - 4]);
+        for _ in 0..capacity - 4 {
+            unprocessed_bytes.pop_front();
+        }
+        unprocessed_bytes.extend(b"A1 LOGIN {12}\r\n");
+        assert!(!unprocessed_bytes.as_slices().1.is_empty());
+
+        let mut line_parser = LineParser::new(0);
+        let (parsed_byte_count, fragment_info) = line_parser.parse(&unprocessed_bytes);
+
+        assert_eq!(parsed_byte_count, 15);
+        assert_eq!(
+            fragment_info,
+            Some(FragmentInfo::Line {
+                start: 0,
+                end: 15,
+                announcement: Some(LiteralAnnouncement {
+                    length: 12,
+                    mode: LiteralMode::Sync,
+                }),
+                ending: LineEnding::CrLf,
+            })
+        );
+    }
+
     #[test]
     fn parse_line_corner_case() {
         // According to the IMAP RFC, this line does not announce a literal.
@@ -1594,4 +2249,126 @@ mod tests {
         assert_eq!(parse_tag(b""), None);
         assert_eq!(parse_tag(b" A1 NOOP\r\n"), None);
     }
+
+    #[test]
+    fn fragmentizer_message_and_fragment_spans() {
+        let mut fragmentizer = Fragmentizer::new(10);
+        fragmentizer.enqueue_bytes(b"A1 NOOP\r\nA2 LOGIN {5}\r\nalice password\r\nA3 NOOP\r\n");
+
+        fragmentizer.progress().unwrap();
+        assert!(fragmentizer.is_message_complete());
+        assert_eq!(fragmentizer.message_span(), 0..9);
+
+        let line = fragmentizer.progress().unwrap();
+        let literal = fragmentizer.progress().unwrap();
+        let rest = fragmentizer.progress().unwrap();
+        assert!(fragmentizer.is_message_complete());
+        assert_eq!(fragmentizer.message_fragments(), &[line, literal, rest]);
+        assert_eq!(fragmentizer.fragment_span(line), 9..23);
+        assert_eq!(fragmentizer.fragment_span(literal), 23..28);
+        assert_eq!(fragmentizer.fragment_span(rest), 28..39);
+        // The span also covers the dropped bytes.
+        assert!(fragmentizer.is_max_message_size_exceeded());
+        assert_eq!(fragmentizer.message_span(), 9..39);
+
+        fragmentizer.progress().unwrap();
+        assert_eq!(fragmentizer.message_fragments().len(), 1);
+        assert_eq!(fragmentizer.message_span(), 39..48);
+
+        assert_eq!(fragmentizer.progress(), None);
+        assert!(fragmentizer.message_fragments().is_empty());
+        assert_eq!(fragmentizer.message_span(), 48..48);
+    }
+
+    #[test]
+    fn fragmentizer_max_literal_size() {
+        let mut fragmentizer = Fragmentizer::without_max_message_size().with_max_literal_size(4);
+        fragmentizer.enqueue_bytes(b"A1 LOGIN {5+}\r\nalice {4}\r\npass\r\nA2 NOOP\r\n");
+
+        let fragment_info = fragmentizer.progress().unwrap();
+        assert_eq!(
+            fragmentizer.fragment_bytes(fragment_info),
+            b"A1 LOGIN {5+}\r\n"
+        );
+        assert_eq!(
+            fragmentizer.limit_exceeded(),
+            Some(LimitExceeded::LiteralSize {
+                announcement: LiteralAnnouncement {
+                    mode: LiteralMode::NonSync,
+                    length: 5
+                }
+            })
+        );
+        assert!(fragmentizer.stream_literal().is_none());
+
+        while !fragmentizer.is_message_complete() {
+            fragmentizer.progress().unwrap();
+        }
+        assert!(fragmentizer.is_max_literal_size_exceeded());
+        assert_eq!(fragmentizer.message_bytes(), b"A1 LOGIN {5+}\r\n");
+        assert!(matches!(
+            fragmentizer.decode_message(&CommandCodec::default()),
+            Err(DecodeMessageError::LiteralTooLarge { .. })
+        ));
+
+        fragmentizer.progress().unwrap();
+        assert!(fragmentizer.is_message_complete());
+        assert_eq!(fragmentizer.limit_exceeded(), None);
+        assert_eq!(
+            fragmentizer.decode_message(&CommandCodec::default()),
+            Ok(Command::new("A2", CommandBody::Noop).unwrap())
+        );
+    }
+
+    #[test]
+    fn fragmentizer_streamed_literals() {
+        let mut fragmentizer = Fragmentizer::new(64).with_streamed_literals(10);
+        fragmentizer.enqueue_bytes(
+            b"A1 APPEND INBOX {4}\r\nsmol\r\nA2 APPEND INBOX {12}\r\nhello world!\r\n",
+        );
+
+        // Small literals are not streamed.
+        while !fragmentizer.is_message_complete() {
+            fragmentizer.progress().unwrap();
+            assert!(!fragmentizer.is_literal_streamed());
+        }
+        assert_eq!(
+            fragmentizer.message_bytes(),
+            b"A1 APPEND INBOX {4}\r\nsmol\r\n"
+        );
+
+        let fragment_info = fragmentizer.progress().unwrap();
+        assert_eq!(
+            fragment_info,
+            FragmentInfo::Line {
+                start: 0,
+                end: 21,
+                announcement: Some(LiteralAnnouncement {
+                    mode: LiteralMode::Sync,
+                    length: 12,
+                }),
+                ending: LineEnding::CrLf,
+            }
+        );
+        assert_eq!(
+            fragmentizer.fragment_bytes(fragment_info),
+            b"A2 APPEND INBOX {0}\r\n"
+        );
+        assert_eq!(fragmentizer.message_fragments(), &[fragment_info]);
+        assert!(fragmentizer.is_literal_streamed());
+        assert_eq!(fragmentizer.progress(), None);
+
+        let mut handle = fragmentizer.stream_literal().unwrap();
+        assert_eq!(handle.length(), 12);
+        assert_eq!(handle.skip(), 12);
+        assert!(handle.is_complete());
+        assert!(!fragmentizer.is_literal_streamed());
+
+        fragmentizer.progress().unwrap();
+        assert!(fragmentizer.is_message_complete());
+        assert_eq!(fragmentizer.message_bytes(), b"A2 APPEND INBOX {0}\r\n\r\n");
+        assert!(fragmentizer
+            .decode_message(&CommandCodec::default())
+            .is_ok());
+    }
 }
@@ -82,11 +82,27 @@
 //!
 //! In addition, imap-codec defines the following features:
 //!
-//! | Feature               | Description                    | Enabled by default |
-//! |-----------------------|--------------------------------|--------------------|
-//! | quirk_crlf_relaxed    | Make `\r` in `\r\n` optional.  | No                 |
-//! | quirk_rectify_numbers | Rectify (invalid) numbers.     | No                 |
-//! | quirk_missing_text    | Rectify missing `text` element.| No                 |
+//! | Feature                 | Description                          | Enabled by default |
+//! |-------------------------|--------------------------------------|--------------------|
+//! | quirk_8bit_text         | Accept 8-bit (UTF-8) text.           | No                 |
+//! | quirk_bare_system_flags | Rectify bare system flags.           | No                 |
+//! | quirk_crlf_relaxed      | Make `\r` in `\r\n` optional.        | No                 |
+//! | quirk_empty_lists       | Accept empty lists, e.g., `()`.      | No                 |
+//! | quirk_rectify_numbers   | Rectify (invalid) numbers.           | No                 |
+//! | quirk_missing_text      | Rectify missing `text` element.      | No                 |
+//! | futures                 | Async reading/writing (futures).     | No                 |
+//! | tokio                   | Async reading/writing (tokio).       | No                 |
+//! | tokio_util              | Codecs for tokio_util's Framed.      | No                 |
+//! | perf                    | Fewer allocations for short lists.   | No                 |
+//! | std                     | Use the standard library, see below. | Yes                |
+//!
+//! Without the `std` feature, imap-codec is `#![no_std]` and only requires `alloc`.
+//! Messages can still be encoded into a `Vec<u8>` (or any other [`Writer`](encode::Writer)) and decoded.
+//! However, the decoding configuration of a codec, i.e., quirks, recursion limits, and the maximum literal size, is
+//! passed to the parsers via a thread local. Without `std`, codecs can't be configured (e.g., there is no
+//! `CommandCodec::parse_mode`) and the defaults are used instead, i.e., the quirks of the activated `quirk_` features,
+//! the default recursion limits, and no literal size limit.
+//! The features `futures`, `tokio`, and `tokio_util` require `std`.
 //!
 //! ## Quirks
 //!
@@ -97,15 +113,24 @@
 //! imap-codec can't otherwise access their emails, we may add a `quirk_` feature to quickly resolve the problem.
 //! Of course, imap-codec should never violate the IMAP standard itself. So, we need to do this carefully.
 //!
+//! The `quirk_` features only decide which quirks are enabled by default. With `std`, every codec can override them
+//! at runtime, e.g., a server may use [`ParseMode::Strict`](decode::ParseMode::Strict) to reject
+//! non-conforming clients while a client uses [`ParseMode::Lenient`](decode::ParseMode::Lenient) to be
+//! forgiving toward servers. See [`Quirks`](decode::Quirks).
+//! An exception is `quirk_8bit_text`, which must be activated for 8-bit text to be accepted at all.
+//!
 //! [imap-types]: https://docs.rs/imap-types/latest/imap_types
 //! [imap-types features]: https://docs.rs/imap-types/latest/imap_types/#features
 //! [IMAP4rev1]: https://tools.ietf.org/html/rfc3501
 //! [parse_command]: https://github.com/duesee/imap-codec/blob/main/examples/parse_command.rs
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![forbid(unsafe_code)]
 #![deny(missing_debug_implementations)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+extern crate alloc;
+
 // Test examples from repository root README.
 #[doc = include_str!("../../README.md")]
 #[cfg(doctest)]
@@ -1,9 +1,12 @@
+use alloc::vec::Vec;
+
 use abnf_core::streaming::{dquote, sp};
 use imap_types::{
     core::QuotedChar,
     flag::FlagNameAttribute,
     mailbox::{ListCharString, ListMailbox, Mailbox},
     response::Data,
+    search::SearchResults,
     utils::indicators::is_list_char,
 };
 #[cfg(feature = "ext_condstore_qresync")]
@@ -11,8 +14,8 @@ use nom::character::streaming::char;
 use nom::{
     branch::alt,
     bytes::streaming::{tag, tag_no_case, take_while1},
-    combinator::{map, opt, value},
-    multi::many0,
+    combinator::{cond, map, opt, value},
+    multi::{fold_many0, many0},
     sequence::{delimited, preceded, terminated, tuple},
 };
 
@@ -20,14 +23,17 @@ use nom::{
 use crate::extensions::condstore_qresync::search_sort_mod_seq;
 #[cfg(feature = "ext_metadata")]
 use crate::extensions::metadata::metadata_resp;
+#[cfg(feature = "ext_xlist")]
+use crate::extensions::xlist::xlist_response;
 use crate::{
     core::{astring, nil, number, nz_number, quoted_char, string},
-    decode::IMAPResult,
+    decode::{quirks, IMAPResult},
     extensions::{
         quota::{quota_response, quotaroot_response},
+        sort::esearch_response,
         thread::thread_data,
     },
-    flag::{flag_list, mbx_list_flags},
+    flag::{flag_list_response, mbx_list_flags},
     status::status_att_list,
 };
 
@@ -41,7 +47,7 @@ pub(crate) fn list_mailbox(input: &[u8]) -> IMAPResult<&[u8], ListMailbox> {
             //   * contain ASCII-only characters, i.e., `from_utf8` will return `Ok`.
             //   * are valid according to `ListCharString::verify()`, i.e., `unvalidated` is safe.
             ListMailbox::Token(ListCharString::unvalidated(
-                std::str::from_utf8(bytes).unwrap(),
+                core::str::from_utf8(bytes).unwrap(),
             ))
         }),
         map(string, ListMailbox::String),
@@ -69,10 +75,12 @@ pub(crate) fn mailbox(input: &[u8]) -> IMAPResult<&[u8], Mailbox> {
 ///                                         ^^^^^^^^^^^^^^^^^^^^^^^^
 ///                                         |
 ///                                         RFC 7162 (edited)
+///                esearch-response / ; RFC 4731, RFC 5267
 ///                "STATUS" SP mailbox SP "(" [status-att-list] ")" /
 ///                "METADATA" SP mailbox SP (entry-values / entry-list) / ; RFC 5464
 ///                number SP "EXISTS" /
-///                number SP "RECENT"
+///                number SP "RECENT" /
+///                xlist-response ; XLIST (legacy)
 /// ```
 ///
 /// FROM RFC 7162 (CONDSTORE/QRESYNC):
@@ -84,7 +92,10 @@ pub(crate) fn mailbox(input: &[u8]) -> IMAPResult<&[u8], Mailbox> {
 /// ```
 pub(crate) fn mailbox_data(input: &[u8]) -> IMAPResult<&[u8], Data> {
     alt((
-        map(preceded(tag_no_case(b"FLAGS "), flag_list), Data::Flags),
+        map(
+            preceded(tag_no_case(b"FLAGS "), flag_list_response),
+            Data::Flags,
+        ),
         map(
             preceded(tag_no_case(b"LIST "), mailbox_list),
             |(items, delimiter, mailbox)| Data::List {
@@ -103,14 +114,14 @@ pub(crate) fn mailbox_data(input: &[u8]) -> IMAPResult<&[u8], Data> {
         ),
         #[cfg(not(feature = "ext_condstore_qresync"))]
         map(
-            tuple((tag_no_case(b"SEARCH"), many0(preceded(sp, nz_number)))),
-            |(_, nums)| Data::Search(nums),
+            preceded(tag_no_case(b"SEARCH"), search_results),
+            Data::Search,
         ),
         #[cfg(feature = "ext_condstore_qresync")]
         map(
             tuple((
                 tag_no_case(b"SEARCH"),
-                many0(preceded(sp, nz_number)),
+                search_results,
                 opt(preceded(char(' '), search_sort_mod_seq)),
             )),
             |(_, nums, modseq)| Data::Search(nums, modseq),
@@ -129,16 +140,14 @@ pub(crate) fn mailbox_data(input: &[u8]) -> IMAPResult<&[u8], Data> {
             )),
             |(_, nums, modseq)| Data::Sort(nums, modseq),
         ),
+        esearch_response,
         thread_data,
         map(
             tuple((
                 tag_no_case(b"STATUS "),
                 mailbox,
                 delimited(tag(b" ("), opt(status_att_list), tag(b")")),
-                #[cfg(feature = "quirk_trailing_space")]
-                opt(sp),
-                #[cfg(not(feature = "quirk_trailing_space"))]
-                nom::combinator::success(()),
+                cond(quirks().trailing_space, opt(sp)),
             )),
             |(_, mailbox, items, _)| Data::Status {
                 mailbox,
@@ -151,9 +160,25 @@ pub(crate) fn mailbox_data(input: &[u8]) -> IMAPResult<&[u8], Data> {
         map(terminated(number, tag_no_case(b" RECENT")), Data::Recent),
         quotaroot_response,
         quota_response,
+        #[cfg(feature = "ext_xlist")]
+        xlist_response,
     ))(input)
 }
 
+/// `*(SP nz-number)` (in `"SEARCH" *(SP nz-number)`)
+///
+/// The numbers are collected into runs directly, i.e., without materializing them.
+pub(crate) fn search_results(input: &[u8]) -> IMAPResult<&[u8], SearchResults> {
+    fold_many0(
+        preceded(sp, nz_number),
+        SearchResults::new,
+        |mut results, number| {
+            results.push(number);
+            results
+        },
+    )(input)
+}
+
 /// `mailbox-list = "(" [mbx-list-flags] ")" SP
 ///                 (DQUOTE QUOTED-CHAR DQUOTE / nil) SP
 ///                 mailbox`
@@ -1,7 +1,3 @@
-#[cfg(not(feature = "quirk_crlf_relaxed"))]
-use abnf_core::streaming::crlf;
-#[cfg(feature = "quirk_crlf_relaxed")]
-use abnf_core::streaming::crlf_relaxed as crlf;
 use abnf_core::streaming::sp;
 use base64::{engine::general_purpose::STANDARD as _base64, Engine};
 #[cfg(feature = "ext_condstore_qresync")]
@@ -13,13 +9,12 @@ use imap_types::{
         Bye, Capability, Code, CodeOther, CommandContinuationRequest, Data, Greeting, GreetingKind,
         Response, Status, StatusBody, StatusKind, Tagged,
     },
+    sequence::SeqNo,
 };
-#[cfg(feature = "quirk_missing_text")]
-use nom::combinator::peek;
 use nom::{
     branch::alt,
     bytes::streaming::{tag, tag_no_case, take_until, take_while},
-    combinator::{map, map_res, opt, value},
+    combinator::{map, map_res, opt, peek, value, verify},
     multi::separated_list1,
     sequence::{delimited, preceded, terminated, tuple},
 };
@@ -28,9 +23,11 @@ use nom::{
 use crate::extensions::id::id_response;
 #[cfg(feature = "ext_metadata")]
 use crate::extensions::metadata::metadata_code;
+#[cfg(feature = "ext_xapplepushservice")]
+use crate::extensions::xapplepushservice::xapplepushservice;
 use crate::{
-    core::{atom, charset, nz_number, tag_imap, text},
-    decode::IMAPResult,
+    core::{atom, charset, crlf, nz_number, tag_imap, text},
+    decode::{quirks, IMAPResult},
     extensions::{
         enable::enable_data,
         uidplus::{resp_code_apnd, resp_code_copy},
@@ -104,12 +101,9 @@ pub(crate) fn resp_text(input: &[u8]) -> IMAPResult<&[u8], (Option<Code>, Text)>
                     Some,
                 ),
             ),
-            #[cfg(not(feature = "quirk_missing_text"))]
-            preceded(sp, text),
-            #[cfg(feature = "quirk_missing_text")]
             alt((
                 preceded(sp, text),
-                map(peek(crlf), |_| {
+                map(verify(peek(crlf), |_: &[u8]| quirks().missing_text), |_| {
                     log::warn!("Rectified missing `text` to \"...\"");
 
                     Text::unvalidated("...")
@@ -273,22 +267,7 @@ pub(crate) fn continue_req(input: &[u8]) -> IMAPResult<&[u8], CommandContinuatio
     let mut parser = tuple((
         tag(b"+ "),
         alt((
-            #[cfg(not(feature = "quirk_crlf_relaxed"))]
-            map(
-                map_res(take_until("\r\n"), |input| _base64.decode(input)),
-                Either::Base64,
-            ),
-            #[cfg(feature = "quirk_crlf_relaxed")]
-            map(
-                map_res(take_until("\n"), |input: &[u8]| {
-                    if !input.is_empty() && input[input.len().saturating_sub(1)] == b'\r' {
-                        _base64.decode(&input[..input.len().saturating_sub(1)])
-                    } else {
-                        _base64.decode(input)
-                    }
-                }),
-                Either::Base64,
-            ),
+            map(map_res(line, |input| _base64.decode(input)), Either::Base64),
             map(resp_text, Either::Basic),
         )),
         crlf,
@@ -304,6 +283,17 @@ pub(crate) fn continue_req(input: &[u8]) -> IMAPResult<&[u8], CommandContinuatio
     Ok((remaining, continue_request))
 }
 
+/// Returns the rest of the line (without the line ending).
+fn line(input: &[u8]) -> IMAPResult<&[u8], &[u8]> {
+    if quirks().crlf_relaxed {
+        map(take_until("\n"), |line: &[u8]| {
+            line.strip_suffix(b"\r").unwrap_or(line)
+        })(input)
+    } else {
+        take_until("\r\n")(input)
+    }
+}
+
 /// ```abnf
 /// response-data = "*" SP (
 ///                    resp-cond-state /
@@ -311,7 +301,8 @@ pub(crate) fn continue_req(input: &[u8]) -> IMAPResult<&[u8], CommandContinuatio
 ///                    mailbox-data /
 ///                    message-data /
 ///                    capability-data /
-///                    id_response ; (See RFC 2971)
+///                    id_response / ; (See RFC 2971)
+///                    xapplepushservice ; XAPPLEPUSHSERVICE (Apple)
 ///                  ) CRLF
 /// ```
 pub(crate) fn response_data(input: &[u8]) -> IMAPResult<&[u8], Response> {
@@ -334,6 +325,10 @@ pub(crate) fn response_data(input: &[u8]) -> IMAPResult<&[u8], Response> {
             map(id_response, |parameters| {
                 Response::Data(Data::Id { parameters })
             }),
+            #[cfg(feature = "ext_xapplepushservice")]
+            map(xapplepushservice, |parameters| {
+                Response::Data(Data::XApplePushService { parameters })
+            }),
         )),
         crlf,
     )(input)
@@ -428,8 +423,11 @@ pub(crate) fn message_data(input: &[u8]) -> IMAPResult<&[u8], Data> {
     Ok((
         remaining,
         match tmp {
-            TmpData::Expunge => Data::Expunge(seq),
-            TmpData::Fetch(items) => Data::Fetch { seq, items },
+            TmpData::Expunge => Data::Expunge(SeqNo::from(seq)),
+            TmpData::Fetch(items) => Data::Fetch {
+                seq: SeqNo::from(seq),
+                items,
+            },
             #[cfg(feature = "ext_condstore_qresync")]
             TmpData::Vanished(earlier, known_uids) => Data::Vanished {
                 earlier,
@@ -511,11 +509,12 @@ mod tests {
                 b"",
                 Response::Data(Data::Search(
                     vec![
-                        1.try_into().unwrap(),
-                        2.try_into().unwrap(),
-                        3.try_into().unwrap(),
-                        42.try_into().unwrap(),
-                    ],
+                        NonZeroU32::new(1).unwrap(),
+                        NonZeroU32::new(2).unwrap(),
+                        NonZeroU32::new(3).unwrap(),
+                        NonZeroU32::new(42).unwrap(),
+                    ]
+                    .into(),
                     #[cfg(feature = "ext_condstore_qresync")]
                     None,
                 )),
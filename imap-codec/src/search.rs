@@ -1,3 +1,5 @@
+use alloc::boxed::Box;
+
 use abnf_core::streaming::sp;
 use imap_types::{
     command::CommandBody,
@@ -17,7 +19,7 @@ use crate::extensions::condstore_qresync::search_modsequence;
 use crate::{
     core::{astring, atom, charset, number},
     datetime::date,
-    decode::{IMAPErrorKind, IMAPParseError, IMAPResult},
+    decode::{recursion_limits, IMAPErrorKind, IMAPParseError, IMAPResult},
     fetch::header_fld_name,
     sequence::sequence_set,
 };
@@ -35,7 +37,10 @@ pub(crate) fn search(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
             |(_, _, _, charset)| charset,
         )),
         sp,
-        map(separated_list1(sp, search_key(9)), Vec1::unvalidated),
+        map(
+            separated_list1(sp, search_key(recursion_limits().search_key)),
+            Vec1::unvalidated,
+        ),
     ));
 
     let (remaining, (_, charset, _, criteria)) = parser(input)?;
@@ -93,7 +98,7 @@ pub(crate) fn search(input: &[u8]) -> IMAPResult<&[u8], CommandBody> {
 /// ```
 ///
 /// This parser is recursively defined. Thus, in order to not overflow the stack,
-/// it is needed to limit how may recursions are allowed. (8 should suffice).
+/// it is needed to limit how may recursions are allowed. See [`RecursionLimits`](crate::decode::RecursionLimits).
 pub(crate) fn search_key(
     remaining_recursions: usize,
 ) -> impl Fn(&[u8]) -> IMAPResult<&[u8], SearchKey> {
@@ -232,7 +237,10 @@ pub(crate) fn search_criteria(input: &[u8]) -> IMAPResult<&[u8], (Charset, Vec1<
     let mut parser = separated_pair(
         charset,
         sp,
-        map(separated_list1(sp, search_key(9)), Vec1::unvalidated),
+        map(
+            separated_list1(sp, search_key(recursion_limits().search_key)),
+            Vec1::unvalidated,
+        ),
     );
 
     let (remaining, (charset, search_keys)) = parser(input)?;
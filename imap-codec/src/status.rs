@@ -1,43 +1,59 @@
+use alloc::vec::Vec;
+
 use abnf_core::streaming::sp;
-use imap_types::status::{StatusDataItem, StatusDataItemName};
+use imap_types::status::{StatusDataItem, StatusDataItemName, StatusDataItemNameOther};
 use nom::{
     branch::alt,
-    bytes::streaming::tag_no_case,
-    combinator::{map, value},
+    combinator::{map, map_res, value},
     multi::separated_list1,
-    sequence::preceded,
+    sequence::{preceded, separated_pair},
 };
 
 #[cfg(feature = "ext_condstore_qresync")]
 use crate::extensions::condstore_qresync::mod_sequence_valzer;
 use crate::{
-    core::{number, number64, nz_number},
+    core::{keyword, nil, number, number64, nz_number},
     decode::IMAPResult,
+    extensions::tagged_ext::tagged_ext_label,
 };
 
-/// `status-att = "MESSAGES" /
-///               "RECENT" /
-///               "UIDNEXT" /
-///               "UIDVALIDITY" /
-///               "UNSEEN"`
+/// ```abnf
+/// status-att = "MESSAGES" /
+///              "RECENT" /
+///              "UIDNEXT" /
+///              "UIDVALIDITY" /
+///              "UNSEEN" /
+///              "DELETED" /         ; RFC 9051
+///              "DELETED-STORAGE" / ; RFC 9208
+///              "HIGHESTMODSEQ" /   ; RFC 7162
+///              tagged-ext-label    ; Unknown extension
+/// ```
+///
+/// Note: Known items are recognized by their keyword. Everything else is a `tagged-ext-label`.
 pub(crate) fn status_att(input: &[u8]) -> IMAPResult<&[u8], StatusDataItemName> {
-    alt((
-        value(StatusDataItemName::Messages, tag_no_case(b"MESSAGES")),
-        value(StatusDataItemName::Recent, tag_no_case(b"RECENT")),
-        value(StatusDataItemName::UidNext, tag_no_case(b"UIDNEXT")),
-        value(StatusDataItemName::UidValidity, tag_no_case(b"UIDVALIDITY")),
-        value(StatusDataItemName::Unseen, tag_no_case(b"UNSEEN")),
-        value(
-            StatusDataItemName::DeletedStorage,
-            tag_no_case(b"DELETED-STORAGE"),
-        ),
-        value(StatusDataItemName::Deleted, tag_no_case(b"DELETED")),
-        #[cfg(feature = "ext_condstore_qresync")]
-        value(
-            StatusDataItemName::HighestModSeq,
-            tag_no_case(b"HIGHESTMODSEQ"),
-        ),
-    ))(input)
+    // Length of the longest keyword handled below, i.e., "DELETED-STORAGE".
+    let mut buffer = [0; 15];
+
+    if let Some(keyword) = keyword(input, &mut buffer)? {
+        let item = match keyword {
+            b"MESSAGES" => Some(StatusDataItemName::Messages),
+            b"RECENT" => Some(StatusDataItemName::Recent),
+            b"UIDNEXT" => Some(StatusDataItemName::UidNext),
+            b"UIDVALIDITY" => Some(StatusDataItemName::UidValidity),
+            b"UNSEEN" => Some(StatusDataItemName::Unseen),
+            b"DELETED-STORAGE" => Some(StatusDataItemName::DeletedStorage),
+            b"DELETED" => Some(StatusDataItemName::Deleted),
+            #[cfg(feature = "ext_condstore_qresync")]
+            b"HIGHESTMODSEQ" => Some(StatusDataItemName::HighestModSeq),
+            _ => None,
+        };
+
+        if let Some(item) = item {
+            return Ok((&input[keyword.len()..], item));
+        }
+    }
+
+    map(tagged_ext_label, StatusDataItemName::from)(input)
 }
 
 /// `status-att-list = status-att-val *(SP status-att-val)`
@@ -53,46 +69,51 @@ pub(crate) fn status_att_list(input: &[u8]) -> IMAPResult<&[u8], Vec<StatusDataI
 ///                   "UIDNEXT" SP nz-number /
 ///                   "UIDVALIDITY" SP nz-number /
 ///                   "UNSEEN" SP number /
-///                   "HIGHESTMODSEQ" SP mod-sequence-valzer
+///                   "DELETED" SP number /           ; RFC 9051
+///                   "DELETED-STORAGE" SP number64 / ; RFC 9208
+///                   "HIGHESTMODSEQ" SP mod-sequence-valzer /
+///                   tagged-ext-label SP (number64 / nil) ; Unknown extension
 /// ```
 ///
 /// Note: See errata id: 261
 fn status_att_val(input: &[u8]) -> IMAPResult<&[u8], StatusDataItem> {
-    alt((
-        map(
-            preceded(tag_no_case(b"MESSAGES "), number),
-            StatusDataItem::Messages,
-        ),
-        map(
-            preceded(tag_no_case(b"RECENT "), number),
-            StatusDataItem::Recent,
-        ),
-        map(
-            preceded(tag_no_case(b"UIDNEXT "), nz_number),
-            StatusDataItem::UidNext,
-        ),
-        map(
-            preceded(tag_no_case(b"UIDVALIDITY "), nz_number),
-            StatusDataItem::UidValidity,
-        ),
-        map(
-            preceded(tag_no_case(b"UNSEEN "), number),
-            StatusDataItem::Unseen,
-        ),
-        map(
-            preceded(tag_no_case(b"DELETED-STORAGE "), number64),
-            StatusDataItem::DeletedStorage,
-        ),
-        map(
-            preceded(tag_no_case(b"DELETED "), number),
-            StatusDataItem::Deleted,
-        ),
-        #[cfg(feature = "ext_condstore_qresync")]
-        map(
-            preceded(tag_no_case(b"HIGHESTMODSEQ "), mod_sequence_valzer),
-            StatusDataItem::HighestModSeq,
+    // Length of the longest keyword handled below, i.e., "DELETED-STORAGE".
+    let mut buffer = [0; 15];
+
+    if let Some(keyword) = keyword(input, &mut buffer)? {
+        let remaining = &input[keyword.len()..];
+
+        match keyword {
+            b"MESSAGES" => return map(preceded(sp, number), StatusDataItem::Messages)(remaining),
+            b"RECENT" => return map(preceded(sp, number), StatusDataItem::Recent)(remaining),
+            b"UIDNEXT" => return map(preceded(sp, nz_number), StatusDataItem::UidNext)(remaining),
+            b"UIDVALIDITY" => {
+                return map(preceded(sp, nz_number), StatusDataItem::UidValidity)(remaining)
+            }
+            b"UNSEEN" => return map(preceded(sp, number), StatusDataItem::Unseen)(remaining),
+            b"DELETED-STORAGE" => {
+                return map(preceded(sp, number64), StatusDataItem::DeletedStorage)(remaining)
+            }
+            b"DELETED" => return map(preceded(sp, number), StatusDataItem::Deleted)(remaining),
+            #[cfg(feature = "ext_condstore_qresync")]
+            b"HIGHESTMODSEQ" => {
+                return map(
+                    preceded(sp, mod_sequence_valzer),
+                    StatusDataItem::HighestModSeq,
+                )(remaining)
+            }
+            _ => {}
+        }
+    }
+
+    map(
+        separated_pair(
+            map_res(tagged_ext_label, StatusDataItemNameOther::try_from),
+            sp,
+            alt((map(number64, Some), value(None, nil))),
         ),
-    ))(input)
+        |(name, value)| StatusDataItem::Other { name, value },
+    )(input)
 }
 
 #[cfg(test)]
@@ -100,7 +121,7 @@ mod tests {
     use std::num::NonZeroU32;
 
     use super::*;
-    use crate::testing::known_answer_test_encode;
+    use crate::testing::{known_answer_test_encode, known_answer_test_parse};
 
     #[test]
     fn test_encode_status_data_item_name() {
@@ -144,4 +165,83 @@ mod tests {
             known_answer_test_encode(test);
         }
     }
+
+    #[test]
+    fn test_parse_status_att() {
+        let tests = [
+            (
+                b"DELETED ".as_ref(),
+                b" ".as_ref(),
+                StatusDataItemName::Deleted,
+            ),
+            (
+                b"DELETED-STORAGE ",
+                b" ",
+                StatusDataItemName::DeletedStorage,
+            ),
+            (
+                b"deleted-storage)",
+                b")",
+                StatusDataItemName::DeletedStorage,
+            ),
+            (
+                b"UNSEEN_X:1 ",
+                b" ",
+                StatusDataItemName::try_from("UNSEEN_X:1").unwrap(),
+            ),
+            (
+                b"DELETEDX ",
+                b" ",
+                StatusDataItemName::try_from("DELETEDX").unwrap(),
+            ),
+            (
+                b"UNSEEN-FOO)",
+                b")",
+                StatusDataItemName::try_from("UNSEEN-FOO").unwrap(),
+            ),
+        ];
+
+        for test in tests {
+            known_answer_test_parse(test, status_att);
+        }
+    }
+
+    #[test]
+    fn test_parse_status_att_val_known() {
+        // A known item is never parsed as `tagged-ext-label`.
+        assert!(matches!(
+            status_att_val(b"uidnext 0)"),
+            Err(nom::Err::Error(_))
+        ));
+
+        // ... but a label that only starts with a known item is.
+        let (remaining, item) = status_att_val(b"UNSEEN_X 1)").unwrap();
+        assert_eq!(remaining, b")");
+        assert!(matches!(item, StatusDataItem::Other { value: Some(1), .. }));
+
+        let (remaining, item) = status_att_val(b"DELETEDX NIL)").unwrap();
+        assert_eq!(remaining, b")");
+        assert!(matches!(item, StatusDataItem::Other { value: None, .. }));
+    }
+
+    #[test]
+    fn test_parse_status_att_list() {
+        let tests = [(
+            b"MESSAGES 3 DELETED 1 DELETED-STORAGE 9223372036854775807)".as_ref(),
+            b")".as_ref(),
+            vec![
+                StatusDataItem::Messages(3),
+                StatusDataItem::Deleted(1),
+                StatusDataItem::DeletedStorage(i64::MAX as u64),
+            ],
+        )];
+
+        for test in tests {
+            known_answer_test_parse(test, status_att_list);
+        }
+
+        // `number64` is a 63-bit number.
+        assert!(status_att_list(b"DELETED-STORAGE 9223372036854775808)").is_err());
+        assert!(status_att_list(b"DELETED-STORAGE 18446744073709551615)").is_err());
+    }
 }
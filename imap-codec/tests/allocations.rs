@@ -0,0 +1,167 @@
+//! Encoding into a sufficiently large buffer must not allocate.
+//!
+//! Decoding must borrow from the input where the IMAP grammar allows it.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use imap_codec::{
+    decode::Decoder,
+    encode::Encoder,
+    imap_types::{
+        command::{Command, CommandBody},
+        core::{Atom, IString, Literal, NString, Quoted},
+        fetch::{MessageDataItem, MessageDataItemName},
+        flag::{Flag, FlagFetch},
+        response::{Capability, Data, Response},
+        sequence::Uid,
+    },
+    CommandCodec, ResponseCodec,
+};
+
+/// Allocator that counts the allocations of the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count() {
+    let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count();
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn encode_command_without_allocation() {
+    let codec = CommandCodec::default();
+    let cmd = Command::new(
+        "A1",
+        CommandBody::fetch(
+            "1,3:*",
+            vec![
+                MessageDataItemName::Uid,
+                MessageDataItemName::Flags,
+                MessageDataItemName::Rfc822Size,
+            ],
+            true,
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    let mut out = Vec::with_capacity(1024);
+    assert_eq!(allocations(|| codec.encode_to(&cmd, &mut out).unwrap()), 0);
+    assert_eq!(out, b"A1 UID FETCH 1,3:* (UID FLAGS RFC822.SIZE)\r\n");
+}
+
+#[test]
+fn encode_response_without_allocation() {
+    let codec = ResponseCodec::default();
+    let rsp = Response::Data(
+        Data::fetch(
+            u32::MAX,
+            vec![
+                MessageDataItem::Uid(Uid::new(1234).unwrap()),
+                MessageDataItem::Flags(vec![
+                    FlagFetch::Flag(Flag::Seen),
+                    FlagFetch::Flag(Flag::Keyword(Atom::try_from("custom").unwrap())),
+                ]),
+                MessageDataItem::Rfc822Size(0),
+                MessageDataItem::BodyExt {
+                    section: None,
+                    origin: Some(42),
+                    data: NString(Some(IString::Literal(Literal::try_from("hello").unwrap()))),
+                },
+            ],
+        )
+        .unwrap(),
+    );
+
+    let mut out = Vec::with_capacity(1024);
+    assert_eq!(allocations(|| codec.encode_to(&rsp, &mut out).unwrap()), 0);
+    assert_eq!(
+        out,
+        b"* 4294967295 FETCH (UID 1234 FLAGS (\\Seen custom) RFC822.SIZE 0 BODY[]<42> {5}\r\nhello)\r\n"
+    );
+}
+
+#[test]
+fn decode_command_without_allocation() {
+    let codec = CommandCodec::default();
+
+    let mut result = None;
+    assert_eq!(
+        allocations(|| result = Some(codec.decode(b"A1 LOGIN alice \"secret\"\r\n"))),
+        0
+    );
+    let (remaining, cmd) = result.unwrap().unwrap();
+    assert!(remaining.is_empty());
+    assert_eq!(
+        cmd,
+        Command::new(
+            "A1",
+            CommandBody::login("alice", Quoted::try_from("secret").unwrap()).unwrap()
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn decode_escaped_quoted_allocates_once() {
+    let codec = CommandCodec::default();
+
+    let mut result = None;
+    assert_eq!(
+        allocations(|| result = Some(codec.decode(b"A1 LOGIN alice \"a\\\\b\\\"c\"\r\n"))),
+        1
+    );
+    let (_, cmd) = result.unwrap().unwrap();
+    assert_eq!(
+        cmd,
+        Command::new("A1", CommandBody::login("alice", "a\\b\"c").unwrap()).unwrap()
+    );
+}
+
+#[test]
+fn keywords_without_allocation() {
+    let seen = Atom::try_from("SEEN").unwrap();
+    let imap4rev1 = Atom::try_from("IMAP4rev1").unwrap();
+
+    let mut flag = None;
+    let mut capability = None;
+    assert_eq!(
+        allocations(|| {
+            flag = Some(Flag::system(seen));
+            capability = Some(Capability::from(imap4rev1));
+        }),
+        0
+    );
+    assert_eq!(flag, Some(Flag::Seen));
+    assert_eq!(capability, Some(Capability::Imap4Rev1));
+}
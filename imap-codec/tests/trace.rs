@@ -1418,6 +1418,22 @@ S: A284 OK SORT completed
     test_lines_of_trace(trace);
 }
 
+#[test]
+fn test_trace_esort() {
+    let trace = br#"C: A283 SORT RETURN (COUNT) (DATE) UTF-8 SINCE 1-Feb-1994
+S: * ESEARCH (TAG "A283") COUNT 3
+S: A283 OK SORT completed
+C: A284 UID SORT RETURN (MIN MAX) (REVERSE SUBJECT) UTF-8 ALL
+S: * ESEARCH (TAG "A284") UID MIN 7 MAX 3
+S: A284 OK SORT completed
+C: A285 SORT RETURN (PARTIAL 1:3) (ARRIVAL) US-ASCII UNSEEN
+S: * ESEARCH (TAG "A285") PARTIAL (1:3 882,2,84)
+S: A285 OK SORT completed
+"#;
+
+    test_lines_of_trace(trace);
+}
+
 #[test]
 fn test_trace_thread() {
     let trace = br#"C: A283 THREAD ORDEREDSUBJECT UTF-8 SINCE 5-MAR-2000
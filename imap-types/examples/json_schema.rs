@@ -0,0 +1,15 @@
+use imap_types::{
+    command::Command,
+    response::{Greeting, Response},
+};
+use schemars::schema_for;
+
+fn main() {
+    let greeting = schema_for!(Greeting);
+    let command = schema_for!(Command);
+    let response = schema_for!(Response);
+
+    for schema in [greeting, command, response] {
+        println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+    }
+}
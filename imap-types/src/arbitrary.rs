@@ -1,27 +1,37 @@
+use core::num::NonZeroU32;
+
 use arbitrary::{Arbitrary, Unstructured};
 use chrono::{FixedOffset, TimeZone};
 
+#[cfg(feature = "ext_acl")]
+use crate::extensions::acl::{Right, Rights};
 #[cfg(feature = "ext_condstore_qresync")]
-use crate::extensions::condstore_qresync::AttributeFlag;
+use crate::extensions::condstore_qresync::{AttributeFlag, ModSeq};
 use crate::{
     auth::AuthMechanism,
     body::{
         BasicFields, Body, BodyExtension, BodyStructure, SinglePartExtensionData, SpecificFields,
     },
+    command::SelectParameterOther,
     core::{
-        AString, Atom, AtomExt, IString, Literal, LiteralMode, NString, Quoted, QuotedChar, Tag,
-        Text, Vec1, Vec2,
+        AString, Atom, AtomExt, IString, Literal, LiteralData, LiteralMode, NString, Quoted,
+        QuotedChar, Tag, Text, Vec1, Vec2,
     },
     datetime::{DateTime, NaiveDate},
-    extensions::{enable::CapabilityEnable, quota::Resource},
+    extensions::{
+        enable::CapabilityEnable,
+        quota::{QuotaGet, QuotaSet, Resource, QUOTA_VALUE_MAX},
+        tagged_ext::{ExtensionItem, TaggedExtLabel, TaggedExtSimple},
+    },
     flag::{Flag, FlagNameAttribute},
     mailbox::{ListCharString, Mailbox, MailboxOther},
     response::{
         Bye, Capability, Code, CodeOther, CommandContinuationRequestBasic, Greeting, GreetingKind,
         Status, StatusBody, StatusKind, Tagged,
     },
-    search::SearchKey,
-    sequence::SequenceSet,
+    search::{SearchKey, SearchResults},
+    sequence::{SeqOrUid, Sequence, SequenceSet},
+    status::{StatusDataItem, StatusDataItemNameOther},
 };
 #[cfg(not(feature = "arbitrary_simplified"))]
 use crate::{body::MultiPartExtensionData, envelope::Envelope};
@@ -76,6 +86,11 @@ impl_arbitrary_try_from! { MailboxOther<'a>, AString<'a> }
 impl_arbitrary_try_from! { CapabilityEnable<'a>, &str }
 impl_arbitrary_try_from! { Resource<'a>, &str }
 impl_arbitrary_try_from! { AuthMechanism<'a>, &str }
+impl_arbitrary_try_from! { TaggedExtLabel<'a>, &str }
+impl_arbitrary_try_from! { StatusDataItemNameOther<'a>, TaggedExtLabel<'a> }
+impl_arbitrary_try_from! { SelectParameterOther<'a>, ExtensionItem<'a> }
+#[cfg(feature = "ext_acl")]
+impl_arbitrary_try_from! { Right, char }
 impl_arbitrary_try_from_t! { Vec1<T>, Vec<T> }
 impl_arbitrary_try_from_t! { Vec2<T>, Vec<T> }
 
@@ -86,6 +101,97 @@ impl<'a> Arbitrary<'a> for CommandContinuationRequestBasic<'a> {
     }
 }
 
+impl<'a> Arbitrary<'a> for QuotaGet<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self::unvalidated(
+            Resource::arbitrary(u)?,
+            u.int_in_range(0..=QUOTA_VALUE_MAX)?,
+            u.int_in_range(0..=QUOTA_VALUE_MAX)?,
+        ))
+    }
+}
+
+impl<'a> Arbitrary<'a> for QuotaSet<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(Self::unvalidated(
+            Resource::arbitrary(u)?,
+            u.int_in_range(0..=QUOTA_VALUE_MAX)?,
+        ))
+    }
+}
+
+#[cfg(feature = "ext_acl")]
+impl<'a> Arbitrary<'a> for Rights {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut rights = Rights::from(Vec::<Right>::arbitrary(u)?);
+        // `RIGHTS=` (without any right) is not a valid capability.
+        rights.insert(Right::arbitrary(u)?);
+        Ok(rights)
+    }
+}
+
+#[cfg(feature = "ext_condstore_qresync")]
+impl<'a> Arbitrary<'a> for ModSeq {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        // Safety: `unwrap` can't panic because the range is valid.
+        Ok(ModSeq::new(u.int_in_range(1..=ModSeq::MAX.get())?).unwrap())
+    }
+}
+
+/// Generate a `number64`, i.e., an unsigned 63-bit integer.
+fn number64(u: &mut Unstructured) -> arbitrary::Result<u64> {
+    u.int_in_range(0..=i64::MAX as u64)
+}
+
+impl<'a> Arbitrary<'a> for StatusDataItem<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let max = if cfg!(feature = "ext_condstore_qresync") {
+            8
+        } else {
+            7
+        };
+
+        Ok(match u.int_in_range(0u8..=max)? {
+            0 => StatusDataItem::Messages(Arbitrary::arbitrary(u)?),
+            1 => StatusDataItem::Recent(Arbitrary::arbitrary(u)?),
+            2 => StatusDataItem::UidNext(Arbitrary::arbitrary(u)?),
+            3 => StatusDataItem::UidValidity(Arbitrary::arbitrary(u)?),
+            4 => StatusDataItem::Unseen(Arbitrary::arbitrary(u)?),
+            5 => StatusDataItem::Deleted(Arbitrary::arbitrary(u)?),
+            6 => StatusDataItem::DeletedStorage(number64(u)?),
+            7 => StatusDataItem::Other {
+                name: Arbitrary::arbitrary(u)?,
+                value: match bool::arbitrary(u)? {
+                    true => Some(number64(u)?),
+                    false => None,
+                },
+            },
+            #[cfg(feature = "ext_condstore_qresync")]
+            8 => StatusDataItem::HighestModSeq(Arbitrary::arbitrary(u)?),
+            _ => unreachable!(),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for TaggedExtSimple {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(match bool::arbitrary(u)? {
+            true => {
+                let sequence_set = SequenceSet::arbitrary(u)?;
+
+                // A single number is encoded exactly like `Number`, so it's normalized here.
+                match sequence_set.0.as_ref() {
+                    [Sequence::Single(SeqOrUid::Value(value))] => {
+                        TaggedExtSimple::Number(u64::from(value.get()))
+                    }
+                    _ => TaggedExtSimple::SequenceSet(sequence_set),
+                }
+            }
+            false => TaggedExtSimple::Number(number64(u)?),
+        })
+    }
+}
+
 // TODO(#301): This is due to the `Code`/`Text` ambiguity.
 impl<'a> Arbitrary<'a> for Greeting<'a> {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
@@ -177,6 +283,18 @@ impl<'a> Arbitrary<'a> for Literal<'a> {
     }
 }
 
+impl<'a> Arbitrary<'a> for SearchResults {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(SearchResults::from(Vec::<NonZeroU32>::arbitrary(u)?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for LiteralData<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(LiteralData::Borrowed(<&[u8]>::arbitrary(u)?))
+    }
+}
+
 impl<'a> Arbitrary<'a> for CodeOther<'a> {
     fn arbitrary(_: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
         // `CodeOther` is a fallback and should usually not be created.
@@ -494,6 +612,16 @@ impl<'a> Arbitrary<'a> for NaiveDate {
     }
 }
 
+/// Generates the flags of a STORE command, i.e., without `\Recent` (see [`Flag::is_storable`]).
+pub(crate) fn arbitrary_storable_flags<'a>(
+    u: &mut Unstructured<'a>,
+) -> arbitrary::Result<Vec<Flag<'a>>> {
+    Ok(Vec::<Flag>::arbitrary(u)?
+        .into_iter()
+        .filter(Flag::is_storable)
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use arbitrary::{Arbitrary, Error, Unstructured};
@@ -1,7 +1,12 @@
 //! Authentication-related types.
 
-use std::{
+use alloc::{
     borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
     fmt::{Display, Formatter},
     str::FromStr,
 };
@@ -9,17 +14,23 @@ use std::{
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "cram_md5")]
+use crate::response::CommandContinuationRequest;
 use crate::{
     core::{impl_try_from, Atom},
-    error::ValidationError,
+    error::{ValidationError, ValidationErrorKind},
     secret::Secret,
+    utils::ascii_lowercase,
 };
 
 /// Authentication mechanism.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 #[non_exhaustive]
 pub enum AuthMechanism<'a> {
@@ -33,6 +44,8 @@ pub enum AuthMechanism<'a> {
     /// base64(b"<authorization identity>\x00<authentication identity>\x00<password>")
     /// ```
     ///
+    /// See [`PlainCredentials`] to create or inspect the (decoded) message.
+    ///
     /// # Reference(s):
     ///
     /// * RFC4616: The PLAIN Simple Authentication and Security Layer (SASL) Mechanism
@@ -64,6 +77,9 @@ pub enum AuthMechanism<'a> {
     /// base64(b"n,a=<user>,\x01host=<host>\x01port=<port>\x01auth=Bearer <token>\x01\x01")
     /// ```
     ///
+    /// See [`OAuthBearerCredentials`] to create or inspect the (decoded) message, and
+    /// [`OAuthBearerErrorResponse`] to inspect the server's error.
+    ///
     /// # Reference(s):
     ///
     /// * <https://datatracker.ietf.org/doc/html/rfc7628>
@@ -79,6 +95,9 @@ pub enum AuthMechanism<'a> {
     /// base64(b"user=<user>\x01auth=Bearer <token>\x01\x01")
     /// ```
     ///
+    /// See [`XOAuth2Credentials`] to create or inspect the (decoded) message, and
+    /// [`XOAuth2ErrorResponse`] to inspect the server's error.
+    ///
     /// # Reference(s):
     ///
     /// * <https://developers.google.com/gmail/imap/xoauth2-protocol>
@@ -147,22 +166,24 @@ impl_try_from!(Atom<'a>, 'a, Cow<'a, str>, AuthMechanism<'a>);
 
 impl<'a> From<Atom<'a>> for AuthMechanism<'a> {
     fn from(atom: Atom<'a>) -> Self {
-        match atom.as_ref().to_ascii_uppercase().as_str() {
-            "PLAIN" => Self::Plain,
-            "LOGIN" => Self::Login,
-            "OAUTHBEARER" => Self::OAuthBearer,
-            "XOAUTH2" => Self::XOAuth2,
-            "SCRAM-SHA-1" => Self::ScramSha1,
-            "SCRAM-SHA-1-PLUS" => Self::ScramSha1Plus,
-            "SCRAM-SHA-256" => Self::ScramSha256,
-            "SCRAM-SHA-256-PLUS" => Self::ScramSha256Plus,
+        match ascii_lowercase(atom.as_ref(), &mut [0; 20]).unwrap_or_default() {
+            "plain" => Self::Plain,
+            "login" => Self::Login,
+            "oauthbearer" => Self::OAuthBearer,
+            "xoauth2" => Self::XOAuth2,
+            "scram-sha-1" => Self::ScramSha1,
+            "scram-sha-1-plus" => Self::ScramSha1Plus,
+            "scram-sha-256" => Self::ScramSha256,
+            "scram-sha-256-plus" => Self::ScramSha256Plus,
+            "scram-sha3-512" => Self::ScramSha3_512,
+            "scram-sha3-512-plus" => Self::ScramSha3_512Plus,
             _ => Self::Other(AuthMechanismOther(atom)),
         }
     }
 }
 
 impl Display for AuthMechanism<'_> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.write_str(self.as_ref())
     }
 }
@@ -197,6 +218,7 @@ impl FromStr for AuthMechanism<'static> {
 ///
 /// It's guaranteed that this type can't represent any mechanism from [`AuthMechanism`].
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct AuthMechanismOther<'a>(Atom<'a>);
 
@@ -205,6 +227,7 @@ pub struct AuthMechanismOther<'a>(Atom<'a>);
 /// Holds the raw binary data, i.e., a `Vec<u8>`, *not* the BASE64 string.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum AuthenticateData<'a> {
     /// Continue SASL authentication.
@@ -225,6 +248,828 @@ impl<'a> AuthenticateData<'a> {
     }
 }
 
+/// Credentials of the PLAIN SASL mechanism.
+///
+/// Can be used to create and inspect the (decoded) PLAIN message, e.g., as an initial response or
+/// as [`AuthenticateData`].
+///
+/// ```abnf
+/// message = [authzid] UTF8NUL authcid UTF8NUL passwd
+/// ```
+///
+/// # Reference(s):
+///
+/// * RFC4616: The PLAIN Simple Authentication and Security Layer (SASL) Mechanism
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "serde", serde(try_from = "PlainCredentialsShadow"))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub struct PlainCredentials<'a> {
+    authzid: Option<Cow<'a, str>>,
+    authcid: Cow<'a, str>,
+    password: Secret<Cow<'a, str>>,
+}
+
+/// Use shadow type to support validated deserialization
+/// until `serde` provides built-in support for this case.
+#[cfg(feature = "serde")]
+#[derive(Deserialize, Debug)]
+struct PlainCredentialsShadow<'a> {
+    authzid: Option<Cow<'a, str>>,
+    authcid: Cow<'a, str>,
+    password: Secret<Cow<'a, str>>,
+}
+
+#[cfg(feature = "serde")]
+impl<'a> TryFrom<PlainCredentialsShadow<'a>> for PlainCredentials<'a> {
+    type Error = ValidationError;
+
+    fn try_from(value: PlainCredentialsShadow<'a>) -> Result<Self, Self::Error> {
+        let PlainCredentialsShadow {
+            authzid,
+            authcid,
+            password,
+        } = value;
+
+        Self::new(authzid, authcid, password.declassify().clone())
+    }
+}
+
+impl<'a> PlainCredentials<'a> {
+    /// Create PLAIN credentials.
+    ///
+    /// Note: None of the values may contain NUL. The authentication identity and the password must
+    /// not be empty. An empty authorization identity is treated as absent.
+    pub fn new<Z, C, P>(
+        authzid: Option<Z>,
+        authcid: C,
+        password: P,
+    ) -> Result<Self, ValidationError>
+    where
+        Z: Into<Cow<'a, str>>,
+        C: Into<Cow<'a, str>>,
+        P: Into<Cow<'a, str>>,
+    {
+        let authzid = authzid
+            .map(Into::into)
+            .filter(|authzid| !authzid.is_empty());
+        let authcid = authcid.into();
+        let password = password.into();
+
+        if let Some(ref authzid) = authzid {
+            Self::validate(authzid)?;
+        }
+        Self::validate(&authcid)?;
+        Self::validate(&password)?;
+
+        Ok(Self {
+            authzid,
+            authcid,
+            password: Secret::new(password),
+        })
+    }
+
+    fn validate(value: &str) -> Result<(), ValidationError> {
+        if value.is_empty() {
+            return Err(ValidationError::new(ValidationErrorKind::Empty));
+        }
+
+        if let Some(at) = value.bytes().position(|byte| byte == 0x00) {
+            return Err(ValidationError::new(ValidationErrorKind::InvalidByteAt {
+                byte: 0x00,
+                at,
+            }));
+        }
+
+        Ok(())
+    }
+
+    /// Authorization identity, i.e., the identity to act as.
+    pub fn authzid(&self) -> Option<&str> {
+        self.authzid.as_deref()
+    }
+
+    /// Authentication identity, i.e., the identity whose password is used.
+    pub fn authcid(&self) -> &str {
+        &self.authcid
+    }
+
+    pub fn password(&self) -> &Secret<Cow<'a, str>> {
+        &self.password
+    }
+
+    /// Serialize into the (decoded) PLAIN message, i.e., `[authzid] NUL authcid NUL passwd`.
+    pub fn to_bytes(&self) -> Secret<Vec<u8>> {
+        let mut out = Vec::new();
+
+        if let Some(ref authzid) = self.authzid {
+            out.extend_from_slice(authzid.as_bytes());
+        }
+        out.push(0x00);
+        out.extend_from_slice(self.authcid.as_bytes());
+        out.push(0x00);
+        out.extend_from_slice(self.password.declassify().as_bytes());
+
+        Secret::new(out)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for PlainCredentials<'a> {
+    type Error = ValidationError;
+
+    /// Parse the (decoded) PLAIN message, i.e., `[authzid] NUL authcid NUL passwd`.
+    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
+        let message = core::str::from_utf8(value)
+            .map_err(|_| ValidationError::new(ValidationErrorKind::Invalid))?;
+
+        let mut parts = message.split('\x00');
+
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(authzid), Some(authcid), Some(password), None) => {
+                Self::new(Some(authzid), authcid, password)
+            }
+            _ => Err(ValidationError::new(ValidationErrorKind::Invalid)),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a AuthenticateData<'_>> for PlainCredentials<'a> {
+    type Error = ValidationError;
+
+    fn try_from(value: &'a AuthenticateData<'_>) -> Result<Self, Self::Error> {
+        match value {
+            AuthenticateData::Continue(data) => {
+                let data: &[u8] = data.declassify();
+                Self::try_from(data)
+            }
+            AuthenticateData::Cancel => Err(ValidationError::new(ValidationErrorKind::Invalid)),
+        }
+    }
+}
+
+impl From<&PlainCredentials<'_>> for AuthenticateData<'static> {
+    fn from(value: &PlainCredentials<'_>) -> Self {
+        AuthenticateData::Continue(Secret::new(Cow::Owned(
+            value.to_bytes().declassify().clone(),
+        )))
+    }
+}
+
+/// Compute the client response to a CRAM-MD5 challenge.
+///
+/// The server sends a (base64-encoded) challenge as a continuation request. The client answers
+/// with its user name and the (hex-encoded) keyed MD5 digest of the challenge, using the password
+/// as key:
+///
+/// ```text
+/// S: + PDE4OTYuNjk3MTcwOTUyQHBvc3RvZmZpY2UucmVzdG9uLm1jaS5uZXQ+
+/// C: dGltIGI5MTNhNjAyYzdlZGE3YTQ5NWI0ZTZlNzMzNGQzODkw
+/// ```
+///
+/// Note: CRAM-MD5 is considered obsolete. Prefer SCRAM (or PLAIN over TLS) when available.
+///
+/// # Reference(s):
+///
+/// * RFC2195: IMAP/POP AUTHorize Extension for Simple Challenge/Response
+#[cfg(feature = "cram_md5")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cram_md5")))]
+pub fn cram_md5(
+    challenge: &CommandContinuationRequest,
+    user: &str,
+    password: &str,
+) -> Result<AuthenticateData<'static>, ValidationError> {
+    let CommandContinuationRequest::Base64(challenge) = challenge else {
+        return Err(ValidationError::new(ValidationErrorKind::Invalid));
+    };
+
+    let digest = hmac_md5(password.as_bytes(), challenge);
+
+    let mut response = format!("{user} ");
+    for byte in digest {
+        response.push_str(&format!("{byte:02x}"));
+    }
+
+    Ok(AuthenticateData::Continue(Secret::new(Cow::Owned(
+        response.into_bytes(),
+    ))))
+}
+
+/// HMAC-MD5 as defined in RFC 2104.
+#[cfg(feature = "cram_md5")]
+fn hmac_md5(key: &[u8], message: &[u8]) -> [u8; 16] {
+    const BLOCK_SIZE: usize = 64;
+
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..16].copy_from_slice(&md5::compute(key).0);
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = md5::Context::new();
+    inner.consume(block.map(|byte| byte ^ 0x36));
+    inner.consume(message);
+    let inner = inner.compute();
+
+    let mut outer = md5::Context::new();
+    outer.consume(block.map(|byte| byte ^ 0x5c));
+    outer.consume(inner.0);
+    outer.compute().0
+}
+
+/// Credentials of the XOAUTH2 SASL mechanism.
+///
+/// Can be used to create and inspect the (decoded) XOAUTH2 message, e.g., as an initial response
+/// or as [`AuthenticateData`].
+///
+/// ```text
+/// user=<user>\x01auth=Bearer <token>\x01\x01
+/// ```
+///
+/// # Reference(s):
+///
+/// * <https://developers.google.com/gmail/imap/xoauth2-protocol>
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "serde", serde(try_from = "XOAuth2CredentialsShadow"))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub struct XOAuth2Credentials<'a> {
+    user: Cow<'a, str>,
+    token: Secret<Cow<'a, str>>,
+}
+
+/// Use shadow type to support validated deserialization
+/// until `serde` provides built-in support for this case.
+#[cfg(feature = "serde")]
+#[derive(Deserialize, Debug)]
+struct XOAuth2CredentialsShadow<'a> {
+    user: Cow<'a, str>,
+    token: Secret<Cow<'a, str>>,
+}
+
+#[cfg(feature = "serde")]
+impl<'a> TryFrom<XOAuth2CredentialsShadow<'a>> for XOAuth2Credentials<'a> {
+    type Error = ValidationError;
+
+    fn try_from(value: XOAuth2CredentialsShadow<'a>) -> Result<Self, Self::Error> {
+        Self::new(value.user, value.token.declassify().clone())
+    }
+}
+
+impl<'a> XOAuth2Credentials<'a> {
+    /// Create XOAUTH2 credentials.
+    ///
+    /// Note: Neither the user nor the token may be empty or contain `\x01`.
+    pub fn new<U, T>(user: U, token: T) -> Result<Self, ValidationError>
+    where
+        U: Into<Cow<'a, str>>,
+        T: Into<Cow<'a, str>>,
+    {
+        let user = user.into();
+        let token = token.into();
+
+        validate_kvsep_free(&user)?;
+        validate_kvsep_free(&token)?;
+
+        Ok(Self {
+            user,
+            token: Secret::new(token),
+        })
+    }
+
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    /// OAuth 2.0 access token (without the `Bearer ` prefix).
+    pub fn token(&self) -> &Secret<Cow<'a, str>> {
+        &self.token
+    }
+
+    /// Serialize into the (decoded) XOAUTH2 message, i.e., `user=...\x01auth=Bearer ...\x01\x01`.
+    pub fn to_bytes(&self) -> Secret<Vec<u8>> {
+        Secret::new(
+            format!(
+                "user={}\x01auth=Bearer {}\x01\x01",
+                self.user,
+                self.token.declassify()
+            )
+            .into_bytes(),
+        )
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for XOAuth2Credentials<'a> {
+    type Error = ValidationError;
+
+    /// Parse the (decoded) XOAUTH2 message, i.e., `user=...\x01auth=Bearer ...\x01\x01`.
+    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
+        let invalid = || ValidationError::new(ValidationErrorKind::Invalid);
+
+        let message = core::str::from_utf8(value).map_err(|_| invalid())?;
+        let message = message.strip_suffix("\x01\x01").ok_or_else(invalid)?;
+        let (user, auth) = message.split_once('\x01').ok_or_else(invalid)?;
+        let user = user.strip_prefix("user=").ok_or_else(invalid)?;
+        let token = auth.strip_prefix("auth=Bearer ").ok_or_else(invalid)?;
+
+        Self::new(user, token)
+    }
+}
+
+impl<'a> TryFrom<&'a AuthenticateData<'_>> for XOAuth2Credentials<'a> {
+    type Error = ValidationError;
+
+    fn try_from(value: &'a AuthenticateData<'_>) -> Result<Self, Self::Error> {
+        match value {
+            AuthenticateData::Continue(data) => {
+                let data: &[u8] = data.declassify();
+                Self::try_from(data)
+            }
+            AuthenticateData::Cancel => Err(ValidationError::new(ValidationErrorKind::Invalid)),
+        }
+    }
+}
+
+impl From<&XOAuth2Credentials<'_>> for AuthenticateData<'static> {
+    fn from(value: &XOAuth2Credentials<'_>) -> Self {
+        AuthenticateData::Continue(Secret::new(Cow::Owned(
+            value.to_bytes().declassify().clone(),
+        )))
+    }
+}
+
+/// Error sent by the server (as a base64-encoded continuation request) when XOAUTH2 failed.
+///
+/// ```text
+/// {"status":"401","schemes":"Bearer","scope":"https://mail.google.com/"}
+/// ```
+///
+/// Note: The client must answer with an empty line (or cancel) to receive the final tagged
+/// response.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub struct XOAuth2ErrorResponse<'a> {
+    pub status: Cow<'a, str>,
+    pub schemes: Option<Cow<'a, str>>,
+    pub scope: Option<Cow<'a, str>>,
+}
+
+impl XOAuth2ErrorResponse<'_> {
+    /// Serialize into the (decoded) JSON error.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = format!("{{\"status\":\"{}\"", json_escape(&self.status));
+
+        if let Some(ref schemes) = self.schemes {
+            out.push_str(&format!(",\"schemes\":\"{}\"", json_escape(schemes)));
+        }
+
+        if let Some(ref scope) = self.scope {
+            out.push_str(&format!(",\"scope\":\"{}\"", json_escape(scope)));
+        }
+
+        out.push('}');
+        out.into_bytes()
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for XOAuth2ErrorResponse<'a> {
+    type Error = ValidationError;
+
+    /// Parse the (decoded) JSON error.
+    ///
+    /// Note: This is not a general-purpose JSON parser. It accepts a flat object with string (or
+    /// bare) values and ignores unknown keys.
+    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
+        let invalid = || ValidationError::new(ValidationErrorKind::Invalid);
+
+        let (mut status, mut schemes, mut scope) = (None, None, None);
+
+        for (key, value) in json_object(value).ok_or_else(invalid)? {
+            match key.as_ref() {
+                "status" => status = Some(value),
+                "schemes" => schemes = Some(value),
+                "scope" => scope = Some(value),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            status: status.ok_or_else(invalid)?,
+            schemes,
+            scope,
+        })
+    }
+}
+
+/// Credentials of the OAUTHBEARER SASL mechanism.
+///
+/// Can be used to create and inspect the (decoded) OAUTHBEARER message, e.g., as an initial
+/// response or as [`AuthenticateData`].
+///
+/// ```abnf
+/// client-resp = (gs2-header kvsep *kvpair kvsep) / kvsep
+///
+/// gs2-header  = "n," ["a=" saslname] ","
+///
+/// kvpair      = key "=" value kvsep
+///
+/// kvsep       = %x01
+/// ```
+///
+/// Note: Key/value pairs other than `host`, `port`, and `auth` are ignored during parsing.
+///
+/// # Reference(s):
+///
+/// * RFC7628: A Set of Simple Authentication and Security Layer (SASL) Mechanisms for OAuth
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "serde", serde(try_from = "OAuthBearerCredentialsShadow"))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub struct OAuthBearerCredentials<'a> {
+    authzid: Option<Cow<'a, str>>,
+    host: Option<Cow<'a, str>>,
+    port: Option<u16>,
+    token: Secret<Cow<'a, str>>,
+}
+
+/// Use shadow type to support validated deserialization
+/// until `serde` provides built-in support for this case.
+#[cfg(feature = "serde")]
+#[derive(Deserialize, Debug)]
+struct OAuthBearerCredentialsShadow<'a> {
+    authzid: Option<Cow<'a, str>>,
+    host: Option<Cow<'a, str>>,
+    port: Option<u16>,
+    token: Secret<Cow<'a, str>>,
+}
+
+#[cfg(feature = "serde")]
+impl<'a> TryFrom<OAuthBearerCredentialsShadow<'a>> for OAuthBearerCredentials<'a> {
+    type Error = ValidationError;
+
+    fn try_from(value: OAuthBearerCredentialsShadow<'a>) -> Result<Self, Self::Error> {
+        let OAuthBearerCredentialsShadow {
+            authzid,
+            host,
+            port,
+            token,
+        } = value;
+
+        Self::new(authzid, host, port, token.declassify().clone())
+    }
+}
+
+impl<'a> OAuthBearerCredentials<'a> {
+    /// Create OAUTHBEARER credentials.
+    ///
+    /// Note: No value may be empty or contain `\x01`. An empty authorization identity is treated
+    /// as absent.
+    pub fn new<Z, H, T>(
+        authzid: Option<Z>,
+        host: Option<H>,
+        port: Option<u16>,
+        token: T,
+    ) -> Result<Self, ValidationError>
+    where
+        Z: Into<Cow<'a, str>>,
+        H: Into<Cow<'a, str>>,
+        T: Into<Cow<'a, str>>,
+    {
+        let authzid = authzid
+            .map(Into::into)
+            .filter(|authzid| !authzid.is_empty());
+        let host = host.map(Into::into);
+        let token = token.into();
+
+        if let Some(ref authzid) = authzid {
+            validate_kvsep_free(authzid)?;
+        }
+        if let Some(ref host) = host {
+            validate_kvsep_free(host)?;
+        }
+        validate_kvsep_free(&token)?;
+
+        Ok(Self {
+            authzid,
+            host,
+            port,
+            token: Secret::new(token),
+        })
+    }
+
+    /// Authorization identity, i.e., the identity to act as.
+    pub fn authzid(&self) -> Option<&str> {
+        self.authzid.as_deref()
+    }
+
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_deref()
+    }
+
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+
+    /// OAuth 2.0 access token (without the `Bearer ` prefix).
+    pub fn token(&self) -> &Secret<Cow<'a, str>> {
+        &self.token
+    }
+
+    /// Serialize into the (decoded) OAUTHBEARER message, i.e., `n,a=...,\x01host=...\x01port=...\x01auth=Bearer ...\x01\x01`.
+    pub fn to_bytes(&self) -> Secret<Vec<u8>> {
+        let mut out = String::from("n,");
+
+        if let Some(ref authzid) = self.authzid {
+            out.push_str("a=");
+            out.push_str(&authzid.replace('=', "=3D").replace(',', "=2C"));
+        }
+        out.push_str(",\x01");
+
+        if let Some(ref host) = self.host {
+            out.push_str(&format!("host={host}\x01"));
+        }
+
+        if let Some(port) = self.port {
+            out.push_str(&format!("port={port}\x01"));
+        }
+
+        out.push_str(&format!("auth=Bearer {}\x01\x01", self.token.declassify()));
+
+        Secret::new(out.into_bytes())
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for OAuthBearerCredentials<'a> {
+    type Error = ValidationError;
+
+    /// Parse the (decoded) OAUTHBEARER message.
+    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
+        let invalid = || ValidationError::new(ValidationErrorKind::Invalid);
+
+        let message = core::str::from_utf8(value).map_err(|_| invalid())?;
+        let message = message.strip_suffix("\x01\x01").ok_or_else(invalid)?;
+        let (gs2_header, kvpairs) = message.split_once('\x01').ok_or_else(invalid)?;
+
+        let authzid = {
+            let gs2_header = gs2_header
+                .strip_prefix("n,")
+                .and_then(|gs2_header| gs2_header.strip_suffix(','))
+                .ok_or_else(invalid)?;
+
+            if gs2_header.is_empty() {
+                None
+            } else {
+                let saslname = gs2_header.strip_prefix("a=").ok_or_else(invalid)?;
+
+                Some(unescape_saslname(saslname).ok_or_else(invalid)?)
+            }
+        };
+
+        let (mut host, mut port, mut token) = (None, None, None);
+
+        for kvpair in kvpairs.split('\x01') {
+            let (key, value) = kvpair.split_once('=').ok_or_else(invalid)?;
+
+            match key {
+                "host" => host = Some(value),
+                "port" => port = Some(value.parse::<u16>().map_err(|_| invalid())?),
+                "auth" => token = Some(value.strip_prefix("Bearer ").ok_or_else(invalid)?),
+                _ => {}
+            }
+        }
+
+        Self::new(authzid, host, port, token.ok_or_else(invalid)?)
+    }
+}
+
+impl<'a> TryFrom<&'a AuthenticateData<'_>> for OAuthBearerCredentials<'a> {
+    type Error = ValidationError;
+
+    fn try_from(value: &'a AuthenticateData<'_>) -> Result<Self, Self::Error> {
+        match value {
+            AuthenticateData::Continue(data) => {
+                let data: &[u8] = data.declassify();
+                Self::try_from(data)
+            }
+            AuthenticateData::Cancel => Err(ValidationError::new(ValidationErrorKind::Invalid)),
+        }
+    }
+}
+
+impl From<&OAuthBearerCredentials<'_>> for AuthenticateData<'static> {
+    fn from(value: &OAuthBearerCredentials<'_>) -> Self {
+        AuthenticateData::Continue(Secret::new(Cow::Owned(
+            value.to_bytes().declassify().clone(),
+        )))
+    }
+}
+
+/// Error sent by the server (as a base64-encoded continuation request) when OAUTHBEARER failed.
+///
+/// ```text
+/// {"status":"invalid_token","scope":"example_scope","openid-configuration":"https://example.com/.well-known/openid-configuration"}
+/// ```
+///
+/// Note: The client must answer with a single `\x01` (or cancel) to receive the final tagged
+/// response.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub struct OAuthBearerErrorResponse<'a> {
+    pub status: Cow<'a, str>,
+    pub scope: Option<Cow<'a, str>>,
+    pub openid_configuration: Option<Cow<'a, str>>,
+}
+
+impl OAuthBearerErrorResponse<'_> {
+    /// Serialize into the (decoded) JSON error.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = format!("{{\"status\":\"{}\"", json_escape(&self.status));
+
+        if let Some(ref scope) = self.scope {
+            out.push_str(&format!(",\"scope\":\"{}\"", json_escape(scope)));
+        }
+
+        if let Some(ref openid_configuration) = self.openid_configuration {
+            out.push_str(&format!(
+                ",\"openid-configuration\":\"{}\"",
+                json_escape(openid_configuration)
+            ));
+        }
+
+        out.push('}');
+        out.into_bytes()
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for OAuthBearerErrorResponse<'a> {
+    type Error = ValidationError;
+
+    /// Parse the (decoded) JSON error.
+    ///
+    /// Note: This is not a general-purpose JSON parser. It accepts a flat object with string (or
+    /// bare) values and ignores unknown keys.
+    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
+        let invalid = || ValidationError::new(ValidationErrorKind::Invalid);
+
+        let (mut status, mut scope, mut openid_configuration) = (None, None, None);
+
+        for (key, value) in json_object(value).ok_or_else(invalid)? {
+            match key.as_ref() {
+                "status" => status = Some(value),
+                "scope" => scope = Some(value),
+                "openid-configuration" => openid_configuration = Some(value),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            status: status.ok_or_else(invalid)?,
+            scope,
+            openid_configuration,
+        })
+    }
+}
+
+/// Values in XOAUTH2 and OAUTHBEARER messages must not be empty or contain the separator `\x01`.
+fn validate_kvsep_free(value: &str) -> Result<(), ValidationError> {
+    if value.is_empty() {
+        return Err(ValidationError::new(ValidationErrorKind::Empty));
+    }
+
+    if let Some(at) = value.bytes().position(|byte| byte == 0x01) {
+        return Err(ValidationError::new(ValidationErrorKind::InvalidByteAt {
+            byte: 0x01,
+            at,
+        }));
+    }
+
+    Ok(())
+}
+
+/// ```abnf
+/// saslname = 1*(UTF8-char-safe / "=2C" / "=3D")
+/// ```
+fn unescape_saslname(saslname: &str) -> Option<Cow<'_, str>> {
+    if saslname.contains(',') {
+        return None;
+    }
+
+    if !saslname.contains('=') {
+        return Some(Cow::Borrowed(saslname));
+    }
+
+    let mut unescaped = String::with_capacity(saslname.len());
+    let mut rest = saslname;
+
+    while let Some(idx) = rest.find('=') {
+        unescaped.push_str(&rest[..idx]);
+        unescaped.push(match rest.get(idx..idx + 3)? {
+            "=2C" => ',',
+            "=3D" => '=',
+            _ => return None,
+        });
+        rest = &rest[idx + 3..];
+    }
+    unescaped.push_str(rest);
+
+    Some(Cow::Owned(unescaped))
+}
+
+#[allow(clippy::type_complexity)]
+fn json_object(input: &[u8]) -> Option<Vec<(Cow<'_, str>, Cow<'_, str>)>> {
+    let input = core::str::from_utf8(input).ok()?.trim();
+    let mut rest = input
+        .strip_prefix('{')
+        .and_then(|input| input.strip_suffix('}'))?
+        .trim_start();
+
+    let mut members = Vec::new();
+
+    while !rest.is_empty() {
+        let (key, remaining) = json_string(rest)?;
+        let remaining = remaining.trim_start().strip_prefix(':')?.trim_start();
+        let (value, remaining) = json_value(remaining)?;
+
+        members.push((key, value));
+
+        let remaining = remaining.trim_start();
+        rest = match remaining.strip_prefix(',') {
+            Some(remaining) => remaining.trim_start(),
+            None if remaining.is_empty() => remaining,
+            None => return None,
+        };
+    }
+
+    Some(members)
+}
+
+fn json_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn json_string(input: &str) -> Option<(Cow<'_, str>, &str)> {
+    let input = input.strip_prefix('"')?;
+    let mut escaped = false;
+
+    for (idx, chr) in input.char_indices() {
+        match chr {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => {
+                let (raw, rest) = (&input[..idx], &input[idx + 1..]);
+
+                let value = if raw.contains('\\') {
+                    let mut unescaped = String::with_capacity(raw.len());
+                    let mut chars = raw.chars();
+
+                    while let Some(chr) = chars.next() {
+                        if chr == '\\' {
+                            unescaped.push(match chars.next()? {
+                                '"' => '"',
+                                '\\' => '\\',
+                                '/' => '/',
+                                'n' => '\n',
+                                'r' => '\r',
+                                't' => '\t',
+                                _ => return None,
+                            });
+                        } else {
+                            unescaped.push(chr);
+                        }
+                    }
+
+                    Cow::Owned(unescaped)
+                } else {
+                    Cow::Borrowed(raw)
+                };
+
+                return Some((value, rest));
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn json_value(input: &str) -> Option<(Cow<'_, str>, &str)> {
+    if input.starts_with('"') {
+        return json_string(input);
+    }
+
+    let end = input
+        .find(|chr: char| chr == ',' || chr.is_whitespace())
+        .unwrap_or(input.len());
+
+    if end == 0 {
+        return None;
+    }
+
+    Some((Cow::Borrowed(&input[..end]), &input[end..]))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -239,4 +1084,276 @@ mod tests {
         assert!(AuthMechanism::try_from("xxxlogin").is_ok());
         assert!(AuthMechanism::try_from("xxxxoauth2").is_ok());
     }
+
+    #[test]
+    fn test_conversion_is_lossless() {
+        let mechanisms = [
+            AuthMechanism::Plain,
+            AuthMechanism::Login,
+            AuthMechanism::OAuthBearer,
+            AuthMechanism::XOAuth2,
+            AuthMechanism::ScramSha1,
+            AuthMechanism::ScramSha1Plus,
+            AuthMechanism::ScramSha256,
+            AuthMechanism::ScramSha256Plus,
+            AuthMechanism::ScramSha3_512,
+            AuthMechanism::ScramSha3_512Plus,
+        ];
+
+        for mechanism in mechanisms {
+            let string = mechanism.to_string();
+            assert_eq!(string.parse::<AuthMechanism>().unwrap(), mechanism);
+            assert_eq!(
+                AuthMechanism::try_from(string.to_ascii_lowercase()).unwrap(),
+                mechanism
+            );
+        }
+
+        // Unknown mechanisms are kept as-is.
+        let mechanism = AuthMechanism::try_from("GSSAPI").unwrap();
+        assert_eq!(
+            mechanism.to_string().parse::<AuthMechanism>().unwrap(),
+            mechanism
+        );
+    }
+
+    #[test]
+    fn test_plain_credentials() {
+        let tests = [
+            (None, "alice", "pass", b"\x00alice\x00pass".as_ref()),
+            (Some("admin"), "alice", "pass", b"admin\x00alice\x00pass"),
+            (Some(""), "alice", "pass", b"\x00alice\x00pass"),
+            (
+                None,
+                "\u{00e4}lice",
+                "p\u{00e4}ss",
+                "\x00\u{00e4}lice\x00p\u{00e4}ss".as_bytes(),
+            ),
+        ];
+
+        for (authzid, authcid, password, expected) in tests {
+            let credentials = PlainCredentials::new(authzid, authcid, password).unwrap();
+            assert_eq!(credentials.to_bytes().declassify(), expected);
+
+            let parsed = PlainCredentials::try_from(expected).unwrap();
+            assert_eq!(parsed, credentials);
+
+            let data = AuthenticateData::from(&credentials);
+            assert_eq!(PlainCredentials::try_from(&data).unwrap(), credentials);
+        }
+    }
+
+    #[test]
+    fn test_plain_credentials_invalid() {
+        assert!(PlainCredentials::new(None::<&str>, "", "pass").is_err());
+        assert!(PlainCredentials::new(None::<&str>, "alice", "").is_err());
+        assert!(PlainCredentials::new(None::<&str>, "al\x00ice", "pass").is_err());
+        assert!(PlainCredentials::new(Some("ad\x00min"), "alice", "pass").is_err());
+
+        let tests = [
+            b"".as_ref(),
+            b"alice",
+            b"\x00alice",
+            b"\x00alice\x00",
+            b"\x00\x00pass",
+            b"\x00alice\x00pa\x00ss",
+            b"\x00alice\x00\xff",
+        ];
+
+        for test in tests {
+            assert!(PlainCredentials::try_from(test).is_err());
+        }
+
+        assert!(PlainCredentials::try_from(&AuthenticateData::Cancel).is_err());
+    }
+
+    #[test]
+    fn test_xoauth2_credentials() {
+        let credentials = XOAuth2Credentials::new(
+            "someuser@example.com",
+            "ya29.vF9dft4qmTc2Nvb3RlckBhdHRhdmlzdGEuY29tCg",
+        )
+        .unwrap();
+        let expected = b"user=someuser@example.com\x01auth=Bearer ya29.vF9dft4qmTc2Nvb3RlckBhdHRhdmlzdGEuY29tCg\x01\x01";
+
+        assert_eq!(credentials.to_bytes().declassify(), expected);
+        assert_eq!(
+            XOAuth2Credentials::try_from(&expected[..]).unwrap(),
+            credentials
+        );
+
+        let data = AuthenticateData::from(&credentials);
+        assert_eq!(XOAuth2Credentials::try_from(&data).unwrap(), credentials);
+
+        assert!(XOAuth2Credentials::new("", "token").is_err());
+        assert!(XOAuth2Credentials::new("user", "to\x01ken").is_err());
+
+        let tests = [
+            b"".as_ref(),
+            b"user=alice\x01auth=Bearer token\x01",
+            b"user=alice\x01auth=token\x01\x01",
+            b"alice\x01auth=Bearer token\x01\x01",
+            b"user=alice\x01auth=Bearer \x01\x01",
+        ];
+
+        for test in tests {
+            assert!(XOAuth2Credentials::try_from(test).is_err());
+        }
+    }
+
+    #[test]
+    fn test_xoauth2_error_response() {
+        let tests = [
+            (
+                br#"{"status":"401","schemes":"Bearer","scope":"https://mail.google.com/"}"#
+                    .as_ref(),
+                XOAuth2ErrorResponse {
+                    status: "401".into(),
+                    schemes: Some("Bearer".into()),
+                    scope: Some("https://mail.google.com/".into()),
+                },
+            ),
+            (
+                br#" { "status" : 400 , "other" : "x\"y" } "#,
+                XOAuth2ErrorResponse {
+                    status: "400".into(),
+                    schemes: None,
+                    scope: None,
+                },
+            ),
+            (
+                br#"{"scope":"a \"b\"","status":"invalid_token"}"#,
+                XOAuth2ErrorResponse {
+                    status: "invalid_token".into(),
+                    schemes: None,
+                    scope: Some("a \"b\"".into()),
+                },
+            ),
+        ];
+
+        for (test, expected) in tests {
+            let got = XOAuth2ErrorResponse::try_from(test).unwrap();
+            assert_eq!(got, expected);
+
+            let encoded = got.to_bytes();
+            assert_eq!(
+                XOAuth2ErrorResponse::try_from(encoded.as_slice()).unwrap(),
+                expected
+            );
+        }
+
+        let tests = [
+            b"".as_ref(),
+            b"{}",
+            br#"{"schemes":"Bearer"}"#,
+            br#"{"status":"401""#,
+            br#"{"status" "401"}"#,
+            br#"{"status":"401" "scope":"x"}"#,
+        ];
+
+        for test in tests {
+            assert!(XOAuth2ErrorResponse::try_from(test).is_err());
+        }
+    }
+
+    #[test]
+    fn test_oauthbearer_credentials() {
+        let tests = [
+            (
+                OAuthBearerCredentials::new(
+                    Some("user@example.com"),
+                    Some("server.example.com"),
+                    Some(143),
+                    "vF9dft4qmTc2Nvb3RlckBhbHRhdmlzdGEuY29tCg==",
+                )
+                .unwrap(),
+                b"n,a=user@example.com,\x01host=server.example.com\x01port=143\x01auth=Bearer vF9dft4qmTc2Nvb3RlckBhbHRhdmlzdGEuY29tCg==\x01\x01".as_ref(),
+            ),
+            (
+                OAuthBearerCredentials::new(None::<&str>, None::<&str>, None, "token").unwrap(),
+                b"n,,\x01auth=Bearer token\x01\x01",
+            ),
+            (
+                OAuthBearerCredentials::new(Some("a,b=c"), None::<&str>, None, "token").unwrap(),
+                b"n,a=a=2Cb=3Dc,\x01auth=Bearer token\x01\x01",
+            ),
+        ];
+
+        for (credentials, expected) in tests {
+            assert_eq!(credentials.to_bytes().declassify(), expected);
+            assert_eq!(
+                OAuthBearerCredentials::try_from(expected).unwrap(),
+                credentials
+            );
+
+            let data = AuthenticateData::from(&credentials);
+            assert_eq!(
+                OAuthBearerCredentials::try_from(&data).unwrap(),
+                credentials
+            );
+        }
+
+        let tests = [
+            b"".as_ref(),
+            b"\x01",
+            b"n,,\x01host=example.com\x01\x01",
+            b"n,,\x01auth=token\x01\x01",
+            b"y,,\x01auth=Bearer token\x01\x01",
+            b"n,a=a=2Xb,\x01auth=Bearer token\x01\x01",
+            b"n,,\x01port=abc\x01auth=Bearer token\x01\x01",
+            b"n,,\x01auth=Bearer token\x01",
+        ];
+
+        for test in tests {
+            assert!(OAuthBearerCredentials::try_from(test).is_err());
+        }
+    }
+
+    #[test]
+    fn test_oauthbearer_error_response() {
+        let test = br#"{"status":"invalid_token","scope":"example_scope","openid-configuration":"https://example.com/.well-known/openid-configuration"}"#;
+        let expected = OAuthBearerErrorResponse {
+            status: "invalid_token".into(),
+            scope: Some("example_scope".into()),
+            openid_configuration: Some(
+                "https://example.com/.well-known/openid-configuration".into(),
+            ),
+        };
+
+        let got = OAuthBearerErrorResponse::try_from(test.as_slice()).unwrap();
+        assert_eq!(got, expected);
+        assert_eq!(got.to_bytes(), test);
+
+        assert!(OAuthBearerErrorResponse::try_from(br#"{"scope":"x"}"#.as_slice()).is_err());
+    }
+
+    #[cfg(feature = "cram_md5")]
+    #[test]
+    fn test_cram_md5() {
+        // Example from RFC 2195.
+        let challenge = CommandContinuationRequest::base64(
+            b"<1896.697170952@postoffice.reston.mci.net>".as_ref(),
+        );
+
+        let got = cram_md5(&challenge, "tim", "tanstaaftanstaaf").unwrap();
+        assert_eq!(
+            got,
+            AuthenticateData::r#continue(b"tim b913a602c7eda7a495b4e6e7334d3890".as_ref())
+        );
+
+        // Keys longer than the block size are hashed first (RFC 2202, test case 6).
+        assert_eq!(
+            hmac_md5(
+                &[0xaa; 80],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            ),
+            [
+                0x6b, 0x1a, 0xb7, 0xfe, 0x4b, 0xd7, 0xbf, 0x8f, 0x0b, 0x62, 0xe6, 0xce, 0x61, 0xb9,
+                0xd0, 0xcd
+            ]
+        );
+
+        let basic = CommandContinuationRequest::basic(None, "Ready").unwrap();
+        assert!(cram_md5(&basic, "tim", "tanstaaftanstaaf").is_err());
+    }
 }
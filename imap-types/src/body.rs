@@ -1,19 +1,26 @@
 //! Body(structure)-related types.
 
+use alloc::{boxed::Box, vec::Vec};
+
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    core::{IString, NString, Vec1},
+    body::error::BodyStructureError,
+    core::{IString, NString, Quoted, Vec1},
     envelope::Envelope,
+    error::ValidationError,
 };
 
 /// Inner part of [`BodyStructure`].
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct Body<'a> {
     /// Basic fields
@@ -25,6 +32,7 @@ pub struct Body<'a> {
 /// Basic fields of a non-multipart body part.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct BasicFields<'a> {
     /// List of attribute/value pairs ([MIME-IMB].)
@@ -49,6 +57,7 @@ pub struct BasicFields<'a> {
 /// Specific fields of a non-multipart body part.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum SpecificFields<'a> {
     /// # Example (not in RFC)
@@ -166,6 +175,7 @@ pub enum SpecificFields<'a> {
 
 /// The BODY(STRUCTURE).
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum BodyStructure<'a> {
     /// For example, a simple text message of 48 lines and 2279 octets
@@ -246,9 +256,361 @@ pub enum BodyStructure<'a> {
     },
 }
 
+impl<'a> BodyStructure<'a> {
+    /// Start building a `text/<subtype>` body part.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imap_types::body::BodyStructure;
+    ///
+    /// let body_structure = BodyStructure::text("plain")
+    ///     .charset("utf-8")
+    ///     .size(2279)
+    ///     .lines(48)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn text<S>(subtype: S) -> SinglePartBuilder<'a>
+    where
+        S: TryInto<IString<'a>, Error = ValidationError>,
+    {
+        let mut builder = SinglePartBuilder::new();
+        builder.specific = builder.check(subtype).map(|subtype| SpecificFields::Text {
+            subtype,
+            number_of_lines: 0,
+        });
+        builder
+    }
+
+    /// Start building a `<type>/<subtype>` body part, e.g., `application/pdf`.
+    ///
+    /// Use [`BodyStructure::text`] and [`BodyStructure::message`] for `text/*` and
+    /// `message/rfc822` body parts. These are represented differently in IMAP and building them
+    /// here fails with [`BodyStructureError::ReservedType`].
+    pub fn basic<T, S>(r#type: T, subtype: S) -> SinglePartBuilder<'a>
+    where
+        T: TryInto<IString<'a>, Error = ValidationError>,
+        S: TryInto<IString<'a>, Error = ValidationError>,
+    {
+        let mut builder = SinglePartBuilder::new();
+        let r#type = builder.check(r#type);
+        let subtype = builder.check(subtype);
+
+        if let (Some(r#type), Some(subtype)) = (r#type, subtype) {
+            let is_text = r#type.as_ref().eq_ignore_ascii_case(b"text");
+            let is_message = r#type.as_ref().eq_ignore_ascii_case(b"message")
+                && subtype.as_ref().eq_ignore_ascii_case(b"rfc822");
+
+            if is_text || is_message {
+                builder.fail(BodyStructureError::ReservedType);
+            } else {
+                builder.specific = Some(SpecificFields::Basic { r#type, subtype });
+            }
+        }
+
+        builder
+    }
+
+    /// Start building a `message/rfc822` body part that encapsulates another message.
+    pub fn message(
+        envelope: Envelope<'a>,
+        body_structure: BodyStructure<'a>,
+    ) -> SinglePartBuilder<'a> {
+        let mut builder = SinglePartBuilder::new();
+        builder.specific = Some(SpecificFields::Message {
+            envelope: Box::new(envelope),
+            body_structure: Box::new(body_structure),
+            number_of_lines: 0,
+        });
+        builder
+    }
+
+    /// Start building a `multipart/<subtype>` body part, e.g., `multipart/mixed`.
+    ///
+    /// Building fails with [`BodyStructureError::EmptyMultipart`] when `parts` is empty.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imap_types::body::BodyStructure;
+    ///
+    /// let body_structure = BodyStructure::multipart(
+    ///     "mixed",
+    ///     [
+    ///         BodyStructure::text("plain").size(11).build().unwrap(),
+    ///         BodyStructure::basic("application", "pdf")
+    ///             .encoding("base64")
+    ///             .size(4554)
+    ///             .build()
+    ///             .unwrap(),
+    ///     ],
+    /// )
+    /// .boundary("xxx")
+    /// .build()
+    /// .unwrap();
+    /// ```
+    pub fn multipart<S, P>(subtype: S, parts: P) -> MultiPartBuilder<'a>
+    where
+        S: TryInto<IString<'a>, Error = ValidationError>,
+        P: IntoIterator<Item = BodyStructure<'a>>,
+    {
+        let (subtype, error) = match subtype.try_into() {
+            Ok(subtype) => (Some(subtype), None),
+            Err(error) => (None, Some(BodyStructureError::Invalid(error))),
+        };
+
+        MultiPartBuilder {
+            bodies: parts.into_iter().collect(),
+            subtype,
+            parameter_list: Vec::new(),
+            tail: None,
+            error,
+        }
+    }
+}
+
+/// Builder for a non-multipart [`BodyStructure`].
+///
+/// Created by [`BodyStructure::text`], [`BodyStructure::basic`], and [`BodyStructure::message`].
+/// Unset fields default to `NIL`, a size of `0`, and the `7bit` content transfer encoding. Errors
+/// are reported by [`SinglePartBuilder::build`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SinglePartBuilder<'a> {
+    specific: Option<SpecificFields<'a>>,
+    parameter_list: Vec<(IString<'a>, IString<'a>)>,
+    id: NString<'a>,
+    description: NString<'a>,
+    content_transfer_encoding: Option<IString<'a>>,
+    size: u32,
+    number_of_lines: u32,
+    extension_data: Option<SinglePartExtensionData<'a>>,
+    error: Option<BodyStructureError>,
+}
+
+impl<'a> SinglePartBuilder<'a> {
+    fn new() -> Self {
+        Self {
+            specific: None,
+            parameter_list: Vec::new(),
+            id: NString::NIL,
+            description: NString::NIL,
+            content_transfer_encoding: None,
+            size: 0,
+            number_of_lines: 0,
+            extension_data: None,
+            error: None,
+        }
+    }
+
+    fn fail(&mut self, error: BodyStructureError) {
+        self.error.get_or_insert(error);
+    }
+
+    fn check<T, V>(&mut self, value: V) -> Option<T>
+    where
+        V: TryInto<T, Error = ValidationError>,
+    {
+        match value.try_into() {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.fail(BodyStructureError::Invalid(error));
+                None
+            }
+        }
+    }
+
+    /// Add a body parameter, e.g., `("name", "cc.diff")`.
+    pub fn parameter<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: TryInto<IString<'a>, Error = ValidationError>,
+        V: TryInto<IString<'a>, Error = ValidationError>,
+    {
+        if let (Some(key), Some(value)) = (self.check(key), self.check(value)) {
+            self.parameter_list.push((key, value));
+        }
+        self
+    }
+
+    /// Add the `charset` body parameter.
+    pub fn charset<V>(self, charset: V) -> Self
+    where
+        V: TryInto<IString<'a>, Error = ValidationError>,
+    {
+        self.parameter("charset", charset)
+    }
+
+    /// Set the content id.
+    pub fn id<V>(mut self, id: V) -> Self
+    where
+        V: TryInto<NString<'a>, Error = ValidationError>,
+    {
+        if let Some(id) = self.check(id) {
+            self.id = id;
+        }
+        self
+    }
+
+    /// Set the content description.
+    pub fn description<V>(mut self, description: V) -> Self
+    where
+        V: TryInto<NString<'a>, Error = ValidationError>,
+    {
+        if let Some(description) = self.check(description) {
+            self.description = description;
+        }
+        self
+    }
+
+    /// Set the content transfer encoding, e.g., `base64`.
+    pub fn encoding<V>(mut self, encoding: V) -> Self
+    where
+        V: TryInto<IString<'a>, Error = ValidationError>,
+    {
+        if let Some(encoding) = self.check(encoding) {
+            self.content_transfer_encoding = Some(encoding);
+        }
+        self
+    }
+
+    /// Set the size of the body (in its transfer encoding) in octets.
+    pub fn size(mut self, size: u32) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Set the size of the body in text lines.
+    ///
+    /// Note: Only `text/*` and `message/rfc822` body parts have a number of lines. It's ignored
+    /// otherwise.
+    pub fn lines(mut self, number_of_lines: u32) -> Self {
+        self.number_of_lines = number_of_lines;
+        self
+    }
+
+    /// Set the extension data (only returned with a BODYSTRUCTURE fetch).
+    pub fn extension_data(mut self, extension_data: SinglePartExtensionData<'a>) -> Self {
+        self.extension_data = Some(extension_data);
+        self
+    }
+
+    /// Build the body structure or return the first error.
+    pub fn build(self) -> Result<BodyStructure<'a>, BodyStructureError> {
+        let mut specific = match (self.specific, self.error) {
+            (_, Some(error)) => return Err(error),
+            (Some(specific), None) => specific,
+            // Note: `specific` is only missing after an error.
+            (None, None) => unreachable!(),
+        };
+
+        match specific {
+            SpecificFields::Basic { .. } => {}
+            SpecificFields::Message {
+                ref mut number_of_lines,
+                ..
+            }
+            | SpecificFields::Text {
+                ref mut number_of_lines,
+                ..
+            } => *number_of_lines = self.number_of_lines,
+        }
+
+        let content_transfer_encoding = self
+            .content_transfer_encoding
+            .unwrap_or_else(|| IString::from(Quoted::unvalidated("7bit")));
+
+        Ok(BodyStructure::Single {
+            body: Body {
+                basic: BasicFields {
+                    parameter_list: self.parameter_list,
+                    id: self.id,
+                    description: self.description,
+                    content_transfer_encoding,
+                    size: self.size,
+                },
+                specific,
+            },
+            extension_data: self.extension_data,
+        })
+    }
+}
+
+/// Builder for a multipart [`BodyStructure`].
+///
+/// Created by [`BodyStructure::multipart`]. Errors are reported by [`MultiPartBuilder::build`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiPartBuilder<'a> {
+    bodies: Vec<BodyStructure<'a>>,
+    subtype: Option<IString<'a>>,
+    parameter_list: Vec<(IString<'a>, IString<'a>)>,
+    tail: Option<Disposition<'a>>,
+    error: Option<BodyStructureError>,
+}
+
+impl<'a> MultiPartBuilder<'a> {
+    /// Add a body parameter, e.g., `("boundary", "xxx")`.
+    ///
+    /// Note: Body parameters of multipart body parts are extension data.
+    pub fn parameter<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: TryInto<IString<'a>, Error = ValidationError>,
+        V: TryInto<IString<'a>, Error = ValidationError>,
+    {
+        match (key.try_into(), value.try_into()) {
+            (Ok(key), Ok(value)) => self.parameter_list.push((key, value)),
+            (Err(error), _) | (_, Err(error)) => {
+                self.error.get_or_insert(BodyStructureError::Invalid(error));
+            }
+        }
+        self
+    }
+
+    /// Add the `boundary` body parameter.
+    pub fn boundary<V>(self, boundary: V) -> Self
+    where
+        V: TryInto<IString<'a>, Error = ValidationError>,
+    {
+        self.parameter("boundary", boundary)
+    }
+
+    /// Set the remaining extension data, i.e., disposition, language, and location.
+    pub fn disposition(mut self, disposition: Disposition<'a>) -> Self {
+        self.tail = Some(disposition);
+        self
+    }
+
+    /// Build the body structure or return the first error.
+    pub fn build(self) -> Result<BodyStructure<'a>, BodyStructureError> {
+        let subtype = match (self.subtype, self.error) {
+            (_, Some(error)) => return Err(error),
+            (Some(subtype), None) => subtype,
+            // Note: `subtype` is only missing after an error.
+            (None, None) => unreachable!(),
+        };
+
+        let bodies = Vec1::try_from(self.bodies).map_err(|_| BodyStructureError::EmptyMultipart)?;
+
+        let extension_data = if self.parameter_list.is_empty() && self.tail.is_none() {
+            None
+        } else {
+            Some(MultiPartExtensionData {
+                parameter_list: self.parameter_list,
+                tail: self.tail,
+            })
+        };
+
+        Ok(BodyStructure::Multi {
+            bodies,
+            subtype,
+            extension_data,
+        })
+    }
+}
+
 /// The extension data of a non-multipart body part.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct SinglePartExtensionData<'a> {
     /// A string giving the body MD5 value as defined in \[MD5\].
@@ -274,6 +636,7 @@ pub struct SinglePartExtensionData<'a> {
 /// ```
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct MultiPartExtensionData<'a> {
     /// A parenthesized list of attribute/value pairs [e.g., ("foo"
@@ -288,6 +651,7 @@ pub struct MultiPartExtensionData<'a> {
 /// Helper to enforce correct usage of [`SinglePartExtensionData`] and [`MultiPartExtensionData`].
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct Disposition<'a> {
     /// A parenthesized list, consisting of a disposition type
@@ -302,6 +666,7 @@ pub struct Disposition<'a> {
 /// Helper to enforce correct usage of [`SinglePartExtensionData`] and [`MultiPartExtensionData`].
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct Language<'a> {
     /// A string or parenthesized list giving the body language
@@ -315,6 +680,7 @@ pub struct Language<'a> {
 /// Helper to enforce correct usage of [`SinglePartExtensionData`] and [`MultiPartExtensionData`].
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct Location<'a> {
     /// A string list giving the body content URI as defined in \[LOCATION\].
@@ -326,6 +692,7 @@ pub struct Location<'a> {
 
 /// Helper to enforce correct usage of [`SinglePartExtensionData`] and [`MultiPartExtensionData`].
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum BodyExtension<'a> {
     /// NString.
@@ -335,3 +702,108 @@ pub enum BodyExtension<'a> {
     /// List.
     List(Vec1<BodyExtension<'a>>),
 }
+
+/// Error-related types.
+pub mod error {
+    use thiserror::Error;
+
+    use crate::error::ValidationError;
+
+    #[derive(Clone, Debug, Eq, Error, Hash, Ord, PartialEq, PartialOrd)]
+    pub enum BodyStructureError {
+        #[error("Invalid value: {0}")]
+        Invalid(ValidationError),
+        #[error("Multipart body part without parts")]
+        EmptyMultipart,
+        #[error("`text/*` and `message/rfc822` require `BodyStructure::text` or `BodyStructure::message`")]
+        ReservedType,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_single_part() {
+        let got = BodyStructure::text("plain")
+            .charset("us-ascii")
+            .size(2279)
+            .lines(48)
+            .build()
+            .unwrap();
+
+        let expected = BodyStructure::Single {
+            body: Body {
+                basic: BasicFields {
+                    parameter_list: vec![(
+                        IString::try_from("charset").unwrap(),
+                        IString::try_from("us-ascii").unwrap(),
+                    )],
+                    id: NString::NIL,
+                    description: NString::NIL,
+                    content_transfer_encoding: IString::try_from("7bit").unwrap(),
+                    size: 2279,
+                },
+                specific: SpecificFields::Text {
+                    subtype: IString::try_from("plain").unwrap(),
+                    number_of_lines: 48,
+                },
+            },
+            extension_data: None,
+        };
+
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn test_build_multi_part() {
+        let text = BodyStructure::text("plain").build().unwrap();
+        let pdf = BodyStructure::basic("application", "pdf")
+            .encoding("base64")
+            .build()
+            .unwrap();
+
+        let got = BodyStructure::multipart("mixed", [text.clone(), pdf.clone()])
+            .boundary("xxx")
+            .build()
+            .unwrap();
+
+        let expected = BodyStructure::Multi {
+            bodies: Vec1::try_from(vec![text, pdf]).unwrap(),
+            subtype: IString::try_from("mixed").unwrap(),
+            extension_data: Some(MultiPartExtensionData {
+                parameter_list: vec![(
+                    IString::try_from("boundary").unwrap(),
+                    IString::try_from("xxx").unwrap(),
+                )],
+                tail: None,
+            }),
+        };
+
+        assert_eq!(got, expected);
+    }
+
+    #[test]
+    fn test_build_errors() {
+        assert_eq!(
+            BodyStructure::multipart("mixed", []).build(),
+            Err(BodyStructureError::EmptyMultipart)
+        );
+        assert_eq!(
+            BodyStructure::basic("TEXT", "plain").build(),
+            Err(BodyStructureError::ReservedType)
+        );
+        assert_eq!(
+            BodyStructure::basic("message", "RFC822").build(),
+            Err(BodyStructureError::ReservedType)
+        );
+        assert!(BodyStructure::basic("message", "delivery-status")
+            .build()
+            .is_ok());
+        assert!(matches!(
+            BodyStructure::text("plain").charset("\x00").build(),
+            Err(BodyStructureError::Invalid(_))
+        ));
+    }
+}
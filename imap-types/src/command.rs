@@ -2,43 +2,59 @@
 //!
 //! See <https://tools.ietf.org/html/rfc3501#section-6>.
 
-use std::borrow::Cow;
+use alloc::{borrow::Cow, string::ToString, vec::Vec};
+use core::fmt::{Debug, Display, Formatter};
 #[cfg(feature = "ext_condstore_qresync")]
-use std::num::NonZeroU32;
-#[cfg(feature = "ext_condstore_qresync")]
-use std::num::NonZeroU64;
+use core::num::NonZeroU32;
 
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "ext_id")]
-use crate::core::{IString, NString};
+use crate::core::NString;
+#[cfg(feature = "ext_condstore_qresync")]
+use crate::extensions::condstore_qresync::ModSeq;
 #[cfg(feature = "ext_metadata")]
 use crate::extensions::metadata::{Entry, EntryValue, GetMetadataOption};
 use crate::{
     auth::AuthMechanism,
-    command::error::{AppendError, CopyError, ListError, LoginError, RenameError},
-    core::{AString, Charset, Literal, Tag, Vec1},
+    command::error::{
+        AppendError, CopyError, ListError, LoginError, RenameError, StoreError, UnsupportedError,
+    },
+    core::{AString, Charset, IString, Literal, LiteralMode, Tag, Vec1},
     datetime::DateTime,
+    error::{ValidationError, ValidationErrorKind},
     extensions::{
-        binary::LiteralOrLiteral8, compress::CompressionAlgorithm, enable::CapabilityEnable,
-        quota::QuotaSet, sort::SortCriterion, thread::ThreadingAlgorithm,
+        binary::LiteralOrLiteral8,
+        compress::CompressionAlgorithm,
+        enable::CapabilityEnable,
+        quota::QuotaSet,
+        sort::{SortCriterion, SortReturnOption},
+        tagged_ext::ExtensionItem,
+        thread::ThreadingAlgorithm,
     },
-    fetch::MacroOrMessageDataItemNames,
+    fetch::{MacroOrMessageDataItemNames, MessageDataItemName, Section},
     flag::{Flag, StoreResponse, StoreType},
-    mailbox::{ListMailbox, Mailbox},
+    mailbox::{fmt_list_mailbox, fmt_mailbox, ListMailbox, Mailbox},
+    response::{Capability, CapabilitySet},
     search::SearchKey,
-    secret::Secret,
+    secret::{redaction_policy, Redacted, Secret},
     sequence::SequenceSet,
+    state::State,
     status::StatusDataItemName,
 };
+#[cfg(feature = "ext_xapplepushservice")]
+use crate::{core::Atom, extensions::xapplepushservice::ApplePushValue};
 
 /// Command.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct Command<'a> {
     /// Tag.
@@ -63,6 +79,429 @@ impl<'a> Command<'a> {
     pub fn name(&self) -> &'static str {
         self.body.name()
     }
+
+    /// Check whether the command can be sent to a server advertising `capabilities`.
+    ///
+    /// Besides the command itself, this inspects the payload:
+    ///
+    /// * non-synchronizing literals require `LITERAL+` (or `LITERAL-` for up to 4096 bytes),
+    /// * quoted strings with 8-bit characters require `UTF8=ACCEPT`,
+    /// * literal8s and BINARY fetch attributes require `BINARY`, and
+    /// * an initial response requires `SASL-IR`.
+    ///
+    /// Note: Capabilities that must be enabled first (see ENABLE) are only checked for being
+    /// advertised.
+    ///
+    /// ```rust
+    /// use imap_types::{
+    ///     command::{error::UnsupportedError, Command, CommandBody},
+    ///     response::{Capability, CapabilitySet},
+    /// };
+    ///
+    /// let capabilities = CapabilitySet::from(vec![Capability::Imap4Rev1]);
+    ///
+    /// let command = Command::new("A", CommandBody::Idle).unwrap();
+    /// assert_eq!(
+    ///     command.validate(&capabilities),
+    ///     Err(UnsupportedError::MissingCapability("IDLE".into()))
+    /// );
+    /// ```
+    pub fn validate(&self, capabilities: &CapabilitySet) -> Result<(), UnsupportedError> {
+        Validator(capabilities).command_body(&self.body)
+    }
+}
+
+/// One-line summary, e.g., `A1 LOGIN <redacted> <redacted>`.
+///
+/// Meant for log files and tracing spans. Secrets and usernames are always redacted, mailboxes
+/// according to the [`RedactionPolicy`](crate::secret::RedactionPolicy). Unlike the wire form,
+/// literals and most arguments are omitted.
+///
+/// ```rust
+/// use imap_types::command::{Command, CommandBody};
+///
+/// let command = Command::new("A1", CommandBody::login("alice", "password").unwrap()).unwrap();
+/// assert_eq!(command.to_string(), "A1 LOGIN <redacted> <redacted>");
+///
+/// let command = Command::new("A2", CommandBody::select("Sent").unwrap()).unwrap();
+/// assert_eq!(command.to_string(), r#"A2 SELECT "Sent""#);
+/// ```
+impl Display for Command<'_> {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        write!(f, "{} ", self.tag.as_ref())?;
+
+        let uid = match &self.body {
+            CommandBody::ExpungeUid { .. } => true,
+            CommandBody::Search { uid, .. }
+            | CommandBody::Sort { uid, .. }
+            | CommandBody::Thread { uid, .. }
+            | CommandBody::Fetch { uid, .. }
+            | CommandBody::Store { uid, .. }
+            | CommandBody::Copy { uid, .. }
+            | CommandBody::Move { uid, .. } => *uid,
+            _ => false,
+        };
+
+        if uid {
+            f.write_str("UID ")?;
+        }
+
+        f.write_str(self.body.name())?;
+
+        match &self.body {
+            CommandBody::Authenticate {
+                mechanism,
+                initial_response,
+            } => {
+                write!(f, " {mechanism}")?;
+
+                if initial_response.is_some() {
+                    f.write_str(" <redacted>")?;
+                }
+
+                Ok(())
+            }
+            CommandBody::Login { .. } => f.write_str(" <redacted> <redacted>"),
+            CommandBody::Select { mailbox, .. }
+            | CommandBody::Examine { mailbox, .. }
+            | CommandBody::Create { mailbox }
+            | CommandBody::Delete { mailbox }
+            | CommandBody::Subscribe { mailbox }
+            | CommandBody::Unsubscribe { mailbox }
+            | CommandBody::Status { mailbox, .. }
+            | CommandBody::Copy { mailbox, .. }
+            | CommandBody::Move { mailbox, .. }
+            | CommandBody::GetQuotaRoot { mailbox } => {
+                f.write_str(" ")?;
+                fmt_mailbox(f, mailbox)
+            }
+            CommandBody::Rename { from, to } => {
+                f.write_str(" ")?;
+                fmt_mailbox(f, from)?;
+                f.write_str(" ")?;
+                fmt_mailbox(f, to)
+            }
+            CommandBody::List {
+                reference,
+                mailbox_wildcard,
+            }
+            | CommandBody::Lsub {
+                reference,
+                mailbox_wildcard,
+            } => {
+                f.write_str(" ")?;
+                fmt_mailbox(f, reference)?;
+                f.write_str(" ")?;
+                fmt_list_mailbox(f, mailbox_wildcard)
+            }
+            CommandBody::Append {
+                mailbox, message, ..
+            } => {
+                f.write_str(" ")?;
+                fmt_mailbox(f, mailbox)?;
+                write!(f, " ({} bytes)", message.data().len())
+            }
+            CommandBody::Enable { capabilities } => {
+                for capability in capabilities.as_ref() {
+                    write!(f, " {capability}")?;
+                }
+
+                Ok(())
+            }
+            CommandBody::Compress { algorithm } => write!(f, " {algorithm}"),
+            _ => Ok(()),
+        }
+    }
+}
+
+struct Validator<'c, 'a>(&'c CapabilitySet<'a>);
+
+impl Validator<'_, '_> {
+    fn require(&self, capability: Capability) -> Result<(), UnsupportedError> {
+        if self.0.has(&capability) {
+            Ok(())
+        } else {
+            Err(UnsupportedError::MissingCapability(capability.to_string()))
+        }
+    }
+
+    fn require_if(&self, supported: bool, capability: &str) -> Result<(), UnsupportedError> {
+        if supported {
+            Ok(())
+        } else {
+            Err(UnsupportedError::MissingCapability(capability.to_string()))
+        }
+    }
+
+    fn command_body(&self, body: &CommandBody) -> Result<(), UnsupportedError> {
+        match body {
+            CommandBody::Capability
+            | CommandBody::Noop
+            | CommandBody::Logout
+            | CommandBody::Check
+            | CommandBody::Close
+            | CommandBody::Expunge => Ok(()),
+            #[cfg(feature = "starttls")]
+            CommandBody::StartTLS => self.require(Capability::StartTls),
+            CommandBody::Authenticate {
+                mechanism,
+                initial_response,
+            } => {
+                self.require(Capability::Auth(mechanism.clone()))?;
+                self.require_if(
+                    initial_response.is_none() || self.0.supports_sasl_ir(),
+                    "SASL-IR",
+                )
+            }
+            CommandBody::Login { username, password } => {
+                if self.0.login_disabled() {
+                    return Err(UnsupportedError::LoginDisabled);
+                }
+
+                self.astring(username)?;
+                self.astring(password.declassify())
+            }
+            CommandBody::Select {
+                mailbox,
+                parameters,
+            }
+            | CommandBody::Examine {
+                mailbox,
+                parameters,
+            } => {
+                self.select_parameters(parameters)?;
+                self.mailbox(mailbox)
+            }
+            CommandBody::Unselect => self.require(Capability::Unselect),
+            CommandBody::Create { mailbox }
+            | CommandBody::Delete { mailbox }
+            | CommandBody::Subscribe { mailbox }
+            | CommandBody::Unsubscribe { mailbox }
+            | CommandBody::Status { mailbox, .. }
+            | CommandBody::Copy { mailbox, .. } => self.mailbox(mailbox),
+            CommandBody::Rename { from, to } => {
+                self.mailbox(from)?;
+                self.mailbox(to)
+            }
+            CommandBody::List {
+                reference,
+                mailbox_wildcard,
+            }
+            | CommandBody::Lsub {
+                reference,
+                mailbox_wildcard,
+            } => {
+                self.mailbox(reference)?;
+                self.list_mailbox(mailbox_wildcard)
+            }
+            CommandBody::Append {
+                mailbox, message, ..
+            } => {
+                self.mailbox(mailbox)?;
+
+                match message {
+                    LiteralOrLiteral8::Literal(literal) => {
+                        self.literal(literal.mode(), literal.data().len())
+                    }
+                    LiteralOrLiteral8::Literal8(literal8) => {
+                        self.require(Capability::Binary)?;
+                        self.literal(literal8.mode(), literal8.data().len())
+                    }
+                }
+            }
+            CommandBody::ExpungeUid { .. } => self.require(Capability::UidPlus),
+            CommandBody::Search { criteria, .. } => self.search_keys(criteria.as_ref()),
+            CommandBody::Sort {
+                search_criteria, ..
+            } => {
+                self.require_if(
+                    self.0
+                        .iter()
+                        .any(|capability| matches!(capability, Capability::Sort(_))),
+                    "SORT",
+                )?;
+                self.search_keys(search_criteria.as_ref())
+            }
+            CommandBody::Thread {
+                algorithm,
+                search_criteria,
+                ..
+            } => {
+                self.require(Capability::Thread(algorithm.clone()))?;
+                self.search_keys(search_criteria.as_ref())
+            }
+            CommandBody::Fetch {
+                macro_or_item_names,
+                ..
+            } => match macro_or_item_names {
+                MacroOrMessageDataItemNames::Macro(_) => Ok(()),
+                MacroOrMessageDataItemNames::MessageDataItemNames(item_names) => {
+                    for item_name in item_names {
+                        match item_name {
+                            MessageDataItemName::Binary { .. }
+                            | MessageDataItemName::BinarySize { .. } => {
+                                self.require(Capability::Binary)?
+                            }
+                            MessageDataItemName::BodyExt {
+                                section:
+                                    Some(
+                                        Section::HeaderFields(_, fields)
+                                        | Section::HeaderFieldsNot(_, fields),
+                                    ),
+                                ..
+                            } => {
+                                for field in fields.as_ref() {
+                                    self.astring(field)?;
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+
+                    Ok(())
+                }
+            },
+            CommandBody::Store { .. } => Ok(()),
+            CommandBody::Idle => self.require(Capability::Idle),
+            CommandBody::Enable { .. } => self.require(Capability::Enable),
+            CommandBody::Compress { algorithm } => self.require(Capability::Compress {
+                algorithm: algorithm.clone(),
+            }),
+            CommandBody::GetQuota { root } => {
+                self.require(Capability::Quota)?;
+                self.astring(root)
+            }
+            CommandBody::GetQuotaRoot { mailbox } => {
+                self.require(Capability::Quota)?;
+                self.mailbox(mailbox)
+            }
+            CommandBody::SetQuota { root, .. } => {
+                self.require(Capability::Quota)?;
+                self.astring(root)
+            }
+            CommandBody::Move { mailbox, .. } => {
+                self.require(Capability::Move)?;
+                self.mailbox(mailbox)
+            }
+            #[cfg(feature = "ext_id")]
+            CommandBody::Id { parameters } => {
+                self.require(Capability::Id)?;
+
+                for (key, value) in parameters.iter().flatten() {
+                    self.istring(key)?;
+
+                    if let Some(value) = &value.0 {
+                        self.istring(value)?;
+                    }
+                }
+
+                Ok(())
+            }
+            #[cfg(feature = "ext_metadata")]
+            CommandBody::SetMetadata { mailbox, .. } | CommandBody::GetMetadata { mailbox, .. } => {
+                self.require_if(
+                    self.0.has(&Capability::Metadata) || self.0.has(&Capability::MetadataServer),
+                    "METADATA",
+                )?;
+                self.mailbox(mailbox)
+            }
+            #[cfg(feature = "ext_xapplepushservice")]
+            CommandBody::XApplePushService { .. } => self.require(Capability::XApplePushService),
+            #[cfg(feature = "ext_xlist")]
+            CommandBody::XList {
+                reference,
+                mailbox_wildcard,
+            } => {
+                self.require(Capability::XList)?;
+                self.mailbox(reference)?;
+                self.list_mailbox(mailbox_wildcard)
+            }
+        }
+    }
+
+    fn select_parameters(&self, _parameters: &[SelectParameter]) -> Result<(), UnsupportedError> {
+        #[cfg(feature = "ext_condstore_qresync")]
+        for parameter in _parameters {
+            match parameter {
+                SelectParameter::CondStore => self.require(Capability::CondStore)?,
+                SelectParameter::QResync { .. } => self.require(Capability::QResync)?,
+                SelectParameter::Other(_) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn literal(&self, mode: LiteralMode, length: usize) -> Result<(), UnsupportedError> {
+        match mode {
+            LiteralMode::Sync => Ok(()),
+            LiteralMode::NonSync if self.0.supports_literal_plus() => Ok(()),
+            LiteralMode::NonSync => self.require_if(
+                length <= 4096 && self.0.supports_literal_minus(),
+                "LITERAL+",
+            ),
+        }
+    }
+
+    fn istring(&self, istring: &IString) -> Result<(), UnsupportedError> {
+        match istring {
+            IString::Literal(literal) => self.literal(literal.mode(), literal.data().len()),
+            IString::Quoted(quoted) => self.require_if(
+                quoted.inner().is_ascii() || self.0.supports_utf8_accept(),
+                "UTF8=ACCEPT",
+            ),
+        }
+    }
+
+    fn astring(&self, astring: &AString) -> Result<(), UnsupportedError> {
+        match astring {
+            AString::Atom(_) => Ok(()),
+            AString::String(istring) => self.istring(istring),
+        }
+    }
+
+    fn mailbox(&self, mailbox: &Mailbox) -> Result<(), UnsupportedError> {
+        match mailbox {
+            Mailbox::Inbox => Ok(()),
+            Mailbox::Other(other) => self.astring(&other.0),
+        }
+    }
+
+    fn list_mailbox(&self, list_mailbox: &ListMailbox) -> Result<(), UnsupportedError> {
+        match list_mailbox {
+            ListMailbox::Token(_) => Ok(()),
+            ListMailbox::String(istring) => self.istring(istring),
+        }
+    }
+
+    fn search_keys(&self, search_keys: &[SearchKey]) -> Result<(), UnsupportedError> {
+        search_keys
+            .iter()
+            .try_for_each(|search_key| self.search_key(search_key))
+    }
+
+    fn search_key(&self, search_key: &SearchKey) -> Result<(), UnsupportedError> {
+        match search_key {
+            SearchKey::And(search_keys) => self.search_keys(search_keys.as_ref()),
+            SearchKey::Not(search_key) => self.search_key(search_key),
+            SearchKey::Or(left, right) => {
+                self.search_key(left)?;
+                self.search_key(right)
+            }
+            SearchKey::Bcc(astring)
+            | SearchKey::Body(astring)
+            | SearchKey::Cc(astring)
+            | SearchKey::From(astring)
+            | SearchKey::Subject(astring)
+            | SearchKey::Text(astring)
+            | SearchKey::To(astring) => self.astring(astring),
+            SearchKey::Header(field, value) => {
+                self.astring(field)?;
+                self.astring(value)
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 /// Command body.
@@ -70,7 +509,8 @@ impl<'a> Command<'a> {
 /// This enum is used to encode all the different commands.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[derive(Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum CommandBody<'a> {
     // ----- Any State (see https://tools.ietf.org/html/rfc3501#section-6.1) -----
     /// ### 6.1.1.  CAPABILITY Command
@@ -400,8 +840,7 @@ pub enum CommandBody<'a> {
     Select {
         /// Mailbox.
         mailbox: Mailbox<'a>,
-        #[cfg(feature = "ext_condstore_qresync")]
-        parameters: Vec<SelectParameter>,
+        parameters: Vec<SelectParameter<'a>>,
     },
 
     /// Unselect a mailbox.
@@ -435,8 +874,7 @@ pub enum CommandBody<'a> {
     Examine {
         /// Mailbox.
         mailbox: Mailbox<'a>,
-        #[cfg(feature = "ext_condstore_qresync")]
-        parameters: Vec<SelectParameter>,
+        parameters: Vec<SelectParameter<'a>>,
     },
 
     /// ### 6.3.3.  CREATE Command
@@ -841,7 +1279,7 @@ pub enum CommandBody<'a> {
         /// Mailbox.
         mailbox: Mailbox<'a>,
         /// Status data items.
-        item_names: Cow<'a, [StatusDataItemName]>,
+        item_names: Vec<StatusDataItemName<'a>>,
     },
 
     /// 6.3.11. APPEND Command
@@ -1088,6 +1526,14 @@ pub enum CommandBody<'a> {
     /// This extension must only be used when the server advertised support for it sending the SORT capability.
     /// </div>
     Sort {
+        /// Return options (RFC 5267).
+        ///
+        /// When non-empty, the server answers with an ESEARCH response instead of a SORT response.
+        ///
+        /// <div class="warning">
+        /// Return options must only be used when the server advertised support for them sending the ESORT capability.
+        /// </div>
+        return_options: Vec<SortReturnOption>,
         /// Sort criteria.
         sort_criteria: Vec1<SortCriterion>,
         /// Charset.
@@ -1217,7 +1663,14 @@ pub enum CommandBody<'a> {
         /// Kind of response, i.e., answer or silent.
         response: StoreResponse,
         /// Flags.
-        flags: Vec<Flag<'a>>, // FIXME(misuse): must not accept "\*" or "\Recent"
+        ///
+        /// Note: Must not contain `\Recent`, see [`Flag::is_storable`]. Use [`CommandBody::store`]
+        /// to enforce this.
+        #[cfg_attr(
+            feature = "arbitrary",
+            arbitrary(with = crate::arbitrary::arbitrary_storable_flags)
+        )]
+        flags: Vec<Flag<'a>>,
         /// Use UID variant.
         uid: bool,
         /// --- Modifiers ---
@@ -1537,6 +1990,291 @@ pub enum CommandBody<'a> {
         mailbox: Mailbox<'a>,
         entries: Vec1<Entry<'a>>,
     },
+
+    #[cfg(feature = "ext_xapplepushservice")]
+    /// XAPPLEPUSHSERVICE command.
+    ///
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the XAPPLEPUSHSERVICE capability.
+    /// </div>
+    XApplePushService {
+        /// Parameters, e.g., `aps-version`, `aps-account-id`, `aps-device-token`, ...
+        parameters: Vec1<(Atom<'a>, ApplePushValue<'a>)>,
+    },
+
+    #[cfg(feature = "ext_xlist")]
+    /// XLIST command.
+    ///
+    /// Legacy variant of LIST that additionally returns special-use attributes.
+    ///
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the XLIST capability.
+    /// </div>
+    XList {
+        /// Reference.
+        reference: Mailbox<'a>,
+        /// Mailbox (wildcard).
+        mailbox_wildcard: ListMailbox<'a>,
+    },
+}
+
+// We want to redact usernames (see `RedactionPolicy`) and thus implement `Debug` manually.
+impl Debug for CommandBody<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Capability => f.write_str("Capability"),
+            Self::Noop => f.write_str("Noop"),
+            Self::Logout => f.write_str("Logout"),
+            #[cfg(feature = "starttls")]
+            Self::StartTLS => f.write_str("StartTLS"),
+            Self::Authenticate {
+                mechanism,
+                initial_response,
+            } => f
+                .debug_struct("Authenticate")
+                .field("mechanism", mechanism)
+                .field("initial_response", initial_response)
+                .finish(),
+            Self::Login { username, password } => {
+                let mut debug = f.debug_struct("Login");
+
+                if redaction_policy().usernames {
+                    debug.field("username", &Redacted(username.as_ref().len()));
+                } else {
+                    debug.field("username", username);
+                }
+
+                debug.field("password", password).finish()
+            }
+            Self::Select {
+                mailbox,
+                parameters,
+            } => f
+                .debug_struct("Select")
+                .field("mailbox", mailbox)
+                .field("parameters", parameters)
+                .finish(),
+            Self::Unselect => f.write_str("Unselect"),
+            Self::Examine {
+                mailbox,
+                parameters,
+            } => f
+                .debug_struct("Examine")
+                .field("mailbox", mailbox)
+                .field("parameters", parameters)
+                .finish(),
+            Self::Create { mailbox } => f.debug_struct("Create").field("mailbox", mailbox).finish(),
+            Self::Delete { mailbox } => f.debug_struct("Delete").field("mailbox", mailbox).finish(),
+            Self::Rename { from, to } => f
+                .debug_struct("Rename")
+                .field("from", from)
+                .field("to", to)
+                .finish(),
+            Self::Subscribe { mailbox } => f
+                .debug_struct("Subscribe")
+                .field("mailbox", mailbox)
+                .finish(),
+            Self::Unsubscribe { mailbox } => f
+                .debug_struct("Unsubscribe")
+                .field("mailbox", mailbox)
+                .finish(),
+            Self::List {
+                reference,
+                mailbox_wildcard,
+            } => f
+                .debug_struct("List")
+                .field("reference", reference)
+                .field("mailbox_wildcard", mailbox_wildcard)
+                .finish(),
+            Self::Lsub {
+                reference,
+                mailbox_wildcard,
+            } => f
+                .debug_struct("Lsub")
+                .field("reference", reference)
+                .field("mailbox_wildcard", mailbox_wildcard)
+                .finish(),
+            Self::Status {
+                mailbox,
+                item_names,
+            } => f
+                .debug_struct("Status")
+                .field("mailbox", mailbox)
+                .field("item_names", item_names)
+                .finish(),
+            Self::Append {
+                mailbox,
+                flags,
+                date,
+                message,
+            } => f
+                .debug_struct("Append")
+                .field("mailbox", mailbox)
+                .field("flags", flags)
+                .field("date", date)
+                .field("message", message)
+                .finish(),
+            Self::Check => f.write_str("Check"),
+            Self::Close => f.write_str("Close"),
+            Self::Expunge => f.write_str("Expunge"),
+            Self::ExpungeUid { sequence_set } => f
+                .debug_struct("ExpungeUid")
+                .field("sequence_set", sequence_set)
+                .finish(),
+            Self::Search {
+                charset,
+                criteria,
+                uid,
+            } => f
+                .debug_struct("Search")
+                .field("charset", charset)
+                .field("criteria", criteria)
+                .field("uid", uid)
+                .finish(),
+            Self::Sort {
+                return_options,
+                sort_criteria,
+                charset,
+                search_criteria,
+                uid,
+            } => f
+                .debug_struct("Sort")
+                .field("return_options", return_options)
+                .field("sort_criteria", sort_criteria)
+                .field("charset", charset)
+                .field("search_criteria", search_criteria)
+                .field("uid", uid)
+                .finish(),
+            Self::Thread {
+                algorithm,
+                charset,
+                search_criteria,
+                uid,
+            } => f
+                .debug_struct("Thread")
+                .field("algorithm", algorithm)
+                .field("charset", charset)
+                .field("search_criteria", search_criteria)
+                .field("uid", uid)
+                .finish(),
+            Self::Fetch {
+                sequence_set,
+                macro_or_item_names,
+                uid,
+                #[cfg(feature = "ext_condstore_qresync")]
+                modifiers,
+            } => {
+                let mut debug = f.debug_struct("Fetch");
+
+                debug.field("sequence_set", sequence_set);
+                debug.field("macro_or_item_names", macro_or_item_names);
+                debug.field("uid", uid);
+                #[cfg(feature = "ext_condstore_qresync")]
+                debug.field("modifiers", modifiers);
+
+                debug.finish()
+            }
+            Self::Store {
+                sequence_set,
+                kind,
+                response,
+                flags,
+                uid,
+                #[cfg(feature = "ext_condstore_qresync")]
+                modifiers,
+            } => {
+                let mut debug = f.debug_struct("Store");
+
+                debug.field("sequence_set", sequence_set);
+                debug.field("kind", kind);
+                debug.field("response", response);
+                debug.field("flags", flags);
+                debug.field("uid", uid);
+                #[cfg(feature = "ext_condstore_qresync")]
+                debug.field("modifiers", modifiers);
+
+                debug.finish()
+            }
+            Self::Copy {
+                sequence_set,
+                mailbox,
+                uid,
+            } => f
+                .debug_struct("Copy")
+                .field("sequence_set", sequence_set)
+                .field("mailbox", mailbox)
+                .field("uid", uid)
+                .finish(),
+            Self::Idle => f.write_str("Idle"),
+            Self::Enable { capabilities } => f
+                .debug_struct("Enable")
+                .field("capabilities", capabilities)
+                .finish(),
+            Self::Compress { algorithm } => f
+                .debug_struct("Compress")
+                .field("algorithm", algorithm)
+                .finish(),
+            Self::GetQuota { root } => f.debug_struct("GetQuota").field("root", root).finish(),
+            Self::GetQuotaRoot { mailbox } => f
+                .debug_struct("GetQuotaRoot")
+                .field("mailbox", mailbox)
+                .finish(),
+            Self::SetQuota { root, quotas } => f
+                .debug_struct("SetQuota")
+                .field("root", root)
+                .field("quotas", quotas)
+                .finish(),
+            Self::Move {
+                sequence_set,
+                mailbox,
+                uid,
+            } => f
+                .debug_struct("Move")
+                .field("sequence_set", sequence_set)
+                .field("mailbox", mailbox)
+                .field("uid", uid)
+                .finish(),
+            #[cfg(feature = "ext_id")]
+            Self::Id { parameters } => f
+                .debug_struct("Id")
+                .field("parameters", parameters)
+                .finish(),
+            #[cfg(feature = "ext_metadata")]
+            Self::SetMetadata {
+                mailbox,
+                entry_values,
+            } => f
+                .debug_struct("SetMetadata")
+                .field("mailbox", mailbox)
+                .field("entry_values", entry_values)
+                .finish(),
+            #[cfg(feature = "ext_metadata")]
+            Self::GetMetadata {
+                options,
+                mailbox,
+                entries,
+            } => f
+                .debug_struct("GetMetadata")
+                .field("options", options)
+                .field("mailbox", mailbox)
+                .field("entries", entries)
+                .finish(),
+            #[cfg(feature = "ext_xapplepushservice")]
+            Self::XApplePushService { parameters } => f
+                .debug_struct("XApplePushService")
+                .field("parameters", parameters)
+                .finish(),
+            #[cfg(feature = "ext_xlist")]
+            Self::XList {
+                reference,
+                mailbox_wildcard,
+            } => f
+                .debug_struct("XList")
+                .field("reference", reference)
+                .field("mailbox_wildcard", mailbox_wildcard)
+                .finish(),
+        }
+    }
 }
 
 impl<'a> CommandBody<'a> {
@@ -1597,7 +2335,6 @@ impl<'a> CommandBody<'a> {
     {
         Ok(CommandBody::Select {
             mailbox: mailbox.try_into()?,
-            #[cfg(feature = "ext_condstore_qresync")]
             parameters: Vec::default(),
         })
     }
@@ -1609,7 +2346,6 @@ impl<'a> CommandBody<'a> {
     {
         Ok(CommandBody::Examine {
             mailbox: mailbox.try_into()?,
-            #[cfg(feature = "ext_condstore_qresync")]
             parameters: Vec::default(),
         })
     }
@@ -1700,7 +2436,7 @@ impl<'a> CommandBody<'a> {
     pub fn status<M, I>(mailbox: M, item_names: I) -> Result<Self, M::Error>
     where
         M: TryInto<Mailbox<'a>>,
-        I: Into<Cow<'a, [StatusDataItemName]>>,
+        I: Into<Vec<StatusDataItemName<'a>>>,
     {
         let mailbox = mailbox.try_into()?;
 
@@ -1756,17 +2492,23 @@ impl<'a> CommandBody<'a> {
     }
 
     /// Construct a STORE command.
+    ///
+    /// Fails when `flags` contains a flag that can't be stored, i.e., `\Recent`.
     pub fn store<S>(
         sequence_set: S,
         kind: StoreType,
         response: StoreResponse,
         flags: Vec<Flag<'a>>,
         uid: bool,
-    ) -> Result<Self, S::Error>
+    ) -> Result<Self, StoreError<S::Error>>
     where
         S: TryInto<SequenceSet>,
     {
-        let sequence_set = sequence_set.try_into()?;
+        let sequence_set = sequence_set.try_into().map_err(StoreError::Sequence)?;
+
+        if !flags.iter().all(Flag::is_storable) {
+            return Err(StoreError::UnstorableFlag);
+        }
 
         Ok(CommandBody::Store {
             sequence_set,
@@ -1841,32 +2583,168 @@ impl<'a> CommandBody<'a> {
             Self::SetMetadata { .. } => "SETMETADATA",
             #[cfg(feature = "ext_metadata")]
             Self::GetMetadata { .. } => "GETMETADATA",
+            #[cfg(feature = "ext_xapplepushservice")]
+            Self::XApplePushService { .. } => "XAPPLEPUSHSERVICE",
+            #[cfg(feature = "ext_xlist")]
+            Self::XList { .. } => "XLIST",
+        }
+    }
+
+    /// Check whether the command is valid in the given `state`.
+    ///
+    /// Follows the RFC 3501 command/state matrix and the RFCs of the implemented extensions.
+    /// No command is valid before the greeting, after logout, or while idling.
+    ///
+    /// ```rust
+    /// use imap_types::{command::CommandBody, mailbox::Mailbox, state::State};
+    ///
+    /// assert!(CommandBody::Noop.allowed_in(&State::NotAuthenticated));
+    /// assert!(!CommandBody::Close.allowed_in(&State::Authenticated));
+    /// assert!(CommandBody::Close.allowed_in(&State::Selected(Mailbox::Inbox)));
+    /// ```
+    pub fn allowed_in(&self, state: &State<'_>) -> bool {
+        let (not_authenticated, authenticated, selected) = match self {
+            // Any state
+            Self::Capability | Self::Noop | Self::Logout => (true, true, true),
+            #[cfg(feature = "ext_id")]
+            Self::Id { .. } => (true, true, true),
+            // Not Authenticated state
+            #[cfg(feature = "starttls")]
+            Self::StartTLS => (true, false, false),
+            Self::Authenticate { .. } | Self::Login { .. } => (true, false, false),
+            // Authenticated state
+            Self::Enable { .. } => (false, true, false),
+            Self::Select { .. }
+            | Self::Examine { .. }
+            | Self::Create { .. }
+            | Self::Delete { .. }
+            | Self::Rename { .. }
+            | Self::Subscribe { .. }
+            | Self::Unsubscribe { .. }
+            | Self::List { .. }
+            | Self::Lsub { .. }
+            | Self::Status { .. }
+            | Self::Append { .. }
+            | Self::Idle
+            | Self::Compress { .. }
+            | Self::GetQuota { .. }
+            | Self::GetQuotaRoot { .. }
+            | Self::SetQuota { .. } => (false, true, true),
+            #[cfg(feature = "ext_metadata")]
+            Self::SetMetadata { .. } | Self::GetMetadata { .. } => (false, true, true),
+            #[cfg(feature = "ext_xapplepushservice")]
+            Self::XApplePushService { .. } => (false, true, true),
+            #[cfg(feature = "ext_xlist")]
+            Self::XList { .. } => (false, true, true),
+            // Selected state
+            Self::Check
+            | Self::Close
+            | Self::Unselect
+            | Self::Expunge
+            | Self::ExpungeUid { .. }
+            | Self::Search { .. }
+            | Self::Sort { .. }
+            | Self::Thread { .. }
+            | Self::Fetch { .. }
+            | Self::Store { .. }
+            | Self::Copy { .. }
+            | Self::Move { .. } => (false, false, true),
+        };
+
+        match state {
+            State::NotAuthenticated => not_authenticated,
+            State::Authenticated => authenticated,
+            State::Selected(_) => selected,
+            State::Greeting
+            | State::Logout
+            | State::IdleAuthenticated(_)
+            | State::IdleSelected(_, _) => false,
         }
     }
 }
 
-#[cfg(feature = "ext_condstore_qresync")]
-#[cfg_attr(docsrs, doc(cfg("ext_condstore_qresync")))]
+/// Parameter of a SELECT or EXAMINE command (see RFC 4466).
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
-pub enum SelectParameter {
+pub enum SelectParameter<'a> {
+    #[cfg(feature = "ext_condstore_qresync")]
+    #[cfg_attr(docsrs, doc(cfg("ext_condstore_qresync")))]
     CondStore,
+    #[cfg(feature = "ext_condstore_qresync")]
+    #[cfg_attr(docsrs, doc(cfg("ext_condstore_qresync")))]
     QResync {
         uid_validity: NonZeroU32,
-        mod_sequence_value: NonZeroU64,
+        mod_sequence_value: ModSeq,
         known_uids: Option<SequenceSet>, // TODO(misuse): "*" is not allowed.
         seq_match_data: Option<(SequenceSet, SequenceSet)>, // TODO(misuse): ensure both have the same length?
     },
+    /// Parameter of an unknown extension.
+    Other(SelectParameterOther<'a>),
+}
+
+/// A SELECT or EXAMINE parameter of an unknown extension.
+///
+/// It's guaranteed that this type can't represent any parameter from [`SelectParameter`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "serde", serde(try_from = "ExtensionItem<'a>"))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub struct SelectParameterOther<'a>(ExtensionItem<'a>);
+
+impl<'a> SelectParameterOther<'a> {
+    pub fn validate(value: &ExtensionItem) -> Result<(), ValidationError> {
+        let label = value.label.as_ref();
+
+        if cfg!(feature = "ext_condstore_qresync")
+            && (label.eq_ignore_ascii_case("condstore") || label.eq_ignore_ascii_case("qresync"))
+        {
+            return Err(ValidationError::new(ValidationErrorKind::Invalid));
+        }
+
+        Ok(())
+    }
+
+    pub fn inner(&self) -> &ExtensionItem<'a> {
+        &self.0
+    }
+
+    /// Constructs a parameter without validation.
+    ///
+    /// # Warning: IMAP conformance
+    ///
+    /// The caller must ensure that `value` is valid according to [`Self::validate`]. Failing to do
+    /// so may create invalid/unparsable IMAP messages, or even produce unintended protocol flows.
+    /// Do not call this constructor with untrusted data.
+    ///
+    /// Note: This method will `panic!` on wrong input in debug builds.
+    pub fn unvalidated(value: ExtensionItem<'a>) -> Self {
+        #[cfg(debug_assertions)]
+        Self::validate(&value).unwrap();
+
+        Self(value)
+    }
+}
+
+impl<'a> TryFrom<ExtensionItem<'a>> for SelectParameterOther<'a> {
+    type Error = ValidationError;
+
+    fn try_from(value: ExtensionItem<'a>) -> Result<Self, Self::Error> {
+        Self::validate(&value)?;
+
+        Ok(Self(value))
+    }
 }
 
 #[cfg(feature = "ext_condstore_qresync")]
 #[cfg_attr(docsrs, doc(cfg("ext_condstore_qresync")))]
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum FetchModifier {
-    ChangedSince(NonZeroU64),
+    ChangedSince(ModSeq),
     Vanished,
 }
 
@@ -1874,13 +2752,17 @@ pub enum FetchModifier {
 #[cfg_attr(docsrs, doc(cfg("ext_condstore_qresync")))]
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum StoreModifier {
-    UnchangedSince(u64),
+    /// Mod-sequence or `None` for `0` (`mod-sequence-valzer`).
+    UnchangedSince(Option<ModSeq>),
 }
 
 /// Error-related types.
 pub mod error {
+    use alloc::string::String;
+
     use thiserror::Error;
 
     #[derive(Clone, Debug, Eq, Error, Hash, Ord, PartialEq, PartialOrd)]
@@ -1915,6 +2797,14 @@ pub mod error {
         Data(D),
     }
 
+    #[derive(Clone, Debug, Eq, Error, Hash, Ord, PartialEq, PartialOrd)]
+    pub enum StoreError<S> {
+        #[error("Invalid sequence: {0}")]
+        Sequence(S),
+        #[error("Flag can't be stored")]
+        UnstorableFlag,
+    }
+
     #[derive(Clone, Debug, Eq, Error, Hash, Ord, PartialEq, PartialOrd)]
     pub enum CopyError<S, M> {
         #[error("Invalid sequence: {0}")]
@@ -1922,6 +2812,14 @@ pub mod error {
         #[error("Invalid mailbox: {0}")]
         Mailbox(M),
     }
+
+    #[derive(Clone, Debug, Eq, Error, Hash, Ord, PartialEq, PartialOrd)]
+    pub enum UnsupportedError {
+        #[error("Missing capability: {0}")]
+        MissingCapability(String),
+        #[error("LOGIN is disabled")]
+        LoginDisabled,
+    }
 }
 
 #[cfg(test)]
@@ -1931,7 +2829,7 @@ mod tests {
     use super::*;
     use crate::{
         auth::AuthMechanism,
-        core::{AString, Charset, IString, Literal, LiteralMode, Vec1},
+        core::{AString, Charset, IString, Literal, LiteralMode, Quoted, Vec1},
         datetime::DateTime,
         extensions::{
             binary::Literal8,
@@ -2126,6 +3024,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_store_unstorable_flag() {
+        assert_eq!(
+            CommandBody::store(
+                "1",
+                StoreType::Add,
+                StoreResponse::Silent,
+                vec![Flag::Seen, Flag::try_from("\\Recent").unwrap()],
+                false,
+            ),
+            Err(StoreError::UnstorableFlag)
+        );
+    }
+
     #[test]
     fn test_command_body_name() {
         let tests = [
@@ -2151,7 +3063,6 @@ mod tests {
             (
                 CommandBody::Select {
                     mailbox: Mailbox::Inbox,
-                    #[cfg(feature = "ext_condstore_qresync")]
                     parameters: Vec::default(),
                 },
                 "SELECT",
@@ -2160,7 +3071,6 @@ mod tests {
             (
                 CommandBody::Examine {
                     mailbox: Mailbox::Inbox,
-                    #[cfg(feature = "ext_condstore_qresync")]
                     parameters: Vec::default(),
                 },
                 "EXAMINE",
@@ -2325,4 +3235,116 @@ mod tests {
             assert_eq!(test.name(), expected);
         }
     }
+
+    #[test]
+    fn test_command_body_allowed_in() {
+        let not_authenticated = State::NotAuthenticated;
+        let authenticated = State::Authenticated;
+        let selected = State::Selected(Mailbox::Inbox);
+
+        let tests = [
+            (CommandBody::Noop, [true, true, true]),
+            (
+                CommandBody::login("alice", "pass").unwrap(),
+                [true, false, false],
+            ),
+            (CommandBody::select("INBOX").unwrap(), [false, true, true]),
+            (
+                CommandBody::Enable {
+                    capabilities: Vec1::from(CapabilityEnable::Utf8(Utf8Kind::Accept)),
+                },
+                [false, true, false],
+            ),
+            (CommandBody::Idle, [false, true, true]),
+            (CommandBody::Close, [false, false, true]),
+            (CommandBody::Expunge, [false, false, true]),
+        ];
+
+        for (test, expected) in tests {
+            assert_eq!(test.allowed_in(&not_authenticated), expected[0]);
+            assert_eq!(test.allowed_in(&authenticated), expected[1]);
+            assert_eq!(test.allowed_in(&selected), expected[2]);
+            assert!(!test.allowed_in(&State::Greeting));
+            assert!(!test.allowed_in(&State::Logout));
+        }
+    }
+
+    #[test]
+    fn test_command_validate() {
+        let missing = |name: &str| Err(UnsupportedError::MissingCapability(name.into()));
+        let caps = |caps: &[&'static str]| {
+            caps.iter()
+                .map(|cap| Capability::try_from(*cap).unwrap())
+                .collect::<CapabilitySet>()
+        };
+
+        let idle = Command::new("A", CommandBody::Idle).unwrap();
+        assert_eq!(idle.validate(&caps(&["IMAP4REV1"])), missing("IDLE"));
+        assert_eq!(idle.validate(&caps(&["IMAP4REV1", "IDLE"])), Ok(()));
+
+        let login = Command::new("A", CommandBody::login("alice", "pass").unwrap()).unwrap();
+        assert_eq!(
+            login.validate(&caps(&["LOGINDISABLED"])),
+            Err(UnsupportedError::LoginDisabled)
+        );
+
+        let authenticate = Command::new(
+            "A",
+            CommandBody::authenticate_with_ir(AuthMechanism::Plain, &b"\x00alice\x00pass"[..]),
+        )
+        .unwrap();
+        assert_eq!(
+            authenticate.validate(&caps(&["IMAP4REV1"])),
+            missing("AUTH=PLAIN")
+        );
+        assert_eq!(
+            authenticate.validate(&caps(&["AUTH=PLAIN"])),
+            missing("SASL-IR")
+        );
+        assert_eq!(
+            authenticate.validate(&caps(&["AUTH=PLAIN", "SASL-IR"])),
+            Ok(())
+        );
+
+        let append = |size: usize| {
+            Command::new(
+                "A",
+                CommandBody::append(
+                    "INBOX",
+                    vec![],
+                    None,
+                    Literal::unvalidated_non_sync(vec![b'x'; size]),
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+        assert_eq!(
+            append(10).validate(&caps(&["IMAP4REV1"])),
+            missing("LITERAL+")
+        );
+        assert_eq!(append(10).validate(&caps(&["LITERAL-"])), Ok(()));
+        assert_eq!(
+            append(5000).validate(&caps(&["LITERAL-"])),
+            missing("LITERAL+")
+        );
+        assert_eq!(append(5000).validate(&caps(&["LITERAL+"])), Ok(()));
+
+        let search = Command::new(
+            "A",
+            CommandBody::search(
+                None,
+                Vec1::from(SearchKey::Not(Box::new(SearchKey::Subject(
+                    AString::String(IString::Quoted(Quoted::unvalidated_8bit("Grüße"))),
+                )))),
+                false,
+            ),
+        )
+        .unwrap();
+        assert_eq!(
+            search.validate(&caps(&["IMAP4REV1"])),
+            missing("UTF8=ACCEPT")
+        );
+        assert_eq!(search.validate(&caps(&["UTF8=ACCEPT"])), Ok(()));
+    }
 }
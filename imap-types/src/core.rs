@@ -35,25 +35,39 @@
 //! ```
 
 #[cfg(feature = "tag_generator")]
-use std::sync::atomic::{AtomicUsize, Ordering};
-use std::{
+use alloc::format;
+use alloc::{
     borrow::Cow,
+    string::{String, ToString},
+    vec,
+    vec::{IntoIter, Vec},
+};
+#[cfg(feature = "tag_generator")]
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use core::{
     fmt::{Debug, Display, Formatter},
+    hash::{Hash, Hasher},
+    ops::Deref,
     str::from_utf8,
-    vec::IntoIter,
 };
 
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
+use bounded_static::{IntoBoundedStatic, ToBoundedStatic};
 use bounded_static_derive::ToStatic;
 #[cfg(feature = "tag_generator")]
-#[cfg(not(debug_assertions))]
 use rand::distributions::{Alphanumeric, DistString};
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::utils::indicators::{
-    is_any_text_char_except_quoted_specials, is_astring_char, is_atom_char, is_char8, is_text_char,
+use crate::utils::{
+    escape_quoted,
+    indicators::{
+        is_any_text_char_except_quoted_specials, is_astring_char, is_atom_char, is_char8,
+        is_text_char,
+    },
 };
 
 #[cfg(feature = "tag_generator")]
@@ -78,6 +92,7 @@ pub(crate) use impl_try_from;
 use crate::{
     error::{ValidationError, ValidationErrorKind},
     extensions::binary::Literal8,
+    secret::{redaction_policy, Redacted},
 };
 
 /// A string subset to model IMAP's `atom`s.
@@ -105,17 +120,73 @@ use crate::{
 /// resp-specials   = "]"
 /// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(feature = "serde", serde(try_from = "String"))]
-#[derive(Clone, PartialEq, Eq, Ord, PartialOrd, Hash, ToStatic)]
+#[derive(Clone, PartialEq, Eq, Ord, PartialOrd, Hash)]
 pub struct Atom<'a>(pub(crate) Cow<'a, str>);
 
 // We want a slightly more dense `Debug` implementation.
 impl Debug for Atom<'_> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         write!(f, "Atom({:?})", self.0)
     }
 }
 
+/// Well-known atoms that don't need an allocation when made `'static`.
+///
+/// Note: Only exact (case-sensitive) matches are interned to preserve the original spelling.
+#[cfg(feature = "perf")]
+const INTERNED_ATOMS: &[&str] = &[
+    "$Forwarded",
+    "$MDNSent",
+    "$Junk",
+    "$NotJunk",
+    "$Phishing",
+    "$Important",
+    "$SubmitPending",
+    "$Submitted",
+    "Junk",
+    "NonJunk",
+    "NotJunk",
+];
+
+impl Atom<'_> {
+    #[cfg(feature = "perf")]
+    fn interned(&self) -> Option<Atom<'static>> {
+        INTERNED_ATOMS
+            .iter()
+            .find(|interned| **interned == self.0)
+            .map(|interned| Atom(Cow::Borrowed(interned)))
+    }
+
+    #[cfg(not(feature = "perf"))]
+    fn interned(&self) -> Option<Atom<'static>> {
+        None
+    }
+}
+
+impl IntoBoundedStatic for Atom<'_> {
+    type Static = Atom<'static>;
+
+    fn into_static(self) -> Self::Static {
+        match self.interned() {
+            Some(interned) => interned,
+            None => Atom(self.0.into_static()),
+        }
+    }
+}
+
+impl ToBoundedStatic for Atom<'_> {
+    type Static = Atom<'static>;
+
+    fn to_static(&self) -> Self::Static {
+        match self.interned() {
+            Some(interned) => interned,
+            None => Atom(self.0.to_static()),
+        }
+    }
+}
+
 impl<'a> Atom<'a> {
     /// Validates if value conforms to atom's ABNF definition.
     pub fn validate(value: impl AsRef<[u8]>) -> Result<(), ValidationError> {
@@ -226,7 +297,7 @@ impl AsRef<str> for Atom<'_> {
 }
 
 impl Display for Atom<'_> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         write!(f, "{}", self.0)
     }
 }
@@ -255,13 +326,14 @@ impl Display for Atom<'_> {
 /// ;              See `Atom`
 /// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(feature = "serde", serde(try_from = "String"))]
 #[derive(Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct AtomExt<'a>(pub(crate) Cow<'a, str>);
 
 // We want a slightly more dense `Debug` implementation.
 impl Debug for AtomExt<'_> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         write!(f, "AtomExt({:?})", self.0)
     }
 }
@@ -387,6 +459,7 @@ impl AsRef<str> for AtomExt<'_> {
 /// ```
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum IString<'a> {
     /// Literal, see [`Literal`].
@@ -396,6 +469,48 @@ pub enum IString<'a> {
 }
 
 impl<'a> IString<'a> {
+    /// Choose the best representation for `value`.
+    ///
+    /// The decision is deterministic and only depends on `value`:
+    ///
+    /// 1. A quoted string, if `value` consists of `TEXT-CHAR`s only (no CR, LF, or 8-bit data).
+    ///    `"` and `\` are escaped.
+    /// 2. Otherwise, a synchronizing literal. Sending a literal requires a command continuation
+    ///    request from the server (unless changed to a non-synchronizing literal).
+    ///
+    /// Fails when `value` contains a NUL byte, which can't be represented at all.
+    ///
+    /// See also [`AString::best`].
+    pub fn best<V>(value: V) -> Result<Self, ValidationError>
+    where
+        V: Into<Cow<'a, [u8]>>,
+    {
+        match value.into() {
+            Cow::Borrowed(value) => Self::try_from(value),
+            Cow::Owned(value) => Self::try_from(value),
+        }
+    }
+
+    /// Create a quoted string if possible, a literal otherwise.
+    ///
+    /// Same as [`IString::best`].
+    pub fn quoted_or_literal<V>(value: V) -> Result<Self, ValidationError>
+    where
+        V: Into<Cow<'a, [u8]>>,
+    {
+        Self::best(value)
+    }
+
+    /// Return the content as bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.as_ref()
+    }
+
+    /// Return the content as string, replacing invalid UTF-8 with `U+FFFD`.
+    pub fn as_str_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self.as_bytes())
+    }
+
     pub fn into_inner(self) -> Cow<'a, [u8]> {
         match self {
             Self::Literal(literal) => literal.into_inner(),
@@ -502,13 +617,15 @@ impl AsRef<[u8]> for IString<'_> {
 ///           ; any OCTET except NUL, %x00
 /// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct Literal<'a> {
     #[cfg_attr(
         feature = "serde",
         serde(deserialize_with = "deserialize_literal_data")
     )]
-    pub(crate) data: Cow<'a, [u8]>,
+    #[cfg_attr(feature = "schemars", schemars(with = "Vec<u8>"))]
+    pub(crate) data: LiteralData<'a>,
     /// Specifies whether this is a synchronizing or non-synchronizing literal.
     ///
     /// `true` (default) denotes a synchronizing literal, e.g., `{3}\r\nfoo`.
@@ -520,34 +637,35 @@ pub struct Literal<'a> {
 }
 
 #[cfg(feature = "serde")]
-fn deserialize_literal_data<'de, 'a, D>(deserializer: D) -> Result<Cow<'a, [u8]>, D::Error>
+fn deserialize_literal_data<'de, 'a, D>(deserializer: D) -> Result<LiteralData<'a>, D::Error>
 where
     D: serde::de::Deserializer<'de>,
 {
-    let data = Vec::deserialize(deserializer)?;
-    Literal::validate(&data).map_err(serde::de::Error::custom)?;
-    Ok(Cow::Owned(data))
+    let data = LiteralData::deserialize(deserializer)?;
+    Literal::validate(&*data).map_err(serde::de::Error::custom)?;
+    Ok(data)
 }
 
 // We want a more readable `Debug` implementation.
 impl Debug for Literal<'_> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        struct BStr<'a>(&'a Cow<'a, [u8]>);
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        struct BStr<'a>(&'a [u8]);
 
         impl Debug for BStr<'_> {
-            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-                write!(
-                    f,
-                    "b\"{}\"",
-                    crate::utils::escape_byte_string(self.0.as_ref())
-                )
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+                write!(f, "b\"{}\"", crate::utils::escape_byte_string(self.0))
             }
         }
 
-        f.debug_struct("Literal")
-            .field("data", &BStr(&self.data))
-            .field("mode", &self.mode)
-            .finish()
+        let mut debug = f.debug_struct("Literal");
+
+        if redaction_policy().literals {
+            debug.field("data", &Redacted(self.data.len()));
+        } else {
+            debug.field("data", &BStr(&self.data));
+        }
+
+        debug.field("mode", &self.mode).finish()
     }
 }
 
@@ -593,10 +711,31 @@ impl<'a> Literal<'a> {
         self
     }
 
+    /// Return the data.
+    ///
+    /// Note: Data backed by [`Bytes`](bytes::Bytes) is copied. Use [`Self::into_data`] to avoid this.
     pub fn into_inner(self) -> Cow<'a, [u8]> {
+        self.data.into()
+    }
+
+    /// Return the data without copying it.
+    pub fn into_data(self) -> LiteralData<'a> {
         self.data
     }
 
+    /// Share the data with `buffer` if it was borrowed from it.
+    ///
+    /// Useful to keep a (large) literal around after decoding a message from `buffer` without
+    /// copying it. See [`LiteralData::into_shared`].
+    #[cfg(feature = "bytes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+    pub fn into_shared(self, buffer: &bytes::Bytes) -> Literal<'static> {
+        Literal {
+            data: self.data.into_shared(buffer),
+            mode: self.mode,
+        }
+    }
+
     /// Constructs a literal without validation.
     ///
     /// # Warning: IMAP conformance
@@ -610,13 +749,13 @@ impl<'a> Literal<'a> {
     where
         D: Into<Cow<'a, [u8]>>,
     {
-        let data = data.into();
+        let data: Cow<'a, [u8]> = data.into();
 
         #[cfg(debug_assertions)]
         Self::validate(&data).unwrap();
 
         Self {
-            data,
+            data: data.into(),
             mode: LiteralMode::Sync,
         }
     }
@@ -638,13 +777,13 @@ impl<'a> Literal<'a> {
     where
         D: Into<Cow<'a, [u8]>>,
     {
-        let data = data.into();
+        let data: Cow<'a, [u8]> = data.into();
 
         #[cfg(debug_assertions)]
         Self::validate(&data).unwrap();
 
         Self {
-            data,
+            data: data.into(),
             mode: LiteralMode::NonSync,
         }
     }
@@ -657,7 +796,7 @@ impl<'a> TryFrom<&'a [u8]> for Literal<'a> {
         Self::validate(value)?;
 
         Ok(Literal {
-            data: Cow::Borrowed(value),
+            data: LiteralData::Borrowed(value),
             mode: LiteralMode::Sync,
         })
     }
@@ -670,7 +809,7 @@ impl TryFrom<Vec<u8>> for Literal<'_> {
         Self::validate(&value)?;
 
         Ok(Literal {
-            data: Cow::Owned(value),
+            data: LiteralData::Owned(value),
             mode: LiteralMode::Sync,
         })
     }
@@ -683,7 +822,7 @@ impl<'a> TryFrom<&'a str> for Literal<'a> {
         Self::validate(value)?;
 
         Ok(Literal {
-            data: Cow::Borrowed(value.as_bytes()),
+            data: LiteralData::Borrowed(value.as_bytes()),
             mode: LiteralMode::Sync,
         })
     }
@@ -696,7 +835,22 @@ impl TryFrom<String> for Literal<'_> {
         Self::validate(&value)?;
 
         Ok(Literal {
-            data: Cow::Owned(value.into_bytes()),
+            data: LiteralData::Owned(value.into_bytes()),
+            mode: LiteralMode::Sync,
+        })
+    }
+}
+
+#[cfg(feature = "bytes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+impl TryFrom<bytes::Bytes> for Literal<'_> {
+    type Error = ValidationError;
+
+    fn try_from(value: bytes::Bytes) -> Result<Self, Self::Error> {
+        Self::validate(&value)?;
+
+        Ok(Literal {
+            data: LiteralData::Shared(value),
             mode: LiteralMode::Sync,
         })
     }
@@ -708,9 +862,238 @@ impl AsRef<[u8]> for Literal<'_> {
     }
 }
 
+/// Data of a [`Literal`] or [`Literal8`].
+///
+/// Like a `Cow<'a, [u8]>`. With the `bytes` feature, the data can also be a reference-counted
+/// [`Bytes`](bytes::Bytes), i.e., be shared with the buffer a message was decoded from. Then,
+/// neither decoding nor [`IntoStatic`](crate::IntoStatic) copy (potentially large) literals.
+#[derive(Clone)]
+pub enum LiteralData<'a> {
+    Borrowed(&'a [u8]),
+    Owned(Vec<u8>),
+    #[cfg(feature = "bytes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+    Shared(bytes::Bytes),
+}
+
+impl LiteralData<'_> {
+    /// Share the data with `buffer` if it was borrowed from it.
+    ///
+    /// Borrowed data that points into `buffer` is turned into a [`Bytes`](bytes::Bytes) slice of
+    /// `buffer` without copying. Other borrowed data is copied. Owned data is moved.
+    #[cfg(feature = "bytes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+    pub fn into_shared(self, buffer: &bytes::Bytes) -> LiteralData<'static> {
+        match self {
+            Self::Borrowed(data) => {
+                let range = buffer.as_ptr_range();
+
+                if !data.is_empty()
+                    && range.contains(&data.as_ptr())
+                    && data.as_ptr_range().end <= range.end
+                {
+                    LiteralData::Shared(buffer.slice_ref(data))
+                } else {
+                    LiteralData::Shared(bytes::Bytes::copy_from_slice(data))
+                }
+            }
+            Self::Owned(data) => LiteralData::Shared(data.into()),
+            Self::Shared(data) => LiteralData::Shared(data),
+        }
+    }
+}
+
+impl Deref for LiteralData<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Self::Borrowed(data) => data,
+            Self::Owned(data) => data,
+            #[cfg(feature = "bytes")]
+            Self::Shared(data) => data,
+        }
+    }
+}
+
+impl AsRef<[u8]> for LiteralData<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl Debug for LiteralData<'_> {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        Debug::fmt(&**self, f)
+    }
+}
+
+impl PartialEq for LiteralData<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for LiteralData<'_> {}
+
+impl Hash for LiteralData<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl<'a> From<&'a [u8]> for LiteralData<'a> {
+    fn from(value: &'a [u8]) -> Self {
+        Self::Borrowed(value)
+    }
+}
+
+impl<'a, const N: usize> From<&'a [u8; N]> for LiteralData<'a> {
+    fn from(value: &'a [u8; N]) -> Self {
+        Self::Borrowed(value)
+    }
+}
+
+impl From<Vec<u8>> for LiteralData<'_> {
+    fn from(value: Vec<u8>) -> Self {
+        Self::Owned(value)
+    }
+}
+
+impl<'a> From<Cow<'a, [u8]>> for LiteralData<'a> {
+    fn from(value: Cow<'a, [u8]>) -> Self {
+        match value {
+            Cow::Borrowed(data) => Self::Borrowed(data),
+            Cow::Owned(data) => Self::Owned(data),
+        }
+    }
+}
+
+#[cfg(feature = "bytes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+impl From<bytes::Bytes> for LiteralData<'_> {
+    fn from(value: bytes::Bytes) -> Self {
+        Self::Shared(value)
+    }
+}
+
+impl<'a> From<LiteralData<'a>> for Cow<'a, [u8]> {
+    fn from(value: LiteralData<'a>) -> Self {
+        match value {
+            LiteralData::Borrowed(data) => Cow::Borrowed(data),
+            LiteralData::Owned(data) => Cow::Owned(data),
+            #[cfg(feature = "bytes")]
+            LiteralData::Shared(data) => Cow::Owned(data.to_vec()),
+        }
+    }
+}
+
+impl IntoBoundedStatic for LiteralData<'_> {
+    type Static = LiteralData<'static>;
+
+    fn into_static(self) -> Self::Static {
+        match self {
+            Self::Borrowed(data) => LiteralData::Owned(data.to_vec()),
+            Self::Owned(data) => LiteralData::Owned(data),
+            #[cfg(feature = "bytes")]
+            Self::Shared(data) => LiteralData::Shared(data),
+        }
+    }
+}
+
+impl ToBoundedStatic for LiteralData<'_> {
+    type Static = LiteralData<'static>;
+
+    fn to_static(&self) -> Self::Static {
+        match self {
+            Self::Borrowed(data) => LiteralData::Owned(data.to_vec()),
+            Self::Owned(data) => LiteralData::Owned(data.clone()),
+            #[cfg(feature = "bytes")]
+            Self::Shared(data) => LiteralData::Shared(data.clone()),
+        }
+    }
+}
+
+// Literal data is serialized as bytes, i.e., compact in binary formats such as postcard or bincode.
+// Self-describing formats may still represent bytes as a sequence, e.g., JSON uses `[1, 2, 3]`.
+#[cfg(feature = "serde")]
+impl Serialize for LiteralData<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_bytes(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for LiteralData<'_> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct LiteralDataVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for LiteralDataVisitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, f: &mut Formatter) -> core::fmt::Result {
+                f.write_str("bytes")
+            }
+
+            fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(value.to_vec())
+            }
+
+            fn visit_byte_buf<E>(self, value: Vec<u8>) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(value)
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut data = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+
+                while let Some(byte) = seq.next_element()? {
+                    data.push(byte);
+                }
+
+                Ok(data)
+            }
+        }
+
+        deserializer
+            .deserialize_byte_buf(LiteralDataVisitor)
+            .map(Self::Owned)
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl JsonSchema for LiteralData<'_> {
+    fn schema_name() -> String {
+        <Vec<u8>>::schema_name()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        <Vec<u8>>::json_schema(gen)
+    }
+
+    fn is_referenceable() -> bool {
+        false
+    }
+}
+
 /// Literal mode, i.e., sync or non-sync.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ToStatic)]
 pub enum LiteralMode {
     /// A synchronizing literal, i.e., `{<n>}\r\n<data>`.
@@ -746,12 +1129,13 @@ pub enum LiteralMode {
 /// quoted-specials = DQUOTE / "\"
 /// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(feature = "serde", serde(try_from = "String"))]
 #[derive(Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct Quoted<'a>(pub(crate) Cow<'a, str>);
 
 impl Debug for Quoted<'_> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         write!(f, "Quoted({:?})", self.0)
     }
 }
@@ -778,6 +1162,29 @@ impl<'a> Quoted<'a> {
         self.0
     }
 
+    /// Return the logical (unescaped) value.
+    ///
+    /// A `Quoted` always holds the unescaped value, e.g., a decoded `"a\"b"` holds `a"b`. Use this
+    /// value to compare against user data.
+    ///
+    /// ```rust
+    /// use imap_types::core::Quoted;
+    ///
+    /// let quoted = Quoted::try_from("a\"b").unwrap();
+    /// assert_eq!(quoted.unescaped(), "a\"b");
+    /// assert_eq!(quoted.escaped(), "a\\\"b");
+    /// ```
+    pub fn unescaped(&self) -> &str {
+        self.inner()
+    }
+
+    /// Return the escaped value, i.e., as sent on the wire (without the surrounding `"`).
+    ///
+    /// See [`escape_quoted`](crate::utils::escape_quoted).
+    pub fn escaped(&self) -> Cow<'_, str> {
+        escape_quoted(self.inner())
+    }
+
     /// Constructs a quoted string without validation.
     ///
     /// # Warning: IMAP conformance
@@ -798,6 +1205,42 @@ impl<'a> Quoted<'a> {
 
         Self(inner)
     }
+
+    /// Constructs a quoted string that may contain 8-bit characters without validation.
+    ///
+    /// RFC 3501 only allows 7-bit characters. However, some servers send raw UTF-8 in quoted
+    /// strings. This constructor is meant for parsers that want to preserve such content.
+    ///
+    /// # Warning: IMAP conformance
+    ///
+    /// The caller must ensure that `inner` is valid according to [`Self::validate`] except for
+    /// 8-bit characters. Do not call this constructor with untrusted data.
+    ///
+    /// Note: This method will `panic!` on wrong input in debug builds.
+    pub fn unvalidated_8bit<C>(inner: C) -> Self
+    where
+        C: Into<Cow<'a, str>>,
+    {
+        let inner = inner.into();
+
+        #[cfg(debug_assertions)]
+        assert!(inner.bytes().all(|b| is_text_char(b) || b >= 0x80));
+
+        Self(inner)
+    }
+
+    /// Constructs a quoted string by replacing forbidden characters.
+    ///
+    /// Line breaks (CR, LF, or CRLF) are replaced with a single space. All other forbidden
+    /// characters, e.g., NUL or non-ASCII characters, are replaced with `?`.
+    ///
+    /// Useful to pass arbitrary (untrusted) strings, e.g., error messages, to the other side.
+    pub fn sanitize<C>(inner: C) -> Self
+    where
+        C: Into<Cow<'a, str>>,
+    {
+        Self(sanitize_text(inner.into()))
+    }
 }
 
 impl<'a> TryFrom<&'a [u8]> for Quoted<'a> {
@@ -864,6 +1307,7 @@ impl AsRef<str> for Quoted<'_> {
 /// ```
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct NString<'a>(
     // This wrapper is merely used for formatting.
@@ -875,6 +1319,33 @@ impl<'a> NString<'a> {
     /// Convenience alias to `NString(None)` for any lifetime.
     pub const NIL: NString<'a> = NString(None);
 
+    /// Create a (non-NIL) string from the `Display` representation of `value`.
+    ///
+    /// ```rust
+    /// use imap_types::core::NString;
+    ///
+    /// assert_eq!(
+    ///     NString::from_display(42).unwrap(),
+    ///     NString::try_from("42").unwrap()
+    /// );
+    /// ```
+    pub fn from_display<D>(value: D) -> Result<NString<'static>, ValidationError>
+    where
+        D: Display,
+    {
+        NString::try_from(value.to_string())
+    }
+
+    /// Return the content as bytes (or `None` for `NIL`).
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        self.0.as_ref().map(IString::as_bytes)
+    }
+
+    /// Return the content as string (or `None` for `NIL`), replacing invalid UTF-8 with `U+FFFD`.
+    pub fn as_str_lossy(&self) -> Option<Cow<'_, str>> {
+        self.0.as_ref().map(IString::as_str_lossy)
+    }
+
     pub fn into_option(self) -> Option<Cow<'a, [u8]>> {
         self.0.map(|inner| inner.into_inner())
     }
@@ -904,6 +1375,29 @@ impl_try_from_nstring!(Vec<u8>);
 impl_try_from_nstring!(&'a str);
 impl_try_from_nstring!(String);
 
+macro_rules! impl_try_from_option_nstring {
+    ($from:ty) => {
+        /// `None` is `NIL`.
+        ///
+        /// Note: This is not infallible because a string must not contain NUL bytes.
+        impl<'a> TryFrom<Option<$from>> for NString<'a> {
+            type Error = ValidationError;
+
+            fn try_from(value: Option<$from>) -> Result<Self, Self::Error> {
+                match value {
+                    Some(value) => Self::try_from(value),
+                    None => Ok(Self::NIL),
+                }
+            }
+        }
+    };
+}
+
+impl_try_from_option_nstring!(&'a [u8]);
+impl_try_from_option_nstring!(Vec<u8>);
+impl_try_from_option_nstring!(&'a str);
+impl_try_from_option_nstring!(String);
+
 impl<'a> From<Literal<'a>> for NString<'a> {
     fn from(value: Literal<'a>) -> Self {
         Self(Some(IString::from(value)))
@@ -928,6 +1422,7 @@ impl<'a> From<Quoted<'a>> for NString<'a> {
 /// ```
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum AString<'a> {
     // `1*ATOM-CHAR` does not allow resp-specials, but `1*ASTRING-CHAR` does ... :-/
@@ -935,6 +1430,47 @@ pub enum AString<'a> {
     String(IString<'a>), // string
 }
 
+impl<'a> AString<'a> {
+    /// Choose the best representation for `value`.
+    ///
+    /// The decision is deterministic and only depends on `value`:
+    ///
+    /// 1. An atom, if `value` is non-empty and consists of `ASTRING-CHAR`s only.
+    /// 2. Otherwise, a quoted string, if `value` consists of `TEXT-CHAR`s only (no CR, LF, or 8-bit
+    ///    data). `"` and `\` are escaped.
+    /// 3. Otherwise, a synchronizing literal. Sending a literal requires a command continuation
+    ///    request from the server (unless changed to a non-synchronizing literal).
+    ///
+    /// Fails when `value` contains a NUL byte, which can't be represented at all.
+    ///
+    /// ```rust
+    /// use imap_types::core::{AString, IString};
+    ///
+    /// assert!(matches!(
+    ///     AString::best(&b"alice"[..]).unwrap(),
+    ///     AString::Atom(_)
+    /// ));
+    /// assert!(matches!(
+    ///     AString::best(&b"Alice Doe"[..]).unwrap(),
+    ///     AString::String(IString::Quoted(_))
+    /// ));
+    /// assert!(matches!(
+    ///     AString::best(&b"Alice\r\nDoe"[..]).unwrap(),
+    ///     AString::String(IString::Literal(_))
+    /// ));
+    /// assert!(AString::best(&b"\x00"[..]).is_err());
+    /// ```
+    pub fn best<V>(value: V) -> Result<Self, ValidationError>
+    where
+        V: Into<Cow<'a, [u8]>>,
+    {
+        match value.into() {
+            Cow::Borrowed(value) => Self::try_from(value),
+            Cow::Owned(value) => Self::try_from(value),
+        }
+    }
+}
+
 impl<'a> TryFrom<&'a [u8]> for AString<'a> {
     type Error = ValidationError;
 
@@ -1042,13 +1578,14 @@ impl AsRef<[u8]> for AString<'_> {
 /// resp-specials   = "]"
 /// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(feature = "serde", serde(try_from = "String"))]
 #[derive(PartialEq, Eq, Hash, Clone, ToStatic)]
 pub struct Tag<'a>(pub(crate) Cow<'a, str>);
 
 // We want a slightly more dense `Debug` implementation.
 impl Debug for Tag<'_> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         write!(f, "Tag({:?})", self.0)
     }
 }
@@ -1148,12 +1685,48 @@ impl AsRef<str> for Tag<'_> {
     }
 }
 
+/// Source of tags, e.g., for automatic tag generation in a codec.
+///
+/// Implemented for [`TagGenerator`] and all `FnMut() -> Tag<'static>` closures.
+pub trait GenerateTag {
+    /// Generate the next tag.
+    ///
+    /// Implementations should never return the same tag twice (in a single connection).
+    fn generate(&mut self) -> Tag<'static>;
+}
+
+impl<F> GenerateTag for F
+where
+    F: FnMut() -> Tag<'static>,
+{
+    fn generate(&mut self) -> Tag<'static> {
+        self()
+    }
+}
+
+/// Generator for unique and unpredictable tags.
+///
+/// A `TagGenerator` is `Send + Sync` and can be shared, e.g., in an `Arc`, because
+/// [`TagGenerator::generate`] only requires a shared reference.
+///
+/// # Example
+///
+/// ```rust
+/// use imap_types::core::TagGenerator;
+///
+/// let generator = TagGenerator::with_prefix("A").unwrap().width(4).random(0);
+///
+/// assert_eq!(generator.generate().as_ref(), "A0000");
+/// assert_eq!(generator.generate().as_ref(), "A0001");
+/// ```
 #[cfg(feature = "tag_generator")]
 #[cfg_attr(docsrs, doc(cfg(feature = "tag_generator")))]
 #[derive(Debug)]
 pub struct TagGenerator {
-    global: usize,
-    counter: u64,
+    prefix: String,
+    width: usize,
+    random: usize,
+    counter: AtomicU64,
 }
 
 #[cfg(feature = "tag_generator")]
@@ -1165,32 +1738,74 @@ impl TagGenerator {
     pub fn new() -> TagGenerator {
         // There is no synchronization required and we only care about each thread seeing a unique value.
         let global = GLOBAL_TAG_GENERATOR_COUNT.fetch_add(1, Ordering::Relaxed);
-        let counter = 0;
 
-        TagGenerator { global, counter }
+        TagGenerator {
+            prefix: format!("{global}."),
+            width: 0,
+            // Minimize randomness lending the library for security analysis.
+            random: if cfg!(debug_assertions) { 0 } else { 8 },
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    /// Generate an instance of a `TagGenerator` using a custom `prefix`.
+    ///
+    /// Note: Unlike with [`TagGenerator::new`], the prefix is not unique. Tags from different
+    /// generators with the same prefix are only distinguishable by their random part.
+    pub fn with_prefix(prefix: impl Into<String>) -> Result<TagGenerator, ValidationError> {
+        let prefix = prefix.into();
+        Tag::validate(&prefix)?;
+
+        Ok(TagGenerator {
+            prefix,
+            ..TagGenerator::new()
+        })
+    }
+
+    /// Pad the counter with zeros to (at least) `width` digits.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Append `length` random alphanumeric characters (0 disables the random part).
+    ///
+    /// Defaults to 8 in release builds and 0 in debug builds.
+    pub fn random(mut self, length: usize) -> Self {
+        self.random = length;
+        self
     }
 
     /// Generate a unique `Tag`
     ///
-    /// The tag has the form `<Instance>.<Counter>.<Random>`, and is guaranteed to be unique and not
-    /// guessable ("forward-secure").
+    /// The tag has the form `<Prefix><Counter>.<Random>`, and is guaranteed to be unique and not
+    /// guessable ("forward-secure"). By default, `<Prefix>` is `<Instance>.`.
     ///
     /// Rational: `Instance` and `Counter` improve IMAP trace readability.
-    /// The non-guessable `Random` hampers protocol-confusion attacks (to a limiting extend).
-    pub fn generate(&mut self) -> Tag<'static> {
-        #[cfg(not(debug_assertions))]
-        let inner = {
-            let token = Alphanumeric.sample_string(&mut rand::thread_rng(), 8);
-            format!("{}.{}.{token}", self.global, self.counter)
+    /// The non-guessable `Random` hampers protocol-confusion attacks (to a limiting extend). It is
+    /// drawn from a cryptographically secure random number generator.
+    pub fn generate(&self) -> Tag<'static> {
+        let counter = self.counter.fetch_add(1, Ordering::Relaxed);
+
+        let inner = if self.random > 0 {
+            let token = Alphanumeric.sample_string(&mut rand::thread_rng(), self.random);
+            format!(
+                "{}{counter:0width$}.{token}",
+                self.prefix,
+                width = self.width
+            )
+        } else {
+            format!("{}{counter:0width$}", self.prefix, width = self.width)
         };
 
-        // Minimize randomness lending the library for security analysis.
-        #[cfg(debug_assertions)]
-        let inner = format!("{}.{}", self.global, self.counter);
+        Tag::unvalidated(inner)
+    }
+}
 
-        let tag = Tag::unvalidated(inner);
-        self.counter = self.counter.wrapping_add(1);
-        tag
+#[cfg(feature = "tag_generator")]
+impl GenerateTag for TagGenerator {
+    fn generate(&mut self) -> Tag<'static> {
+        TagGenerator::generate(self)
     }
 }
 
@@ -1215,19 +1830,20 @@ impl TagGenerator {
 /// LF        = %x0A                        ; linefeed
 /// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(feature = "serde", serde(try_from = "String"))]
 #[derive(PartialEq, Eq, Hash, Clone, ToStatic)]
 pub struct Text<'a>(pub(crate) Cow<'a, str>);
 
 // We want a slightly more dense `Debug` implementation.
 impl Debug for Text<'_> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         write!(f, "Text({:?})", self.0)
     }
 }
 
 impl Display for Text<'_> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         write!(f, "{}", self.0.as_ref())
     }
 }
@@ -1278,6 +1894,89 @@ impl<'a> Text<'a> {
 
         Self(inner)
     }
+
+    /// Constructs a text that may contain 8-bit characters without validation.
+    ///
+    /// RFC 3501 only allows 7-bit characters. However, some servers send raw UTF-8 in response
+    /// texts. This constructor is meant for parsers that want to preserve such content.
+    ///
+    /// # Warning: IMAP conformance
+    ///
+    /// The caller must ensure that `inner` is valid according to [`Self::validate`] except for
+    /// 8-bit characters. Do not call this constructor with untrusted data.
+    ///
+    /// Note: This method will `panic!` on wrong input in debug builds.
+    pub fn unvalidated_8bit<C>(inner: C) -> Self
+    where
+        C: Into<Cow<'a, str>>,
+    {
+        let inner = inner.into();
+
+        #[cfg(debug_assertions)]
+        assert!(!inner.is_empty() && inner.bytes().all(|b| is_text_char(b) || b >= 0x80));
+
+        Self(inner)
+    }
+
+    /// Constructs a text by replacing forbidden characters.
+    ///
+    /// Line breaks (CR, LF, or CRLF) are replaced with a single space. All other forbidden
+    /// characters, e.g., NUL or non-ASCII characters, are replaced with `?`. An empty input
+    /// results in `...`. A leading `[` is replaced with `?`, too, because it would be confused
+    /// with a response code.
+    ///
+    /// Useful to put arbitrary (untrusted) strings, e.g., error messages, into status responses.
+    ///
+    /// ```rust
+    /// use imap_types::core::Text;
+    ///
+    /// let text = Text::sanitize("Can't open \"Ünïcödé\":\r\nPermission denied");
+    /// assert_eq!(text.inner(), "Can't open \"?n?c?d?\": Permission denied");
+    /// ```
+    pub fn sanitize<C>(inner: C) -> Self
+    where
+        C: Into<Cow<'a, str>>,
+    {
+        let inner = inner.into();
+
+        if inner.is_empty() {
+            return Self(Cow::Borrowed("..."));
+        }
+
+        let mut sanitized = sanitize_text(inner);
+
+        if sanitized.starts_with('[') {
+            sanitized.to_mut().replace_range(..1, "?");
+        }
+
+        Self(sanitized)
+    }
+}
+
+/// Replace line breaks with a space and all other non-`TEXT-CHAR`s with `?`.
+///
+/// Borrowed input is only copied when something needs to be replaced.
+fn sanitize_text(inner: Cow<str>) -> Cow<str> {
+    if inner.bytes().all(is_text_char) {
+        return inner;
+    }
+
+    let mut sanitized = String::with_capacity(inner.len());
+    let mut chars = inner.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' => {
+                let _ = chars.next_if_eq(&'\n');
+                sanitized.push(' ');
+            }
+            '\n' => sanitized.push(' '),
+            c if c.is_ascii() && is_text_char(c as u8) => sanitized.push(c),
+            _ => sanitized.push('?'),
+        }
+    }
+
+    Cow::Owned(sanitized)
 }
 
 impl<'a> TryFrom<&'a [u8]> for Text<'a> {
@@ -1342,6 +2041,7 @@ impl AsRef<str> for Text<'_> {
 /// DQUOTE          =  %x22                       ; " (Double Quote)
 /// ```
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(feature = "serde", serde(try_from = "char"))]
 #[derive(Copy, Debug, PartialEq, Eq, Hash, Clone, ToStatic)]
 pub struct QuotedChar(char);
@@ -1417,6 +2117,7 @@ impl TryFrom<char> for QuotedChar {
 /// ```
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum Charset<'a> {
     Atom(Atom<'a>),
@@ -1496,6 +2197,7 @@ impl AsRef<str> for Charset<'_> {
 
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Clone, Debug, Eq, Hash, PartialEq, ToStatic)]
 pub enum NString8<'a> {
     NString(NString<'a>),
@@ -1516,11 +2218,27 @@ pub enum NString8<'a> {
 #[derive(Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct VecN<T, const N: usize>(pub(crate) Vec<T>);
 
+#[cfg(feature = "schemars")]
+impl<T, const N: usize> JsonSchema for VecN<T, N>
+where
+    T: JsonSchema,
+{
+    fn schema_name() -> String {
+        format!("Vec{}_{}", N, T::schema_name())
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        let mut schema = <Vec<T>>::json_schema(gen).into_object();
+        schema.array().min_items = Some(N as u32);
+        schema.into()
+    }
+}
+
 impl<T, const N: usize> Debug for VecN<T, N>
 where
     T: Debug,
 {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         self.0.fmt(f)?;
         match N {
             0 => write!(f, "*"),
@@ -1625,6 +2343,84 @@ mod tests {
     use rand::random;
 
     use super::*;
+    use crate::response::Status;
+
+    #[test]
+    fn test_conversion_nstring() {
+        assert_eq!(NString::try_from(None::<&str>).unwrap(), NString::NIL);
+        assert_eq!(
+            NString::try_from(Some("alice")).unwrap(),
+            NString::try_from("alice").unwrap()
+        );
+        assert_eq!(
+            NString::try_from(Some(b"a\r\nb".to_vec())).unwrap(),
+            NString(Some(IString::Literal(Literal::try_from("a\r\nb").unwrap())))
+        );
+        assert!(NString::try_from(Some("\x00")).is_err());
+
+        assert_eq!(
+            NString::from_display(1234).unwrap(),
+            NString::try_from("1234").unwrap()
+        );
+
+        let nstring = NString::try_from(&b"\xffabc"[..]).unwrap();
+        assert_eq!(nstring.as_bytes(), Some(&b"\xffabc"[..]));
+        assert_eq!(nstring.as_str_lossy().unwrap(), "\u{fffd}abc");
+        assert_eq!(NString::NIL.as_bytes(), None);
+        assert_eq!(NString::NIL.as_str_lossy(), None);
+    }
+
+    #[test]
+    fn test_best_representation() {
+        let tests: [(&[u8], &str); 7] = [
+            (b"alice", "atom"),
+            (b"INBOX.Sent]", "atom"),
+            (b"", "quoted"),
+            (b"Alice Doe", "quoted"),
+            (b"\"(x)\"", "quoted"),
+            (b"a\r\nb", "literal"),
+            (b"\xc3\xa4", "literal"),
+        ];
+
+        for (test, expected) in tests {
+            let got = match AString::best(test).unwrap() {
+                AString::Atom(_) => "atom",
+                AString::String(IString::Quoted(_)) => "quoted",
+                AString::String(IString::Literal(literal)) => {
+                    assert_eq!(literal.mode(), LiteralMode::Sync);
+                    "literal"
+                }
+            };
+            assert_eq!(got, expected, "{:?}", test);
+            assert_eq!(AString::best(test.to_vec()).unwrap().as_ref(), test);
+
+            let got = match IString::best(test).unwrap() {
+                IString::Quoted(_) => "quoted",
+                IString::Literal(_) => "literal",
+            };
+            assert_eq!(got, expected.replace("atom", "quoted"), "{:?}", test);
+        }
+
+        assert!(AString::best(&b"\x00"[..]).is_err());
+        assert!(IString::best(b"\x00".to_vec()).is_err());
+    }
+
+    #[test]
+    fn test_conversion_istring_quoted_or_literal() {
+        assert!(matches!(
+            IString::quoted_or_literal(&b"abc"[..]).unwrap(),
+            IString::Quoted(_)
+        ));
+        assert!(matches!(
+            IString::quoted_or_literal(b"a\nb".to_vec()).unwrap(),
+            IString::Literal(_)
+        ));
+        assert!(IString::quoted_or_literal(&b"\x00"[..]).is_err());
+
+        let istring = IString::quoted_or_literal(&b"abc"[..]).unwrap();
+        assert_eq!(istring.as_bytes(), b"abc");
+        assert_eq!(istring.as_str_lossy(), "abc");
+    }
 
     #[test]
     fn test_conversion_atom() {
@@ -2036,6 +2832,46 @@ mod tests {
         assert!(VecN::<u8, 2>::try_from(vec![1, 2]).is_ok());
     }
 
+    #[test]
+    fn test_sanitize() {
+        let tests = [
+            ("", "..."),
+            ("Hello, world!", "Hello, world!"),
+            ("a\r\nb", "a b"),
+            ("a\rb\nc", "a b c"),
+            ("a\r\n\r\nb", "a  b"),
+            ("a\x00b", "a?b"),
+            ("Grüße", "Gr??e"),
+        ];
+
+        for (test, expected) in tests {
+            let text = Text::sanitize(test);
+            assert_eq!(text.inner(), expected);
+            assert!(Text::validate(text.inner()).is_ok());
+
+            let quoted = Quoted::sanitize(test);
+            if test.is_empty() {
+                assert_eq!(quoted.inner(), "");
+            } else {
+                assert_eq!(quoted.inner(), expected);
+            }
+            assert!(Quoted::validate(quoted.inner()).is_ok());
+        }
+
+        // Valid input is not copied.
+        assert!(matches!(
+            Text::sanitize("Hello").into_inner(),
+            Cow::Borrowed("Hello")
+        ));
+
+        // A leading `[` would be confused with a response code.
+        let text = Text::sanitize("[ALERT] Hello");
+        assert_eq!(text.inner(), "?ALERT] Hello");
+        assert!(Status::ok(None, None, text).is_ok());
+        assert_eq!(Text::sanitize("a [b]").inner(), "a [b]");
+        assert_eq!(Quoted::sanitize("[b]").inner(), "[b]");
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_deserialization_text() {
@@ -2090,6 +2926,48 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_literal_into_shared() {
+        let buffer = bytes::Bytes::from_static(b"A1 APPEND inbox {5}\r\nhello\r\n");
+
+        let literal = Literal::try_from(&buffer[21..26])
+            .unwrap()
+            .into_shared(&buffer);
+        assert_eq!(literal.data(), b"hello");
+        // The data is shared with (and not copied from) `buffer`.
+        assert_eq!(literal.data().as_ptr(), buffer[21..].as_ptr());
+        // ... even after `into_static`.
+        let literal = literal.into_static();
+        assert_eq!(literal.data().as_ptr(), buffer[21..].as_ptr());
+
+        // Data not borrowed from `buffer` is copied.
+        let other = b"hello".to_vec();
+        let literal = Literal::try_from(other.as_slice())
+            .unwrap()
+            .into_shared(&buffer);
+        assert_eq!(literal.data(), b"hello");
+        assert_ne!(literal.data().as_ptr(), buffer[21..].as_ptr());
+        assert_ne!(literal.data().as_ptr(), other.as_ptr());
+    }
+
+    #[test]
+    fn test_atom_into_static() {
+        let input = String::from("$Junk $junk");
+        let (junk, other) = input.split_once(' ').unwrap();
+
+        let junk = Atom::try_from(junk).unwrap().into_static();
+        let other = Atom::try_from(other).unwrap().to_static();
+        assert_eq!(junk.as_ref(), "$Junk");
+        assert_eq!(other.as_ref(), "$junk");
+
+        // Well-known atoms don't need an allocation.
+        #[cfg(feature = "perf")]
+        assert!(matches!(junk.0, Cow::Borrowed(_)));
+        // Other atoms are copied (even when only the case differs.)
+        assert!(matches!(other.0, Cow::Owned(_)));
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_deserialization_literal() {
@@ -2101,7 +2979,7 @@ mod tests {
         assert_eq!(
             literal,
             Literal {
-                data: Cow::Borrowed(b"\x01\x02\x03"),
+                data: LiteralData::Borrowed(b"\x01\x02\x03"),
                 mode: LiteralMode::Sync
             }
         );
@@ -2196,7 +3074,7 @@ mod tests {
                 let handle = s.spawn(move || {
                     let mut tags = Vec::with_capacity(INVOCATIONS);
 
-                    let mut generator = TagGenerator::new();
+                    let generator = TagGenerator::new();
                     thread::sleep(Duration::from_millis(random::<u8>() as u64));
 
                     for _ in 1..=INVOCATIONS {
@@ -2222,4 +3100,46 @@ mod tests {
             }
         });
     }
+
+    #[cfg(feature = "tag_generator")]
+    #[test]
+    fn test_generator_shared() {
+        let generator = TagGenerator::with_prefix("A").unwrap().random(8);
+
+        let tags: Vec<_> = thread::scope(|s| {
+            let handles: Vec<_> = (0..10)
+                .map(|_| s.spawn(|| (0..100).map(|_| generator.generate()).collect::<Vec<_>>()))
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        let mut set = BTreeSet::new();
+        for tag in tags {
+            let (counter, token) = tag.as_ref()[1..].split_once('.').unwrap();
+            assert_eq!(token.len(), 8);
+            assert!(
+                set.insert(counter.parse::<u64>().unwrap()),
+                "duplicate tag found"
+            );
+        }
+        assert_eq!(set.len(), 1000);
+    }
+
+    #[cfg(feature = "tag_generator")]
+    #[test]
+    fn test_generator_config() {
+        assert!(TagGenerator::with_prefix("").is_err());
+        assert!(TagGenerator::with_prefix("A+").is_err());
+
+        let mut generator = TagGenerator::with_prefix("imap-")
+            .unwrap()
+            .width(3)
+            .random(0);
+        assert_eq!(generator.generate().as_ref(), "imap-000");
+        assert_eq!(GenerateTag::generate(&mut generator).as_ref(), "imap-001");
+    }
 }
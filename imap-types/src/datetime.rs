@@ -1,15 +1,18 @@
 //! Date and time-related types.
 
-use std::fmt::{Debug, Formatter};
+use core::fmt::{Debug, Formatter};
 
 use bounded_static::{IntoBoundedStatic, ToBoundedStatic};
 use chrono::{Datelike, FixedOffset};
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use crate::datetime::error::{DateTimeError, NaiveDateError};
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(feature = "serde", serde(try_from = "chrono::DateTime<FixedOffset>"))]
 #[derive(Clone, Eq, PartialEq, Hash)]
 pub struct DateTime(chrono::DateTime<FixedOffset>);
@@ -51,6 +54,51 @@ impl DateTime {
 
         Self(value)
     }
+
+    /// Constructs a date time from a UNIX timestamp (in seconds) and an `offset` (in seconds east
+    /// of UTC).
+    ///
+    /// Fails when the date time isn't representable in IMAP, e.g., when the year isn't within
+    /// 0 to 9999, or the offset isn't a multiple of 60 seconds.
+    ///
+    /// ```rust
+    /// use imap_types::datetime::DateTime;
+    ///
+    /// let date_time = DateTime::from_unix_timestamp(1_700_000_000, 3600).unwrap();
+    /// assert_eq!(date_time.as_ref().to_rfc3339(), "2023-11-14T23:13:20+01:00");
+    /// ```
+    pub fn from_unix_timestamp(seconds: i64, offset: i32) -> Result<Self, DateTimeError> {
+        let offset =
+            FixedOffset::east_opt(offset).ok_or(DateTimeError::OffsetOutOfRange { got: offset })?;
+
+        let value = chrono::DateTime::from_timestamp(seconds, 0)
+            .ok_or(DateTimeError::TimestampOutOfRange { got: seconds })?
+            .with_timezone(&offset);
+
+        Self::try_from(value)
+    }
+
+    /// Constructs a date time from the current (local) time.
+    ///
+    /// Sub-second precision is discarded. When the local offset isn't representable in IMAP, UTC is
+    /// used instead.
+    #[cfg(feature = "clock")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "clock")))]
+    pub fn now() -> Self {
+        use chrono::Timelike;
+
+        // Safety: `unwrap` can't panic because 0 is a valid nanosecond.
+        let now = chrono::Local::now()
+            .fixed_offset()
+            .with_nanosecond(0)
+            .unwrap();
+
+        match Self::try_from(now) {
+            Ok(now) => now,
+            // Safety: `unwrap` can't panic because 0 is a valid offset.
+            Err(_) => Self::unvalidated(now.with_timezone(&FixedOffset::east_opt(0).unwrap())),
+        }
+    }
 }
 
 impl TryFrom<chrono::DateTime<FixedOffset>> for DateTime {
@@ -63,8 +111,96 @@ impl TryFrom<chrono::DateTime<FixedOffset>> for DateTime {
     }
 }
 
+#[cfg(feature = "time")]
+#[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+impl TryFrom<time::OffsetDateTime> for DateTime {
+    type Error = DateTimeError;
+
+    fn try_from(value: time::OffsetDateTime) -> Result<Self, Self::Error> {
+        if !(0..=9999).contains(&value.year()) {
+            return Err(DateTimeError::YearOutOfRange { got: value.year() });
+        }
+
+        let offset = value.offset().whole_seconds();
+        let offset =
+            FixedOffset::east_opt(offset).ok_or(DateTimeError::OffsetOutOfRange { got: offset })?;
+
+        // Safety: `unwrap` can't panic because the timestamp is in the (validated) year range and
+        // the nanoseconds are always less than 10^9.
+        let value = chrono::DateTime::from_timestamp(value.unix_timestamp(), value.nanosecond())
+            .unwrap()
+            .with_timezone(&offset);
+
+        Self::try_from(value)
+    }
+}
+
+#[cfg(feature = "time")]
+#[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+impl From<DateTime> for time::OffsetDateTime {
+    fn from(value: DateTime) -> Self {
+        // Safety: `unwrap`s can't panic because a `DateTime` is within year 0 to 9999 and has an
+        // offset of less than 24 hours.
+        let offset =
+            time::UtcOffset::from_whole_seconds(value.0.offset().local_minus_utc()).unwrap();
+
+        time::OffsetDateTime::from_unix_timestamp(value.0.timestamp())
+            .unwrap()
+            .to_offset(offset)
+    }
+}
+
+#[cfg(feature = "jiff")]
+#[cfg_attr(docsrs, doc(cfg(feature = "jiff")))]
+impl TryFrom<jiff::Zoned> for DateTime {
+    type Error = DateTimeError;
+
+    fn try_from(value: jiff::Zoned) -> Result<Self, Self::Error> {
+        let year = i32::from(value.year());
+        if !(0..=9999).contains(&year) {
+            return Err(DateTimeError::YearOutOfRange { got: year });
+        }
+
+        let offset = value.offset().seconds();
+        let offset =
+            FixedOffset::east_opt(offset).ok_or(DateTimeError::OffsetOutOfRange { got: offset })?;
+
+        // Note: `jiff` uses negative nanoseconds for negative timestamps.
+        let timestamp = value.timestamp();
+        let (seconds, nanoseconds) = match timestamp.subsec_nanosecond() {
+            nanoseconds if nanoseconds < 0 => {
+                (timestamp.as_second() - 1, nanoseconds + 1_000_000_000)
+            }
+            nanoseconds => (timestamp.as_second(), nanoseconds),
+        };
+
+        // Safety: `unwrap` can't panic because the timestamp is in the (validated) year range and
+        // the nanoseconds are in `0..10^9`.
+        let value = chrono::DateTime::from_timestamp(seconds, nanoseconds as u32)
+            .unwrap()
+            .with_timezone(&offset);
+
+        Self::try_from(value)
+    }
+}
+
+#[cfg(feature = "jiff")]
+#[cfg_attr(docsrs, doc(cfg(feature = "jiff")))]
+impl From<DateTime> for jiff::Zoned {
+    /// Convert into a [`jiff::Zoned`] with a fixed offset time zone.
+    fn from(value: DateTime) -> Self {
+        // Safety: `unwrap`s can't panic because a `DateTime` is within year 0 to 9999 and has an
+        // offset of less than 24 hours.
+        let offset = jiff::tz::Offset::from_seconds(value.0.offset().local_minus_utc()).unwrap();
+
+        jiff::Timestamp::from_second(value.0.timestamp())
+            .unwrap()
+            .to_zoned(jiff::tz::TimeZone::fixed(offset))
+    }
+}
+
 impl Debug for DateTime {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         Debug::fmt(&self.0, f)
     }
 }
@@ -92,6 +228,7 @@ impl ToBoundedStatic for DateTime {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(feature = "serde", serde(try_from = "chrono::NaiveDate"))]
 #[derive(Clone, Eq, PartialEq, Hash)]
 pub struct NaiveDate(chrono::NaiveDate);
@@ -133,8 +270,83 @@ impl TryFrom<chrono::NaiveDate> for NaiveDate {
     }
 }
 
+#[cfg(feature = "time")]
+#[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+impl TryFrom<time::Date> for NaiveDate {
+    type Error = NaiveDateError;
+
+    fn try_from(value: time::Date) -> Result<Self, Self::Error> {
+        if !(0..=9999).contains(&value.year()) {
+            return Err(NaiveDateError::YearOutOfRange { got: value.year() });
+        }
+
+        // Safety: `unwrap` can't panic because a `time::Date` is always a valid date.
+        let value = chrono::NaiveDate::from_ymd_opt(
+            value.year(),
+            u8::from(value.month()).into(),
+            value.day().into(),
+        )
+        .unwrap();
+
+        Self::try_from(value)
+    }
+}
+
+#[cfg(feature = "time")]
+#[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+impl From<NaiveDate> for time::Date {
+    fn from(value: NaiveDate) -> Self {
+        // Safety: `unwrap`s can't panic because a `NaiveDate` is always a valid date within year 0
+        // to 9999.
+        time::Date::from_calendar_date(
+            value.0.year(),
+            time::Month::try_from(value.0.month() as u8).unwrap(),
+            value.0.day() as u8,
+        )
+        .unwrap()
+    }
+}
+
+#[cfg(feature = "jiff")]
+#[cfg_attr(docsrs, doc(cfg(feature = "jiff")))]
+impl TryFrom<jiff::civil::Date> for NaiveDate {
+    type Error = NaiveDateError;
+
+    fn try_from(value: jiff::civil::Date) -> Result<Self, Self::Error> {
+        let year = i32::from(value.year());
+        if !(0..=9999).contains(&year) {
+            return Err(NaiveDateError::YearOutOfRange { got: year });
+        }
+
+        // Safety: `unwrap` can't panic because a `jiff::civil::Date` is always a valid date.
+        let value = chrono::NaiveDate::from_ymd_opt(
+            year,
+            value.month().unsigned_abs().into(),
+            value.day().unsigned_abs().into(),
+        )
+        .unwrap();
+
+        Self::try_from(value)
+    }
+}
+
+#[cfg(feature = "jiff")]
+#[cfg_attr(docsrs, doc(cfg(feature = "jiff")))]
+impl From<NaiveDate> for jiff::civil::Date {
+    fn from(value: NaiveDate) -> Self {
+        // Safety: `unwrap` can't panic because a `NaiveDate` is always a valid date within year 0
+        // to 9999.
+        jiff::civil::Date::new(
+            value.0.year() as i16,
+            value.0.month() as i8,
+            value.0.day() as i8,
+        )
+        .unwrap()
+    }
+}
+
 impl Debug for NaiveDate {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         Debug::fmt(&self.0, f)
     }
 }
@@ -173,6 +385,10 @@ pub mod error {
         UnalignedNanoSeconds { got: u32 },
         #[error("expected `offset % 60 == 0`, got {got}")]
         UnalignedOffset { got: i32 },
+        #[error("expected `-86400 < offset < 86400`, got {got}")]
+        OffsetOutOfRange { got: i32 },
+        #[error("timestamp out of range, got {got}")]
+        TimestampOutOfRange { got: i64 },
     }
 
     #[derive(Clone, Debug, Eq, Error, Hash, Ord, PartialEq, PartialOrd)]
@@ -262,6 +478,133 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_from_unix_timestamp() {
+        assert_eq!(
+            DateTime::from_unix_timestamp(0, -3600).unwrap(),
+            DateTime::try_from(
+                chrono::FixedOffset::east_opt(-3600)
+                    .unwrap()
+                    .with_ymd_and_hms(1969, 12, 31, 23, 0, 0)
+                    .unwrap()
+            )
+            .unwrap()
+        );
+
+        assert_eq!(
+            DateTime::from_unix_timestamp(253402300800, 0),
+            Err(DateTimeError::YearOutOfRange { got: 10000 })
+        );
+        assert_eq!(
+            DateTime::from_unix_timestamp(0, 30),
+            Err(DateTimeError::UnalignedOffset { got: 30 })
+        );
+        assert_eq!(
+            DateTime::from_unix_timestamp(0, 86400),
+            Err(DateTimeError::OffsetOutOfRange { got: 86400 })
+        );
+        assert_eq!(
+            DateTime::from_unix_timestamp(i64::MAX, 0),
+            Err(DateTimeError::TimestampOutOfRange { got: i64::MAX })
+        );
+    }
+
+    #[cfg(feature = "clock")]
+    #[test]
+    fn test_now() {
+        let now = DateTime::now();
+        assert!(DateTime::validate(now.as_ref()).is_ok());
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn test_conversion_time() {
+        let expected = DateTime::try_from(
+            chrono::FixedOffset::east_opt(-7 * 3600)
+                .unwrap()
+                .with_ymd_and_hms(2023, 2, 28, 12, 34, 56)
+                .unwrap(),
+        )
+        .unwrap();
+
+        let value = time::Date::from_calendar_date(2023, time::Month::February, 28)
+            .unwrap()
+            .with_hms(12, 34, 56)
+            .unwrap()
+            .assume_offset(time::UtcOffset::from_hms(-7, 0, 0).unwrap());
+
+        assert_eq!(DateTime::try_from(value).unwrap(), expected);
+        assert_eq!(time::OffsetDateTime::from(expected.clone()), value);
+        assert_eq!(
+            time::OffsetDateTime::from(expected).offset(),
+            value.offset()
+        );
+
+        assert_eq!(
+            DateTime::try_from(value.replace_nanosecond(1).unwrap()),
+            Err(DateTimeError::UnalignedNanoSeconds { got: 1 })
+        );
+        assert_eq!(
+            DateTime::try_from(value.replace_offset(time::UtcOffset::from_hms(0, 0, 1).unwrap())),
+            Err(DateTimeError::UnalignedOffset { got: 1 })
+        );
+        assert_eq!(
+            DateTime::try_from(value.replace_offset(time::UtcOffset::from_hms(25, 0, 0).unwrap())),
+            Err(DateTimeError::OffsetOutOfRange { got: 90000 })
+        );
+
+        let date = time::Date::from_calendar_date(2023, time::Month::February, 28).unwrap();
+        let expected =
+            NaiveDate::try_from(chrono::NaiveDate::from_ymd_opt(2023, 2, 28).unwrap()).unwrap();
+        assert_eq!(NaiveDate::try_from(date).unwrap(), expected);
+        assert_eq!(time::Date::from(expected), date);
+        assert_eq!(
+            NaiveDate::try_from(date.replace_year(-1).unwrap()),
+            Err(NaiveDateError::YearOutOfRange { got: -1 })
+        );
+    }
+
+    #[cfg(feature = "jiff")]
+    #[test]
+    fn test_conversion_jiff() {
+        let expected = DateTime::try_from(
+            chrono::FixedOffset::east_opt(-7 * 3600)
+                .unwrap()
+                .with_ymd_and_hms(2023, 2, 28, 12, 34, 56)
+                .unwrap(),
+        )
+        .unwrap();
+
+        let value = jiff::civil::date(2023, 2, 28)
+            .at(12, 34, 56, 0)
+            .to_zoned(jiff::tz::TimeZone::fixed(jiff::tz::offset(-7)))
+            .unwrap();
+
+        assert_eq!(DateTime::try_from(value.clone()).unwrap(), expected);
+        let got = jiff::Zoned::from(expected);
+        assert_eq!(got.timestamp(), value.timestamp());
+        assert_eq!(got.offset(), value.offset());
+
+        let value = jiff::civil::date(2023, 2, 28)
+            .at(12, 34, 56, 1)
+            .to_zoned(jiff::tz::TimeZone::UTC)
+            .unwrap();
+        assert_eq!(
+            DateTime::try_from(value),
+            Err(DateTimeError::UnalignedNanoSeconds { got: 1 })
+        );
+
+        let date = jiff::civil::date(2023, 2, 28);
+        let expected =
+            NaiveDate::try_from(chrono::NaiveDate::from_ymd_opt(2023, 2, 28).unwrap()).unwrap();
+        assert_eq!(NaiveDate::try_from(date).unwrap(), expected);
+        assert_eq!(jiff::civil::Date::from(expected), date);
+        assert_eq!(
+            NaiveDate::try_from(jiff::civil::date(-1, 1, 1)),
+            Err(NaiveDateError::YearOutOfRange { got: -1 })
+        );
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_deserialization_date_time() {
@@ -1,15 +1,28 @@
 //! Envelope-related types.
 
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::{Display, Formatter};
+
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::core::NString;
+use crate::{
+    core::NString,
+    error::{ValidationError, ValidationErrorKind},
+};
 
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct Envelope<'a> {
     pub date: NString<'a>,
@@ -24,9 +37,151 @@ pub struct Envelope<'a> {
     pub message_id: NString<'a>,
 }
 
+impl<'a> Envelope<'a> {
+    /// Start building an envelope.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use imap_types::envelope::Envelope;
+    ///
+    /// let envelope = Envelope::builder()
+    ///     .subject("Hello")
+    ///     .from("Alice <alice@example.org>")
+    ///     .to("bob@example.org")
+    ///     .message_id("<1234@example.org>")
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn builder() -> EnvelopeBuilder<'a> {
+        EnvelopeBuilder {
+            envelope: Envelope {
+                date: NString::NIL,
+                subject: NString::NIL,
+                from: Vec::new(),
+                sender: Vec::new(),
+                reply_to: Vec::new(),
+                to: Vec::new(),
+                cc: Vec::new(),
+                bcc: Vec::new(),
+                in_reply_to: NString::NIL,
+                message_id: NString::NIL,
+            },
+            error: None,
+        }
+    }
+}
+
+/// Builder for an [`Envelope`].
+///
+/// Created by [`Envelope::builder`]. Unset fields are `NIL` (or empty). As in IMAP, the sender and
+/// reply-to fields default to the from field. Errors are reported by [`EnvelopeBuilder::build`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvelopeBuilder<'a> {
+    envelope: Envelope<'a>,
+    error: Option<ValidationError>,
+}
+
+macro_rules! impl_builder_nstring {
+    ($($(#[$meta:meta])* $field:ident),*) => {
+        $(
+            $(#[$meta])*
+            pub fn $field<V>(mut self, value: V) -> Self
+            where
+                V: TryInto<NString<'a>, Error = ValidationError>,
+            {
+                if let Some(value) = self.check(value) {
+                    self.envelope.$field = value;
+                }
+                self
+            }
+        )*
+    };
+}
+
+macro_rules! impl_builder_address {
+    ($($(#[$meta:meta])* $field:ident),*) => {
+        $(
+            $(#[$meta])*
+            ///
+            /// Can be called multiple times to add multiple addresses.
+            pub fn $field<V>(mut self, address: V) -> Self
+            where
+                V: TryInto<Address<'a>, Error = ValidationError>,
+            {
+                if let Some(address) = self.check(address) {
+                    self.envelope.$field.push(address);
+                }
+                self
+            }
+        )*
+    };
+}
+
+impl<'a> EnvelopeBuilder<'a> {
+    fn check<T, V>(&mut self, value: V) -> Option<T>
+    where
+        V: TryInto<T, Error = ValidationError>,
+    {
+        match value.try_into() {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.error.get_or_insert(error);
+                None
+            }
+        }
+    }
+
+    impl_builder_nstring!(
+        /// Set the date.
+        date,
+        /// Set the subject.
+        subject,
+        /// Set the message id this message is a reply to.
+        in_reply_to,
+        /// Set the message id.
+        message_id
+    );
+
+    impl_builder_address!(
+        /// Add a from address.
+        from,
+        /// Add a sender address (defaults to the from addresses).
+        sender,
+        /// Add a reply-to address (defaults to the from addresses).
+        reply_to,
+        /// Add a to address.
+        to,
+        /// Add a cc address.
+        cc,
+        /// Add a bcc address.
+        bcc
+    );
+
+    /// Build the envelope or return the first error.
+    pub fn build(self) -> Result<Envelope<'a>, ValidationError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        let mut envelope = self.envelope;
+
+        if envelope.sender.is_empty() {
+            envelope.sender.clone_from(&envelope.from);
+        }
+
+        if envelope.reply_to.is_empty() {
+            envelope.reply_to.clone_from(&envelope.from);
+        }
+
+        Ok(envelope)
+    }
+}
+
 /// An address structure describes an electronic mail address.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 /// TODO(misuse):
 ///
@@ -50,3 +205,356 @@ pub struct Address<'a> {
     /// Host name
     pub host: NString<'a>,
 }
+
+impl<'a> Address<'a> {
+    /// Create an address from a mailbox, e.g., `alice`, and a host, e.g., `example.org`.
+    pub fn new<M, H>(mailbox: M, host: H) -> Result<Self, ValidationError>
+    where
+        M: TryInto<NString<'a>, Error = ValidationError>,
+        H: TryInto<NString<'a>, Error = ValidationError>,
+    {
+        Ok(Self {
+            name: NString::NIL,
+            adl: NString::NIL,
+            mailbox: mailbox.try_into()?,
+            host: host.try_into()?,
+        })
+    }
+
+    /// Create an address with a personal name, e.g., `Alice`.
+    pub fn named<N, M, H>(name: N, mailbox: M, host: H) -> Result<Self, ValidationError>
+    where
+        N: TryInto<NString<'a>, Error = ValidationError>,
+        M: TryInto<NString<'a>, Error = ValidationError>,
+        H: TryInto<NString<'a>, Error = ValidationError>,
+    {
+        Ok(Self {
+            name: name.try_into()?,
+            ..Self::new(mailbox, host)?
+        })
+    }
+
+    /// Is this the start of an [RFC-2822] group, i.e., `<name>:`?
+    ///
+    /// The group name is stored in the mailbox field.
+    pub fn is_group_start(&self) -> bool {
+        self.mailbox.0.is_some() && self.host.0.is_none()
+    }
+
+    /// Is this the end of an [RFC-2822] group, i.e., `;`?
+    pub fn is_group_end(&self) -> bool {
+        self.mailbox.0.is_none()
+    }
+
+    /// Return the personal name, e.g., `Alice`.
+    ///
+    /// For a group start, this returns the group name. Non-UTF-8 data is replaced lossily. Note
+    /// that encoded words, e.g., `=?UTF-8?Q?...?=`, are not decoded.
+    pub fn display_name(&self) -> Option<Cow<'_, str>> {
+        if self.is_group_start() {
+            return nstring_to_str(&self.mailbox);
+        }
+
+        nstring_to_str(&self.name)
+    }
+
+    /// Return the email address, i.e., `mailbox@host`.
+    ///
+    /// Returns `None` for group markers. The local part is quoted when necessary.
+    pub fn email(&self) -> Option<String> {
+        let mailbox = nstring_to_str(&self.mailbox)?;
+        let host = nstring_to_str(&self.host)?;
+
+        let mut email = String::new();
+        if is_dot_atom(&mailbox) {
+            email.push_str(&mailbox);
+        } else {
+            push_quoted(&mut email, &mailbox);
+        }
+        email.push('@');
+        email.push_str(&host);
+
+        Some(email)
+    }
+
+    /// Render an address list, e.g., from an [`Envelope`], as [RFC-5322] string.
+    ///
+    /// Group markers are rendered using the group syntax, e.g., `Friends: alice@example.org;`.
+    /// The source route (`adl`) is obsolete and ignored.
+    ///
+    /// ```rust
+    /// use imap_types::envelope::Address;
+    ///
+    /// let addresses = [
+    ///     Address::try_from("Alice <alice@example.org>").unwrap(),
+    ///     Address::try_from("bob@example.org").unwrap(),
+    /// ];
+    ///
+    /// assert_eq!(
+    ///     Address::render_list(&addresses),
+    ///     "Alice <alice@example.org>, bob@example.org"
+    /// );
+    /// ```
+    pub fn render_list(addresses: &[Address]) -> String {
+        let mut out = String::new();
+        let mut separator = "";
+
+        for address in addresses {
+            if address.is_group_end() {
+                out.push(';');
+                separator = ", ";
+                continue;
+            }
+
+            out.push_str(separator);
+            out.push_str(&address.to_string());
+
+            separator = if address.is_group_start() { " " } else { ", " };
+        }
+
+        out
+    }
+}
+
+/// Render a single address, e.g., `Alice <alice@example.org>`, as [RFC-5322] string.
+///
+/// A group start is rendered as `<name>:`, a group end as `;`.
+impl Display for Address<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        if self.is_group_end() {
+            return f.write_str(";");
+        }
+
+        if self.is_group_start() {
+            let name = self.display_name().unwrap_or_default();
+            return write!(f, "{}:", Phrase(&name));
+        }
+
+        let email = self.email().unwrap_or_default();
+
+        match nstring_to_str(&self.name) {
+            Some(name) if !name.is_empty() => write!(f, "{} <{}>", Phrase(&name), email),
+            _ => f.write_str(&email),
+        }
+    }
+}
+
+fn nstring_to_str<'b>(value: &'b NString) -> Option<Cow<'b, str>> {
+    value
+        .0
+        .as_ref()
+        .map(|value| String::from_utf8_lossy(value.as_ref()))
+}
+
+/// `atext` of RFC 5322 (including non-ASCII characters, see RFC 6532).
+fn is_atext(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(c) || !c.is_ascii()
+}
+
+fn is_dot_atom(value: &str) -> bool {
+    value
+        .split('.')
+        .all(|atom| !atom.is_empty() && atom.chars().all(is_atext))
+}
+
+fn push_quoted(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+}
+
+/// A display name or group name, quoted when necessary.
+struct Phrase<'b>(&'b str);
+
+impl Display for Phrase<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let is_atoms = self
+            .0
+            .split(' ')
+            .all(|atom| !atom.is_empty() && atom.chars().all(is_atext));
+
+        if is_atoms {
+            f.write_str(self.0)
+        } else {
+            let mut quoted = String::new();
+            push_quoted(&mut quoted, self.0);
+            f.write_str(&quoted)
+        }
+    }
+}
+
+/// Parse `mailbox@host` or `Name <mailbox@host>`.
+impl<'a> TryFrom<&'a str> for Address<'a> {
+    type Error = ValidationError;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        let invalid = || ValidationError::new(ValidationErrorKind::Invalid);
+
+        let (name, addr_spec) = match value.trim().strip_suffix('>') {
+            Some(value) => {
+                let (name, addr_spec) = value.rsplit_once('<').ok_or_else(invalid)?;
+                let name = name.trim();
+                let name = name
+                    .strip_prefix('"')
+                    .and_then(|name| name.strip_suffix('"'))
+                    .unwrap_or(name);
+
+                let name = match name {
+                    "" => NString::NIL,
+                    name => NString::try_from(name)?,
+                };
+
+                (name, addr_spec)
+            }
+            None => (NString::NIL, value.trim()),
+        };
+
+        let (mailbox, host) = addr_spec.rsplit_once('@').ok_or_else(invalid)?;
+
+        if mailbox.is_empty() || host.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            name,
+            adl: NString::NIL,
+            mailbox: NString::try_from(mailbox)?,
+            host: NString::try_from(host)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_try_from() {
+        let tests = [
+            ("alice@example.org", None, "alice", "example.org"),
+            ("<alice@example.org>", None, "alice", "example.org"),
+            (
+                "Alice <alice@example.org>",
+                Some("Alice"),
+                "alice",
+                "example.org",
+            ),
+            (
+                "\"Alice Doe\" <alice@example.org>",
+                Some("Alice Doe"),
+                "alice",
+                "example.org",
+            ),
+        ];
+
+        for (test, name, mailbox, host) in tests {
+            let got = Address::try_from(test).unwrap();
+            let expected = match name {
+                Some(name) => Address::named(name, mailbox, host),
+                None => Address::new(mailbox, host),
+            }
+            .unwrap();
+
+            assert_eq!(got, expected);
+        }
+
+        for test in [
+            "",
+            "alice",
+            "alice@",
+            "@example.org",
+            "Alice alice@example.org>",
+        ] {
+            assert!(Address::try_from(test).is_err());
+        }
+    }
+
+    #[test]
+    fn test_address_helpers() {
+        let alice = Address::try_from("\"Doe, Alice\" <alice@example.org>").unwrap();
+        assert_eq!(alice.display_name().unwrap(), "Doe, Alice");
+        assert_eq!(alice.email().unwrap(), "alice@example.org");
+        assert_eq!(alice.to_string(), "\"Doe, Alice\" <alice@example.org>");
+        assert!(!alice.is_group_start());
+        assert!(!alice.is_group_end());
+
+        let bob = Address::new("bob smith", "example.org").unwrap();
+        assert_eq!(bob.display_name(), None);
+        assert_eq!(bob.email().unwrap(), "\"bob smith\"@example.org");
+        assert_eq!(bob.to_string(), "\"bob smith\"@example.org");
+
+        let carol = Address::named("Carol", "carol.c", "example.org").unwrap();
+        assert_eq!(carol.to_string(), "Carol <carol.c@example.org>");
+
+        let group_start = Address {
+            name: NString::NIL,
+            adl: NString::NIL,
+            mailbox: NString::try_from("Friends").unwrap(),
+            host: NString::NIL,
+        };
+        let group_end = Address {
+            name: NString::NIL,
+            adl: NString::NIL,
+            mailbox: NString::NIL,
+            host: NString::NIL,
+        };
+        assert!(group_start.is_group_start());
+        assert!(!group_start.is_group_end());
+        assert_eq!(group_start.display_name().unwrap(), "Friends");
+        assert_eq!(group_start.email(), None);
+        assert!(group_end.is_group_end());
+        assert_eq!(group_end.email(), None);
+
+        let tests: [(&[Address], &str); 4] = [
+            (&[], ""),
+            (
+                core::slice::from_ref(&alice),
+                "\"Doe, Alice\" <alice@example.org>",
+            ),
+            (
+                &[
+                    group_start.clone(),
+                    carol.clone(),
+                    bob.clone(),
+                    group_end.clone(),
+                    alice.clone(),
+                ],
+                "Friends: Carol <carol.c@example.org>, \"bob smith\"@example.org;, \"Doe, Alice\" <alice@example.org>",
+            ),
+            (
+                &[group_start.clone(), group_end.clone()],
+                "Friends:;",
+            ),
+        ];
+
+        for (addresses, expected) in tests {
+            assert_eq!(Address::render_list(addresses), expected);
+        }
+    }
+
+    #[test]
+    fn test_envelope_builder() {
+        let envelope = Envelope::builder()
+            .subject("Hello")
+            .from("alice@example.org")
+            .to("bob@example.org")
+            .to("carol@example.org")
+            .build()
+            .unwrap();
+
+        assert_eq!(envelope.subject, NString::try_from("Hello").unwrap());
+        assert_eq!(envelope.date, NString::NIL);
+        assert_eq!(envelope.from.len(), 1);
+        assert_eq!(envelope.sender, envelope.from);
+        assert_eq!(envelope.reply_to, envelope.from);
+        assert_eq!(envelope.to.len(), 2);
+        assert!(envelope.cc.is_empty());
+
+        assert!(Envelope::builder().from("alice").build().is_err());
+    }
+}
@@ -1,16 +1,25 @@
 //! IMAP extensions.
 
+#[cfg(feature = "ext_acl")]
+pub mod acl;
 pub mod binary;
 pub mod compress;
 #[cfg(feature = "ext_condstore_qresync")]
 pub mod condstore_qresync;
 pub mod enable;
+#[cfg(feature = "ext_gmail")]
+pub mod gmail;
 pub mod idle;
 #[cfg(feature = "ext_metadata")]
 pub mod metadata;
 pub mod r#move;
 pub mod quota;
 pub mod sort;
+pub mod tagged_ext;
 pub mod thread;
 pub mod uidplus;
 pub mod unselect;
+#[cfg(feature = "ext_xapplepushservice")]
+pub mod xapplepushservice;
+#[cfg(feature = "ext_xlist")]
+pub mod xlist;
@@ -0,0 +1,434 @@
+//! IMAP4 Access Control List (ACL) Extension
+//!
+//! This extends ...
+//!
+//! * [`Capability`](crate::response::Capability) with new variants:
+//!
+//!     - [`Capability::Acl`](crate::response::Capability::Acl)
+//!     - [`Capability::Rights`](crate::response::Capability::Rights)
+
+use alloc::vec::Vec;
+use core::{
+    fmt::{Display, Formatter},
+    hash::{Hash, Hasher},
+};
+
+use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ValidationError, ValidationErrorKind};
+
+/// The standard rights defined in RFC 4314, i.e., "lrswipkxtea".
+///
+/// Servers must support all of them, although some rights may be tied together.
+pub const STANDARD_RIGHTS: [Right; 11] = [
+    Right::Lookup,
+    Right::Read,
+    Right::Seen,
+    Right::Write,
+    Right::Insert,
+    Right::Post,
+    Right::CreateMailbox,
+    Right::DeleteMailbox,
+    Right::DeleteMessages,
+    Right::Expunge,
+    Right::Administer,
+];
+
+/// An access right.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ToStatic)]
+pub enum Right {
+    /// `l`: Mailbox is visible to LIST/LSUB commands, SUBSCRIBE mailbox.
+    Lookup,
+    /// `r`: SELECT the mailbox, perform STATUS.
+    Read,
+    /// `s`: Keep seen/unseen information across sessions.
+    Seen,
+    /// `w`: Set or clear flags other than \Seen and \Deleted.
+    Write,
+    /// `i`: Perform APPEND, COPY into mailbox.
+    Insert,
+    /// `p`: Send mail to submission address for mailbox.
+    Post,
+    /// `k`: CREATE new sub-mailboxes.
+    CreateMailbox,
+    /// `x`: DELETE mailbox, old mailbox name in RENAME.
+    DeleteMailbox,
+    /// `t`: Set or clear the \Deleted flag.
+    DeleteMessages,
+    /// `e`: Perform EXPUNGE and expunge as a part of CLOSE.
+    Expunge,
+    /// `a`: Administer (perform SETACL/DELETEACL/GETACL/LISTRIGHTS).
+    Administer,
+    /// `c`: Obsolete "create" right (RFC 2086).
+    ///
+    /// This is a virtual right. See [`VirtualRights`].
+    Create,
+    /// `d`: Obsolete "delete" right (RFC 2086).
+    ///
+    /// This is a virtual right. See [`VirtualRights`].
+    Delete,
+    /// An implementation-defined right (`0`-`9`).
+    Other(RightOther),
+}
+
+/// An implementation-defined right.
+///
+/// It's guaranteed that this type can't represent any right from [`Right`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ToStatic)]
+pub struct RightOther(char);
+
+impl Right {
+    pub fn is_virtual(&self) -> bool {
+        matches!(self, Self::Create | Self::Delete)
+    }
+}
+
+impl TryFrom<char> for Right {
+    type Error = ValidationError;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        Ok(match value {
+            'l' => Self::Lookup,
+            'r' => Self::Read,
+            's' => Self::Seen,
+            'w' => Self::Write,
+            'i' => Self::Insert,
+            'p' => Self::Post,
+            'k' => Self::CreateMailbox,
+            'x' => Self::DeleteMailbox,
+            't' => Self::DeleteMessages,
+            'e' => Self::Expunge,
+            'a' => Self::Administer,
+            'c' => Self::Create,
+            'd' => Self::Delete,
+            '0'..='9' => Self::Other(RightOther(value)),
+            _ => {
+                return Err(ValidationError::new(ValidationErrorKind::Invalid));
+            }
+        })
+    }
+}
+
+impl From<Right> for char {
+    fn from(value: Right) -> Self {
+        match value {
+            Right::Lookup => 'l',
+            Right::Read => 'r',
+            Right::Seen => 's',
+            Right::Write => 'w',
+            Right::Insert => 'i',
+            Right::Post => 'p',
+            Right::CreateMailbox => 'k',
+            Right::DeleteMailbox => 'x',
+            Right::DeleteMessages => 't',
+            Right::Expunge => 'e',
+            Right::Administer => 'a',
+            Right::Create => 'c',
+            Right::Delete => 'd',
+            Right::Other(RightOther(other)) => other,
+        }
+    }
+}
+
+impl Display for Right {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        write!(f, "{}", char::from(*self))
+    }
+}
+
+/// A set of rights, e.g., "lrswipkxtea".
+///
+/// Rights are kept in the order they were inserted (without duplicates) so that a rights string
+/// round-trips unchanged. Still, two sets compare equal regardless of order, i.e., "lr" == "rl".
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "serde", serde(from = "Vec<Right>"))]
+#[derive(Debug, Clone, Default, ToStatic)]
+pub struct Rights(Vec<Right>);
+
+impl Rights {
+    /// Create an empty set of rights.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// All standard rights, i.e., "lrswipkxtea".
+    pub fn standard() -> Self {
+        Self(STANDARD_RIGHTS.to_vec())
+    }
+
+    pub fn contains(&self, right: Right) -> bool {
+        self.0.contains(&right)
+    }
+
+    /// Check if every right in `other` is also in `self`.
+    pub fn contains_all(&self, other: &Rights) -> bool {
+        other.iter().all(|right| self.contains(right))
+    }
+
+    /// Insert a right. Returns `false` when it was already present.
+    pub fn insert(&mut self, right: Right) -> bool {
+        if self.contains(right) {
+            return false;
+        }
+
+        self.0.push(right);
+        true
+    }
+
+    /// Remove a right. Returns `false` when it was not present.
+    pub fn remove(&mut self, right: Right) -> bool {
+        let len = self.0.len();
+        self.0.retain(|item| *item != right);
+        self.0.len() != len
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Right> + '_ {
+        self.0.iter().copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Check if this set only contains standard rights, i.e., rights from "lrswipkxtea".
+    pub fn is_standard(&self) -> bool {
+        self.iter().all(|right| STANDARD_RIGHTS.contains(&right))
+    }
+
+    /// Replace the virtual rights `c` and `d` with the real rights they stand for.
+    pub fn expand_virtual(&self, mode: VirtualRights) -> Self {
+        let mut expanded = Self::new();
+
+        for right in self.iter() {
+            match right {
+                Right::Create => mode.create().iter().for_each(|right| {
+                    expanded.insert(*right);
+                }),
+                Right::Delete => mode.delete().iter().for_each(|right| {
+                    expanded.insert(*right);
+                }),
+                right => {
+                    expanded.insert(right);
+                }
+            }
+        }
+
+        expanded
+    }
+
+    /// Order-independent representation used for `PartialEq` and `Hash`.
+    fn mask(&self) -> u32 {
+        self.iter().fold(0, |mask, right| {
+            let bit = match right {
+                Right::Lookup => 0,
+                Right::Read => 1,
+                Right::Seen => 2,
+                Right::Write => 3,
+                Right::Insert => 4,
+                Right::Post => 5,
+                Right::CreateMailbox => 6,
+                Right::DeleteMailbox => 7,
+                Right::DeleteMessages => 8,
+                Right::Expunge => 9,
+                Right::Administer => 10,
+                Right::Create => 11,
+                Right::Delete => 12,
+                // `RightOther` is always in `0`-`9`, so this never falls back.
+                Right::Other(RightOther(other)) => 13 + other.to_digit(10).unwrap_or(10),
+            };
+
+            mask | (1 << bit)
+        })
+    }
+
+    /// Check if this set is a valid value for the `RIGHTS=` capability.
+    ///
+    /// RFC 4314 requires the capability to include (at least) "texk".
+    pub fn is_valid_capability(&self) -> bool {
+        [
+            Right::DeleteMessages,
+            Right::Expunge,
+            Right::DeleteMailbox,
+            Right::CreateMailbox,
+        ]
+        .into_iter()
+        .all(|right| self.contains(right))
+    }
+}
+
+impl PartialEq for Rights {
+    fn eq(&self, other: &Self) -> bool {
+        self.mask() == other.mask()
+    }
+}
+
+impl Eq for Rights {}
+
+impl Hash for Rights {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.mask().hash(state);
+    }
+}
+
+impl From<Vec<Right>> for Rights {
+    fn from(value: Vec<Right>) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+impl FromIterator<Right> for Rights {
+    fn from_iter<T: IntoIterator<Item = Right>>(iter: T) -> Self {
+        let mut rights = Self::new();
+
+        for right in iter {
+            rights.insert(right);
+        }
+
+        rights
+    }
+}
+
+impl TryFrom<&str> for Rights {
+    type Error = ValidationError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.chars().map(Right::try_from).collect()
+    }
+}
+
+impl Display for Rights {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        for right in self.iter() {
+            write!(f, "{}", right)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// How the obsolete (virtual) rights `c` and `d` map to real rights.
+///
+/// RFC 2086 was ambiguous about which right controls DELETE, so RFC 4314 allows two mappings.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ToStatic)]
+pub enum VirtualRights {
+    /// `c` = "kx" and `d` = "et", i.e., DELETE is controlled by `c`.
+    DeleteMailboxInCreate,
+    /// `c` = "k" and `d` = "etx", i.e., DELETE is controlled by `d`.
+    DeleteMailboxInDelete,
+}
+
+impl VirtualRights {
+    /// Real rights represented by `c`.
+    pub fn create(&self) -> &'static [Right] {
+        match self {
+            Self::DeleteMailboxInCreate => &[Right::CreateMailbox, Right::DeleteMailbox],
+            Self::DeleteMailboxInDelete => &[Right::CreateMailbox],
+        }
+    }
+
+    /// Real rights represented by `d`.
+    pub fn delete(&self) -> &'static [Right] {
+        match self {
+            Self::DeleteMailboxInCreate => &[Right::Expunge, Right::DeleteMessages],
+            Self::DeleteMailboxInDelete => {
+                &[Right::Expunge, Right::DeleteMessages, Right::DeleteMailbox]
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::response::Capability;
+
+    #[test]
+    fn test_rights_conversion() {
+        let rights = Rights::try_from("lrswipkxtea").unwrap();
+        assert_eq!(rights, Rights::standard());
+        assert!(rights.is_standard());
+        assert_eq!(rights.to_string(), "lrswipkxtea");
+
+        let rights = Rights::try_from("texk").unwrap();
+        assert_eq!(rights.to_string(), "texk");
+        assert!(rights.is_valid_capability());
+        assert!(!Rights::try_from("tek").unwrap().is_valid_capability());
+
+        let rights = Rights::try_from("lrr0").unwrap();
+        assert_eq!(rights.to_string(), "lr0");
+        assert!(!rights.is_standard());
+
+        assert!(Rights::try_from("").unwrap().is_empty());
+        assert!(Rights::try_from("lrX").is_err());
+        assert!(Rights::try_from("lr ").is_err());
+    }
+
+    #[test]
+    fn test_rights_expand_virtual() {
+        let rights = Rights::try_from("lrcd").unwrap();
+
+        assert_eq!(
+            rights.expand_virtual(VirtualRights::DeleteMailboxInCreate),
+            Rights::try_from("lrkxet").unwrap()
+        );
+        assert_eq!(
+            rights.expand_virtual(VirtualRights::DeleteMailboxInDelete),
+            Rights::try_from("lrketx").unwrap()
+        );
+        assert!(rights
+            .expand_virtual(VirtualRights::DeleteMailboxInDelete)
+            .is_standard());
+    }
+
+    #[test]
+    fn test_rights_insert_remove() {
+        let mut rights = Rights::new();
+
+        assert!(rights.insert(Right::Lookup));
+        assert!(!rights.insert(Right::Lookup));
+        assert!(rights.insert(Right::Read));
+        assert!(rights.contains_all(&Rights::try_from("rl").unwrap()));
+        assert_eq!(rights, Rights::try_from("rl").unwrap());
+        assert_eq!(rights.to_string(), "lr");
+        assert_ne!(rights, Rights::try_from("l").unwrap());
+        assert_ne!(rights, Rights::try_from("lr0").unwrap());
+        assert!(rights.remove(Right::Lookup));
+        assert!(!rights.remove(Right::Lookup));
+        assert_eq!(rights.len(), 1);
+    }
+
+    #[test]
+    fn test_capability_rights() {
+        let rights = Rights::try_from("texk").unwrap();
+
+        assert_eq!(Capability::try_from("ACL").unwrap(), Capability::Acl);
+        assert_eq!(
+            Capability::try_from("RIGHTS=texk").unwrap(),
+            Capability::Rights(rights.clone())
+        );
+        assert_eq!(Capability::Rights(rights).to_string(), "RIGHTS=texk");
+        assert!(matches!(
+            Capability::try_from("RIGHTS=").unwrap(),
+            Capability::Other(_)
+        ));
+        assert!(matches!(
+            Capability::try_from("RIGHTS=teXk").unwrap(),
+            Capability::Other(_)
+        ));
+    }
+}
@@ -1,33 +1,69 @@
 //! IMAP4 Binary Content Extension
 
-use std::{
-    borrow::Cow,
-    fmt::{Debug, Formatter},
-};
+use alloc::{borrow::Cow, vec::Vec};
+use core::fmt::{Debug, Formatter};
 
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::core::{Literal, LiteralMode};
+use crate::{
+    core::{Literal, LiteralData, LiteralMode},
+    error::ValidationError,
+    secret::{redaction_policy, Redacted},
+};
 
 /// Either a [`Literal`] or [`Literal8`].
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Clone, Debug, PartialEq, Eq, Hash, ToStatic)]
 pub enum LiteralOrLiteral8<'a> {
     Literal(Literal<'a>),
     Literal8(Literal8<'a>),
 }
 
+impl LiteralOrLiteral8<'_> {
+    /// Return the data.
+    pub fn data(&self) -> &[u8] {
+        match self {
+            Self::Literal(literal) => literal.data(),
+            Self::Literal8(literal8) => literal8.data(),
+        }
+    }
+
+    /// Return the literal mode.
+    pub fn mode(&self) -> LiteralMode {
+        match self {
+            Self::Literal(literal) => literal.mode(),
+            Self::Literal8(literal8) => literal8.mode(),
+        }
+    }
+
+    /// Share the data with `buffer` if it was borrowed from it.
+    ///
+    /// See [`LiteralData::into_shared`].
+    #[cfg(feature = "bytes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+    pub fn into_shared(self, buffer: &bytes::Bytes) -> LiteralOrLiteral8<'static> {
+        match self {
+            Self::Literal(literal) => LiteralOrLiteral8::Literal(literal.into_shared(buffer)),
+            Self::Literal8(literal8) => LiteralOrLiteral8::Literal8(literal8.into_shared(buffer)),
+        }
+    }
+}
+
 /// String that might contain NULs.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct Literal8<'a> {
-    pub data: Cow<'a, [u8]>,
+    pub data: LiteralData<'a>,
     /// Specifies whether this is a synchronizing or non-synchronizing literal.
     ///
     /// `true` (default) denotes a synchronizing literal, e.g., `~{3}\r\nfoo`.
@@ -40,22 +76,155 @@ pub struct Literal8<'a> {
 
 // We want a more readable `Debug` implementation.
 impl Debug for Literal8<'_> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        struct BStr<'a>(&'a Cow<'a, [u8]>);
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        struct BStr<'a>(&'a [u8]);
 
         impl Debug for BStr<'_> {
-            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-                write!(
-                    f,
-                    "b\"{}\"",
-                    crate::utils::escape_byte_string(self.0.as_ref())
-                )
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+                write!(f, "b\"{}\"", crate::utils::escape_byte_string(self.0))
+            }
+        }
+
+        let mut debug = f.debug_struct("Literal8");
+
+        if redaction_policy().literals {
+            debug.field("data", &Redacted(self.data.len()));
+        } else {
+            debug.field("data", &BStr(&self.data));
+        }
+
+        debug.field("mode", &self.mode).finish()
+    }
+}
+
+impl Literal8<'_> {
+    /// Return the data.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Return the literal mode.
+    pub fn mode(&self) -> LiteralMode {
+        self.mode
+    }
+
+    /// Set the literal mode.
+    pub fn set_mode(&mut self, mode: LiteralMode) {
+        self.mode = mode;
+    }
+
+    /// Turn literal into sync literal.
+    pub fn into_sync(mut self) -> Self {
+        self.mode = LiteralMode::Sync;
+        self
+    }
+
+    /// Turn literal into non-sync literal.
+    ///
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the
+    /// LITERAL+ or LITERAL- capability.
+    /// </div>
+    pub fn into_non_sync(mut self) -> Self {
+        self.mode = LiteralMode::NonSync;
+        self
+    }
+
+    /// Share the data with `buffer` if it was borrowed from it.
+    ///
+    /// See [`LiteralData::into_shared`].
+    #[cfg(feature = "bytes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+    pub fn into_shared(self, buffer: &bytes::Bytes) -> Literal8<'static> {
+        Literal8 {
+            data: self.data.into_shared(buffer),
+            mode: self.mode,
+        }
+    }
+}
+
+macro_rules! impl_from_literal8 {
+    ($from:ty) => {
+        /// Create a synchronizing literal8.
+        ///
+        /// Note: This is infallible because a literal8 may contain any byte.
+        impl<'a> From<$from> for Literal8<'a> {
+            fn from(data: $from) -> Self {
+                Self {
+                    data: LiteralData::from(data),
+                    mode: LiteralMode::Sync,
+                }
             }
         }
+    };
+}
+
+impl_from_literal8!(&'a [u8]);
+impl_from_literal8!(Vec<u8>);
+impl_from_literal8!(Cow<'a, [u8]>);
+
+impl<'a> From<Literal<'a>> for Literal8<'a> {
+    fn from(literal: Literal<'a>) -> Self {
+        Self {
+            data: literal.data,
+            mode: literal.mode,
+        }
+    }
+}
+
+/// Fails when the data contains a NUL byte.
+impl<'a> TryFrom<Literal8<'a>> for Literal<'a> {
+    type Error = ValidationError;
+
+    fn try_from(literal8: Literal8<'a>) -> Result<Self, Self::Error> {
+        Literal::validate(&*literal8.data)?;
+
+        Ok(Literal {
+            data: literal8.data,
+            mode: literal8.mode,
+        })
+    }
+}
+
+impl<'a> From<Literal<'a>> for LiteralOrLiteral8<'a> {
+    fn from(literal: Literal<'a>) -> Self {
+        Self::Literal(literal)
+    }
+}
+
+impl<'a> From<Literal8<'a>> for LiteralOrLiteral8<'a> {
+    fn from(literal8: Literal8<'a>) -> Self {
+        Self::Literal8(literal8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_literal8_conversions() {
+        let literal8 = Literal8::from(&b"a\x00b"[..]);
+        assert_eq!(literal8.data(), b"a\x00b");
+        assert_eq!(literal8.mode(), LiteralMode::Sync);
+        assert_eq!(Literal8::from(b"a\x00b".to_vec()), literal8);
+        assert_eq!(
+            literal8.clone().into_non_sync().mode(),
+            LiteralMode::NonSync
+        );
+        assert!(Literal::try_from(literal8).is_err());
+
+        let literal = Literal::try_from("abc").unwrap().into_non_sync();
+        let literal8 = Literal8::from(literal.clone());
+        assert_eq!(literal8.data(), b"abc");
+        assert_eq!(literal8.mode(), LiteralMode::NonSync);
+        assert_eq!(Literal::try_from(literal8.clone()), Ok(literal.clone()));
 
-        f.debug_struct("Literal8")
-            .field("data", &BStr(&self.data))
-            .field("mode", &self.mode)
-            .finish()
+        let value = LiteralOrLiteral8::from(literal);
+        assert_eq!(value.data(), b"abc");
+        assert_eq!(value.mode(), LiteralMode::NonSync);
+        let value = LiteralOrLiteral8::from(literal8.into_sync());
+        assert_eq!(value.data(), b"abc");
+        assert_eq!(value.mode(), LiteralMode::Sync);
     }
 }
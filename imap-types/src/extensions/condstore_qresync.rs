@@ -1,14 +1,102 @@
-use std::fmt::{Display, Formatter};
+use core::fmt::{Display, Formatter};
+#[cfg(feature = "ext_condstore_qresync")]
+use core::num::NonZeroU64;
 
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "ext_condstore_qresync")]
+use crate::extensions::condstore_qresync::error::ModSeqError;
 use crate::{core::Atom, error::ValidationError};
 
+/// Modification sequence (mod-sequence) of a message or mailbox.
+///
+/// A mod-sequence is a positive unsigned 63-bit integer, i.e., `1 <= n <= 2^63 - 1`.
+///
+/// Note: The zero value allowed in a few places (`mod-sequence-valzer`) is not a [`ModSeq`].
+#[cfg(feature = "ext_condstore_qresync")]
+#[cfg_attr(docsrs, doc(cfg("ext_condstore_qresync")))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "serde", serde(try_from = "u64", into = "u64"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ToStatic)]
+pub struct ModSeq(NonZeroU64);
+
+#[cfg(feature = "ext_condstore_qresync")]
+impl ModSeq {
+    /// Largest valid mod-sequence, i.e., `2^63 - 1`.
+    pub const MAX: Self = match NonZeroU64::new(i64::MAX as u64) {
+        Some(value) => Self(value),
+        None => unreachable!(),
+    };
+
+    /// Create a mod-sequence, returning `None` when `value` is zero or exceeds [`ModSeq::MAX`].
+    pub const fn new(value: u64) -> Option<Self> {
+        if value > Self::MAX.get() {
+            return None;
+        }
+
+        match NonZeroU64::new(value) {
+            Some(value) => Some(Self(value)),
+            None => None,
+        }
+    }
+
+    /// Return the mod-sequence as a primitive integer.
+    pub const fn get(self) -> u64 {
+        self.0.get()
+    }
+}
+
+#[cfg(feature = "ext_condstore_qresync")]
+impl TryFrom<u64> for ModSeq {
+    type Error = ModSeqError;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        match value {
+            0 => Err(ModSeqError::Zero),
+            value => Self::new(value).ok_or(ModSeqError::TooLarge),
+        }
+    }
+}
+
+#[cfg(feature = "ext_condstore_qresync")]
+impl TryFrom<NonZeroU64> for ModSeq {
+    type Error = ModSeqError;
+
+    fn try_from(value: NonZeroU64) -> Result<Self, Self::Error> {
+        Self::try_from(value.get())
+    }
+}
+
+#[cfg(feature = "ext_condstore_qresync")]
+impl From<ModSeq> for NonZeroU64 {
+    fn from(value: ModSeq) -> Self {
+        value.0
+    }
+}
+
+#[cfg(feature = "ext_condstore_qresync")]
+impl From<ModSeq> for u64 {
+    fn from(value: ModSeq) -> Self {
+        value.get()
+    }
+}
+
+#[cfg(feature = "ext_condstore_qresync")]
+impl Display for ModSeq {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum AttributeFlag<'a> {
     Answered,
@@ -37,6 +125,7 @@ impl<'a> AttributeFlag<'a> {
     }
 }
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct AttributeFlagExtension<'a>(Atom<'a>);
 
@@ -53,7 +142,7 @@ impl<'a> TryFrom<&'a str> for AttributeFlag<'a> {
 }
 
 impl Display for AttributeFlag<'_> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         match self {
             AttributeFlag::Answered => f.write_str("\\\\Answered"),
             AttributeFlag::Flagged => f.write_str("\\\\Flagged"),
@@ -69,6 +158,7 @@ impl Display for AttributeFlag<'_> {
 #[cfg(feature = "ext_condstore_qresync")]
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum EntryTypeReq {
     Private,
@@ -78,7 +168,7 @@ pub enum EntryTypeReq {
 
 #[cfg(feature = "ext_condstore_qresync")]
 impl Display for EntryTypeReq {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         match self {
             EntryTypeReq::Private => write!(f, "priv"),
             EntryTypeReq::Shared => write!(f, "shared"),
@@ -86,3 +176,35 @@ impl Display for EntryTypeReq {
         }
     }
 }
+
+/// Error-related types.
+#[cfg(feature = "ext_condstore_qresync")]
+pub mod error {
+    use thiserror::Error;
+
+    #[derive(Clone, Debug, Eq, Error, Hash, Ord, PartialEq, PartialOrd)]
+    pub enum ModSeqError {
+        #[error("Must not be zero")]
+        Zero,
+        #[error("Must not exceed 2^63 - 1")]
+        TooLarge,
+    }
+}
+
+#[cfg(all(test, feature = "ext_condstore_qresync"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mod_seq() {
+        assert_eq!(ModSeq::try_from(0), Err(ModSeqError::Zero));
+        assert_eq!(ModSeq::try_from(1).unwrap().get(), 1);
+        assert_eq!(ModSeq::try_from(i64::MAX as u64), Ok(ModSeq::MAX));
+        assert_eq!(
+            ModSeq::try_from(i64::MAX as u64 + 1),
+            Err(ModSeqError::TooLarge)
+        );
+        assert_eq!(ModSeq::new(u64::MAX), None);
+        assert_eq!(ModSeq::MAX.to_string(), "9223372036854775807");
+    }
+}
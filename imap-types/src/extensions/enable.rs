@@ -6,11 +6,13 @@
 //! * the [CommandBody] enum with a new variant [CommandBody::Enable], and
 //! * the [Data](crate::response::Data) enum with a new variant [Data::Enabled](crate::response::Data#variant.Enabled).
 
-use std::fmt::{Display, Formatter};
+use core::fmt::{Display, Formatter};
 
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
@@ -35,6 +37,7 @@ impl<'a> CommandBody<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 #[non_exhaustive]
 pub enum CapabilityEnable<'a> {
@@ -76,7 +79,7 @@ impl<'a> From<Atom<'a>> for CapabilityEnable<'a> {
 }
 
 impl Display for CapabilityEnable<'_> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         match self {
             Self::Utf8(kind) => write!(f, "UTF8={}", kind),
             #[cfg(feature = "ext_condstore_qresync")]
@@ -94,11 +97,13 @@ impl Display for CapabilityEnable<'_> {
 ///
 /// It's guaranteed that this type can't represent any capability from [`CapabilityEnable`].
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct CapabilityEnableOther<'a>(Atom<'a>);
 
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 #[non_exhaustive]
 pub enum Utf8Kind {
@@ -107,7 +112,7 @@ pub enum Utf8Kind {
 }
 
 impl Display for Utf8Kind {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         f.write_str(match self {
             Self::Accept => "ACCEPT",
             Self::Only => "ONLY",
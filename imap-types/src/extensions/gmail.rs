@@ -0,0 +1,56 @@
+//! Gmail IMAP Extensions
+//!
+//! This extends ...
+//!
+//! * [`MessageDataItemName`](crate::fetch::MessageDataItemName) with new variants:
+//!
+//!     - [`MessageDataItemName::GmailMessageId`](crate::fetch::MessageDataItemName::GmailMessageId)
+//!     - [`MessageDataItemName::GmailThreadId`](crate::fetch::MessageDataItemName::GmailThreadId)
+//!     - [`MessageDataItemName::GmailLabels`](crate::fetch::MessageDataItemName::GmailLabels)
+//!
+//! * [`MessageDataItem`](crate::fetch::MessageDataItem) with new variants:
+//!
+//!     - [`MessageDataItem::GmailMessageId`](crate::fetch::MessageDataItem::GmailMessageId)
+//!     - [`MessageDataItem::GmailThreadId`](crate::fetch::MessageDataItem::GmailThreadId)
+//!     - [`MessageDataItem::GmailLabels`](crate::fetch::MessageDataItem::GmailLabels)
+//!
+//! <div class="warning">
+//! This extension must only be used when the server advertised support for it sending the X-GM-EXT-1 capability.
+//! </div>
+
+use alloc::string::String;
+use core::fmt::{Display, Formatter};
+
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::core::{AString, Atom};
+
+/// A Gmail label as used in `X-GM-LABELS`.
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub enum GmailLabel<'a> {
+    /// A system label, e.g., `\Inbox`, `\Important`, or `\Sent`.
+    ///
+    /// Note: The leading backslash is not part of the atom.
+    System(Atom<'a>),
+
+    /// A user-defined label.
+    Other(AString<'a>),
+}
+
+impl Display for GmailLabel<'_> {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        match self {
+            Self::System(atom) => write!(f, "\\{}", atom),
+            Self::Other(other) => f.write_str(&String::from_utf8_lossy(other.as_ref())),
+        }
+    }
+}
@@ -1,6 +1,8 @@
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
@@ -11,6 +13,7 @@ use crate::{
 
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Clone, Debug, Eq, Hash, PartialEq, ToStatic)]
 pub struct EntryValue<'a> {
     pub entry: Entry<'a>,
@@ -20,6 +23,7 @@ pub struct EntryValue<'a> {
 /// Slash-separated path to entry.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Clone, Debug, Eq, Hash, PartialEq, ToStatic)]
 pub struct Entry<'a>(AString<'a>);
 
@@ -47,6 +51,7 @@ impl AsRef<[u8]> for Entry<'_> {
 
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Clone, Debug, Eq, Hash, PartialEq, ToStatic)]
 pub enum GetMetadataOption {
     /// Only return values that are less than or equal in octet size to the specified limit.
@@ -63,6 +68,7 @@ pub enum GetMetadataOption {
 
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Clone, Debug, Eq, Hash, PartialEq, ToStatic)]
 pub enum Depth {
     /// No entries below the specified entry are returned
@@ -75,6 +81,7 @@ pub enum Depth {
 
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Clone, Debug, Eq, Hash, PartialEq, ToStatic)]
 pub enum MetadataCode {
     LongEntries(u32),
@@ -85,6 +92,7 @@ pub enum MetadataCode {
 
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Clone, Debug, Eq, Hash, PartialEq, ToStatic)]
 pub enum MetadataResponse<'a> {
     WithValues(Vec1<EntryValue<'a>>),
@@ -33,21 +33,19 @@
 //!     - [`StatusDataItem::Deleted`](crate::status::StatusDataItem::Deleted)
 //!     - [`StatusDataItem::DeletedStorage`](crate::status::StatusDataItem::DeletedStorage)
 
-use std::{
-    borrow::Cow,
-    fmt::{Display, Formatter},
-};
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::fmt::{Display, Formatter};
 
-#[cfg(feature = "arbitrary")]
-use arbitrary::Arbitrary;
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use crate::{
     command::CommandBody,
     core::{impl_try_from, AString, Atom, Vec1},
-    extensions::quota::error::{QuotaError, QuotaRootError, SetQuotaError},
+    extensions::quota::error::{QuotaError, QuotaRootError, QuotaValueError, SetQuotaError},
     mailbox::Mailbox,
     response::Data,
 };
@@ -123,6 +121,7 @@ impl<'a> Data<'a> {
 ///
 /// Supported resource names MUST be advertised as a capability by prepending the resource name with "QUOTA=RES-".
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum Resource<'a> {
     /// The physical space estimate, in units of 1024 octets, of the mailboxes governed by the quota
@@ -184,6 +183,7 @@ pub enum Resource<'a> {
 ///
 /// It's guaranteed that this type can't represent any resource from [`Resource`].
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct ResourceOther<'a>(Atom<'a>);
 
@@ -206,7 +206,7 @@ impl<'a> From<Atom<'a>> for Resource<'a> {
 }
 
 impl Display for Resource<'_> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.write_str(match self {
             Self::Storage => "STORAGE",
             Self::Message => "MESSAGE",
@@ -217,41 +217,160 @@ impl Display for Resource<'_> {
     }
 }
 
+/// The maximum value of a resource usage or limit.
+///
+/// RFC 9208 defines both as `number64`, i.e., an unsigned 63-bit integer.
+pub const QUOTA_VALUE_MAX: u64 = i64::MAX as u64;
+
 /// A type that holds a resource name, usage, and limit.
 /// Used in the response of the GETQUOTA command.
-#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "serde", serde(try_from = "QuotaGetShadow"))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct QuotaGet<'a> {
-    pub resource: Resource<'a>,
-    pub usage: u64,
-    pub limit: u64,
+    resource: Resource<'a>,
+    usage: u64,
+    limit: u64,
+}
+
+/// Use shadow type to support validated deserialization
+/// until `serde` provides built-in support for this case.
+#[cfg(feature = "serde")]
+#[derive(Deserialize, Debug)]
+struct QuotaGetShadow<'a> {
+    resource: Resource<'a>,
+    usage: u64,
+    limit: u64,
+}
+
+#[cfg(feature = "serde")]
+impl<'a> TryFrom<QuotaGetShadow<'a>> for QuotaGet<'a> {
+    type Error = QuotaValueError;
+
+    fn try_from(value: QuotaGetShadow<'a>) -> Result<Self, Self::Error> {
+        Self::new(value.resource, value.usage, value.limit)
+    }
 }
 
 impl<'a> QuotaGet<'a> {
-    pub fn new(resource: Resource<'a>, usage: u64, limit: u64) -> Self {
+    /// Create a new quota.
+    ///
+    /// Note: Both, `usage` and `limit`, must not exceed [`QUOTA_VALUE_MAX`].
+    pub fn new(resource: Resource<'a>, usage: u64, limit: u64) -> Result<Self, QuotaValueError> {
+        if usage > QUOTA_VALUE_MAX {
+            return Err(QuotaValueError::Usage(usage));
+        }
+
+        if limit > QUOTA_VALUE_MAX {
+            return Err(QuotaValueError::Limit(limit));
+        }
+
+        Ok(Self {
+            resource,
+            usage,
+            limit,
+        })
+    }
+
+    /// Create a new quota without validation.
+    ///
+    /// # Warning: IMAP conformance
+    ///
+    /// The caller must ensure that `usage` and `limit` do not exceed [`QUOTA_VALUE_MAX`]. Failing
+    /// to do so may create invalid/unparsable IMAP messages.
+    ///
+    /// Note: This method will `panic!` on wrong input in debug builds.
+    pub fn unvalidated(resource: Resource<'a>, usage: u64, limit: u64) -> Self {
+        #[cfg(debug_assertions)]
+        {
+            assert!(usage <= QUOTA_VALUE_MAX);
+            assert!(limit <= QUOTA_VALUE_MAX);
+        }
+
         Self {
             resource,
             usage,
             limit,
         }
     }
+
+    pub fn resource(&self) -> &Resource<'a> {
+        &self.resource
+    }
+
+    pub fn usage(&self) -> u64 {
+        self.usage
+    }
+
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
 }
 
 /// A type that holds a resource name and limit.
 /// Used in the SETQUOTA command.
-#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "serde", serde(try_from = "QuotaSetShadow"))]
 #[derive(ToStatic, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct QuotaSet<'a> {
-    pub resource: Resource<'a>,
-    pub limit: u64,
+    resource: Resource<'a>,
+    limit: u64,
+}
+
+/// Use shadow type to support validated deserialization
+/// until `serde` provides built-in support for this case.
+#[cfg(feature = "serde")]
+#[derive(Deserialize, Debug)]
+struct QuotaSetShadow<'a> {
+    resource: Resource<'a>,
+    limit: u64,
+}
+
+#[cfg(feature = "serde")]
+impl<'a> TryFrom<QuotaSetShadow<'a>> for QuotaSet<'a> {
+    type Error = QuotaValueError;
+
+    fn try_from(value: QuotaSetShadow<'a>) -> Result<Self, Self::Error> {
+        Self::new(value.resource, value.limit)
+    }
 }
 
 impl<'a> QuotaSet<'a> {
-    pub fn new(resource: Resource<'a>, limit: u64) -> Self {
+    /// Create a new quota limit.
+    ///
+    /// Note: `limit` must not exceed [`QUOTA_VALUE_MAX`].
+    pub fn new(resource: Resource<'a>, limit: u64) -> Result<Self, QuotaValueError> {
+        if limit > QUOTA_VALUE_MAX {
+            return Err(QuotaValueError::Limit(limit));
+        }
+
+        Ok(Self { resource, limit })
+    }
+
+    /// Create a new quota limit without validation.
+    ///
+    /// # Warning: IMAP conformance
+    ///
+    /// The caller must ensure that `limit` does not exceed [`QUOTA_VALUE_MAX`]. Failing to do so
+    /// may create invalid/unparsable IMAP messages.
+    ///
+    /// Note: This method will `panic!` on wrong input in debug builds.
+    pub fn unvalidated(resource: Resource<'a>, limit: u64) -> Self {
+        #[cfg(debug_assertions)]
+        assert!(limit <= QUOTA_VALUE_MAX);
+
         Self { resource, limit }
     }
+
+    pub fn resource(&self) -> &Resource<'a> {
+        &self.resource
+    }
+
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
 }
 
 /// Error-related types.
@@ -274,6 +393,14 @@ pub mod error {
         Roots(R),
     }
 
+    #[derive(Clone, Debug, Eq, Error, Hash, Ord, PartialEq, PartialOrd)]
+    pub enum QuotaValueError {
+        #[error("Usage must not exceed 2^63 - 1, got {0}")]
+        Usage(u64),
+        #[error("Limit must not exceed 2^63 - 1, got {0}")]
+        Limit(u64),
+    }
+
     #[derive(Clone, Debug, Eq, Error, Hash, Ord, PartialEq, PartialOrd)]
     pub enum SetQuotaError<R, S> {
         #[error("Invalid root: {0}")]
@@ -282,3 +409,27 @@ pub mod error {
         QuotaSet(S),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_value_range() {
+        assert!(QuotaGet::new(Resource::Storage, QUOTA_VALUE_MAX, QUOTA_VALUE_MAX).is_ok());
+        assert_eq!(
+            QuotaGet::new(Resource::Storage, QUOTA_VALUE_MAX + 1, 0),
+            Err(QuotaValueError::Usage(QUOTA_VALUE_MAX + 1))
+        );
+        assert_eq!(
+            QuotaGet::new(Resource::Storage, 0, u64::MAX),
+            Err(QuotaValueError::Limit(u64::MAX))
+        );
+
+        assert!(QuotaSet::new(Resource::Message, QUOTA_VALUE_MAX).is_ok());
+        assert_eq!(
+            QuotaSet::new(Resource::Message, u64::MAX),
+            Err(QuotaValueError::Limit(u64::MAX))
+        );
+    }
+}
@@ -1,16 +1,26 @@
-use std::fmt::{Display, Formatter};
+use core::{
+    fmt::{Display, Formatter},
+    num::NonZeroU32,
+};
 
 #[cfg(feature = "arbitrary")]
 use arbitrary::{Arbitrary, Unstructured};
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "arbitrary")]
 use crate::arbitrary::impl_arbitrary_try_from;
-use crate::core::Atom;
+use crate::{
+    core::{Atom, Vec1},
+    extensions::tagged_ext::{TaggedExtLabel, TaggedExtVal},
+    sequence::SequenceSet,
+};
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum SortAlgorithm<'a> {
     Display,
@@ -27,7 +37,7 @@ impl<'a> From<Atom<'a>> for SortAlgorithm<'a> {
 }
 
 impl Display for SortAlgorithm<'_> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         match self {
             SortAlgorithm::Display => f.write_str("DISPLAY"),
             SortAlgorithm::Other(other) => f.write_str(other.as_ref()),
@@ -39,6 +49,7 @@ impl Display for SortAlgorithm<'_> {
 impl_arbitrary_try_from! { SortAlgorithm<'a>, Atom<'a> }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct SortAlgorithmOther<'a>(Atom<'a>);
 
@@ -50,14 +61,133 @@ impl AsRef<str> for SortAlgorithmOther<'_> {
 
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct SortCriterion {
     pub reverse: bool,
     pub key: SortKey,
 }
 
+impl SortCriterion {
+    /// Sort by `key` in ascending order.
+    pub const fn new(key: SortKey) -> Self {
+        Self {
+            reverse: false,
+            key,
+        }
+    }
+
+    /// Sort by `ARRIVAL`.
+    pub const fn arrival() -> Self {
+        Self::new(SortKey::Arrival)
+    }
+
+    /// Sort by `CC`.
+    pub const fn cc() -> Self {
+        Self::new(SortKey::Cc)
+    }
+
+    /// Sort by `DATE`.
+    pub const fn date() -> Self {
+        Self::new(SortKey::Date)
+    }
+
+    /// Sort by `FROM`.
+    pub const fn from() -> Self {
+        Self::new(SortKey::From)
+    }
+
+    /// Sort by `SIZE`.
+    pub const fn size() -> Self {
+        Self::new(SortKey::Size)
+    }
+
+    /// Sort by `SUBJECT`.
+    pub const fn subject() -> Self {
+        Self::new(SortKey::Subject)
+    }
+
+    /// Sort by `TO`.
+    pub const fn to() -> Self {
+        Self::new(SortKey::To)
+    }
+
+    /// Sort by `DISPLAYFROM`.
+    ///
+    /// Note: Only use when server advertised `SORT=DISPLAY`.
+    pub const fn display_from() -> Self {
+        Self::new(SortKey::DisplayFrom)
+    }
+
+    /// Sort by `DISPLAYTO`.
+    ///
+    /// Note: Only use when server advertised `SORT=DISPLAY`.
+    pub const fn display_to() -> Self {
+        Self::new(SortKey::DisplayTo)
+    }
+
+    /// Sort in descending order (`REVERSE`).
+    ///
+    /// Calling this more than once has no further effect, i.e., there is no "double REVERSE".
+    pub const fn reverse(mut self) -> Self {
+        self.reverse = true;
+        self
+    }
+
+    /// Use `next` to order messages that compare equal by `self`.
+    ///
+    /// See `Vec1::<SortCriterion>::then`.
+    pub fn then(self, next: SortCriterion) -> Vec1<SortCriterion> {
+        Vec1::from(self).then(next)
+    }
+}
+
+impl Vec1<SortCriterion> {
+    /// Append `next` as a tie-breaker.
+    ///
+    /// A criterion with a key that is already used is ignored, as it can't change the order.
+    ///
+    /// ```rust
+    /// use imap_types::extensions::sort::SortCriterion;
+    ///
+    /// let criteria = SortCriterion::date()
+    ///     .reverse()
+    ///     .then(SortCriterion::subject())
+    ///     .then(SortCriterion::date());
+    ///
+    /// assert_eq!(
+    ///     criteria.as_ref(),
+    ///     [SortCriterion::date().reverse(), SortCriterion::subject()]
+    /// );
+    /// ```
+    pub fn then(mut self, next: SortCriterion) -> Self {
+        if !self.0.iter().any(|criterion| criterion.key == next.key) {
+            self.0.push(next);
+        }
+
+        self
+    }
+}
+
+impl From<SortKey> for SortCriterion {
+    fn from(key: SortKey) -> Self {
+        Self::new(key)
+    }
+}
+
+impl Display for SortCriterion {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        if self.reverse {
+            f.write_str("REVERSE ")?;
+        }
+
+        Display::fmt(&self.key, f)
+    }
+}
+
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum SortKey {
     Arrival,
@@ -90,3 +220,129 @@ impl AsRef<str> for SortKey {
         }
     }
 }
+
+impl Display for SortKey {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        f.write_str(self.as_ref())
+    }
+}
+
+/// Return option of an extended SORT command (see RFC 5267).
+///
+/// <div class="warning">
+/// This extension must only be used when the server advertised support for it sending the ESORT capability.
+/// </div>
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub enum SortReturnOption {
+    /// Return the first message number/UID of the sorted result.
+    Min,
+    /// Return the last message number/UID of the sorted result.
+    Max,
+    /// Return all message numbers/UIDs of the sorted result.
+    All,
+    /// Return the number of messages in the sorted result.
+    Count,
+    /// Return a window of the sorted result.
+    ///
+    /// Note: Only use when server advertised `CONTEXT=SORT`.
+    Partial(PartialRange),
+}
+
+impl AsRef<str> for SortReturnOption {
+    fn as_ref(&self) -> &str {
+        match self {
+            SortReturnOption::Min => "MIN",
+            SortReturnOption::Max => "MAX",
+            SortReturnOption::All => "ALL",
+            SortReturnOption::Count => "COUNT",
+            SortReturnOption::Partial(_) => "PARTIAL",
+        }
+    }
+}
+
+/// A (1-based, inclusive) window into a sorted result.
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ToStatic)]
+pub struct PartialRange {
+    pub first: NonZeroU32,
+    pub last: NonZeroU32,
+}
+
+/// Data returned in an ESEARCH response (see RFC 4731 and RFC 5267).
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub enum SearchReturnData<'a> {
+    /// First message number/UID.
+    Min(NonZeroU32),
+    /// Last message number/UID.
+    Max(NonZeroU32),
+    /// All message numbers/UIDs.
+    ///
+    /// Note: The order of a sorted result is preserved.
+    All(SequenceSet),
+    /// Number of messages.
+    Count(u32),
+    /// Window of the result as requested by [`SortReturnOption::Partial`].
+    Partial {
+        range: PartialRange,
+        /// `None` when the window is empty.
+        results: Option<SequenceSet>,
+    },
+    /// Data of an unknown extension (see RFC 4466).
+    ///
+    /// Note: Must not be used to send known data.
+    Other {
+        name: TaggedExtLabel<'a>,
+        value: TaggedExtVal<'a>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_criterion() {
+        assert_eq!(
+            SortCriterion::date(),
+            SortCriterion {
+                reverse: false,
+                key: SortKey::Date
+            }
+        );
+        assert_eq!(
+            SortCriterion::date().reverse().reverse(),
+            SortCriterion {
+                reverse: true,
+                key: SortKey::Date
+            }
+        );
+        assert_eq!(SortCriterion::cc(), SortKey::Cc.into());
+
+        assert_eq!(SortCriterion::arrival().to_string(), "ARRIVAL");
+        assert_eq!(
+            SortCriterion::display_from().reverse().to_string(),
+            "REVERSE DISPLAYFROM"
+        );
+
+        let criteria = SortCriterion::from()
+            .then(SortCriterion::size().reverse())
+            .then(SortCriterion::from().reverse())
+            .then(SortCriterion::to());
+        assert_eq!(
+            criteria.as_ref(),
+            [
+                SortCriterion::from(),
+                SortCriterion::size().reverse(),
+                SortCriterion::to()
+            ]
+        );
+    }
+}
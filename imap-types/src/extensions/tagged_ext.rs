@@ -0,0 +1,251 @@
+//! Generic Extension Parameters (RFC 4466)
+//!
+//! RFC 4466 defines a generic syntax for parameters of (future) extensions. This makes it possible
+//! to process well-formed parameters of extensions that are not (yet) known.
+//!
+//! This extends ...
+//!
+//! * [`SelectParameter`](crate::command::SelectParameter) with a new variant:
+//!
+//!     - [`SelectParameter::Other`](crate::command::SelectParameter::Other)
+//!
+//! * [`StatusDataItemName`](crate::status::StatusDataItemName) with a new variant:
+//!
+//!     - [`StatusDataItemName::Other`](crate::status::StatusDataItemName::Other)
+//!
+//! * [`StatusDataItem`](crate::status::StatusDataItem) with a new variant:
+//!
+//!     - [`StatusDataItem::Other`](crate::status::StatusDataItem::Other)
+//!
+//! * [`SearchReturnData`](crate::extensions::sort::SearchReturnData) with a new variant:
+//!
+//!     - [`SearchReturnData::Other`](crate::extensions::sort::SearchReturnData::Other)
+//!
+//! Known select parameters and status data item names are never represented by these variants, see
+//! [`SelectParameterOther`](crate::command::SelectParameterOther) and
+//! [`StatusDataItemNameOther`](crate::status::StatusDataItemNameOther).
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::{
+    fmt::{Debug, Display, Formatter},
+    str::from_utf8,
+};
+
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::{AString, Vec1},
+    error::{ValidationError, ValidationErrorKind},
+    sequence::SequenceSet,
+    utils::indicators::{is_tagged_label_char, is_tagged_label_fchar},
+};
+
+/// Label of an extension parameter, e.g., `CONDSTORE`.
+///
+/// ```abnf
+/// tagged-ext-label   = tagged-label-fchar *tagged-label-char
+///                      ;; Is a valid RFC 3501 "atom".
+///
+/// tagged-label-fchar = ALPHA / "-" / "_" / "."
+///
+/// tagged-label-char  = tagged-label-fchar / DIGIT / ":"
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "serde", serde(try_from = "String"))]
+#[derive(Clone, PartialEq, Eq, Hash, ToStatic)]
+pub struct TaggedExtLabel<'a>(Cow<'a, str>);
+
+// We want a slightly more dense `Debug` implementation.
+impl Debug for TaggedExtLabel<'_> {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        write!(f, "TaggedExtLabel({:?})", self.0)
+    }
+}
+
+impl<'a> TaggedExtLabel<'a> {
+    /// Validates if value conforms to tagged-ext-label's ABNF definition.
+    pub fn validate(value: impl AsRef<[u8]>) -> Result<(), ValidationError> {
+        let value = value.as_ref();
+
+        match value.first() {
+            None => return Err(ValidationError::new(ValidationErrorKind::Empty)),
+            Some(first) if !is_tagged_label_fchar(*first) => {
+                return Err(ValidationError::new(ValidationErrorKind::InvalidByteAt {
+                    byte: *first,
+                    at: 0,
+                }));
+            }
+            _ => {}
+        }
+
+        if let Some(at) = value.iter().position(|b| !is_tagged_label_char(*b)) {
+            return Err(ValidationError::new(ValidationErrorKind::InvalidByteAt {
+                byte: value[at],
+                at,
+            }));
+        };
+
+        Ok(())
+    }
+
+    /// Returns a reference to the inner value.
+    pub fn inner(&self) -> &str {
+        self.0.as_ref()
+    }
+
+    /// Constructs a label without validation.
+    ///
+    /// # Warning: IMAP conformance
+    ///
+    /// The caller must ensure that `inner` is valid according to [`Self::validate`]. Failing to do
+    /// so may create invalid/unparsable IMAP messages, or even produce unintended protocol flows.
+    /// Do not call this constructor with untrusted data.
+    ///
+    /// Note: This method will `panic!` on wrong input in debug builds.
+    pub fn unvalidated<C>(inner: C) -> Self
+    where
+        C: Into<Cow<'a, str>>,
+    {
+        let inner = inner.into();
+
+        #[cfg(debug_assertions)]
+        Self::validate(inner.as_bytes()).unwrap();
+
+        Self(inner)
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for TaggedExtLabel<'a> {
+    type Error = ValidationError;
+
+    fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
+        Self::validate(value)?;
+
+        // Safety: `unwrap` can't panic due to `validate`.
+        Ok(Self(Cow::Borrowed(from_utf8(value).unwrap())))
+    }
+}
+
+impl<'a> TryFrom<&'a str> for TaggedExtLabel<'a> {
+    type Error = ValidationError;
+
+    fn try_from(value: &'a str) -> Result<Self, Self::Error> {
+        Self::validate(value)?;
+
+        Ok(Self(Cow::Borrowed(value)))
+    }
+}
+
+impl TryFrom<String> for TaggedExtLabel<'_> {
+    type Error = ValidationError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::validate(&value)?;
+
+        Ok(Self(Cow::Owned(value)))
+    }
+}
+
+impl AsRef<str> for TaggedExtLabel<'_> {
+    fn as_ref(&self) -> &str {
+        self.0.as_ref()
+    }
+}
+
+impl Display for TaggedExtLabel<'_> {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Value of an extension parameter.
+///
+/// ```abnf
+/// tagged-ext-val = tagged-ext-simple /
+///                  "(" [tagged-ext-comp] ")"
+/// ```
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub enum TaggedExtVal<'a> {
+    Simple(TaggedExtSimple),
+    /// A parenthesized (and possibly empty) list.
+    Comp(Vec<TaggedExtComp<'a>>),
+}
+
+/// ```abnf
+/// tagged-ext-simple = sequence-set / number / number64
+/// ```
+///
+/// Note: A number is always decoded as [`TaggedExtSimple::Number`] even though it is also a valid
+/// sequence set.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub enum TaggedExtSimple {
+    SequenceSet(SequenceSet),
+    Number(u64),
+}
+
+/// ```abnf
+/// tagged-ext-comp = astring /
+///                   tagged-ext-comp *(SP tagged-ext-comp) /
+///                   "(" tagged-ext-comp ")"
+/// ```
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub enum TaggedExtComp<'a> {
+    AString(AString<'a>),
+    /// A parenthesized list.
+    List(Vec1<TaggedExtComp<'a>>),
+}
+
+/// An extension parameter, i.e., a label with an optional value.
+///
+/// ```abnf
+/// select-param = select-param-name [SP select-param-value]
+///
+/// select-param-name = tagged-ext-label
+///
+/// select-param-value = tagged-ext-val
+/// ```
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub struct ExtensionItem<'a> {
+    pub label: TaggedExtLabel<'a>,
+    pub value: Option<TaggedExtVal<'a>>,
+}
+
+impl<'a> ExtensionItem<'a> {
+    pub fn new(label: TaggedExtLabel<'a>, value: Option<TaggedExtVal<'a>>) -> Self {
+        Self { label, value }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversion_tagged_ext_label() {
+        for valid in ["CONDSTORE", "x", "X-FOO", "_a.b:1", ".-_"] {
+            assert!(TaggedExtLabel::try_from(valid).is_ok());
+        }
+
+        for invalid in ["", "1abc", ":abc", "a b", "a(", "ä"] {
+            assert!(TaggedExtLabel::try_from(invalid).is_err());
+        }
+    }
+}
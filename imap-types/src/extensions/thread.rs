@@ -1,4 +1,5 @@
-use std::{
+use alloc::{vec, vec::Vec};
+use core::{
     fmt::{Display, Formatter},
     num::NonZeroU32,
 };
@@ -6,6 +7,8 @@ use std::{
 #[cfg(feature = "arbitrary")]
 use arbitrary::{Arbitrary, Unstructured};
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
@@ -14,6 +17,7 @@ use crate::arbitrary::impl_arbitrary_try_from;
 use crate::core::{Atom, Vec1, Vec2};
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum Thread {
     Members {
@@ -26,7 +30,7 @@ pub enum Thread {
 }
 
 impl Display for Thread {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         let empty_answers: Vec<Thread> = vec![];
 
         write!(f, "(")?;
@@ -77,7 +81,7 @@ impl Display for Thread {
     }
 }
 
-fn write_prefix(f: &mut Formatter, prefix: &Vec1<NonZeroU32>) -> std::fmt::Result {
+fn write_prefix(f: &mut Formatter, prefix: &Vec1<NonZeroU32>) -> core::fmt::Result {
     let (head, tail) = prefix.as_ref().split_first().unwrap();
 
     write!(f, "{}", head)?;
@@ -88,6 +92,204 @@ fn write_prefix(f: &mut Formatter, prefix: &Vec1<NonZeroU32>) -> std::fmt::Resul
     Ok(())
 }
 
+/// Identifier of a node in a [`ThreadTree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ThreadNodeId(usize);
+
+/// A node in a [`ThreadTree`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ThreadNode {
+    message: Option<NonZeroU32>,
+    parent: Option<ThreadNodeId>,
+    children: Vec<ThreadNodeId>,
+}
+
+impl ThreadNode {
+    /// Return the message (sequence number or UID, depending on the command).
+    ///
+    /// Returns `None` for a placeholder, i.e., a missing parent of multiple (sibling) threads.
+    pub fn message(&self) -> Option<NonZeroU32> {
+        self.message
+    }
+
+    /// Return the parent node.
+    pub fn parent(&self) -> Option<ThreadNodeId> {
+        self.parent
+    }
+
+    /// Return the child nodes, i.e., the answers.
+    pub fn children(&self) -> &[ThreadNodeId] {
+        &self.children
+    }
+}
+
+/// Threads as a tree with parent/child navigation.
+///
+/// ```rust
+/// use std::num::NonZeroU32;
+///
+/// use imap_types::{
+///     core::{Vec1, Vec2},
+///     extensions::thread::{Thread, ThreadTree},
+/// };
+///
+/// let n = |n| NonZeroU32::new(n).unwrap();
+///
+/// // (1 2 (3)(4))
+/// let thread = Thread::Members {
+///     prefix: Vec1::try_from(vec![n(1), n(2)]).unwrap(),
+///     answers: Some(
+///         Vec2::try_from(vec![
+///             Thread::Members {
+///                 prefix: Vec1::from(n(3)),
+///                 answers: None,
+///             },
+///             Thread::Members {
+///                 prefix: Vec1::from(n(4)),
+///                 answers: None,
+///             },
+///         ])
+///         .unwrap(),
+///     ),
+/// };
+///
+/// let tree = ThreadTree::new(&[thread]);
+/// let root = tree.node(tree.roots()[0]);
+/// assert_eq!(root.message(), Some(n(1)));
+///
+/// let depths: Vec<_> = tree
+///     .depth_first()
+///     .map(|(id, depth)| (tree.node(id).message().unwrap().get(), depth))
+///     .collect();
+/// assert_eq!(depths, [(1, 0), (2, 1), (3, 2), (4, 2)]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ThreadTree {
+    nodes: Vec<ThreadNode>,
+    roots: Vec<ThreadNodeId>,
+}
+
+impl ThreadTree {
+    /// Build a tree from threads, e.g., from a [`Data::Thread`](crate::response::Data::Thread).
+    pub fn new(threads: &[Thread]) -> Self {
+        let mut tree = Self {
+            nodes: Vec::new(),
+            roots: Vec::new(),
+        };
+
+        let mut stack: Vec<(Option<ThreadNodeId>, &Thread)> =
+            threads.iter().rev().map(|thread| (None, thread)).collect();
+
+        while let Some((parent, thread)) = stack.pop() {
+            let (last, answers) = match thread {
+                Thread::Members { prefix, answers } => {
+                    let mut last = parent;
+
+                    for message in prefix.as_ref() {
+                        last = Some(tree.push(Some(*message), last));
+                    }
+
+                    (last, answers.as_ref().map(|answers| answers.as_ref()))
+                }
+                Thread::Nested { answers } => {
+                    (Some(tree.push(None, parent)), Some(answers.as_ref()))
+                }
+            };
+
+            if let Some(answers) = answers {
+                stack.extend(answers.iter().rev().map(|answer| (last, answer)));
+            }
+        }
+
+        tree
+    }
+
+    fn push(&mut self, message: Option<NonZeroU32>, parent: Option<ThreadNodeId>) -> ThreadNodeId {
+        let id = ThreadNodeId(self.nodes.len());
+
+        self.nodes.push(ThreadNode {
+            message,
+            parent,
+            children: Vec::new(),
+        });
+
+        match parent {
+            Some(parent) => self.nodes[parent.0].children.push(id),
+            None => self.roots.push(id),
+        }
+
+        id
+    }
+
+    /// Return the roots, i.e., the first message of each thread.
+    pub fn roots(&self) -> &[ThreadNodeId] {
+        &self.roots
+    }
+
+    /// Return the node with the given identifier.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `id` doesn't belong to this tree.
+    pub fn node(&self, id: ThreadNodeId) -> &ThreadNode {
+        &self.nodes[id.0]
+    }
+
+    /// Return the number of nodes (including placeholders).
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Return `true` when the tree has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Iterate over all nodes (depth-first, pre-order) together with their depth.
+    pub fn depth_first(&self) -> DepthFirst<'_> {
+        DepthFirst {
+            tree: self,
+            stack: self.roots.iter().rev().map(|id| (*id, 0)).collect(),
+        }
+    }
+
+    /// Iterate over all messages (depth-first, pre-order), skipping placeholders.
+    ///
+    /// Use, e.g., `tree.messages().map(Uid::from)` to get typed UIDs.
+    pub fn messages(&self) -> impl Iterator<Item = NonZeroU32> + '_ {
+        self.depth_first()
+            .filter_map(|(id, _)| self.node(id).message())
+    }
+}
+
+/// Depth-first iterator over a [`ThreadTree`].
+///
+/// See [`ThreadTree::depth_first`].
+#[derive(Debug, Clone)]
+pub struct DepthFirst<'a> {
+    tree: &'a ThreadTree,
+    stack: Vec<(ThreadNodeId, usize)>,
+}
+
+impl Iterator for DepthFirst<'_> {
+    type Item = (ThreadNodeId, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (id, depth) = self.stack.pop()?;
+
+        self.stack.extend(
+            self.tree
+                .node(id)
+                .children
+                .iter()
+                .rev()
+                .map(|child| (*child, depth + 1)),
+        );
+
+        Some((id, depth))
+    }
+}
+
 #[cfg(feature = "arbitrary")]
 impl<'a> Arbitrary<'a> for Thread {
     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
@@ -136,6 +338,7 @@ fn arbitrary_thread_leaf(u: &mut Unstructured) -> arbitrary::Result<Thread> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum ThreadingAlgorithm<'a> {
     OrderedSubject,
@@ -154,7 +357,7 @@ impl<'a> From<Atom<'a>> for ThreadingAlgorithm<'a> {
 }
 
 impl Display for ThreadingAlgorithm<'_> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         f.write_str(match self {
             ThreadingAlgorithm::OrderedSubject => "ORDEREDSUBJECT",
             ThreadingAlgorithm::References => "REFERENCES",
@@ -167,6 +370,7 @@ impl Display for ThreadingAlgorithm<'_> {
 impl_arbitrary_try_from! { ThreadingAlgorithm<'a>, Atom<'a> }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct ThreadingAlgorithmOther<'a>(Atom<'a>);
 
@@ -175,3 +379,83 @@ impl AsRef<str> for ThreadingAlgorithmOther<'_> {
         self.0.as_ref()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequence::Uid;
+
+    fn n(n: u32) -> NonZeroU32 {
+        NonZeroU32::new(n).unwrap()
+    }
+
+    fn leaf(messages: &[u32]) -> Thread {
+        Thread::Members {
+            prefix: Vec1::try_from(messages.iter().copied().map(n).collect::<Vec<_>>()).unwrap(),
+            answers: None,
+        }
+    }
+
+    #[test]
+    fn test_thread_tree() {
+        // (2)(3 6 (4 23)(44 7 96))((5)(8))
+        let threads = vec![
+            leaf(&[2]),
+            Thread::Members {
+                prefix: Vec1::try_from(vec![n(3), n(6)]).unwrap(),
+                answers: Some(Vec2::try_from(vec![leaf(&[4, 23]), leaf(&[44, 7, 96])]).unwrap()),
+            },
+            Thread::Nested {
+                answers: Vec2::try_from(vec![leaf(&[5]), leaf(&[8])]).unwrap(),
+            },
+        ];
+
+        let tree = ThreadTree::new(&threads);
+        assert_eq!(tree.len(), 11);
+        assert_eq!(tree.roots().len(), 3);
+
+        let got: Vec<_> = tree
+            .depth_first()
+            .map(|(id, depth)| (tree.node(id).message().map(NonZeroU32::get), depth))
+            .collect();
+        assert_eq!(
+            got,
+            [
+                (Some(2), 0),
+                (Some(3), 0),
+                (Some(6), 1),
+                (Some(4), 2),
+                (Some(23), 3),
+                (Some(44), 2),
+                (Some(7), 3),
+                (Some(96), 4),
+                (None, 0),
+                (Some(5), 1),
+                (Some(8), 1),
+            ]
+        );
+
+        let six = tree.node(tree.roots()[1]).children()[0];
+        assert_eq!(tree.node(six).message(), Some(n(6)));
+        assert_eq!(tree.node(six).parent(), Some(tree.roots()[1]));
+        assert_eq!(
+            tree.node(six)
+                .children()
+                .iter()
+                .map(|id| tree.node(*id).message().unwrap().get())
+                .collect::<Vec<_>>(),
+            [4, 44]
+        );
+        assert_eq!(tree.node(tree.roots()[0]).parent(), None);
+
+        assert_eq!(
+            tree.messages().map(Uid::from).collect::<Vec<_>>(),
+            [2, 3, 6, 4, 23, 44, 7, 96, 5, 8]
+                .into_iter()
+                .map(|uid| Uid::new(uid).unwrap())
+                .collect::<Vec<_>>()
+        );
+
+        assert!(ThreadTree::new(&[]).is_empty());
+    }
+}
@@ -0,0 +1,83 @@
+//! Apple's XAPPLEPUSHSERVICE command
+//!
+//! Registers a device for push notifications. Sent by iOS/macOS Mail when the server advertised
+//! the XAPPLEPUSHSERVICE capability.
+//!
+//! This extends ...
+//!
+//! * [`Capability`](crate::response::Capability) with a new variant:
+//!
+//!     - [`Capability::XApplePushService`](crate::response::Capability::XApplePushService)
+//!
+//! * [`CommandBody`] with a new variant:
+//!
+//!     - [`CommandBody::XApplePushService`]
+//!
+//! * [`Data`] with a new variant:
+//!
+//!     - [`Data::XApplePushService`]
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "arbitrary")]
+use arbitrary::Arbitrary;
+use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    command::CommandBody,
+    core::{AString, Atom, Vec1},
+    response::Data,
+};
+
+impl<'a> CommandBody<'a> {
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the XAPPLEPUSHSERVICE capability.
+    /// </div>
+    pub fn xapplepushservice<P>(parameters: P) -> Result<Self, P::Error>
+    where
+        P: TryInto<Vec1<(Atom<'a>, ApplePushValue<'a>)>>,
+    {
+        Ok(CommandBody::XApplePushService {
+            parameters: parameters.try_into()?,
+        })
+    }
+}
+
+impl<'a> Data<'a> {
+    pub fn xapplepushservice<P>(parameters: P) -> Result<Self, P::Error>
+    where
+        P: TryInto<Vec1<(Atom<'a>, ApplePushValue<'a>)>>,
+    {
+        Ok(Data::XApplePushService {
+            parameters: parameters.try_into()?,
+        })
+    }
+}
+
+/// Value of an XAPPLEPUSHSERVICE parameter, e.g., `aps-version 2` or `mailboxes (INBOX Sent)`.
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub enum ApplePushValue<'a> {
+    /// A single value.
+    String(AString<'a>),
+    /// A parenthesized list of values.
+    List(Vec<AString<'a>>),
+}
+
+impl<'a> From<AString<'a>> for ApplePushValue<'a> {
+    fn from(value: AString<'a>) -> Self {
+        Self::String(value)
+    }
+}
+
+impl<'a> From<Vec<AString<'a>>> for ApplePushValue<'a> {
+    fn from(values: Vec<AString<'a>>) -> Self {
+        Self::List(values)
+    }
+}
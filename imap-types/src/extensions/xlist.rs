@@ -0,0 +1,50 @@
+//! The (legacy) XLIST command
+//!
+//! XLIST predates RFC 6154 (SPECIAL-USE) and is still sent by older Gmail and Zimbra clients.
+//!
+//! This extends ...
+//!
+//! * [`Capability`](crate::response::Capability) with a new variant:
+//!
+//!     - [`Capability::XList`](crate::response::Capability::XList)
+//!
+//! * [`CommandBody`] with a new variant:
+//!
+//!     - [`CommandBody::XList`]
+//!
+//! * [`Data`](crate::response::Data) with a new variant:
+//!
+//!     - [`Data::XList`](crate::response::Data::XList)
+//!
+//! * [`FlagNameAttribute`](crate::flag::FlagNameAttribute) with new variants:
+//!
+//!     - [`FlagNameAttribute::Inbox`](crate::flag::FlagNameAttribute::Inbox)
+//!     - [`FlagNameAttribute::AllMail`](crate::flag::FlagNameAttribute::AllMail)
+//!     - [`FlagNameAttribute::Spam`](crate::flag::FlagNameAttribute::Spam)
+//!     - [`FlagNameAttribute::Starred`](crate::flag::FlagNameAttribute::Starred)
+
+use crate::{
+    command::{error::ListError, CommandBody},
+    mailbox::{ListMailbox, Mailbox},
+};
+
+impl<'a> CommandBody<'a> {
+    /// Construct a XLIST command.
+    ///
+    /// <div class="warning">
+    /// This extension must only be used when the server advertised support for it sending the XLIST capability.
+    /// </div>
+    pub fn xlist<A, B>(
+        reference: A,
+        mailbox_wildcard: B,
+    ) -> Result<Self, ListError<A::Error, B::Error>>
+    where
+        A: TryInto<Mailbox<'a>>,
+        B: TryInto<ListMailbox<'a>>,
+    {
+        Ok(CommandBody::XList {
+            reference: reference.try_into().map_err(ListError::Reference)?,
+            mailbox_wildcard: mailbox_wildcard.try_into().map_err(ListError::Mailbox)?,
+        })
+    }
+}
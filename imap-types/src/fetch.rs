@@ -1,29 +1,38 @@
 //! Fetch-related types.
 
-#[cfg(feature = "ext_condstore_qresync")]
-use std::num::NonZeroU64;
-use std::{
+use alloc::vec::Vec;
+use core::{
     fmt::{Display, Formatter},
     num::NonZeroU32,
+    str::FromStr,
 };
 
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "ext_condstore_qresync")]
+use crate::extensions::condstore_qresync::ModSeq;
+#[cfg(feature = "ext_gmail")]
+use crate::extensions::gmail::GmailLabel;
 use crate::{
     body::BodyStructure,
     core::{AString, NString, NString8, Vec1},
     datetime::DateTime,
     envelope::Envelope,
+    error::{ValidationError, ValidationErrorKind},
     flag::FlagFetch,
+    sequence::Uid,
 };
 
 /// Shorthands for commonly-used message data items.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 #[non_exhaustive]
 pub enum Macro {
@@ -36,19 +45,47 @@ pub enum Macro {
 }
 
 impl Macro {
-    pub fn expand(&self) -> Vec<MessageDataItemName> {
-        use MessageDataItemName::*;
-
+    /// Message data item names `FAST` expands to.
+    pub const FAST_ITEM_NAMES: &'static [MessageDataItemName<'static>] = &[
+        MessageDataItemName::Flags,
+        MessageDataItemName::InternalDate,
+        MessageDataItemName::Rfc822Size,
+    ];
+
+    /// Message data item names `ALL` expands to.
+    pub const ALL_ITEM_NAMES: &'static [MessageDataItemName<'static>] = &[
+        MessageDataItemName::Flags,
+        MessageDataItemName::InternalDate,
+        MessageDataItemName::Rfc822Size,
+        MessageDataItemName::Envelope,
+    ];
+
+    /// Message data item names `FULL` expands to.
+    pub const FULL_ITEM_NAMES: &'static [MessageDataItemName<'static>] = &[
+        MessageDataItemName::Flags,
+        MessageDataItemName::InternalDate,
+        MessageDataItemName::Rfc822Size,
+        MessageDataItemName::Envelope,
+        MessageDataItemName::Body,
+    ];
+
+    /// Return the message data item names this macro expands to.
+    pub fn item_names(&self) -> &'static [MessageDataItemName<'static>] {
         match self {
-            Self::All => vec![Flags, InternalDate, Rfc822Size, Envelope],
-            Self::Fast => vec![Flags, InternalDate, Rfc822Size],
-            Self::Full => vec![Flags, InternalDate, Rfc822Size, Envelope, Body],
+            Self::All => Self::ALL_ITEM_NAMES,
+            Self::Fast => Self::FAST_ITEM_NAMES,
+            Self::Full => Self::FULL_ITEM_NAMES,
         }
     }
+
+    /// Expand the macro into a list of message data item names.
+    pub fn expand(&self) -> Vec<MessageDataItemName<'static>> {
+        self.item_names().to_vec()
+    }
 }
 
 impl Display for Macro {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.write_str(match self {
             Macro::All => "ALL",
             Macro::Fast => "FAST",
@@ -62,6 +99,7 @@ impl Display for Macro {
 /// A macro must be used by itself, and not in conjunction with other macros or data items.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum MacroOrMessageDataItemNames<'a> {
     Macro(Macro),
@@ -80,9 +118,55 @@ impl<'a> From<Vec<MessageDataItemName<'a>>> for MacroOrMessageDataItemNames<'a>
     }
 }
 
+impl<'a> MacroOrMessageDataItemNames<'a> {
+    /// Return the message data item names, expanding a macro if necessary.
+    pub fn item_names(&self) -> &[MessageDataItemName<'a>] {
+        match self {
+            Self::Macro(m) => m.item_names(),
+            Self::MessageDataItemNames(item_names) => item_names,
+        }
+    }
+}
+
+/// Commonly-used sets of message data item names.
+///
+/// Use, e.g., `MacroOrMessageDataItemNames::from(preset::METADATA_SYNC.to_vec())` in a FETCH.
+pub mod preset {
+    use super::MessageDataItemName::{self, *};
+
+    /// `UID FLAGS` (and `MODSEQ`) for re-synchronizing flags of known messages.
+    #[cfg(not(feature = "ext_condstore_qresync"))]
+    pub const FLAGS_SYNC: &[MessageDataItemName<'static>] = &[Uid, Flags];
+
+    /// `UID FLAGS` (and `MODSEQ`) for re-synchronizing flags of known messages.
+    #[cfg(feature = "ext_condstore_qresync")]
+    pub const FLAGS_SYNC: &[MessageDataItemName<'static>] = &[Uid, Flags, ModSeq];
+
+    /// `UID FLAGS INTERNALDATE RFC822.SIZE` (and `MODSEQ`) for synchronizing message metadata.
+    #[cfg(not(feature = "ext_condstore_qresync"))]
+    pub const METADATA_SYNC: &[MessageDataItemName<'static>] =
+        &[Uid, Flags, InternalDate, Rfc822Size];
+
+    /// `UID FLAGS INTERNALDATE RFC822.SIZE` (and `MODSEQ`) for synchronizing message metadata.
+    #[cfg(feature = "ext_condstore_qresync")]
+    pub const METADATA_SYNC: &[MessageDataItemName<'static>] =
+        &[Uid, Flags, ModSeq, InternalDate, Rfc822Size];
+
+    /// `UID FLAGS INTERNALDATE RFC822.SIZE ENVELOPE BODYSTRUCTURE` for building a message list.
+    pub const MESSAGE_LIST: &[MessageDataItemName<'static>] = &[
+        Uid,
+        Flags,
+        InternalDate,
+        Rfc822Size,
+        Envelope,
+        BodyStructure,
+    ];
+}
+
 /// Message data item name used to request a message data item.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 #[doc(alias = "FetchAttribute")]
 pub enum MessageDataItemName<'a> {
@@ -241,11 +325,39 @@ pub enum MessageDataItemName<'a> {
     #[cfg(feature = "ext_condstore_qresync")]
     #[cfg_attr(docsrs, doc(cfg("ext_condstore_qresync")))]
     ModSeq,
+
+    /// The Gmail message ID of a message.
+    ///
+    /// ```imap
+    /// X-GM-MSGID
+    /// ```
+    #[cfg(feature = "ext_gmail")]
+    #[cfg_attr(docsrs, doc(cfg("ext_gmail")))]
+    GmailMessageId,
+
+    /// The Gmail thread ID of a message.
+    ///
+    /// ```imap
+    /// X-GM-THRID
+    /// ```
+    #[cfg(feature = "ext_gmail")]
+    #[cfg_attr(docsrs, doc(cfg("ext_gmail")))]
+    GmailThreadId,
+
+    /// The Gmail labels of a message.
+    ///
+    /// ```imap
+    /// X-GM-LABELS
+    /// ```
+    #[cfg(feature = "ext_gmail")]
+    #[cfg_attr(docsrs, doc(cfg("ext_gmail")))]
+    GmailLabels,
 }
 
 /// Message data item.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 #[doc(alias = "FetchAttributeValue")]
 pub enum MessageDataItem<'a> {
@@ -366,7 +478,7 @@ pub enum MessageDataItem<'a> {
     /// ```imap
     /// UID
     /// ```
-    Uid(NonZeroU32),
+    Uid(Uid),
 
     Binary {
         section: Vec<NonZeroU32>,
@@ -380,7 +492,85 @@ pub enum MessageDataItem<'a> {
 
     #[cfg(feature = "ext_condstore_qresync")]
     #[cfg_attr(docsrs, doc(cfg("ext_condstore_qresync")))]
-    ModSeq(NonZeroU64),
+    ModSeq(ModSeq),
+
+    /// A unique, immutable 64-bit ID of a message across all of Gmail.
+    ///
+    /// ```imap
+    /// X-GM-MSGID
+    /// ```
+    #[cfg(feature = "ext_gmail")]
+    #[cfg_attr(docsrs, doc(cfg("ext_gmail")))]
+    GmailMessageId(u64),
+
+    /// A 64-bit ID of the thread a message belongs to.
+    ///
+    /// ```imap
+    /// X-GM-THRID
+    /// ```
+    #[cfg(feature = "ext_gmail")]
+    #[cfg_attr(docsrs, doc(cfg("ext_gmail")))]
+    GmailThreadId(u64),
+
+    /// The labels of a message.
+    ///
+    /// ```imap
+    /// X-GM-LABELS
+    /// ```
+    #[cfg(feature = "ext_gmail")]
+    #[cfg_attr(docsrs, doc(cfg("ext_gmail")))]
+    GmailLabels(Vec<GmailLabel<'a>>),
+}
+
+/// Lookup of message data items, e.g., in a [`Data::Fetch`](crate::response::Data::Fetch).
+///
+/// Each accessor returns the first matching item.
+impl<'a> Vec1<MessageDataItem<'a>> {
+    /// Return the `UID` (if any).
+    pub fn uid(&self) -> Option<Uid> {
+        self.as_ref().iter().find_map(|item| match item {
+            MessageDataItem::Uid(uid) => Some(*uid),
+            _ => None,
+        })
+    }
+
+    /// Return the `ENVELOPE` (if any).
+    pub fn envelope(&self) -> Option<&Envelope<'a>> {
+        self.as_ref().iter().find_map(|item| match item {
+            MessageDataItem::Envelope(envelope) => Some(envelope),
+            _ => None,
+        })
+    }
+
+    /// Return the `FLAGS` (if any).
+    pub fn flags(&self) -> Option<&[FlagFetch<'a>]> {
+        self.as_ref().iter().find_map(|item| match item {
+            MessageDataItem::Flags(flags) => Some(flags.as_slice()),
+            _ => None,
+        })
+    }
+
+    /// Return the data of `BODY[<section>]` (if any).
+    ///
+    /// Use `None` to look up `BODY[]`. The origin octet is ignored.
+    pub fn body_section(&self, section: Option<&Section<'a>>) -> Option<&NString<'a>> {
+        self.as_ref().iter().find_map(|item| match item {
+            MessageDataItem::BodyExt {
+                section: candidate,
+                data,
+                ..
+            } if candidate.as_ref() == section => Some(data),
+            _ => None,
+        })
+    }
+
+    /// Return the `INTERNALDATE` (if any).
+    pub fn internal_date(&self) -> Option<&DateTime> {
+        self.as_ref().iter().find_map(|item| match item {
+            MessageDataItem::InternalDate(date_time) => Some(date_time),
+            _ => None,
+        })
+    }
 }
 
 /// A part specifier is either a part number or one of the following:
@@ -429,6 +619,7 @@ pub enum MessageDataItem<'a> {
 /// ```
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum Section<'a> {
     Part(Part),
@@ -453,9 +644,180 @@ pub enum Section<'a> {
 
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct Part(pub Vec1<NonZeroU32>);
 
+impl TryFrom<&str> for Part {
+    type Error = ValidationError;
+
+    /// Parse a part in dotted notation, e.g., `1.2.3`.
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        if value.is_empty() {
+            return Err(ValidationError::new(ValidationErrorKind::Empty));
+        }
+
+        let numbers = value
+            .split('.')
+            .map(|number| {
+                // `u32::from_str` accepts a leading "+".
+                if !number.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(ValidationError::new(ValidationErrorKind::Invalid));
+                }
+
+                number
+                    .parse::<NonZeroU32>()
+                    .map_err(|_| ValidationError::new(ValidationErrorKind::Invalid))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self(Vec1::try_from(numbers)?))
+    }
+}
+
+impl FromStr for Part {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        for (index, number) in self.0.as_ref().iter().enumerate() {
+            if index > 0 {
+                f.write_str(".")?;
+            }
+
+            write!(f, "{number}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Section<'_> {
+    /// Start building a section.
+    ///
+    /// ```rust
+    /// use imap_types::fetch::{Part, Section};
+    ///
+    /// let section = Section::builder().part(1).part(2).mime().unwrap();
+    /// assert_eq!(section, Section::Mime(Part::try_from("1.2").unwrap()));
+    ///
+    /// let section = Section::builder()
+    ///     .header_fields(["From", "Subject"])
+    ///     .unwrap();
+    /// ```
+    pub fn builder() -> SectionBuilder {
+        SectionBuilder::default()
+    }
+}
+
+/// Builder for [`Section`]s.
+///
+/// Part numbers are collected via [`SectionBuilder::part`] and validated when the section is
+/// built, i.e., a `0` results in an error.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SectionBuilder {
+    part: Vec<u32>,
+}
+
+impl SectionBuilder {
+    /// Append a part number.
+    pub fn part(mut self, number: u32) -> Self {
+        self.part.push(number);
+        self
+    }
+
+    /// Build `<part>`, e.g., `BODY[1.2]`.
+    pub fn build(self) -> Result<Section<'static>, ValidationError> {
+        Ok(Section::Part(self.into_part()?))
+    }
+
+    /// Build `[<part>.]HEADER`.
+    pub fn header(self) -> Result<Section<'static>, ValidationError> {
+        Ok(Section::Header(self.into_maybe_part()?))
+    }
+
+    /// Build `[<part>.]HEADER.FIELDS (<fields>)`.
+    pub fn header_fields<'a, I, F>(self, fields: I) -> Result<Section<'a>, ValidationError>
+    where
+        I: IntoIterator<Item = F>,
+        F: TryInto<AString<'a>, Error = ValidationError>,
+    {
+        Ok(Section::HeaderFields(
+            self.into_maybe_part()?,
+            Self::fields(fields)?,
+        ))
+    }
+
+    /// Build `[<part>.]HEADER.FIELDS.NOT (<fields>)`.
+    pub fn header_fields_not<'a, I, F>(self, fields: I) -> Result<Section<'a>, ValidationError>
+    where
+        I: IntoIterator<Item = F>,
+        F: TryInto<AString<'a>, Error = ValidationError>,
+    {
+        Ok(Section::HeaderFieldsNot(
+            self.into_maybe_part()?,
+            Self::fields(fields)?,
+        ))
+    }
+
+    /// Build `[<part>.]TEXT`.
+    pub fn text(self) -> Result<Section<'static>, ValidationError> {
+        Ok(Section::Text(self.into_maybe_part()?))
+    }
+
+    /// Build `<part>.MIME`.
+    pub fn mime(self) -> Result<Section<'static>, ValidationError> {
+        Ok(Section::Mime(self.into_part()?))
+    }
+
+    fn into_part(self) -> Result<Part, ValidationError> {
+        let numbers = self
+            .part
+            .into_iter()
+            .map(|number| {
+                NonZeroU32::new(number)
+                    .ok_or_else(|| ValidationError::new(ValidationErrorKind::Invalid))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Part(Vec1::try_from(numbers)?))
+    }
+
+    fn into_maybe_part(self) -> Result<Option<Part>, ValidationError> {
+        if self.part.is_empty() {
+            Ok(None)
+        } else {
+            self.into_part().map(Some)
+        }
+    }
+
+    fn fields<'a, I, F>(fields: I) -> Result<Vec1<AString<'a>>, ValidationError>
+    where
+        I: IntoIterator<Item = F>,
+        F: TryInto<AString<'a>, Error = ValidationError>,
+    {
+        let fields = fields
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Vec1::try_from(fields)
+    }
+}
+
+impl From<Part> for SectionBuilder {
+    fn from(part: Part) -> Self {
+        Self {
+            part: part.0.into_iter().map(NonZeroU32::get).collect(),
+        }
+    }
+}
+
 /// A part specifier is either a part number or one of the following:
 /// `HEADER`, `HEADER.FIELDS`, `HEADER.FIELDS.NOT`, `MIME`, and `TEXT`.
 ///
@@ -472,6 +834,7 @@ pub struct Part(pub Vec1<NonZeroU32>);
 /// except in the case of a message which has no body and no blank
 /// line.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum PartSpecifier<'a> {
     PartNumber(u32),
@@ -481,3 +844,168 @@ pub enum PartSpecifier<'a> {
     Mime,
     Text,
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::{core::IString, flag::Flag};
+
+    #[test]
+    fn test_macro_expand() {
+        use MessageDataItemName::*;
+
+        assert_eq!(Macro::Fast.expand(), vec![Flags, InternalDate, Rfc822Size]);
+        assert_eq!(
+            Macro::All.expand(),
+            vec![Flags, InternalDate, Rfc822Size, Envelope]
+        );
+        assert_eq!(
+            Macro::Full.expand(),
+            vec![Flags, InternalDate, Rfc822Size, Envelope, Body]
+        );
+
+        assert_eq!(
+            MacroOrMessageDataItemNames::from(Macro::Fast).item_names(),
+            Macro::FAST_ITEM_NAMES
+        );
+        assert_eq!(
+            MacroOrMessageDataItemNames::from(preset::METADATA_SYNC.to_vec()).item_names(),
+            preset::METADATA_SYNC
+        );
+        assert!(preset::METADATA_SYNC.contains(&MessageDataItemName::Uid));
+    }
+
+    #[test]
+    fn test_part() {
+        let tests = [
+            ("1", vec![1]),
+            ("1.2.3", vec![1, 2, 3]),
+            ("4294967295.1", vec![u32::MAX, 1]),
+        ];
+
+        for (test, expected) in tests {
+            let part = Part::try_from(test).unwrap();
+            assert_eq!(
+                part.0.as_ref(),
+                expected
+                    .into_iter()
+                    .map(|n| NonZeroU32::new(n).unwrap())
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(part.to_string(), test);
+            assert_eq!(test.parse::<Part>().unwrap(), part);
+        }
+
+        for test in [
+            "",
+            ".",
+            "1.",
+            ".1",
+            "1..2",
+            "0",
+            "1.0",
+            "+1",
+            "-1",
+            "1.a",
+            "4294967296",
+        ] {
+            assert!(Part::try_from(test).is_err(), "{test}");
+        }
+    }
+
+    #[test]
+    fn test_section_builder() {
+        let part = Part::try_from("1.2.3").unwrap();
+
+        let tests = [
+            (
+                Section::builder().part(1).part(2).part(3).build(),
+                Section::Part(part.clone()),
+            ),
+            (Section::builder().header(), Section::Header(None)),
+            (
+                SectionBuilder::from(part.clone()).header(),
+                Section::Header(Some(part.clone())),
+            ),
+            (Section::builder().text(), Section::Text(None)),
+            (
+                Section::builder().part(1).part(2).part(3).mime(),
+                Section::Mime(part.clone()),
+            ),
+            (
+                Section::builder()
+                    .part(1)
+                    .part(2)
+                    .part(3)
+                    .header_fields(["From", "To"]),
+                Section::HeaderFields(
+                    Some(part.clone()),
+                    Vec1::try_from(vec![
+                        AString::try_from("From").unwrap(),
+                        AString::try_from("To").unwrap(),
+                    ])
+                    .unwrap(),
+                ),
+            ),
+            (
+                Section::builder().header_fields_not(vec![String::from("Received")]),
+                Section::HeaderFieldsNot(None, Vec1::from(AString::try_from("Received").unwrap())),
+            ),
+        ];
+
+        for (got, expected) in tests {
+            assert_eq!(got.unwrap(), expected);
+        }
+
+        assert!(Section::builder().build().is_err());
+        assert!(Section::builder().mime().is_err());
+        assert!(Section::builder().part(1).part(0).text().is_err());
+        assert!(Section::builder().header_fields::<_, &str>([]).is_err());
+        assert!(Section::builder().header_fields(["\0"]).is_err());
+    }
+
+    #[test]
+    fn test_message_data_item_lookup() {
+        let date_time = DateTime::from_unix_timestamp(0, 0).unwrap();
+
+        let items = Vec1::try_from(vec![
+            MessageDataItem::Flags(vec![FlagFetch::Flag(Flag::Seen)]),
+            MessageDataItem::Uid(Uid::try_from(42).unwrap()),
+            MessageDataItem::BodyExt {
+                section: Some(Section::Text(None)),
+                origin: None,
+                data: NString(Some(IString::try_from("text").unwrap())),
+            },
+            MessageDataItem::BodyExt {
+                section: None,
+                origin: Some(0),
+                data: NString(None),
+            },
+            MessageDataItem::InternalDate(date_time.clone()),
+        ])
+        .unwrap();
+
+        assert_eq!(items.uid(), Some(Uid::try_from(42).unwrap()));
+        assert_eq!(items.flags(), Some([FlagFetch::Flag(Flag::Seen)].as_ref()));
+        assert_eq!(items.envelope(), None);
+        assert_eq!(
+            items.body_section(Some(&Section::Text(None))),
+            Some(&NString(Some(IString::try_from("text").unwrap())))
+        );
+        assert_eq!(items.body_section(None), Some(&NString(None)));
+        assert_eq!(
+            items.body_section(Some(&Section::Mime(Part(Vec1::from(
+                NonZeroU32::new(1).unwrap()
+            ))))),
+            None
+        );
+        assert_eq!(items.internal_date(), Some(&date_time));
+
+        let items = Vec1::from(MessageDataItem::Rfc822Size(123));
+        assert_eq!(items.uid(), None);
+        assert_eq!(items.flags(), None);
+        assert_eq!(items.internal_date(), None);
+    }
+}
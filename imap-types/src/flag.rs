@@ -1,14 +1,20 @@
 //! Flag-related types.
 
-use std::fmt::{Display, Formatter};
+use alloc::string::String;
+use core::{
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
 
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::{core::Atom, error::ValidationError};
+use crate::{core::Atom, error::ValidationError, utils::ascii_lowercase};
 
 /// There are two types of flags in IMAP4rev1: System and keyword flags.
 ///
@@ -23,6 +29,7 @@ use crate::{core::Atom, error::ValidationError};
 ///
 /// Note that a flag of either type can be permanent or session-only.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum Flag<'a> {
     /// Message has been answered (`\Answered`).
@@ -45,12 +52,13 @@ pub enum Flag<'a> {
 ///
 /// It's guaranteed that this type can't represent any flag from [`Flag`].
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct FlagExtension<'a>(Atom<'a>);
 
 impl<'a> Flag<'a> {
     pub fn system(atom: Atom<'a>) -> Self {
-        match atom.as_ref().to_ascii_lowercase().as_ref() {
+        match ascii_lowercase(atom.as_ref(), &mut [0; 8]).unwrap_or_default() {
             "answered" => Self::Answered,
             "deleted" => Self::Deleted,
             "draft" => Self::Draft,
@@ -63,6 +71,36 @@ impl<'a> Flag<'a> {
     pub fn keyword(atom: Atom<'a>) -> Self {
         Self::Keyword(atom)
     }
+
+    /// Returns `true` for the system flags pre-defined in RFC 3501, e.g., `\Seen`.
+    ///
+    /// Note: Extension flags, e.g., `\Important`, are not considered system flags.
+    pub fn is_system(&self) -> bool {
+        matches!(
+            self,
+            Self::Answered | Self::Deleted | Self::Draft | Self::Flagged | Self::Seen
+        )
+    }
+
+    /// Returns `true` for extension flags, i.e., flags starting with `\` that are not pre-defined.
+    pub fn is_extension(&self) -> bool {
+        matches!(self, Self::Extension(_))
+    }
+
+    /// Returns `true` for keywords, e.g., `$Forwarded`.
+    pub fn is_keyword(&self) -> bool {
+        matches!(self, Self::Keyword(_))
+    }
+
+    /// Returns `false` for flags a client must not STORE, i.e., `\Recent`.
+    ///
+    /// Note: `\*` is not representable as a [`Flag`] (see [`FlagPerm::Asterisk`]).
+    pub fn is_storable(&self) -> bool {
+        match self {
+            Self::Extension(FlagExtension(atom)) => !atom.as_ref().eq_ignore_ascii_case("recent"),
+            _ => true,
+        }
+    }
 }
 
 impl<'a> TryFrom<&'a str> for Flag<'a> {
@@ -77,8 +115,31 @@ impl<'a> TryFrom<&'a str> for Flag<'a> {
     }
 }
 
+impl TryFrom<String> for Flag<'_> {
+    type Error = ValidationError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Ok(if let Some(value) = value.strip_prefix('\\') {
+            Self::system(Atom::try_from(String::from(value))?)
+        } else {
+            Self::keyword(Atom::try_from(value)?)
+        })
+    }
+}
+
+/// Parses the representation produced by [`Display`], e.g., `\Seen` or `$Forwarded`.
+///
+/// System flags are recognized case-insensitively.
+impl FromStr for Flag<'static> {
+    type Err = ValidationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Flag::try_from(String::from(s))
+    }
+}
+
 impl Display for Flag<'_> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         match self {
             Flag::Answered => f.write_str("\\Answered"),
             Flag::Deleted => f.write_str("\\Deleted"),
@@ -93,6 +154,7 @@ impl Display for Flag<'_> {
 
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum FlagFetch<'a> {
     Flag(Flag<'a>),
@@ -114,6 +176,7 @@ impl<'a> From<Flag<'a>> for FlagFetch<'a> {
 
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum FlagPerm<'a> {
     Flag(Flag<'a>),
@@ -131,6 +194,7 @@ impl<'a> From<Flag<'a>> for FlagPerm<'a> {
 
 /// Four name attributes are defined.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum FlagNameAttribute<'a> {
     /// It is not possible for any child levels of hierarchy to exist
@@ -150,6 +214,26 @@ pub enum FlagNameAttribute<'a> {
     /// last time the mailbox was selected. (`\Unmarked`)
     Unmarked,
 
+    /// The mailbox is the INBOX. (`\Inbox`, XLIST)
+    #[cfg(feature = "ext_xlist")]
+    #[cfg_attr(docsrs, doc(cfg("ext_xlist")))]
+    Inbox,
+
+    /// The mailbox contains all messages. (`\AllMail`, XLIST)
+    #[cfg(feature = "ext_xlist")]
+    #[cfg_attr(docsrs, doc(cfg("ext_xlist")))]
+    AllMail,
+
+    /// The mailbox contains spam. (`\Spam`, XLIST)
+    #[cfg(feature = "ext_xlist")]
+    #[cfg_attr(docsrs, doc(cfg("ext_xlist")))]
+    Spam,
+
+    /// The mailbox contains starred messages. (`\Starred`, XLIST)
+    #[cfg(feature = "ext_xlist")]
+    #[cfg_attr(docsrs, doc(cfg("ext_xlist")))]
+    Starred,
+
     /// An extension flags.
     Extension(FlagNameAttributeExtension<'a>),
 }
@@ -162,6 +246,7 @@ impl<'a> From<FlagNameAttributeExtension<'a>> for FlagNameAttribute<'a> {
 
 /// An extension flag.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct FlagNameAttributeExtension<'a>(Atom<'a>);
 
@@ -176,23 +261,39 @@ impl FlagNameAttribute<'_> {
 
 impl<'a> From<Atom<'a>> for FlagNameAttribute<'a> {
     fn from(atom: Atom<'a>) -> Self {
-        match atom.as_ref().to_ascii_lowercase().as_ref() {
+        match ascii_lowercase(atom.as_ref(), &mut [0; 11]).unwrap_or_default() {
             "noinferiors" => Self::Noinferiors,
             "noselect" => Self::Noselect,
             "marked" => Self::Marked,
             "unmarked" => Self::Unmarked,
+            #[cfg(feature = "ext_xlist")]
+            "inbox" => Self::Inbox,
+            #[cfg(feature = "ext_xlist")]
+            "allmail" => Self::AllMail,
+            #[cfg(feature = "ext_xlist")]
+            "spam" => Self::Spam,
+            #[cfg(feature = "ext_xlist")]
+            "starred" => Self::Starred,
             _ => Self::Extension(FlagNameAttributeExtension(atom)),
         }
     }
 }
 
 impl Display for FlagNameAttribute<'_> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         match self {
             Self::Noinferiors => f.write_str("\\Noinferiors"),
             Self::Noselect => f.write_str("\\Noselect"),
             Self::Marked => f.write_str("\\Marked"),
             Self::Unmarked => f.write_str("\\Unmarked"),
+            #[cfg(feature = "ext_xlist")]
+            Self::Inbox => f.write_str("\\Inbox"),
+            #[cfg(feature = "ext_xlist")]
+            Self::AllMail => f.write_str("\\AllMail"),
+            #[cfg(feature = "ext_xlist")]
+            Self::Spam => f.write_str("\\Spam"),
+            #[cfg(feature = "ext_xlist")]
+            Self::Starred => f.write_str("\\Starred"),
             Self::Extension(extension) => write!(f, "\\{}", extension.0),
         }
     }
@@ -200,6 +301,7 @@ impl Display for FlagNameAttribute<'_> {
 
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ToStatic)]
 pub enum StoreType {
     Replace,
@@ -209,6 +311,7 @@ pub enum StoreType {
 
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ToStatic)]
 pub enum StoreResponse {
     Answer,
@@ -219,6 +322,59 @@ pub enum StoreResponse {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_flag_display_from_str() {
+        let tests = [
+            (Flag::Answered, "\\Answered"),
+            (Flag::Deleted, "\\Deleted"),
+            (Flag::Draft, "\\Draft"),
+            (Flag::Flagged, "\\Flagged"),
+            (Flag::Seen, "\\Seen"),
+            (
+                Flag::Extension(FlagExtension(Atom::try_from("Important").unwrap())),
+                "\\Important",
+            ),
+            (
+                Flag::Keyword(Atom::try_from("$Forwarded").unwrap()),
+                "$Forwarded",
+            ),
+        ];
+
+        for (flag, expected) in tests {
+            assert_eq!(flag.to_string(), expected);
+            assert_eq!(expected.parse::<Flag>().unwrap(), flag);
+            assert_eq!(Flag::try_from(expected).unwrap(), flag);
+        }
+
+        assert_eq!("\\SEEN".parse::<Flag>().unwrap(), Flag::Seen);
+        assert!("".parse::<Flag>().is_err());
+        assert!("\\".parse::<Flag>().is_err());
+        assert!("two words".parse::<Flag>().is_err());
+    }
+
+    #[test]
+    fn test_flag_kind() {
+        assert!(Flag::Seen.is_system());
+        assert!(!Flag::Seen.is_keyword());
+
+        let extension = Flag::system(Atom::try_from("Important").unwrap());
+        assert!(!extension.is_system());
+        assert!(extension.is_extension());
+
+        let keyword = Flag::keyword(Atom::try_from("Seen").unwrap());
+        assert!(!keyword.is_system());
+        assert!(keyword.is_keyword());
+    }
+
+    #[test]
+    fn test_flag_is_storable() {
+        assert!(Flag::Seen.is_storable());
+        assert!(Flag::try_from("Recent").unwrap().is_storable());
+        assert!(Flag::try_from("\\Important").unwrap().is_storable());
+        assert!(!Flag::try_from("\\Recent").unwrap().is_storable());
+        assert!(!Flag::try_from("\\RECENT").unwrap().is_storable());
+    }
+
     #[test]
     fn test_flagfetch() {
         let flag: Flag<'static> = Flag::Seen;
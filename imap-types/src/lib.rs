@@ -57,18 +57,19 @@
 //! The following example is a server fetch response containing the size and MIME structure of a message with the sequence number (or UID) 42.
 //!
 //! ```
-//! use std::{borrow::Cow, num::NonZeroU32};
+//! use std::borrow::Cow;
 //!
 //! use imap_types::{
 //!     body::{BasicFields, Body, BodyStructure, SinglePartExtensionData, SpecificFields},
 //!     core::{IString, NString, Vec1},
 //!     fetch::MessageDataItem,
 //!     response::{Data, Response},
+//!     sequence::SeqNo,
 //! };
 //!
 //! let fetch = {
 //!     let data = Data::Fetch {
-//!         seq: NonZeroU32::new(42).unwrap(),
+//!         seq: SeqNo::new(42).unwrap(),
 //!         items: Vec1::try_from(vec![
 //!             MessageDataItem::Rfc822Size(1337),
 //!             MessageDataItem::Body(BodyStructure::Single {
@@ -97,6 +98,20 @@
 //! };
 //! ```
 //!
+//! Body structures can also be created with a builder that fills in defaults, e.g., `NIL` for the
+//! content id.
+//!
+//! ```
+//! use imap_types::body::BodyStructure;
+//!
+//! let body_structure = BodyStructure::text("html")
+//!     .description("Important message.")
+//!     .encoding("base64")
+//!     .size(512)
+//!     .build()
+//!     .unwrap();
+//! ```
+//!
 //! # Supported IMAP extensions
 //!
 //! | Description                                                                                             |
@@ -112,6 +127,8 @@
 //! | IMAP4 UIDPLUS extension ([RFC 2359], [RFC 4315])                                                        |
 //! | IMAP4 Binary Content Extension ([RFC 3516])                                                             |
 //! | Internet Message Access Protocol - SORT and THREAD Extensions ([RFC 5256], [RFC 5957])                  |
+//! | Contexts for IMAP4 (ESORT) ([RFC 5267])                                                                 |
+//! | Collected Extensions to IMAP4 ABNF ([RFC 4466])                                                         |
 //!
 //! # Features
 //!
@@ -120,11 +137,15 @@
 //! | Feature               | Description                                                                                                                  | Status     |
 //! |-----------------------|------------------------------------------------------------------------------------------------------------------------------|------------|
 //! | starttls              | IMAP4rev1 ([RFC 3501]; section 6.2.1)                                                                                        |            |
+//! | ext_acl               | IMAP4 Access Control List (ACL) Extension ([RFC 4314])                                                                       | Unfinished |
 //! | ext_condstore_qresync | IMAP Extensions: Quick Flag Changes Resynchronization (CONDSTORE) and Quick Mailbox Resynchronization (QRESYNC) ([RFC 7162]) | Unfinished |
+//! | ext_gmail             | Gmail IMAP Extensions ([X-GM-EXT-1])                                                                                         | Unfinished |
 //! | ext_id                | IMAP4 ID extension ([RFC 2971])                                                                                              | Unfinished |
 //! | ext_login_referrals   | IMAP4 Login Referrals ([RFC 2221])                                                                                           | Unfinished |
 //! | ext_mailbox_referrals | IMAP4 Mailbox Referrals ([RFC 2193])                                                                                         | Unfinished |
 //! | ext_metadata          | The IMAP METADATA Extension ([RFC 5464])                                                                                     | Unfinished |
+//! | ext_xapplepushservice | Apple's XAPPLEPUSHSERVICE command                                                                                            | Unfinished |
+//! | ext_xlist             | Legacy XLIST command ([XLIST])                                                                                               | Unfinished |
 //!
 //! STARTTLS is not an IMAP extension but feature-gated because it [should be avoided](https://nostarttls.secvuln.info/).
 //! For better performance and security, use "implicit TLS", i.e., IMAP-over-TLS on port 993, and don't use STARTTLS at all.
@@ -135,15 +156,28 @@
 //! |------------------|---------------------------------------------------------------|--------------------|
 //! | arbitrary        | Derive `Arbitrary` implementations                            | No                 |
 //! | serde            | Derive `serde`s `Serialize` and `Deserialize` implementations | No                 |
+//! | schemars         | Derive `JsonSchema` implementations (implies `serde`)         | No                 |
 //! | tag_generator    | Provide a generator for randomized `Tag`s                     | No                 |
+//! | cram_md5         | Provide a CRAM-MD5 helper (`auth::cram_md5`)                  | No                 |
+//! | bytes            | Share literal data via `bytes::Bytes` (`core::LiteralData`)   | No                 |
+//! | perf             | Intern well-known atoms, e.g., `$Junk`, in `into_static`      | No                 |
+//! | time             | Convert date types from/to the `time` crate                   | No                 |
+//! | jiff             | Convert date types from/to the `jiff` crate                   | No                 |
+//! | clock            | Provide `DateTime::now()`                                     | No                 |
+//! | std              | Use the standard library (see below)                          | Yes                |
 //!
 //! When using `arbitrary`, all types defined in imap-types implement the [Arbitrary] trait to ease testing.
 //! This is used, for example, to generate instances during fuzz-testing.
 //! (See, e.g., `imap-types/fuzz/fuzz_targets/to_static.rs`)
 //! When the `serde` feature is used, all types implement [Serde](https://serde.rs/)'s [Serialize](https://docs.serde.rs/serde/trait.Serialize.html) and
 //! [Deserialize](https://docs.serde.rs/serde/trait.Deserialize.html) traits. (Try running `cargo run --example serde_json`.)
+//! The representation doesn't rely on a self-describing format, i.e., compact binary formats such as postcard or bincode work, too.
+//! The `schemars` feature additionally derives [JsonSchema](https://docs.rs/schemars/latest/schemars/trait.JsonSchema.html)
+//! implementations describing this serde representation, e.g., to validate messages or generate bindings in other languages.
 //! Using `tag_generator` unlocks a `TagGenerator` to generate random tags.
 //! This may help to prevent attacks that depend on the knowledge of the next tag.
+//! Without the `std` feature, imap-types is `#![no_std]` and only requires `alloc`.
+//! The features `arbitrary`, `tag_generator`, `cram_md5`, and `clock` require `std`.
 //!
 //! [Arbitrary]: https://docs.rs/arbitrary/1.0.1/arbitrary/trait.Arbitrary.html
 //! [parse_command]: https://github.com/duesee/imap-codec/blob/main/imap-codec/examples/parse_command.rs
@@ -156,24 +190,32 @@
 //! [RFC 3501]: https://datatracker.ietf.org/doc/html/rfc3501
 //! [RFC 3516]: https://datatracker.ietf.org/doc/html/rfc3516
 //! [RFC 3691]: https://datatracker.ietf.org/doc/html/rfc3691
+//! [RFC 4314]: https://datatracker.ietf.org/doc/html/rfc4314
 //! [RFC 4315]: https://datatracker.ietf.org/doc/html/rfc4315
+//! [RFC 4466]: https://datatracker.ietf.org/doc/html/rfc4466
 //! [RFC 4959]: https://datatracker.ietf.org/doc/html/rfc4959
 //! [RFC 4978]: https://datatracker.ietf.org/doc/html/rfc4978
 //! [RFC 5161]: https://datatracker.ietf.org/doc/html/rfc5161
 //! [RFC 5256]: https://datatracker.ietf.org/doc/html/rfc5256
+//! [RFC 5267]: https://datatracker.ietf.org/doc/html/rfc5267
 //! [RFC 5464]: https://datatracker.ietf.org/doc/html/rfc5464
 //! [RFC 5957]: https://datatracker.ietf.org/doc/html/rfc5957
 //! [RFC 6851]: https://datatracker.ietf.org/doc/html/rfc6851
 //! [RFC 7162]: https://datatracker.ietf.org/doc/html/rfc7162
 //! [RFC 7888]: https://datatracker.ietf.org/doc/html/rfc7888
 //! [RFC 9208]: https://datatracker.ietf.org/doc/html/rfc9208
+//! [X-GM-EXT-1]: https://developers.google.com/gmail/imap/imap-extensions
+//! [XLIST]: https://developers.google.com/gmail/imap/imap-extensions#xlist_is_deprecated
 
+#![cfg_attr(not(feature = "std"), no_std)]
 #![forbid(unsafe_code)]
 #![deny(missing_debug_implementations)]
 // TODO(#313)
 // #![deny(missing_docs)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+extern crate alloc;
+
 use bounded_static::{IntoBoundedStatic, ToBoundedStatic};
 
 // Test examples from imap-types' README.
@@ -200,6 +242,7 @@ pub mod secret;
 pub mod sequence;
 pub mod state;
 pub mod status;
+pub mod tracker;
 pub mod utils;
 
 /// Create owned variant of object.
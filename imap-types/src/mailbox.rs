@@ -1,21 +1,29 @@
 //! Mailbox-related types.
 
-use std::{borrow::Cow, str::from_utf8};
+use alloc::{borrow::Cow, string::String, vec, vec::Vec};
+use core::{
+    fmt::{Debug, Formatter},
+    str::from_utf8,
+};
 
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    core::{impl_try_from, AString, IString},
+    core::{impl_try_from, AString, IString, QuotedChar},
     error::{ValidationError, ValidationErrorKind},
     mailbox::error::MailboxOtherError,
+    secret::{redaction_policy, Redacted},
     utils::indicators::is_list_char,
 };
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(feature = "serde", serde(try_from = "String"))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct ListCharString<'a>(pub(crate) Cow<'a, str>);
@@ -88,12 +96,70 @@ impl AsRef<[u8]> for ListCharString<'_> {
 
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum ListMailbox<'a> {
     Token(ListCharString<'a>),
     String(IString<'a>),
 }
 
+impl ListMailbox<'_> {
+    /// Check if `mailbox` matches this LIST pattern.
+    ///
+    /// `*` matches zero or more characters, and `%` matches zero or more characters except the
+    /// hierarchy `delimiter`. All other characters match themselves. `INBOX` is matched
+    /// case-insensitively.
+    ///
+    /// Note: The reference name of a LIST command is not considered. Servers should prepend it to
+    /// the pattern (as described in RFC 3501, section 6.3.8) before calling this method.
+    pub fn matches(&self, mailbox: &Mailbox, delimiter: Option<QuotedChar>) -> bool {
+        let pattern = match self {
+            Self::Token(token) => token.as_ref(),
+            Self::String(string) => string.as_ref(),
+        };
+
+        match mailbox {
+            Mailbox::Inbox => {
+                wildcard_match(pattern, b"INBOX", delimiter, u8::eq_ignore_ascii_case)
+            }
+            Mailbox::Other(other) => {
+                wildcard_match(pattern, other.as_ref(), delimiter, |a, b| a == b)
+            }
+        }
+    }
+}
+
+/// Match `name` against `pattern` containing `*` and `%` wildcards.
+///
+/// Uses dynamic programming to avoid exponential backtracking on patterns with many wildcards.
+fn wildcard_match(
+    pattern: &[u8],
+    name: &[u8],
+    delimiter: Option<QuotedChar>,
+    eq: impl Fn(&u8, &u8) -> bool,
+) -> bool {
+    let delimiter = delimiter.and_then(|delimiter| u8::try_from(delimiter.inner()).ok());
+
+    // `next[j]` tells if `pattern[i + 1..]` matches `name[j..]`.
+    let mut next = vec![false; name.len() + 1];
+    next[name.len()] = true;
+    let mut current = vec![false; name.len() + 1];
+
+    for p in pattern.iter().rev() {
+        for j in (0..=name.len()).rev() {
+            current[j] = match p {
+                b'*' => next[j] || (j < name.len() && current[j + 1]),
+                b'%' => next[j] || (j < name.len() && Some(name[j]) != delimiter && current[j + 1]),
+                p => j < name.len() && eq(p, &name[j]) && next[j + 1],
+            };
+        }
+
+        core::mem::swap(&mut next, &mut current);
+    }
+
+    next[0]
+}
+
 impl<'a> TryFrom<&'a str> for ListMailbox<'a> {
     type Error = ValidationError;
 
@@ -170,6 +236,7 @@ impl TryFrom<String> for ListMailbox<'_> {
 /// 5) Two characters, "#" and "&", have meanings by convention, and should be avoided except
 ///    when used in that convention.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum Mailbox<'a> {
     Inbox,
@@ -194,10 +261,25 @@ impl<'a> From<AString<'a>> for Mailbox<'a> {
 // `match`es on `Mailbox::Inbox`/`Mailbox::Other`.
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(feature = "serde", serde(try_from = "AString<'a>"))]
-#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+#[derive(Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct MailboxOther<'a>(pub(crate) AString<'a>);
 
+impl Debug for MailboxOther<'_> {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        let mut debug = f.debug_tuple("MailboxOther");
+
+        if redaction_policy().mailboxes {
+            debug.field(&Redacted(self.0.as_ref().len()));
+        } else {
+            debug.field(&self.0);
+        }
+
+        debug.finish()
+    }
+}
+
 impl<'a> MailboxOther<'a> {
     pub fn validate(value: impl AsRef<[u8]>) -> Result<(), MailboxOtherError> {
         if value.as_ref().eq_ignore_ascii_case(b"inbox") {
@@ -265,6 +347,32 @@ impl AsRef<[u8]> for MailboxOther<'_> {
     }
 }
 
+/// Write a mailbox for a one-line summary, i.e., `INBOX`, a quoted name, or `<redacted>`.
+///
+/// Respects the [`RedactionPolicy`](crate::secret::RedactionPolicy).
+pub(crate) fn fmt_mailbox(f: &mut Formatter, mailbox: &Mailbox) -> core::fmt::Result {
+    match mailbox {
+        Mailbox::Inbox => f.write_str("INBOX"),
+        Mailbox::Other(other) => fmt_name(f, other.as_ref()),
+    }
+}
+
+/// Write a mailbox (wildcard) for a one-line summary (see [`fmt_mailbox`]).
+pub(crate) fn fmt_list_mailbox(f: &mut Formatter, mailbox: &ListMailbox) -> core::fmt::Result {
+    match mailbox {
+        ListMailbox::Token(token) => fmt_name(f, token.as_ref()),
+        ListMailbox::String(string) => fmt_name(f, string.as_ref()),
+    }
+}
+
+fn fmt_name(f: &mut Formatter, name: &[u8]) -> core::fmt::Result {
+    if redaction_policy().mailboxes {
+        f.write_str("<redacted>")
+    } else {
+        write!(f, "{:?}", String::from_utf8_lossy(name))
+    }
+}
+
 /// Error-related types.
 pub mod error {
     use thiserror::Error;
@@ -287,7 +395,7 @@ mod tests {
     use super::*;
     #[cfg(feature = "serde")]
     use crate::core::AtomExt;
-    use crate::core::{AString, IString, Literal, LiteralMode};
+    use crate::core::{AString, IString, Literal, LiteralData, LiteralMode};
 
     #[test]
     fn test_conversion_mailbox() {
@@ -300,7 +408,7 @@ mod tests {
             (
                 "INBO²",
                 Mailbox::Other(MailboxOther(AString::String(IString::Literal(Literal {
-                    data: Cow::Borrowed("INBO²".as_bytes()),
+                    data: LiteralData::Borrowed("INBO²".as_bytes()),
                     mode: LiteralMode::Sync,
                 })))),
             ),
@@ -325,6 +433,121 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_list_mailbox_matches() {
+        let delimiter = Some(QuotedChar::try_from('/').unwrap());
+
+        let tests = [
+            ("*", "INBOX", true),
+            ("%", "INBOX", true),
+            ("inbox", "INBOX", true),
+            ("IN%", "INBOX", true),
+            ("*", "a/b/c", true),
+            ("%", "a/b/c", false),
+            ("a/%", "a/b", true),
+            ("a/%", "a/b/c", false),
+            ("a/%/c", "a/b/c", true),
+            ("a/*", "a/b/c", true),
+            ("a*c", "a/b/c", true),
+            ("a%c", "a/b/c", false),
+            ("a%", "abc", true),
+            ("", "", true),
+            ("", "a", false),
+            ("a", "A", false),
+            ("%%%", "abc", true),
+            ("*b*", "abc", true),
+            ("*d*", "abc", false),
+        ];
+
+        for (pattern, mailbox, expected) in tests {
+            let pattern = ListMailbox::try_from(pattern).unwrap();
+            let mailbox = Mailbox::try_from(mailbox).unwrap();
+
+            assert_eq!(
+                pattern.matches(&mailbox, delimiter),
+                expected,
+                "{pattern:?} vs. {mailbox:?}"
+            );
+        }
+
+        // Without a delimiter, `%` behaves like `*`.
+        assert!(ListMailbox::try_from("%")
+            .unwrap()
+            .matches(&Mailbox::try_from("a/b/c").unwrap(), None));
+    }
+
+    /// Naive (exponential) reference implementation.
+    fn wildcard_match_naive(pattern: &[u8], name: &[u8], delimiter: u8) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some((b'*', rest)) => {
+                (0..=name.len()).any(|i| wildcard_match_naive(rest, &name[i..], delimiter))
+            }
+            Some((b'%', rest)) => (0..=name.len())
+                .take_while(|i| *i == 0 || name[i - 1] != delimiter)
+                .any(|i| wildcard_match_naive(rest, &name[i..], delimiter)),
+            Some((p, rest)) => {
+                name.first() == Some(p) && wildcard_match_naive(rest, &name[1..], delimiter)
+            }
+        }
+    }
+
+    #[test]
+    fn test_list_mailbox_matches_properties() {
+        use rand::{rngs::SmallRng, seq::SliceRandom, Rng, SeedableRng};
+
+        let mut rng = SmallRng::seed_from_u64(1337);
+        let delimiter = QuotedChar::try_from('/').unwrap();
+
+        let random_string = |rng: &mut SmallRng, alphabet: &[u8]| -> String {
+            let len = rng.gen_range(1..8);
+            (0..len)
+                .map(|_| char::from(*alphabet.choose(rng).unwrap()))
+                .collect()
+        };
+
+        for _ in 0..2_000 {
+            let name = random_string(&mut rng, b"ab/");
+            let mailbox = Mailbox::try_from(name.as_str()).unwrap();
+
+            // `*` matches everything.
+            assert!(ListMailbox::try_from("*")
+                .unwrap()
+                .matches(&mailbox, Some(delimiter)));
+
+            // `%` matches everything without a delimiter.
+            assert_eq!(
+                ListMailbox::try_from("%")
+                    .unwrap()
+                    .matches(&mailbox, Some(delimiter)),
+                !name.contains('/')
+            );
+
+            // A name matches itself.
+            assert!(ListMailbox::try_from(name.as_str())
+                .unwrap()
+                .matches(&mailbox, Some(delimiter)));
+
+            // Replacing any part of the name with `*` still matches.
+            let start = rng.gen_range(0..=name.len());
+            let end = rng.gen_range(start..=name.len());
+            let pattern = format!("{}*{}", &name[..start], &name[end..]);
+            assert!(ListMailbox::try_from(pattern.as_str())
+                .unwrap()
+                .matches(&mailbox, Some(delimiter)));
+
+            // Random patterns agree with the naive implementation.
+            let pattern = random_string(&mut rng, b"ab/*%");
+            assert_eq!(
+                ListMailbox::try_from(pattern.as_str())
+                    .unwrap()
+                    .matches(&mailbox, Some(delimiter)),
+                wildcard_match_naive(pattern.as_bytes(), name.as_bytes(), b'/'),
+                "{pattern} vs. {name}"
+            );
+        }
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_deserialization_list_char_string() {
@@ -1,9 +1,11 @@
 //! # 7. Server Responses
 
-#[cfg(feature = "ext_condstore_qresync")]
-use std::num::NonZeroU64;
-use std::{
-    borrow::Cow,
+use alloc::{
+    borrow::{Cow, ToOwned},
+    string::String,
+    vec::Vec,
+};
+use core::{
     fmt::{Debug, Display, Formatter},
     num::{NonZeroU32, TryFromIntError},
 };
@@ -12,39 +14,50 @@ use std::{
 use arbitrary::Arbitrary;
 use base64::{engine::general_purpose::STANDARD as _base64, Engine};
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "ext_id")]
 use crate::core::{IString, NString};
+#[cfg(feature = "ext_acl")]
+use crate::extensions::acl::Rights;
 #[cfg(feature = "ext_metadata")]
 use crate::extensions::metadata::{MetadataCode, MetadataResponse};
-#[cfg(feature = "ext_condstore_qresync")]
-use crate::sequence::SequenceSet;
+#[cfg(feature = "ext_xapplepushservice")]
+use crate::extensions::xapplepushservice::ApplePushValue;
 use crate::{
     auth::AuthMechanism,
+    command::CommandBody,
     core::{impl_try_from, AString, Atom, Charset, QuotedChar, Tag, Text, Vec1},
     error::ValidationError,
     extensions::{
         compress::CompressionAlgorithm,
         enable::CapabilityEnable,
         quota::{QuotaGet, Resource},
-        sort::SortAlgorithm,
+        sort::{SearchReturnData, SortAlgorithm},
         thread::{Thread, ThreadingAlgorithm},
         uidplus::UidSet,
     },
     fetch::MessageDataItem,
     flag::{Flag, FlagNameAttribute, FlagPerm},
-    mailbox::Mailbox,
+    mailbox::{fmt_mailbox, Mailbox},
     response::error::{ContinueError, FetchError},
+    search::SearchResults,
+    sequence::{SeqNo, Uid},
     status::StatusDataItem,
+    utils::ascii_lowercase,
 };
+#[cfg(feature = "ext_condstore_qresync")]
+use crate::{extensions::condstore_qresync::ModSeq, sequence::SequenceSet};
 
 /// Greeting.
 ///
 /// Note: Don't use `code: None` *and* a `text` that starts with "[" as this would be ambiguous in IMAP.
 /// We could fix this but the fix would make this type unconformable to use.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct Greeting<'a> {
     pub kind: GreetingKind,
@@ -88,10 +101,55 @@ impl<'a> Greeting<'a> {
             text: text.try_into()?,
         })
     }
+
+    /// Create an `OK` greeting advertising `capabilities`, e.g., `* OK [CAPABILITY IMAP4REV1] ...`.
+    ///
+    /// Fails when `capabilities` is empty or `text` is invalid.
+    pub fn ok_with_capabilities<C>(capabilities: C, text: &'a str) -> Result<Self, ValidationError>
+    where
+        C: IntoIterator<Item = Capability<'a>>,
+    {
+        Self::with_capabilities(GreetingKind::Ok, capabilities, text)
+    }
+
+    /// Create a `PREAUTH` greeting advertising `capabilities`, e.g., `* PREAUTH [CAPABILITY IMAP4REV1] ...`.
+    ///
+    /// Fails when `capabilities` is empty or `text` is invalid.
+    pub fn preauth_with_capabilities<C>(
+        capabilities: C,
+        text: &'a str,
+    ) -> Result<Self, ValidationError>
+    where
+        C: IntoIterator<Item = Capability<'a>>,
+    {
+        Self::with_capabilities(GreetingKind::PreAuth, capabilities, text)
+    }
+
+    fn with_capabilities<C>(
+        kind: GreetingKind,
+        capabilities: C,
+        text: &'a str,
+    ) -> Result<Self, ValidationError>
+    where
+        C: IntoIterator<Item = Capability<'a>>,
+    {
+        let capabilities = Vec1::try_from(capabilities.into_iter().collect::<Vec<_>>())?;
+
+        Self::new(kind, Some(Code::Capability(capabilities)), text)
+    }
+
+    /// Return the capabilities advertised in the greeting (if any).
+    pub fn capabilities(&self) -> Option<&Vec1<Capability<'a>>> {
+        match &self.code {
+            Some(Code::Capability(capabilities)) => Some(capabilities),
+            _ => None,
+        }
+    }
 }
 
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ToStatic)]
 /// IMAP4rev1 defines three possible greetings at connection startup.
 pub enum GreetingKind {
@@ -112,6 +170,7 @@ pub enum GreetingKind {
 /// Response.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum Response<'a> {
     /// Command continuation request responses use the token "+" instead of a
@@ -130,8 +189,149 @@ pub enum Response<'a> {
     Status(Status<'a>),
 }
 
+/// One-line summary, e.g., `* 42 EXISTS` or `A1 OK done`.
+///
+/// Meant for log files and tracing spans. Response codes and most data are omitted, base64
+/// continuation requests (e.g., SASL challenges) are redacted, and mailboxes are redacted
+/// according to the [`RedactionPolicy`](crate::secret::RedactionPolicy).
+///
+/// ```rust
+/// use imap_types::response::{Data, Response, Status};
+///
+/// assert_eq!(Response::Data(Data::Exists(42)).to_string(), "* 42 EXISTS");
+///
+/// let status = Status::no(None, None, "Mailbox doesn't exist").unwrap();
+/// assert_eq!(
+///     Response::Status(status).to_string(),
+///     "* NO Mailbox doesn't exist"
+/// );
+/// ```
+impl Display for Response<'_> {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        match self {
+            Self::CommandContinuationRequest(CommandContinuationRequest::Basic(basic)) => {
+                write!(f, "+ {}", basic.text)
+            }
+            Self::CommandContinuationRequest(CommandContinuationRequest::Base64(_)) => {
+                f.write_str("+ <redacted>")
+            }
+            Self::Data(data) => {
+                f.write_str("* ")?;
+                fmt_data(f, data)
+            }
+            Self::Status(Status::Untagged(StatusBody { kind, text, .. })) => {
+                write!(f, "* {} {text}", status_kind_name(*kind))
+            }
+            Self::Status(Status::Tagged(Tagged {
+                tag,
+                body: StatusBody { kind, text, .. },
+            })) => write!(f, "{} {} {text}", tag.as_ref(), status_kind_name(*kind)),
+            Self::Status(Status::Bye(Bye { text, .. })) => write!(f, "* BYE {text}"),
+        }
+    }
+}
+
+/// One-line summary, e.g., `* OK IMAP server ready` (see [`Response`]'s `Display`).
+impl Display for Greeting<'_> {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+        let kind = match self.kind {
+            GreetingKind::Ok => "OK",
+            GreetingKind::PreAuth => "PREAUTH",
+            GreetingKind::Bye => "BYE",
+        };
+
+        write!(f, "* {kind} {}", self.text)
+    }
+}
+
+fn status_kind_name(kind: StatusKind) -> &'static str {
+    match kind {
+        StatusKind::Ok => "OK",
+        StatusKind::No => "NO",
+        StatusKind::Bad => "BAD",
+    }
+}
+
+fn fmt_data(f: &mut Formatter, data: &Data) -> core::fmt::Result {
+    match data {
+        Data::Capability(capabilities) => {
+            f.write_str("CAPABILITY")?;
+
+            for capability in capabilities.as_ref() {
+                write!(f, " {capability}")?;
+            }
+
+            Ok(())
+        }
+        Data::List { mailbox, .. } => {
+            f.write_str("LIST ")?;
+            fmt_mailbox(f, mailbox)
+        }
+        Data::Lsub { mailbox, .. } => {
+            f.write_str("LSUB ")?;
+            fmt_mailbox(f, mailbox)
+        }
+        Data::Status { mailbox, .. } => {
+            f.write_str("STATUS ")?;
+            fmt_mailbox(f, mailbox)
+        }
+        Data::Search(results, ..) => write!(f, "SEARCH ({} results)", results.len()),
+        Data::Sort(results, ..) => write!(f, "SORT ({} results)", results.len()),
+        Data::ESearch { .. } => f.write_str("ESEARCH"),
+        Data::Thread(threads) => write!(f, "THREAD ({} threads)", threads.len()),
+        Data::Flags(flags) => {
+            f.write_str("FLAGS (")?;
+
+            for (index, flag) in flags.iter().enumerate() {
+                if index > 0 {
+                    f.write_str(" ")?;
+                }
+
+                write!(f, "{flag}")?;
+            }
+
+            f.write_str(")")
+        }
+        Data::Exists(count) => write!(f, "{count} EXISTS"),
+        Data::Recent(count) => write!(f, "{count} RECENT"),
+        Data::Expunge(seq) => write!(f, "{seq} EXPUNGE"),
+        Data::Fetch { seq, items } => write!(f, "{seq} FETCH ({} items)", items.as_ref().len()),
+        Data::Enabled { capabilities } => {
+            f.write_str("ENABLED")?;
+
+            for capability in capabilities {
+                write!(f, " {capability}")?;
+            }
+
+            Ok(())
+        }
+        Data::Quota { .. } => f.write_str("QUOTA"),
+        Data::QuotaRoot { mailbox, .. } => {
+            f.write_str("QUOTAROOT ")?;
+            fmt_mailbox(f, mailbox)
+        }
+        #[cfg(feature = "ext_id")]
+        Data::Id { .. } => f.write_str("ID"),
+        #[cfg(feature = "ext_metadata")]
+        Data::Metadata { mailbox, .. } => {
+            f.write_str("METADATA ")?;
+            fmt_mailbox(f, mailbox)
+        }
+        #[cfg(feature = "ext_condstore_qresync")]
+        Data::Vanished { .. } => f.write_str("VANISHED"),
+        #[cfg(feature = "ext_xapplepushservice")]
+        Data::XApplePushService { .. } => f.write_str("XAPPLEPUSHSERVICE"),
+        #[cfg(feature = "ext_xlist")]
+        Data::XList { mailbox, .. } => {
+            f.write_str("XLIST ")?;
+            fmt_mailbox(f, mailbox)
+        }
+    }
+}
+
 /// Status response.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum Status<'a> {
     Untagged(StatusBody<'a>),
@@ -144,6 +344,7 @@ pub enum Status<'a> {
 /// Note: Don't use `code: None` *and* a `text` that starts with "[" as this would be ambiguous in IMAP.
 /// We could fix this but the fix would make this type unconformable to use.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct StatusBody<'a> {
     /// Status kind.
@@ -159,6 +360,7 @@ pub struct StatusBody<'a> {
 /// Status kind.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ToStatic)]
 pub enum StatusKind {
     /// Indicates an information from the server.
@@ -179,6 +381,7 @@ pub enum StatusKind {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct Tagged<'a> {
     pub tag: Tag<'a>,
@@ -212,6 +415,7 @@ pub struct Tagged<'a> {
 /// connection is closed; this will ensure that any pending untagged
 /// or completion responses are read and processed.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct Bye<'a> {
     pub code: Option<Code<'a>>,
@@ -294,6 +498,17 @@ impl<'a> Status<'a> {
         }
     }
 
+    /// Check whether the server may send this status while `command` is in progress.
+    ///
+    /// A status response is always allowed. Only its response code is checked (see
+    /// [`Code::is_allowed_during`]).
+    pub fn is_allowed_during(&self, command: &CommandBody) -> bool {
+        match self.code() {
+            Some(code) => code.is_allowed_during(command),
+            None => true,
+        }
+    }
+
     pub fn text(&self) -> &Text {
         match self {
             Self::Untagged(StatusBody { text, .. })
@@ -304,11 +519,59 @@ impl<'a> Status<'a> {
             | Self::Bye(Bye { text, .. }) => text,
         }
     }
+
+    /// Return the status kind, i.e., `OK`, `NO`, or `BAD`.
+    ///
+    /// Returns `None` for `BYE`.
+    pub fn kind(&self) -> Option<StatusKind> {
+        match self {
+            Self::Untagged(StatusBody { kind, .. })
+            | Self::Tagged(Tagged {
+                body: StatusBody { kind, .. },
+                ..
+            }) => Some(*kind),
+            Self::Bye(_) => None,
+        }
+    }
+
+    /// Is this a tagged status, i.e., a command completion result?
+    pub fn is_tagged(&self) -> bool {
+        matches!(self, Self::Tagged(_))
+    }
+
+    /// Is this an untagged status?
+    ///
+    /// Note: `BYE` is always untagged.
+    pub fn is_untagged(&self) -> bool {
+        !self.is_tagged()
+    }
+
+    /// Decompose the status into tag, kind, code, and text.
+    ///
+    /// The tag is `None` for untagged statuses, the kind is `None` for `BYE`.
+    pub fn into_parts(
+        self,
+    ) -> (
+        Option<Tag<'a>>,
+        Option<StatusKind>,
+        Option<Code<'a>>,
+        Text<'a>,
+    ) {
+        match self {
+            Self::Untagged(StatusBody { kind, code, text }) => (None, Some(kind), code, text),
+            Self::Tagged(Tagged {
+                tag,
+                body: StatusBody { kind, code, text },
+            }) => (Some(tag), Some(kind), code, text),
+            Self::Bye(Bye { code, text }) => (None, None, code, text),
+        }
+    }
 }
 
 /// ## 7.2 - 7.4 Server and Mailbox Status; Mailbox Size; Message Status
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum Data<'a> {
     // ## 7.2. Server Responses - Server and Mailbox Status
@@ -413,7 +676,7 @@ pub enum Data<'a> {
         /// Name
         mailbox: Mailbox<'a>,
         /// Status parenthesized list
-        items: Cow<'a, [StatusDataItem]>,
+        items: Vec<StatusDataItem<'a>>,
     },
 
     /// ### 7.2.5. SEARCH Response
@@ -425,21 +688,37 @@ pub enum Data<'a> {
     /// search criteria.  For SEARCH, these are message sequence numbers;
     /// for UID SEARCH, these are unique identifiers.  Each number is
     /// delimited by a space.
+    ///
+    /// Note: The numbers are stored as runs of consecutive numbers, see [`SearchResults`].
+    /// Unlike, e.g., [`Data::Fetch`], they aren't typed as [`SeqNo`] or [`Uid`] because the
+    /// response doesn't tell whether it answers a SEARCH or a UID SEARCH command.
     Search(
-        Vec<NonZeroU32>,
+        SearchResults,
         /// MODSEQ
         #[cfg(feature = "ext_condstore_qresync")]
         #[cfg_attr(docsrs, doc(cfg("ext_condstore_qresync")))]
-        Option<NonZeroU64>,
+        Option<ModSeq>,
     ),
 
     Sort(
         Vec<NonZeroU32>,
         #[cfg(feature = "ext_condstore_qresync")]
         #[cfg_attr(docsrs, doc(cfg("ext_condstore_qresync")))]
-        Option<NonZeroU64>,
+        Option<ModSeq>,
     ),
 
+    /// ESEARCH response (RFC 4731, RFC 5267)
+    ///
+    /// Sent instead of a SORT response when the SORT command specified return options.
+    ESearch {
+        /// Tag of the command that caused this response.
+        tag: Option<Tag<'a>>,
+        /// Whether the returned numbers are UIDs.
+        uid: bool,
+        /// Returned data.
+        data: Vec<SearchReturnData<'a>>,
+    },
+
     Thread(Vec<Thread>),
 
     /// ### 7.2.6.  FLAGS Response
@@ -536,7 +815,7 @@ pub enum Data<'a> {
     ///   response MAY be sent during a UID command.
     ///
     /// The update from the EXPUNGE response MUST be recorded by the client.
-    Expunge(NonZeroU32),
+    Expunge(SeqNo),
 
     /// ### 7.4.2. FETCH Response
     ///
@@ -547,7 +826,7 @@ pub enum Data<'a> {
     /// flag updates).
     Fetch {
         /// Sequence number.
-        seq: NonZeroU32,
+        seq: SeqNo,
         /// Message data items.
         items: Vec1<MessageDataItem<'a>>,
     },
@@ -590,6 +869,28 @@ pub enum Data<'a> {
         earlier: bool,
         known_uids: SequenceSet,
     },
+
+    #[cfg(feature = "ext_xapplepushservice")]
+    #[cfg_attr(docsrs, doc(cfg("ext_xapplepushservice")))]
+    /// XAPPLEPUSHSERVICE response
+    XApplePushService {
+        /// Parameters, e.g., `aps-version` and `aps-topic`.
+        parameters: Vec1<(Atom<'a>, ApplePushValue<'a>)>,
+    },
+
+    #[cfg(feature = "ext_xlist")]
+    #[cfg_attr(docsrs, doc(cfg("ext_xlist")))]
+    /// XLIST response
+    ///
+    /// The data is identical in format to the LIST response.
+    XList {
+        /// Name attributes
+        items: Vec<FlagNameAttribute<'a>>,
+        /// Hierarchy delimiter
+        delimiter: Option<QuotedChar>,
+        /// Name
+        mailbox: Mailbox<'a>,
+    },
 }
 
 impl<'a> Data<'a> {
@@ -626,12 +927,12 @@ impl<'a> Data<'a> {
     // }
 
     pub fn expunge(seq: u32) -> Result<Self, TryFromIntError> {
-        Ok(Self::Expunge(NonZeroU32::try_from(seq)?))
+        Ok(Self::Expunge(SeqNo::try_from(seq)?))
     }
 
     pub fn fetch<S, I>(seq: S, items: I) -> Result<Self, FetchError<S::Error, I::Error>>
     where
-        S: TryInto<NonZeroU32>,
+        S: TryInto<SeqNo>,
         I: TryInto<Vec1<MessageDataItem<'a>>>,
     {
         let seq = seq.try_into().map_err(FetchError::SeqOrUid)?;
@@ -641,6 +942,80 @@ impl<'a> Data<'a> {
     }
 }
 
+impl Data<'_> {
+    /// Names of the commands that can elicit this response (see [`CommandBody::name`]).
+    ///
+    /// Taken from the "Responses:" sections of RFC 3501 and the implemented extensions.
+    pub fn commands(&self) -> &'static [&'static str] {
+        match self {
+            Self::Capability(_) => &["CAPABILITY"],
+            Self::List { .. } => &["LIST"],
+            Self::Lsub { .. } => &["LSUB"],
+            Self::Status { .. } => &["STATUS"],
+            Self::Search(..) => &["SEARCH"],
+            Self::Sort(..) => &["SORT"],
+            Self::ESearch { .. } => &["SEARCH", "SORT"],
+            Self::Thread(_) => &["THREAD"],
+            Self::Flags(_) | Self::Exists(_) | Self::Recent(_) => &["SELECT", "EXAMINE"],
+            Self::Expunge(_) => &["EXPUNGE", "MOVE"],
+            Self::Fetch { .. } => &["FETCH", "STORE"],
+            Self::Enabled { .. } => &["ENABLE"],
+            Self::Quota { .. } => &["GETQUOTA", "GETQUOTAROOT", "SETQUOTA"],
+            Self::QuotaRoot { .. } => &["GETQUOTAROOT"],
+            #[cfg(feature = "ext_id")]
+            Self::Id { .. } => &["ID"],
+            #[cfg(feature = "ext_metadata")]
+            Self::Metadata { .. } => &["GETMETADATA"],
+            #[cfg(feature = "ext_condstore_qresync")]
+            Self::Vanished { .. } => &["SELECT", "EXAMINE", "FETCH", "EXPUNGE", "MOVE"],
+            #[cfg(feature = "ext_xapplepushservice")]
+            Self::XApplePushService { .. } => &["XAPPLEPUSHSERVICE"],
+            #[cfg(feature = "ext_xlist")]
+            Self::XList { .. } => &["XLIST"],
+        }
+    }
+
+    /// Check whether the server may send this response without a related command.
+    ///
+    /// This is the case for mailbox size and message status updates, i.e., `FLAGS`, `EXISTS`,
+    /// `RECENT`, `EXPUNGE`, and `FETCH` (as well as `VANISHED` and `METADATA` when enabled).
+    pub fn may_be_unsolicited(&self) -> bool {
+        match self {
+            Self::Flags(_)
+            | Self::Exists(_)
+            | Self::Recent(_)
+            | Self::Expunge(_)
+            | Self::Fetch { .. } => true,
+            #[cfg(feature = "ext_metadata")]
+            Self::Metadata { .. } => true,
+            #[cfg(feature = "ext_condstore_qresync")]
+            Self::Vanished { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Check whether the server may send this response while `command` is in progress.
+    ///
+    /// A `false` result indicates a protocol violation.
+    ///
+    /// ```rust
+    /// use imap_types::{
+    ///     command::CommandBody,
+    ///     response::{Capability, Data},
+    /// };
+    ///
+    /// let data = Data::Exists(42);
+    /// assert!(data.is_allowed_during(&CommandBody::Noop));
+    ///
+    /// let data = Data::capability(vec![Capability::Imap4Rev1]).unwrap();
+    /// assert!(data.is_allowed_during(&CommandBody::Capability));
+    /// assert!(!data.is_allowed_during(&CommandBody::Noop));
+    /// ```
+    pub fn is_allowed_during(&self, command: &CommandBody) -> bool {
+        self.may_be_unsolicited() || self.commands().contains(&command.name())
+    }
+}
+
 /// ## 7.5. Server Responses - Command Continuation Request
 ///
 /// The command continuation request response is indicated by a "+" token
@@ -661,6 +1036,7 @@ impl<'a> Data<'a> {
 /// space and those arguments.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 #[doc(alias = "Continue")]
 #[doc(alias = "Continuation")]
@@ -689,6 +1065,7 @@ impl<'a> CommandContinuationRequest<'a> {
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[cfg_attr(
     feature = "serde",
     serde(try_from = "CommandContinuationRequestBasicShadow")
@@ -712,7 +1089,7 @@ struct CommandContinuationRequestBasicShadow<'a> {
 impl<'a> TryFrom<CommandContinuationRequestBasicShadow<'a>>
     for CommandContinuationRequestBasic<'a>
 {
-    type Error = ContinueError<std::convert::Infallible>;
+    type Error = ContinueError<core::convert::Infallible>;
 
     fn try_from(value: CommandContinuationRequestBasicShadow<'a>) -> Result<Self, Self::Error> {
         Self::new(value.code, value.text)
@@ -765,6 +1142,7 @@ impl<'a> CommandContinuationRequestBasic<'a> {
 /// The currently defined response codes are:
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum Code<'a> {
     /// `ALERT`
@@ -884,7 +1262,7 @@ pub enum Code<'a> {
         /// UIDVALIDITY of destination mailbox
         uid_validity: NonZeroU32,
         /// UID assigned to appended message in destination mailbox
-        uid: NonZeroU32,
+        uid: Uid,
     },
 
     /// Message(s) have been copied to destination mailbox with stated UID(s)
@@ -905,7 +1283,7 @@ pub enum Code<'a> {
     /// code with every successful SELECT or EXAMINE command
     #[cfg(feature = "ext_condstore_qresync")]
     #[cfg_attr(docsrs, doc(cfg("ext_condstore_qresync")))]
-    HighestModSeq(NonZeroU64),
+    HighestModSeq(ModSeq),
 
     /// IMAP4 Extension for Conditional STORE Operation (RFC 4551)
     /// When the server finished performing the operation on all the
@@ -972,8 +1350,11 @@ impl<'a> Code<'a> {
         Ok(Self::Capability(caps.try_into()?))
     }
 
-    pub fn permanentflags(flags: Vec<FlagPerm<'a>>) -> Self {
-        Self::PermanentFlags(flags)
+    pub fn permanentflags<I>(flags: I) -> Self
+    where
+        I: IntoIterator<Item = FlagPerm<'a>>,
+    {
+        Self::PermanentFlags(flags.into_iter().collect())
     }
 
     pub fn uidnext(uidnext: u32) -> Result<Self, TryFromIntError> {
@@ -987,22 +1368,163 @@ impl<'a> Code<'a> {
     pub fn unseen(uidnext: u32) -> Result<Self, TryFromIntError> {
         Ok(Self::Unseen(NonZeroU32::try_from(uidnext)?))
     }
+
+    /// Construct an APPENDUID code (RFC 4315).
+    pub fn appenduid(uid_validity: u32, uid: u32) -> Result<Self, TryFromIntError> {
+        Ok(Self::AppendUid {
+            uid_validity: NonZeroU32::try_from(uid_validity)?,
+            uid: Uid::try_from(uid)?,
+        })
+    }
+
+    /// Construct a COPYUID code (RFC 4315).
+    pub fn copyuid(
+        uid_validity: u32,
+        source: UidSet,
+        destination: UidSet,
+    ) -> Result<Self, TryFromIntError> {
+        Ok(Self::CopyUid {
+            uid_validity: NonZeroU32::try_from(uid_validity)?,
+            source,
+            destination,
+        })
+    }
+
+    pub fn as_capability(&self) -> Option<&Vec1<Capability<'a>>> {
+        match self {
+            Self::Capability(capabilities) => Some(capabilities),
+            _ => None,
+        }
+    }
+
+    pub fn as_permanent_flags(&self) -> Option<&[FlagPerm<'a>]> {
+        match self {
+            Self::PermanentFlags(flags) => Some(flags),
+            _ => None,
+        }
+    }
+
+    pub fn as_uid_next(&self) -> Option<NonZeroU32> {
+        match self {
+            Self::UidNext(uid_next) => Some(*uid_next),
+            _ => None,
+        }
+    }
+
+    pub fn as_uid_validity(&self) -> Option<NonZeroU32> {
+        match self {
+            Self::UidValidity(uid_validity) => Some(*uid_validity),
+            _ => None,
+        }
+    }
+
+    pub fn as_unseen(&self) -> Option<NonZeroU32> {
+        match self {
+            Self::Unseen(unseen) => Some(*unseen),
+            _ => None,
+        }
+    }
+
+    /// Return `(uid_validity, uid)` of an APPENDUID code.
+    pub fn as_append_uid(&self) -> Option<(NonZeroU32, Uid)> {
+        match self {
+            Self::AppendUid { uid_validity, uid } => Some((*uid_validity, *uid)),
+            _ => None,
+        }
+    }
+
+    /// Return `(uid_validity, source, destination)` of a COPYUID code.
+    pub fn as_copy_uid(&self) -> Option<(NonZeroU32, &UidSet, &UidSet)> {
+        match self {
+            Self::CopyUid {
+                uid_validity,
+                source,
+                destination,
+            } => Some((*uid_validity, source, destination)),
+            _ => None,
+        }
+    }
+
+    /// Return the unknown code, see [`CodeOther::name`] and [`CodeOther::params`].
+    pub fn as_other(&self) -> Option<&CodeOther<'a>> {
+        match self {
+            Self::Other(other) => Some(other),
+            _ => None,
+        }
+    }
+}
+
+impl Code<'_> {
+    /// Names of the commands that can elicit this response code (see [`CommandBody::name`]).
+    ///
+    /// Taken from RFC 3501 and the implemented extensions.
+    pub fn commands(&self) -> &'static [&'static str] {
+        match self {
+            Self::Alert | Self::Parse | Self::Other(_) => &[],
+            #[cfg(any(feature = "ext_mailbox_referrals", feature = "ext_login_referrals"))]
+            Self::Referral(_) => &[],
+            Self::BadCharset { .. } => &["SEARCH", "SORT", "THREAD"],
+            Self::Capability(_) => &["LOGIN", "AUTHENTICATE", "STARTTLS"],
+            Self::PermanentFlags(_)
+            | Self::ReadOnly
+            | Self::ReadWrite
+            | Self::UidNext(_)
+            | Self::UidValidity(_)
+            | Self::Unseen(_)
+            | Self::UidNotSticky => &["SELECT", "EXAMINE"],
+            Self::TryCreate | Self::OverQuota => &["APPEND", "COPY", "MOVE"],
+            Self::CompressionActive => &["COMPRESS"],
+            Self::TooBig => &["APPEND"],
+            #[cfg(feature = "ext_metadata")]
+            Self::Metadata(_) => &["SETMETADATA", "GETMETADATA"],
+            Self::UnknownCte => &["FETCH"],
+            Self::AppendUid { .. } => &["APPEND"],
+            Self::CopyUid { .. } => &["COPY", "MOVE"],
+            #[cfg(feature = "ext_condstore_qresync")]
+            Self::HighestModSeq(_) | Self::NoModSeq | Self::Closed => &["SELECT", "EXAMINE"],
+            #[cfg(feature = "ext_condstore_qresync")]
+            Self::Modified(_) => &["STORE"],
+        }
+    }
+
+    /// Check whether the server may send this response code without a related command.
+    ///
+    /// This is the case for `ALERT`, `PARSE`, `REFERRAL`, `OVERQUOTA`, `CAPABILITY` (e.g., in
+    /// the greeting), and unknown codes.
+    pub fn may_be_unsolicited(&self) -> bool {
+        match self {
+            Self::Alert | Self::Parse | Self::OverQuota | Self::Capability(_) | Self::Other(_) => {
+                true
+            }
+            #[cfg(any(feature = "ext_mailbox_referrals", feature = "ext_login_referrals"))]
+            Self::Referral(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Check whether the server may send this response code while `command` is in progress.
+    ///
+    /// A `false` result indicates a protocol violation.
+    pub fn is_allowed_during(&self, command: &CommandBody) -> bool {
+        self.may_be_unsolicited() || self.commands().contains(&command.name())
+    }
 }
 
 /// An (unknown) code.
 ///
 /// It's guaranteed that this type can't represent any code from [`Code`].
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct CodeOther<'a>(Cow<'a, [u8]>);
 
 // We want a more readable `Debug` implementation.
 impl Debug for CodeOther<'_> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         struct BStr<'a>(&'a Cow<'a, [u8]>);
 
         impl Debug for BStr<'_> {
-            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
                 write!(
                     f,
                     "b\"{}\"",
@@ -1033,9 +1555,29 @@ impl<'a> CodeOther<'a> {
     pub fn inner(&self) -> &[u8] {
         self.0.as_ref()
     }
+
+    /// Name of the code, i.e., everything up to the first space.
+    pub fn name(&self) -> &[u8] {
+        self.split().0
+    }
+
+    /// Parameters of the code, i.e., everything after the first space (if any).
+    pub fn params(&self) -> Option<&[u8]> {
+        self.split().1
+    }
+
+    fn split(&self) -> (&[u8], Option<&[u8]>) {
+        let inner = self.inner();
+
+        match inner.iter().position(|b| *b == b' ') {
+            Some(position) => (&inner[..position], Some(&inner[position + 1..])),
+            None => (inner, None),
+        }
+    }
 }
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 #[non_exhaustive]
 pub enum Capability<'a> {
@@ -1061,6 +1603,14 @@ pub enum Capability<'a> {
     Compress {
         algorithm: CompressionAlgorithm,
     },
+    /// See RFC 4314.
+    #[cfg(feature = "ext_acl")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ext_acl")))]
+    Acl,
+    /// Rights supported in addition to the ones defined in RFC 2086 (see RFC 4314).
+    #[cfg(feature = "ext_acl")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ext_acl")))]
+    Rights(Rights),
     /// See RFC 2087 and RFC 9208
     Quota,
     /// See RFC 9208.
@@ -1077,7 +1627,15 @@ pub enum Capability<'a> {
     Id,
     /// See RFC 3691.
     Unselect,
+    #[cfg(feature = "ext_xapplepushservice")]
+    /// Apple's push notification service.
+    XApplePushService,
+    #[cfg(feature = "ext_xlist")]
+    /// Legacy XLIST command (Gmail, Zimbra).
+    XList,
     Sort(Option<SortAlgorithm<'a>>),
+    /// See RFC 5267.
+    ESort,
     Thread(ThreadingAlgorithm<'a>),
     #[cfg(feature = "ext_metadata")]
     /// Server supports (both) server annotations and mailbox annotations.
@@ -1100,7 +1658,7 @@ pub enum Capability<'a> {
 }
 
 impl Display for Capability<'_> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
         match self {
             Self::Imap4Rev1 => write!(f, "IMAP4REV1"),
             Self::Auth(mechanism) => write!(f, "AUTH={}", mechanism),
@@ -1115,6 +1673,10 @@ impl Display for Capability<'_> {
             Self::Idle => write!(f, "IDLE"),
             Self::Enable => write!(f, "ENABLE"),
             Self::Compress { algorithm } => write!(f, "COMPRESS={}", algorithm),
+            #[cfg(feature = "ext_acl")]
+            Self::Acl => write!(f, "ACL"),
+            #[cfg(feature = "ext_acl")]
+            Self::Rights(rights) => write!(f, "RIGHTS={}", rights),
             Self::Quota => write!(f, "QUOTA"),
             Self::QuotaRes(resource) => write!(f, "QUOTA=RES-{}", resource),
             Self::QuotaSet => write!(f, "QUOTASET"),
@@ -1124,8 +1686,13 @@ impl Display for Capability<'_> {
             #[cfg(feature = "ext_id")]
             Self::Id => write!(f, "ID"),
             Self::Unselect => write!(f, "UNSELECT"),
+            #[cfg(feature = "ext_xapplepushservice")]
+            Self::XApplePushService => write!(f, "XAPPLEPUSHSERVICE"),
+            #[cfg(feature = "ext_xlist")]
+            Self::XList => write!(f, "XLIST"),
             Self::Sort(None) => write!(f, "SORT"),
             Self::Sort(Some(algorithm)) => write!(f, "SORT={}", algorithm),
+            Self::ESort => write!(f, "ESORT"),
             Self::Thread(algorithm) => write!(f, "THREAD={}", algorithm),
             #[cfg(feature = "ext_metadata")]
             Self::Metadata => write!(f, "METADATA"),
@@ -1147,6 +1714,24 @@ impl_try_from!(Atom<'a>, 'a, Vec<u8>, Capability<'a>);
 impl_try_from!(Atom<'a>, 'a, &'a str, Capability<'a>);
 impl_try_from!(Atom<'a>, 'a, String, Capability<'a>);
 
+impl<'a> From<AuthMechanism<'a>> for Capability<'a> {
+    fn from(mechanism: AuthMechanism<'a>) -> Self {
+        Self::Auth(mechanism)
+    }
+}
+
+impl<'a> TryFrom<Capability<'a>> for AuthMechanism<'a> {
+    type Error = Capability<'a>;
+
+    /// Extract the mechanism of an `AUTH=` capability, or return the capability as is.
+    fn try_from(capability: Capability<'a>) -> Result<Self, Self::Error> {
+        match capability {
+            Capability::Auth(mechanism) => Ok(mechanism),
+            other => Err(other),
+        }
+    }
+}
+
 impl<'a> From<Atom<'a>> for Capability<'a> {
     fn from(atom: Atom<'a>) -> Self {
         fn split_once_cow<'a>(
@@ -1174,7 +1759,7 @@ impl<'a> From<Atom<'a>> for Capability<'a> {
 
         let cow = atom.into_inner();
 
-        match cow.to_ascii_lowercase().as_ref() {
+        match ascii_lowercase(&cow, &mut [0; 20]).unwrap_or_default() {
             "imap4rev1" => Self::Imap4Rev1,
             "logindisabled" => Self::LoginDisabled,
             #[cfg(feature = "starttls")]
@@ -1186,6 +1771,8 @@ impl<'a> From<Atom<'a>> for Capability<'a> {
             "login-referrals" => Self::LoginReferrals,
             "sasl-ir" => Self::SaslIr,
             "enable" => Self::Enable,
+            #[cfg(feature = "ext_acl")]
+            "acl" => Self::Acl,
             "quota" => Self::Quota,
             "quotaset" => Self::QuotaSet,
             "literal+" => Self::LiteralPlus,
@@ -1194,12 +1781,17 @@ impl<'a> From<Atom<'a>> for Capability<'a> {
             #[cfg(feature = "ext_id")]
             "id" => Self::Id,
             "sort" => Self::Sort(None),
+            "esort" => Self::ESort,
             #[cfg(feature = "ext_metadata")]
             "metadata" => Self::Metadata,
             #[cfg(feature = "ext_metadata")]
             "metadata-server" => Self::MetadataServer,
             "binary" => Self::Binary,
             "unselect" => Self::Unselect,
+            #[cfg(feature = "ext_xapplepushservice")]
+            "xapplepushservice" => Self::XApplePushService,
+            #[cfg(feature = "ext_xlist")]
+            "xlist" => Self::XList,
             #[cfg(feature = "ext_condstore_qresync")]
             "condstore" => Self::Unselect,
             #[cfg(feature = "ext_condstore_qresync")]
@@ -1208,7 +1800,7 @@ impl<'a> From<Atom<'a>> for Capability<'a> {
             _ => {
                 // TODO(efficiency)
                 if let Some((left, right)) = split_once_cow(cow.clone(), "=") {
-                    match left.as_ref().to_ascii_lowercase().as_ref() {
+                    match ascii_lowercase(&left, &mut [0; 8]).unwrap_or_default() {
                         "auth" => {
                             if let Ok(mechanism) = AuthMechanism::try_from(right) {
                                 return Self::Auth(mechanism);
@@ -1221,6 +1813,14 @@ impl<'a> From<Atom<'a>> for Capability<'a> {
                                 }
                             }
                         }
+                        #[cfg(feature = "ext_acl")]
+                        "rights" => {
+                            if let Ok(rights) = Rights::try_from(right.as_ref()) {
+                                if !rights.is_empty() {
+                                    return Self::Rights(rights);
+                                }
+                            }
+                        }
                         "quota" => {
                             if let Some((_, right)) =
                                 right.as_ref().to_ascii_lowercase().split_once("res-")
@@ -1255,9 +1855,186 @@ impl<'a> From<Atom<'a>> for Capability<'a> {
 ///
 /// It's guaranteed that this type can't represent any capability from [`Capability`].
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct CapabilityOther<'a>(Atom<'a>);
 
+/// A set of capabilities, e.g., taken from a greeting or a CAPABILITY response.
+///
+/// Offers queries for commonly needed capabilities so that clients don't need to scan a
+/// `Vec1<Capability>` manually.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, ToStatic)]
+pub struct CapabilitySet<'a>(Vec<Capability<'a>>);
+
+impl<'a> CapabilitySet<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take capabilities from a greeting, i.e., `* OK [CAPABILITY ...] ...`.
+    pub fn from_greeting(greeting: &Greeting<'a>) -> Option<Self> {
+        greeting.code.as_ref().and_then(Self::from_code)
+    }
+
+    /// Take capabilities from a response code, i.e., `[CAPABILITY ...]`.
+    pub fn from_code(code: &Code<'a>) -> Option<Self> {
+        match code {
+            Code::Capability(capabilities) => Some(Self::from(capabilities.clone())),
+            _ => None,
+        }
+    }
+
+    /// Take capabilities from a CAPABILITY response, i.e., `* CAPABILITY ...`.
+    pub fn from_data(data: &Data<'a>) -> Option<Self> {
+        match data {
+            Data::Capability(capabilities) => Some(Self::from(capabilities.clone())),
+            _ => None,
+        }
+    }
+
+    /// Insert a capability. Returns `false` when it was already present.
+    pub fn insert(&mut self, capability: Capability<'a>) -> bool {
+        if self.has(&capability) {
+            return false;
+        }
+
+        self.0.push(capability);
+        true
+    }
+
+    pub fn has(&self, capability: &Capability) -> bool {
+        self.0.contains(capability)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Capability<'a>> {
+        self.0.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Supported SASL mechanisms, i.e., all `AUTH=` capabilities.
+    pub fn auth_mechanisms(&self) -> impl Iterator<Item = &AuthMechanism<'a>> {
+        self.0.iter().filter_map(|capability| match capability {
+            Capability::Auth(mechanism) => Some(mechanism),
+            _ => None,
+        })
+    }
+
+    pub fn supports_auth(&self, mechanism: &AuthMechanism) -> bool {
+        self.auth_mechanisms()
+            .any(|supported| supported == mechanism)
+    }
+
+    /// Check if LOGIN is disallowed, i.e., if `LOGINDISABLED` was advertised.
+    pub fn login_disabled(&self) -> bool {
+        self.has(&Capability::LoginDisabled)
+    }
+
+    /// Check if non-synchronizing literals of any size can be used (`LITERAL+`).
+    pub fn supports_literal_plus(&self) -> bool {
+        self.has(&Capability::LiteralPlus)
+    }
+
+    /// Check if non-synchronizing literals up to 4096 bytes can be used (`LITERAL-` or `LITERAL+`).
+    pub fn supports_literal_minus(&self) -> bool {
+        self.has(&Capability::LiteralMinus) || self.supports_literal_plus()
+    }
+
+    pub fn supports_sasl_ir(&self) -> bool {
+        self.has(&Capability::SaslIr)
+    }
+
+    pub fn supports_idle(&self) -> bool {
+        self.has(&Capability::Idle)
+    }
+
+    pub fn supports_move(&self) -> bool {
+        self.has(&Capability::Move)
+    }
+
+    pub fn supports_uidplus(&self) -> bool {
+        self.has(&Capability::UidPlus)
+    }
+
+    /// Check if UTF-8 can be used in quoted strings (`UTF8=ACCEPT` or `UTF8=ONLY`, see RFC 6855).
+    pub fn supports_utf8_accept(&self) -> bool {
+        self.0.iter().any(|capability| match capability {
+            Capability::Other(CapabilityOther(atom)) => {
+                let atom = atom.as_ref();
+
+                atom.eq_ignore_ascii_case("UTF8=ACCEPT") || atom.eq_ignore_ascii_case("UTF8=ONLY")
+            }
+            _ => false,
+        })
+    }
+
+    /// Maximum message size accepted by APPEND (see RFC 7889).
+    ///
+    /// Returns `None` when `APPENDLIMIT` wasn't advertised, `Some(None)` when it was advertised
+    /// without a global limit, and `Some(Some(limit))` otherwise.
+    pub fn appendlimit(&self) -> Option<Option<u32>> {
+        self.0.iter().find_map(|capability| match capability {
+            Capability::Other(CapabilityOther(atom)) => {
+                let atom = atom.as_ref();
+
+                if atom.eq_ignore_ascii_case("APPENDLIMIT") {
+                    return Some(None);
+                }
+
+                let (key, value) = atom.split_once('=')?;
+
+                if key.eq_ignore_ascii_case("APPENDLIMIT") {
+                    value.parse().ok().map(Some)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        })
+    }
+}
+
+impl<'a> From<Vec<Capability<'a>>> for CapabilitySet<'a> {
+    fn from(value: Vec<Capability<'a>>) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+impl<'a> From<Vec1<Capability<'a>>> for CapabilitySet<'a> {
+    fn from(value: Vec1<Capability<'a>>) -> Self {
+        value.into_iter().collect()
+    }
+}
+
+impl<'a> FromIterator<Capability<'a>> for CapabilitySet<'a> {
+    fn from_iter<T: IntoIterator<Item = Capability<'a>>>(iter: T) -> Self {
+        let mut set = Self::new();
+
+        for capability in iter {
+            set.insert(capability);
+        }
+
+        set
+    }
+}
+
+impl<'a> IntoIterator for CapabilitySet<'a> {
+    type Item = Capability<'a>;
+    type IntoIter = alloc::vec::IntoIter<Capability<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
 /// Error-related types.
 pub mod error {
     use thiserror::Error;
@@ -1282,6 +2059,188 @@ pub mod error {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::extensions::uidplus::UidElement;
+
+    #[test]
+    fn test_code_accessors() {
+        let code = Code::permanentflags([FlagPerm::Flag(Flag::Seen), FlagPerm::Asterisk]);
+        assert_eq!(
+            code.as_permanent_flags(),
+            Some([FlagPerm::Flag(Flag::Seen), FlagPerm::Asterisk].as_ref())
+        );
+        assert_eq!(code.as_uid_next(), None);
+
+        let code = Code::uidnext(42).unwrap();
+        assert_eq!(code.as_uid_next(), NonZeroU32::new(42));
+        assert_eq!(code.as_capability(), None);
+
+        let code = Code::appenduid(38505, 3955).unwrap();
+        assert_eq!(
+            code.as_append_uid(),
+            Some((NonZeroU32::new(38505).unwrap(), Uid::new(3955).unwrap()))
+        );
+        assert!(Code::appenduid(0, 1).is_err());
+
+        let source = UidSet(Vec1::from(UidElement::Single(NonZeroU32::new(1).unwrap())));
+        let destination = UidSet(Vec1::from(UidElement::Single(NonZeroU32::new(2).unwrap())));
+        let code = Code::copyuid(1, source.clone(), destination.clone()).unwrap();
+        assert_eq!(
+            code.as_copy_uid(),
+            Some((NonZeroU32::new(1).unwrap(), &source, &destination))
+        );
+
+        let code = Code::Other(CodeOther::unvalidated(b"X-FOO 1 2".as_ref()));
+        let other = code.as_other().unwrap();
+        assert_eq!(other.name(), b"X-FOO");
+        assert_eq!(other.params(), Some(b"1 2".as_ref()));
+
+        let other = CodeOther::unvalidated(b"X-BAR".as_ref());
+        assert_eq!(other.name(), b"X-BAR");
+        assert_eq!(other.params(), None);
+    }
+
+    #[test]
+    fn test_capability_auth() {
+        let tests = [
+            ("AUTH=PLAIN", AuthMechanism::Plain),
+            ("auth=scram-sha-256", AuthMechanism::ScramSha256),
+            ("AUTH=SCRAM-SHA3-512-PLUS", AuthMechanism::ScramSha3_512Plus),
+            ("AUTH=GSSAPI", AuthMechanism::try_from("GSSAPI").unwrap()),
+        ];
+
+        for (test, expected) in tests {
+            let capability = Capability::try_from(test).unwrap();
+            assert_eq!(capability, Capability::from(expected.clone()));
+            assert_eq!(
+                Capability::try_from(capability.to_string().as_str()).unwrap(),
+                capability
+            );
+            assert_eq!(AuthMechanism::try_from(capability), Ok(expected));
+        }
+
+        assert_eq!(
+            AuthMechanism::try_from(Capability::Imap4Rev1),
+            Err(Capability::Imap4Rev1)
+        );
+    }
+
+    #[test]
+    fn test_capability_set() {
+        let greeting = Greeting::ok(
+            Some(Code::Capability(
+                Vec1::try_from(vec![
+                    Capability::Imap4Rev1,
+                    Capability::Auth(AuthMechanism::Plain),
+                    Capability::Auth(AuthMechanism::XOAuth2),
+                    Capability::LiteralPlus,
+                    Capability::LiteralPlus,
+                    Capability::try_from("APPENDLIMIT=35651584").unwrap(),
+                ])
+                .unwrap(),
+            )),
+            "Hello",
+        )
+        .unwrap();
+
+        let set = CapabilitySet::from_greeting(&greeting).unwrap();
+        assert_eq!(set.len(), 5);
+        assert!(set.has(&Capability::Imap4Rev1));
+        assert!(!set.has(&Capability::Idle));
+        assert!(!set.login_disabled());
+        assert!(set.supports_literal_plus());
+        assert!(set.supports_literal_minus());
+        assert!(set.supports_auth(&AuthMechanism::XOAuth2));
+        assert!(!set.supports_auth(&AuthMechanism::Login));
+        assert_eq!(
+            set.auth_mechanisms().collect::<Vec<_>>(),
+            vec![&AuthMechanism::Plain, &AuthMechanism::XOAuth2]
+        );
+        assert_eq!(set.appendlimit(), Some(Some(35651584)));
+
+        let set = CapabilitySet::from_data(
+            &Data::capability(vec![
+                Capability::LiteralMinus,
+                Capability::try_from("APPENDLIMIT").unwrap(),
+            ])
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(!set.supports_literal_plus());
+        assert!(set.supports_literal_minus());
+        assert_eq!(set.appendlimit(), Some(None));
+
+        let set = CapabilitySet::from(vec![Capability::Imap4Rev1]);
+        assert_eq!(set.appendlimit(), None);
+        assert!(CapabilitySet::from_greeting(&Greeting::ok(None, "Hello").unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_status_accessors() {
+        let status = Status::no(
+            Some(Tag::try_from("A1").unwrap()),
+            Some(Code::TryCreate),
+            "no such mailbox",
+        )
+        .unwrap();
+        assert!(status.is_tagged());
+        assert!(!status.is_untagged());
+        assert_eq!(status.kind(), Some(StatusKind::No));
+        assert_eq!(status.code(), Some(&Code::TryCreate));
+        assert_eq!(
+            status.into_parts(),
+            (
+                Some(Tag::try_from("A1").unwrap()),
+                Some(StatusKind::No),
+                Some(Code::TryCreate),
+                Text::try_from("no such mailbox").unwrap(),
+            )
+        );
+
+        let status = Status::ok(None, None, "hello").unwrap();
+        assert!(status.is_untagged());
+        assert_eq!(status.kind(), Some(StatusKind::Ok));
+        assert_eq!(status.code(), None);
+
+        let status = Status::bye(Some(Code::Alert), "bye").unwrap();
+        assert!(status.is_untagged());
+        assert_eq!(status.kind(), None);
+        assert_eq!(
+            status.into_parts(),
+            (
+                None,
+                None,
+                Some(Code::Alert),
+                Text::try_from("bye").unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_greeting_with_capabilities() {
+        let greeting =
+            Greeting::ok_with_capabilities([Capability::Imap4Rev1, Capability::Idle], "Hello")
+                .unwrap();
+        assert_eq!(greeting.kind, GreetingKind::Ok);
+        assert_eq!(
+            greeting.code,
+            Some(Code::Capability(
+                Vec1::try_from(vec![Capability::Imap4Rev1, Capability::Idle]).unwrap()
+            ))
+        );
+        assert_eq!(
+            greeting.capabilities().unwrap().as_ref(),
+            &[Capability::Imap4Rev1, Capability::Idle]
+        );
+
+        let greeting =
+            Greeting::preauth_with_capabilities(vec![Capability::Imap4Rev1], "Welcome").unwrap();
+        assert_eq!(greeting.kind, GreetingKind::PreAuth);
+        assert!(greeting.capabilities().is_some());
+
+        assert!(Greeting::ok_with_capabilities([], "Hello").is_err());
+        assert!(Greeting::ok_with_capabilities([Capability::Imap4Rev1], "").is_err());
+        assert!(Greeting::bye(None, "Bye").unwrap().capabilities().is_none());
+    }
 
     #[test]
     fn test_conversion_data() {
@@ -1322,4 +2281,71 @@ mod tests {
             .expect_err("invalid input should not deserialize successfully");
         assert_eq!(err.to_string(), r"ambiguity detected");
     }
+
+    #[test]
+    fn test_is_allowed_during() {
+        let select = CommandBody::select("INBOX").unwrap();
+        let noop = CommandBody::Noop;
+
+        assert_eq!(Data::Exists(1).commands(), &["SELECT", "EXAMINE"]);
+        assert!(Data::Exists(1).is_allowed_during(&noop));
+        assert!(Data::Flags(vec![]).is_allowed_during(&select));
+
+        let list = Data::List {
+            items: vec![],
+            delimiter: None,
+            mailbox: Mailbox::Inbox,
+        };
+        assert!(!list.may_be_unsolicited());
+        assert!(!list.is_allowed_during(&select));
+        assert!(list.is_allowed_during(&CommandBody::list("", "*").unwrap()));
+
+        let code = Code::uidvalidity(1).unwrap();
+        assert!(code.is_allowed_during(&select));
+        assert!(!code.is_allowed_during(&noop));
+        assert!(Code::Alert.is_allowed_during(&noop));
+
+        let status = Status::ok(None, Some(code), "...").unwrap();
+        assert!(status.is_allowed_during(&select));
+        assert!(!status.is_allowed_during(&noop));
+        assert!(Status::ok(None, None, "...")
+            .unwrap()
+            .is_allowed_during(&noop));
+    }
+
+    #[test]
+    fn test_display() {
+        let tests = [
+            (
+                Response::Status(
+                    Status::ok(Some(Tag::try_from("A1").unwrap()), None, "done").unwrap(),
+                ),
+                "A1 OK done",
+            ),
+            (
+                Response::Status(Status::bye(None, "bye").unwrap()),
+                "* BYE bye",
+            ),
+            (
+                Response::Data(Data::Flags(vec![Flag::Seen, Flag::Deleted])),
+                r"* FLAGS (\Seen \Deleted)",
+            ),
+            (Response::Data(Data::expunge(3).unwrap()), "* 3 EXPUNGE"),
+            (
+                Response::CommandContinuationRequest(CommandContinuationRequest::base64(
+                    &b"secret"[..],
+                )),
+                "+ <redacted>",
+            ),
+        ];
+
+        for (response, expected) in tests {
+            assert_eq!(response.to_string(), expected);
+        }
+
+        assert_eq!(
+            Greeting::preauth(None, "hello").unwrap().to_string(),
+            "* PREAUTH hello"
+        );
+    }
 }
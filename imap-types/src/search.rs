@@ -1,19 +1,27 @@
 //! Search-related types.
 
+use alloc::{boxed::Box, vec::Vec};
+use core::{num::NonZeroU32, ops::RangeInclusive, slice};
+
+use bounded_static::{IntoBoundedStatic, ToBoundedStatic};
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "ext_condstore_qresync")]
-use crate::extensions::condstore_qresync::{AttributeFlag, EntryTypeReq};
+use crate::extensions::condstore_qresync::{AttributeFlag, EntryTypeReq, ModSeq};
 use crate::{
-    core::{AString, Atom, Vec1},
+    core::{AString, Atom, AtomExt, IString, Quoted, Vec1},
     datetime::NaiveDate,
+    error::{ValidationError, ValidationErrorKind},
     sequence::SequenceSet,
 };
 
 /// The defined search keys.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum SearchKey<'a> {
     // <Not in RFC.>
@@ -165,15 +173,431 @@ pub enum SearchKey<'a> {
     #[cfg_attr(docsrs, doc(cfg("ext_condstore_qresync")))]
     ModSequence {
         entry: Option<(AttributeFlag<'a>, EntryTypeReq)>,
-        modseq: u64,
+        /// Mod-sequence or `None` for `0` (`mod-sequence-valzer`).
+        modseq: Option<ModSeq>,
     },
 }
 
-impl SearchKey<'_> {
+impl<'a> SearchKey<'a> {
     pub fn uid<S>(sequence_set: S) -> Self
     where
         S: Into<SequenceSet>,
     {
         Self::Uid(sequence_set.into())
     }
+
+    /// Normalize the search key.
+    ///
+    /// The result matches the same messages but is (usually) shorter:
+    ///
+    /// * nested `And`s are flattened, duplicate keys are removed, and `ALL` is dropped when
+    ///   combined with other keys,
+    /// * `And`s with a single key are replaced by the key,
+    /// * double negations are removed, i.e., `NOT NOT x` becomes `x`,
+    /// * `OR x x` becomes `x`, and
+    /// * strings are represented as atom (or quoted) whenever possible.
+    ///
+    /// Thus, two search keys can be compared after normalization.
+    pub fn normalize(self) -> Self {
+        match self {
+            Self::And(keys) => {
+                let mut normalized: Vec<SearchKey<'a>> = Vec::new();
+
+                let mut push = |key: SearchKey<'a>| {
+                    if !normalized.contains(&key) {
+                        normalized.push(key);
+                    }
+                };
+
+                for key in keys {
+                    match key.normalize() {
+                        Self::And(inner) => inner.into_iter().for_each(&mut push),
+                        key => push(key),
+                    }
+                }
+
+                // Duplicates were removed, so there is at least one other key.
+                if normalized.len() > 1 {
+                    normalized.retain(|key| *key != Self::All);
+                }
+
+                if normalized.len() == 1 {
+                    normalized.remove(0)
+                } else {
+                    Self::And(Vec1::unvalidated(normalized))
+                }
+            }
+            Self::Not(key) => match key.normalize() {
+                Self::Not(inner) => *inner,
+                key => Self::Not(Box::new(key)),
+            },
+            Self::Or(left, right) => {
+                let left = left.normalize();
+                let right = right.normalize();
+
+                if left == right {
+                    left
+                } else {
+                    Self::Or(Box::new(left), Box::new(right))
+                }
+            }
+            Self::Bcc(value) => Self::Bcc(canonicalize(value)),
+            Self::Body(value) => Self::Body(canonicalize(value)),
+            Self::Cc(value) => Self::Cc(canonicalize(value)),
+            Self::From(value) => Self::From(canonicalize(value)),
+            Self::Header(name, value) => Self::Header(canonicalize(name), canonicalize(value)),
+            Self::Subject(value) => Self::Subject(canonicalize(value)),
+            Self::Text(value) => Self::Text(canonicalize(value)),
+            Self::To(value) => Self::To(canonicalize(value)),
+            other => other,
+        }
+    }
+}
+
+/// Represent `value` as atom, or, if it is a literal, as quoted, whenever possible.
+fn canonicalize(value: AString<'_>) -> AString<'_> {
+    let canonical = match &value {
+        AString::Atom(_) => None,
+        AString::String(string) => {
+            let data = value.as_ref();
+
+            if let Ok(atom) = AtomExt::try_from(data.to_vec()) {
+                Some(AString::Atom(atom))
+            } else if matches!(string, IString::Literal(_)) {
+                Quoted::try_from(data.to_vec())
+                    .ok()
+                    .map(|quoted| AString::String(IString::Quoted(quoted)))
+            } else {
+                None
+            }
+        }
+    };
+
+    canonical.unwrap_or(value)
+}
+
+/// Numbers of a `SEARCH` response, i.e., message sequence numbers or UIDs.
+///
+/// Servers may return hundreds of thousands of numbers, most of them consecutive. Thus, the numbers
+/// are stored as runs of consecutive numbers, e.g., `1 2 3 4 5 42` is stored as `1..=5` and
+/// `42..=42`. The order of the numbers is retained.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        try_from = "Vec<(NonZeroU32, NonZeroU32)>",
+        into = "Vec<(NonZeroU32, NonZeroU32)>"
+    )
+)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SearchResults {
+    runs: Vec<(NonZeroU32, NonZeroU32)>,
+}
+
+#[cfg(feature = "schemars")]
+impl JsonSchema for SearchResults {
+    fn schema_name() -> String {
+        String::from("SearchResults")
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        <Vec<(NonZeroU32, NonZeroU32)>>::json_schema(gen)
+    }
+}
+
+impl SearchResults {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a number.
+    pub fn push(&mut self, number: NonZeroU32) {
+        self.push_range(number..=number)
+    }
+
+    /// Append all numbers of `range` (in ascending order).
+    pub fn push_range(&mut self, range: RangeInclusive<NonZeroU32>) {
+        let (start, end) = range.into_inner();
+
+        if start > end {
+            return;
+        }
+
+        if let Some((_, last)) = self.runs.last_mut() {
+            if last.checked_add(1) == Some(start) {
+                *last = end;
+                return;
+            }
+        }
+
+        self.runs.push((start, end));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// Return the count of numbers.
+    ///
+    /// Note: The count is a `u64` because runs may repeat numbers, i.e., it can exceed `u32::MAX`
+    /// and overflow a `usize` on 32-bit targets.
+    pub fn len(&self) -> u64 {
+        self.runs
+            .iter()
+            .map(|(start, end)| u64::from(end.get() - start.get()) + 1)
+            .sum()
+    }
+
+    /// Iterate over the runs of consecutive numbers.
+    pub fn ranges(&self) -> impl Iterator<Item = RangeInclusive<NonZeroU32>> + '_ {
+        self.runs.iter().map(|(start, end)| *start..=*end)
+    }
+
+    /// Iterate over the numbers.
+    pub fn iter(&self) -> SearchResultsIter<'_> {
+        SearchResultsIter {
+            runs: self.runs.iter(),
+            current: None,
+        }
+    }
+}
+
+impl Extend<NonZeroU32> for SearchResults {
+    fn extend<T: IntoIterator<Item = NonZeroU32>>(&mut self, iter: T) {
+        for number in iter {
+            self.push(number);
+        }
+    }
+}
+
+impl FromIterator<NonZeroU32> for SearchResults {
+    fn from_iter<T: IntoIterator<Item = NonZeroU32>>(iter: T) -> Self {
+        let mut results = Self::new();
+        results.extend(iter);
+        results
+    }
+}
+
+impl From<Vec<NonZeroU32>> for SearchResults {
+    fn from(numbers: Vec<NonZeroU32>) -> Self {
+        numbers.into_iter().collect()
+    }
+}
+
+impl TryFrom<Vec<(NonZeroU32, NonZeroU32)>> for SearchResults {
+    type Error = ValidationError;
+
+    fn try_from(runs: Vec<(NonZeroU32, NonZeroU32)>) -> Result<Self, Self::Error> {
+        let mut results = Self::new();
+
+        for (start, end) in runs {
+            if start > end {
+                return Err(ValidationError::new(ValidationErrorKind::Invalid));
+            }
+
+            results.push_range(start..=end);
+        }
+
+        Ok(results)
+    }
+}
+
+impl From<SearchResults> for Vec<(NonZeroU32, NonZeroU32)> {
+    fn from(results: SearchResults) -> Self {
+        results.runs
+    }
+}
+
+impl<'a> IntoIterator for &'a SearchResults {
+    type Item = NonZeroU32;
+    type IntoIter = SearchResultsIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoBoundedStatic for SearchResults {
+    type Static = Self;
+
+    fn into_static(self) -> Self::Static {
+        self
+    }
+}
+
+impl ToBoundedStatic for SearchResults {
+    type Static = Self;
+
+    fn to_static(&self) -> Self::Static {
+        self.clone()
+    }
+}
+
+/// Iterator over the numbers of [`SearchResults`].
+#[derive(Clone, Debug)]
+pub struct SearchResultsIter<'a> {
+    runs: slice::Iter<'a, (NonZeroU32, NonZeroU32)>,
+    current: Option<(NonZeroU32, NonZeroU32)>,
+}
+
+impl Iterator for SearchResultsIter<'_> {
+    type Item = NonZeroU32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (next, end) = match self.current {
+            Some(current) => current,
+            None => *self.runs.next()?,
+        };
+
+        self.current = if next < end {
+            next.checked_add(1).map(|next| (next, end))
+        } else {
+            None
+        };
+
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nz(values: &[u32]) -> Vec<NonZeroU32> {
+        values
+            .iter()
+            .map(|value| NonZeroU32::new(*value).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_search_key_normalize() {
+        use crate::core::Literal;
+
+        let and = |keys: Vec<SearchKey<'static>>| SearchKey::And(Vec1::try_from(keys).unwrap());
+        let not = |key: SearchKey<'static>| SearchKey::Not(Box::new(key));
+        let or =
+            |a: SearchKey<'static>, b: SearchKey<'static>| SearchKey::Or(Box::new(a), Box::new(b));
+
+        let tests = [
+            (SearchKey::Seen, SearchKey::Seen),
+            (and(vec![SearchKey::Seen]), SearchKey::Seen),
+            (
+                and(vec![
+                    SearchKey::Seen,
+                    and(vec![SearchKey::Deleted, and(vec![SearchKey::Seen])]),
+                    SearchKey::Draft,
+                ]),
+                and(vec![SearchKey::Seen, SearchKey::Deleted, SearchKey::Draft]),
+            ),
+            (and(vec![SearchKey::All, SearchKey::Seen]), SearchKey::Seen),
+            (and(vec![SearchKey::All, SearchKey::All]), SearchKey::All),
+            (not(not(SearchKey::Seen)), SearchKey::Seen),
+            (not(not(not(SearchKey::Seen))), not(SearchKey::Seen)),
+            (
+                or(and(vec![SearchKey::Seen]), SearchKey::Seen),
+                SearchKey::Seen,
+            ),
+            (
+                or(not(not(SearchKey::Seen)), SearchKey::Draft),
+                or(SearchKey::Seen, SearchKey::Draft),
+            ),
+            (
+                SearchKey::Subject(AString::String(IString::Quoted(
+                    Quoted::try_from("hello").unwrap(),
+                ))),
+                SearchKey::Subject(AString::Atom(AtomExt::try_from("hello").unwrap())),
+            ),
+            (
+                SearchKey::Subject(AString::String(IString::Literal(
+                    Literal::try_from("hello world").unwrap(),
+                ))),
+                SearchKey::Subject(AString::String(IString::Quoted(
+                    Quoted::try_from("hello world").unwrap(),
+                ))),
+            ),
+            (
+                SearchKey::Subject(AString::String(IString::Literal(
+                    Literal::try_from("a\r\nb").unwrap(),
+                ))),
+                SearchKey::Subject(AString::String(IString::Literal(
+                    Literal::try_from("a\r\nb").unwrap(),
+                ))),
+            ),
+            (
+                and(vec![
+                    SearchKey::Subject(AString::try_from("hello").unwrap()),
+                    SearchKey::Subject(AString::String(IString::Quoted(
+                        Quoted::try_from("hello").unwrap(),
+                    ))),
+                ]),
+                SearchKey::Subject(AString::try_from("hello").unwrap()),
+            ),
+        ];
+
+        for (test, expected) in tests {
+            let got = test.clone().normalize();
+            assert_eq!(got, expected, "{test:?}");
+            assert_eq!(got.clone().normalize(), got);
+        }
+    }
+
+    #[test]
+    fn test_search_results() {
+        #[allow(clippy::type_complexity)]
+        let tests: &[(&[u32], &[(u32, u32)])] = &[
+            (&[], &[]),
+            (&[1], &[(1, 1)]),
+            (&[1, 2, 3, 4, 5, 42], &[(1, 5), (42, 42)]),
+            (&[5, 4, 3], &[(5, 5), (4, 4), (3, 3)]),
+            (&[1, 3, 4, 1, 2], &[(1, 1), (3, 4), (1, 2)]),
+            (
+                &[u32::MAX - 1, u32::MAX, 1],
+                &[(u32::MAX - 1, u32::MAX), (1, 1)],
+            ),
+        ];
+
+        for (numbers, runs) in tests {
+            let results = SearchResults::from(nz(numbers));
+
+            assert_eq!(results.len(), numbers.len() as u64);
+            assert_eq!(results.is_empty(), numbers.is_empty());
+            assert_eq!(results.iter().collect::<Vec<_>>(), nz(numbers));
+            assert_eq!(
+                results
+                    .ranges()
+                    .map(|range| (range.start().get(), range.end().get()))
+                    .collect::<Vec<_>>(),
+                *runs
+            );
+        }
+    }
+
+    #[test]
+    fn test_search_results_push_range() {
+        let mut results = SearchResults::new();
+        results.push_range(NonZeroU32::new(1).unwrap()..=NonZeroU32::new(100_000).unwrap());
+        results.push(NonZeroU32::new(100_001).unwrap());
+        // Empty ranges are ignored.
+        results.push_range(NonZeroU32::new(5).unwrap()..=NonZeroU32::new(4).unwrap());
+
+        assert_eq!(results.len(), 100_001);
+        assert_eq!(results.ranges().count(), 1);
+        assert_eq!(
+            results,
+            (1..=100_001).map(|n| NonZeroU32::new(n).unwrap()).collect()
+        );
+
+        // The count doesn't overflow with all possible numbers.
+        let mut results = SearchResults::new();
+        results.push_range(NonZeroU32::MIN..=NonZeroU32::MAX);
+        results.push_range(NonZeroU32::MIN..=NonZeroU32::MAX);
+        assert_eq!(results.len(), 2 * u64::from(u32::MAX));
+
+        assert!(SearchResults::try_from(vec![(
+            NonZeroU32::new(2).unwrap(),
+            NonZeroU32::new(1).unwrap()
+        )])
+        .is_err());
+    }
 }
@@ -2,18 +2,28 @@
 //!
 //! This module provides a `Secret<T>` ensuring that sensitive values are not
 //! `Debug`-printed by accident.
+//!
+//! Further, a process-wide [`RedactionPolicy`] can be set to also redact user data, such as
+//! usernames, mailbox names, and literal contents, during `Debug`-printing. This makes it
+//! possible to share traces without leaking user data.
 
-use std::fmt::{Debug, Formatter};
+use core::{
+    fmt::{Debug, Formatter},
+    sync::atomic::{AtomicU8, Ordering},
+};
 
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 /// A wrapper to ensure that secrets are redacted during `Debug`-printing.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Clone, Eq, Hash, PartialEq, ToStatic)]
 pub struct Secret<T>(T);
 
@@ -27,6 +37,48 @@ impl<T> Secret<T> {
     pub fn declassify(&self) -> &T {
         &self.0
     }
+
+    /// Compare the secret with `other` in constant time.
+    ///
+    /// The running time only depends on the lengths of the secret and `other`, not on their
+    /// contents. Use this, e.g., to verify a password received via LOGIN or AUTHENTICATE.
+    ///
+    /// Works with `&str` and `&[u8]`:
+    ///
+    /// ```rust
+    /// use std::borrow::Cow;
+    ///
+    /// use imap_types::secret::Secret;
+    ///
+    /// let password = Secret::new(Cow::Borrowed("xyz123"));
+    /// assert!(password.compare_with("xyz123"));
+    ///
+    /// let data = Secret::new(Cow::Borrowed(&b"xyz123"[..]));
+    /// assert!(!data.compare_with(&b"xyz124"[..]));
+    /// ```
+    pub fn compare_with<U>(&self, other: &U) -> bool
+    where
+        T: AsRef<U>,
+        U: AsRef<[u8]> + ?Sized,
+    {
+        constant_time_eq(self.0.as_ref().as_ref(), other.as_ref())
+    }
+}
+
+/// Compare two byte slices in constant time.
+///
+/// Slices of different lengths are never equal. Only the length is leaked.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let diff = a
+        .iter()
+        .zip(b.iter())
+        .fold(0u8, |acc, (x, y)| core::hint::black_box(acc | (x ^ y)));
+
+    core::hint::black_box(diff) == 0
 }
 
 impl<T> From<T> for Secret<T> {
@@ -39,7 +91,7 @@ impl<T> Debug for Secret<T>
 where
     T: Debug,
 {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         #[cfg(not(debug_assertions))]
         return write!(f, "/* REDACTED */");
         #[cfg(debug_assertions)]
@@ -47,6 +99,99 @@ where
     }
 }
 
+/// Which values, besides [`Secret`]s, are redacted during `Debug`-printing.
+///
+/// Redacted values are printed as `/* REDACTED (<n> bytes) */`, i.e., their length is preserved.
+///
+/// ```rust
+/// use imap_types::{
+///     command::CommandBody,
+///     secret::{set_redaction_policy, RedactionPolicy},
+/// };
+///
+/// set_redaction_policy(RedactionPolicy::ALL);
+///
+/// let login = CommandBody::login("alice", "password").unwrap();
+/// assert!(!format!("{login:?}").contains("alice"));
+/// # set_redaction_policy(RedactionPolicy::NONE);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RedactionPolicy {
+    /// Redact usernames, i.e., the username in `LOGIN`.
+    pub usernames: bool,
+    /// Redact mailbox names (except `INBOX`).
+    pub mailboxes: bool,
+    /// Redact the contents of literals.
+    pub literals: bool,
+}
+
+impl RedactionPolicy {
+    /// Don't redact anything besides secrets (default).
+    pub const NONE: Self = Self {
+        usernames: false,
+        mailboxes: false,
+        literals: false,
+    };
+
+    /// Redact everything supported.
+    pub const ALL: Self = Self {
+        usernames: true,
+        mailboxes: true,
+        literals: true,
+    };
+
+    const USERNAMES: u8 = 1 << 0;
+    const MAILBOXES: u8 = 1 << 1;
+    const LITERALS: u8 = 1 << 2;
+
+    fn to_bits(self) -> u8 {
+        let mut bits = 0;
+
+        if self.usernames {
+            bits |= Self::USERNAMES;
+        }
+
+        if self.mailboxes {
+            bits |= Self::MAILBOXES;
+        }
+
+        if self.literals {
+            bits |= Self::LITERALS;
+        }
+
+        bits
+    }
+
+    fn from_bits(bits: u8) -> Self {
+        Self {
+            usernames: bits & Self::USERNAMES != 0,
+            mailboxes: bits & Self::MAILBOXES != 0,
+            literals: bits & Self::LITERALS != 0,
+        }
+    }
+}
+
+static REDACTION_POLICY: AtomicU8 = AtomicU8::new(0);
+
+/// Set the process-wide [`RedactionPolicy`].
+pub fn set_redaction_policy(policy: RedactionPolicy) {
+    REDACTION_POLICY.store(policy.to_bits(), Ordering::Relaxed);
+}
+
+/// Get the process-wide [`RedactionPolicy`].
+pub fn redaction_policy() -> RedactionPolicy {
+    RedactionPolicy::from_bits(REDACTION_POLICY.load(Ordering::Relaxed))
+}
+
+/// Placeholder for a redacted value of `.0` bytes.
+pub(crate) struct Redacted(pub(crate) usize);
+
+impl Debug for Redacted {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "/* REDACTED ({} bytes) */", self.0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -104,6 +249,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_compare_with() {
+        use alloc::borrow::Cow;
+
+        use super::Secret;
+
+        let password = Secret::new(Cow::Borrowed("xyz123"));
+        assert!(password.compare_with("xyz123"));
+        assert!(!password.compare_with("xyz124"));
+        assert!(!password.compare_with("xyz12"));
+        assert!(!password.compare_with(""));
+
+        let data = Secret::new(b"\x00\xffxyz".to_vec());
+        assert!(data.compare_with(&b"\x00\xffxyz"[..]));
+        assert!(!data.compare_with(&b"\x00\xfexyz"[..]));
+        assert!(!data.compare_with(&b""[..]));
+
+        let password = Secret::new(AString::from(Atom::try_from("pass").unwrap()));
+        assert!(password.compare_with(&b"pass"[..]));
+        assert!(!password.compare_with(&b"PASS"[..]));
+
+        assert!(Secret::new("").compare_with(""));
+    }
+
     #[test]
     fn test_that_secret_has_no_side_effects_on_eq() {
         assert_ne!(
@@ -1,9 +1,9 @@
-use std::{
+use alloc::{collections::VecDeque, vec, vec::Vec};
+use core::{
     cmp::max,
-    collections::VecDeque,
-    fmt::Debug,
+    fmt::{Debug, Display, Formatter},
     iter::Rev,
-    num::NonZeroU32,
+    num::{NonZeroU32, TryFromIntError},
     ops::{Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive},
     str::FromStr,
 };
@@ -11,6 +11,8 @@ use std::{
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
@@ -29,8 +31,76 @@ pub const MAX: NonZeroU32 = match NonZeroU32::new(u32::MAX) {
     None => panic!(),
 };
 
+macro_rules! impl_number {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+        #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+        #[cfg_attr(feature = "schemars", derive(JsonSchema))]
+        #[cfg_attr(feature = "serde", serde(transparent))]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ToStatic)]
+        pub struct $name(NonZeroU32);
+
+        impl $name {
+            /// Returns `None` when `value` is `0`.
+            pub const fn new(value: u32) -> Option<Self> {
+                match NonZeroU32::new(value) {
+                    Some(value) => Some(Self(value)),
+                    None => None,
+                }
+            }
+
+            pub const fn get(self) -> u32 {
+                self.0.get()
+            }
+        }
+
+        impl From<NonZeroU32> for $name {
+            fn from(value: NonZeroU32) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for NonZeroU32 {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl TryFrom<u32> for $name {
+            type Error = TryFromIntError;
+
+            fn try_from(value: u32) -> Result<Self, Self::Error> {
+                Ok(Self(NonZeroU32::try_from(value)?))
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter) -> core::fmt::Result {
+                Display::fmt(&self.0, f)
+            }
+        }
+    };
+}
+
+impl_number!(
+    /// A message sequence number.
+    ///
+    /// Sequence numbers are relative positions in the mailbox that change when messages are
+    /// expunged. Use [`Uid`] to refer to a message persistently.
+    SeqNo
+);
+
+impl_number!(
+    /// A unique identifier (UID) of a message.
+    ///
+    /// Distinct from [`SeqNo`], so that sequence numbers and UIDs can't be mixed up.
+    Uid
+);
+
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub struct SequenceSet(pub Vec1<Sequence>);
 
@@ -83,6 +153,8 @@ impl TryFrom<Vec<Sequence>> for SequenceSet {
     }
 }
 
+/// Keeps one sequence per number (in order). Use [`SequenceSet::try_from_iter`] (or
+/// [`SequenceSet::normalize`]) to compress consecutive numbers into ranges.
 impl TryFrom<Vec<NonZeroU32>> for SequenceSet {
     type Error = ValidationError;
 
@@ -125,6 +197,7 @@ impl FromStr for SequenceSet {
 
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 pub enum Sequence {
     Single(SeqOrUid),
@@ -176,6 +249,7 @@ impl FromStr for Sequence {
 
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Copy, ToStatic)]
 pub enum SeqOrUid {
     Value(NonZeroU32),
@@ -440,10 +514,13 @@ impl From<RangeInclusive<NonZeroU32>> for Sequence {
 impl<'a> SequenceSet {
     /// Iterate over a sorted, deduplicated set of sequence numbers or UIDs.
     ///
+    /// `*` is resolved to `largest`, i.e., the largest sequence number or UID in use, and reversed
+    /// ranges, e.g., `5:3`, are treated as `3:5` (see RFC 3501, section 9: "`seq-range`").
+    ///
     /// # Example
     ///
     /// ```
-    /// use std::num::NonZeroU32;
+    /// use core::num::NonZeroU32;
     ///
     /// use imap_types::sequence::SequenceSet;
     ///
@@ -459,7 +536,7 @@ impl<'a> SequenceSet {
     /// );
     /// ```
     pub fn iter(&'a self, largest: NonZeroU32) -> impl Iterator<Item = NonZeroU32> + 'a {
-        let ranges = simplify(self.clone(), largest, true);
+        let ranges = simplify(self, largest, true);
         let ranges = cleanup(ranges);
 
         SequenceSetIter {
@@ -475,7 +552,7 @@ impl<'a> SequenceSet {
     /// # Example
     ///
     /// ```
-    /// use std::num::NonZeroU32;
+    /// use core::num::NonZeroU32;
     ///
     /// use imap_types::sequence::SequenceSet;
     ///
@@ -491,7 +568,7 @@ impl<'a> SequenceSet {
     /// );
     /// ```
     pub fn iter_naive(&'a self, largest: NonZeroU32) -> impl Iterator<Item = NonZeroU32> + 'a {
-        let ranges = simplify(self.clone(), largest, false);
+        let ranges = simplify(self, largest, false);
 
         SequenceSetIter {
             ranges,
@@ -500,6 +577,153 @@ impl<'a> SequenceSet {
     }
 }
 
+impl SequenceSet {
+    /// Create the shortest sequence set containing all `values`.
+    ///
+    /// The values are sorted and deduplicated, and consecutive values are compressed into ranges.
+    /// Returns an error when `values` is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use core::num::NonZeroU32;
+    ///
+    /// use imap_types::sequence::SequenceSet;
+    ///
+    /// let values = [5, 1, 2, 3, 9, 4, 3].map(|n| NonZeroU32::new(n).unwrap());
+    ///
+    /// assert_eq!(
+    ///     SequenceSet::try_from_iter(values).unwrap(),
+    ///     SequenceSet::try_from("1:5,9").unwrap(),
+    /// );
+    /// ```
+    pub fn try_from_iter<I>(values: I) -> Result<Self, ValidationError>
+    where
+        I: IntoIterator<Item = NonZeroU32>,
+    {
+        let ranges: VecDeque<_> = values
+            .into_iter()
+            .map(|value| (value.get(), value.get()))
+            .collect();
+
+        if ranges.is_empty() {
+            return Err(ValidationError::new(ValidationErrorKind::Empty));
+        }
+
+        Ok(SequenceSet(Vec1::unvalidated(from_ranges(cleanup(ranges)))))
+    }
+
+    /// Check if the sequence set contains `value`.
+    ///
+    /// `*` is resolved to `largest`, i.e., the largest sequence number or UID in use.
+    pub fn contains(&self, value: NonZeroU32, largest: NonZeroU32) -> bool {
+        let value = value.get();
+
+        simplify(self, largest, true)
+            .into_iter()
+            .any(|(start, end)| start <= value && value <= end)
+    }
+
+    /// Return the sorted set with overlapping and adjacent ranges merged.
+    ///
+    /// Sequences that contain `*` can't be merged without knowing the largest sequence number or
+    /// UID in use. These are kept (deduplicated) at the end.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use imap_types::sequence::SequenceSet;
+    ///
+    /// let seq = SequenceSet::try_from("7,5:1,*:3,2:6,*,3:*").unwrap();
+    ///
+    /// assert_eq!(seq.normalize(), SequenceSet::try_from("1:7,3:*").unwrap());
+    /// ```
+    pub fn normalize(&self) -> SequenceSet {
+        let mut ranges = VecDeque::new();
+        let mut unresolved = Vec::<Sequence>::new();
+
+        for sequence in self.0.as_ref() {
+            let sequence = match *sequence {
+                Sequence::Single(SeqOrUid::Value(value)) => {
+                    ranges.push_back((value.get(), value.get()));
+                    continue;
+                }
+                Sequence::Range(SeqOrUid::Value(a), SeqOrUid::Value(b)) => {
+                    ranges.push_back((a.get().min(b.get()), a.get().max(b.get())));
+                    continue;
+                }
+                Sequence::Range(SeqOrUid::Asterisk, SeqOrUid::Asterisk) => {
+                    Sequence::Single(SeqOrUid::Asterisk)
+                }
+                Sequence::Range(SeqOrUid::Asterisk, value) => {
+                    Sequence::Range(value, SeqOrUid::Asterisk)
+                }
+                ref sequence => sequence.clone(),
+            };
+
+            if !unresolved.contains(&sequence) {
+                unresolved.push(sequence);
+            }
+        }
+
+        // `*` is contained in every `n:*`.
+        if unresolved
+            .iter()
+            .any(|sequence| matches!(sequence, Sequence::Range(_, SeqOrUid::Asterisk)))
+        {
+            unresolved.retain(|sequence| *sequence != Sequence::Single(SeqOrUid::Asterisk));
+        }
+
+        let mut sequences = if ranges.is_empty() {
+            Vec::new()
+        } else {
+            from_ranges(cleanup(ranges))
+        };
+        sequences.extend(unresolved);
+
+        // We started with a non-empty set and every sequence is either merged or kept.
+        SequenceSet(Vec1::unvalidated(sequences))
+    }
+
+    /// Return the (normalized) union of both sets.
+    pub fn union(&self, other: &SequenceSet) -> SequenceSet {
+        let mut sequences = self.0.as_ref().to_vec();
+        sequences.extend_from_slice(other.0.as_ref());
+
+        SequenceSet(Vec1::unvalidated(sequences)).normalize()
+    }
+
+    /// Return the (normalized) intersection of both sets or `None` when it's empty.
+    ///
+    /// `*` is resolved to `largest`, i.e., the largest sequence number or UID in use.
+    pub fn intersect(&self, other: &SequenceSet, largest: NonZeroU32) -> Option<SequenceSet> {
+        let a = cleanup(simplify(self, largest, true));
+        let b = cleanup(simplify(other, largest, true));
+
+        let mut intersection = VecDeque::new();
+        let (mut i, mut j) = (0, 0);
+
+        while i < a.len() && j < b.len() {
+            let start = max(a[i].0, b[j].0);
+            let end = a[i].1.min(b[j].1);
+
+            if start <= end {
+                intersection.push_back((start, end));
+            }
+
+            if a[i].1 < b[j].1 {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        Vec1::try_from(from_ranges(intersection))
+            .ok()
+            .map(SequenceSet)
+    }
+}
+
 impl SeqOrUid {
     pub fn expand(&self, largest: NonZeroU32) -> NonZeroU32 {
         match self {
@@ -562,11 +786,11 @@ impl Iterator for Sorting {
 // * Use u32 instead of NonZeroU32 (for internal purposes)
 // * Expand Single(a) to (a, a)
 // * Sort Range(a, b) so that a <= b
-fn simplify(sequence_set: SequenceSet, largest: NonZeroU32, sort: bool) -> VecDeque<(u32, u32)> {
+fn simplify(sequence_set: &SequenceSet, largest: NonZeroU32, sort: bool) -> VecDeque<(u32, u32)> {
     sequence_set
         .0
-         .0
-        .into_iter()
+        .as_ref()
+        .iter()
         .map(|seq| match seq {
             Sequence::Single(a) => (u32::from(a.expand(largest)), u32::from(a.expand(largest))),
             Sequence::Range(a, b) => {
@@ -610,6 +834,24 @@ fn cleanup(remaining: VecDeque<(u32, u32)>) -> VecDeque<(u32, u32)> {
     stack
 }
 
+// Convert sorted `(start, end)` ranges back into sequences.
+fn from_ranges(ranges: VecDeque<(u32, u32)>) -> Vec<Sequence> {
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            // We know here that `start >= 1` and `end >= 1`.
+            let start = SeqOrUid::Value(NonZeroU32::new(start).unwrap());
+            let end = SeqOrUid::Value(NonZeroU32::new(end).unwrap());
+
+            if start == end {
+                Sequence::Single(start)
+            } else {
+                Sequence::Range(start, end)
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use std::num::NonZeroU32;
@@ -859,6 +1101,129 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_iteration_resolves_asterisk_and_reversed_ranges() {
+        let tests = [
+            ("*", vec![7]),
+            ("*:5", vec![5, 6, 7]),
+            ("5:*", vec![5, 6, 7]),
+            ("9:*", vec![7, 8, 9]),
+            ("3:1,2", vec![1, 2, 3]),
+            ("6:*,1:2", vec![1, 2, 6, 7]),
+        ];
+
+        for (test, expected) in tests {
+            let seq = SequenceSet::try_from(test).unwrap();
+            let got: Vec<_> = seq
+                .iter(NonZeroU32::new(7).unwrap())
+                .map(NonZeroU32::get)
+                .collect();
+
+            assert_eq!(expected, got);
+        }
+    }
+
+    #[test]
+    fn test_contains() {
+        let seq = SequenceSet::try_from("1,5:3,9:*").unwrap();
+        let largest = NonZeroU32::new(7).unwrap();
+
+        let got: Vec<_> = (1..=10)
+            .filter(|n| seq.contains(NonZeroU32::new(*n).unwrap(), largest))
+            .collect();
+
+        assert_eq!(got, [1, 3, 4, 5, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let tests = [
+            ("1", "1"),
+            ("3,2,1", "1:3"),
+            ("1:3,5:7", "1:3,5:7"),
+            ("1:3,4:7", "1:7"),
+            ("5:1,3:9,2", "1:9"),
+            ("*", "*"),
+            ("*:*,*", "*"),
+            ("*:4,4:*,*,1", "1,4:*"),
+        ];
+
+        for (test, expected) in tests {
+            let got = SequenceSet::try_from(test).unwrap().normalize();
+            assert_eq!(SequenceSet::try_from(expected).unwrap(), got);
+        }
+    }
+
+    #[test]
+    fn test_union() {
+        let a = SequenceSet::try_from("1:3,10").unwrap();
+        let b = SequenceSet::try_from("4:5,20:*").unwrap();
+
+        assert_eq!(a.union(&b), SequenceSet::try_from("1:5,10,20:*").unwrap());
+    }
+
+    #[test]
+    fn test_intersect() {
+        let largest = NonZeroU32::new(100).unwrap();
+        let tests = [
+            ("1:10", "5:20", Some("5:10")),
+            ("1:10,20:30", "5:25", Some("5:10,20:25")),
+            ("1,3,5", "2,4", None),
+            ("90:*", "*", Some("100")),
+            ("1:*", "50,150", Some("50")),
+        ];
+
+        for (a, b, expected) in tests {
+            let a = SequenceSet::try_from(a).unwrap();
+            let b = SequenceSet::try_from(b).unwrap();
+            let expected = expected.map(|expected| SequenceSet::try_from(expected).unwrap());
+
+            assert_eq!(a.intersect(&b, largest), expected);
+            assert_eq!(b.intersect(&a, largest), expected);
+        }
+    }
+
+    #[test]
+    fn test_try_from_iter() {
+        let tests = [
+            (vec![1], "1"),
+            (vec![1, 2], "1:2"),
+            (vec![3, 2, 1, 2, 3], "1:3"),
+            (vec![1, 2, 3, 5, 7, 8, 9, 10], "1:3,5,7:10"),
+            (vec![u32::MAX, 1, u32::MAX - 1], "1,4294967294:4294967295"),
+        ];
+
+        for (test, expected) in tests {
+            let values = test
+                .into_iter()
+                .map(|value| NonZeroU32::new(value).unwrap());
+
+            assert_eq!(
+                SequenceSet::try_from_iter(values).unwrap(),
+                SequenceSet::try_from(expected).unwrap()
+            );
+        }
+
+        assert_eq!(
+            SequenceSet::try_from_iter([]),
+            Err(ValidationError::new(ValidationErrorKind::Empty))
+        );
+    }
+
+    #[test]
+    fn test_try_from_vec_non_zero_u32_keeps_order() {
+        let values = [3, 1, 2].map(|value| NonZeroU32::new(value).unwrap());
+
+        assert_eq!(
+            SequenceSet::try_from(values.to_vec()).unwrap(),
+            SequenceSet::try_from("3,1,2").unwrap()
+        );
+        assert_eq!(
+            SequenceSet::try_from(values.to_vec()).unwrap().normalize(),
+            SequenceSet::try_from_iter(values).unwrap()
+        );
+    }
+
     #[test]
     fn test_clean() {
         let tests = vec![
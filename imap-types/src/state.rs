@@ -51,13 +51,22 @@
 //! ```
 
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::{core::Tag, mailbox::Mailbox};
+use crate::{
+    command::{Command, CommandBody},
+    core::Tag,
+    mailbox::Mailbox,
+    response::{Greeting, GreetingKind, Response, Status, StatusKind, Tagged},
+    state::error::TransitionError,
+};
 
 /// State of the IMAP4rev1 connection.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Clone, Debug, Eq, PartialEq, ToStatic)]
 pub enum State<'a> {
     Greeting,
@@ -91,10 +100,168 @@ pub enum State<'a> {
     IdleSelected(Tag<'a>, Mailbox<'a>),
 }
 
+impl<'a> State<'a> {
+    /// Process the server greeting.
+    ///
+    /// * `OK` leads to [`State::NotAuthenticated`],
+    /// * `PREAUTH` leads to [`State::Authenticated`], and
+    /// * `BYE` leads to [`State::Logout`].
+    pub fn greeting(&self, greeting: &Greeting) -> Result<State<'a>, TransitionError> {
+        if *self != State::Greeting {
+            return Err(TransitionError::UnexpectedGreeting);
+        }
+
+        Ok(match greeting.kind {
+            GreetingKind::Ok => State::NotAuthenticated,
+            GreetingKind::PreAuth => State::Authenticated,
+            GreetingKind::Bye => State::Logout,
+        })
+    }
+
+    /// Process a `response` to `command` and return the next state.
+    ///
+    /// The `response` is usually the tagged status completing the `command`. Further, an
+    /// untagged `BYE` always leads to [`State::Logout`], a command continuation request to `IDLE`
+    /// starts idling, and other responses don't change the state.
+    ///
+    /// The `command` must be allowed in the current state (see [`CommandBody::allowed_in`]). While
+    /// idling, `command` must be the `IDLE` command. Its completion ends idling. After logout, only
+    /// the tagged completion of the `LOGOUT` command is accepted.
+    ///
+    /// ```rust
+    /// use imap_types::{
+    ///     command::{Command, CommandBody},
+    ///     core::Tag,
+    ///     mailbox::Mailbox,
+    ///     response::{Response, Status},
+    ///     state::State,
+    /// };
+    ///
+    /// let state = State::Authenticated;
+    ///
+    /// let command = Command::new("A1", CommandBody::select("INBOX").unwrap()).unwrap();
+    /// let response =
+    ///     Response::Status(Status::ok(Some(Tag::try_from("A1").unwrap()), None, "done").unwrap());
+    ///
+    /// let state = state.transition(&command, &response).unwrap();
+    /// assert_eq!(state, State::Selected(Mailbox::Inbox));
+    /// ```
+    pub fn transition(
+        &self,
+        command: &Command<'a>,
+        response: &Response,
+    ) -> Result<State<'a>, TransitionError> {
+        match self {
+            State::Greeting => return Err(TransitionError::NoGreeting),
+            State::Logout => {
+                // "[T]he server MUST send an untagged BYE response and a tagged OK response to the
+                // LOGOUT command", i.e., the tagged completion follows the BYE.
+                return match (&command.body, response) {
+                    (CommandBody::Logout, Response::Status(Status::Tagged(Tagged { tag, .. }))) => {
+                        if *tag == command.tag {
+                            Ok(State::Logout)
+                        } else {
+                            Err(TransitionError::UnrelatedResponse)
+                        }
+                    }
+                    _ => Err(TransitionError::Terminated),
+                };
+            }
+            State::IdleAuthenticated(tag) | State::IdleSelected(tag, _) => {
+                if command.tag != *tag {
+                    return Err(TransitionError::Idling);
+                }
+            }
+            _ => {
+                if !command.body.allowed_in(self) {
+                    return Err(TransitionError::InvalidState);
+                }
+            }
+        }
+
+        let kind = match response {
+            Response::Status(Status::Bye(_)) => return Ok(State::Logout),
+            Response::Status(Status::Tagged(Tagged { tag, body })) => {
+                if *tag != command.tag {
+                    return Err(TransitionError::UnrelatedResponse);
+                }
+
+                body.kind
+            }
+            Response::CommandContinuationRequest(_) => {
+                return match (self, &command.body) {
+                    (State::Authenticated, CommandBody::Idle) => {
+                        Ok(State::IdleAuthenticated(command.tag.clone()))
+                    }
+                    (State::Selected(mailbox), CommandBody::Idle) => {
+                        Ok(State::IdleSelected(command.tag.clone(), mailbox.clone()))
+                    }
+                    (_, CommandBody::Idle) => Err(TransitionError::InvalidState),
+                    // E.g., AUTHENTICATE or a literal.
+                    _ => Ok(self.clone()),
+                };
+            }
+            Response::Status(Status::Untagged(_)) | Response::Data(_) => return Ok(self.clone()),
+        };
+
+        let ok = kind == StatusKind::Ok;
+
+        match (self, &command.body) {
+            (_, CommandBody::Logout) => Ok(if ok { State::Logout } else { self.clone() }),
+            (_, CommandBody::Authenticate { .. } | CommandBody::Login { .. }) => Ok(if ok {
+                State::Authenticated
+            } else {
+                self.clone()
+            }),
+            (_, CommandBody::Select { mailbox, .. } | CommandBody::Examine { mailbox, .. }) => {
+                Ok(match kind {
+                    StatusKind::Ok => State::Selected(mailbox.clone()),
+                    // "If the client is permitted to select a mailbox and the selection fails, the
+                    // current mailbox is deselected."
+                    StatusKind::No => State::Authenticated,
+                    StatusKind::Bad => self.clone(),
+                })
+            }
+            (_, CommandBody::Close | CommandBody::Unselect) => Ok(if ok {
+                State::Authenticated
+            } else {
+                self.clone()
+            }),
+            (State::IdleAuthenticated(_), CommandBody::Idle) => Ok(State::Authenticated),
+            (State::IdleSelected(_, mailbox), CommandBody::Idle) => {
+                Ok(State::Selected(mailbox.clone()))
+            }
+            // Other commands don't change the state.
+            _ => Ok(self.clone()),
+        }
+    }
+}
+
+/// Error-related types.
+pub mod error {
+    use thiserror::Error;
+
+    #[derive(Clone, Debug, Eq, Error, Hash, Ord, PartialEq, PartialOrd)]
+    pub enum TransitionError {
+        #[error("The greeting was not received yet")]
+        NoGreeting,
+        #[error("The greeting was already received")]
+        UnexpectedGreeting,
+        #[error("The connection is terminated")]
+        Terminated,
+        #[error("Only the completion of IDLE is expected")]
+        Idling,
+        #[error("The command is not allowed in this state")]
+        InvalidState,
+        #[error("The response does not complete the command")]
+        UnrelatedResponse,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{core::Tag, mailbox::Mailbox, IntoStatic, ToStatic};
+    use crate::{response::CommandContinuationRequest, IntoStatic, ToStatic};
 
     #[test]
     fn test_conversion() {
@@ -118,4 +285,133 @@ mod tests {
             }
         }
     }
+
+    fn cmd(tag: &'static str, body: CommandBody<'static>) -> Command<'static> {
+        Command::new(tag, body).unwrap()
+    }
+
+    fn ok(tag: &'static str) -> Response<'static> {
+        Response::Status(Status::ok(Some(Tag::try_from(tag).unwrap()), None, "...").unwrap())
+    }
+
+    fn no(tag: &'static str) -> Response<'static> {
+        Response::Status(Status::no(Some(Tag::try_from(tag).unwrap()), None, "...").unwrap())
+    }
+
+    #[test]
+    fn test_greeting() {
+        let tests = [
+            (Greeting::ok(None, "...").unwrap(), State::NotAuthenticated),
+            (
+                Greeting::preauth(None, "...").unwrap(),
+                State::Authenticated,
+            ),
+            (Greeting::bye(None, "...").unwrap(), State::Logout),
+        ];
+
+        for (greeting, expected) in tests {
+            assert_eq!(State::Greeting.greeting(&greeting), Ok(expected));
+            assert_eq!(
+                State::Authenticated.greeting(&greeting),
+                Err(TransitionError::UnexpectedGreeting)
+            );
+        }
+    }
+
+    #[test]
+    fn test_transition() {
+        let login = cmd("A1", CommandBody::login("alice", "password").unwrap());
+        let select = cmd("A2", CommandBody::select("INBOX").unwrap());
+        let close = cmd("A3", CommandBody::Close);
+        let idle = cmd("A4", CommandBody::Idle);
+        let logout = cmd("A5", CommandBody::Logout);
+        let noop = cmd("A6", CommandBody::Noop);
+
+        let state = State::NotAuthenticated;
+        assert_eq!(
+            state.transition(&login, &no("A1")),
+            Ok(State::NotAuthenticated)
+        );
+        let state = state.transition(&login, &ok("A1")).unwrap();
+        assert_eq!(state, State::Authenticated);
+        assert_eq!(
+            state.transition(&login, &ok("A1")),
+            Err(TransitionError::InvalidState)
+        );
+
+        let state = state.transition(&select, &ok("A2")).unwrap();
+        assert_eq!(state, State::Selected(Mailbox::Inbox));
+        assert_eq!(state.transition(&noop, &ok("A6")), Ok(state.clone()));
+        assert_eq!(
+            state.transition(&select, &ok("XXX")),
+            Err(TransitionError::UnrelatedResponse)
+        );
+        assert_eq!(
+            state.transition(&select, &no("A2")),
+            Ok(State::Authenticated)
+        );
+
+        let continuation = Response::CommandContinuationRequest(
+            CommandContinuationRequest::basic(None, "idling").unwrap(),
+        );
+        let state = state.transition(&idle, &continuation).unwrap();
+        assert_eq!(
+            state,
+            State::IdleSelected(Tag::try_from("A4").unwrap(), Mailbox::Inbox)
+        );
+        assert_eq!(
+            state.transition(&noop, &ok("A6")),
+            Err(TransitionError::Idling)
+        );
+        let state = state.transition(&idle, &ok("A4")).unwrap();
+        assert_eq!(state, State::Selected(Mailbox::Inbox));
+
+        let state = state.transition(&close, &ok("A3")).unwrap();
+        assert_eq!(state, State::Authenticated);
+        assert_eq!(
+            state.transition(&close, &ok("A3")),
+            Err(TransitionError::InvalidState)
+        );
+
+        let state = state.transition(&logout, &ok("A5")).unwrap();
+        assert_eq!(state, State::Logout);
+        assert_eq!(
+            state.transition(&noop, &ok("A6")),
+            Err(TransitionError::Terminated)
+        );
+
+        let bye = Response::Status(Status::bye(None, "...").unwrap());
+        assert_eq!(
+            State::Authenticated.transition(&noop, &bye),
+            Ok(State::Logout)
+        );
+        assert_eq!(
+            State::Greeting.transition(&noop, &ok("A6")),
+            Err(TransitionError::NoGreeting)
+        );
+    }
+
+    #[test]
+    fn test_transition_logout() {
+        // C: A1 LOGOUT
+        // S: * BYE ...
+        // S: A1 OK ...
+        let logout = cmd("A1", CommandBody::Logout);
+        let bye = Response::Status(Status::bye(None, "...").unwrap());
+
+        let state = State::Selected(Mailbox::Inbox);
+        let state = state.transition(&logout, &bye).unwrap();
+        assert_eq!(state, State::Logout);
+        assert_eq!(
+            state.transition(&logout, &ok("XXX")),
+            Err(TransitionError::UnrelatedResponse)
+        );
+        let state = state.transition(&logout, &ok("A1")).unwrap();
+        assert_eq!(state, State::Logout);
+
+        assert_eq!(
+            state.transition(&cmd("A2", CommandBody::Noop), &ok("A2")),
+            Err(TransitionError::Terminated)
+        );
+    }
 }
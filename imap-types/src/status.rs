@@ -1,17 +1,30 @@
-use std::num::NonZeroU32;
+use alloc::string::String;
+use core::num::NonZeroU32;
 
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
 use bounded_static_derive::ToStatic;
+#[cfg(feature = "schemars")]
+use schemars::JsonSchema;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "ext_condstore_qresync")]
+use crate::extensions::condstore_qresync::ModSeq;
+use crate::{
+    core::impl_try_from,
+    error::{ValidationError, ValidationErrorKind},
+    extensions::tagged_ext::TaggedExtLabel,
+    utils::ascii_lowercase,
+};
+
 /// Status data item name used to request a status data item.
 #[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 #[doc(alias = "StatusAttribute")]
-pub enum StatusDataItemName {
+pub enum StatusDataItemName<'a> {
     /// The number of messages in the mailbox.
     Messages,
 
@@ -36,14 +49,65 @@ pub enum StatusDataItemName {
     #[cfg(feature = "ext_condstore_qresync")]
     #[cfg_attr(docsrs, doc(cfg(feature = "ext_condstore_qresync")))]
     HighestModSeq,
+
+    /// Status data item of an unknown extension.
+    Other(StatusDataItemNameOther<'a>),
+}
+
+impl_try_from!(TaggedExtLabel<'a>, 'a, &'a [u8], StatusDataItemName<'a>);
+impl_try_from!(TaggedExtLabel<'a>, 'a, &'a str, StatusDataItemName<'a>);
+impl_try_from!(TaggedExtLabel<'a>, 'a, String, StatusDataItemName<'a>);
+
+impl<'a> From<TaggedExtLabel<'a>> for StatusDataItemName<'a> {
+    fn from(label: TaggedExtLabel<'a>) -> Self {
+        match ascii_lowercase(label.as_ref(), &mut [0; 15]).unwrap_or_default() {
+            "messages" => Self::Messages,
+            "recent" => Self::Recent,
+            "uidnext" => Self::UidNext,
+            "uidvalidity" => Self::UidValidity,
+            "unseen" => Self::Unseen,
+            "deleted" => Self::Deleted,
+            "deleted-storage" => Self::DeletedStorage,
+            #[cfg(feature = "ext_condstore_qresync")]
+            "highestmodseq" => Self::HighestModSeq,
+            _ => Self::Other(StatusDataItemNameOther(label)),
+        }
+    }
+}
+
+/// A status data item name of an unknown extension.
+///
+/// It's guaranteed that this type can't represent any status data item name from
+/// [`StatusDataItemName`].
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
+#[cfg_attr(feature = "serde", serde(try_from = "TaggedExtLabel<'a>"))]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
+pub struct StatusDataItemNameOther<'a>(TaggedExtLabel<'a>);
+
+impl<'a> StatusDataItemNameOther<'a> {
+    pub fn inner(&self) -> &TaggedExtLabel<'a> {
+        &self.0
+    }
+}
+
+impl<'a> TryFrom<TaggedExtLabel<'a>> for StatusDataItemNameOther<'a> {
+    type Error = ValidationError;
+
+    fn try_from(label: TaggedExtLabel<'a>) -> Result<Self, Self::Error> {
+        match StatusDataItemName::from(label) {
+            StatusDataItemName::Other(other) => Ok(other),
+            _ => Err(ValidationError::new(ValidationErrorKind::Invalid)),
+        }
+    }
 }
 
 /// Status data item.
-#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "schemars", derive(JsonSchema))]
 #[derive(Debug, Clone, PartialEq, Eq, Hash, ToStatic)]
 #[doc(alias = "StatusAttributeValue")]
-pub enum StatusDataItem {
+pub enum StatusDataItem<'a> {
     /// The number of messages in the mailbox.
     Messages(u32),
 
@@ -75,5 +139,13 @@ pub enum StatusDataItem {
     ///
     /// If the server doesn't support the persistent storage of mod-sequences for the mailbox (see
     /// Section 3.1.2.2), the server MUST return 0 as the value of the HIGHESTMODSEQ status data item.
-    HighestModSeq(u64),
+    /// This is represented as `None`.
+    HighestModSeq(Option<ModSeq>),
+
+    /// Status data item of an unknown extension.
+    Other {
+        name: StatusDataItemNameOther<'a>,
+        /// `None` when the value was `NIL`.
+        value: Option<u64>,
+    },
 }
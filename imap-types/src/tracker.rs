@@ -0,0 +1,287 @@
+//! Correlation of commands and responses.
+//!
+//! A client usually has multiple commands in flight and must assign every response to the command
+//! that caused it. The [`CommandTracker`] does this bookkeeping without doing any I/O: register a
+//! command when it is sent and feed every decoded response into the tracker. Untagged data is
+//! collected until the command is completed by its tagged status response.
+//!
+//! ```rust
+//! use imap_types::{
+//!     command::{Command, CommandBody},
+//!     core::Tag,
+//!     response::{Data, Response, Status},
+//!     tracker::{CommandTracker, Event},
+//! };
+//!
+//! let mut tracker = CommandTracker::new();
+//! tracker
+//!     .register(&Command::new("A1", CommandBody::select("INBOX").unwrap()).unwrap())
+//!     .unwrap();
+//!
+//! // Collected as part of SELECT.
+//! let event = tracker.consume(Response::Data(Data::Exists(42))).unwrap();
+//! assert_eq!(event, None);
+//!
+//! let tag = Tag::try_from("A1").unwrap();
+//! let event = tracker
+//!     .consume(Response::Status(
+//!         Status::ok(Some(tag), None, "done").unwrap(),
+//!     ))
+//!     .unwrap();
+//!
+//! match event {
+//!     Some(Event::Completed(completion)) => {
+//!         assert_eq!(completion.name, "SELECT");
+//!         assert_eq!(completion.data, vec![Data::Exists(42)]);
+//!     }
+//!     _ => unreachable!(),
+//! }
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::{
+    command::Command,
+    core::Tag,
+    response::{CommandContinuationRequest, Data, Response, Status, StatusBody, Tagged},
+    tracker::error::TrackerError,
+};
+
+/// Tracks in-flight commands and assigns responses to them.
+///
+/// See the [module documentation](self).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CommandTracker<'a> {
+    in_flight: Vec<InFlight<'a>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct InFlight<'a> {
+    tag: Tag<'a>,
+    name: &'static str,
+    data: Vec<Data<'a>>,
+    untagged: Vec<StatusBody<'a>>,
+}
+
+/// Result of consuming a response.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event<'a> {
+    /// A command was completed.
+    Completed(Completion<'a>),
+    /// Untagged data that doesn't belong to any in-flight command, e.g., an `EXISTS` during `NOOP`.
+    Data(Data<'a>),
+    /// An untagged status that doesn't belong to any in-flight command, e.g., `BYE`.
+    Status(Status<'a>),
+    /// A command continuation request.
+    ///
+    /// Note: The tracker can't know which command the server is waiting for.
+    Continue(CommandContinuationRequest<'a>),
+}
+
+/// A completed command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Completion<'a> {
+    /// Tag of the command.
+    pub tag: Tag<'a>,
+    /// Name of the command (see [`CommandBody::name`](crate::command::CommandBody::name)).
+    pub name: &'static str,
+    /// Tagged status response completing the command.
+    pub status: StatusBody<'a>,
+    /// Untagged data elicited by the command.
+    pub data: Vec<Data<'a>>,
+    /// Untagged status responses elicited by the command, e.g., `* OK [UIDVALIDITY ...]`.
+    pub untagged: Vec<StatusBody<'a>>,
+}
+
+impl<'a> CommandTracker<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a sent command.
+    ///
+    /// Fails when a command with the same tag is still in flight.
+    pub fn register(&mut self, command: &Command<'a>) -> Result<(), TrackerError> {
+        if self.in_flight.iter().any(|entry| entry.tag == command.tag) {
+            return Err(TrackerError::DuplicateTag);
+        }
+
+        self.in_flight.push(InFlight {
+            tag: command.tag.clone(),
+            name: command.body.name(),
+            data: Vec::new(),
+            untagged: Vec::new(),
+        });
+
+        Ok(())
+    }
+
+    /// Consume a decoded response.
+    ///
+    /// Untagged responses are assigned to the oldest in-flight command that can elicit them (see
+    /// [`Data::commands`] and [`Code::commands`](crate::response::Code::commands)) and returned
+    /// as [`Event::Data`] or [`Event::Status`] otherwise. A tagged status response completes its
+    /// command and yields [`Event::Completed`].
+    pub fn consume(&mut self, response: Response<'a>) -> Result<Option<Event<'a>>, TrackerError> {
+        match response {
+            Response::Status(Status::Tagged(Tagged { tag, body })) => {
+                let index = self
+                    .in_flight
+                    .iter()
+                    .position(|entry| entry.tag == tag)
+                    .ok_or(TrackerError::UnknownTag)?;
+
+                let InFlight {
+                    tag,
+                    name,
+                    data,
+                    untagged,
+                } = self.in_flight.remove(index);
+
+                Ok(Some(Event::Completed(Completion {
+                    tag,
+                    name,
+                    status: body,
+                    data,
+                    untagged,
+                })))
+            }
+            Response::Status(Status::Untagged(body)) => {
+                let entry = body.code.as_ref().and_then(|code| {
+                    self.in_flight
+                        .iter_mut()
+                        .find(|entry| code.commands().contains(&entry.name))
+                });
+
+                match entry {
+                    Some(entry) => {
+                        entry.untagged.push(body);
+                        Ok(None)
+                    }
+                    None => Ok(Some(Event::Status(Status::Untagged(body)))),
+                }
+            }
+            Response::Status(status @ Status::Bye(_)) => Ok(Some(Event::Status(status))),
+            Response::Data(data) => {
+                let entry = match &data {
+                    Data::ESearch { tag: Some(tag), .. } => {
+                        self.in_flight.iter_mut().find(|entry| entry.tag == *tag)
+                    }
+                    _ => self
+                        .in_flight
+                        .iter_mut()
+                        .find(|entry| data.commands().contains(&entry.name)),
+                };
+
+                match entry {
+                    Some(entry) => {
+                        entry.data.push(data);
+                        Ok(None)
+                    }
+                    None => Ok(Some(Event::Data(data))),
+                }
+            }
+            Response::CommandContinuationRequest(continuation) => {
+                Ok(Some(Event::Continue(continuation)))
+            }
+        }
+    }
+
+    /// Iterate over the tags and names of the in-flight commands (oldest first).
+    pub fn in_flight(&self) -> impl Iterator<Item = (&Tag<'a>, &'static str)> {
+        self.in_flight.iter().map(|entry| (&entry.tag, entry.name))
+    }
+
+    pub fn len(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.in_flight.is_empty()
+    }
+}
+
+/// Error-related types.
+pub mod error {
+    use thiserror::Error;
+
+    #[derive(Clone, Debug, Eq, Error, Hash, Ord, PartialEq, PartialOrd)]
+    pub enum TrackerError {
+        #[error("A command with this tag is already in flight")]
+        DuplicateTag,
+        #[error("No command with this tag is in flight")]
+        UnknownTag,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        command::CommandBody,
+        response::{Code, StatusKind},
+    };
+
+    fn tag(tag: &'static str) -> Tag<'static> {
+        Tag::try_from(tag).unwrap()
+    }
+
+    #[test]
+    fn test_tracker() {
+        let mut tracker = CommandTracker::new();
+
+        let select = Command::new("A1", CommandBody::select("INBOX").unwrap()).unwrap();
+        let noop = Command::new("A2", CommandBody::Noop).unwrap();
+        tracker.register(&select).unwrap();
+        tracker.register(&noop).unwrap();
+        assert_eq!(tracker.register(&noop), Err(TrackerError::DuplicateTag));
+        assert_eq!(tracker.len(), 2);
+
+        let uidvalidity = Status::ok(None, Some(Code::uidvalidity(1).unwrap()), "...").unwrap();
+        let alert = Status::ok(None, Some(Code::Alert), "...").unwrap();
+        let fetch = Data::fetch(1, vec![crate::fetch::MessageDataItem::Rfc822Size(42)]).unwrap();
+
+        assert_eq!(tracker.consume(Response::Data(Data::Exists(1))), Ok(None));
+        assert_eq!(
+            tracker.consume(Response::Status(uidvalidity.clone())),
+            Ok(None)
+        );
+        assert_eq!(
+            tracker.consume(Response::Status(alert.clone())),
+            Ok(Some(Event::Status(alert)))
+        );
+        assert_eq!(
+            tracker.consume(Response::Data(fetch.clone())),
+            Ok(Some(Event::Data(fetch)))
+        );
+
+        let Ok(Some(Event::Completed(completion))) = tracker.consume(Response::Status(
+            Status::ok(Some(tag("A1")), None, "...").unwrap(),
+        )) else {
+            panic!("expected completion");
+        };
+        assert_eq!(completion.tag, tag("A1"));
+        assert_eq!(completion.name, "SELECT");
+        assert_eq!(completion.status.kind, StatusKind::Ok);
+        assert_eq!(completion.data, vec![Data::Exists(1)]);
+        assert_eq!(completion.untagged.len(), 1);
+
+        assert_eq!(
+            tracker.in_flight().collect::<Vec<_>>(),
+            vec![(&tag("A2"), "NOOP")]
+        );
+        assert_eq!(
+            tracker.consume(Response::Status(
+                Status::ok(Some(tag("A9")), None, "...").unwrap()
+            )),
+            Err(TrackerError::UnknownTag)
+        );
+        assert!(matches!(
+            tracker.consume(Response::Status(
+                Status::no(Some(tag("A2")), None, "...").unwrap()
+            )),
+            Ok(Some(Event::Completed(_)))
+        ));
+        assert!(tracker.is_empty());
+    }
+}
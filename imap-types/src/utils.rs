@@ -1,6 +1,6 @@
 //! Functions that may come in handy.
 
-use std::borrow::Cow;
+use alloc::{borrow::Cow, format, string::String, vec::Vec};
 
 /// Converts bytes into a ready-to-be-printed form.
 pub fn escape_byte_string<B>(bytes: B) -> String
@@ -107,34 +107,87 @@ pub mod indicators {
     pub fn is_list_char(i: u8) -> bool {
         is_atom_char(i) || is_list_wildcards(i) || is_resp_specials(i)
     }
+
+    /// `tagged-label-fchar = ALPHA / "-" / "_" / "."`
+    pub fn is_tagged_label_fchar(i: u8) -> bool {
+        i.is_ascii_alphabetic() || matches!(i, b'-' | b'_' | b'.')
+    }
+
+    /// `tagged-label-char = tagged-label-fchar / DIGIT / ":"`
+    pub fn is_tagged_label_char(i: u8) -> bool {
+        is_tagged_label_fchar(i) || i.is_ascii_digit() || i == b':'
+    }
 }
 
+/// Escapes `\` and `"` in a quoted string.
+///
+/// Only allocates when something needs to be escaped.
+///
+/// ```rust
+/// use imap_types::utils::escape_quoted;
+///
+/// assert_eq!(escape_quoted("abc"), "abc");
+/// assert_eq!(escape_quoted(r#"a"b\c"#), r#"a\"b\\c"#);
+/// ```
 pub fn escape_quoted(unescaped: &str) -> Cow<str> {
-    let mut escaped = Cow::Borrowed(unescaped);
-
-    if escaped.contains('\\') {
-        escaped = Cow::Owned(escaped.replace('\\', "\\\\"));
+    if !unescaped.contains(['\\', '"']) {
+        return Cow::Borrowed(unescaped);
     }
 
-    if escaped.contains('\"') {
-        escaped = Cow::Owned(escaped.replace('"', "\\\""));
+    let mut escaped = String::with_capacity(unescaped.len() + 2);
+
+    for c in unescaped.chars() {
+        if matches!(c, '\\' | '"') {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
     }
 
-    escaped
+    Cow::Owned(escaped)
 }
 
+/// Unescapes `\\` and `\"` in a quoted string.
+///
+/// Only allocates when something needs to be unescaped. Other backslashes are kept as is.
+///
+/// ```rust
+/// use imap_types::utils::unescape_quoted;
+///
+/// assert_eq!(unescape_quoted("abc"), "abc");
+/// assert_eq!(unescape_quoted(r#"a\"b\\c"#), r#"a"b\c"#);
+/// ```
 pub fn unescape_quoted(escaped: &str) -> Cow<str> {
-    let mut unescaped = Cow::Borrowed(escaped);
-
-    if unescaped.contains("\\\\") {
-        unescaped = Cow::Owned(unescaped.replace("\\\\", "\\"));
+    if !escaped.contains('\\') {
+        return Cow::Borrowed(escaped);
     }
 
-    if unescaped.contains("\\\"") {
-        unescaped = Cow::Owned(unescaped.replace("\\\"", "\""));
+    let mut unescaped = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some(next @ ('\\' | '"'))) => {
+                chars.next();
+                unescaped.push(next);
+            }
+            _ => unescaped.push(c),
+        }
     }
 
-    unescaped
+    Cow::Owned(unescaped)
+}
+
+/// Lowercases `value` into `buffer` to match it against keywords without allocating.
+///
+/// Returns `None` when `value` is longer than `buffer`, i.e., when it can't be a known keyword.
+pub(crate) fn ascii_lowercase<'b>(value: &str, buffer: &'b mut [u8]) -> Option<&'b str> {
+    let buffer = buffer.get_mut(..value.len())?;
+    buffer.copy_from_slice(value.as_bytes());
+    buffer.make_ascii_lowercase();
+
+    // Unwrap: Lowercasing ASCII characters keeps (UTF-8) strings valid.
+    Some(core::str::from_utf8(buffer).unwrap())
 }
 
 #[cfg(test)]
@@ -169,6 +222,8 @@ mod tests {
             ("\\\\alice\\\\", "\\alice\\"),
             ("alice\\\"", "alice\""),
             (r#"\\alice\\ \""#, r#"\alice\ ""#),
+            (r#"\\\""#, r#"\""#),
+            (r#"\\""#, r#"\""#),
         ];
 
         for (test, expected) in tests {
@@ -184,6 +239,14 @@ mod tests {
         assert_eq!(input, unescape_quoted(escape_quoted(input).as_ref()));
     }
 
+    #[test]
+    fn test_ascii_lowercase() {
+        assert_eq!(ascii_lowercase("", &mut [0; 8]), Some(""));
+        assert_eq!(ascii_lowercase("SeEn", &mut [0; 8]), Some("seen"));
+        assert_eq!(ascii_lowercase("Grüße", &mut [0; 8]), Some("grüße"));
+        assert_eq!(ascii_lowercase("NOINFERIORS", &mut [0; 8]), None);
+    }
+
     #[test]
     fn test_escape_byte_string() {
         for byte in 0u8..=255 {
@@ -0,0 +1,52 @@
+//! The redaction policy is process-wide. Thus, it is tested in its own test binary to not affect
+//! (or be affected by) tests running in parallel.
+
+use imap_types::{
+    command::CommandBody,
+    core::{Literal, LiteralMode},
+    mailbox::Mailbox,
+    secret::{redaction_policy, set_redaction_policy, RedactionPolicy},
+};
+
+#[test]
+fn test_redaction_policy() {
+    let login = CommandBody::login("alice", "xyz123").unwrap();
+    let select = CommandBody::select("Secret Plans").unwrap();
+    let inbox = Mailbox::Inbox;
+    let literal = Literal::try_from("hello").unwrap();
+
+    assert_eq!(redaction_policy(), RedactionPolicy::NONE);
+    assert!(format!("{login:?}").contains("alice"));
+    assert!(format!("{select:?}").contains("Secret Plans"));
+    assert!(format!("{literal:?}").contains("hello"));
+
+    set_redaction_policy(RedactionPolicy::ALL);
+    assert_eq!(redaction_policy(), RedactionPolicy::ALL);
+
+    let got = format!("{login:?}");
+    assert!(!got.contains("alice"));
+    assert!(got.contains("username: /* REDACTED (5 bytes) */"));
+
+    let got = format!("{select:?}");
+    assert!(!got.contains("Secret Plans"));
+    assert!(got.contains("MailboxOther(/* REDACTED (12 bytes) */)"));
+
+    assert_eq!(format!("{inbox:?}"), "Inbox");
+
+    assert_eq!(
+        format!("{literal:?}"),
+        format!(
+            "Literal {{ data: /* REDACTED (5 bytes) */, mode: {:?} }}",
+            LiteralMode::Sync
+        )
+    );
+
+    set_redaction_policy(RedactionPolicy {
+        mailboxes: true,
+        ..RedactionPolicy::NONE
+    });
+    assert!(format!("{login:?}").contains("alice"));
+    assert!(!format!("{select:?}").contains("Secret Plans"));
+
+    set_redaction_policy(RedactionPolicy::NONE);
+}
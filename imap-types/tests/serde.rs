@@ -0,0 +1,140 @@
+//! Messages must round-trip through non-self-describing formats, such as postcard and bincode.
+
+#![cfg(feature = "serde")]
+
+use std::{fmt::Debug, num::NonZeroU32};
+
+use chrono::{FixedOffset, TimeZone};
+use imap_types::{
+    auth::{AuthMechanism, AuthenticateData},
+    command::{Command, CommandBody},
+    core::{Literal, NString, Vec1},
+    datetime::DateTime,
+    fetch::{MessageDataItem, MessageDataItemName},
+    flag::{Flag, FlagFetch},
+    response::{Code, Data, Greeting, GreetingKind, Response, Status},
+    search::{SearchKey, SearchResults},
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+fn round_trip<T>(value: T)
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    let encoded = postcard::to_allocvec(&value).unwrap();
+    assert_eq!(postcard::from_bytes::<T>(&encoded).unwrap(), value);
+
+    let encoded = bincode::serialize(&value).unwrap();
+    assert_eq!(bincode::deserialize::<T>(&encoded).unwrap(), value);
+
+    let encoded = serde_json::to_string(&value).unwrap();
+    assert_eq!(serde_json::from_str::<T>(&encoded).unwrap(), value);
+}
+
+fn date_time() -> DateTime {
+    DateTime::try_from(
+        FixedOffset::east_opt(3600)
+            .unwrap()
+            .with_ymd_and_hms(2015, 5, 15, 11, 22, 33)
+            .unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_round_trip_command() {
+    let commands = [
+        CommandBody::login("alice", "password").unwrap(),
+        CommandBody::authenticate(AuthMechanism::Plain),
+        CommandBody::select("INBOX").unwrap(),
+        CommandBody::append(
+            "INBOX",
+            vec![Flag::Seen],
+            Some(date_time()),
+            Literal::try_from(b"Subject: \xff\r\n\r\n".as_ref()).unwrap(),
+        )
+        .unwrap(),
+        CommandBody::search(
+            None,
+            Vec1::from(SearchKey::Not(Box::new(SearchKey::Subject(
+                "hello".try_into().unwrap(),
+            )))),
+            true,
+        ),
+        CommandBody::fetch(
+            "1:*",
+            vec![MessageDataItemName::Flags, MessageDataItemName::Rfc822Size],
+            false,
+        )
+        .unwrap(),
+        CommandBody::Idle,
+    ];
+
+    for (index, body) in commands.into_iter().enumerate() {
+        round_trip(Command::new(format!("A{index}"), body).unwrap());
+    }
+
+    round_trip(AuthenticateData::r#continue(
+        b"\x00alice\x00password".to_vec(),
+    ));
+    round_trip(AuthenticateData::Cancel);
+}
+
+#[test]
+fn test_round_trip_response() {
+    let responses = [
+        Response::Status(Status::ok(None, Some(Code::uidvalidity(42).unwrap()), "...").unwrap()),
+        Response::Status(Status::no(Some("A1".try_into().unwrap()), None, "...").unwrap()),
+        Response::Status(Status::bye(Some(Code::Alert), "...").unwrap()),
+        Response::Data(Data::Exists(42)),
+        Response::Data(
+            Data::fetch(
+                1,
+                vec![
+                    MessageDataItem::Flags(vec![FlagFetch::Flag(Flag::Seen), FlagFetch::Recent]),
+                    MessageDataItem::InternalDate(date_time()),
+                    MessageDataItem::BodyExt {
+                        section: None,
+                        origin: Some(0),
+                        data: NString::from(Literal::try_from(b"\xff\r\n".as_ref()).unwrap()),
+                    },
+                ],
+            )
+            .unwrap(),
+        ),
+    ];
+
+    for response in responses {
+        round_trip(response);
+    }
+
+    round_trip(Greeting::new(GreetingKind::Ok, None, "Hello").unwrap());
+}
+
+#[test]
+fn test_round_trip_values() {
+    let n = |n| NonZeroU32::new(n).unwrap();
+
+    round_trip(date_time());
+    round_trip(SearchResults::from(vec![n(1), n(2), n(3), n(42)]));
+    round_trip(SearchResults::new());
+    round_trip(Vec1::from(n(1)));
+    round_trip(Literal::try_from(b"\x01\x02\xff".as_ref()).unwrap());
+}
+
+#[test]
+fn test_literal_is_serialized_as_bytes() {
+    let literal = Literal::try_from(b"abc".as_ref()).unwrap();
+
+    // Length prefix, data, and mode, i.e., no per-byte overhead.
+    assert_eq!(
+        postcard::to_allocvec(&literal).unwrap(),
+        [3, b'a', b'b', b'c', 0]
+    );
+
+    // Self-describing formats still use a sequence.
+    assert_eq!(
+        serde_json::to_string(&literal).unwrap(),
+        r#"{"data":[97,98,99],"mode":"Sync"}"#
+    );
+}